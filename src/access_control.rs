@@ -0,0 +1,236 @@
+//!
+//! The AccessControl module contains the [TenantTable] and [TenantTableMut] objects, which check every operation
+//! against the access hook of a [Table] before performing it.  The AccessHook, AccessOperation, AccessTarget,
+//! TenantTable, and TenantTableMut types are re-exported
+//!
+
+use std::collections::HashSet;
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::{*};
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+
+/// The kinds of operation checked by an [AccessHook]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccessOperation {
+    /// A lookup, checked against the key supplied to the lookup
+    Lookup,
+    /// Reading a record, either directly or because it was found by a lookup
+    Read,
+    /// Creating a record with a key, checked once for each of the new record's keys
+    Insert,
+    /// Changing the keys or the value of an existing record
+    Update,
+    /// Deleting a record
+    Delete,
+}
+
+/// The key or the record that an [AccessHook] is asked about
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccessTarget<'a, KeyCharT> {
+    Key(&'a [KeyCharT]),
+    Record(RecordID),
+}
+
+/// A function that decides whether a tenant may perform an operation, returning `true` to allow it.  See
+/// [set_access_hook](crate::Table::set_access_hook)
+pub type AccessHook<KeyCharT> = Box<dyn Fn(AccessOperation, &str, AccessTarget<'_, KeyCharT>) -> bool + Send + Sync>;
+
+/// Returns an error if the hook denies the operation, or `Ok` if the Table has no hook
+pub(crate) fn check_access<KeyCharT>(hook : Option<&AccessHook<KeyCharT>>, operation : AccessOperation, tenant : &str, target : AccessTarget<'_, KeyCharT>) -> Result<(), String> {
+    match hook {
+        Some(hook) if !hook(operation, tenant, target) => Err(format!("access denied: tenant \"{}\" may not perform {:?}", tenant, operation)),
+        _ => Ok(())
+    }
+}
+
+/// A view of a [Table] on behalf of a tenant, returned by [Table::as_tenant], which checks every lookup and read
+/// with the Table's [AccessHook]
+///
+/// A lookup is rejected with an error if the hook denies the [Lookup](AccessOperation::Lookup) of its key.  The
+/// records found are each checked with a [Read](AccessOperation::Read), and the ones the tenant may not read are
+/// left out of the results, so one tenant's records are never visible to another.
+///
+/// Like [ShardedTable](crate::ShardedTable), the results aren't lazy, because they must be filtered.
+pub struct TenantTable<'a, ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    table : &'a Table<ConfigT, UTF8_KEYS>,
+    tenant : &'a str,
+}
+
+/// A view of a [Table] on behalf of a tenant, returned by [Table::as_tenant_mut], which checks every write with the
+/// Table's [AccessHook]
+///
+/// A write is rejected with an error, and nothing is written, if the hook denies any part of it.  Each key of a new
+/// record is checked with an [Insert](AccessOperation::Insert), and changes to an existing record are checked with
+/// an [Update](AccessOperation::Update) or [Delete](AccessOperation::Delete) of the record, as well as an `Insert`
+/// for any keys being added.
+pub struct TenantTableMut<'a, ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    table : &'a mut Table<ConfigT, UTF8_KEYS>,
+    tenant : &'a str,
+}
+
+impl <'a, ConfigT : TableConfig, const UTF8_KEYS : bool>TenantTable<'a, ConfigT, UTF8_KEYS> {
+
+    pub(crate) fn new(table : &'a Table<ConfigT, UTF8_KEYS>, tenant : &'a str) -> Self {
+        Self{table, tenant}
+    }
+
+    /// Returns the tenant the view acts on behalf of
+    pub fn tenant(&self) -> &str {
+        self.tenant
+    }
+}
+
+impl <'a, OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>TenantTable<'a, ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Locates the records with keys that precisely match the key supplied, that the tenant may read.  See
+    /// [Table::lookup_exact] for the caveats
+    pub fn lookup_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<RecordID>, String> {
+        let key = key.into_key();
+        self.check_lookup(&key)?;
+        let results = self.table.lookup_exact_internal(&key)?;
+        Ok(results.into_iter().filter(|record_id| self.may_read(*record_id)).collect())
+    }
+
+    /// Performs a fuzzy lookup, and returns the results that the tenant may read.  See [Table::lookup_fuzzy] for
+    /// the meaning of `threshold`
+    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        let key = key.into_key();
+        self.check_lookup(&key)?;
+        let results = self.table.lookup_fuzzy_internal(&key, threshold, None)?.into_vec()?;
+        Ok(results.into_iter().filter(|(record_id, _)| self.may_read(*record_id)).collect())
+    }
+
+    /// Returns the records that the tenant may read whose keys are the shortest distance from the key supplied
+    ///
+    /// The records the tenant may not read are left out before the best distance is chosen, so another tenant's
+    /// closer match won't hide the tenant's own records.
+    pub fn lookup_best<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<RecordID>, String> {
+        let mut results = self.lookup_fuzzy(key, None)?;
+        results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(core::cmp::Ordering::Equal));
+        let best_distance = match results.first() {
+            Some((_, distance)) => *distance,
+            None => return Ok(vec![])
+        };
+        Ok(results.into_iter().take_while(|(_, distance)| *distance == best_distance).map(|(id, _)| id).collect())
+    }
+
+    /// Returns the value associated with the specified record, if the tenant may read it
+    pub fn get_value(&self, record_id : RecordID) -> Result<ConfigT::ValueT, String> {
+        self.check(AccessOperation::Read, AccessTarget::Record(record_id))?;
+        self.table.get_value(record_id)
+    }
+
+    /// Returns all of the keys associated with the specified record, if the tenant may read it
+    pub fn get_keys(&self, record_id : RecordID) -> Result<Vec<OwnedKeyT>, String> {
+        self.check(AccessOperation::Read, AccessTarget::Record(record_id))?;
+        Ok(self.table.get_keys_internal(record_id)?.collect())
+    }
+
+    fn check_lookup<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> Result<(), String> {
+        self.check(AccessOperation::Lookup, AccessTarget::Key(&key.get_key_chars()))
+    }
+
+    fn may_read(&self, record_id : RecordID) -> bool {
+        self.check(AccessOperation::Read, AccessTarget::Record(record_id)).is_ok()
+    }
+
+    fn check(&self, operation : AccessOperation, target : AccessTarget<'_, ConfigT::KeyCharT>) -> Result<(), String> {
+        check_access(self.table.access_hook(), operation, self.tenant, target)
+    }
+}
+
+impl <'a, ConfigT : TableConfig, const UTF8_KEYS : bool>TenantTableMut<'a, ConfigT, UTF8_KEYS> {
+
+    pub(crate) fn new(table : &'a mut Table<ConfigT, UTF8_KEYS>, tenant : &'a str) -> Self {
+        Self{table, tenant}
+    }
+
+    /// Returns the tenant the view acts on behalf of
+    pub fn tenant(&self) -> &str {
+        self.tenant
+    }
+}
+
+impl <'a, OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>TenantTableMut<'a, ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Returns a [TenantTable] for the same tenant, to perform lookups and reads
+    pub fn as_reader(&self) -> TenantTable<'_, ConfigT, UTF8_KEYS> {
+        TenantTable::new(self.table, self.tenant)
+    }
+
+    /// Inserts a new key-value pair, if the tenant may insert the key.  See [Table::insert]
+    pub fn insert<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, key : K, value : &ConfigT::ValueT) -> Result<RecordID, String> {
+        let key = key.into_key();
+        self.check_keys(AccessOperation::Insert, [&key].iter().copied())?;
+        self.table.insert_internal([&key].iter().copied(), 1, value)
+    }
+
+    /// Creates a new record, if the tenant may insert every one of its keys.  See [Table::create]
+    pub fn create<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, keys : &[K], value : &ConfigT::ValueT) -> Result<RecordID, String> {
+        self.check_keys(AccessOperation::Insert, keys.iter())?;
+        self.table.insert_internal(keys.iter(), keys.len(), value)
+    }
+
+    /// Adds the supplied keys to a record, if the tenant may update the record and insert the keys
+    pub fn add_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
+        self.check(AccessOperation::Update, AccessTarget::Record(record_id))?;
+        self.check_keys(AccessOperation::Insert, keys.iter())?;
+        self.table.add_keys_atomic(record_id, keys.iter(), keys.len())
+    }
+
+    /// Removes the supplied keys from a record, if the tenant may update the record.  See [Table::remove_keys]
+    pub fn remove_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
+        self.check(AccessOperation::Update, AccessTarget::Record(record_id))?;
+        let keys_set : HashSet<&K> = keys.iter().collect();
+        self.table.remove_keys_atomic(record_id, &keys_set)
+    }
+
+    /// Replaces a record's keys, if the tenant may update the record and insert the new keys
+    pub fn replace_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
+        self.check(AccessOperation::Update, AccessTarget::Record(record_id))?;
+        self.check_keys(AccessOperation::Insert, keys.iter())?;
+        self.table.replace_keys_atomic(record_id, keys)
+    }
+
+    /// Replaces a record's value, if the tenant may update the record.  Returns the value that was replaced
+    pub fn replace_value(&mut self, record_id : RecordID, value : &ConfigT::ValueT) -> Result<ConfigT::ValueT, String> {
+        self.check(AccessOperation::Update, AccessTarget::Record(record_id))?;
+        self.table.replace_value(record_id, value)
+    }
+
+    /// Deletes a record, if the tenant may delete it
+    pub fn delete(&mut self, record_id : RecordID) -> Result<(), String> {
+        self.check(AccessOperation::Delete, AccessTarget::Record(record_id))?;
+        self.table.delete(record_id)
+    }
+
+    fn check_keys<'k, K : Key<KeyCharT = ConfigT::KeyCharT> + 'k, KeysIterT : Iterator<Item=&'k K>>(&self, operation : AccessOperation, keys_iter : KeysIterT) -> Result<(), String> {
+        for key in keys_iter {
+            self.check(operation, AccessTarget::Key(&key.get_key_chars()))?;
+        }
+        Ok(())
+    }
+
+    fn check(&self, operation : AccessOperation, target : AccessTarget<'_, ConfigT::KeyCharT>) -> Result<(), String> {
+        check_access(self.table.access_hook(), operation, self.tenant, target)
+    }
+}
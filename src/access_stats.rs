@@ -0,0 +1,95 @@
+//!
+//! The AccessStats module contains the in-memory side of the per-record hit counters, which are
+//! periodically flushed to the "stats" column family
+//!
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::records::RecordID;
+use super::database::DBConnection;
+
+/// The number of hits that will be accumulated in memory before they are written to the database
+const FLUSH_THRESHOLD : usize = 1024;
+
+/// Per-record hit counts that have not yet been written to the database
+///
+/// Hits are counted in memory and merged into the "stats" CF in batches, so counting a hit doesn't
+/// require a database write.  The counts are approximate because any hits that haven't been flushed
+/// are lost if the process exits abnormally.
+pub struct AccessStats {
+    enabled : bool,
+    pending : Mutex<PendingHits>,
+}
+
+struct PendingHits {
+    counts : HashMap<RecordID, u64>,
+    total : usize,
+}
+
+impl AccessStats {
+    pub fn new(enabled : bool) -> Self {
+        Self {
+            enabled,
+            pending : Mutex::new(PendingHits{counts : HashMap::new(), total : 0}),
+        }
+    }
+
+    /// Counts a hit on a record, flushing the pending counts to the database if enough have accumulated
+    pub fn record_hit(&self, db : &DBConnection, record_id : RecordID) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let mut pending = self.pending.lock().unwrap();
+        *pending.counts.entry(record_id).or_insert(0) += 1;
+        pending.total += 1;
+
+        if pending.total >= FLUSH_THRESHOLD {
+            Self::flush_pending(db, &mut pending)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the number of hits on a record that haven't been flushed yet
+    pub fn pending_hits(&self, record_id : RecordID) -> u64 {
+        self.pending.lock().unwrap().counts.get(&record_id).copied().unwrap_or(0)
+    }
+
+    /// Returns the total number of hits that haven't been written to the database yet
+    pub fn pending_total(&self) -> usize {
+        self.pending.lock().unwrap().total
+    }
+
+    /// Writes all pending counts to the database
+    pub fn flush(&self, db : &DBConnection) -> Result<(), String> {
+        let mut pending = self.pending.lock().unwrap();
+        Self::flush_pending(db, &mut pending)
+    }
+
+    /// Discards the pending counts for a single record, e.g. because the record was deleted
+    pub fn forget(&self, record_id : RecordID) {
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(count) = pending.counts.remove(&record_id) {
+            pending.total -= count as usize;
+        }
+    }
+
+    /// Discards all pending counts
+    pub fn reset(&self) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.counts.clear();
+        pending.total = 0;
+    }
+
+    fn flush_pending(db : &DBConnection, pending : &mut PendingHits) -> Result<(), String> {
+        if pending.counts.is_empty() {
+            return Ok(());
+        }
+        db.add_access_counts(&pending.counts)?;
+        pending.counts.clear();
+        pending.total = 0;
+        Ok(())
+    }
+}
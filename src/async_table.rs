@@ -0,0 +1,232 @@
+//!
+//! The AsyncTable module contains everything associated with the "async" feature.  The AsyncTable
+//! struct is re-exported
+//!
+
+use std::sync::{Arc, RwLock};
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::RecordID;
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+use super::shutdown::ShutdownSummary;
+
+/// An async wrapper around a [Table], for use in async services
+///
+/// Every operation is run on the [tokio] blocking thread pool using [spawn_blocking](tokio::task::spawn_blocking),
+/// so the RocksDB work never blocks the async executor.  Lookups may run concurrently with each other, while
+/// operations that modify the Table are serialized.
+///
+/// Unlike [Table], the keys and values passed to an AsyncTable are owned, because they must be moved onto
+/// the blocking thread.  Lookups return a [Vec] of results instead of an iterator for the same reason.
+///
+/// NOTE: In order to use AsyncTable, you must enable the `async` feature in the `Cargo.toml` file
+/// with an entry similar to this:
+///
+/// ```toml
+/// [dependencies]
+/// fuzzy_rocks = { version = "0.2.3", features = ["async"] }
+/// ```
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// runtime.block_on(async {
+///     let table = AsyncTable::<DefaultTableConfig, true>::open("async_example.rocks", DefaultTableConfig()).await.unwrap();
+///     table.reset().await.unwrap();
+///
+///     let mon = table.insert("Monday", "Getsuyoubi".to_string()).await.unwrap();
+///     let results = table.lookup_best("Mnday").await.unwrap();
+///     assert_eq!(results, vec![mon]);
+///     assert_eq!(table.get_value(mon).await.unwrap(), "Getsuyoubi");
+/// });
+/// ```
+pub struct AsyncTable<ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    table : Arc<RwLock<Table<ConfigT, UTF8_KEYS>>>,
+}
+
+impl <ConfigT : TableConfig, const UTF8_KEYS : bool>Clone for AsyncTable<ConfigT, UTF8_KEYS> {
+    fn clone(&self) -> Self {
+        Self {
+            table : self.table.clone(),
+        }
+    }
+}
+
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>AsyncTable<ConfigT, UTF8_KEYS>
+    where
+    ConfigT : 'static + Send + Sync,
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8> + Send,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned + Send,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT> + Send,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Creates a new AsyncTable, serving the supplied Table
+    pub fn new(table : Table<ConfigT, UTF8_KEYS>) -> Self {
+        Self {
+            table : Arc::new(RwLock::new(table)),
+        }
+    }
+
+    /// Opens a Table backed by the database at the path provided, and wraps it in an AsyncTable
+    ///
+    /// Functions the same as [Table::new]
+    pub async fn open(path : &str, config : ConfigT) -> Result<Self, String> {
+        let path = path.to_string();
+        let table = run_blocking(move || Table::new(&path, config)).await?;
+        Ok(Self::new(table))
+    }
+
+    /// Returns the underlying Table, for operations that aren't available on the AsyncTable
+    ///
+    /// WARNING: Locking the table will block the current thread, so it should only be done from within
+    /// [spawn_blocking](tokio::task::spawn_blocking) or outside of the async executor.
+    pub fn table(&self) -> &Arc<RwLock<Table<ConfigT, UTF8_KEYS>>> {
+        &self.table
+    }
+
+    /// Resets the Table, dropping every record in the Table
+    ///
+    /// Functions the same as [Table::reset]
+    pub async fn reset(&self) -> Result<(), String> {
+        self.write(|table| table.reset()).await
+    }
+
+    /// Shuts the Table down, completing all of its pending work before the database is closed
+    ///
+    /// Functions the same as [Table::shutdown].  Returns an error without shutting down if the Table is still in
+    /// use, by another clone of the AsyncTable or by an operation that hasn't finished.
+    pub async fn shutdown(self) -> Result<ShutdownSummary, String> {
+        let table = Arc::try_unwrap(self.table).map_err(|_| "the Table is still in use by another AsyncTable".to_string())?;
+        let table = table.into_inner().map_err(|err| err.to_string())?;
+        run_blocking(move || table.shutdown()).await
+    }
+
+    /// Inserts a new key-value pair into the table and returns the RecordID of the new record
+    ///
+    /// Functions the same as [Table::insert]
+    pub async fn insert<K : Into<OwnedKeyT>>(&self, key : K, value : ConfigT::ValueT) -> Result<RecordID, String> {
+        let key = key.into();
+        self.write(move |table| table.insert_internal([&key].iter().copied(), 1, &value)).await
+    }
+
+    /// Creates a new record in the table and returns the RecordID of the new record
+    ///
+    /// Functions the same as [Table::create]
+    pub async fn create(&self, keys : Vec<OwnedKeyT>, value : ConfigT::ValueT) -> Result<RecordID, String> {
+        self.write(move |table| table.insert_internal(keys.iter(), keys.len(), &value)).await
+    }
+
+    /// Deletes a record from the Table
+    ///
+    /// Functions the same as [Table::delete]
+    pub async fn delete(&self, record_id : RecordID) -> Result<(), String> {
+        self.write(move |table| table.delete(record_id)).await
+    }
+
+    /// Replaces a record's value with the supplied value.  Returns the value that was replaced
+    ///
+    /// Functions the same as [Table::replace_value]
+    pub async fn replace_value(&self, record_id : RecordID, value : ConfigT::ValueT) -> Result<ConfigT::ValueT, String> {
+        self.write(move |table| table.replace_value(record_id, &value)).await
+    }
+
+    /// Returns the value associated with the specified record
+    ///
+    /// Functions the same as [Table::get_value]
+    pub async fn get_value(&self, record_id : RecordID) -> Result<ConfigT::ValueT, String> {
+        self.read(move |table| table.get_value(record_id)).await
+    }
+
+    /// Locates all records in the table with keys that precisely match the key supplied
+    ///
+    /// Functions the same as [Table::lookup_exact]
+    pub async fn lookup_exact<K : Into<OwnedKeyT>>(&self, key : K) -> Result<Vec<RecordID>, String> {
+        let key = key.into();
+        self.read(move |table| table.lookup_exact_internal(&key)).await
+    }
+
+    /// Locates all records in the table with a key that is within a deletion distance of `config.max_deletes` of
+    /// the key supplied
+    ///
+    /// Functions the same as [Table::lookup_fuzzy_raw]
+    pub async fn lookup_fuzzy_raw<K : Into<OwnedKeyT>>(&self, key : K) -> Result<Vec<RecordID>, String> {
+        let key = key.into();
+        self.read(move |table| table.lookup_fuzzy_raw_internal(&key, None)?.into_vec()).await
+    }
+
+    /// Locates all records in the table for which the supplied `distance_function` evaluates to a result smaller
+    /// than the supplied `threshold` when comparing the record's key with the supplied `key`
+    ///
+    /// Functions the same as [Table::lookup_fuzzy]
+    pub async fn lookup_fuzzy<K : Into<OwnedKeyT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        let key = key.into();
+        self.read(move |table| table.lookup_fuzzy_internal(&key, threshold, None)?.into_vec()).await
+    }
+
+    /// Performs a fuzzy lookup that only widens to more distant variants if fewer than `min_results` results are found
+    ///
+    /// Functions the same as [Table::lookup_fuzzy_adaptive]
+    pub async fn lookup_fuzzy_adaptive<K : Into<OwnedKeyT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, min_results : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        let key = key.into();
+        self.read(move |table| table.lookup_fuzzy_adaptive_internal(&key, threshold, min_results)).await
+    }
+
+    /// Returns up to `n` of the records closest to the supplied `key`, along with their distances
+    ///
+    /// Functions the same as [Table::lookup_best_n]
+    pub async fn lookup_best_n<K : Into<OwnedKeyT>>(&self, key : K, n : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        let key = key.into();
+        self.read(move |table| table.lookup_best_n_internal(&key, n)).await
+    }
+
+    /// Locates the record in the table for which the supplied `distance_function` evaluates to the lowest value
+    /// when comparing the record's key with the supplied `key`
+    ///
+    /// Functions the same as [Table::lookup_best]
+    pub async fn lookup_best<K : Into<OwnedKeyT>>(&self, key : K) -> Result<Vec<RecordID>, String> {
+        let key = key.into();
+        self.read(move |table| Ok(table.lookup_best_internal(&key)?.collect())).await
+    }
+
+    /// Runs a closure with shared access to the Table on the blocking thread pool
+    async fn read<R, F>(&self, func : F) -> Result<R, String>
+        where
+        R : 'static + Send,
+        F : 'static + Send + FnOnce(&Table<ConfigT, UTF8_KEYS>) -> Result<R, String>,
+    {
+        let table = self.table.clone();
+        run_blocking(move || {
+            let table = table.read().map_err(|err| err.to_string())?;
+            func(&table)
+        }).await
+    }
+
+    /// Runs a closure with exclusive access to the Table on the blocking thread pool
+    async fn write<R, F>(&self, func : F) -> Result<R, String>
+        where
+        R : 'static + Send,
+        F : 'static + Send + FnOnce(&mut Table<ConfigT, UTF8_KEYS>) -> Result<R, String>,
+    {
+        let table = self.table.clone();
+        run_blocking(move || {
+            let mut table = table.write().map_err(|err| err.to_string())?;
+            func(&mut table)
+        }).await
+    }
+}
+
+/// Runs a closure on the blocking thread pool, and converts a panic or cancellation into an error
+async fn run_blocking<R, F>(func : F) -> Result<R, String>
+    where
+    R : 'static + Send,
+    F : 'static + Send + FnOnce() -> Result<R, String>,
+{
+    tokio::task::spawn_blocking(func).await.map_err(|err| err.to_string())?
+}
@@ -0,0 +1,98 @@
+//!
+//! The Backup module contains the incremental backups kept with RocksDB's BackupEngine, used by
+//! [Table::backup_incremental](crate::Table::backup_incremental) and [Table::restore_from_backup](crate::Table::restore_from_backup).
+//! The BackupInfo struct is re-exported
+//!
+
+use std::fs;
+
+use rocksdb::backup::{BackupEngine, BackupEngineOptions, RestoreOptions};
+
+use super::lock_file::LockFile;
+
+/// A description of one of the backups in a backup directory, returned by [Table::list_backups](crate::Table::list_backups)
+///
+/// The backups in a directory share the database files they have in common, so the sum of their sizes is
+/// usually much bigger than the size of the directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupInfo {
+    /// The ID of the backup, which is higher for each backup taken
+    pub backup_id : u32,
+    /// The time the backup was taken, in seconds since the Unix epoch
+    pub timestamp : i64,
+    /// The total size of the backup's files, in bytes
+    pub size : u64,
+    /// The number of files in the backup
+    pub num_files : u32,
+}
+
+/// Opens the BackupEngine for the backups in `backup_dir`, creating the directory if necessary
+pub(crate) fn open_backup_engine(backup_dir : &str) -> Result<BackupEngine, String> {
+    Ok(BackupEngine::open(&BackupEngineOptions::default(), backup_dir)?)
+}
+
+/// Returns the backups in `backup_dir`, oldest first
+pub(crate) fn list_backups(backup_dir : &str) -> Result<Vec<BackupInfo>, String> {
+    Ok(engine_backups(&open_backup_engine(backup_dir)?))
+}
+
+/// Returns the backups known to the BackupEngine, oldest first
+///
+/// NOTE: A BackupEngine doesn't see the backups added by another BackupEngine after it was opened
+pub(crate) fn engine_backups(engine : &BackupEngine) -> Vec<BackupInfo> {
+    let mut backups : Vec<BackupInfo> = engine.get_backup_info().into_iter()
+        .map(|info| BackupInfo{backup_id : info.backup_id, timestamp : info.timestamp, size : info.size, num_files : info.num_files})
+        .collect();
+    backups.sort_by_key(|info| info.backup_id);
+    backups
+}
+
+/// Deletes all but the newest `keep` backups in `backup_dir`
+pub(crate) fn purge_old_backups(backup_dir : &str, keep : usize) -> Result<(), String> {
+    open_backup_engine(backup_dir)?.purge_old_backups(keep)?;
+    Ok(())
+}
+
+/// Replaces the database at `db_path` with the backup identified by `backup_id`, after checking the backup's
+/// files are intact.  The database must not be open
+///
+/// The backup is restored into a directory alongside the database, which is then renamed into place, so the
+/// database is left as it was if the restore fails.
+pub(crate) fn restore_from_backup(backup_dir : &str, db_path : &str, backup_id : u32) -> Result<(), String> {
+
+    let mut engine = open_backup_engine(backup_dir)?;
+    engine.verify_backup(backup_id).map_err(|err| format!("backup {} can't be restored: {}", backup_id, err))?;
+
+    //Hold the lock, so the database can't be opened while it's being replaced
+    //NOTE: RocksDB clears out the directory it restores into, so the restored database only gets a lock of
+    // its own once the restore is done
+    let mut lock = LockFile::acquire(db_path)?;
+    let restore_path = format!("{}.restoring", db_path);
+    let _ = fs::remove_dir_all(&restore_path);
+    if let Err(err) = engine.restore_from_backup(&restore_path, &restore_path, &RestoreOptions::default(), backup_id) {
+        let _ = fs::remove_dir_all(&restore_path);
+        return Err(err.into());
+    }
+    let mut restored_lock = LockFile::acquire(&restore_path)?;
+
+    //Swap the restored database into place, putting the old database back if that fails
+    let replaced_path = format!("{}.replaced", db_path);
+    let _ = fs::remove_dir_all(&replaced_path);
+    if let Err(err) = fs::rename(db_path, &replaced_path) {
+        drop(restored_lock);
+        let _ = fs::remove_dir_all(&restore_path);
+        return Err(format!("couldn't move database {} aside: {}", db_path, err));
+    }
+    lock.renamed(&replaced_path);
+    if let Err(err) = fs::rename(&restore_path, db_path) {
+        if fs::rename(&replaced_path, db_path).is_ok() {
+            lock.renamed(db_path);
+        }
+        drop(restored_lock);
+        let _ = fs::remove_dir_all(&restore_path);
+        return Err(format!("couldn't move the restored database into place at {}: {}", db_path, err));
+    }
+    restored_lock.renamed(db_path);
+    drop(lock);
+    fs::remove_dir_all(&replaced_path).map_err(|err| format!("couldn't remove the replaced database at {}: {}", replaced_path, err))
+}
@@ -0,0 +1,65 @@
+//!
+//! The BandedLevenshtein module contains the bounded edit distance check used to prune the candidate keys of a
+//! fuzzy lookup before the distance function is invoked.  Nothing should be re-exported
+//!
+
+/// A check that accepts the keys within `max_edits` Levenshtein edits of a query key
+///
+/// This is the Wagner-Fischer dynamic programming algorithm, restricted to a band, rather than a true Levenshtein
+/// automaton; there is no precomputed state machine.  The key is consumed one char at a time, and the state is
+/// the row of edit distances between the chars consumed so far and each prefix of the query, limited to the band
+/// of `2 * max_edits + 1` prefixes that can still be within `max_edits`, and capped at `max_edits + 1`.  So each
+/// char only computes the distances in the band, and as soon as every entry in the band exceeds `max_edits`, no continuation of the
+/// key can be accepted, so most non-matching keys are rejected after only a few chars.
+pub(crate) struct BandedLevenshtein<KeyCharT> {
+    query : Vec<KeyCharT>,
+    max_edits : usize,
+}
+
+impl <KeyCharT : PartialEq>BandedLevenshtein<KeyCharT> {
+
+    pub fn new(query : Vec<KeyCharT>, max_edits : usize) -> Self {
+        Self {
+            query,
+            max_edits,
+        }
+    }
+
+    /// Returns `true` if `key` is within `max_edits` Levenshtein edits of the query
+    pub fn accepts(&self, key : &[KeyCharT]) -> bool {
+
+        //Keys whose length differs by more than max_edits can't be accepted
+        if key.len().abs_diff(self.query.len()) > self.max_edits {
+            return false;
+        }
+
+        let reject = self.max_edits + 1;
+        let mut row : Vec<usize> = (0..=self.query.len()).map(|i| i.min(reject)).collect();
+        let mut next_row = vec![reject; self.query.len() + 1];
+        for (key_idx, key_char) in key.iter().enumerate() {
+            let consumed = key_idx + 1;
+
+            //Only the prefixes of the query within max_edits of the consumed length can be within max_edits
+            let band_start = consumed.saturating_sub(self.max_edits);
+            let band_end = (consumed + self.max_edits).min(self.query.len());
+
+            next_row.iter_mut().for_each(|entry| *entry = reject);
+            if band_start == 0 {
+                next_row[0] = consumed.min(reject);
+            }
+            let mut can_match = next_row[0] < reject;
+            for i in band_start.max(1)..=band_end {
+                let substitution = row[i-1] + if self.query[i-1] == *key_char { 0 } else { 1 };
+                let distance = substitution.min(row[i] + 1).min(next_row[i-1] + 1).min(reject);
+                next_row[i] = distance;
+                can_match = can_match || distance < reject;
+            }
+            if !can_match {
+                return false;
+            }
+            std::mem::swap(&mut row, &mut next_row);
+        }
+
+        row[self.query.len()] <= self.max_edits
+    }
+}
@@ -13,6 +13,7 @@ use std::convert::{TryInto};
 /// It's highly likely that the data size reduction completely makes up for the extra work and memcpy incurred
 /// deserializing the structure, and it's faster not to mess with trying to read the buffer without fully
 /// deserializing it.
+#[derive(Clone)]
 pub struct BinCodeVecIterator<'a, T : Sized + Copy> {
     remaining_buf : &'a [u8],
     phantom: PhantomData<&'a T>,
@@ -21,21 +22,32 @@ pub struct BinCodeVecIterator<'a, T : Sized + Copy> {
 /// Returns the length of a Vec<T> that has been encoded with bincode, using
 /// [FixintEncoding](bincode::config::FixintEncoding) and
 /// [LittleEndian](bincode::config::LittleEndian) byte order.
-pub fn bincode_vec_fixint_len(buf : &[u8]) -> usize {
+/// 
+/// Returns an error if the buffer is too short to hold the length.
+pub fn bincode_vec_fixint_len(buf : &[u8]) -> Result<usize, String> {
 
-    let (len_chars, _remainder) = buf.split_at(8);
-    usize::from_le_bytes(len_chars.try_into().unwrap())
+    match buf.get(0..8) {
+        Some(len_chars) => Ok(usize::from_le_bytes(len_chars.try_into().unwrap())),
+        None => Err(format!("buffer of {} bytes is too short for a Vec length", buf.len()))
+    }
 }
 
 /// Returns a [BinCodeVecIterator] to iterate over a Vec<T> that has been encoded with bincode,
 /// without requiring an actual [Vec] to be recreated in memory
-pub fn bincode_vec_iter<T : Sized + Copy>(buf : &[u8]) -> BinCodeVecIterator<'_, T> {
+/// 
+/// Returns an error if the length at the beginning of the buffer doesn't agree with the number of
+/// bytes that follow it, so the iterator can never read past the end of the buffer, or silently skip
+/// trailing bytes.
+pub fn bincode_vec_iter<T : Sized + Copy>(buf : &[u8]) -> Result<BinCodeVecIterator<'_, T>, String> {
 
-    //Skip over the length at the beginning (8 bytes = 64bit usize), because we can infer
-    // the Vec length from the buffer size
-    let (_len_chars, remainder) = buf.split_at(8);
+    //Check the length at the beginning (8 bytes = 64bit usize) against the size of the rest of the buffer
+    let vec_len = bincode_vec_fixint_len(buf)?;
+    let remainder = &buf[8..];
+    if vec_len.checked_mul(::std::mem::size_of::<T>()) != Some(remainder.len()) {
+        return Err(format!("Vec length {} doesn't agree with the {} bytes of elements", vec_len, remainder.len()));
+    }
 
-    BinCodeVecIterator{remaining_buf: remainder, phantom : PhantomData}
+    Ok(BinCodeVecIterator{remaining_buf: remainder, phantom : PhantomData})
 }
 
 impl <'a, T : Sized + Copy>Iterator for BinCodeVecIterator<'a, T> {
@@ -56,49 +68,49 @@ impl <'a, T : Sized + Copy>Iterator for BinCodeVecIterator<'a, T> {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining_buf.len() / ::std::mem::size_of::<T>();
+        (remaining, Some(remaining))
+    }
 }
 
+impl <T : Sized + Copy>ExactSizeIterator for BinCodeVecIterator<'_, T> {}
+
 /// Interprets the bytes at the start of `buf` as an encoded 64-bit unsigned number that has been
 /// encoded with bincode, using [VarintEncoding](bincode::config::VarintEncoding) and
 /// [LittleEndian](bincode::config::LittleEndian) byte order.
 /// 
-/// Returns the encoded value, and sets `num_bytes` to the number of bytes in the buffer used to encode
-/// the value.
-pub fn bincode_u64_le_varint(buf : &[u8], num_bytes : &mut usize) -> u64 {
-
-    match buf[0] {
-        251 => {
-            let (_junk_char, remainder) = buf.split_at(1);
-            let (len_chars, _remainder) = remainder.split_at(2);
-            let value = u16::from_le_bytes(len_chars.try_into().unwrap());
-            *num_bytes = 3;
-            value as u64
+/// Returns the encoded value, and the number of bytes in the buffer used to encode the value.  Returns
+/// an error if the buffer is too short to hold the encoded value, or the value doesn't fit in a `u64`
+pub fn bincode_u64_le_varint(buf : &[u8]) -> Result<(u64, usize), String> {
+
+    //Get the bytes following the tag byte, making sure the buffer is long enough to hold them
+    let value_bytes = |num_bytes : usize| -> Result<&[u8], String> {
+        buf.get(1..1+num_bytes).ok_or_else(|| format!("buffer of {} bytes is too short for a {} byte varint", buf.len(), num_bytes+1))
+    };
+
+    match buf.first() {
+        Some(251) => {
+            let value = u16::from_le_bytes(value_bytes(2)?.try_into().unwrap());
+            Ok((value as u64, 3))
         },
-        252 => {
-            let (_junk_char, remainder) = buf.split_at(1);
-            let (len_chars, _remainder) = remainder.split_at(4);
-            let value = u32::from_le_bytes(len_chars.try_into().unwrap());
-            *num_bytes = 5;
-            value as u64
+        Some(252) => {
+            let value = u32::from_le_bytes(value_bytes(4)?.try_into().unwrap());
+            Ok((value as u64, 5))
         },
-        253 => {
-            let (_junk_char, remainder) = buf.split_at(1);
-            let (len_chars, _remainder) = remainder.split_at(8);
-            let value = u64::from_le_bytes(len_chars.try_into().unwrap());
-            *num_bytes = 9;
-            value
+        Some(253) => {
+            let value = u64::from_le_bytes(value_bytes(8)?.try_into().unwrap());
+            Ok((value, 9))
         },
-        254 => {
-            let (_junk_char, remainder) = buf.split_at(1);
-            let (len_chars, _remainder) = remainder.split_at(16);
-            let value = u128::from_le_bytes(len_chars.try_into().unwrap());
-            *num_bytes = 17;
-            value as u64
+        Some(254) => {
+            let value = u128::from_le_bytes(value_bytes(16)?.try_into().unwrap());
+            let value = u64::try_from(value).map_err(|_| format!("varint value {} doesn't fit in a u64", value))?;
+            Ok((value, 17))
         },
-        _ => {
-            *num_bytes = 1;
-            buf[0] as u64
-        }
+        Some(255) => Err("invalid varint tag byte 255".to_string()),
+        Some(value) => Ok((*value as u64, 1)),
+        None => Err("buffer is empty, expected a varint".to_string())
     }
 }
 
@@ -108,8 +120,7 @@ pub fn bincode_u64_le_varint(buf : &[u8], num_bytes : &mut usize) -> u64 {
 // fn bincode_string_varint(buf : &[u8]) -> &[u8] {
 
 //     //Interpret the length
-//     let mut skip_bytes = 0;
-//     let string_len = bincode_u64_le_varint(buf, &mut skip_bytes);
+//     let (string_len, skip_bytes) = bincode_u64_le_varint(buf).unwrap();
 
 //     //Split the slice to grab the string
 //     let (_len_chars, remainder) = buf.split_at(skip_bytes);
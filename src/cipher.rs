@@ -0,0 +1,110 @@
+//!
+//! The Cipher module contains the [Cipher] trait, which encrypts the contents of a Table before they are
+//! written to the database.  The Cipher trait is re-exported
+//!
+
+/// Encrypts the bytes of a Table's entries before they are written to the database, and decrypts them as they
+/// are read back, so the records are encrypted at rest.  Supplied by [TableConfig::cipher](crate::TableConfig::cipher)
+///
+/// Values are always encrypted.  If [encrypts_keys](Cipher::encrypts_keys) returns `true`, the keys are too, along
+/// with the injected variants and the synonyms.  A fuzzy lookup must still find a variant's entry by its bytes,
+/// so the variants, and the keys of the supplemental indices, are encrypted with
+/// [encrypt_deterministic](Cipher::encrypt_deterministic), which always gives the same ciphertext for the same
+/// plaintext.  That means an attacker who can read the database can tell when two records share a variant, but
+/// can't read the variant.  Prefix lookups, which need the variants to be stored in order, return an error.
+///
+/// The key material is up to the implementation, and is normally supplied by the caller when the Table is opened,
+/// as a field of the config.  A check value encrypted with the cipher is stored in the database when it is
+/// created, so opening the database with the wrong key returns an error, rather than garbage.  The RecordIDs, the
+/// structure of the index, hit counts, and [RecordMetadata](crate::RecordMetadata), are never encrypted.
+///
+/// NOTE: fuzzy_rocks doesn't provide any ciphers, so as not to depend on a particular cryptography crate.  An
+/// implementation would typically use an AEAD cipher such as AES-GCM with a random nonce for
+/// [encrypt](Cipher::encrypt), and a misuse-resistant mode such as AES-SIV for `encrypt_deterministic`.
+///
+/// ```
+/// use std::sync::Arc;
+/// use fuzzy_rocks::{*};
+///
+/// /// A stand-in for a real cipher, that XORs every byte with the key.  It is NOT secure
+/// struct XorCipher(u8);
+/// impl Cipher for XorCipher {
+///     fn encrypt(&self, plaintext : &[u8]) -> Vec<u8> {
+///         plaintext.iter().map(|byte| byte ^ self.0).collect()
+///     }
+///     fn encrypt_deterministic(&self, plaintext : &[u8]) -> Vec<u8> {
+///         self.encrypt(plaintext)
+///     }
+///     fn decrypt(&self, ciphertext : &[u8]) -> Result<Vec<u8>, String> {
+///         Ok(self.encrypt(ciphertext))
+///     }
+/// }
+///
+/// struct Config(u8);
+/// impl TableConfig for Config {
+///     type KeyCharT = char;
+///     type DistanceT = u8;
+///     type ValueT = String;
+///     fn cipher(&self) -> Option<Arc<dyn Cipher>> {
+///         Some(Arc::new(XorCipher(self.0)))
+///     }
+/// }
+///
+/// let mut table = Table::<Config, true>::new("cipher_example.rocks", Config(0x5A)).unwrap();
+/// table.reset().unwrap();
+/// let record_id = table.insert("Hello", &"Greeting".to_string()).unwrap();
+/// assert_eq!(table.get_value(record_id).unwrap(), "Greeting");
+/// drop(table);
+///
+/// //The database can't be opened with the wrong key
+/// assert!(Table::<Config, true>::new("cipher_example.rocks", Config(0x33)).is_err());
+/// ```
+pub trait Cipher : Send + Sync {
+
+    /// Encrypts the bytes of an entry.  The ciphertext may differ each time the same plaintext is encrypted
+    fn encrypt(&self, plaintext : &[u8]) -> Vec<u8>;
+
+    /// Encrypts the bytes of a variant, or of the key of another index entry, so the same plaintext always gives
+    /// the same ciphertext
+    fn encrypt_deterministic(&self, plaintext : &[u8]) -> Vec<u8>;
+
+    /// Decrypts the bytes produced by either [encrypt](Cipher::encrypt) or [encrypt_deterministic](Cipher::encrypt_deterministic),
+    /// or returns an error if they can't be decrypted, e.g. because they have been tampered with
+    fn decrypt(&self, ciphertext : &[u8]) -> Result<Vec<u8>, String>;
+
+    /// Returns whether the keys are encrypted along with the values.  The default is `true`
+    ///
+    /// When the keys aren't encrypted, the variants aren't either, so the lookups are as fast as they would be
+    /// without a cipher, and prefix lookups are available.
+    fn encrypts_keys(&self) -> bool {
+        true
+    }
+}
+
+/// The plaintext of the check value stored in the "cipher_check" metadata entry
+const CIPHER_CHECK_PLAINTEXT : &[u8] = b"fuzzy_rocks cipher check";
+
+/// Returns the "cipher_check" metadata entry for a database encrypted with the cipher, which holds whether the
+/// keys are encrypted, and the check value
+pub(crate) fn cipher_check(cipher : &dyn Cipher) -> (bool, Vec<u8>) {
+    (cipher.encrypts_keys(), cipher.encrypt(CIPHER_CHECK_PLAINTEXT))
+}
+
+/// Returns an error unless the cipher, or the lack of one, matches the "cipher_check" metadata entry stored
+/// in the database
+pub(crate) fn verify_cipher(stored_check : Option<&(bool, Vec<u8>)>, cipher : Option<&dyn Cipher>) -> Result<(), String> {
+    match (stored_check, cipher) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err("the database isn't encrypted, but the config supplies a cipher".to_string()),
+        (Some(_), None) => Err("the database is encrypted, but the config doesn't supply a cipher".to_string()),
+        (Some((encrypts_keys, check_value)), Some(cipher)) => {
+            if *encrypts_keys != cipher.encrypts_keys() {
+                return Err(format!("the database was written with {} keys, but the cipher {} them", if *encrypts_keys { "encrypted" } else { "unencrypted" }, if cipher.encrypts_keys() { "encrypts" } else { "doesn't encrypt" }));
+            }
+            match cipher.decrypt(check_value) {
+                Ok(plaintext) if plaintext == CIPHER_CHECK_PLAINTEXT => Ok(()),
+                _ => Err("the cipher can't decrypt the database, so its key is probably wrong".to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//!
+//! The Compound module contains the results of a compound-word lookup made by [Table::lookup_compound](crate::Table::lookup_compound).
+//! The CompoundMatch struct is re-exported
+//!
+
+use super::records::RecordID;
+
+/// A way of splitting a key into segments, where each segment exactly matches the key of at least one record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompoundMatch<KeyT> {
+    /// The segments the key was split into, in order
+    pub segments : Vec<KeyT>,
+    /// The records with a key matching each segment, in the same order as `segments`
+    pub record_ids : Vec<Vec<RecordID>>,
+}
+
+/// Returns the boundaries of every split of a key with `num_chars` chars into between 2 and `max_splits + 1`
+/// segments, for which `segment_matches` returns `true` for every segment.  Each split is returned as the
+/// index where each segment ends, and splits with fewer segments are returned first
+///
+/// `segment_matches` is called with the start and end index of a segment, and is called at most once for each
+/// segment.
+pub(crate) fn compound_splits<F>(num_chars : usize, max_splits : usize, mut segment_matches : F) -> Result<Vec<Vec<usize>>, String>
+    where F : FnMut(usize, usize) -> Result<bool, String>
+{
+    let mut memo = std::collections::HashMap::new();
+    let mut splits = vec![];
+    let mut boundaries = vec![];
+    collect_splits(0, num_chars, max_splits, &mut boundaries, &mut splits, &mut |start, end| {
+        if let Some(matches) = memo.get(&(start, end)) {
+            return Ok(*matches);
+        }
+        let matches = segment_matches(start, end)?;
+        memo.insert((start, end), matches);
+        Ok(matches)
+    })?;
+
+    //NOTE: A stable sort, so splits with the same number of segments stay ordered by their first boundary
+    splits.sort_by_key(|split| split.len());
+    Ok(splits)
+}
+
+fn collect_splits<F>(start : usize, num_chars : usize, splits_left : usize, boundaries : &mut Vec<usize>, splits : &mut Vec<Vec<usize>>, segment_matches : &mut F) -> Result<(), String>
+    where F : FnMut(usize, usize) -> Result<bool, String>
+{
+    //The rest of the key as a final segment, as long as the key has been split at least once
+    if start > 0 && segment_matches(start, num_chars)? {
+        let mut split = boundaries.clone();
+        split.push(num_chars);
+        splits.push(split);
+    }
+
+    if splits_left == 0 {
+        return Ok(());
+    }
+
+    //Split off each possible segment, and split the remainder of the key
+    for end in start+1..num_chars {
+        if segment_matches(start, end)? {
+            boundaries.push(end);
+            collect_splits(end, num_chars, splits_left - 1, boundaries, splits, segment_matches)?;
+            boundaries.pop();
+        }
+    }
+
+    Ok(())
+}
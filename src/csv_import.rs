@@ -0,0 +1,138 @@
+//!
+//! The CsvImport module contains [Table::import_csv](crate::Table::import_csv), for loading records from
+//! delimited files, and the [CsvMapping] that selects the key columns.  The CsvMapping struct is re-exported
+//!
+
+use std::io::Read;
+
+use csv::StringRecord;
+
+use super::table_config::{*};
+use super::table::{*};
+
+/// Describes how the columns of a delimited file are turned into records by [Table::import_csv](crate::Table::import_csv)
+///
+/// Each of the key columns supplies one key for the record, or several if a separator is set, and the rest of
+/// the columns are deserialized into the record's value with serde, using the column headers as field names.
+#[derive(Debug, Clone)]
+pub struct CsvMapping {
+    key_columns : Vec<String>,
+    key_separator : Option<char>,
+    batch_size : usize,
+}
+
+impl CsvMapping {
+
+    /// Creates a mapping that takes the record's keys from the columns with the supplied headers
+    pub fn new(key_columns : &[&str]) -> Self {
+        Self {
+            key_columns : key_columns.iter().map(|column| column.to_string()).collect(),
+            key_separator : None,
+            batch_size : 1000,
+        }
+    }
+
+    /// Splits the contents of each key column into several keys at the separator, e.g. for a column of
+    /// comma-separated alternate names
+    pub fn key_separator(mut self, separator : char) -> Self {
+        self.key_separator = Some(separator);
+        self
+    }
+
+    /// Sets the number of records that are written to the database together, in a single [Transaction](crate::Transaction).
+    /// The default is 1000
+    pub fn batch_size(mut self, batch_size : usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Returns the distinct, non-empty keys in a row's key columns
+    fn row_keys<'a>(&self, key_fields : impl Iterator<Item=&'a str>) -> Vec<&'a str> {
+        let mut keys : Vec<&str> = vec![];
+        for field in key_fields {
+            let parts : Vec<&str> = match self.key_separator {
+                Some(separator) => field.split(separator).collect(),
+                None => vec![field],
+            };
+            for key in parts.into_iter().map(str::trim) {
+                if !key.is_empty() && !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys
+    }
+}
+
+impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
+
+    /// Creates a record for each row read from a delimited file, such as a CSV or a GeoNames TSV file, and
+    /// returns the number of records created
+    ///
+    /// The reader must be configured with the file's delimiter and quoting, and the file must have a header
+    /// row, which names the columns for the `mapping` and for deserializing the values.  The records are
+    /// inserted in batches, each written as a single [Transaction](crate::Transaction), so if a row can't be
+    /// imported, the records of the earlier batches have already been created.  The error names the line of
+    /// the offending row.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    /// use serde::{Serialize, Deserialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct City {
+    ///     population : u64,
+    /// }
+    /// struct Config();
+    /// impl TableConfig for Config {
+    ///     type KeyCharT = char;
+    ///     type DistanceT = u8;
+    ///     type ValueT = City;
+    /// }
+    ///
+    /// let mut table = Table::<Config, true>::new("import_csv_example.rocks", Config()).unwrap();
+    /// table.reset().unwrap();
+    ///
+    /// let data = "name\talt_names\tpopulation\nTokyo\tTokio,Tōkyō\t37400068\nDelhi\t\t28514000\n";
+    /// let mut reader = csv::ReaderBuilder::new().delimiter(b'\t').from_reader(data.as_bytes());
+    /// let mapping = CsvMapping::new(&["name", "alt_names"]).key_separator(',');
+    /// assert_eq!(table.import_csv(&mut reader, &mapping).unwrap(), 2);
+    ///
+    /// let tokyo = table.lookup_exact("Tokio").unwrap().next().unwrap();
+    /// assert_eq!(table.get_value(tokyo).unwrap().population, 37400068);
+    /// ```
+    pub fn import_csv<R : Read>(&mut self, reader : &mut csv::Reader<R>, mapping : &CsvMapping) -> Result<usize, String> {
+
+        //Split the columns into the keys and the value
+        let headers = reader.headers().map_err(|err| format!("couldn't read the CSV headers: {}", err))?.clone();
+        let mut key_column_idxs = vec![];
+        for key_column in mapping.key_columns.iter() {
+            let idx = headers.iter().position(|header| header == key_column)
+                .ok_or_else(|| format!("key column \"{}\" isn't in the CSV headers", key_column))?;
+            key_column_idxs.push(idx);
+        }
+        let value_column_idxs : Vec<usize> = (0..headers.len()).filter(|idx| !key_column_idxs.contains(idx)).collect();
+        let value_headers : StringRecord = value_column_idxs.iter().map(|idx| &headers[*idx]).collect();
+
+        let mut records = reader.records().peekable();
+        let mut record_count = 0;
+        while records.peek().is_some() {
+            let mut transaction = self.transaction();
+            for row in records.by_ref().take(mapping.batch_size) {
+                let row = row.map_err(|err| format!("couldn't read the CSV row: {}", err))?;
+                let line = row.position().map(|position| position.line()).unwrap_or_default();
+
+                let keys = mapping.row_keys(key_column_idxs.iter().filter_map(|idx| row.get(*idx)));
+                let value_fields : StringRecord = value_column_idxs.iter().map(|idx| row.get(*idx).unwrap_or_default()).collect();
+                let value : ConfigT::ValueT = value_fields.deserialize(Some(&value_headers))
+                    .map_err(|err| format!("couldn't import line {}: {}", line, err))?;
+                transaction.create(&keys[..], &value)
+                    .map_err(|err| format!("couldn't import line {}: {}", line, err))?;
+                record_count += 1;
+            }
+            transaction.commit()?;
+        }
+
+        Ok(record_count)
+    }
+}
@@ -0,0 +1,63 @@
+//!
+//! The Cursor module contains the [LookupCursor] object, which records the progress of a paged lookup.
+//! The LookupCursor struct is re-exported
+//!
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use serde::{Serialize, Deserialize};
+use bincode::Options;
+
+use super::records::RecordID;
+use super::key_groups::KeyGroupID;
+
+/// The progress of a fuzzy lookup made by [Table::lookup_fuzzy_page](crate::Table::lookup_fuzzy_page), so a later
+/// call can continue with the next page of results
+///
+/// A LookupCursor holds the variants that haven't been probed yet, the key groups that have been found but not
+/// evaluated, and the records that have already been returned, so no record is returned on two pages.  It can
+/// be converted to bytes with [to_bytes](LookupCursor::to_bytes), e.g. to be handed to a client between requests,
+/// and restored with [from_bytes](LookupCursor::from_bytes).
+///
+/// The cursor doesn't pin a snapshot of the Table, so records written between pages may or may not appear on the
+/// later pages.  The cursor must be used with the same Table, key, and threshold as the lookup that produced it,
+/// and a cursor produced before the Table was [reset](crate::Table::reset) can't be used after it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LookupCursor {
+    pub(crate) table_id : u64,
+    pub(crate) lookup_key : Vec<u8>,
+    pub(crate) threshold : Vec<u8>,
+    pub(crate) variants : Vec<Vec<u8>>,
+    pub(crate) pending_groups : Vec<KeyGroupID>,
+    pub(crate) visited_groups : Vec<KeyGroupID>,
+    pub(crate) evaluated_records : Vec<RecordID>,
+    pub(crate) rejected_by_keys : Vec<RecordID>,
+}
+
+impl LookupCursor {
+
+    /// Encodes the cursor as bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        coder.serialize(self).unwrap()
+    }
+
+    /// Decodes a cursor from the bytes produced by [to_bytes](LookupCursor::to_bytes)
+    pub fn from_bytes(bytes : &[u8]) -> Result<Self, String> {
+        let coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        coder.deserialize(bytes).map_err(|err| format!("invalid lookup cursor: {}", err))
+    }
+}
+
+/// Returns a new random id for the contents of a table, so a cursor can't be used with a table other than the
+/// one it was produced by.  A table is given a new id when it is reset
+pub(crate) fn new_table_id() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Encodes a lookup's threshold, so a cursor can be checked against the threshold of the lookup it's used with
+pub(crate) fn threshold_bytes<DistanceT : Serialize>(threshold : &Option<DistanceT>) -> Result<Vec<u8>, String> {
+    let coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+    coder.serialize(threshold).map_err(|err| format!("failed to encode the threshold: {}", err))
+}
@@ -5,279 +5,2803 @@
 
 use core::hash::Hash;
 
-use std::collections::{HashSet};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use serde::{Serialize};
 use bincode::Options;
 
-use rocksdb::{DB, DBWithThreadMode, ColumnFamily, ColumnFamilyDescriptor, MergeOperands};
+use rocksdb::{DB, DBWithThreadMode, BoundColumnFamily, MultiThreaded, AsColumnFamilyRef, ColumnFamilyDescriptor, MergeOperands, WriteBatch, IteratorMode};
 
 use super::bincode_helpers::{*};
 
 use super::records::{*};
 use super::key_groups::{*};
 use super::perf_counters::{*};
+use super::quarantine::{*};
+use super::lock_file::{*};
+use super::merge_diagnostics::{*};
+use super::pin_cache::PinCache;
+use super::record_metadata::{RecordMetadata, now_millis};
+use super::cipher::Cipher;
+use super::rocks_config::RocksConfig;
+use super::db_statistics::DBStatistics;
+use super::disk_usage::{DiskUsage, CFDiskUsage};
+use super::db_properties::{DBProperties, CFProperties};
 
 /// The ColumnFamily names used for the different types of data
 pub const KEYS_CF_NAME : &str = "keys";
 pub const RECORD_DATA_CF_NAME : &str = "rec_data";
 pub const VALUES_CF_NAME : &str = "values";
 pub const VARIANTS_CF_NAME : &str = "variants";
+pub const QUARANTINE_CF_NAME : &str = "quarantine";
+pub const STATS_CF_NAME : &str = "stats";
+pub const VARIANT_PAGES_CF_NAME : &str = "variant_pages";
+pub const METADATA_CF_NAME : &str = "metadata";
+pub const INJECTED_CF_NAME : &str = "injected";
+pub const EXACT_KEYS_CF_NAME : &str = "exact_keys";
+pub const PHONETIC_CF_NAME : &str = "phonetic";
+pub const NGRAMS_CF_NAME : &str = "ngrams";
+pub const TOKENS_CF_NAME : &str = "tokens";
+pub const SYNONYMS_CF_NAME : &str = "synonyms";
+pub const RECORD_META_CF_NAME : &str = "record_meta";
+pub const TOMBSTONES_CF_NAME : &str = "tombstones";
+pub const BLOBS_CF_NAME : &str = "blobs";
+
+/// The names of all of the column families in a database
+pub const COLUMN_FAMILY_NAMES : [&str; 17] = [KEYS_CF_NAME, RECORD_DATA_CF_NAME, VALUES_CF_NAME, VARIANTS_CF_NAME, QUARANTINE_CF_NAME, STATS_CF_NAME, VARIANT_PAGES_CF_NAME, METADATA_CF_NAME, INJECTED_CF_NAME, EXACT_KEYS_CF_NAME, PHONETIC_CF_NAME, NGRAMS_CF_NAME, TOKENS_CF_NAME, SYNONYMS_CF_NAME, RECORD_META_CF_NAME, TOMBSTONES_CF_NAME, BLOBS_CF_NAME];
+
+/// The separator between a table's name and the name of a column family, in the names of the column families
+/// of a database shared by several tables
+pub const SHARED_CF_SEPARATOR : char = ':';
+
+/// The column families that are opened regardless of which are requested, because a Table can't be opened without them
+pub const REQUIRED_COLUMN_FAMILY_NAMES : [&str; 3] = [RECORD_DATA_CF_NAME, METADATA_CF_NAME, TOMBSTONES_CF_NAME];
+
+/// The name of the "metadata" entry that holds the [TableParams](crate::TableParams) a table was created with
+pub const TABLE_PARAMS_METADATA_NAME : &str = "table_params";
+
+/// The name of the "metadata" entry that holds the version of the on-disk format the database was written in
+pub const FORMAT_VERSION_METADATA_NAME : &str = "format_version";
+
+/// The name of the "metadata" entry that holds the random id of the table's contents, which changes when the table
+/// is reset
+pub const TABLE_ID_METADATA_NAME : &str = "table_id";
+
+/// The name of the "metadata" entry that records whether the database's entries have checksums
+pub const CHECKSUMS_METADATA_NAME : &str = "checksums";
+
+/// The name of the "metadata" entry that holds whether the database is encrypted, and the check value used to
+/// make sure it is opened with the right key
+pub const CIPHER_CHECK_METADATA_NAME : &str = "cipher_check";
+
+/// An entry in a CF, as the raw bytes of its key and value
+pub type RawEntry = (Vec<u8>, Vec<u8>);
+
+/// The name of the "metadata" entry that holds the RecordIDs of the hottest records, to preload when a table is opened
+pub const PRELOAD_MANIFEST_METADATA_NAME : &str = "preload_manifest";
+
+/// The name of the "metadata" entry that holds the RecordIDs of the pinned records
+pub const PINNED_RECORDS_METADATA_NAME : &str = "pinned_records";
+
+/// The column families whose entries are held in the [PinCache] for pinned records
+const PINNED_CF_NAMES : [&str; 3] = [KEYS_CF_NAME, RECORD_DATA_CF_NAME, VALUES_CF_NAME];
+
+/// A write recorded in a [DBWriteBatch], so the batch can answer reads for the entries it has touched
+#[derive(Clone)]
+enum PendingEntry {
+    Put(Vec<u8>),
+    Delete,
+    Merge(Vec<Vec<u8>>), //The merge operands, to be applied on top of the committed entry
+}
+
+/// A set of writes spanning all of the column families, that will be applied to the database atomically
+/// by [DBConnection::commit_batch]
+/// 
+/// A whole record operation (e.g. an insert or a delete) is composed in a single batch, so a crash part
+/// way through the operation can't leave the table in an inconsistent state.
+/// 
+/// The batch holds the final state of each entry it has written, and the RocksDB `WriteBatch` is only built
+/// from them when the batch is committed.  So reads made through the batch while an operation is being composed
+/// will see the operation's own writes.  This also lets a [Transaction](crate::Transaction) compose many
+/// operations in one batch, each one in a [scratch](DBWriteBatch::scratch) batch on top of it, so a failed
+/// operation can be discarded without discarding the operations before it.
+pub struct DBWriteBatch {
+    pending : HashMap<(&'static str, Vec<u8>), PendingEntry>,
+    /// The variant references to add (`true`) to, or remove (`false`) from, the variants buffered by a bulk
+    /// load, which are applied when the batch is committed
+    bulk_variant_refs : Vec<(Vec<u8>, KeyGroupID, bool)>,
+    /// The batch this one is composed on top of, if it's a scratch batch
+    base : Option<Box<DBWriteBatch>>,
+}
+
+impl DBWriteBatch {
+    pub fn new() -> Self {
+        Self {
+            pending : HashMap::new(),
+            bulk_variant_refs : vec![],
+            base : None,
+        }
+    }
+
+    /// Returns a scratch batch on top of this one.  Reads through the scratch batch see the writes in both
+    /// batches, and its own writes can be merged into this one with [merge_scratch](Self::merge_scratch), or
+    /// discarded with [discard_scratch](Self::discard_scratch)
+    pub fn scratch(self) -> Self {
+        Self {
+            base : Some(Box::new(self)),
+            ..Self::new()
+        }
+    }
+
+    /// Merges the writes made in a scratch batch into the batch it was created from, and returns that batch
+    pub fn merge_scratch(self) -> Self {
+        match self.base {
+            Some(base) => {
+                let mut base = *base;
+                base.pending.extend(self.pending);
+                base.bulk_variant_refs.extend(self.bulk_variant_refs);
+                base
+            },
+            None => self
+        }
+    }
+
+    /// Discards the writes made in a scratch batch, and returns the batch it was created from unchanged
+    pub fn discard_scratch(self) -> Self {
+        match self.base {
+            Some(base) => *base,
+            None => Self::new()
+        }
+    }
+
+    fn put(&mut self, cf_name : &'static str, key : &[u8], value : Vec<u8>) {
+        self.pending.insert((cf_name, key.to_vec()), PendingEntry::Put(value));
+    }
+
+    fn delete(&mut self, cf_name : &'static str, key : &[u8]) {
+        self.pending.insert((cf_name, key.to_vec()), PendingEntry::Delete);
+    }
+
+    /// Queues a merge of a variant entry with the supplied operand.  If the batch, or a batch it's composed on top
+    /// of, already wrote the entry, the operand is folded into that write.  Otherwise the merge is applied on top
+    /// of whatever is in the DB at commit time
+    fn merge_variant(&mut self, cf_name : &'static str, key : Vec<u8>, operand : Vec<u8>) -> Result<(), String> {
+
+        //A scratch batch's entry must hold everything written on top of the committed entry, so it can replace
+        // the base batch's entry when it's merged
+        let pending_key = (cf_name, key);
+        if let Some(PendingEntry::Merge(operands)) = self.pending.get_mut(&pending_key) {
+            operands.push(operand);
+            return Ok(());
+        }
+        let merged_entry = match self.pending_entry(&pending_key) {
+            Some(PendingEntry::Put(existing_bytes)) => {
+                let merged_bytes = variant_merge_entries(Some(existing_bytes), [&operand[..]].into_iter())
+                    .ok_or_else(|| "malformed KeyGroupID vec in pending write".to_string())?;
+                PendingEntry::Put(merged_bytes)
+            },
+            Some(PendingEntry::Delete) => PendingEntry::Put(operand),
+            Some(PendingEntry::Merge(operands)) => {
+                let mut operands = operands.clone();
+                operands.push(operand);
+                PendingEntry::Merge(operands)
+            },
+            None => PendingEntry::Merge(vec![operand])
+        };
+        self.pending.insert(pending_key, merged_entry);
+        Ok(())
+    }
+
+    /// Returns the write to an entry in this batch, or in the closest batch it's composed on top of that wrote
+    /// the entry
+    fn pending_entry(&self, pending_key : &(&'static str, Vec<u8>)) -> Option<&PendingEntry> {
+        match self.pending.get(pending_key) {
+            Some(pending_entry) => Some(pending_entry),
+            None => self.base.as_ref().and_then(|base| base.pending_entry(pending_key))
+        }
+    }
+
+    /// Merges every scratch batch this batch is composed on top of, so only the writes remain
+    fn into_merged(mut self) -> Self {
+        while self.base.is_some() {
+            self = self.merge_scratch();
+        }
+        self
+    }
+}
+
+/// The bytes of a record's value, as they were read from the database but before they are decoded
+///
+/// The bytes don't depend on the type of the value, so they may be fetched on one thread and decoded on another.
+pub struct ValueBytes {
+    cf_name : &'static str,
+    record_id : RecordID,
+    bytes : Vec<u8>,
+}
+
+/// The entries of the "rec_data", "keys", and "values" column families, as found by [DBConnection::scan_entries]
+#[derive(Default)]
+pub struct EntryScan {
+    /// The key groups listed by each record's "rec_data" entry, which are empty for a deleted record
+    pub records : HashMap<RecordID, Vec<KeyGroupID>>,
+    /// Every key group with an entry in the "keys" CF, including the entries that can't be decoded
+    pub key_groups : HashSet<KeyGroupID>,
+    /// Every record with a value, whether the value is stored inline or in the "values" CF, including the values
+    /// that can't be decoded
+    pub values : HashSet<RecordID>,
+    /// The entries that can't be decoded, as the CF name, the key, and the error
+    pub corrupt_entries : Vec<(&'static str, Vec<u8>, String)>,
+}
+
+/// An iterator over the KeyGroupIDs in a variant entry that has been checked to be well-formed
+pub type VariantEntryIter<'a> = std::iter::Map<BinCodeVecIterator<'a, KeyGroupID>, fn(&[u8]) -> KeyGroupID>;
+
+/// The settings that affect how the database layer reads and writes entries
+/// 
+/// These are derived from the [TableConfig](crate::TableConfig) when a [Table](crate::Table) is opened
+#[derive(Clone, Copy, Default)]
+pub struct DBConfig {
+    /// If set, entries that can't be decoded will be moved into the "quarantine" CF when they are encountered
+    pub quarantine_corrupt_entries : bool,
+    /// The maximum number of KeyGroupIDs stored in a single physical variant entry, or 0 to never page
+    pub variant_page_size : usize,
+    /// If set, entries that are well-formed but semantically invalid are treated as corrupt
+    pub strict_decoding : bool,
+    /// The number of operands at which a merge of a variant entry is reported as an anomaly, or 0 to never report it
+    pub large_merge_operand_count : usize,
+    /// The maximum number of merge anomalies kept in the diagnostic log, or 0 to disable the log
+    pub merge_diagnostic_log_len : usize,
+    /// The maximum serialized size of a value that is stored inline in the "rec_data" CF, or 0 to never inline values
+    pub inline_value_max_size : usize,
+    /// The maximum number of files RocksDB keeps open, -1 to keep every file open, or 0 to use RocksDB's default
+    pub max_open_files : i32,
+    /// The number of bits used to shard RocksDB's table cache, or 0 to use RocksDB's default
+    pub table_cache_num_shard_bits : i32,
+    /// If set, a record whose "record_meta" entry was last modified longer ago than this is treated as missing
+    pub ttl : Option<Duration>,
+    /// If set, a CRC32 checksum is appended to each "keys", "rec_data", "values", and "blobs" entry, and checked
+    /// when the entry is read
+    pub checksums : bool,
+    /// The maximum size of a value stored in a single "values" entry.  Larger values are split into chunks of this
+    /// size in the "blobs" CF.  0 to never split values
+    pub blob_threshold : usize,
+    /// The tuning options passed through to RocksDB
+    pub rocks_config : RocksConfig,
+}
+
+impl DBConfig {
+
+    /// Returns the RocksDB options for the database itself, before any are specific to how it's opened
+    fn db_options(&self) -> rocksdb::Options {
+        let mut db_opts = rocksdb::Options::default();
+        if self.max_open_files != 0 {
+            db_opts.set_max_open_files(self.max_open_files);
+        }
+        if self.table_cache_num_shard_bits != 0 {
+            db_opts.set_table_cache_num_shard_bits(self.table_cache_num_shard_bits);
+        }
+        if self.rocks_config.max_background_jobs != 0 {
+            db_opts.set_max_background_jobs(self.rocks_config.max_background_jobs);
+        }
+        if self.rocks_config.statistics {
+            db_opts.enable_statistics();
+        }
+        db_opts
+    }
+
+    /// Returns the block cache shared by the column families, or `None` if each column family should have
+    /// RocksDB's default cache
+    fn block_cache(&self) -> Result<Option<rocksdb::Cache>, String> {
+        if self.rocks_config.block_cache_size == 0 {
+            return Ok(None);
+        }
+        Ok(Some(rocksdb::Cache::new_lru_cache(self.rocks_config.block_cache_size)?))
+    }
+
+    /// Returns an error if the `ttl` is zero, which would expire every record as soon as it's written
+    fn check_ttl(&self) -> Result<(), String> {
+        match self.ttl {
+            Some(ttl) if ttl.as_millis() == 0 => Err("ttl must be at least one millisecond".to_string()),
+            _ => Ok(())
+        }
+    }
+}
+
+/// The state shared by the [DBConnection]s of the tables in a [SharedDB]
+pub struct SharedDBState {
+    path : String,
+    rocks_config : RocksConfig,
+    merge_diagnostics : Arc<MergeDiagnosticsCollector>,
+    block_cache : Option<rocksdb::Cache>,
+    open_tables : Mutex<HashSet<String>>,
+    _lock : LockFile,
+}
+
+/// A database that holds the column families of several named tables, each prefixed with the table's name
+pub struct SharedDB {
+    db : Arc<DBWithThreadMode<MultiThreaded>>,
+    state : Arc<SharedDBState>, //NOTE: Declared after `db`, so the lock is released after the database is closed
+}
+
+impl SharedDB {
+
+    /// Opens the shared database at the specified path, creating it if necessary, along with the column families
+    /// of every table it holds
+    /// 
+    /// Takes the write lock, like [DBConnection::new], and returns an error if the database belongs to a single
+    /// table rather than being shared.
+    pub fn open(path : &str, rocks_config : RocksConfig) -> Result<Self, String> {
+
+        let native_path = native_path(path)?;
+        let lock = LockFile::acquire(&native_path)?;
+
+        let config = DBConfig{rocks_config, ..DBConfig::default()};
+        let mut db_opts = config.db_options();
+        db_opts.create_missing_column_families(true);
+        db_opts.create_if_missing(true);
+
+        //RocksDB must be given every existing column family when the database is opened, so each is opened with
+        // the options for the column family it is named after.  A new database has no column families to list
+        let cf_names = DB::list_cf(&db_opts, &native_path).unwrap_or_default();
+        if cf_names.iter().any(|cf_name| cf_name != rocksdb::DEFAULT_COLUMN_FAMILY_NAME && !cf_name.contains(SHARED_CF_SEPARATOR)) {
+            return Err(format!("database {} belongs to a single Table, so it can't be shared", path));
+        }
+
+        //NOTE: The tables' merge diagnostics come from the same collector, which only counts the merges
+        let merge_diagnostics = Arc::new(MergeDiagnosticsCollector::new(0, 0));
+        let block_cache = config.block_cache()?;
+        let cf_descriptors : Vec<ColumnFamilyDescriptor> = cf_names.iter()
+            .filter_map(|full_name| full_name.split_once(SHARED_CF_SEPARATOR).map(|(_table_name, cf_name)| (full_name, cf_name)))
+            .map(|(full_name, cf_name)| ColumnFamilyDescriptor::new(full_name, cf_options(&config, &merge_diagnostics, block_cache.as_ref(), cf_name)))
+            .collect();
+        let db = DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(&db_opts, &native_path, cf_descriptors)?;
+
+        Ok(Self{
+            db : Arc::new(db),
+            state : Arc::new(SharedDBState {
+                path : path.to_string(),
+                rocks_config,
+                merge_diagnostics,
+                block_cache,
+                open_tables : Mutex::new(HashSet::new()),
+                _lock : lock,
+            }),
+        })
+    }
+
+    /// Returns the path that the database was opened from
+    pub fn path(&self) -> &str {
+        &self.state.path
+    }
+
+    /// Returns the names of the tables in the database, in alphabetical order
+    pub fn table_names(&self) -> Result<Vec<String>, String> {
+        let cf_names = DB::list_cf(&rocksdb::Options::default(), native_path(&self.state.path)?)?;
+        let table_names : BTreeSet<String> = cf_names.iter()
+            .filter_map(|full_name| full_name.split_once(SHARED_CF_SEPARATOR).map(|(table_name, _cf_name)| table_name.to_string()))
+            .collect();
+        Ok(table_names.into_iter().collect())
+    }
+
+    /// Drops every column family of the named table, which must not be open.  Returns `false` if there was no
+    /// such table
+    pub fn drop_table(&self, name : &str) -> Result<bool, String> {
+
+        check_shared_table_name(name)?;
+
+        //The lock is held until the column families are gone, so the table can't be opened while they're dropped
+        let open_tables = self.state.open_tables.lock().unwrap();
+        if open_tables.contains(name) {
+            return Err(format!("table \"{}\" is open, so it can't be dropped", name));
+        }
+        let mut found = false;
+        for cf_name in COLUMN_FAMILY_NAMES {
+            let full_name = format!("{}{}{}", name, SHARED_CF_SEPARATOR, cf_name);
+            if self.db.cf_handle(&full_name).is_some() {
+                self.db.drop_cf(&full_name)?;
+                found = true;
+            }
+        }
+        drop(open_tables);
+        Ok(found)
+    }
+}
+
+/// Returns an error if `name` can't be used as the name of a table in a [SharedDB]
+fn check_shared_table_name(name : &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(SHARED_CF_SEPARATOR) {
+        return Err(format!("\"{}\" isn't a valid table name, which must be non-empty and can't contain '{}'", name, SHARED_CF_SEPARATOR));
+    }
+    Ok(())
+}
+
+/// The ways a [DBConnection] may be opened
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DBAccess {
+    /// The database is open for reading and writing, and holds the write lock
+    ReadWrite,
+    /// The database is open for reading, and sees the entries as they were when it was opened
+    ReadOnly,
+    /// The database is a secondary instance, that may catch up with the writes made by the primary
+    Secondary,
+}
+
+/// The variant references buffered in memory during a bulk load, in the order of the variants
+type BulkLoadVariants = BTreeMap<Vec<u8>, Vec<KeyGroupID>>;
 
 /// Encapsulates a connection to a database
 pub struct DBConnection {
-    db : DBWithThreadMode<rocksdb::SingleThreaded>,
+    db : Arc<DBWithThreadMode<MultiThreaded>>,
     path : String,
+    cf_prefix : String,
+    config : DBConfig,
+    access : DBAccess,
+    merge_diagnostics : Arc<MergeDiagnosticsCollector>,
+    pin_cache : PinCache,
+    tombstones : RwLock<HashSet<RecordID>>,
+    cipher : Option<Arc<dyn Cipher>>,
+    block_cache : Option<rocksdb::Cache>,
+    bulk_load : Mutex<Option<BulkLoadVariants>>,
+    shared : Option<Arc<SharedDBState>>, //NOTE: Declared after `db`, like `_lock`, because it holds the shared database's lock
+    _lock : Option<LockFile>, //NOTE: Declared after `db`, so the lock is released after the database is closed
 }
 
 impl DBConnection {
 
-    pub fn new(path : &str) -> Result<Self, String> {
-
-        //Configure the "keys" and "values" column families
-        let keys_cf = ColumnFamilyDescriptor::new(KEYS_CF_NAME, rocksdb::Options::default());
-        let rec_data_cf = ColumnFamilyDescriptor::new(RECORD_DATA_CF_NAME, rocksdb::Options::default());
-        let values_cf = ColumnFamilyDescriptor::new(VALUES_CF_NAME, rocksdb::Options::default());
+    /// Opens the database at the specified path
+    /// 
+    /// Returns an error naming the owning process if another DBConnection, in this process or any other, already
+    /// has the database open for writing.
+    pub fn new(path : &str, config : DBConfig) -> Result<Self, String> {
 
-        //Configure the "variants" column family
-        let mut variants_opts = rocksdb::Options::default();
-        variants_opts.create_if_missing(true);
-        variants_opts.set_merge_operator_associative("append to RecordID vec", variant_append_merge);
-        let variants_cf = ColumnFamilyDescriptor::new(VARIANTS_CF_NAME, variants_opts);
+        //Claim the database before RocksDB does, so we can report who owns it
+        let native_path = native_path(path)?;
+        let lock = LockFile::acquire(&native_path)?;
 
         //Configure the database itself
-        let mut db_opts = rocksdb::Options::default();
+        let mut db_opts = config.db_options();
         db_opts.create_missing_column_families(true);
         db_opts.create_if_missing(true);
 
         //Open the database
-        let db = DB::open_cf_descriptors(&db_opts, path, vec![keys_cf, rec_data_cf, values_cf, variants_cf])?;
+        config.check_ttl()?;
+        let merge_diagnostics = Arc::new(MergeDiagnosticsCollector::new(config.large_merge_operand_count, config.merge_diagnostic_log_len));
+        let block_cache = config.block_cache()?;
+        let cf_descriptors = column_family_descriptors(&config, &merge_diagnostics, block_cache.as_ref(), &COLUMN_FAMILY_NAMES);
+        let db = DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(&db_opts, &native_path, cf_descriptors)?;
+
+        let connection = Self{
+            db : Arc::new(db),
+            path : path.to_string(),
+            cf_prefix : String::new(),
+            config,
+            access : DBAccess::ReadWrite,
+            merge_diagnostics,
+            pin_cache : PinCache::new(),
+            tombstones : RwLock::new(HashSet::new()),
+            block_cache,
+            bulk_load : Mutex::new(None),
+            cipher : None,
+            shared : None,
+            _lock : Some(lock),
+        };
+        connection.load_tombstones()?;
+        Ok(connection)
+    }
 
-        Ok(Self{
-            db,
+    /// Opens an existing database at the specified path, without taking the write lock
+    /// 
+    /// Any number of processes may open the same database read-only, alongside at most one process that has it
+    /// open for writing.  Writes made by another process after the database is opened will not be visible.
+    /// Entries that can't be decoded are never quarantined, regardless of the config.
+    /// 
+    /// Only the named column families are opened, along with the [REQUIRED_COLUMN_FAMILY_NAMES].  Any access to
+    /// another column family returns an error.  If the `config` has a `ttl`, the "record_meta" column family is
+    /// opened too, so expired records can be recognized.  A column family that was added after the database was
+    /// created isn't opened until the database is opened for writing, which creates it.
+    pub fn open_read_only(path : &str, config : DBConfig, cf_names : &[&str]) -> Result<Self, String> {
+
+        config.check_ttl()?;
+        if let Some(cf_name) = cf_names.iter().find(|cf_name| !COLUMN_FAMILY_NAMES.contains(cf_name)) {
+            return Err(format!("unknown column family \"{}\"", cf_name));
+        }
+        let cf_names : Vec<&str> = COLUMN_FAMILY_NAMES.into_iter()
+            .filter(|cf_name| cf_names.contains(cf_name) || REQUIRED_COLUMN_FAMILY_NAMES.contains(cf_name)
+                || (config.ttl.is_some() && *cf_name == RECORD_META_CF_NAME))
+            .collect();
+
+        let db_opts = config.db_options();
+        let cf_names = existing_cf_names(&db_opts, path, &cf_names)?;
+
+        //NOTE: We use the descriptors, rather than `open_cf_for_read_only`, because the "variants" CF needs
+        // its merge operator to read entries that haven't been compacted
+        let merge_diagnostics = Arc::new(MergeDiagnosticsCollector::new(config.large_merge_operand_count, config.merge_diagnostic_log_len));
+        let block_cache = config.block_cache()?;
+        let db = DBWithThreadMode::<MultiThreaded>::open_cf_descriptors_read_only(&db_opts, native_path(path)?, column_family_descriptors(&config, &merge_diagnostics, block_cache.as_ref(), &cf_names), false)?;
+
+        let connection = Self{
+            db : Arc::new(db),
             path : path.to_string(),
-        })
+            cf_prefix : String::new(),
+            config : DBConfig {
+                quarantine_corrupt_entries : false,
+                ..config
+            },
+            access : DBAccess::ReadOnly,
+            merge_diagnostics,
+            pin_cache : PinCache::new(),
+            tombstones : RwLock::new(HashSet::new()),
+            block_cache,
+            bulk_load : Mutex::new(None),
+            cipher : None,
+            shared : None,
+            _lock : None,
+        };
+        connection.load_tombstones()?;
+        Ok(connection)
+    }
+
+    /// Opens the database at `primary_path` as a secondary instance, keeping its own logs at `secondary_path`
+    /// 
+    /// A secondary instance doesn't take the write lock, so any number of them may run alongside the process
+    /// that has the database open for writing.  Writes made by that process become visible after a call to
+    /// [catch_up_with_primary](Self::catch_up_with_primary).  Entries that can't be decoded are never quarantined,
+    /// regardless of the config, and `max_open_files` is ignored.
+    pub fn open_as_secondary(primary_path : &str, secondary_path : &str, config : DBConfig) -> Result<Self, String> {
+
+        config.check_ttl()?;
+
+        //A secondary instance must keep every file open, so the primary can't delete a file out from under it
+        let mut db_opts = config.db_options();
+        db_opts.set_max_open_files(-1);
+
+        //NOTE: We use the descriptors, rather than `open_cf_as_secondary`, for the same reason as in `open_read_only`
+        let merge_diagnostics = Arc::new(MergeDiagnosticsCollector::new(config.large_merge_operand_count, config.merge_diagnostic_log_len));
+        let block_cache = config.block_cache()?;
+        let cf_names = existing_cf_names(&db_opts, primary_path, &COLUMN_FAMILY_NAMES)?;
+        let db = DBWithThreadMode::<MultiThreaded>::open_cf_descriptors_as_secondary(&db_opts, native_path(primary_path)?, native_path(secondary_path)?, column_family_descriptors(&config, &merge_diagnostics, block_cache.as_ref(), &cf_names))?;
+
+        let connection = Self{
+            db : Arc::new(db),
+            path : primary_path.to_string(),
+            cf_prefix : String::new(),
+            config : DBConfig {
+                quarantine_corrupt_entries : false,
+                ..config
+            },
+            access : DBAccess::Secondary,
+            merge_diagnostics,
+            pin_cache : PinCache::new(),
+            tombstones : RwLock::new(HashSet::new()),
+            block_cache,
+            bulk_load : Mutex::new(None),
+            cipher : None,
+            shared : None,
+            _lock : None,
+        };
+        connection.load_tombstones()?;
+        Ok(connection)
+    }
+
+    /// Opens the named table in a [SharedDB], creating its column families if it's a new table
+    /// 
+    /// Only one DBConnection may have a given table open at a time.  The RocksDB options of the column families,
+    /// such as the block cache, are those the SharedDB was opened with, rather than those in `config`.
+    pub fn open_shared(shared : &SharedDB, name : &str, config : DBConfig) -> Result<Self, String> {
+
+        config.check_ttl()?;
+        check_shared_table_name(name)?;
+        if !shared.state.open_tables.lock().unwrap().insert(name.to_string()) {
+            return Err(format!("table \"{}\" is already open in shared database {}", name, shared.state.path));
+        }
+
+        //NOTE: From here on, dropping the connection releases the table's name, including on an error
+        let connection = Self{
+            db : shared.db.clone(),
+            path : shared.state.path.clone(),
+            cf_prefix : format!("{}{}", name, SHARED_CF_SEPARATOR),
+            config : DBConfig {
+                rocks_config : shared.state.rocks_config,
+                ..config
+            },
+            access : DBAccess::ReadWrite,
+            merge_diagnostics : shared.state.merge_diagnostics.clone(),
+            pin_cache : PinCache::new(),
+            tombstones : RwLock::new(HashSet::new()),
+            block_cache : shared.state.block_cache.clone(),
+            bulk_load : Mutex::new(None),
+            cipher : None,
+            shared : Some(shared.state.clone()),
+            _lock : None,
+        };
+        for cf_name in COLUMN_FAMILY_NAMES {
+            if connection.cf_handle(cf_name).is_err() {
+                connection.db.create_cf(connection.cf_full_name(cf_name), &connection.cf_options(cf_name))?;
+            }
+        }
+        connection.load_tombstones()?;
+        Ok(connection)
+    }
+
+    /// Writes everything held in memory out to the database files
+    /// 
+    /// A read-only or secondary database has nothing to flush, and is left as it was found.
+    pub fn flush(&self) -> Result<(), String> {
+        if self.access == DBAccess::ReadWrite {
+            self.db.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Syncs the write-ahead log to disk, and writes the memtables of every column family out to the database
+    /// files, so nothing needs to be replayed from the log when the database is next opened
+    /// 
+    /// A read-only or secondary database has nothing to flush, and is left as it was found.
+    pub fn flush_and_sync(&self) -> Result<(), String> {
+        if self.access != DBAccess::ReadWrite {
+            return Ok(());
+        }
+        self.db.flush_wal(true)?;
+        for cf_name in COLUMN_FAMILY_NAMES {
+            self.db.flush_cf(&self.cf_handle(cf_name)?)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a RocksDB checkpoint of the database in a new directory at `path`, which must not already exist
+    /// 
+    /// The checkpoint is a consistent copy of the database as of the moment it was taken, and writes may carry
+    /// on while it is created.  Where possible, the database files are hard-linked rather than copied, so a
+    /// checkpoint on the same file system is fast, and takes little space until the database moves on.
+    pub fn create_checkpoint(&self, path : &str) -> Result<(), String> {
+        let checkpoint = rocksdb::checkpoint::Checkpoint::new(&self.db)?;
+        checkpoint.create_checkpoint(native_path(path)?)?;
+        Ok(())
+    }
+
+    /// Writes the entries of each column family, as of a single snapshot, to an SST file in `dir` named after
+    /// the column family.  Column families with no entries are skipped.  Returns the number of files written
+    pub fn export_sst_files(&self, dir : &str) -> Result<usize, String> {
+
+        let snapshot = self.db.snapshot();
+        let sst_opts = rocksdb::Options::default();
+        let mut file_count = 0;
+        for cf_name in COLUMN_FAMILY_NAMES {
+            let cf_handle = &self.cf_handle(cf_name)?;
+            let mut entries = snapshot.iterator_cf(cf_handle, IteratorMode::Start).peekable();
+            if entries.peek().is_none() {
+                continue;
+            }
+
+            //NOTE: The iterator returns the entries in key order, which is the order the SstFileWriter needs
+            let mut writer = rocksdb::SstFileWriter::create(&sst_opts);
+            writer.open(sst_file_path(dir, cf_name))?;
+            for (key, entry) in entries {
+                writer.put(key, entry)?;
+            }
+            writer.finish()?;
+            file_count += 1;
+        }
+
+        Ok(file_count)
+    }
+
+    /// Ingests the SST files written by [export_sst_files](Self::export_sst_files) from `dir` into their column
+    /// families.  Returns the number of files ingested
+    pub fn ingest_sst_files(&self, dir : &str) -> Result<usize, String> {
+
+        let mut file_count = 0;
+        for cf_name in COLUMN_FAMILY_NAMES {
+            let path = sst_file_path(dir, cf_name);
+            if path.exists() {
+                self.db.ingest_external_file_cf(&self.cf_handle(cf_name)?, vec![path])?;
+                file_count += 1;
+            }
+        }
+
+        //The ingested files may have brought tombstones with them
+        self.load_tombstones()?;
+
+        Ok(file_count)
+    }
+
+    /// Compacts the entries of a column family with keys from `start` up to `end`, or to either end of the
+    /// column family if they are `None`, and waits for the compaction to finish
+    pub fn compact_range(&self, cf_name : &str, start : Option<&[u8]>, end : Option<&[u8]>) -> Result<(), String> {
+        self.db.compact_range_cf(&self.cf_handle(cf_name)?, start, end);
+        Ok(())
+    }
+
+    /// Begins a bulk load, during which automatic compactions are stopped, writes skip the write-ahead log,
+    /// and new references to unpaged variant entries are held in memory rather than merged into the "variants" CF
+    pub fn begin_bulk_load(&self) -> Result<(), String> {
+
+        let mut bulk_load = self.bulk_load.lock().unwrap();
+        if bulk_load.is_some() {
+            return Err("a bulk load is already in progress".to_string());
+        }
+        for cf_name in COLUMN_FAMILY_NAMES {
+            self.db.set_options_cf(&self.cf_handle(cf_name)?, &[("disable_auto_compactions", "true")])?;
+        }
+        *bulk_load = Some(BTreeMap::new());
+        Ok(())
+    }
+
+    /// Returns `true` if a bulk load is in progress
+    pub fn is_bulk_loading(&self) -> bool {
+        self.bulk_load.lock().unwrap().is_some()
+    }
+
+    /// Finishes a bulk load, writing the buffered variant references to the "variants" CF in key order,
+    /// flushing everything written during the bulk load to the database files, and restarting automatic
+    /// compactions.  Returns the number of variant entries written
+    pub fn finish_bulk_load(&self) -> Result<usize, String> {
+
+        //NOTE: Each batch holds a contiguous run of variants, so the SST files they are flushed into don't overlap
+        const BULK_LOAD_BATCH_LEN : usize = 65536;
+
+        let bulk_load_variants = self.bulk_load.lock().unwrap().take()
+            .ok_or_else(|| "there is no bulk load in progress".to_string())?;
+        let variants_cf_handle = &self.cf_handle(VARIANTS_CF_NAME)?;
+        let vec_coder = bincode::DefaultOptions::new().with_fixint_encoding().with_little_endian();
+        let mut variant_count = 0;
+        let mut batch = WriteBatch::default();
+        for (variant, key_groups) in bulk_load_variants {
+            if key_groups.is_empty() {
+                continue;
+            }
+            batch.merge_cf(variants_cf_handle, &variant, vec_coder.serialize(&key_groups).unwrap());
+            variant_count += 1;
+            if batch.len() >= BULK_LOAD_BATCH_LEN {
+                self.db.write(std::mem::take(&mut batch))?;
+            }
+        }
+        self.db.write(batch)?;
+
+        self.flush_and_sync()?;
+        for cf_name in COLUMN_FAMILY_NAMES {
+            self.db.set_options_cf(&self.cf_handle(cf_name)?, &[("disable_auto_compactions", "false")])?;
+        }
+        Ok(variant_count)
+    }
+
+    /// Adds a backup of the database to the BackupEngine.  The memtables are flushed first, so the backup
+    /// doesn't depend on the write-ahead log
+    pub fn create_engine_backup(&self, engine : &mut rocksdb::backup::BackupEngine) -> Result<(), String> {
+        engine.create_new_backup_flush(&self.db, true)?;
+        Ok(())
+    }
+
+    /// Applies the writes the primary instance has made since the database was opened, or since the last
+    /// call to this function.  Returns an error if the database wasn't opened as a secondary instance
+    pub fn catch_up_with_primary(&self) -> Result<(), String> {
+        if self.access != DBAccess::Secondary {
+            return Err("database was not opened as a secondary instance".to_string());
+        }
+        self.db.try_catch_up_with_primary()?;
+
+        //The primary may have changed the pinned records, or their entries, or the tombstones
+        self.pin_cache.clear();
+        self.load_pinned_records()?;
+        self.load_tombstones()
+    }
+
+    /// Takes a snapshot of the database, for reads that must be consistent with each other while writes continue
+    pub fn snapshot(&self) -> DBSnapshot<'_> {
+        DBSnapshot{db : self, snapshot : self.db.snapshot()}
+    }
+
+    /// Returns the handle to a column family, or an error if the column family wasn't opened
+    /// Returns the options a column family is created with
+    fn cf_options(&self, cf_name : &str) -> rocksdb::Options {
+        cf_options(&self.config, &self.merge_diagnostics, self.block_cache.as_ref(), cf_name)
+    }
+
+    fn cf_handle(&self, cf_name : &str) -> Result<Arc<BoundColumnFamily<'_>>, String> {
+        self.db.cf_handle(&self.cf_full_name(cf_name)).ok_or_else(|| format!("the \"{}\" column family isn't open", cf_name))
+    }
+
+    /// Returns the name RocksDB knows a column family by, which carries the table's name as a prefix if the
+    /// database is shared by several tables
+    fn cf_full_name<'a>(&self, cf_name : &'a str) -> Cow<'a, str> {
+        if self.cf_prefix.is_empty() {
+            Cow::Borrowed(cf_name)
+        } else {
+            Cow::Owned(format!("{}{}", self.cf_prefix, cf_name))
+        }
+    }
+
+    /// Returns `true` if the database was opened for writing, rather than read-only or as a secondary instance
+    pub fn is_writable(&self) -> bool {
+        self.access == DBAccess::ReadWrite
+    }
+
+    /// Returns `true` if the database is a [SharedDB], which holds other tables too
+    pub fn is_shared(&self) -> bool {
+        self.shared.is_some()
+    }
+
+    /// Returns `true` if the database was opened with a TTL, so its records may expire
+    pub fn has_ttl(&self) -> bool {
+        self.config.ttl.is_some()
+    }
+
+    /// Returns `true` if the database has a TTL, and the record's "record_meta" entry was last modified longer ago
+    /// than the TTL.  A record without a "record_meta" entry never expires
+    pub fn is_expired(&self, record_id : RecordID) -> Result<bool, String> {
+        match self.config.ttl {
+            Some(ttl) => Ok(self.get_record_metadata(record_id)?
+                .map_or(false, |metadata| metadata.modified.saturating_add(ttl.as_millis() as u64) <= now_millis())),
+            None => Ok(false)
+        }
+    }
+
+    /// Removes the records that have expired from a list of RecordIDs.  See [is_expired](Self::is_expired)
+    pub fn retain_unexpired(&self, record_ids : &mut Vec<RecordID>) -> Result<(), String> {
+        if self.has_ttl() {
+            let mut unexpired = Vec::with_capacity(record_ids.len());
+            for record_id in record_ids.drain(..) {
+                if !self.is_expired(record_id)? {
+                    unexpired.push(record_id);
+                }
+            }
+            *record_ids = unexpired;
+        }
+        Ok(())
+    }
+
+    /// Returns the path that the database was opened from
+    pub fn path(&self) -> &str {
+        &self.path
     }
 
     ///Deletes all entries associated with a database and resets it to a fresh state
     pub fn reset_database(&mut self) -> Result<(), String> {
 
         //Drop all the existing column families
-        self.db.drop_cf(KEYS_CF_NAME)?;
-        self.db.drop_cf(RECORD_DATA_CF_NAME)?;
-        self.db.drop_cf(VALUES_CF_NAME)?;
-        self.db.drop_cf(VARIANTS_CF_NAME)?;
-
-        //Recreate the "keys", "rec_data", and "values" column families
-        self.db.create_cf(KEYS_CF_NAME, &rocksdb::Options::default())?;
-        self.db.create_cf(RECORD_DATA_CF_NAME, &rocksdb::Options::default())?;
-        self.db.create_cf(VALUES_CF_NAME, &rocksdb::Options::default())?;
+        self.pin_cache.clear();
+        self.db.drop_cf(&self.cf_full_name(KEYS_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(RECORD_DATA_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(VALUES_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(VARIANTS_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(QUARANTINE_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(STATS_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(VARIANT_PAGES_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(METADATA_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(INJECTED_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(EXACT_KEYS_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(PHONETIC_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(NGRAMS_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(TOKENS_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(SYNONYMS_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(RECORD_META_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(TOMBSTONES_CF_NAME))?;
+        self.db.drop_cf(&self.cf_full_name(BLOBS_CF_NAME))?;
+        self.tombstones.get_mut().unwrap().clear();
+
+        //Recreate the "keys", "rec_data", "values", "quarantine", "stats", "variant_pages", "metadata", "injected", "exact_keys", "phonetic", "ngrams", "tokens", "synonyms", "record_meta", "tombstones", and "blobs" column families
+        self.db.create_cf(self.cf_full_name(KEYS_CF_NAME), &self.cf_options(KEYS_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(RECORD_DATA_CF_NAME), &self.cf_options(RECORD_DATA_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(VALUES_CF_NAME), &self.cf_options(VALUES_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(QUARANTINE_CF_NAME), &self.cf_options(QUARANTINE_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(STATS_CF_NAME), &self.cf_options(STATS_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(VARIANT_PAGES_CF_NAME), &self.cf_options(VARIANT_PAGES_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(METADATA_CF_NAME), &self.cf_options(METADATA_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(INJECTED_CF_NAME), &self.cf_options(INJECTED_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(EXACT_KEYS_CF_NAME), &self.cf_options(EXACT_KEYS_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(PHONETIC_CF_NAME), &self.cf_options(PHONETIC_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(NGRAMS_CF_NAME), &self.cf_options(NGRAMS_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(TOKENS_CF_NAME), &self.cf_options(TOKENS_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(SYNONYMS_CF_NAME), &self.cf_options(SYNONYMS_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(RECORD_META_CF_NAME), &self.cf_options(RECORD_META_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(TOMBSTONES_CF_NAME), &self.cf_options(TOMBSTONES_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(BLOBS_CF_NAME), &self.cf_options(BLOBS_CF_NAME))?;
         
         //Recreate the "variants" column family
-        let mut variants_opts = rocksdb::Options::default();
-        variants_opts.create_if_missing(true);
-        variants_opts.set_merge_operator_associative("append to RecordID vec", variant_append_merge);
-        self.db.create_cf(VARIANTS_CF_NAME, &variants_opts)?;
+        self.db.create_cf(self.cf_full_name(VARIANTS_CF_NAME), &self.cf_options(VARIANTS_CF_NAME))?;
 
         Ok(())
     }
 
-    ///Returns the number of record entries in the database, by probing the entries in the
-    /// "rec_data" column family
-    /// 
-    ///NOTE: this is not a simple lookup, and is designed to be called when loading a new table, not
-    /// as a simple accessor
-    pub fn record_count(&self) -> Result<usize, String> {
-        let rec_data_cf_handle = self.db.cf_handle(RECORD_DATA_CF_NAME).unwrap();
-        let record_count = probe_for_max_sequential_key(&self.db, rec_data_cf_handle, 255)?;
-        Ok(record_count)
+    /// Composes the error for an entry that couldn't be decoded, and moves the entry into the quarantine
+    /// if the database is configured to do that
+    fn corrupt_entry_error<E : core::fmt::Display>(&self, cf_name : &'static str, key : &[u8], err : E) -> String {
+
+        let message = format!("corrupt entry in \"{}\" CF, key {:?}: {}", cf_name, key, err);
+        if self.config.quarantine_corrupt_entries {
+            match self.quarantine_entry(cf_name, key) {
+                Ok(()) => format!("{}, entry moved to quarantine", message),
+                Err(quarantine_err) => format!("{}, failed to quarantine entry: {}", message, quarantine_err),
+            }
+        } else {
+            message
+        }
     }
 
-    /// Returns an iterator for every key group associated with a specified record
-    /// 
-    /// Internal FuzzyRocks interface, but exported outside the key_groups module
-    #[inline(always)]
-    pub fn get_record_key_groups(&self, record_id : RecordID) -> Result<impl Iterator<Item=KeyGroupID>, String> {
+    /// Appends the checksum to the bytes of a "keys", "rec_data", or "values" entry, if the database is configured
+    /// with checksums
+    fn seal_entry(&self, mut entry_bytes : Vec<u8>) -> Vec<u8> {
+        if self.config.checksums {
+            let checksum = crc32fast::hash(&entry_bytes);
+            entry_bytes.extend_from_slice(&checksum.to_le_bytes());
+        }
+        entry_bytes
+    }
 
-        let rec_data_cf_handle = self.db.cf_handle(RECORD_DATA_CF_NAME).unwrap();
-        if let Some(rec_data_vec_bytes) = self.db.get_pinned_cf(rec_data_cf_handle, record_id.to_le_bytes())? {
-            let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
-            let rec_data : RecordData = record_coder.deserialize(&rec_data_vec_bytes).unwrap();
+    /// Checks the checksum at the end of a "keys", "rec_data", or "values" entry, if the database is configured
+    /// with checksums, and returns the length of the entry without it
+    fn check_entry(&self, cf_name : &'static str, key : &[u8], record_id : RecordID, entry_bytes : &[u8]) -> Result<usize, String> {
+        if !self.config.checksums {
+            return Ok(entry_bytes.len());
+        }
+        checked_entry_len(entry_bytes)
+            .ok_or_else(|| self.corrupt_entry_error(cf_name, key, format!("Corruption: checksum mismatch in the entry for record {}", record_id)))
+    }
+
+    /// Sets the cipher used to encrypt the database's entries
+    ///
+    /// NOTE: This must be called before any entries are read or written
+    pub fn set_cipher(&mut self, cipher : Arc<dyn Cipher>) {
+        self.cipher = Some(cipher);
+    }
+
+    /// Returns the cipher used to encrypt the database's entries, if there is one
+    pub fn cipher(&self) -> Option<&dyn Cipher> {
+        self.cipher.as_deref()
+    }
+
+    /// Returns the cipher used to encrypt keys, if the database's keys are encrypted
+    fn keys_cipher(&self) -> Option<&dyn Cipher> {
+        self.cipher.as_deref().filter(|cipher| cipher.encrypts_keys())
+    }
+
+    /// Encrypts the bytes of an entry with the cipher supplied, if there is one
+    fn encrypt_entry(cipher : Option<&dyn Cipher>, entry_bytes : Vec<u8>) -> Vec<u8> {
+        match cipher {
+            Some(cipher) => cipher.encrypt(&entry_bytes),
+            None => entry_bytes
+        }
+    }
+
+    /// Decrypts the bytes of an entry with the cipher supplied, if there is one
+    fn decrypt_entry<'b>(&self, cipher : Option<&dyn Cipher>, cf_name : &'static str, key : &[u8], entry_bytes : &'b [u8]) -> Result<Cow<'b, [u8]>, String> {
+        match cipher {
+            Some(cipher) => cipher.decrypt(entry_bytes).map(Cow::Owned)
+                .map_err(|err| self.corrupt_entry_error(cf_name, key, format!("failed to decrypt entry: {}", err))),
+            None => Ok(Cow::Borrowed(entry_bytes))
+        }
+    }
 
-            if !rec_data.key_groups.is_empty() {
-                Ok(rec_data.key_groups.into_iter().map(move |group_idx| KeyGroupID::from_record_and_idx(record_id, group_idx)))
+    /// Returns the key an index entry is stored under, which is the supplied key encrypted deterministically if the
+    /// database's keys are encrypted
+    fn index_key<'b>(&self, key : &'b [u8]) -> Cow<'b, [u8]> {
+        match self.keys_cipher() {
+            Some(cipher) => Cow::Owned(cipher.encrypt_deterministic(key)),
+            None => Cow::Borrowed(key)
+        }
+    }
+
+    /// Moves the raw bytes of an entry into the "quarantine" CF, recording the original CF and key
+    /// 
+    /// NOTE: A quarantined "rec_data" entry is replaced by the empty sentinel used for deleted records, rather
+    /// than being removed, because we assume there are no gaps in the RecordIDs
+    pub fn quarantine_entry(&self, cf_name : &'static str, key : &[u8]) -> Result<(), String> {
+
+        let cf_handle = &self.cf_handle(cf_name)?;
+        if let Some(entry_bytes) = self.db.get_cf(cf_handle, key)? {
+
+            let mut batch = DBWriteBatch::new();
+            batch.put(QUARANTINE_CF_NAME, &QuarantinedEntry::quarantine_key(cf_name, key), entry_bytes);
+            if cf_name == RECORD_DATA_CF_NAME {
+                let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+                batch.put(cf_name, key, self.seal_entry(record_coder.serialize(&RecordData::new(&[])).unwrap()));
             } else {
-                Err("Invalid record_id".to_string())
+                batch.delete(cf_name, key);
+            }
+            self.commit_batch(batch)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns all of the entries in the "quarantine" CF
+    pub fn quarantined_entries(&self) -> Result<Vec<QuarantinedEntry>, String> {
+
+        let quarantine_cf_handle = &self.cf_handle(QUARANTINE_CF_NAME)?;
+        let entries = self.db.iterator_cf(quarantine_cf_handle, IteratorMode::Start)
+            .filter_map(|(quarantine_key, value)| QuarantinedEntry::from_quarantine_entry(&quarantine_key, &value))
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Deletes all of the entries in the "quarantine" CF
+    pub fn clear_quarantine(&mut self) -> Result<(), String> {
+        self.db.drop_cf(&self.cf_full_name(QUARANTINE_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(QUARANTINE_CF_NAME), &self.cf_options(QUARANTINE_CF_NAME))?;
+        Ok(())
+    }
+
+    /// Returns the counts and log of the merge operator invocations since the database was opened, or since
+    /// the last reset
+    pub fn merge_diagnostics(&self) -> MergeDiagnostics {
+        self.merge_diagnostics.get()
+    }
+
+    /// Clears the merge operator counts and log
+    pub fn reset_merge_diagnostics(&self) {
+        self.merge_diagnostics.reset()
+    }
+
+    /// Returns the statistics RocksDB has collected since the database was opened, or `None` if the database
+    /// wasn't opened with statistics enabled
+    pub fn statistics(&self) -> Result<Option<DBStatistics>, String> {
+        if !self.config.rocks_config.statistics {
+            return Ok(None);
+        }
+        Ok(self.db.property_value("rocksdb.options-statistics")?.map(DBStatistics::parse))
+    }
+
+    /// Returns the space taken up by each open column family, and by the database directory as a whole
+    pub fn disk_usage(&self) -> Result<DiskUsage, String> {
+
+        let mut column_families = vec![];
+        for cf_name in COLUMN_FAMILY_NAMES {
+            if self.cf_handle(cf_name).is_err() {
+                continue;
+            }
+            column_families.push(CFDiskUsage {
+                name : cf_name,
+                sst_files_size : self.cf_int_property(cf_name, "rocksdb.total-sst-files-size")?,
+                estimated_live_data_size : self.cf_int_property(cf_name, "rocksdb.estimate-live-data-size")?,
+                memtables_size : self.cf_int_property(cf_name, "rocksdb.size-all-mem-tables")?,
+            });
+        }
+
+        let mut total_size = 0;
+        let dir = std::fs::read_dir(native_path(&self.path)?).map_err(|err| format!("couldn't read database directory {}: {}", self.path, err))?;
+        for dir_entry in dir {
+            let metadata = dir_entry.and_then(|dir_entry| dir_entry.metadata()).map_err(|err| format!("couldn't read database directory {}: {}", self.path, err))?;
+            if metadata.is_file() {
+                total_size += metadata.len();
+            }
+        }
+
+        Ok(DiskUsage{column_families, total_size})
+    }
+
+    /// Returns a selection of the properties RocksDB reports about the database and each open column family
+    pub fn properties(&self) -> Result<DBProperties, String> {
+
+        let mut column_families = vec![];
+        for cf_name in COLUMN_FAMILY_NAMES {
+            if self.cf_handle(cf_name).is_err() {
+                continue;
+            }
+            column_families.push(CFProperties {
+                name : cf_name,
+                estimate_num_keys : self.cf_int_property(cf_name, "rocksdb.estimate-num-keys")?,
+                cur_size_active_mem_table : self.cf_int_property(cf_name, "rocksdb.cur-size-active-mem-table")?,
+                size_all_mem_tables : self.cf_int_property(cf_name, "rocksdb.size-all-mem-tables")?,
+                num_immutable_mem_table : self.cf_int_property(cf_name, "rocksdb.num-immutable-mem-table")?,
+                estimate_pending_compaction_bytes : self.cf_int_property(cf_name, "rocksdb.estimate-pending-compaction-bytes")?,
+            });
+        }
+
+        Ok(DBProperties {
+            column_families,
+            num_running_compactions : self.db.property_int_value("rocksdb.num-running-compactions")?.unwrap_or(0),
+            num_running_flushes : self.db.property_int_value("rocksdb.num-running-flushes")?.unwrap_or(0),
+            background_errors : self.db.property_int_value("rocksdb.background-errors")?.unwrap_or(0),
+        })
+    }
+
+    /// Returns the value of an integer RocksDB property of the named column family, or 0 if RocksDB doesn't
+    /// report it
+    fn cf_int_property(&self, cf_name : &str, property_name : &str) -> Result<u64, String> {
+        Ok(self.db.property_int_value_cf(&self.cf_handle(cf_name)?, property_name)?.unwrap_or(0))
+    }
+
+    /// Returns the entry in the "metadata" CF with the specified name, or `None` if there is no such entry
+    pub fn get_metadata<T : serde::de::DeserializeOwned>(&self, name : &str) -> Result<Option<T>, String> {
+
+        let metadata_cf_handle = &self.cf_handle(METADATA_CF_NAME)?;
+        match self.db.get_pinned_cf(metadata_cf_handle, name.as_bytes())? {
+            Some(bytes) => {
+                let value = bincode::deserialize(&bytes)
+                    .map_err(|err| self.corrupt_entry_error(METADATA_CF_NAME, name.as_bytes(), err))?;
+                Ok(Some(value))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Stores an entry in the "metadata" CF with the specified name, replacing any existing entry
+    pub fn put_metadata<T : Serialize>(&self, name : &str, value : &T) -> Result<(), String> {
+
+        let metadata_cf_handle = &self.cf_handle(METADATA_CF_NAME)?;
+        let bytes = bincode::serialize(value).map_err(|err| err.to_string())?;
+        self.db.put_cf(metadata_cf_handle, name.as_bytes(), bytes)?;
+        Ok(())
+    }
+
+    /// Puts an entry in the "metadata" CF with the specified name in the batch, replacing any existing entry
+    pub fn put_metadata_in_batch<T : Serialize>(&self, batch : &mut DBWriteBatch, name : &str, value : &T) -> Result<(), String> {
+
+        let bytes = bincode::serialize(value).map_err(|err| err.to_string())?;
+        batch.put(METADATA_CF_NAME, name.as_bytes(), bytes);
+        Ok(())
+    }
+
+    /// Returns every entry in a CF, as raw bytes
+    ///
+    /// NOTE: This reads the whole CF into memory, so it is intended for migrating a database between formats
+    #[allow(dead_code)] //NOTE: Only used by migration steps, and there are none until the format changes
+    pub fn raw_entries(&self, cf_name : &'static str) -> Result<Vec<RawEntry>, String> {
+        let cf_handle = &self.cf_handle(cf_name)?;
+        Ok(self.db.iterator_cf(cf_handle, IteratorMode::Start).map(|(key, value)| (key.to_vec(), value.to_vec())).collect())
+    }
+
+    /// Puts an entry in a CF in the batch, as raw bytes
+    #[allow(dead_code)]
+    pub fn put_raw(&self, batch : &mut DBWriteBatch, cf_name : &'static str, key : &[u8], value : Vec<u8>) -> Result<(), String> {
+        batch.put(cf_name, key, value);
+        Ok(())
+    }
+
+    /// Deletes an entry from a CF in the batch
+    #[allow(dead_code)]
+    pub fn delete_raw(&self, batch : &mut DBWriteBatch, cf_name : &'static str, key : &[u8]) -> Result<(), String> {
+        batch.delete(cf_name, key);
+        Ok(())
+    }
+
+    /// Adds the supplied hit counts to the records' entries in the "stats" CF
+    pub fn add_access_counts(&self, counts : &HashMap<RecordID, u64>) -> Result<(), String> {
+
+        let stats_cf_handle = &self.cf_handle(STATS_CF_NAME)?;
+        let mut batch = WriteBatch::default();
+        for (record_id, count) in counts.iter() {
+            batch.merge_cf(stats_cf_handle, record_id.to_le_bytes(), count.to_le_bytes());
+        }
+        self.db.write(batch)?;
+
+        Ok(())
+    }
+
+    /// Returns the hit count stored in the "stats" CF for a record
+    pub fn get_access_count(&self, record_id : RecordID) -> Result<u64, String> {
+
+        let stats_cf_handle = &self.cf_handle(STATS_CF_NAME)?;
+        match self.db.get_pinned_cf(stats_cf_handle, record_id.to_le_bytes())? {
+            Some(count_bytes) => self.checked_access_count(record_id, &count_bytes),
+            None => Ok(0)
+        }
+    }
+
+    /// Returns every record with a hit count stored in the "stats" CF, along with the count
+    pub fn access_counts(&self) -> Result<Vec<(RecordID, u64)>, String> {
+
+        let stats_cf_handle = &self.cf_handle(STATS_CF_NAME)?;
+        self.db.iterator_cf(stats_cf_handle, IteratorMode::Start)
+            .map(|(record_id_bytes, count_bytes)| {
+                let record_id_bytes : [u8; 8] = record_id_bytes.as_ref().try_into()
+                    .map_err(|err| self.corrupt_entry_error(STATS_CF_NAME, &record_id_bytes, err))?;
+                let record_id = RecordID::from(usize::from_le_bytes(record_id_bytes));
+                Ok((record_id, self.checked_access_count(record_id, &count_bytes)?))
+            })
+            .collect()
+    }
+
+    /// Decodes a hit count from the "stats" CF.  A malformed count is read as zero, unless strict decoding
+    /// is enabled
+    fn checked_access_count(&self, record_id : RecordID, count_bytes : &[u8]) -> Result<u64, String> {
+        if self.config.strict_decoding && count_bytes.len() != 8 {
+            return Err(self.corrupt_entry_error(STATS_CF_NAME, &record_id.to_le_bytes(), format!("hit count is {} bytes, expected 8", count_bytes.len())));
+        }
+        Ok(decode_access_count(count_bytes))
+    }
+
+    /// Deletes a record's entry in the "stats" CF
+    pub fn delete_access_count(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+
+        batch.delete(STATS_CF_NAME, &record_id.to_le_bytes());
+
+        Ok(())
+    }
+
+    /// Deletes all of the entries in the "stats" CF
+    pub fn clear_access_counts(&mut self) -> Result<(), String> {
+        self.db.drop_cf(&self.cf_full_name(STATS_CF_NAME))?;
+        self.db.create_cf(self.cf_full_name(STATS_CF_NAME), &self.cf_options(STATS_CF_NAME))?;
+        Ok(())
+    }
+
+    /// Returns a record's entry in the "record_meta" CF, if it has one
+    pub fn get_record_metadata(&self, record_id : RecordID) -> Result<Option<RecordMetadata>, String> {
+
+        let cf_handle = &self.cf_handle(RECORD_META_CF_NAME)?;
+        match self.db.get_pinned_cf(cf_handle, record_id.to_le_bytes())? {
+            Some(metadata_bytes) => Ok(Some(self.decode_record_metadata(&record_id.to_le_bytes(), &metadata_bytes)?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns a record's entry in the "record_meta" CF, including any changes in the batch
+    pub fn get_record_metadata_in_batch(&self, batch : &DBWriteBatch, record_id : RecordID) -> Result<Option<RecordMetadata>, String> {
+        match self.get_cf_in_batch(batch, RECORD_META_CF_NAME, &record_id.to_le_bytes())? {
+            Some(metadata_bytes) => Ok(Some(self.decode_record_metadata(&record_id.to_le_bytes(), &metadata_bytes)?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns every entry in the "record_meta" CF, in order of RecordID
+    pub fn all_record_metadata(&self) -> Result<Vec<(RecordID, RecordMetadata)>, String> {
+
+        let cf_handle = &self.cf_handle(RECORD_META_CF_NAME)?;
+        self.db.iterator_cf(cf_handle, IteratorMode::Start)
+            .map(|(record_id_bytes, metadata_bytes)| {
+                let record_id_array : [u8; 8] = record_id_bytes.as_ref().try_into()
+                    .map_err(|err| self.corrupt_entry_error(RECORD_META_CF_NAME, &record_id_bytes, err))?;
+                Ok((RecordID::from(usize::from_le_bytes(record_id_array)), self.decode_record_metadata(&record_id_bytes, &metadata_bytes)?))
+            })
+            .collect()
+    }
+
+    /// Replaces a record's entry in the "record_meta" CF
+    pub fn put_record_metadata(&self, batch : &mut DBWriteBatch, record_id : RecordID, metadata : &RecordMetadata) -> Result<(), String> {
+
+        batch.put(RECORD_META_CF_NAME, &record_id.to_le_bytes(), bincode::serialize(metadata).unwrap());
+
+        Ok(())
+    }
+
+    /// Deletes a record's entry in the "record_meta" CF
+    pub fn delete_record_metadata(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+
+        batch.delete(RECORD_META_CF_NAME, &record_id.to_le_bytes());
+
+        Ok(())
+    }
+
+    fn decode_record_metadata(&self, key : &[u8], metadata_bytes : &[u8]) -> Result<RecordMetadata, String> {
+        bincode::deserialize(metadata_bytes)
+            .map_err(|err| self.corrupt_entry_error(RECORD_META_CF_NAME, key, err))
+    }
+
+    /// Returns `true` if the record has a tombstone in the "tombstones" CF
+    pub fn is_tombstoned(&self, record_id : RecordID) -> bool {
+        let tombstones = self.tombstones.read().unwrap();
+        !tombstones.is_empty() && tombstones.contains(&record_id)
+    }
+
+    /// Returns `true` if any record has a tombstone
+    pub fn has_tombstones(&self) -> bool {
+        !self.tombstones.read().unwrap().is_empty()
+    }
+
+    /// Returns the number of records with a tombstone
+    pub fn tombstone_count(&self) -> usize {
+        self.tombstones.read().unwrap().len()
+    }
+
+    /// Returns the RecordIDs of every record with a tombstone, in ascending order
+    pub fn tombstoned_records(&self) -> Vec<RecordID> {
+        let mut record_ids : Vec<RecordID> = self.tombstones.read().unwrap().iter().copied().collect();
+        record_ids.sort();
+        record_ids
+    }
+
+    /// Puts a tombstone for the record in the "tombstones" CF
+    pub fn put_tombstone(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+
+        batch.put(TOMBSTONES_CF_NAME, &record_id.to_le_bytes(), vec![]);
+
+        Ok(())
+    }
+
+    /// Deletes the record's tombstone from the "tombstones" CF, if it has one
+    pub fn delete_tombstone(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+
+        batch.delete(TOMBSTONES_CF_NAME, &record_id.to_le_bytes());
+
+        Ok(())
+    }
+
+    /// Replaces the tombstones held in memory with the entries in the "tombstones" CF
+    fn load_tombstones(&self) -> Result<(), String> {
+
+        //NOTE: A database created before soft deletes were added has no "tombstones" CF until it's next opened
+        // for writing, so a read-only or secondary connection treats the missing CF as empty
+        let cf_handle = &match self.cf_handle(TOMBSTONES_CF_NAME) {
+            Ok(cf_handle) => cf_handle,
+            Err(_) if self.access != DBAccess::ReadWrite => {
+                self.tombstones.write().unwrap().clear();
+                return Ok(());
+            },
+            Err(err) => return Err(err)
+        };
+        let tombstones = self.db.iterator_cf(cf_handle, IteratorMode::Start)
+            .map(|(record_id_bytes, _)| {
+                let record_id_array : [u8; 8] = record_id_bytes.as_ref().try_into()
+                    .map_err(|err| self.corrupt_entry_error(TOMBSTONES_CF_NAME, &record_id_bytes, err))?;
+                Ok(RecordID::from(usize::from_le_bytes(record_id_array)))
+            })
+            .collect::<Result<HashSet<RecordID>, String>>()?;
+        *self.tombstones.write().unwrap() = tombstones;
+
+        Ok(())
+    }
+
+    /// Atomically applies all of the writes in a [DBWriteBatch] to the database
+    pub fn commit_batch(&self, batch : DBWriteBatch) -> Result<(), String> {
+
+        //Build the RocksDB batch from the final state of each entry the batch wrote
+        let batch = batch.into_merged();
+        let mut write_batch = WriteBatch::default();
+        let mut cf_handles = HashMap::new();
+        for ((cf_name, key), pending_entry) in batch.pending.iter() {
+            if !cf_handles.contains_key(cf_name) {
+                cf_handles.insert(*cf_name, self.cf_handle(cf_name)?);
+            }
+            let cf_handle = &cf_handles[cf_name];
+            match pending_entry {
+                PendingEntry::Put(value_bytes) => write_batch.put_cf(cf_handle, key, value_bytes),
+                PendingEntry::Delete => write_batch.delete_cf(cf_handle, key),
+                PendingEntry::Merge(operands) => operands.iter().for_each(|operand| write_batch.merge_cf(cf_handle, key, operand)),
+            }
+        }
+        drop(cf_handles);
+
+        //During a bulk load, the write-ahead log is skipped, and the variant references are buffered
+        let mut bulk_load = self.bulk_load.lock().unwrap();
+        match bulk_load.as_mut() {
+            Some(bulk_load_variants) => {
+                let mut write_opts = rocksdb::WriteOptions::default();
+                write_opts.disable_wal(true);
+                self.db.write_opt(write_batch, &write_opts)?;
+                for (variant, key_group, add) in batch.bulk_variant_refs {
+                    let key_groups = bulk_load_variants.entry(variant).or_default();
+                    if add {
+                        key_groups.push(key_group);
+                    } else {
+                        key_groups.retain(|existing| *existing != key_group);
+                    }
+                }
+            },
+            None => self.db.write(write_batch)?
+        }
+        drop(bulk_load);
+
+        //Bring the tombstones held in memory up to date with the batch
+        for ((cf_name, key), pending_entry) in batch.pending.iter() {
+            if *cf_name != TOMBSTONES_CF_NAME || key.len() != 8 {
+                continue;
+            }
+            let record_id = RecordID::from(usize::from_le_bytes(key[..].try_into().unwrap()));
+            match pending_entry {
+                PendingEntry::Delete => { self.tombstones.write().unwrap().remove(&record_id); },
+                _ => { self.tombstones.write().unwrap().insert(record_id); },
+            }
+        }
+
+        //Bring the entries of the pinned records up to date with the batch
+        let pinned_records_key = (METADATA_CF_NAME, PINNED_RECORDS_METADATA_NAME.as_bytes().to_vec());
+        let pinned_records_entry = batch.pending.get(&pinned_records_key);
+        if self.pin_cache.is_empty() && pinned_records_entry.is_none() {
+            return Ok(());
+        }
+        for ((cf_name, key), pending_entry) in batch.pending.iter() {
+            if !PINNED_CF_NAMES.contains(cf_name) || key.len() != 8 {
+                continue;
+            }
+            let id = usize::from_le_bytes(key[..].try_into().unwrap());
+            let record_id = if *cf_name == KEYS_CF_NAME { KeyGroupID::from(id).record_id() } else { RecordID::from(id) };
+            match pending_entry {
+                PendingEntry::Put(entry_bytes) => self.pin_cache.update(cf_name, key, record_id, Some(entry_bytes.clone())),
+                PendingEntry::Delete => self.pin_cache.update(cf_name, key, record_id, None),
+                PendingEntry::Merge(_) => self.pin_cache.unpin(record_id),
+            }
+        }
+
+        //Pin and unpin the records, if the batch changed which are pinned
+        if let Some(PendingEntry::Put(bytes)) = pinned_records_entry {
+            let record_ids : HashSet<RecordID> = bincode::deserialize::<Vec<RecordID>>(bytes).map_err(|err| err.to_string())?.into_iter().collect();
+            for record_id in self.pin_cache.record_ids() {
+                if !record_ids.contains(&record_id) {
+                    self.pin_cache.unpin(record_id);
+                }
+            }
+            for record_id in record_ids {
+                if !self.pin_cache.is_pinned(record_id) {
+                    self.pin_record(record_id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the RecordIDs of the pinned records, as they would be after the supplied batch were committed
+    pub fn get_pinned_records_in_batch(&self, batch : &DBWriteBatch) -> Result<Vec<RecordID>, String> {
+
+        match self.get_cf_in_batch(batch, METADATA_CF_NAME, PINNED_RECORDS_METADATA_NAME.as_bytes())? {
+            Some(bytes) => bincode::deserialize(&bytes)
+                .map_err(|err| self.corrupt_entry_error(METADATA_CF_NAME, PINNED_RECORDS_METADATA_NAME.as_bytes(), err)),
+            None => Ok(vec![])
+        }
+    }
+
+    /// Replaces the RecordIDs of the pinned records.  The records are pinned and unpinned when the batch is committed
+    pub fn put_pinned_records(&self, batch : &mut DBWriteBatch, record_ids : &[RecordID]) -> Result<(), String> {
+
+        let bytes = bincode::serialize(record_ids).map_err(|err| err.to_string())?;
+        batch.put(METADATA_CF_NAME, PINNED_RECORDS_METADATA_NAME.as_bytes(), bytes);
+        Ok(())
+    }
+
+    /// Loads the entries of every record in the "pinned_records" metadata entry into the [PinCache], and returns
+    /// the number of records pinned.  Records that have been deleted are skipped
+    pub fn load_pinned_records(&self) -> Result<usize, String> {
+
+        let record_ids = self.get_metadata::<Vec<RecordID>>(PINNED_RECORDS_METADATA_NAME)?.unwrap_or_default();
+        let mut pinned = 0;
+        for record_id in record_ids {
+            if self.pin_record(record_id)? {
+                pinned += 1;
+            }
+        }
+        Ok(pinned)
+    }
+
+    /// Returns the RecordIDs of the records in the [PinCache]
+    pub fn pinned_record_ids(&self) -> HashSet<RecordID> {
+        self.pin_cache.record_ids()
+    }
+
+    /// Reads the entries of a record into the [PinCache].  Returns `false`, and leaves the record unpinned, if the
+    /// record doesn't exist or has been deleted
+    ///
+    /// NOTE: The entries in column families that aren't open are left out, and read from the database as usual
+    fn pin_record(&self, record_id : RecordID) -> Result<bool, String> {
+
+        let key_groups : Vec<KeyGroupID> = match self.get_record_key_groups(record_id) {
+            Ok(key_groups) => key_groups.collect(),
+            Err(_) => return Ok(false)
+        };
+        if key_groups.is_empty() {
+            return Ok(false);
+        }
+
+        let mut entries = vec![];
+        let mut read_entry = |cf_name : &'static str, key : [u8; 8]| -> Result<(), String> {
+            if let Ok(cf_handle) = self.cf_handle(cf_name) {
+                let entry = self.db.get_cf(&cf_handle, key)?;
+                entries.push((cf_name, key.to_vec(), entry));
+            }
+            Ok(())
+        };
+        read_entry(RECORD_DATA_CF_NAME, record_id.to_le_bytes())?;
+        read_entry(VALUES_CF_NAME, record_id.to_le_bytes())?;
+        for key_group in key_groups {
+            read_entry(KEYS_CF_NAME, key_group.to_le_bytes())?;
+        }
+        self.pin_cache.pin(record_id, entries);
+
+        Ok(true)
+    }
+
+    /// Returns the entry for a key, as it would be after the supplied batch were committed
+    fn get_cf_in_batch(&self, batch : &DBWriteBatch, cf_name : &'static str, key : &[u8]) -> Result<Option<Vec<u8>>, String> {
+
+        let cf_handle = &self.cf_handle(cf_name)?;
+        match batch.pending_entry(&(cf_name, key.to_vec())) {
+            Some(PendingEntry::Put(value_bytes)) => Ok(Some(value_bytes.clone())),
+            Some(PendingEntry::Delete) => Ok(None),
+            Some(PendingEntry::Merge(operands)) => {
+                let existing_bytes = self.db.get_pinned_cf(cf_handle, key)?;
+                match variant_merge_entries(existing_bytes.as_deref(), operands.iter().map(|operand| &operand[..])) {
+                    Some(merged_bytes) => Ok(Some(merged_bytes)),
+                    None => Err(self.corrupt_entry_error(cf_name, key, "malformed KeyGroupID vec"))
+                }
+            },
+            None => Ok(self.db.get_cf(cf_handle, key)?)
+        }
+    }
+
+    ///Returns the number of record entries in the database, by probing the entries in the
+    /// "rec_data" column family
+    /// 
+    ///NOTE: this is not a simple lookup, and is designed to be called when loading a new table, not
+    /// as a simple accessor
+    pub fn record_count(&self) -> Result<usize, String> {
+        let rec_data_cf_handle = &self.cf_handle(RECORD_DATA_CF_NAME)?;
+        let record_count = probe_for_max_sequential_key(&self.db, rec_data_cf_handle, 255)?;
+        Ok(record_count)
+    }
+
+    /// Returns `true` if a record with the RecordID exists and hasn't been deleted, as it would be after the
+    /// supplied batch were committed
+    pub fn record_is_live_in_batch(&self, batch : &DBWriteBatch, record_id : RecordID) -> Result<bool, String> {
+
+        match self.get_cf_in_batch(batch, RECORD_DATA_CF_NAME, &record_id.to_le_bytes())? {
+            Some(rec_data_vec_bytes) => {
+                let (rec_data, _inline_value) = self.decode_rec_data(record_id, &rec_data_vec_bytes)?;
+                Ok(!rec_data.key_groups.is_empty())
+            },
+            None => Ok(false)
+        }
+    }
+
+    /// Returns an iterator for every key group associated with a specified record
+    /// 
+    /// Internal FuzzyRocks interface, but exported outside the key_groups module
+    #[inline(always)]
+    pub fn get_record_key_groups(&self, record_id : RecordID) -> Result<impl Iterator<Item=KeyGroupID>, String> {
+        self.get_live_record_key_groups(record_id)?.ok_or_else(|| "Invalid record_id".to_string())
+    }
+
+    /// Returns an iterator for every key group associated with a specified record, or `None` if the record has
+    /// expired, or has no entry because it has been deleted
+    #[inline(always)]
+    pub fn get_live_record_key_groups(&self, record_id : RecordID) -> Result<Option<impl Iterator<Item=KeyGroupID>>, String> {
+
+        if self.is_expired(record_id)? {
+            return Ok(None);
+        }
+
+        if let Some(entry) = self.pin_cache.get(RECORD_DATA_CF_NAME, &record_id.to_le_bytes()) {
+            return match entry {
+                Some(rec_data_vec_bytes) => self.decode_record_key_groups(record_id, &rec_data_vec_bytes),
+                None => Ok(None)
+            };
+        }
+
+        let rec_data_cf_handle = &self.cf_handle(RECORD_DATA_CF_NAME)?;
+        if let Some(rec_data_vec_bytes) = self.db.get_pinned_cf(rec_data_cf_handle, record_id.to_le_bytes())? {
+            self.decode_record_key_groups(record_id, &rec_data_vec_bytes)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns an iterator for every key group associated with a specified record, as it would be after the
+    /// supplied batch were committed
+    pub fn get_record_key_groups_in_batch(&self, batch : &DBWriteBatch, record_id : RecordID) -> Result<impl Iterator<Item=KeyGroupID>, String> {
+
+        let key_groups = match self.get_cf_in_batch(batch, RECORD_DATA_CF_NAME, &record_id.to_le_bytes())? {
+            Some(rec_data_vec_bytes) => self.decode_record_key_groups(record_id, &rec_data_vec_bytes)?,
+            None => None
+        };
+        key_groups.ok_or_else(|| "Invalid record_id".to_string())
+    }
+
+    /// Decodes the bytes of a "rec_data" entry into an iterator over the record's key groups, or `None` if the
+    /// record has been deleted
+    #[inline(always)]
+    fn decode_record_key_groups(&self, record_id : RecordID, rec_data_vec_bytes : &[u8]) -> Result<Option<impl Iterator<Item=KeyGroupID>>, String> {
+
+        let (rec_data, _inline_value) = self.decode_rec_data(record_id, rec_data_vec_bytes)?;
+
+        if self.config.strict_decoding && !all_unique(rec_data.key_groups.iter()) {
+            return Err(self.corrupt_entry_error(RECORD_DATA_CF_NAME, &record_id.to_le_bytes(), "duplicate key group index"));
+        }
+
+        if !rec_data.key_groups.is_empty() {
+            Ok(Some(rec_data.key_groups.into_iter().map(move |group_idx| KeyGroupID::from_record_and_idx(record_id, group_idx))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Decodes the bytes of a "rec_data" entry, and returns the bytes of the record's value along with it, if
+    /// the value is stored inline
+    /// 
+    /// NOTE: An inline value is stored after the RecordData in the same entry, so an entry without one is
+    /// the same as an entry written before values could be inlined
+    fn decode_rec_data<'b>(&self, record_id : RecordID, rec_data_vec_bytes : &'b [u8]) -> Result<(RecordData, Option<&'b [u8]>), String> {
+
+        let entry_len = self.check_entry(RECORD_DATA_CF_NAME, &record_id.to_le_bytes(), record_id, rec_data_vec_bytes)?;
+        let rec_data_vec_bytes = &rec_data_vec_bytes[..entry_len];
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian().allow_trailing_bytes();
+        let rec_data : RecordData = record_coder.deserialize(rec_data_vec_bytes)
+            .map_err(|err| self.corrupt_entry_error(RECORD_DATA_CF_NAME, &record_id.to_le_bytes(), err))?;
+
+        let rec_data_len = record_coder.serialized_size(&rec_data).unwrap() as usize;
+        let inline_value = if rec_data_vec_bytes.len() > rec_data_len {
+            Some(&rec_data_vec_bytes[rec_data_len..])
+        } else {
+            None
+        };
+
+        Ok((rec_data, inline_value))
+    }
+
+    /// Puts a "rec_data" entry in the batch, with the value stored inline after the RecordData if one is supplied
+    fn put_rec_data(&self, batch : &mut DBWriteBatch, record_id : RecordID, rec_data : &RecordData, inline_value : Option<&[u8]>) {
+
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        let mut rec_data_bytes = record_coder.serialize(rec_data).unwrap();
+        if let Some(inline_value) = inline_value {
+            rec_data_bytes.extend_from_slice(inline_value);
+        }
+        batch.put(RECORD_DATA_CF_NAME, &record_id.to_le_bytes(), self.seal_entry(rec_data_bytes));
+    }
+
+    /// Replaces the key groups in the specified record with the provided vec.  If the vec is empty, any value
+    /// stored inline is discarded
+    /// 
+    /// Internal FuzzyRocks interface, but exported outside the key_groups module
+    pub fn put_record_key_groups(&self, batch : &mut DBWriteBatch, record_id : RecordID, key_groups_vec : &[usize]) -> Result<(), String> {
+
+        //Carry over the value if it's stored inline
+        let existing_bytes = if self.config.inline_value_max_size > 0 && !key_groups_vec.is_empty() {
+            self.get_cf_in_batch(batch, RECORD_DATA_CF_NAME, &record_id.to_le_bytes())?
+        } else {
+            None
+        };
+        let inline_value = match &existing_bytes {
+            Some(existing_bytes) => self.decode_rec_data(record_id, existing_bytes)?.1,
+            None => None
+        };
+
+        //Create the RecordData, serialize it, and put in into the rec_data table.
+        self.put_rec_data(batch, record_id, &RecordData::new(key_groups_vec), inline_value);
+
+        Ok(())
+    }
+
+    /// Returns the keys associated with a single key group of a single specified record
+    #[inline(always)]
+    pub fn get_keys_in_group<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, key_group : KeyGroupID, perf_counters : &PerfCounters) -> Result<impl Iterator<Item=OwnedKeyT>, String> {
+        self.get_live_keys_in_group(key_group, perf_counters)?.ok_or_else(|| "Invalid record_id".to_string())
+    }
+
+    /// Returns the keys associated with a single key group, or `None` if the key group has no entry, e.g. because
+    /// it has expired, or has no keys
+    #[inline(always)]
+    pub fn get_live_keys_in_group<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, key_group : KeyGroupID, perf_counters : &PerfCounters) -> Result<Option<impl Iterator<Item=OwnedKeyT>>, String> {
+
+        #[cfg(feature = "perf_counters")]
+        let load_started = std::time::Instant::now();
+
+        let keys = self.load_live_keys_in_group(key_group, perf_counters);
+
+        #[cfg(feature = "perf_counters")]
+        { perf_counters.update(|fields| fields.key_group_load_latency.record(load_started.elapsed())); }
+
+        keys
+    }
+
+    /// Loads the keys of a key group for [get_live_keys_in_group](Self::get_live_keys_in_group), from the pin cache
+    /// if the key group is pinned, otherwise from the "keys" CF
+    fn load_live_keys_in_group<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, key_group : KeyGroupID, perf_counters : &PerfCounters) -> Result<Option<impl Iterator<Item=OwnedKeyT>>, String> {
+
+        if let Some(entry) = self.pin_cache.get(KEYS_CF_NAME, &key_group.to_le_bytes()) {
+            return match entry {
+                Some(keys_vec_bytes) => self.decode_keys_in_group(key_group, &keys_vec_bytes, perf_counters),
+                None => Ok(None)
+            };
+        }
+
+        //Get the keys vec by deserializing the bytes from the db
+        let keys_cf_handle = &self.cf_handle(KEYS_CF_NAME)?;
+        if let Some(keys_vec_bytes) = self.db.get_pinned_cf(keys_cf_handle, key_group.to_le_bytes())? {
+            self.decode_keys_in_group(key_group, &keys_vec_bytes, perf_counters)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the keys associated with a single key group, as they would be after the supplied batch
+    /// were committed
+    pub fn get_keys_in_group_in_batch<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, batch : &DBWriteBatch, key_group : KeyGroupID, perf_counters : &PerfCounters) -> Result<impl Iterator<Item=OwnedKeyT>, String> {
+
+        let keys = match self.get_cf_in_batch(batch, KEYS_CF_NAME, &key_group.to_le_bytes())? {
+            Some(keys_vec_bytes) => self.decode_keys_in_group(key_group, &keys_vec_bytes, perf_counters)?,
+            None => None
+        };
+        keys.ok_or_else(|| "Invalid record_id".to_string())
+    }
+
+    /// Decodes the bytes of a "keys" entry into an iterator over the keys, or `None` if there are no keys
+    #[inline(always)]
+    #[allow(unused_variables)] //NOTE: To silence the warning about perf_counters when that code path is disabled
+    fn decode_keys_in_group<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, key_group : KeyGroupID, keys_vec_bytes : &[u8], perf_counters : &PerfCounters) -> Result<Option<impl Iterator<Item=OwnedKeyT>>, String> {
+
+        let entry_len = self.check_entry(KEYS_CF_NAME, &key_group.to_le_bytes(), key_group.record_id(), keys_vec_bytes)?;
+        let keys_vec_bytes = self.decrypt_entry(self.keys_cipher(), KEYS_CF_NAME, &key_group.to_le_bytes(), &keys_vec_bytes[..entry_len])?;
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        let keys_vec : Vec<OwnedKeyT> = record_coder.deserialize(&keys_vec_bytes)
+            .map_err(|err| self.corrupt_entry_error(KEYS_CF_NAME, &key_group.to_le_bytes(), err))?;
+
+        #[cfg(feature = "perf_counters")]
+        perf_counters.update(|counter_fields| {
+            counter_fields.key_group_load_count += 1;
+            counter_fields.keys_found_count += keys_vec.len();
+        });
+
+        if !keys_vec.is_empty() {
+            Ok(Some(keys_vec.into_iter()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the number of keys in a key group, without returning the keys themselves
+    /// 
+    /// This is intended to be faster than get_keys_in_group, but it's unclear if it actually
+    /// saves much as RocksDB still loads the whole entry from the DB, even though the count is
+    /// stored in the first few bytes.
+    #[inline(always)]
+    pub fn keys_count_in_group(&self, key_group : KeyGroupID) -> Result<usize, String> {
+
+        let keys_cf_handle = &self.cf_handle(KEYS_CF_NAME)?;
+        if let Some(keys_vec_bytes) = self.db.get_pinned_cf(keys_cf_handle, key_group.to_le_bytes())? {
+
+            //The vector element count should be the first encoded usize
+            let entry_len = self.check_entry(KEYS_CF_NAME, &key_group.to_le_bytes(), key_group.record_id(), &keys_vec_bytes)?;
+            let keys_vec_bytes = self.decrypt_entry(self.keys_cipher(), KEYS_CF_NAME, &key_group.to_le_bytes(), &keys_vec_bytes[..entry_len])?;
+            let (keys_count, _num_bytes) = bincode_u64_le_varint(&keys_vec_bytes)
+                .map_err(|err| self.corrupt_entry_error(KEYS_CF_NAME, &key_group.to_le_bytes(), err))?;
+
+            Ok(keys_count as usize)
+        } else {
+            Err("Invalid record_id".to_string())
+        }
+    }
+
+    /// Creates entries in the keys table.  If we are updating an old record, we will overwrite it.
+    /// 
+    /// NOTE: This function will NOT update any variants used to locate the key
+    pub fn put_key_group_entry<K : Eq + Hash + Serialize>(&self, batch : &mut DBWriteBatch, key_group_id : KeyGroupID, raw_keys : &HashSet<K>) -> Result<(), String> {
+        
+        //Serialize the keys into a vec of bytes
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        let keys_bytes = record_coder.serialize(&raw_keys).unwrap();
+
+        //Put the vector of keys into the keys table
+        batch.put(KEYS_CF_NAME, &key_group_id.to_le_bytes(), self.seal_entry(Self::encrypt_entry(self.keys_cipher(), keys_bytes)));
+
+        Ok(())
+    }
+
+    /// Deletes a key group entry from the db.  Does not clean up variants that may reference
+    /// the key group, so must be called as part of another operation
+    pub fn delete_key_group_entry(&self, batch : &mut DBWriteBatch, key_group : KeyGroupID) -> Result<(), String> {
+        
+        batch.delete(KEYS_CF_NAME, &key_group.to_le_bytes());
+
+        Ok(())
+    }
+
+    /// Returns the value associated with the specified record
+    #[inline(always)]
+    pub fn get_value<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, record_id : RecordID) -> Result<ValueT, String> {
+
+        if self.is_expired(record_id)? {
+            return Err("Invalid record_id".to_string());
+        }
+
+        //Get the value object by deserializing the bytes from the db, unless the record is pinned
+        self.find_value(record_id, |cf_name, key| {
+            if let Some(entry) = self.pin_cache.get(cf_name, key) {
+                return Ok(entry);
+            }
+            let cf_handle = &self.cf_handle(cf_name)?;
+            Ok(self.db.get_cf(cf_handle, key)?)
+        })
+    }
+
+    /// Returns the values associated with each of the specified records, in the same order, fetching them
+    /// all from the database with a single `multi_get_cf` call
+    /// 
+    /// NOTE: If values may be stored inline, the "rec_data" entries are fetched, and any values that aren't
+    /// inline are then fetched one at a time
+    pub fn get_values_multi<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, record_ids : &[RecordID]) -> Result<Vec<ValueT>, String> {
+        self.get_value_bytes_multi(record_ids)?.iter().map(|value_bytes| self.decode_value_bytes(value_bytes)).collect()
+    }
+
+    /// Works like [get_values_multi](Self::get_values_multi), but returns the bytes of the values without decoding them
+    pub fn get_value_bytes_multi(&self, record_ids : &[RecordID]) -> Result<Vec<ValueBytes>, String> {
+
+        //NOTE: If any of the records are pinned, it's simplest to get the values one at a time, so the pinned
+        // values are taken from the PinCache
+        if !self.pin_cache.is_empty() && record_ids.iter().any(|record_id| self.pin_cache.is_pinned(*record_id)) {
+            return record_ids.iter().map(|record_id| self.get_value_bytes(*record_id)).collect();
+        }
+
+        let cf_name = if self.config.inline_value_max_size > 0 { RECORD_DATA_CF_NAME } else { VALUES_CF_NAME };
+        let cf_handle = &self.cf_handle(cf_name)?;
+        let results = self.db.multi_get_cf(record_ids.iter().map(|record_id| (cf_handle, record_id.to_le_bytes())));
+
+        record_ids.iter().zip(results).map(|(record_id, result)| {
+            match result? {
+                Some(value_bytes) if cf_name == VALUES_CF_NAME => self.value_bytes_from_entry(*record_id, value_bytes),
+                Some(rec_data_bytes) => match self.decode_rec_data(*record_id, &rec_data_bytes)?.1 {
+                    Some(value_bytes) => Ok(ValueBytes{cf_name : RECORD_DATA_CF_NAME, record_id : *record_id, bytes : value_bytes.to_vec()}),
+                    None => self.get_value_bytes(*record_id)
+                },
+                None => self.get_value_bytes(*record_id)
+            }
+        }).collect()
+    }
+
+    /// Returns the bytes of the value associated with the specified record, without decoding them
+    fn get_value_bytes(&self, record_id : RecordID) -> Result<ValueBytes, String> {
+        self.find_value_bytes(record_id, |cf_name, key| {
+            if let Some(entry) = self.pin_cache.get(cf_name, key) {
+                return Ok(entry);
+            }
+            let cf_handle = &self.cf_handle(cf_name)?;
+            Ok(self.db.get_cf(cf_handle, key)?)
+        })
+    }
+
+    /// Decodes the bytes of a value returned by [get_value_bytes_multi](Self::get_value_bytes_multi)
+    pub fn decode_value_bytes<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, value_bytes : &ValueBytes) -> Result<ValueT, String> {
+        self.decode_value(value_bytes.cf_name, value_bytes.record_id, &value_bytes.bytes)
+    }
+
+    /// Returns the value associated with the specified record, as it would be after the supplied batch
+    /// were committed
+    pub fn get_value_in_batch<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, batch : &DBWriteBatch, record_id : RecordID) -> Result<ValueT, String> {
+        self.find_value(record_id, |cf_name, key| self.get_cf_in_batch(batch, cf_name, key))
+    }
+
+    /// Finds and decodes a record's value, which is stored inline in its "rec_data" entry, in the "values" CF, or
+    /// in chunks in the "blobs" CF, using the supplied function to read an entry from a CF
+    fn find_value<ValueT, GetF>(&self, record_id : RecordID, get_entry : GetF) -> Result<ValueT, String>
+        where
+        ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+        GetF : Fn(&'static str, &[u8]) -> Result<Option<Vec<u8>>, String>
+    {
+        self.decode_value_bytes(&self.find_value_bytes(record_id, get_entry)?)
+    }
+
+    /// Finds the bytes of a record's value, using the supplied function to read an entry from a CF, like [find_value](Self::find_value)
+    fn find_value_bytes<GetF>(&self, record_id : RecordID, get_entry : GetF) -> Result<ValueBytes, String>
+        where
+        GetF : Fn(&'static str, &[u8]) -> Result<Option<Vec<u8>>, String>
+    {
+        if let Some(value_bytes) = self.find_unsplit_value_bytes(record_id, &get_entry)? {
+            return Ok(value_bytes);
+        }
+
+        //A value without a "values" entry may have been split into chunks
+        let mut blob_bytes = vec![];
+        if self.visit_blob_chunks(record_id, &get_entry, |chunk_bytes| { blob_bytes.extend_from_slice(chunk_bytes); Ok(()) })? > 0 {
+            Ok(ValueBytes{cf_name : BLOBS_CF_NAME, record_id, bytes : blob_bytes})
+        } else {
+            Err("Invalid record_id".to_string())
+        }
+    }
+
+    /// Finds the bytes of a record's value if it is stored inline or in the "values" CF, or returns `None` if
+    /// it may have been split into chunks
+    fn find_unsplit_value_bytes<GetF>(&self, record_id : RecordID, get_entry : &GetF) -> Result<Option<ValueBytes>, String>
+        where
+        GetF : Fn(&'static str, &[u8]) -> Result<Option<Vec<u8>>, String>
+    {
+
+        //NOTE: We don't pay for the extra read of the "rec_data" entry unless values may be stored inline
+        if self.config.inline_value_max_size > 0 {
+            if let Some(rec_data_bytes) = get_entry(RECORD_DATA_CF_NAME, &record_id.to_le_bytes())? {
+                if let Some(value_bytes) = self.decode_rec_data(record_id, &rec_data_bytes)?.1 {
+                    return Ok(Some(ValueBytes{cf_name : RECORD_DATA_CF_NAME, record_id, bytes : value_bytes.to_vec()}));
+                }
+            }
+        }
+
+        match get_entry(VALUES_CF_NAME, &record_id.to_le_bytes())? {
+            Some(value_bytes) => Ok(Some(self.value_bytes_from_entry(record_id, value_bytes)?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Checks each of the chunks of a record's value in the "blobs" CF in order, and calls the closure with the
+    /// bytes of the chunk without the checksum.  Returns the number of chunks
+    fn visit_blob_chunks<GetF, F>(&self, record_id : RecordID, get_entry : &GetF, mut visitor_closure : F) -> Result<u32, String>
+        where
+        GetF : Fn(&'static str, &[u8]) -> Result<Option<Vec<u8>>, String>,
+        F : FnMut(&[u8]) -> Result<(), String>
+    {
+        let mut chunk_idx = 0;
+        let mut next_chunk = get_entry(BLOBS_CF_NAME, &blob_chunk_key(record_id, chunk_idx))?;
+        if next_chunk.is_none() {
+            chunk_idx = BLOB_SLOT_BIT;
+            next_chunk = get_entry(BLOBS_CF_NAME, &blob_chunk_key(record_id, chunk_idx))?;
+        }
+        let mut chunk_count = 0;
+        while let Some(chunk_bytes) = next_chunk {
+            let chunk_len = self.check_entry(BLOBS_CF_NAME, &blob_chunk_key(record_id, chunk_idx), record_id, &chunk_bytes)?;
+            visitor_closure(&chunk_bytes[..chunk_len])?;
+            chunk_idx += 1;
+            chunk_count += 1;
+            next_chunk = get_entry(BLOBS_CF_NAME, &blob_chunk_key(record_id, chunk_idx))?;
+        }
+        Ok(chunk_count)
+    }
+
+    /// Writes the bytes of a record's value, which must serialize as a sequence of bytes, such as a `Vec<u8>`,
+    /// to `writer`.  A value split into chunks is written one chunk at a time, unless it is encrypted.  Returns
+    /// the number of bytes written
+    pub fn get_value_to_writer<W : Write>(&self, record_id : RecordID, writer : &mut W) -> Result<u64, String> {
+
+        let get_entry = |cf_name : &'static str, key : &[u8]| -> Result<Option<Vec<u8>>, String> {
+            if let Some(entry) = self.pin_cache.get(cf_name, key) {
+                return Ok(entry);
+            }
+            let cf_handle = &self.cf_handle(cf_name)?;
+            Ok(self.db.get_cf(cf_handle, key)?)
+        };
+
+        //NOTE: An encrypted value can only be decrypted whole
+        let value_bytes = match self.find_unsplit_value_bytes(record_id, &get_entry)? {
+            Some(value_bytes) => Some(value_bytes),
+            None if self.cipher().is_some() => Some(self.find_value_bytes(record_id, get_entry)?),
+            None => None
+        };
+        let mut payload_writer = ValuePayloadWriter::new(writer);
+        match value_bytes {
+            Some(value_bytes) => {
+                let decrypted_bytes = self.decrypt_entry(self.cipher(), value_bytes.cf_name, &record_id.to_le_bytes(), &value_bytes.bytes)?;
+                payload_writer.write(&decrypted_bytes)?;
+            },
+            None => {
+                if self.visit_blob_chunks(record_id, &get_entry, |chunk_bytes| payload_writer.write(chunk_bytes))? == 0 {
+                    return Err("Invalid record_id".to_string());
+                }
+            }
+        }
+        payload_writer.finish()
+    }
+
+    /// Deletes the chunks of a record's value from the "blobs" CF, starting from the chunk at `first_chunk_idx`, up
+    /// to the first chunk that is missing, as it would be after the supplied batch were committed
+    ///
+    /// NOTE: The first chunk of a slot is deleted without checking for the chunks after it, because a value that
+    /// failed to stream in can leave chunks behind in a slot without its first chunk
+    fn delete_blob_chunks(&self, batch : &mut DBWriteBatch, record_id : RecordID, first_chunk_idx : u32) -> Result<(), String> {
+        let mut chunk_idx = first_chunk_idx;
+        loop {
+            let chunk_key = blob_chunk_key(record_id, chunk_idx);
+            if chunk_idx & !BLOB_SLOT_BIT != 0 && self.get_cf_in_batch(batch, BLOBS_CF_NAME, &chunk_key)?.is_none() {
+                return Ok(());
+            }
+            batch.delete(BLOBS_CF_NAME, &chunk_key);
+            chunk_idx += 1;
+        }
+    }
+
+    /// Deletes all of the chunks of a record's value from both of the record's slots in the "blobs" CF
+    fn delete_all_blob_chunks(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+        self.delete_blob_chunks(batch, record_id, 0)?;
+        self.delete_blob_chunks(batch, record_id, BLOB_SLOT_BIT)
+    }
+
+    /// Checks a "values" entry, and returns the bytes of the value without the checksum
+    fn value_bytes_from_entry(&self, record_id : RecordID, mut entry_bytes : Vec<u8>) -> Result<ValueBytes, String> {
+        let entry_len = self.check_entry(VALUES_CF_NAME, &record_id.to_le_bytes(), record_id, &entry_bytes)?;
+        entry_bytes.truncate(entry_len);
+        Ok(ValueBytes{cf_name : VALUES_CF_NAME, record_id, bytes : entry_bytes})
+    }
+
+    /// Decodes the bytes of a value, from a "values" entry or stored inline in a "rec_data" entry
+    #[inline(always)]
+    fn decode_value<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, cf_name : &'static str, record_id : RecordID, value_bytes : &[u8]) -> Result<ValueT, String> {
+
+        let value_bytes = self.decrypt_entry(self.cipher(), cf_name, &record_id.to_le_bytes(), value_bytes)?;
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        let value : ValueT = record_coder.deserialize(&value_bytes)
+            .map_err(|err| self.corrupt_entry_error(cf_name, &record_id.to_le_bytes(), err))?;
+
+        Ok(value)
+    }
+
+    /// Deletes a record's value in the values table
+    /// 
+    /// This should only be called as part of another operation as it leaves the record in an
+    /// inconsistent state
+    pub fn delete_value(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+
+        batch.delete(VALUES_CF_NAME, &record_id.to_le_bytes());
+        self.delete_all_blob_chunks(batch, record_id)?;
+
+        Ok(())
+    }
+
+    /// Returns the injected variants of a record, as they would be after the supplied batch were committed.
+    /// A record without injected variants returns an empty Vec
+    pub fn get_injected_variants_in_batch<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, batch : &DBWriteBatch, record_id : RecordID) -> Result<Vec<OwnedKeyT>, String> {
+
+        match self.get_cf_in_batch(batch, INJECTED_CF_NAME, &record_id.to_le_bytes())? {
+            Some(variants_bytes) => self.decode_injected_variants(record_id, &variants_bytes),
+            None => Ok(vec![])
+        }
+    }
+
+    /// Returns the injected variants of a record.  A record without injected variants returns an empty Vec
+    pub fn get_injected_variants<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, record_id : RecordID) -> Result<Vec<OwnedKeyT>, String> {
+
+        let injected_cf_handle = &self.cf_handle(INJECTED_CF_NAME)?;
+        match self.db.get_pinned_cf(injected_cf_handle, record_id.to_le_bytes())? {
+            Some(variants_bytes) => self.decode_injected_variants(record_id, &variants_bytes),
+            None => Ok(vec![])
+        }
+    }
+
+    /// Decodes the bytes of an "injected" entry
+    fn decode_injected_variants<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, record_id : RecordID, variants_bytes : &[u8]) -> Result<Vec<OwnedKeyT>, String> {
+
+        let variants_bytes = self.decrypt_entry(self.keys_cipher(), INJECTED_CF_NAME, &record_id.to_le_bytes(), variants_bytes)?;
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        record_coder.deserialize(&variants_bytes)
+            .map_err(|err| self.corrupt_entry_error(INJECTED_CF_NAME, &record_id.to_le_bytes(), err))
+    }
+
+    /// Replaces the injected variants of a record, or deletes the entry if `variants` is empty
+    /// 
+    /// NOTE: This function will NOT update the variant entries that reference the record
+    pub fn put_injected_variants<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, batch : &mut DBWriteBatch, record_id : RecordID, variants : &[OwnedKeyT]) -> Result<(), String> {
+
+        if variants.is_empty() {
+            batch.delete(INJECTED_CF_NAME, &record_id.to_le_bytes());
+        } else {
+            let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+            let variants_bytes = record_coder.serialize(variants).unwrap();
+            batch.put(INJECTED_CF_NAME, &record_id.to_le_bytes(), Self::encrypt_entry(self.keys_cipher(), variants_bytes));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the RecordIDs of the records with the exact key, from the "exact_keys" CF
+    pub fn get_exact_key_records(&self, key : &[u8]) -> Result<Vec<RecordID>, String> {
+        self.get_record_references(EXACT_KEYS_CF_NAME, key)
+    }
+
+    /// Returns the RecordIDs of the records with a key that has the phonetic code, from the "phonetic" CF
+    pub fn get_phonetic_records(&self, code : &[u8]) -> Result<Vec<RecordID>, String> {
+        self.get_record_references(PHONETIC_CF_NAME, code)
+    }
+
+    /// Returns the RecordIDs of the records with a key that contains the n-gram, from the "ngrams" CF
+    pub fn get_ngram_records(&self, ngram : &[u8]) -> Result<Vec<RecordID>, String> {
+        self.get_record_references(NGRAMS_CF_NAME, ngram)
+    }
+
+    /// Returns the RecordIDs of the records with a key that has a token with the token variant, from the "tokens" CF
+    pub fn get_token_records(&self, token_variant : &[u8]) -> Result<Vec<RecordID>, String> {
+        self.get_record_references(TOKENS_CF_NAME, token_variant)
+    }
+
+    /// Returns the synonyms of a term, from the "synonyms" CF
+    pub fn get_synonyms(&self, term : &str) -> Result<Vec<String>, String> {
+
+        let cf_handle = &self.cf_handle(SYNONYMS_CF_NAME)?;
+        match self.db.get_pinned_cf(cf_handle, self.index_key(term.as_bytes()))? {
+            Some(synonyms_bytes) => self.decode_synonyms(term, &synonyms_bytes),
+            None => Ok(vec![])
+        }
+    }
+
+    /// Returns the synonyms of a term, including any changes in the batch
+    pub fn get_synonyms_in_batch(&self, batch : &DBWriteBatch, term : &str) -> Result<Vec<String>, String> {
+        match self.get_cf_in_batch(batch, SYNONYMS_CF_NAME, &self.index_key(term.as_bytes()))? {
+            Some(synonyms_bytes) => self.decode_synonyms(term, &synonyms_bytes),
+            None => Ok(vec![])
+        }
+    }
+
+    /// Replaces the synonyms of a term, deleting its entry if there are none
+    pub fn put_synonyms(&self, batch : &mut DBWriteBatch, term : &str, synonyms : &[String]) -> Result<(), String> {
+
+        let term_key = self.index_key(term.as_bytes());
+        if synonyms.is_empty() {
+            batch.delete(SYNONYMS_CF_NAME, &term_key);
+        } else {
+            let synonyms_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+            let synonyms_bytes = Self::encrypt_entry(self.keys_cipher(), synonyms_coder.serialize(synonyms).unwrap());
+            batch.put(SYNONYMS_CF_NAME, &term_key, synonyms_bytes);
+        }
+        Ok(())
+    }
+
+    fn decode_synonyms(&self, term : &str, synonyms_bytes : &[u8]) -> Result<Vec<String>, String> {
+
+        let synonyms_bytes = self.decrypt_entry(self.keys_cipher(), SYNONYMS_CF_NAME, term.as_bytes(), synonyms_bytes)?;
+        let synonyms_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        synonyms_coder.deserialize(&synonyms_bytes)
+            .map_err(|err| self.corrupt_entry_error(SYNONYMS_CF_NAME, term.as_bytes(), err))
+    }
+
+    /// Returns the RecordIDs in an entry of a CF that maps a key to a `Vec` of RecordIDs, leaving out the records
+    /// with a tombstone, and those that have expired
+    fn get_record_references(&self, cf_name : &'static str, key : &[u8]) -> Result<Vec<RecordID>, String> {
+
+        let cf_handle = &self.cf_handle(cf_name)?;
+        let key = &self.index_key(key);
+        let mut record_ids = match self.db.get_pinned_cf(cf_handle, key)? {
+            Some(record_ids_bytes) => self.decode_record_references(cf_name, key, &record_ids_bytes)?,
+            None => vec![]
+        };
+        if self.has_tombstones() {
+            record_ids.retain(|record_id| !self.is_tombstoned(*record_id));
+        }
+        self.retain_unexpired(&mut record_ids)?;
+        Ok(record_ids)
+    }
+
+    /// Returns `true` if any record has the supplied key, without decoding the "exact_keys" entry
+    ///
+    /// NOTE: Entries are deleted when they no longer reference any records, so an entry that exists is never empty
+    pub fn exact_key_exists(&self, key : &[u8]) -> Result<bool, String> {
+        let exact_keys_cf_handle = &self.cf_handle(EXACT_KEYS_CF_NAME)?;
+        Ok(self.db.get_pinned_cf(exact_keys_cf_handle, self.index_key(key))?.is_some())
+    }
+
+    /// Decodes the bytes of an entry in the "exact_keys", "phonetic", "ngrams", or "tokens" CF
+    fn decode_record_references(&self, cf_name : &'static str, key : &[u8], record_ids_bytes : &[u8]) -> Result<Vec<RecordID>, String> {
+
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        record_coder.deserialize(record_ids_bytes)
+            .map_err(|err| self.corrupt_entry_error(cf_name, key, err))
+    }
+
+    /// Adds a record to the "exact_keys" entry of each of the supplied keys, if it isn't there already
+    pub fn put_exact_key_references<KeysIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, keys_iter : KeysIterT) -> Result<(), String> {
+        self.put_record_references(batch, EXACT_KEYS_CF_NAME, record_id, keys_iter)
+    }
+
+    /// Removes a record from the "exact_keys" entry of each of the supplied keys, and deletes the entries
+    /// that no longer reference any records
+    pub fn delete_exact_key_references<KeysIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, keys_iter : KeysIterT) -> Result<(), String> {
+        self.delete_record_references(batch, EXACT_KEYS_CF_NAME, record_id, keys_iter)
+    }
+
+    /// Adds a record to the "phonetic" entry of each of the supplied codes, if it isn't there already
+    pub fn put_phonetic_references<CodesIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, codes_iter : CodesIterT) -> Result<(), String> {
+        self.put_record_references(batch, PHONETIC_CF_NAME, record_id, codes_iter)
+    }
+
+    /// Removes a record from the "phonetic" entry of each of the supplied codes, and deletes the entries
+    /// that no longer reference any records
+    pub fn delete_phonetic_references<CodesIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, codes_iter : CodesIterT) -> Result<(), String> {
+        self.delete_record_references(batch, PHONETIC_CF_NAME, record_id, codes_iter)
+    }
+
+    /// Adds a record to the "ngrams" entry of each of the supplied n-grams, if it isn't there already
+    pub fn put_ngram_references<NgramsIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, ngrams_iter : NgramsIterT) -> Result<(), String> {
+        self.put_record_references(batch, NGRAMS_CF_NAME, record_id, ngrams_iter)
+    }
+
+    /// Removes a record from the "ngrams" entry of each of the supplied n-grams, and deletes the entries
+    /// that no longer reference any records
+    pub fn delete_ngram_references<NgramsIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, ngrams_iter : NgramsIterT) -> Result<(), String> {
+        self.delete_record_references(batch, NGRAMS_CF_NAME, record_id, ngrams_iter)
+    }
+
+    /// Adds a record to the "tokens" entry of each of the supplied token variants, if it isn't there already
+    pub fn put_token_references<VariantsIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, variants_iter : VariantsIterT) -> Result<(), String> {
+        self.put_record_references(batch, TOKENS_CF_NAME, record_id, variants_iter)
+    }
+
+    /// Removes a record from the "tokens" entry of each of the supplied token variants, and deletes the entries
+    /// that no longer reference any records
+    pub fn delete_token_references<VariantsIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, variants_iter : VariantsIterT) -> Result<(), String> {
+        self.delete_record_references(batch, TOKENS_CF_NAME, record_id, variants_iter)
+    }
+
+    fn put_record_references<KeysIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, cf_name : &'static str, record_id : RecordID, keys_iter : KeysIterT) -> Result<(), String> {
+        self.update_record_references(batch, cf_name, keys_iter, |record_ids| {
+            if !record_ids.contains(&record_id) {
+                record_ids.push(record_id);
+            }
+        })
+    }
+
+    fn delete_record_references<KeysIterT : Iterator<Item=Vec<u8>>>(&self, batch : &mut DBWriteBatch, cf_name : &'static str, record_id : RecordID, keys_iter : KeysIterT) -> Result<(), String> {
+        self.update_record_references(batch, cf_name, keys_iter, |record_ids| record_ids.retain(|existing_id| *existing_id != record_id))
+    }
+
+    /// Reads, updates, and rewrites the entry of each of the supplied keys, in a CF that maps a key to a `Vec`
+    /// of RecordIDs
+    fn update_record_references<KeysIterT, F>(&self, batch : &mut DBWriteBatch, cf_name : &'static str, keys_iter : KeysIterT, update : F) -> Result<(), String>
+        where
+        KeysIterT : Iterator<Item=Vec<u8>>,
+        F : Fn(&mut Vec<RecordID>)
+    {
+
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        for key in keys_iter {
+            let key = self.index_key(&key).into_owned();
+            let mut record_ids = match self.get_cf_in_batch(batch, cf_name, &key)? {
+                Some(record_ids_bytes) => self.decode_record_references(cf_name, &key, &record_ids_bytes)?,
+                None => vec![]
+            };
+            update(&mut record_ids);
+
+            if record_ids.is_empty() {
+                batch.delete(cf_name, &key);
+            } else {
+                batch.put(cf_name, &key, record_coder.serialize(&record_ids).unwrap());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates entries in the values table, or stores the value inline in the record's "rec_data" entry if
+    /// it's small enough.  If we are updating an old record, we will overwrite it.
+    /// 
+    /// NOTE: The record's "rec_data" entry must be put before its value, so the value has somewhere to go if
+    /// it is stored inline.
+    /// 
+    /// NOTE: This function will NOT update any variants used to locate the key
+    pub fn put_value<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, batch : &mut DBWriteBatch, record_id : RecordID, value : &ValueT) -> Result<(), String> {
+        
+        //Serialize the value
+        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        let value_bytes = record_coder.serialize(value).unwrap();
+        let empty_value = value_bytes.is_empty();
+        let value_bytes = Self::encrypt_entry(self.cipher(), value_bytes);
+
+        //If the value is small enough, store it inline.  Otherwise make sure an old inline value won't shadow it
+        //NOTE: An empty value can't be stored inline, because it would be indistinguishable from no value
+        if self.config.inline_value_max_size > 0 {
+            if let Some(rec_data_bytes) = self.get_cf_in_batch(batch, RECORD_DATA_CF_NAME, &record_id.to_le_bytes())? {
+                let (rec_data, old_inline_value) = self.decode_rec_data(record_id, &rec_data_bytes)?;
+                if !empty_value && value_bytes.len() <= self.config.inline_value_max_size {
+                    self.put_rec_data(batch, record_id, &rec_data, Some(&value_bytes));
+                    batch.delete(VALUES_CF_NAME, &record_id.to_le_bytes());
+                    if self.config.blob_threshold > 0 {
+                        self.delete_all_blob_chunks(batch, record_id)?;
+                    }
+                    return Ok(());
+                }
+                if old_inline_value.is_some() {
+                    self.put_rec_data(batch, record_id, &rec_data, None);
+                }
+            }
+        }
+
+        //If the value is too big for a single entry, split it into chunks, and discard any chunks left over from
+        // a bigger value, or in the other slot
+        if self.config.blob_threshold > 0 {
+            if value_bytes.len() > self.config.blob_threshold {
+                let mut chunk_count = 0;
+                for chunk in value_bytes.chunks(self.config.blob_threshold) {
+                    batch.put(BLOBS_CF_NAME, &blob_chunk_key(record_id, chunk_count), self.seal_entry(chunk.to_vec()));
+                    chunk_count += 1;
+                }
+                self.delete_blob_chunks(batch, record_id, chunk_count)?;
+                self.delete_blob_chunks(batch, record_id, BLOB_SLOT_BIT)?;
+                batch.delete(VALUES_CF_NAME, &record_id.to_le_bytes());
+                return Ok(());
+            }
+            self.delete_all_blob_chunks(batch, record_id)?;
+        }
+
+        //Put it in the values table.
+        batch.put(VALUES_CF_NAME, &record_id.to_le_bytes(), self.seal_entry(value_bytes));
+
+        Ok(())
+    }
+
+    /// Replaces a record's value with one that serializes as a sequence of `len` bytes, such as a `Vec<u8>`,
+    /// reading the bytes from `reader` one chunk at a time, so the whole value is never held in memory
+    /// 
+    /// All but the first chunk are committed to the record's slot in the "blobs" CF that isn't in use, as soon
+    /// as they are read.  The first chunk goes in the supplied batch, along with the deletes of the old value, so
+    /// the record switches to the new value when the batch is committed.  If reading fails, the chunks already
+    /// committed are deleted, and the old value is left in place.
+    /// 
+    /// NOTE: This function will NOT update any variants used to locate the key
+    pub fn put_value_from_reader<R : Read>(&self, batch : &mut DBWriteBatch, record_id : RecordID, reader : &mut R, len : u64) -> Result<(), String> {
+
+        if self.config.blob_threshold == 0 {
+            return Err("a value can only be streamed if BLOB_THRESHOLD is set".to_string());
+        }
+        if self.cipher().is_some() {
+            return Err("a value can't be streamed into an encrypted Table".to_string());
+        }
+
+        //The new value goes in whichever slot doesn't hold the first chunk of the old value
+        let first_chunk_idx = match self.get_cf_in_batch(batch, BLOBS_CF_NAME, &blob_chunk_key(record_id, 0))? {
+            Some(_) => BLOB_SLOT_BIT,
+            None => 0
+        };
+        let (first_chunk, chunk_count) = match self.stream_blob_chunks(record_id, first_chunk_idx, reader, len) {
+            Ok(result) => result,
+            Err(err) => {
+                let mut cleanup_batch = DBWriteBatch::new();
+                self.delete_blob_chunks(&mut cleanup_batch, record_id, first_chunk_idx + 1)?;
+                self.commit_batch(cleanup_batch)?;
+                return Err(err);
+            }
+        };
+
+        //Switch the record to the new chunks, and clear the old value, wherever it was stored
+        if self.config.inline_value_max_size > 0 {
+            if let Some(rec_data_bytes) = self.get_cf_in_batch(batch, RECORD_DATA_CF_NAME, &record_id.to_le_bytes())? {
+                let (rec_data, old_inline_value) = self.decode_rec_data(record_id, &rec_data_bytes)?;
+                if old_inline_value.is_some() {
+                    self.put_rec_data(batch, record_id, &rec_data, None);
+                }
             }
-        } else {
-            Err("Invalid record_id".to_string())
         }
+        batch.delete(VALUES_CF_NAME, &record_id.to_le_bytes());
+        self.delete_blob_chunks(batch, record_id, first_chunk_idx ^ BLOB_SLOT_BIT)?;
+        self.delete_blob_chunks(batch, record_id, first_chunk_idx + chunk_count)?;
+        batch.put(BLOBS_CF_NAME, &blob_chunk_key(record_id, first_chunk_idx), self.seal_entry(first_chunk));
+
+        Ok(())
     }
 
-    /// Replaces the key groups in the specified record with the provided vec
-    /// 
-    /// Internal FuzzyRocks interface, but exported outside the key_groups module
-    pub fn put_record_key_groups(&self, record_id : RecordID, key_groups_vec : &[usize]) -> Result<(), String> {
+    /// Reads a value for [put_value_from_reader](Self::put_value_from_reader), and commits every chunk but the
+    /// first, starting from `first_chunk_idx`.  Returns the first chunk, and the number of chunks
+    fn stream_blob_chunks<R : Read>(&self, record_id : RecordID, first_chunk_idx : u32, reader : &mut R, len : u64) -> Result<(Vec<u8>, u32), String> {
 
-        //Create the RecordData, serialize it, and put in into the rec_data table.
-        let rec_data_cf_handle = self.db.cf_handle(RECORD_DATA_CF_NAME).unwrap();
+        //Write the serialized value one chunk at a time, starting with the length
+        //NOTE: The length may not fit in the first chunk if the chunks are tiny
         let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
-        let new_rec_data = RecordData::new(key_groups_vec);
-        let rec_data_bytes = record_coder.serialize(&new_rec_data).unwrap();
-        self.db.put_cf(rec_data_cf_handle, record_id.to_le_bytes(), rec_data_bytes)?;
+        let mut pending_bytes = record_coder.serialize(&len).unwrap();
+        let mut remaining_len = len;
+        let mut first_chunk = None;
+        let mut chunk_count = 0;
+        while !pending_bytes.is_empty() || remaining_len > 0 {
+            let read_len = (self.config.blob_threshold.saturating_sub(pending_bytes.len()) as u64).min(remaining_len) as usize;
+            let filled_len = pending_bytes.len();
+            pending_bytes.resize(filled_len + read_len, 0);
+            reader.read_exact(&mut pending_bytes[filled_len..]).map_err(|err| format!("failed to read the value: {}", err))?;
+            remaining_len -= read_len as u64;
+
+            let next_bytes = pending_bytes.split_off(pending_bytes.len().min(self.config.blob_threshold));
+            if first_chunk.is_none() {
+                first_chunk = Some(pending_bytes);
+            } else {
+                let mut chunk_batch = DBWriteBatch::new();
+                chunk_batch.put(BLOBS_CF_NAME, &blob_chunk_key(record_id, first_chunk_idx + chunk_count), self.seal_entry(pending_bytes));
+                self.commit_batch(chunk_batch)?;
+            }
+            pending_bytes = next_bytes;
+            chunk_count += 1;
+        }
+
+        Ok((first_chunk.unwrap_or_default(), chunk_count))
+    }
 
+    /// Reloads the entries of a record into the [PinCache] if it is pinned, so none of the entries cached before a
+    /// write made outside of a single batch can outlive it
+    pub fn refresh_pinned_record(&self, record_id : RecordID) -> Result<(), String> {
+        if self.pin_cache.is_pinned(record_id) {
+            self.pin_record(record_id)?;
+        }
         Ok(())
     }
 
-    /// Returns the keys associated with a single key group of a single specified record
+    /// Visits the only the exact variant specified from the database and executes a closure.
+    /// 
+    /// NOTE: The closure gets an iterator over the entry's KeyGroupIDs, rather than a Vec, because
+    /// sometimes we don't want to decode the whole entry.  The closure will be called once for each
+    /// page of the entry, if the entry is paged.
     #[inline(always)]
-    #[allow(unused_variables)] //NOTE: To silence the warning about perf_counters when that code path is disabled
-    pub fn get_keys_in_group<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, key_group : KeyGroupID, perf_counters : &PerfCounters) -> Result<impl Iterator<Item=OwnedKeyT>, String> {
-
-        //Get the keys vec by deserializing the bytes from the db
-        let keys_cf_handle = self.db.cf_handle(KEYS_CF_NAME).unwrap();
-        if let Some(keys_vec_bytes) = self.db.get_pinned_cf(keys_cf_handle, key_group.to_le_bytes())? {
-            let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
-            let keys_vec : Vec<OwnedKeyT> = record_coder.deserialize(&keys_vec_bytes).unwrap();
+    pub fn visit_exact_variant<F : FnMut(VariantEntryIter<'_>)>(&self, variant : &[u8], mut visitor_closure : F) -> Result<(), String> {
 
-            #[cfg(feature = "perf_counters")]
-            {
-                let mut counter_fields = perf_counters.get();
-                counter_fields.key_group_load_count += 1;
-                counter_fields.keys_found_count += keys_vec.len();
-                perf_counters.set(counter_fields);
-            }
+        let variant = &self.index_key(variant);
+        let variants_cf_handle = &self.cf_handle(VARIANTS_CF_NAME)?;
+        if let Some(variant_vec_bytes) = self.db.get_pinned_cf(variants_cf_handle, variant)? {
 
-            if !keys_vec.is_empty() {
-                Ok(keys_vec.into_iter())
-            } else {
-                Err("Invalid record_id".to_string())
-            }
-        } else {
-            Err("Invalid record_id".to_string())
+            visitor_closure(self.decode_variant_entry(VARIANTS_CF_NAME, variant, &variant_vec_bytes)?);
+            self.visit_variant_pages(variant, &mut visitor_closure)?;
         }
+
+        Ok(())
     }
 
-    /// Returns the number of keys in a key group, without returning the keys themselves
+    /// Visits every page of a variant entry after the first, if variant paging is enabled
     /// 
-    /// This is intended to be faster than get_keys_in_group, but it's unclear if it actually
-    /// saves much as RocksDB still loads the whole entry from the DB, even though the count is
-    /// stored in the first few bytes.
+    /// The first page is the entry in the "variants" CF, and the subsequent pages are stored in the
+    /// "variant_pages" CF, numbered sequentially from 1.
     #[inline(always)]
-    pub fn keys_count_in_group(&self, key_group : KeyGroupID) -> Result<usize, String> {
+    fn visit_variant_pages<F : FnMut(VariantEntryIter<'_>)>(&self, variant : &[u8], visitor_closure : &mut F) -> Result<(), String> {
 
-        let keys_cf_handle = self.db.cf_handle(KEYS_CF_NAME).unwrap();
-        if let Some(keys_vec_bytes) = self.db.get_pinned_cf(keys_cf_handle, key_group.to_le_bytes())? {
+        if self.config.variant_page_size == 0 {
+            return Ok(());
+        }
 
-            //The vector element count should be the first encoded usize
-            let mut skip_bytes = 0;
-            let keys_count = bincode_u64_le_varint(&keys_vec_bytes, &mut skip_bytes);
+        let variant_pages_cf_handle = &self.cf_handle(VARIANT_PAGES_CF_NAME)?;
+        let mut page_idx = 1;
+        while let Some(page_bytes) = self.db.get_pinned_cf(variant_pages_cf_handle, variant_page_key(variant, page_idx))? {
 
-            Ok(keys_count as usize)
-        } else {
-            panic!(); //If we hit this, we have a corrupt DB
+            visitor_closure(self.decode_variant_entry(VARIANT_PAGES_CF_NAME, &variant_page_key(variant, page_idx), &page_bytes)?);
+            page_idx += 1;
         }
+
+        Ok(())
     }
 
-    /// Creates entries in the keys table.  If we are updating an old record, we will overwrite it.
-    /// 
-    /// NOTE: This function will NOT update any variants used to locate the key
-    pub fn put_key_group_entry<K : Eq + Hash + Serialize>(&mut self, key_group_id : KeyGroupID, raw_keys : &HashSet<K>) -> Result<(), String> {
-        
-        //Serialize the keys into a vec of bytes
-        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
-        let keys_bytes = record_coder.serialize(&raw_keys).unwrap();
+    /// Visits every variant that begins with the supplied prefix, including the prefix itself, and executes a
+    /// closure for each of them, in the same way as [visit_exact_variant](Self::visit_exact_variant)
+    ///
+    /// NOTE: The "variants" CF is ordered by the variant bytes, so the variants that share a prefix are adjacent
+    /// and we can seek to the first one.  We can't use a RocksDB prefix extractor, because the prefixes we search
+    /// for don't have a fixed length
+    pub fn visit_variants_with_prefix<F : FnMut(VariantEntryIter<'_>)>(&self, prefix : &[u8], mut visitor_closure : F) -> Result<(), String> {
 
-        //Put the vector of keys into the keys table
-        let keys_cf_handle = self.db.cf_handle(KEYS_CF_NAME).unwrap();
-        self.db.put_cf(keys_cf_handle, key_group_id.to_le_bytes(), keys_bytes)?;
+        if self.keys_cipher().is_some() {
+            return Err("prefix lookups aren't available when the keys are encrypted".to_string());
+        }
+
+        let variants_cf_handle = &self.cf_handle(VARIANTS_CF_NAME)?;
+        for (variant, variant_vec_bytes) in self.db.iterator_cf(variants_cf_handle, IteratorMode::From(prefix, rocksdb::Direction::Forward)) {
+            if !variant.starts_with(prefix) {
+                break;
+            }
+
+            visitor_closure(self.decode_variant_entry(VARIANTS_CF_NAME, &variant, &variant_vec_bytes)?);
+            self.visit_variant_pages(&variant, &mut visitor_closure)?;
+        }
 
         Ok(())
     }
 
-    /// Deletes a key group entry from the db.  Does not clean up variants that may reference
-    /// the key group, so must be called as part of another operation
-    pub fn delete_key_group_entry(&mut self, key_group : KeyGroupID) -> Result<(), String> {
-        
-        let keys_cf_handle = self.db.cf_handle(KEYS_CF_NAME).unwrap();
-        self.db.delete_cf(keys_cf_handle, key_group.to_le_bytes())?;
+    /// Executes a provided closure for every variant entry in the database, including each page of any
+    /// paged entries.  The closure gets the variant and an iterator over the entry's KeyGroupIDs
+    ///
+    /// NOTE: This scans the whole "variants" CF, so it is intended for maintenance operations, not lookups
+    pub fn visit_all_variants<F : FnMut(&[u8], VariantEntryIter<'_>)>(&self, mut visitor_closure : F) -> Result<(), String> {
+
+        let variants_cf_handle = &self.cf_handle(VARIANTS_CF_NAME)?;
+        for (variant, variant_vec_bytes) in self.db.iterator_cf(variants_cf_handle, IteratorMode::Start) {
+            let key_group_ids = self.decode_variant_entry(VARIANTS_CF_NAME, &variant, &variant_vec_bytes)?;
+            visitor_closure(&self.decrypt_entry(self.keys_cipher(), VARIANTS_CF_NAME, &variant, &variant)?, key_group_ids);
+        }
+
+        let variant_pages_cf_handle = &self.cf_handle(VARIANT_PAGES_CF_NAME)?;
+        for (page_key, page_bytes) in self.db.iterator_cf(variant_pages_cf_handle, IteratorMode::Start) {
+            if page_key.len() < 4 {
+                return Err(self.corrupt_entry_error(VARIANT_PAGES_CF_NAME, &page_key, "page key is missing the page number"));
+            }
+            let key_group_ids = self.decode_variant_entry(VARIANT_PAGES_CF_NAME, &page_key, &page_bytes)?;
+            visitor_closure(&self.decrypt_entry(self.keys_cipher(), VARIANT_PAGES_CF_NAME, &page_key, &page_key[..page_key.len()-4])?, key_group_ids);
+        }
 
         Ok(())
     }
 
-    /// Returns the value associated with the specified record
-    #[inline(always)]
-    pub fn get_value<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, record_id : RecordID) -> Result<ValueT, String> {
+    /// Scans the "rec_data", "keys", and "values" CFs, decoding every entry, and returns what was found so the
+    /// CFs can be checked against each other
+    ///
+    /// Entries that can't be decoded are returned in the [EntryScan], rather than as an error, and are never
+    /// quarantined.
+    ///
+    /// NOTE: This scans all three CFs in full, so it is intended for maintenance operations
+    pub fn scan_entries<OwnedKeyT, ValueT>(&self) -> Result<EntryScan, String>
+        where
+        OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned,
+        ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    {
+        let mut scan = EntryScan::default();
+        let entry_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+
+        let rec_data_cf_handle = &self.cf_handle(RECORD_DATA_CF_NAME)?;
+        for (key, rec_data_bytes) in self.db.iterator_cf(rec_data_cf_handle, IteratorMode::Start) {
+            let record_id = match <[u8; 8]>::try_from(key.as_ref()) {
+                Ok(record_id_bytes) => RecordID::from(usize::from_le_bytes(record_id_bytes)),
+                Err(err) => {
+                    scan.corrupt_entries.push((RECORD_DATA_CF_NAME, key.to_vec(), err.to_string()));
+                    continue;
+                }
+            };
+            let rec_data_bytes = match self.scan_checked_entry(&rec_data_bytes, record_id) {
+                Ok(rec_data_bytes) => rec_data_bytes,
+                Err(err) => {
+                    scan.corrupt_entries.push((RECORD_DATA_CF_NAME, key.to_vec(), err));
+                    continue;
+                }
+            };
+            let rec_data : RecordData = match entry_coder.allow_trailing_bytes().deserialize(rec_data_bytes) {
+                Ok(rec_data) => rec_data,
+                Err(err) => {
+                    scan.corrupt_entries.push((RECORD_DATA_CF_NAME, key.to_vec(), err.to_string()));
+                    continue;
+                }
+            };
+            let rec_data_len = entry_coder.serialized_size(&rec_data).unwrap() as usize;
+            if rec_data_bytes.len() > rec_data_len {
+                scan.values.insert(record_id);
+                if let Err(err) = self.scan_value::<ValueT>(&rec_data_bytes[rec_data_len..]) {
+                    scan.corrupt_entries.push((RECORD_DATA_CF_NAME, key.to_vec(), err));
+                }
+            }
+            scan.records.insert(record_id, rec_data.key_groups.into_iter().map(|group_idx| KeyGroupID::from_record_and_idx(record_id, group_idx)).collect());
+        }
 
-        //Get the value object by deserializing the bytes from the db
-        let values_cf_handle = self.db.cf_handle(VALUES_CF_NAME).unwrap();
-        if let Some(value_bytes) = self.db.get_pinned_cf(values_cf_handle, record_id.to_le_bytes())? {
-            let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
-            let value : ValueT = record_coder.deserialize(&value_bytes).unwrap();
+        let keys_cf_handle = &self.cf_handle(KEYS_CF_NAME)?;
+        for (key, keys_bytes) in self.db.iterator_cf(keys_cf_handle, IteratorMode::Start) {
+            let key_group_id = match <[u8; 8]>::try_from(key.as_ref()) {
+                Ok(key_group_bytes) => KeyGroupID::from(usize::from_le_bytes(key_group_bytes)),
+                Err(err) => {
+                    scan.corrupt_entries.push((KEYS_CF_NAME, key.to_vec(), err.to_string()));
+                    continue;
+                }
+            };
+            scan.key_groups.insert(key_group_id);
+            let keys_bytes = match self.scan_checked_entry(&keys_bytes, key_group_id.record_id()) {
+                Ok(keys_bytes) => keys_bytes,
+                Err(err) => {
+                    scan.corrupt_entries.push((KEYS_CF_NAME, key.to_vec(), err));
+                    continue;
+                }
+            };
+            let keys_bytes = match self.keys_cipher().map(|cipher| cipher.decrypt(keys_bytes)) {
+                Some(Ok(keys_bytes)) => Cow::Owned(keys_bytes),
+                Some(Err(err)) => {
+                    scan.corrupt_entries.push((KEYS_CF_NAME, key.to_vec(), format!("failed to decrypt entry: {}", err)));
+                    continue;
+                },
+                None => Cow::Borrowed(keys_bytes)
+            };
+            match entry_coder.deserialize::<Vec<OwnedKeyT>>(&keys_bytes) {
+                Ok(keys) if keys.is_empty() => scan.corrupt_entries.push((KEYS_CF_NAME, key.to_vec(), "key group has no keys".to_string())),
+                Ok(_) => {},
+                Err(err) => scan.corrupt_entries.push((KEYS_CF_NAME, key.to_vec(), err.to_string())),
+            }
+        }
 
-            Ok(value)
-        } else {
-            Err("Invalid record_id".to_string())
+        let values_cf_handle = &self.cf_handle(VALUES_CF_NAME)?;
+        for (key, value_bytes) in self.db.iterator_cf(values_cf_handle, IteratorMode::Start) {
+            let record_id = match <[u8; 8]>::try_from(key.as_ref()) {
+                Ok(record_id_bytes) => RecordID::from(usize::from_le_bytes(record_id_bytes)),
+                Err(err) => {
+                    scan.corrupt_entries.push((VALUES_CF_NAME, key.to_vec(), err.to_string()));
+                    continue;
+                }
+            };
+            scan.values.insert(record_id);
+            let value_bytes = match self.scan_checked_entry(&value_bytes, record_id) {
+                Ok(value_bytes) => value_bytes,
+                Err(err) => {
+                    scan.corrupt_entries.push((VALUES_CF_NAME, key.to_vec(), err));
+                    continue;
+                }
+            };
+            if let Err(err) = self.scan_value::<ValueT>(value_bytes) {
+                scan.corrupt_entries.push((VALUES_CF_NAME, key.to_vec(), err));
+            }
         }
-    }
 
-    /// Deletes a record's value in the values table
-    /// 
-    /// This should only be called as part of another operation as it leaves the record in an
-    /// inconsistent state
-    pub fn delete_value(&mut self, record_id : RecordID) -> Result<(), String> {
+        //The chunks of each value are adjacent, and in order, so each value is decoded once its last chunk is found
+        let blobs_cf_handle = &self.cf_handle(BLOBS_CF_NAME)?;
+        //NOTE: Each of a record's slots is scanned as a separate value, and a slot without its first chunk is
+        // left over from a value that failed to stream in
+        let mut blob : Option<(RecordID, u32, Vec<u8>, Result<(), String>)> = None;
+        let finish_blob = |scan : &mut EntryScan, blob : Option<(RecordID, u32, Vec<u8>, Result<(), String>)>| {
+            if let Some((record_id, first_chunk_idx, blob_bytes, result)) = blob {
+                scan.values.insert(record_id);
+                let result = match first_chunk_idx & !BLOB_SLOT_BIT {
+                    0 => result.and_then(|()| self.scan_value::<ValueT>(&blob_bytes).map(|_| ())),
+                    _ => Err("value chunks are missing their first chunk".to_string())
+                };
+                if let Err(err) = result {
+                    scan.corrupt_entries.push((BLOBS_CF_NAME, blob_chunk_key(record_id, first_chunk_idx).to_vec(), err));
+                }
+            }
+        };
+        for (key, chunk_bytes) in self.db.iterator_cf(blobs_cf_handle, IteratorMode::Start) {
+            let (record_id, chunk_idx) = match (<[u8; 8]>::try_from(&key[..key.len().min(8)]), key.get(8..).and_then(|idx_bytes| <[u8; 4]>::try_from(idx_bytes).ok())) {
+                (Ok(record_id_bytes), Some(chunk_idx_bytes)) => (RecordID::from(usize::from_le_bytes(record_id_bytes)), u32::from_be_bytes(chunk_idx_bytes)),
+                _ => {
+                    scan.corrupt_entries.push((BLOBS_CF_NAME, key.to_vec(), "blob chunk key isn't 12 bytes".to_string()));
+                    continue;
+                }
+            };
+            if blob.as_ref().map(|(blob_record_id, first_chunk_idx, _, _)| (*blob_record_id, first_chunk_idx & BLOB_SLOT_BIT)) != Some((record_id, chunk_idx & BLOB_SLOT_BIT)) {
+                finish_blob(&mut scan, blob.take());
+                blob = Some((record_id, chunk_idx, vec![], Ok(())));
+            }
+            let (_, _, blob_bytes, result) = blob.as_mut().unwrap();
+            match self.scan_checked_entry(&chunk_bytes, record_id) {
+                Ok(chunk_bytes) => blob_bytes.extend_from_slice(chunk_bytes),
+                Err(err) => *result = Err(err),
+            }
+        }
+        finish_blob(&mut scan, blob);
 
-        let value_cf_handle = self.db.cf_handle(VALUES_CF_NAME).unwrap();
-        self.db.delete_cf(value_cf_handle, record_id.to_le_bytes())?;
+        Ok(scan)
+    }
 
-        Ok(())
+    /// Decrypts and decodes a value encountered by [scan_entries](Self::scan_entries), without quarantining it
+    fn scan_value<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, value_bytes : &[u8]) -> Result<ValueT, String> {
+        let value_bytes = match self.cipher() {
+            Some(cipher) => Cow::Owned(cipher.decrypt(value_bytes).map_err(|err| format!("failed to decrypt entry: {}", err))?),
+            None => Cow::Borrowed(value_bytes)
+        };
+        let entry_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
+        entry_coder.deserialize(&value_bytes).map_err(|err| err.to_string())
     }
 
-    /// Creates entries in the values table
-    /// If we are updating an old record, we will overwrite it.
-    /// 
-    /// NOTE: This function will NOT update any variants used to locate the key
-    pub fn put_value<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&mut self, record_id : RecordID, value : &ValueT) -> Result<(), String> {
-        
-        //Serialize the value and put it in the values table.
-        let value_cf_handle = self.db.cf_handle(VALUES_CF_NAME).unwrap();
-        let record_coder = bincode::DefaultOptions::new().with_varint_encoding().with_little_endian();
-        let value_bytes = record_coder.serialize(value).unwrap();
-        self.db.put_cf(value_cf_handle, record_id.to_le_bytes(), value_bytes)?;
+    /// Checks the checksum of an entry encountered by [scan_entries](Self::scan_entries), without quarantining
+    /// the entry, and returns the entry without it
+    fn scan_checked_entry<'b>(&self, entry_bytes : &'b [u8], record_id : RecordID) -> Result<&'b [u8], String> {
+        if !self.config.checksums {
+            return Ok(entry_bytes);
+        }
+        match checked_entry_len(entry_bytes) {
+            Some(entry_len) => Ok(&entry_bytes[..entry_len]),
+            None => Err(format!("Corruption: checksum mismatch in the entry for record {}", record_id))
+        }
+    }
 
-        Ok(())
+    /// Returns the KeyGroupIDs of every entry in the "keys" CF
+    ///
+    /// NOTE: This scans the whole "keys" CF, so it is intended for maintenance operations
+    pub fn key_group_ids(&self) -> Result<HashSet<KeyGroupID>, String> {
+
+        let keys_cf_handle = &self.cf_handle(KEYS_CF_NAME)?;
+        let mut key_group_ids = HashSet::new();
+        for (key_group_bytes, _keys_bytes) in self.db.iterator_cf(keys_cf_handle, IteratorMode::Start) {
+            let key_group_bytes : [u8; 8] = match key_group_bytes.as_ref().try_into() {
+                Ok(key_group_bytes) => key_group_bytes,
+                Err(err) => return Err(self.corrupt_entry_error(KEYS_CF_NAME, &key_group_bytes, err))
+            };
+            key_group_ids.insert(KeyGroupID::from(usize::from_le_bytes(key_group_bytes)));
+        }
+
+        Ok(key_group_ids)
     }
 
-    /// Executes a provided closure for every variant entry that exists from the provided set
-    /// 
-    /// NOTE: The closure gets the raw entry bytes, rather than the parsed KeyGroupIDs
-    /// because sometimes we don't want to parse the whole entry.  Also it takes a set of variants
-    /// so we don't need to create the CFHandle every time as we would with a simple "get_variant"
-    /// function
+    /// Checks that the bytes of a variant entry are a well-formed Vec of KeyGroupIDs, and returns an iterator
+    /// over them
     #[inline(always)]
-    pub fn visit_variants<F : FnMut(&[u8])>(&self, variants : HashSet<Vec<u8>>, mut visitor_closure : F) -> Result<(), String> {
+    fn decode_variant_entry<'a>(&self, cf_name : &'static str, key : &[u8], entry_bytes : &'a [u8]) -> Result<VariantEntryIter<'a>, String> {
+        let key_group_ids = match bincode_vec_iter::<KeyGroupID>(entry_bytes) {
+            Ok(key_group_id_bytes_iter) => key_group_id_bytes_iter.map(key_group_id_from_bytes as fn(&[u8]) -> KeyGroupID),
+            Err(err) => return Err(self.corrupt_entry_error(cf_name, key, err))
+        };
+
+        if self.config.strict_decoding && !all_unique(key_group_ids.clone()) {
+            return Err(self.corrupt_entry_error(cf_name, key, "duplicate KeyGroupID"));
+        }
 
-        let variants_cf_handle = self.db.cf_handle(VARIANTS_CF_NAME).unwrap();
-        for variant in variants {
+        Ok(key_group_ids)
+    }
+
+    /// Returns the KeyGroupIDs in every page of a variant entry, as they would be after the supplied batch
+    /// were committed.  Returns an empty Vec if the variant has no entry
+    fn get_variant_pages_in_batch(&self, batch : &DBWriteBatch, variant : &[u8]) -> Result<Vec<Vec<KeyGroupID>>, String> {
+
+        let mut pages = vec![];
+        while let Some(page) = self.get_variant_page_in_batch(batch, variant, pages.len())? {
+            pages.push(page);
+        }
+
+        Ok(pages)
+    }
+
+    /// Returns the KeyGroupIDs in a single page of a variant entry, as they would be after the supplied batch
+    /// were committed, or `None` if there is no such page.  Page 0 is the entry in the "variants" CF
+    fn get_variant_page_in_batch(&self, batch : &DBWriteBatch, variant : &[u8], page_idx : usize) -> Result<Option<Vec<KeyGroupID>>, String> {
+
+        let (cf_name, page_key) = match page_idx {
+            0 => (VARIANTS_CF_NAME, variant.to_vec()),
+            _ => (VARIANT_PAGES_CF_NAME, variant_page_key(variant, page_idx))
+        };
+        match self.get_cf_in_batch(batch, cf_name, &page_key)? {
+            Some(page_bytes) => Ok(Some(self.decode_variant_entry(cf_name, &page_key, &page_bytes)?.collect())),
+            None => Ok(None)
+        }
+    }
 
-            // See if we have an entry in the "variants" database for the supplied key variant
-            if let Some(variant_vec_bytes) = self.db.get_pinned_cf(variants_cf_handle, variant)? {
+    /// Returns the number of pages in a variant entry, as it would be after the supplied batch were committed
+    ///
+    /// The pages are numbered without gaps, so rather than reading every page, the count is found by probing for
+    /// pages at doubling intervals, and then bisecting the interval where the pages run out
+    fn variant_page_count_in_batch(&self, batch : &DBWriteBatch, variant : &[u8]) -> Result<usize, String> {
 
-                visitor_closure(&variant_vec_bytes);
+        if self.get_cf_in_batch(batch, VARIANTS_CF_NAME, variant)?.is_none() {
+            return Ok(0);
+        }
+        let page_exists = |page_idx : usize| -> Result<bool, String> {
+            Ok(self.get_cf_in_batch(batch, VARIANT_PAGES_CF_NAME, &variant_page_key(variant, page_idx))?.is_some())
+        };
+
+        //The page at `present` always exists, and the page at `absent` never does
+        let mut present = 0;
+        let mut absent = 1;
+        while page_exists(absent)? {
+            present = absent;
+            absent *= 2;
+        }
+        while absent - present > 1 {
+            let page_idx = present + (absent - present) / 2;
+            if page_exists(page_idx)? {
+                present = page_idx;
+            } else {
+                absent = page_idx;
             }
         }
 
-        Ok(())
+        Ok(absent)
     }
 
-    /// Visits the only the exact variant specified from the database and executes a closure.
-    #[inline(always)]
-    pub fn visit_exact_variant<F : FnMut(&[u8])>(&self, variant : &[u8], mut visitor_closure : F) -> Result<(), String> {
+    /// Writes a single page of a variant entry.  Page 0 is the entry in the "variants" CF
+    fn put_variant_page(&self, batch : &mut DBWriteBatch, variant : &[u8], page_idx : usize, key_groups : &[KeyGroupID]) {
 
-        let variants_cf_handle = self.db.cf_handle(VARIANTS_CF_NAME).unwrap();
-        if let Some(variant_vec_bytes) = self.db.get_pinned_cf(variants_cf_handle, variant)? {
+        let vec_coder = bincode::DefaultOptions::new().with_fixint_encoding().with_little_endian();
+        let page_bytes = vec_coder.serialize(key_groups).unwrap();
+        if page_idx == 0 {
+            batch.put(VARIANTS_CF_NAME, variant, page_bytes);
+        } else {
+            batch.put(VARIANT_PAGES_CF_NAME, &variant_page_key(variant, page_idx), page_bytes);
+        }
+    }
 
-            visitor_closure(&variant_vec_bytes);
+    /// Deletes a single page of a variant entry.  Page 0 is the entry in the "variants" CF
+    fn delete_variant_page(&self, batch : &mut DBWriteBatch, variant : &[u8], page_idx : usize) {
+
+        if page_idx == 0 {
+            batch.delete(VARIANTS_CF_NAME, variant);
+        } else {
+            batch.delete(VARIANT_PAGES_CF_NAME, &variant_page_key(variant, page_idx));
+        }
+    }
+
+    /// Adds a reference to a key group to a paged variant entry, appending it to the last page, or to a new page
+    /// if the last page is full
+    ///
+    /// NOTE: Only the last page is read, so, just like a merge into an unpaged entry, the key group must not
+    /// already be referenced by the entry
+    fn add_paged_variant_reference(&self, batch : &mut DBWriteBatch, key_group : KeyGroupID, variant : &[u8]) -> Result<(), String> {
+
+        let page_count = self.variant_page_count_in_batch(batch, variant)?;
+        let (page_idx, mut page) = match page_count.checked_sub(1) {
+            Some(last_page_idx) => {
+                let last_page = self.get_variant_page_in_batch(batch, variant, last_page_idx)?.unwrap_or_default();
+                if last_page.len() < self.config.variant_page_size {
+                    (last_page_idx, last_page)
+                } else {
+                    (page_count, vec![])
+                }
+            },
+            None => (0, vec![])
+        };
+        page.push(key_group);
+        self.put_variant_page(batch, variant, page_idx, &page);
+
+        Ok(())
+    }
+
+    /// Removes a reference to a key group from a paged variant entry
+    /// 
+    /// If that leaves the page empty, the last page is moved into its place so there are no gaps in the
+    /// page numbers.  If the last page is emptied, the variant entry is deleted.
+    fn delete_paged_variant_reference(&self, batch : &mut DBWriteBatch, key_group : KeyGroupID, variant : &[u8]) -> Result<(), String> {
+
+        let mut pages = self.get_variant_pages_in_batch(batch, variant)?;
+        let old_page_count = pages.len();
+        for page_idx in 0..old_page_count {
+            if let Some(ref_idx) = pages[page_idx].iter().position(|other_key_group| *other_key_group == key_group) {
+
+                pages[page_idx].swap_remove(ref_idx);
+                if pages[page_idx].is_empty() {
+                    pages.swap_remove(page_idx);
+                }
+
+                if page_idx < pages.len() {
+                    self.put_variant_page(batch, variant, page_idx, &pages[page_idx]);
+                }
+                if pages.len() < old_page_count {
+                    self.delete_variant_page(batch, variant, old_page_count - 1);
+                }
+                break;
+            }
         }
 
         Ok(())
@@ -286,31 +2810,47 @@ impl DBConnection {
     /// Deletes references to a specified key group from a number of specified variant entries.
     /// 
     /// If the variant references no key groups after deletion then the variant entry is deleted
-    pub fn delete_variant_references(&mut self, key_group : KeyGroupID, variants : HashSet<Vec<u8>>) -> Result<(), String> {
-        
+    /// 
+    /// NOTE: The variant entries are read through the batch, because several key groups in the same
+    /// operation may reference the same variant
+    pub fn delete_variant_references(&self, batch : &mut DBWriteBatch, key_group : KeyGroupID, variants : HashSet<Vec<u8>>) -> Result<(), String> {
+
+        let variants = self.index_keys(variants);
+        if self.config.variant_page_size > 0 {
+            for variant in variants.iter() {
+                self.delete_paged_variant_reference(batch, key_group, variant)?;
+            }
+            return Ok(());
+        }
+
         //Loop over each variant, and remove the KeyGroupID from its associated variant entry in
         // the database, and remove the variant entry if it only referenced the key_group we're removing
-        let variants_cf_handle = self.db.cf_handle(VARIANTS_CF_NAME).unwrap();
+        //NOTE: During a bulk load, the reference may also be buffered, or only buffered
+        let bulk_loading = self.is_bulk_loading();
         for variant in variants.iter() {
 
-            if let Some(variant_entry_bytes) = self.db.get_pinned_cf(variants_cf_handle, variant)? {
+            if bulk_loading {
+                batch.bulk_variant_refs.push((variant.clone(), key_group, false));
+            }
+
+            if let Some(variant_entry_bytes) = self.get_cf_in_batch(batch, VARIANTS_CF_NAME, variant)? {
 
-                let variant_entry_len = bincode_vec_fixint_len(&variant_entry_bytes);
+                let key_group_ids = self.decode_variant_entry(VARIANTS_CF_NAME, variant, &variant_entry_bytes)?;
+                let variant_entry_len = key_group_ids.len();
 
                 //If the variant entry references more than one record, rebuild it with our records absent
                 if variant_entry_len > 1 {
                     let mut new_vec : Vec<KeyGroupID> = Vec::with_capacity(variant_entry_len-1);
-                    for key_group_id_bytes in bincode_vec_iter::<KeyGroupID>(&variant_entry_bytes) {
-                        let other_key_group_id = KeyGroupID::from(usize::from_le_bytes(key_group_id_bytes.try_into().unwrap()));
+                    for other_key_group_id in key_group_ids {
                         if other_key_group_id != key_group {
                             new_vec.push(other_key_group_id);
                         }
                     }
                     let vec_coder = bincode::DefaultOptions::new().with_fixint_encoding().with_little_endian();
-                    self.db.put_cf(variants_cf_handle, variant, vec_coder.serialize(&new_vec).unwrap())?;
+                    batch.put(VARIANTS_CF_NAME, variant, vec_coder.serialize(&new_vec).unwrap());
                 } else {
                     //Otherwise, remove the variant entry entirely
-                    self.db.delete_cf(variants_cf_handle, variant)?;
+                    batch.delete(VARIANTS_CF_NAME, variant);
                 }
             }
         }
@@ -318,8 +2858,18 @@ impl DBConnection {
         Ok(())
     }
 
+    /// Returns the keys the supplied variants' entries are stored under.  See [index_key](Self::index_key)
+    fn index_keys(&self, variants : HashSet<Vec<u8>>) -> HashSet<Vec<u8>> {
+        match self.keys_cipher() {
+            Some(cipher) => variants.iter().map(|variant| cipher.encrypt_deterministic(variant)).collect(),
+            None => variants
+        }
+    }
+
     /// Adds the KeyGroupID to each of the supplied variants
-    pub fn put_variant_references(&mut self, key_group : KeyGroupID, variants : HashSet<Vec<u8>>) -> Result<(), String> {
+    /// 
+    /// NOTE: The KeyGroupID must not already be referenced by any of the variants' entries
+    pub fn put_variant_references(&self, batch : &mut DBWriteBatch, key_group : KeyGroupID, variants : HashSet<Vec<u8>>) -> Result<(), String> {
 
         // Creates a Vec<KeyGroupID> with one entry, serialized out as a string of bytes
         fn new_variant_vec(key_group : KeyGroupID) -> Vec<u8> {
@@ -330,12 +2880,26 @@ impl DBConnection {
             vec_coder.serialize(&new_vec).unwrap()
         }
 
+        let variants = self.index_keys(variants);
+
+        //If variant entries are paged, the new reference goes on the last page
+        if self.config.variant_page_size > 0 {
+            for variant in variants {
+                self.add_paged_variant_reference(batch, key_group, &variant)?;
+            }
+            return Ok(());
+        }
+
+        //During a bulk load, the references are buffered until the bulk load is finished
+        if self.is_bulk_loading() {
+            batch.bulk_variant_refs.extend(variants.into_iter().map(|variant| (variant, key_group, true)));
+            return Ok(());
+        }
+
         //Add the key_group to each variant
-        let variants_cf_handle = self.db.cf_handle(VARIANTS_CF_NAME).unwrap();
         for variant in variants {
             //TODO: Benchmark using merge_cf() against using a combination of get_pinned_cf() and put_cf()
-            let val_bytes = new_variant_vec(key_group);
-            self.db.merge_cf(variants_cf_handle, variant, val_bytes)?;
+            batch.merge_variant(VARIANTS_CF_NAME, variant, new_variant_vec(key_group))?;
         }
 
         Ok(())
@@ -343,52 +2907,265 @@ impl DBConnection {
 
 }
 
+/// A consistent view of the database at the moment it was taken, which isn't affected by any writes made
+/// afterwards
+pub struct DBSnapshot<'a> {
+    db : &'a DBConnection,
+    snapshot : rocksdb::SnapshotWithThreadMode<'a, DBWithThreadMode<MultiThreaded>>,
+}
+
+impl <'a>DBSnapshot<'a> {
+
+    /// Returns the indices of a record's key groups, an empty Vec if the record was deleted, or `None` if there
+    /// was no record with the RecordID when the snapshot was taken
+    pub fn get_record_key_groups(&self, record_id : RecordID) -> Result<Option<Vec<KeyGroupID>>, String> {
+
+        let rec_data_cf_handle = &self.db.cf_handle(RECORD_DATA_CF_NAME)?;
+        match self.snapshot.get_cf(rec_data_cf_handle, record_id.to_le_bytes())? {
+            Some(rec_data_vec_bytes) => {
+                let (rec_data, _inline_value) = self.db.decode_rec_data(record_id, &rec_data_vec_bytes)?;
+                Ok(Some(rec_data.key_groups.into_iter().map(|group_idx| KeyGroupID::from_record_and_idx(record_id, group_idx)).collect()))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Returns the RecordID of every record in the snapshot, including deleted records, in ascending order
+    pub fn record_ids(&self) -> Result<Vec<RecordID>, String> {
+
+        let rec_data_cf_handle = &self.db.cf_handle(RECORD_DATA_CF_NAME)?;
+        let mut record_ids = vec![];
+        for (record_id_bytes, _) in self.snapshot.iterator_cf(rec_data_cf_handle, IteratorMode::Start) {
+            let record_id_bytes : [u8; 8] = (*record_id_bytes).try_into()
+                .map_err(|_| self.db.corrupt_entry_error(RECORD_DATA_CF_NAME, &record_id_bytes, "RecordID isn't 8 bytes"))?;
+            record_ids.push(RecordID::from(usize::from_le_bytes(record_id_bytes)));
+        }
+
+        //NOTE: The entries are ordered by their little-endian keys, which isn't the order of the RecordIDs
+        record_ids.sort();
+        Ok(record_ids)
+    }
+
+    /// Returns the keys in a single key group
+    pub fn get_keys_in_group<OwnedKeyT : 'static + Sized + Serialize + serde::de::DeserializeOwned>(&self, key_group : KeyGroupID, perf_counters : &PerfCounters) -> Result<impl Iterator<Item=OwnedKeyT>, String> {
+
+        let keys_cf_handle = &self.db.cf_handle(KEYS_CF_NAME)?;
+        let keys = match self.snapshot.get_cf(keys_cf_handle, key_group.to_le_bytes())? {
+            Some(keys_vec_bytes) => self.db.decode_keys_in_group(key_group, &keys_vec_bytes, perf_counters)?,
+            None => None
+        };
+        keys.ok_or_else(|| "Invalid record_id".to_string())
+    }
+
+    /// Returns the value associated with a record
+    pub fn get_value<ValueT : 'static + Serialize + serde::de::DeserializeOwned>(&self, record_id : RecordID) -> Result<ValueT, String> {
+
+        self.db.find_value(record_id, |cf_name, key| {
+            let cf_handle = &self.db.cf_handle(cf_name)?;
+            Ok(self.snapshot.get_cf(cf_handle, key)?)
+        })
+    }
+}
+
 impl Drop for DBConnection {
     fn drop(&mut self) {
-        //Close down Rocks
-        self.db.flush().unwrap();
-        let _ = DB::destroy(&rocksdb::Options::default(), self.path.as_str());
+        //Close down Rocks.  The database files are left in place, so the data persists
+        //NOTE: A bulk load must be finished, or the buffered variants, and the writes that skipped the log, are
+        // lost.  A Table finishes its bulk load before this, so this only catches a connection used on its own
+        if self.is_bulk_loading() {
+            if let Err(err) = self.finish_bulk_load() {
+                eprintln!("fuzzy_rocks: failed to finish the bulk load of {} when the database was closed: {}", self.path, err);
+            }
+        }
+        let _ = self.flush();
+        if let Some(shared) = &self.shared {
+            let name = self.cf_prefix.trim_end_matches(SHARED_CF_SEPARATOR);
+            shared.open_tables.lock().unwrap().remove(name);
+        }
+    }
+}
+
+/// Deletes the database at the specified path, along with all of its files
+/// 
+/// NOTE: The database must not be open, or this will fail
+pub fn destroy_database(path : &str) -> Result<(), String> {
+    DB::destroy(&rocksdb::Options::default(), native_path(path)?)?;
+    Ok(())
+}
+
+/// Returns the form of `path` that is handed to RocksDB and the file system
+/// 
+/// On Windows, the path is made absolute and given the `\\?\` prefix, so the database's files can be reached
+/// even when their full paths exceed `MAX_PATH` (260 characters).  Paths that already have the prefix, and device
+/// paths, are returned unchanged.
+#[cfg(windows)]
+pub fn native_path(path : &str) -> Result<String, String> {
+    const VERBATIM_PREFIX : &str = r"\\?\";
+    if path.starts_with(VERBATIM_PREFIX) || path.starts_with(r"\\.\") {
+        return Ok(path.to_string());
     }
+    let absolute = std::path::absolute(path).map_err(|err| format!("couldn't resolve database path {}: {}", path, err))?;
+    let absolute = absolute.to_str().ok_or_else(|| format!("database path {} isn't valid Unicode", path))?;
+    match absolute.strip_prefix(r"\\") {
+        Some(unc_path) => Ok(format!(r"{}UNC\{}", VERBATIM_PREFIX, unc_path)),
+        None => Ok(format!("{}{}", VERBATIM_PREFIX, absolute)),
+    }
+}
+
+/// Returns the form of `path` that is handed to RocksDB and the file system, which is `path` itself on this platform
+#[cfg(not(windows))]
+pub fn native_path(path : &str) -> Result<String, String> {
+    Ok(path.to_string())
+}
+
+/// Returns the descriptors for the named column families, configured the same way regardless of how
+/// the database is opened
+fn column_family_descriptors(config : &DBConfig, merge_diagnostics : &Arc<MergeDiagnosticsCollector>, block_cache : Option<&rocksdb::Cache>, cf_names : &[&str]) -> Vec<ColumnFamilyDescriptor> {
+    cf_names.iter().map(|cf_name| {
+        ColumnFamilyDescriptor::new(*cf_name, cf_options(config, merge_diagnostics, block_cache, cf_name))
+    }).collect()
+}
+
+// Returns the options for a column family, with the merge operator it needs, and the tuning from the RocksConfig
+fn cf_options(config : &DBConfig, merge_diagnostics : &Arc<MergeDiagnosticsCollector>, block_cache : Option<&rocksdb::Cache>, cf_name : &str) -> rocksdb::Options {
+
+    let mut cf_opts = match cf_name {
+        VARIANTS_CF_NAME => variants_cf_options(merge_diagnostics),
+        STATS_CF_NAME => stats_cf_options(),
+        _ => rocksdb::Options::default(),
+    };
+    let rocks_config = &config.rocks_config;
+    let is_variants_cf = cf_name == VARIANTS_CF_NAME || cf_name == VARIANT_PAGES_CF_NAME;
+    if block_cache.is_some() || is_variants_cf {
+        let mut block_opts = rocksdb::BlockBasedOptions::default();
+        if let Some(block_cache) = block_cache {
+            block_opts.set_block_cache(block_cache);
+        }
+        if is_variants_cf {
+            if rocks_config.variants_bloom_bits_per_key != 0 {
+                block_opts.set_bloom_filter(f64::from(rocks_config.variants_bloom_bits_per_key), false);
+            }
+            block_opts.set_whole_key_filtering(rocks_config.variants_whole_key_filtering);
+
+            //Without a block cache of their own size, the index and filter blocks would have to compete for RocksDB's
+            // small default cache
+            let cache_index_and_filter_blocks = rocks_config.variants_cache_index_and_filter_blocks && block_cache.is_some();
+            block_opts.set_cache_index_and_filter_blocks(cache_index_and_filter_blocks);
+            block_opts.set_pin_l0_filter_and_index_blocks_in_cache(cache_index_and_filter_blocks);
+        }
+        cf_opts.set_block_based_table_factory(&block_opts);
+    }
+    if rocks_config.write_buffer_size != 0 {
+        cf_opts.set_write_buffer_size(rocks_config.write_buffer_size);
+    }
+    let target_file_size = rocks_config.cf_target_file_size(cf_name);
+    if target_file_size != 0 {
+        cf_opts.set_target_file_size_base(target_file_size);
+    }
+    cf_opts
+}
+
+// Returns the options for the "variants" column family, whose merge operator appends KeyGroupIDs and reports
+// each invocation to `merge_diagnostics`
+fn variants_cf_options(merge_diagnostics : &Arc<MergeDiagnosticsCollector>) -> rocksdb::Options {
+    let mut variants_opts = rocksdb::Options::default();
+    variants_opts.create_if_missing(true);
+    let merge_diagnostics = merge_diagnostics.clone();
+    variants_opts.set_merge_operator_associative("append to RecordID vec", move |key : &[u8], existing_val : Option<&[u8]>, operands : &MergeOperands| {
+        variant_append_merge(key, existing_val, operands, &merge_diagnostics)
+    });
+    variants_opts
+}
+
+// Returns the options for the "stats" column family, which sums the hit counts as they are merged in
+fn stats_cf_options() -> rocksdb::Options {
+    let mut stats_opts = rocksdb::Options::default();
+    stats_opts.set_merge_operator_associative("add to access count", access_count_merge);
+    stats_opts
+}
+
+// Decodes a hit count from the "stats" CF, treating a malformed entry as zero because the counts are
+// only approximate anyway
+fn decode_access_count(count_bytes : &[u8]) -> u64 {
+    count_bytes.try_into().map(u64::from_le_bytes).unwrap_or(0)
+}
+
+// The function to sum hit counts in the "stats" CF, formulated as a RocksDB callback
+fn access_count_merge(_key: &[u8], existing_val: Option<&[u8]>, operands: &MergeOperands) -> Option<Vec<u8>> {
+
+    let mut count = existing_val.map(decode_access_count).unwrap_or(0);
+    for operand in operands {
+        count = count.saturating_add(decode_access_count(operand));
+    }
+
+    Some(count.to_le_bytes().to_vec())
 }
 
 // The function to add a new entry for a variant in the database, formulated as a RocksDB callback
-fn variant_append_merge(_key: &[u8], existing_val: Option<&[u8]>, operands: &MergeOperands) -> Option<Vec<u8>> {
+fn variant_append_merge(key: &[u8], existing_val: Option<&[u8]>, operands: &MergeOperands, merge_diagnostics: &MergeDiagnosticsCollector) -> Option<Vec<u8>> {
 
     // Note: I've seen this function be called at odd times by RocksDB, such as when a DB is
     // opened.  I haven't been able to get a straight answer on why RocksDB calls this function
-    // unnecessarily, but it doesn't seem to be hurting performance much.
+    // unnecessarily, but it doesn't seem to be hurting performance much.  The invocations are
+    // recorded in the merge diagnostics, so the behavior can be characterized.
+    merge_diagnostics.record_merge(key, existing_val.is_some(), operands.len());
+
+    //NOTE: Returning None tells RocksDB the merge failed, which surfaces as an error on the read,
+    // rather than a panic unwinding across the FFI boundary
+    let merged = variant_merge_entries(existing_val, operands.into_iter());
+    if merged.is_none() {
+        merge_diagnostics.record_failure(key, operands.len());
+    }
+    merged
+}
 
-    //TODO: Status prints in this function to understand the behavior of RocksDB.
-    // Remove them when this is understood.
-    // println!("Append-Called {:?}", std::str::from_utf8(key).unwrap());
-    let vec_coder = bincode::DefaultOptions::new().with_fixint_encoding().with_little_endian();
+// Merges the KeyGroupIDs from each of the operands into the existing variant entry, and returns the
+// serialized result.  Shared by the RocksDB merge callback and by reads through a [DBWriteBatch]
+//
+// Returns None if the existing entry or any of the operands is malformed
+fn variant_merge_entries<'a, OperandsIterT : Iterator<Item=&'a [u8]>>(existing_val: Option<&[u8]>, operands_iter: OperandsIterT) -> Option<Vec<u8>> {
 
-    let operands_iter = operands.into_iter();
+    let vec_coder = bincode::DefaultOptions::new().with_fixint_encoding().with_little_endian();
 
     //Deserialize the existing database entry into a vec of KeyGroupIDs
     //NOTE: we're actually using a HashSet because we don't want any duplicates
     let mut variant_vec = if let Some(existing_bytes) = existing_val {
-        let new_vec : HashSet<KeyGroupID> = vec_coder.deserialize(existing_bytes).unwrap();
+        let new_vec : HashSet<KeyGroupID> = bincode_vec_iter::<KeyGroupID>(existing_bytes).ok()?.map(key_group_id_from_bytes).collect();
         new_vec
     } else {
-        //TODO: Remove status println!()
-        // println!("MERGE WITH NONE!!");
         HashSet::with_capacity(operands_iter.size_hint().0)
     };
 
     //Add the new KeyGroupID(s)
     for op in operands_iter {
         //Deserialize the vec on the operand, and merge its entries into the existing vec
-        let operand_vec : HashSet<KeyGroupID> = vec_coder.deserialize(op).unwrap();
+        let operand_vec = bincode_vec_iter::<KeyGroupID>(op).ok()?.map(key_group_id_from_bytes);
         variant_vec.extend(operand_vec);
     }
 
-    //TODO: Remove status println!()
-    // println!("AppendResults {:?}", variant_vec);
-
     //Serialize the vec back out again
-    let result = vec_coder.serialize(&variant_vec).unwrap();
-    Some(result)
+    Some(vec_coder.serialize(&variant_vec).unwrap())
+}
+
+// Composes the key for a page of a variant entry in the "variant_pages" CF
+//
+// The page number is a fixed-length suffix, so keys for different variants can never collide
+fn variant_page_key(variant : &[u8], page_idx : usize) -> Vec<u8> {
+    let mut page_key = Vec::with_capacity(variant.len() + 4);
+    page_key.extend_from_slice(variant);
+    page_key.extend_from_slice(&(page_idx as u32).to_be_bytes());
+    page_key
+}
+
+// Returns true if no item appears more than once
+fn all_unique<T : Eq + Hash, I : Iterator<Item=T>>(items : I) -> bool {
+    let mut seen = HashSet::new();
+    items.into_iter().all(|item| seen.insert(item))
+}
+
+// Decodes one KeyGroupID from a variant entry.  The BinCodeVecIterator guarantees the slice is the right size
+fn key_group_id_from_bytes(key_group_id_bytes : &[u8]) -> KeyGroupID {
+    KeyGroupID::from(usize::from_le_bytes(key_group_id_bytes.try_into().unwrap()))
 }
 
 // Returns the usize that is one larger than the largest key, assuming the column family contains a
@@ -397,7 +3174,7 @@ fn variant_append_merge(_key: &[u8], existing_val: Option<&[u8]>, operands: &Mer
 // Implements a binary search through the possible keys, looking for the highest numbered key
 // This function should resolve one bit of the key, each time through the loop, so it should loop
 // at most 64 times for a 64 bit key, and likely much less because of the starting hint
-fn probe_for_max_sequential_key(db : &DBWithThreadMode<rocksdb::SingleThreaded>, cf : &ColumnFamily, starting_hint : usize) -> Result<usize, rocksdb::Error> {
+fn probe_for_max_sequential_key(db : &DBWithThreadMode<MultiThreaded>, cf : &impl AsColumnFamilyRef, starting_hint : usize) -> Result<usize, rocksdb::Error> {
 
     let mut min = 0;
     let mut max = usize::MAX;
@@ -443,4 +3220,98 @@ fn probe_for_max_sequential_key(db : &DBWithThreadMode<rocksdb::SingleThreaded>,
     }
 }
 
+/// Writes the bytes of a value that serializes as a sequence of bytes to a [Write], leaving out the length at
+/// the start of the serialized value, and checking it against the number of bytes that follow
+struct ValuePayloadWriter<'a, W : Write> {
+    writer : &'a mut W,
+    len_bytes : Vec<u8>,
+    len : Option<u64>,
+    written_len : u64,
+}
+
+impl <'a, W : Write>ValuePayloadWriter<'a, W> {
+
+    fn new(writer : &'a mut W) -> Self {
+        Self{writer, len_bytes : vec![], len : None, written_len : 0}
+    }
+
+    /// Writes the next bytes of the serialized value
+    fn write(&mut self, mut bytes : &[u8]) -> Result<(), String> {
+
+        //Collect the bytes of the length, which may be split across chunks
+        let len = match self.len {
+            Some(len) => len,
+            None => {
+                let len_bytes_len : usize = match self.len_bytes.first().or(bytes.first()) {
+                    Some(251) => 3,
+                    Some(252) => 5,
+                    Some(253) => 9,
+                    Some(254) => 17,
+                    Some(_) => 1,
+                    None => return Ok(())
+                };
+                let take_len = len_bytes_len.saturating_sub(self.len_bytes.len()).min(bytes.len());
+                self.len_bytes.extend_from_slice(&bytes[..take_len]);
+                bytes = &bytes[take_len..];
+                if self.len_bytes.len() < len_bytes_len {
+                    return Ok(());
+                }
+                let len = bincode_u64_le_varint(&self.len_bytes)?.0;
+                self.len = Some(len);
+                len
+            }
+        };
+
+        if self.written_len + bytes.len() as u64 > len {
+            return Err("the value doesn't serialize as a sequence of bytes".to_string());
+        }
+        self.writer.write_all(bytes).map_err(|err| format!("failed to write the value: {}", err))?;
+        self.written_len += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Returns the number of bytes written, or an error if the value ended before its length was reached
+    fn finish(self) -> Result<u64, String> {
+        match self.len {
+            Some(len) if len == self.written_len => Ok(len),
+            _ => Err("the value doesn't serialize as a sequence of bytes".to_string())
+        }
+    }
+}
+
+/// Returns the path of the SST file holding the entries of a column family, written by
+/// [export_sst_files](DBConnection::export_sst_files)
+fn sst_file_path(dir : &str, cf_name : &str) -> std::path::PathBuf {
+    std::path::Path::new(dir).join(format!("{}.sst", cf_name))
+}
+
+/// Returns the named column families that exist in the database at `path`, so a database created before some of
+/// the column families were added can be opened without creating them
+fn existing_cf_names<'a>(db_opts : &rocksdb::Options, path : &str, cf_names : &[&'a str]) -> Result<Vec<&'a str>, String> {
+    let existing_names = DB::list_cf(db_opts, native_path(path)?)?;
+    Ok(cf_names.iter().copied().filter(|cf_name| existing_names.iter().any(|existing_name| existing_name == cf_name)).collect())
+}
+
+/// The bit of a chunk index that selects which of a record's two slots in the "blobs" CF the chunk is in.  A value
+/// is streamed into the slot that isn't in use, and the slot that holds a first chunk is the one in use
+const BLOB_SLOT_BIT : u32 = 1 << 31;
+
+/// Returns the key of a chunk of a value in the "blobs" CF, which is the little-endian [RecordID] followed by the
+/// big-endian chunk index, so the chunks of a value are adjacent, and in order
+fn blob_chunk_key(record_id : RecordID, chunk_idx : u32) -> [u8; 12] {
+    let mut key = [0; 12];
+    key[..8].copy_from_slice(&record_id.to_le_bytes());
+    key[8..].copy_from_slice(&chunk_idx.to_be_bytes());
+    key
+}
 
+/// Returns the length of an entry without the CRC32 checksum at its end, or `None` if the checksum doesn't match
+fn checked_entry_len(entry_bytes : &[u8]) -> Option<usize> {
+    let entry_len = entry_bytes.len().checked_sub(4)?;
+    let checksum = u32::from_le_bytes(entry_bytes[entry_len..].try_into().unwrap());
+    if crc32fast::hash(&entry_bytes[..entry_len]) == checksum {
+        Some(entry_len)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,60 @@
+//!
+//! The DBProperties module contains the [DBProperties] and [CFProperties] structs, which hold a selection of
+//! the properties RocksDB reports about a Table's database.  The DBProperties and CFProperties structs are
+//! re-exported
+//!
+
+use super::database::RECORD_DATA_CF_NAME;
+
+/// A selection of the properties RocksDB reports about a Table's database, returned by
+/// [Table::db_properties](crate::Table::db_properties)
+///
+/// The properties are read when the DBProperties is returned, and don't follow later changes.  The key counts
+/// are RocksDB's estimates, which count overwritten and deleted entries that haven't been compacted yet, so
+/// they are good for rough sizing, but not for exact counts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DBProperties {
+    /// The properties of each open column family.  The column families are described in the crate documentation
+    pub column_families : Vec<CFProperties>,
+    /// The number of compactions that are currently running
+    pub num_running_compactions : u64,
+    /// The number of flushes that are currently running
+    pub num_running_flushes : u64,
+    /// The number of errors RocksDB has encountered in the background, e.g. during a flush or compaction
+    pub background_errors : u64,
+}
+
+/// The properties of a single column family, part of a [DBProperties]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CFProperties {
+    /// The name of the column family
+    pub name : &'static str,
+    /// RocksDB's estimate of the number of entries in the column family
+    pub estimate_num_keys : u64,
+    /// The size, in bytes, of the memtable that is currently being written
+    pub cur_size_active_mem_table : u64,
+    /// The size, in bytes, of all of the column family's memtables, including those waiting to be flushed
+    pub size_all_mem_tables : u64,
+    /// The number of memtables that are full, and waiting to be flushed
+    pub num_immutable_mem_table : u64,
+    /// RocksDB's estimate of the number of bytes compaction needs to rewrite to bring the levels down to size
+    pub estimate_pending_compaction_bytes : u64,
+}
+
+impl DBProperties {
+
+    /// Returns the properties of the named column family, or `None` if it isn't open
+    pub fn cf(&self, name : &str) -> Option<&CFProperties> {
+        self.column_families.iter().find(|cf_properties| cf_properties.name == name)
+    }
+
+    /// Returns RocksDB's estimate of the number of records in the Table, including deleted records
+    pub fn estimate_record_count(&self) -> u64 {
+        self.cf(RECORD_DATA_CF_NAME).map(|cf_properties| cf_properties.estimate_num_keys).unwrap_or(0)
+    }
+
+    /// Returns the total size, in bytes, of the memtables of all the column families
+    pub fn size_all_mem_tables(&self) -> u64 {
+        self.column_families.iter().map(|cf_properties| cf_properties.size_all_mem_tables).sum()
+    }
+}
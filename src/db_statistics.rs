@@ -0,0 +1,78 @@
+//!
+//! The DBStatistics module contains the [DBStatistics] struct, which holds the statistics RocksDB collects
+//! about a Table's database.  The DBStatistics struct is re-exported
+//!
+
+use std::collections::HashMap;
+
+/// The statistics RocksDB has collected since a Table's database was opened, returned by
+/// [Table::db_statistics](crate::Table::db_statistics)
+///
+/// RocksDB only collects statistics when [statistics](crate::RocksConfig::statistics) is set in the Table's
+/// [ROCKS_CONFIG](crate::TableConfig::ROCKS_CONFIG), since collecting them slows down every operation a little.
+/// Each counter, or "ticker", is named as it is in RocksDB, e.g. "rocksdb.block.cache.hit".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DBStatistics {
+    /// The value of each ticker, by name
+    pub tickers : HashMap<String, u64>,
+    /// The full statistics, including the histograms, in the text form RocksDB dumps to its log
+    pub text : String,
+}
+
+impl DBStatistics {
+
+    /// Parses the text form of the statistics, as returned by RocksDB's "rocksdb.options-statistics" property
+    pub(crate) fn parse(text : String) -> Self {
+
+        //Tickers are the lines of the form "rocksdb.block.cache.hit COUNT : 123".  Histograms have several
+        // fields on one line, so they are left in the text
+        let tickers = text.lines()
+            .filter_map(|line| {
+                let fields : Vec<&str> = line.split_whitespace().collect();
+                match fields[..] {
+                    [name, "COUNT", ":", count] => count.parse::<u64>().ok().map(|count| (name.to_string(), count)),
+                    _ => None
+                }
+            })
+            .collect();
+
+        Self{tickers, text}
+    }
+
+    /// Returns the value of the named ticker, or 0 if RocksDB didn't report it
+    pub fn ticker(&self, name : &str) -> u64 {
+        self.tickers.get(name).copied().unwrap_or(0)
+    }
+
+    /// Returns the fraction of the block cache lookups that found the block in the cache, or `None` if there
+    /// haven't been any lookups
+    pub fn block_cache_hit_rate(&self) -> Option<f64> {
+        ratio(self.ticker("rocksdb.block.cache.hit"), self.ticker("rocksdb.block.cache.miss"))
+    }
+
+    /// Returns the fraction of the bloom filter checks that ruled out a file without reading it, or `None` if no
+    /// filters have been checked.  Filters are described on [RocksConfig](crate::RocksConfig)
+    pub fn bloom_useful_ratio(&self) -> Option<f64> {
+        ratio(self.ticker("rocksdb.bloom.filter.useful"), self.ticker("rocksdb.bloom.filter.full.positive"))
+    }
+
+    /// Returns the number of bytes read by compactions
+    pub fn compaction_bytes_read(&self) -> u64 {
+        self.ticker("rocksdb.compact.read.bytes")
+    }
+
+    /// Returns the number of bytes written by compactions
+    pub fn compaction_bytes_written(&self) -> u64 {
+        self.ticker("rocksdb.compact.write.bytes")
+    }
+}
+
+/// Returns `hits` as a fraction of `hits + misses`, or `None` if both are 0
+fn ratio(hits : u64, misses : u64) -> Option<f64> {
+    let total = hits + misses;
+    if total == 0 {
+        None
+    } else {
+        Some(hits as f64 / total as f64)
+    }
+}
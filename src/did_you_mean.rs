@@ -0,0 +1,69 @@
+//!
+//! The DidYouMean module contains the results of a phrase correction made by [Table::did_you_mean](crate::Table::did_you_mean).
+//! The PhraseCorrection and TokenCorrection structs are re-exported
+//!
+
+use num_traits::Zero;
+
+use super::records::RecordID;
+
+/// The correction made to a single token (word) of a phrase
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenCorrection<DistanceT> {
+    /// The token, as it appeared in the phrase
+    pub original : String,
+    /// The key the token was corrected to, or the original token if no key in the table was close enough
+    pub corrected : String,
+    /// The record with the corrected key, or `None` if the token wasn't corrected
+    pub record_id : Option<RecordID>,
+    /// The distance from the original token to the corrected key
+    pub distance : DistanceT,
+}
+
+/// A corrected phrase, as returned by [Table::did_you_mean](crate::Table::did_you_mean)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhraseCorrection<DistanceT> {
+    /// The correction for each token in the phrase, in order
+    pub tokens : Vec<TokenCorrection<DistanceT>>,
+    /// The sum of the distances of all of the tokens
+    pub distance : DistanceT,
+}
+
+impl <DistanceT>PhraseCorrection<DistanceT> {
+
+    /// Returns the corrected phrase, with the tokens separated by single spaces
+    pub fn phrase(&self) -> String {
+        self.tokens.iter().map(|token| token.corrected.as_str()).collect::<Vec<&str>>().join(" ")
+    }
+}
+
+/// Combines the candidate corrections for each token into at most `max_phrases` phrases, ranked by
+/// ascending total distance
+///
+/// Only the best `max_phrases` partial phrases are extended with each successive token, so the number of
+/// combinations considered stays proportional to the number of tokens.
+pub(crate) fn rank_phrases<DistanceT : Copy + Zero + PartialOrd>(token_candidates : Vec<Vec<TokenCorrection<DistanceT>>>, max_phrases : usize) -> Vec<PhraseCorrection<DistanceT>> {
+
+    if token_candidates.is_empty() {
+        return vec![];
+    }
+
+    let mut phrases = vec![PhraseCorrection{tokens : vec![], distance : DistanceT::zero()}];
+    for candidates in token_candidates {
+        let mut extended_phrases = Vec::with_capacity(phrases.len() * candidates.len());
+        for phrase in phrases.iter() {
+            for candidate in candidates.iter() {
+                let mut tokens = phrase.tokens.clone();
+                tokens.push(candidate.clone());
+                extended_phrases.push(PhraseCorrection{tokens, distance : phrase.distance + candidate.distance});
+            }
+        }
+
+        //NOTE: A stable sort, so ties are ranked in the order the candidates were found
+        extended_phrases.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(core::cmp::Ordering::Equal));
+        extended_phrases.truncate(max_phrases);
+        phrases = extended_phrases;
+    }
+
+    phrases
+}
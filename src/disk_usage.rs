@@ -0,0 +1,45 @@
+//!
+//! The DiskUsage module contains the [DiskUsage] and [CFDiskUsage] structs, which report how much space a
+//! Table's database takes up.  The DiskUsage and CFDiskUsage structs are re-exported
+//!
+
+/// The space taken up by a Table's database, returned by [Table::disk_usage](crate::Table::disk_usage)
+///
+/// The sizes of the column families come from RocksDB, and are approximate.  They don't include the
+/// write-ahead log, or RocksDB's own logs and manifests, which are counted in `total_size`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// The space taken up by each open column family.  The column families are described in the crate documentation
+    pub column_families : Vec<CFDiskUsage>,
+    /// The total size, in bytes, of every file in the database directory.  For a Table in a
+    /// [SharedDatabase](crate::SharedDatabase), this covers every Table in the database, while `column_families`
+    /// covers only this Table
+    pub total_size : u64,
+}
+
+/// The space taken up by a single column family, part of a [DiskUsage]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CFDiskUsage {
+    /// The name of the column family
+    pub name : &'static str,
+    /// The total size, in bytes, of the column family's files, including files that are about to be deleted by
+    /// a compaction
+    pub sst_files_size : u64,
+    /// RocksDB's estimate, in bytes, of the live data in the column family's files
+    pub estimated_live_data_size : u64,
+    /// The size, in bytes, of the column family's memtables, which haven't been written to files yet
+    pub memtables_size : u64,
+}
+
+impl DiskUsage {
+
+    /// Returns the space taken up by the named column family, or `None` if it isn't open
+    pub fn cf(&self, name : &str) -> Option<&CFDiskUsage> {
+        self.column_families.iter().find(|cf_usage| cf_usage.name == name)
+    }
+
+    /// Returns the total size, in bytes, of the files of all the column families
+    pub fn sst_files_size(&self) -> u64 {
+        self.column_families.iter().map(|cf_usage| cf_usage.sst_files_size).sum()
+    }
+}
@@ -0,0 +1,114 @@
+//!
+//! The Export module contains the [RecordExport] iterator returned by [Table::export_stream](crate::Table::export_stream).
+//! The RecordExport and ExportedRecord structs are re-exported
+//!
+
+use core::marker::PhantomData;
+
+use serde::{Serialize, Deserialize};
+
+use super::records::RecordID;
+use super::key::{*};
+use super::database::{*};
+use super::perf_counters::{*};
+
+/// A record produced by a [RecordExport], with all of its keys and its value
+///
+/// An ExportedRecord implements [Serialize], so it can be written out with any serde format, such as [bincode].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedRecord<OwnedKeyT, ValueT> {
+    pub record_id : RecordID,
+    pub keys : Vec<OwnedKeyT>,
+    pub value : ValueT,
+}
+
+/// A lazy iterator over every record in a [Table](crate::Table), as it was when the export was started
+///
+/// The export reads from a database snapshot, so writes made to the Table while the export is in progress
+/// don't appear in it, and can't leave it with a partially written record.  Records are produced in order of
+/// their RecordIDs, and deleted records are skipped.
+///
+/// If a database error is encountered partway through, the iterator produces the error, and then ends.  Use
+/// [into_vec](RecordExport::into_vec) to collect the records, or the error.
+pub struct RecordExport<'a, OwnedKeyT, ValueT> {
+    snapshot : DBSnapshot<'a>,
+    perf_counters : &'a PerfCounters,
+    /// The RecordIDs still to be visited
+    record_ids : std::vec::IntoIter<RecordID>,
+    error : Option<String>,
+    failed : bool,
+    phantom : PhantomData<(OwnedKeyT, ValueT)>,
+}
+
+impl <'a, OwnedKeyT : OwnedKey, ValueT : 'static + Serialize + serde::de::DeserializeOwned>RecordExport<'a, OwnedKeyT, ValueT> {
+
+    pub(crate) fn new(snapshot : DBSnapshot<'a>, perf_counters : &'a PerfCounters) -> Self {
+
+        //The RecordIDs are found up front from the "rec_data" CF, so a gap in the RecordIDs, e.g. left by
+        // deterministic RecordIDs or an interrupted write, can't end the export early
+        let (record_ids, error) = match snapshot.record_ids() {
+            Ok(record_ids) => (record_ids.into_iter(), None),
+            Err(err) => (vec![].into_iter(), Some(err))
+        };
+
+        Self {
+            snapshot,
+            perf_counters,
+            record_ids,
+            error,
+            failed : false,
+            phantom : PhantomData,
+        }
+    }
+
+    /// Collects all of the remaining records into a [Vec], or returns the error if one was encountered
+    pub fn into_vec(self) -> Result<Vec<ExportedRecord<OwnedKeyT, ValueT>>, String> {
+        self.collect()
+    }
+
+    /// Reads the next record that hasn't been deleted, or returns `None` if there are no more records
+    fn next_record(&mut self) -> Result<Option<ExportedRecord<OwnedKeyT, ValueT>>, String> {
+
+        for record_id in self.record_ids.by_ref() {
+            let key_groups = match self.snapshot.get_record_key_groups(record_id)? {
+                Some(key_groups) => key_groups,
+                None => continue
+            };
+            if key_groups.is_empty() {
+                continue;
+            }
+
+            let mut keys = vec![];
+            for key_group in key_groups {
+                keys.extend(self.snapshot.get_keys_in_group::<OwnedKeyT>(key_group, self.perf_counters)?);
+            }
+            let value = self.snapshot.get_value(record_id)?;
+
+            return Ok(Some(ExportedRecord{record_id, keys, value}));
+        }
+        Ok(None)
+    }
+}
+
+impl <OwnedKeyT : OwnedKey, ValueT : 'static + Serialize + serde::de::DeserializeOwned>Iterator for RecordExport<'_, OwnedKeyT, ValueT> {
+    type Item = Result<ExportedRecord<OwnedKeyT, ValueT>, String>;
+
+    fn next(&mut self) -> Option<Result<ExportedRecord<OwnedKeyT, ValueT>, String>> {
+        if self.failed {
+            return None;
+        }
+
+        //The error from finding the RecordIDs up front is produced first
+        let result = match self.error.take() {
+            Some(err) => Err(err),
+            None => self.next_record()
+        };
+        match result {
+            Ok(record) => record.map(Ok),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//!
+//! The FallbackScan module contains the [FallbackScan] enum, which selects how a lookup beyond the indexed
+//! distance finds its candidates.  The FallbackScan enum is re-exported
+//!
+
+use super::records::RecordID;
+
+/// How [lookup_fuzzy_extended](crate::Table::lookup_fuzzy_extended) finds the candidates for a lookup with a
+/// `max_distance` beyond [MAX_DELETES](crate::TableConfig::MAX_DELETES), set with
+/// [FALLBACK_SCAN](crate::TableConfig::FALLBACK_SCAN)
+///
+/// The index only finds the keys within `MAX_DELETES` edits of the lookup key, so a lookup beyond that must scan
+/// records that the index can't rule out.  Whichever scan is used, each scanned key is first checked with a
+/// banded Levenshtein check against the lookup key, so only the keys within `max_distance` edits reach the
+/// [DISTANCE_FUNCTION](crate::TableConfig::DISTANCE_FUNCTION).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackScan {
+    /// A lookup beyond `MAX_DELETES` returns an error, rather than results that may be incomplete
+    Disabled,
+    /// Every record in the Table is scanned, so the results are complete, but the lookup takes time proportional
+    /// to the size of the Table
+    Full,
+    /// The records found through the index within `MAX_DELETES`, along with up to this many other records sampled
+    /// evenly across the Table, are scanned.  The lookup's cost is bounded, but records further than `MAX_DELETES`
+    /// are only found if they are sampled
+    Sampled(usize),
+}
+
+/// Returns up to `n` of the records, spaced evenly through the `Vec`
+pub(crate) fn sample_records(record_ids : Vec<RecordID>, n : usize) -> Vec<RecordID> {
+
+    if record_ids.len() <= n {
+        return record_ids;
+    }
+    let stride = record_ids.len() as f64 / n as f64;
+    (0..n).map(|i| record_ids[(i as f64 * stride) as usize]).collect()
+}
@@ -0,0 +1,256 @@
+//!
+//! The Fuzzing module contains an entry point for fuzzing a [Table] against a simple in-memory model of its
+//! contents.  It is only compiled with the `fuzzing` feature.  The FuzzOp type and the fuzz_table function are
+//! re-exported
+//!
+
+use std::collections::{HashMap, HashSet};
+use core::fmt::Debug;
+use core::hash::Hash;
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::{*};
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+
+/// The longest key produced by [FuzzOp::decode_ops].  Short keys make it likely that different operations
+/// touch the same keys
+const MAX_FUZZ_KEY_LEN : usize = 8;
+
+/// An operation applied by [fuzz_table], to both the Table and the model
+///
+/// Records are chosen by an index into the records that exist when the operation is applied, wrapping around,
+/// so every operation after the first insert touches an existing record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FuzzOp<KeyCharT, ValueT> {
+    Insert{keys : Vec<Vec<KeyCharT>>, value : ValueT},
+    Delete{record : usize},
+    AddKey{record : usize, key : Vec<KeyCharT>},
+    RemoveKey{record : usize, key : usize},
+    ReplaceValue{record : usize, value : ValueT},
+    LookupExact{key : Vec<KeyCharT>},
+    LookupFuzzy{key : Vec<KeyCharT>},
+}
+
+/// Reads the fields of the operations from the bytes supplied by a fuzzer
+struct FuzzBytes<'a> {
+    data : &'a [u8],
+}
+
+impl <'a>FuzzBytes<'a> {
+
+    fn byte(&mut self) -> Option<u8> {
+        let (first, rest) = self.data.split_first()?;
+        self.data = rest;
+        Some(*first)
+    }
+
+    /// Returns between 1 and `max_len` bytes, with the length taken from the first byte
+    fn slice(&mut self, max_len : usize) -> Option<&'a [u8]> {
+        let len = 1 + self.byte()? as usize % max_len;
+        if self.data.len() < len {
+            return None;
+        }
+        let (slice, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(slice)
+    }
+}
+
+impl <KeyCharT, ValueT>FuzzOp<KeyCharT, ValueT> {
+
+    /// Decodes a sequence of operations from the raw bytes supplied by a fuzzer, such as `cargo fuzz`
+    ///
+    /// `make_key` and `make_value` build a key or a value from a few bytes, so the operations can be decoded for
+    /// any key and value types.  `make_key` should map the bytes onto a small alphabet, so keys collide often
+    /// enough to exercise the fuzzy lookups.  Operations with an empty key are left out.
+    pub fn decode_ops<KeyF, ValueF>(data : &[u8], make_key : KeyF, make_value : ValueF) -> Vec<Self>
+        where
+        KeyF : Fn(&[u8]) -> Vec<KeyCharT>,
+        ValueF : Fn(&[u8]) -> ValueT,
+    {
+        let mut bytes = FuzzBytes{data};
+        let mut ops = vec![];
+        let next_key = |bytes : &mut FuzzBytes| bytes.slice(MAX_FUZZ_KEY_LEN).map(&make_key).filter(|key| !key.is_empty());
+        while let Some(tag) = bytes.byte() {
+            let op = match tag % 7 {
+                0 => {
+                    let num_keys = 1 + bytes.byte().unwrap_or(0) as usize % 3;
+                    let keys : Option<Vec<Vec<KeyCharT>>> = (0..num_keys).map(|_| next_key(&mut bytes)).collect();
+                    keys.zip(bytes.slice(MAX_FUZZ_KEY_LEN).map(&make_value)).map(|(keys, value)| FuzzOp::Insert{keys, value})
+                },
+                1 => bytes.byte().map(|record| FuzzOp::Delete{record : record as usize}),
+                2 => bytes.byte().zip(next_key(&mut bytes)).map(|(record, key)| FuzzOp::AddKey{record : record as usize, key}),
+                3 => bytes.byte().zip(bytes.byte()).map(|(record, key)| FuzzOp::RemoveKey{record : record as usize, key : key as usize}),
+                4 => bytes.byte().zip(bytes.slice(MAX_FUZZ_KEY_LEN).map(&make_value)).map(|(record, value)| FuzzOp::ReplaceValue{record : record as usize, value}),
+                5 => next_key(&mut bytes).map(|key| FuzzOp::LookupExact{key}),
+                _ => next_key(&mut bytes).map(|key| FuzzOp::LookupFuzzy{key}),
+            };
+            match op {
+                Some(op) => ops.push(op),
+                None if bytes.data.is_empty() => break,
+                None => {}
+            }
+        }
+        ops
+    }
+}
+
+/// A record in the model, with its keys in the order they were added
+struct ModelRecord<KeyCharT, ValueT> {
+    record_id : RecordID,
+    keys : Vec<Vec<KeyCharT>>,
+    value : ValueT,
+}
+
+/// Resets the Table, applies each operation to both the Table and an in-memory model, and returns an error
+/// describing the first difference between them
+///
+/// After each write, the value and keys of the record are read back and compared with the model.  Lookups are
+/// checked more loosely, because the model doesn't implement SymSpell: every record with a key equal to the
+/// lookup key must be returned, and every record returned must exist, with the distance of its closest key.
+/// Unless the Table has an [EXACT_KEY_INDEX](TableConfig::EXACT_KEY_INDEX), `lookup_exact` may also return
+/// records with keys that only share a prefix with the lookup key, so only the index is checked for extra results.
+///
+/// The model compares keys exactly, so the Table must be case-sensitive.
+///
+/// ```ignore
+/// #![no_main]
+/// use libfuzzer_sys::fuzz_target;
+/// use fuzzy_rocks::{*};
+///
+/// fuzz_target!(|data : &[u8]| {
+///     let mut table = Table::<DefaultTableConfig, true>::new("fuzz.rocks", DefaultTableConfig()).unwrap();
+///     let ops = FuzzOp::decode_ops(data, |bytes| bytes.iter().map(|b| (b'a' + b % 4) as char).collect(), |bytes| format!("{:?}", bytes));
+///     fuzz_table(&mut table, &ops).unwrap();
+/// });
+/// ```
+pub fn fuzz_table<OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>(table : &mut Table<ConfigT, UTF8_KEYS>, ops : &[FuzzOp<ConfigT::KeyCharT, ConfigT::ValueT>]) -> Result<(), String>
+    where
+    ConfigT::KeyCharT : 'static + Clone + Eq + Hash + Debug + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + Debug + From<u8>,
+    ConfigT::ValueT : 'static + Clone + PartialEq + Debug + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+{
+    if table.params().folds_keys() {
+        return Err("fuzz_table needs a Table that doesn't fold keys".to_string());
+    }
+    table.reset()?;
+
+    let mut model : Vec<ModelRecord<ConfigT::KeyCharT, ConfigT::ValueT>> = vec![];
+    for (op_idx, op) in ops.iter().enumerate() {
+        let fail = |msg : String| Err(format!("op {} ({:?}): {}", op_idx, op, msg));
+        let pick = |record : usize, model_len : usize| if model_len > 0 { Some(record % model_len) } else { None };
+
+        let touched = match op {
+            FuzzOp::Insert{keys, value} => {
+                //NOTE: Like add_keys, create doesn't look for duplicates across key groups, so a key repeated in
+                // the same record may be stored twice.  We don't exercise that case
+                let mut unique_keys = vec![];
+                for key in keys {
+                    if !unique_keys.contains(key) {
+                        unique_keys.push(key.clone());
+                    }
+                }
+                let record_id = table.insert_internal(unique_keys.iter(), unique_keys.len(), value)?;
+                model.push(ModelRecord{record_id, keys : unique_keys, value : value.clone()});
+                Some(model.len() - 1)
+            },
+            FuzzOp::Delete{record} => {
+                if let Some(idx) = pick(*record, model.len()) {
+                    let removed = model.swap_remove(idx);
+                    table.delete(removed.record_id)?;
+                    if table.get_value(removed.record_id).is_ok() {
+                        return fail(format!("record {} can still be read after it was deleted", removed.record_id));
+                    }
+                }
+                None
+            },
+            FuzzOp::AddKey{record, key} => {
+                //NOTE: add_keys puts the new keys in a new key group without checking the existing groups, so a key
+                // the record already has would be stored twice.  We don't exercise that case
+                pick(*record, model.len()).filter(|idx| !model[*idx].keys.contains(key)).map(|idx| {
+                    table.add_keys_atomic(model[idx].record_id, [key].into_iter(), 1)?;
+                    model[idx].keys.push(key.clone());
+                    Ok::<usize, String>(idx)
+                }).transpose()?
+            },
+            FuzzOp::RemoveKey{record, key} => {
+                match pick(*record, model.len()) {
+                    Some(idx) => {
+                        let key_idx = key % model[idx].keys.len();
+                        let remove_key = model[idx].keys[key_idx].clone();
+                        let remove_keys : HashSet<&Vec<ConfigT::KeyCharT>> = [&remove_key].into_iter().collect();
+                        let result = table.remove_keys_atomic(model[idx].record_id, &remove_keys);
+                        if model[idx].keys.len() == 1 {
+                            if result.is_ok() {
+                                return fail("removing the last key of a record succeeded".to_string());
+                            }
+                        } else {
+                            result?;
+                            model[idx].keys.remove(key_idx);
+                        }
+                        Some(idx)
+                    },
+                    None => None
+                }
+            },
+            FuzzOp::ReplaceValue{record, value} => {
+                pick(*record, model.len()).map(|idx| {
+                    let old_value = table.replace_value(model[idx].record_id, value)?;
+                    if old_value != model[idx].value {
+                        return Err(format!("op {} ({:?}): replace_value returned {:?}, expected {:?}", op_idx, op, old_value, model[idx].value));
+                    }
+                    model[idx].value = value.clone();
+                    Ok(idx)
+                }).transpose()?
+            },
+            FuzzOp::LookupExact{key} => {
+                let results : HashSet<RecordID> = table.lookup_exact_internal(key)?.into_iter().collect();
+                let expected : HashSet<RecordID> = model.iter().filter(|record| record.keys.contains(key)).map(|record| record.record_id).collect();
+                if !expected.is_subset(&results) || (ConfigT::EXACT_KEY_INDEX && expected != results) {
+                    return fail(format!("lookup_exact returned {:?}, expected {:?}", results, expected));
+                }
+                None
+            },
+            FuzzOp::LookupFuzzy{key} => {
+                let results : HashMap<RecordID, ConfigT::DistanceT> = table.lookup_fuzzy_internal(key, None, None)?.into_vec()?.into_iter().collect();
+                for record in model.iter() {
+                    let closest = record.keys.iter().map(|record_key| (ConfigT::DISTANCE_FUNCTION)(record_key, key))
+                        .fold(None, |closest : Option<ConfigT::DistanceT>, distance| match closest {
+                            Some(closest) if closest <= distance => Some(closest),
+                            _ => Some(distance)
+                        });
+                    match results.get(&record.record_id) {
+                        Some(distance) if Some(*distance) != closest => return fail(format!("lookup_fuzzy returned {} at distance {:?}, expected {:?}", record.record_id, distance, closest)),
+                        None if record.keys.contains(key) => return fail(format!("lookup_fuzzy didn't return {}, which has the lookup key", record.record_id)),
+                        _ => {}
+                    }
+                }
+                if let Some(record_id) = results.keys().find(|record_id| !model.iter().any(|record| record.record_id == **record_id)) {
+                    return fail(format!("lookup_fuzzy returned {}, which doesn't exist", record_id));
+                }
+                None
+            },
+        };
+
+        //Read back the record the op wrote to, and make sure it matches the model
+        if let Some(idx) = touched {
+            let record = &model[idx];
+            let value = table.get_value(record.record_id)?;
+            if value != record.value {
+                return fail(format!("record {} has value {:?}, expected {:?}", record.record_id, value, record.value));
+            }
+            let keys : Vec<Vec<ConfigT::KeyCharT>> = table.get_keys_internal(record.record_id)?.map(|key| key.into_vec()).collect();
+            if keys.len() != record.keys.len() || !keys.iter().all(|key| record.keys.contains(key)) {
+                return fail(format!("record {} has keys {:?}, expected {:?}", record.record_id, keys, record.keys));
+            }
+        }
+    }
+
+    Ok(())
+}
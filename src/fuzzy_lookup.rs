@@ -0,0 +1,616 @@
+//!
+//! The FuzzyLookup module contains the lazy iterators returned by fuzzy lookups.  The [FuzzyRawLookup],
+//! [FuzzyLookup], and [FuzzyScoredLookup] structs, and the [LookupStrategy] enum are re-exported
+//!
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use super::records::RecordID;
+use super::key::{*};
+use super::database::{*};
+use super::table_config::{*};
+use super::key_groups::{*};
+use super::perf_counters::{*};
+use super::cursor::LookupCursor;
+
+/// A distance function boxed for a [FuzzyLookup], which may borrow from the Table
+pub(crate) type BoxedDistanceFunction<'a, KeyCharT, DistanceT> = Box<dyn Fn(&[KeyCharT], &[KeyCharT]) -> DistanceT + 'a>;
+
+/// A function that decides whether a key is worth passing to a [FuzzyLookup]'s distance function
+pub(crate) type KeyFilter<'a, KeyCharT> = Box<dyn Fn(&[KeyCharT]) -> bool + 'a>;
+
+/// A function called with each key group a [FuzzyLookup] loads
+pub(crate) type KeyGroupCheck<'a> = Box<dyn Fn(KeyGroupID) + 'a>;
+
+/// How a [FuzzyLookup] finds its candidate records
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LookupStrategy {
+    /// The candidates are the records referenced by the variants of the lookup key, in the "variants" CF, or by
+    /// its n-grams, if the Table indexes n-grams
+    Variants,
+    /// Every record in the Table is a candidate, because the Table has so few records that scanning them was
+    /// estimated to cost less than probing the variants of the lookup key
+    Scan,
+}
+
+/// Produces the KeyGroupIDs referenced by a set of variants, probing one variant entry at a time
+///
+/// Variants are probed in descending order of priority, so the candidates referenced by the highest-priority
+/// variant are all produced before the next variant is loaded.  Each KeyGroupID is only produced once, even if
+/// it is referenced by several of the variants.
+pub(crate) struct KeyGroupCandidates<'a> {
+    db : &'a DBConnection,
+    perf_counters : &'a PerfCounters,
+    variants : std::vec::IntoIter<Vec<u8>>,
+    priority_function : VariantPriorityFunction,
+    pending_groups : Vec<KeyGroupID>,
+    visited_groups : HashSet<KeyGroupID>,
+    match_prefixes : bool,
+}
+
+impl <'a>KeyGroupCandidates<'a> {
+
+    pub(crate) fn new(db : &'a DBConnection, perf_counters : &'a PerfCounters, variants : HashSet<Vec<u8>>, priority_function : VariantPriorityFunction) -> Self {
+
+        let mut candidates = Self {
+            db,
+            perf_counters,
+            variants : vec![].into_iter(),
+            priority_function,
+            pending_groups : vec![],
+            visited_groups : HashSet::new(),
+            match_prefixes : false,
+        };
+        candidates.push_variants(variants);
+        candidates
+    }
+
+    /// Creates candidates that produce a KeyGroupID for each of the supplied records, starting from the end of
+    /// the `Vec`, without probing any variants
+    ///
+    /// NOTE: The KeyGroupIDs only identify the records.  A [FuzzyLookup] evaluates all of a record's keys,
+    /// regardless of which of its key groups it was found through
+    pub(crate) fn from_records(db : &'a DBConnection, perf_counters : &'a PerfCounters, record_ids : Vec<RecordID>, priority_function : VariantPriorityFunction) -> Self {
+        Self {
+            db,
+            perf_counters,
+            variants : vec![].into_iter(),
+            priority_function,
+            pending_groups : record_ids.into_iter().map(|record_id| KeyGroupID::from_record_and_idx(record_id, 0)).collect(),
+            visited_groups : HashSet::new(),
+            match_prefixes : false,
+        }
+    }
+
+    /// Adds a KeyGroupID for each of the supplied records, to be produced before any variants are probed.  See
+    /// [from_records](Self::from_records)
+    pub(crate) fn with_records(mut self, record_ids : Vec<RecordID>) -> Self {
+        self.pending_groups.extend(record_ids.into_iter().map(|record_id| KeyGroupID::from_record_and_idx(record_id, 0)));
+        self
+    }
+
+    /// Makes each variant also match every variant in the database that it is a prefix of, rather than only the
+    /// identical variant
+    pub(crate) fn matching_prefixes(mut self) -> Self {
+        self.match_prefixes = true;
+        self
+    }
+
+    /// Adds more variants to be probed, ordered by priority along with any variants that haven't been
+    /// probed yet.  The KeyGroupIDs that have already been produced won't be produced again
+    pub(crate) fn push_variants(&mut self, variants : HashSet<Vec<u8>>) {
+
+        #[cfg(feature = "perf_counters")]
+        { self.perf_counters.update(|fields| fields.variant_lookup_count += variants.len() ); }
+
+        let priority_function = self.priority_function;
+        let mut all_variants : Vec<Vec<u8>> = self.variants.by_ref().chain(variants).collect();
+        all_variants.sort_by_cached_key(|variant| Reverse(priority_function(variant)));
+        self.variants = all_variants.into_iter();
+    }
+
+    /// Adds the candidates from another set of candidates, e.g. for an alternate form of the lookup key.  The
+    /// variants of both are probed in order of priority, and a KeyGroupID found by both is only produced once
+    pub(crate) fn merge(mut self, other : Self) -> Self {
+
+        self.pending_groups.extend(other.pending_groups);
+        let priority_function = self.priority_function;
+        let all_variants : HashSet<Vec<u8>> = self.variants.by_ref().chain(other.variants).collect();
+        let mut all_variants : Vec<Vec<u8>> = all_variants.into_iter().collect();
+        all_variants.sort_by_cached_key(|variant| Reverse(priority_function(variant)));
+        self.variants = all_variants.into_iter();
+        self
+    }
+
+    /// Returns `true` if every variant has been probed and every KeyGroupID found has been produced
+    fn is_exhausted(&self) -> bool {
+        self.pending_groups.is_empty() && self.variants.as_slice().is_empty()
+    }
+
+    /// Produces every remaining KeyGroupID, and returns the records they belong to in the order they were first
+    /// found, each with `true` if it was found through any of its injected variants
+    pub(crate) fn into_records(mut self) -> Result<Vec<(RecordID, bool)>, String> {
+        let mut records : Vec<(RecordID, bool)> = vec![];
+        let mut record_idxs : HashMap<RecordID, usize> = HashMap::new();
+        while let Some(key_group_id) = self.next_group()? {
+            let record_id = key_group_id.record_id();
+            match record_idxs.get(&record_id) {
+                Some(&idx) => records[idx].1 |= key_group_id.is_injected(),
+                None => {
+                    record_idxs.insert(record_id, records.len());
+                    records.push((record_id, key_group_id.is_injected()));
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// Returns the next KeyGroupID that hasn't been returned before, loading the next variant entry if
+    /// the ones already loaded are used up.  Returns `None` when every variant has been probed
+    ///
+    /// The KeyGroupIDs of [soft deleted](crate::Table::soft_delete) records, and of records that have outlived the
+    /// Table's [TTL](crate::TableConfig::TTL), are never returned.
+    fn next_group(&mut self) -> Result<Option<KeyGroupID>, String> {
+        loop {
+            while let Some(key_group_id) = self.pending_groups.pop() {
+                if self.visited_groups.insert(key_group_id) && !self.db.is_tombstoned(key_group_id.record_id()) {
+                    if self.db.has_ttl() && self.db.is_expired(key_group_id.record_id())? {
+                        continue;
+                    }
+                    return Ok(Some(key_group_id));
+                }
+            }
+
+            let variant = match self.variants.next() {
+                Some(variant) => variant,
+                None => return Ok(None)
+            };
+
+            let pending_groups = &mut self.pending_groups;
+            #[cfg(feature = "perf_counters")]
+            let perf_counters = self.perf_counters;
+            let visitor_closure = |key_group_ids : VariantEntryIter<'_>| {
+
+                #[cfg(feature = "perf_counters")]
+                {
+                    let num_key_group_ids = key_group_ids.len();
+                    perf_counters.update(|counter_fields| {
+                        counter_fields.variant_load_count += 1;
+                        counter_fields.key_group_ref_count += num_key_group_ids;
+                        if counter_fields.max_variant_entry_refs < num_key_group_ids {
+                            counter_fields.max_variant_entry_refs = num_key_group_ids;
+                        }
+                    });
+                }
+
+                pending_groups.extend(key_group_ids);
+            };
+
+            #[cfg(feature = "perf_counters")]
+            let probe_started = std::time::Instant::now();
+
+            if self.match_prefixes {
+                self.db.visit_variants_with_prefix(&variant, visitor_closure)?;
+            } else {
+                self.db.visit_exact_variant(&variant, visitor_closure)?;
+            }
+
+            #[cfg(feature = "perf_counters")]
+            { self.perf_counters.update(|fields| fields.variant_probe_latency.record(probe_started.elapsed())); }
+        }
+    }
+}
+
+/// Returns the smallest distance between any of a record's keys and the lookup key, as measured by `distance`,
+/// or `None` if the record has expired.  The keys are loaded and folded the same way a [FuzzyLookup] loads them
+///
+/// Unlike a FuzzyLookup, this only borrows things that may be shared between threads, so the records found by
+/// [Table::lookup_fuzzy_parallel](crate::Table::lookup_fuzzy_parallel) can be evaluated concurrently.
+#[allow(unused_variables)] //NOTE: To silence the warning about perf_counters when that code path is disabled
+pub(crate) fn closest_record_distance<OwnedKeyT, DistanceT, F>(db : &DBConnection, perf_counters : &PerfCounters, key_params : Option<&TableParams>, record_id : RecordID, include_injected : bool, distance : &F) -> Result<Option<DistanceT>, String>
+    where
+    OwnedKeyT : OwnedKey,
+    DistanceT : Copy + PartialOrd,
+    F : Fn(&[OwnedKeyT::KeyCharT]) -> DistanceT
+{
+    let key_group_ids : Vec<KeyGroupID> = match db.get_live_record_key_groups(record_id)? {
+        Some(key_group_ids) => key_group_ids.collect(),
+        None if db.has_ttl() => return Ok(None),
+        None => return Err("Invalid record_id".to_string())
+    };
+    let mut record_keys = vec![];
+    for key_group_id in key_group_ids {
+        record_keys.extend(db.get_keys_in_group::<OwnedKeyT>(key_group_id, perf_counters)?);
+    }
+    if include_injected {
+        record_keys.extend(db.get_injected_variants::<OwnedKeyT>(record_id)?);
+    }
+
+    let mut closest : Option<DistanceT> = None;
+    for record_key in record_keys {
+        let key_chars = match key_params {
+            Some(key_params) => key_params.fold_key(record_key).into_vec(),
+            None => record_key.into_vec()
+        };
+
+        #[cfg(feature = "perf_counters")]
+        let distance_started = std::time::Instant::now();
+
+        let key_distance = distance(&key_chars[..]);
+
+        #[cfg(feature = "perf_counters")]
+        {
+            let elapsed = distance_started.elapsed();
+            perf_counters.update(|fields| {
+                fields.distance_function_invocation_count += 1;
+                fields.distance_latency.record(elapsed);
+            });
+        }
+
+        if closest.is_none_or(|closest_distance| key_distance < closest_distance) {
+            closest = Some(key_distance);
+        }
+    }
+    Ok(closest)
+}
+
+/// A lazy iterator over the [RecordID]s found by [Table::lookup_fuzzy_raw](crate::Table::lookup_fuzzy_raw)
+///
+/// Variant entries are loaded from the database as the iterator advances, so a caller that only needs the
+/// first few results, e.g. with [take](Iterator::take), doesn't pay for the full result set.  The order of
+/// the results is unspecified, but each record is returned only once.
+///
+/// If a database error is encountered partway through, the iterator produces the error, and then ends.  Use
+/// [into_vec](FuzzyRawLookup::into_vec) to collect the results, or the error.
+pub struct FuzzyRawLookup<'a> {
+    candidates : KeyGroupCandidates<'a>,
+    found_records : HashSet<RecordID>,
+    failed : bool,
+}
+
+impl <'a>FuzzyRawLookup<'a> {
+
+    pub(crate) fn new(candidates : KeyGroupCandidates<'a>) -> Self {
+        Self {
+            candidates,
+            found_records : HashSet::new(),
+            failed : false,
+        }
+    }
+
+    /// Collects all of the remaining results into a [Vec], or returns the error if one was encountered
+    pub fn into_vec(self) -> Result<Vec<RecordID>, String> {
+        self.collect()
+    }
+}
+
+impl Iterator for FuzzyRawLookup<'_> {
+    type Item = Result<RecordID, String>;
+
+    fn next(&mut self) -> Option<Result<RecordID, String>> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            match self.candidates.next_group() {
+                Ok(Some(key_group_id)) => {
+                    let record_id = key_group_id.record_id();
+                    if self.found_records.insert(record_id) {
+
+                        #[cfg(feature = "perf_counters")]
+                        { self.candidates.perf_counters.update(|fields| fields.records_found_count += 1 ); }
+
+                        return Some(Ok(record_id));
+                    }
+                },
+                Ok(None) => return None,
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// A lazy iterator over the [RecordID]s and distances found by [Table::lookup_fuzzy](crate::Table::lookup_fuzzy)
+///
+/// Variant entries and key groups are loaded from the database as the iterator advances, so a caller that only
+/// needs the first few results doesn't pay for the full result set.  The first time a record is encountered,
+/// the distance function is evaluated against all of the record's keys, so the distance returned is the smallest
+/// distance for the record, and each record is returned only once.  A record found through one of its injected
+/// variants (see [create_with_variants](crate::Table::create_with_variants)) is evaluated against those as well.
+/// The results are ordered by the priority of the variants that reference them, as ranked by
+/// [VARIANT_PRIORITY_FUNCTION](TableConfig::VARIANT_PRIORITY_FUNCTION).
+///
+/// If a database error is encountered partway through, the iterator produces the error, and then ends.  Use
+/// [into_vec](FuzzyLookup::into_vec) to collect the results, or the error.
+pub struct FuzzyLookup<'a, OwnedKeyT : OwnedKey, DistanceT> {
+    candidates : KeyGroupCandidates<'a>,
+    evaluated_records : HashSet<RecordID>,
+    rejected_by_keys : HashSet<RecordID>,
+    lookup_key_chars : Vec<OwnedKeyT::KeyCharT>,
+    key_chars_buf : Vec<OwnedKeyT::KeyCharT>,
+    distance_function : Box<dyn Fn(&[OwnedKeyT::KeyCharT], &[OwnedKeyT::KeyCharT]) -> DistanceT + 'a>,
+    threshold : Option<DistanceT>,
+    key_threshold : Option<Box<dyn Fn(usize, usize) -> DistanceT + 'a>>,
+    key_filter : Option<KeyFilter<'a, OwnedKeyT::KeyCharT>>,
+    key_group_check : Option<KeyGroupCheck<'a>>,
+    key_params : Option<&'a TableParams>,
+    strategy : LookupStrategy,
+    failed : bool,
+}
+
+impl <'a, OwnedKeyT : OwnedKey, DistanceT : Copy + PartialOrd>FuzzyLookup<'a, OwnedKeyT, DistanceT> {
+
+    pub(crate) fn new(candidates : KeyGroupCandidates<'a>, lookup_key_chars : Vec<OwnedKeyT::KeyCharT>, distance_function : Box<dyn Fn(&[OwnedKeyT::KeyCharT], &[OwnedKeyT::KeyCharT]) -> DistanceT + 'a>, threshold : Option<DistanceT>, key_params : Option<&'a TableParams>) -> Self {
+        Self {
+            candidates,
+            evaluated_records : HashSet::new(),
+            rejected_by_keys : HashSet::new(),
+            lookup_key_chars,
+            //pre-allocate the buffer we'll expand the key-chars into
+            key_chars_buf : Vec::with_capacity(MAX_KEY_LENGTH),
+            distance_function,
+            threshold,
+            key_threshold : None,
+            key_filter : None,
+            key_group_check : None,
+            key_params,
+            strategy : LookupStrategy::Variants,
+            failed : false,
+        }
+    }
+
+    /// Sets a function that computes the threshold for each key from the lengths of the key and the lookup key,
+    /// in that order.  A key must be within both this threshold and the lookup's `threshold`, if there is one
+    pub(crate) fn with_key_threshold(mut self, key_threshold : Box<dyn Fn(usize, usize) -> DistanceT + 'a>) -> Self {
+        self.key_threshold = Some(key_threshold);
+        self
+    }
+
+    /// Sets a function that is called with each key before the distance function, and skips the key unless it
+    /// returns `true`
+    pub(crate) fn with_key_filter(mut self, key_filter : KeyFilter<'a, OwnedKeyT::KeyCharT>) -> Self {
+        self.key_filter = Some(key_filter);
+        self
+    }
+
+    /// Records the strategy the candidates were found with, if it wasn't probing the variants
+    pub(crate) fn with_strategy(mut self, strategy : LookupStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets a function that is called with each key group loaded to evaluate a record
+    pub(crate) fn with_key_group_check(mut self, key_group_check : KeyGroupCheck<'a>) -> Self {
+        self.key_group_check = Some(key_group_check);
+        self
+    }
+
+    /// Collects all of the remaining results into a [Vec], or returns the error if one was encountered
+    pub fn into_vec(self) -> Result<Vec<(RecordID, DistanceT)>, String> {
+        self.collect()
+    }
+
+    /// Adds more variants to be probed, so the iterator may produce more results even after it has
+    /// returned `None`.  Records that have already been evaluated are not evaluated again
+    pub(crate) fn push_variants(&mut self, variants : HashSet<Vec<u8>>) {
+        self.candidates.push_variants(variants);
+    }
+
+    /// Restores the progress saved in a cursor by [into_cursor](Self::into_cursor), replacing the variants
+    /// the lookup was created with
+    pub(crate) fn resume(mut self, cursor : LookupCursor) -> Self {
+        self.candidates.variants = cursor.variants.into_iter();
+        self.candidates.pending_groups = cursor.pending_groups;
+        self.candidates.visited_groups = cursor.visited_groups.into_iter().collect();
+        self.evaluated_records = cursor.evaluated_records.into_iter().collect();
+        self.rejected_by_keys = cursor.rejected_by_keys.into_iter().collect();
+        self
+    }
+
+    /// Saves the progress of the lookup in a cursor, so it can be resumed later.  Returns `None` if there is
+    /// nothing left to probe
+    pub(crate) fn into_cursor(self, table_id : u64, lookup_key : Vec<u8>, threshold : Vec<u8>) -> Option<LookupCursor> {
+        if self.candidates.is_exhausted() {
+            return None;
+        }
+        Some(LookupCursor {
+            table_id,
+            lookup_key,
+            threshold,
+            variants : self.candidates.variants.collect(),
+            pending_groups : self.candidates.pending_groups,
+            visited_groups : self.candidates.visited_groups.into_iter().collect(),
+            evaluated_records : self.evaluated_records.into_iter().collect(),
+            rejected_by_keys : self.rejected_by_keys.into_iter().collect(),
+        })
+    }
+
+    /// Returns the [LookupStrategy] this lookup uses to find its candidate records
+    pub fn strategy(&self) -> LookupStrategy {
+        self.strategy
+    }
+
+    /// Returns a [FuzzyScoredLookup], which produces the key that was closest to the lookup key along with
+    /// each result
+    pub fn scored(self) -> FuzzyScoredLookup<'a, OwnedKeyT, DistanceT> {
+        FuzzyScoredLookup{lookup : self}
+    }
+
+    /// Evaluates the distance function against every key of the record that the key group belongs to, and
+    /// optionally the record's injected variants, and returns the closest key and its distance if it is within
+    /// the threshold
+    /// 
+    /// If the database has a [TTL](crate::TableConfig::TTL), a record that has expired is skipped, rather than
+    /// returning an error.
+    fn evaluate_record(&mut self, found_key_group_id : KeyGroupID, include_injected : bool) -> Result<Option<(OwnedKeyT, DistanceT)>, String> {
+
+        let db = self.candidates.db;
+        let record_id = found_key_group_id.record_id();
+        let key_group_ids : Vec<KeyGroupID> = match db.get_live_record_key_groups(record_id)? {
+            Some(key_group_ids) => key_group_ids.collect(),
+            None if db.has_ttl() => return Ok(None),
+            None => return Err("Invalid record_id".to_string())
+        };
+        let mut record_keys = vec![];
+        for key_group_id in key_group_ids {
+            let group_keys : Vec<OwnedKeyT> = db.get_keys_in_group::<OwnedKeyT>(key_group_id, self.candidates.perf_counters)?.collect();
+            if let Some(key_group_check) = &self.key_group_check {
+                key_group_check(key_group_id);
+            }
+            record_keys.extend(group_keys);
+        }
+        if include_injected {
+            record_keys.extend(db.get_injected_variants::<OwnedKeyT>(record_id)?);
+        }
+
+        let mut closest : Option<(OwnedKeyT, DistanceT)> = None;
+        for record_key in record_keys {
+            let folded_key;
+            let key_chars = if let Some(key_params) = self.key_params {
+                folded_key = key_params.fold_key(record_key.clone());
+                folded_key.move_into_buf(&mut self.key_chars_buf)
+            } else {
+                record_key.move_into_buf(&mut self.key_chars_buf)
+            };
+
+            //Keys rejected by the filter are never passed to the distance function
+            if let Some(key_filter) = &self.key_filter {
+                if !key_filter(&key_chars[..]) {
+
+                    #[cfg(feature = "perf_counters")]
+                    { self.candidates.perf_counters.update(|fields| fields.keys_pruned_count += 1); }
+
+                    continue;
+                }
+            }
+
+            #[cfg(feature = "perf_counters")]
+            let distance_started = std::time::Instant::now();
+
+            let distance = (self.distance_function)(&key_chars[..], &self.lookup_key_chars[..]);
+
+            #[cfg(feature = "perf_counters")]
+            {
+                let elapsed = distance_started.elapsed();
+                self.candidates.perf_counters.update(|fields| {
+                    fields.distance_function_invocation_count += 1;
+                    fields.distance_latency.record(elapsed);
+                });
+            }
+
+            //Keys beyond their own threshold can't be the closest key
+            if let Some(key_threshold) = &self.key_threshold {
+                if distance > key_threshold(key_chars.len(), self.lookup_key_chars.len()) {
+                    continue;
+                }
+            }
+            if closest.as_ref().is_none_or(|(_, closest_distance)| distance < *closest_distance) {
+                closest = Some((record_key, distance));
+            }
+        }
+
+        Ok(match (closest, self.threshold) {
+            (Some((key, distance)), Some(threshold)) if distance <= threshold => Some((key, distance)),
+            (Some(closest), None) => Some(closest),
+            _ => None
+        })
+    }
+
+    /// Advances the lookup to the next record within the threshold, returning the record's closest key
+    fn next_scored(&mut self) -> Option<Result<(RecordID, OwnedKeyT, DistanceT), String>> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            let result = match self.candidates.next_group() {
+                Ok(Some(key_group_id)) => {
+                    let record_id = key_group_id.record_id();
+
+                    //A record found through one of its injected variants is evaluated against those too.  But a
+                    // record found through its keys is only evaluated against its keys, so it may be evaluated again
+                    // if it's rejected and then found through an injected variant
+                    let closest = if key_group_id.is_injected() {
+                        if !self.evaluated_records.insert(record_id) {
+                            continue;
+                        }
+                        self.evaluate_record(key_group_id, true)
+                    } else {
+                        if self.evaluated_records.contains(&record_id) || self.rejected_by_keys.contains(&record_id) {
+                            continue;
+                        }
+                        let closest = self.evaluate_record(key_group_id, false);
+                        if let Ok(None) = closest {
+                            self.rejected_by_keys.insert(record_id);
+                        } else {
+                            self.evaluated_records.insert(record_id);
+                        }
+                        closest
+                    };
+                    closest.map(|closest| closest.map(|(key, distance)| (record_id, key, distance)))
+                },
+                Ok(None) => return None,
+                Err(err) => Err(err)
+            };
+
+            match result {
+                Ok(Some(result)) => {
+
+                    #[cfg(feature = "perf_counters")]
+                    { self.candidates.perf_counters.update(|fields| fields.records_found_count += 1 ); }
+
+                    return Some(Ok(result));
+                },
+                Ok(None) => {},
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+impl <OwnedKeyT : OwnedKey, DistanceT : Copy + PartialOrd>Iterator for FuzzyLookup<'_, OwnedKeyT, DistanceT> {
+    type Item = Result<(RecordID, DistanceT), String>;
+
+    fn next(&mut self) -> Option<Result<(RecordID, DistanceT), String>> {
+        self.next_scored().map(|result| result.map(|(record_id, _key, distance)| (record_id, distance)))
+    }
+}
+
+/// A lazy iterator over the [RecordID]s found by [Table::lookup_fuzzy_scored](crate::Table::lookup_fuzzy_scored),
+/// along with the key of each record that was closest to the lookup key, and its distance
+///
+/// The results are the same as those produced by [FuzzyLookup], in the same order.  The key is the one the
+/// distance was computed from, so a caller ranking the results doesn't need to fetch the keys and compute the
+/// distances again.
+pub struct FuzzyScoredLookup<'a, OwnedKeyT : OwnedKey, DistanceT> {
+    lookup : FuzzyLookup<'a, OwnedKeyT, DistanceT>,
+}
+
+impl <OwnedKeyT : OwnedKey, DistanceT : Copy + PartialOrd>FuzzyScoredLookup<'_, OwnedKeyT, DistanceT> {
+
+    /// Collects all of the remaining results into a [Vec], or returns the error if one was encountered
+    pub fn into_vec(self) -> Result<Vec<(RecordID, OwnedKeyT, DistanceT)>, String> {
+        self.collect()
+    }
+
+    /// Returns the [LookupStrategy] this lookup uses to find its candidate records
+    pub fn strategy(&self) -> LookupStrategy {
+        self.lookup.strategy
+    }
+}
+
+impl <OwnedKeyT : OwnedKey, DistanceT : Copy + PartialOrd>Iterator for FuzzyScoredLookup<'_, OwnedKeyT, DistanceT> {
+    type Item = Result<(RecordID, OwnedKeyT, DistanceT), String>;
+
+    fn next(&mut self) -> Option<Result<(RecordID, OwnedKeyT, DistanceT), String>> {
+        self.lookup.next_scored()
+    }
+}
@@ -1,16 +1,79 @@
 //!
 //! The Key module contains all of the implementation for the abstract Key trait and all
-//! helper traits associated with it. Only the [Key] trait should be publicly re-exported.
+//! helper traits associated with it. Only the [Key] and [KeyChar] traits should be publicly re-exported.
 //! 
 
 use std::{slice};
+use std::borrow::Cow;
 use core::hash::Hash;
-use std::mem::{forget, size_of, transmute};
+use std::mem::{size_of, transmute};
 
 use serde::{Serialize};
+use bincode::Options;
 
 use super::unicode_string_helpers::{*};
 
+/// Returns a hash of a key that stays the same from one run to the next
+///
+/// NOTE: We can't use std's DefaultHasher, because its output isn't guaranteed to stay the same between
+/// releases of Rust, and the hash may be persisted.  So we use FNV-1a over the key's serialized form instead
+pub(crate) fn stable_key_hash<OwnedKeyT : OwnedKey>(key : &OwnedKeyT) -> u64 {
+    let key_bytes = bincode::serialize(key).unwrap();
+    key_bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Implemented by the types that can be the elements of a key, i.e. the [KeyCharT](crate::TableConfig::KeyCharT)
+/// of a [Table](crate::Table)
+///
+/// Besides [char], [String], and the primitive integer types, which are implemented here, any type can be used as a `KeyCharT`,
+/// for example a token ID, a phoneme, or a DNA codon.  The type only needs to be comparable, hashable and serializable,
+/// after which the default implementation of this trait is sufficient.
+///
+/// ```
+/// use fuzzy_rocks::KeyChar;
+/// use serde::{Serialize, Deserialize};
+///
+/// #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// enum Nucleotide { A, C, G, T }
+///
+/// impl KeyChar for Nucleotide {}
+/// ```
+pub trait KeyChar : 'static + Clone + Eq + Hash + Serialize + serde::de::DeserializeOwned {
+
+    /// Encodes a sequence of elements as the bytes that identify it in the database
+    ///
+    /// The default implementation concatenates the [bincode] encoding of each element.  An implementation must
+    /// encode equal sequences as equal bytes, and the encoding of a sequence must begin with the encoding of
+    /// each of its prefixes, so that prefix lookups work.
+    fn encode_key_chars(key_chars : &[Self]) -> Cow<'_, [u8]> {
+        let coder = bincode::DefaultOptions::new().with_fixint_encoding().with_little_endian();
+        let mut bytes = Vec::new();
+        for key_char in key_chars {
+            coder.serialize_into(&mut bytes, key_char).unwrap();
+        }
+        Cow::Owned(bytes)
+    }
+}
+
+/// Implements [KeyChar] for types whose in-memory representation has no padding, so a sequence of them can
+/// be viewed as bytes without copying
+macro_rules! impl_key_char_in_place {
+    ($($t:ty),*) => {
+        $(
+        impl KeyChar for $t {
+            #[inline(always)]
+            fn encode_key_chars(key_chars : &[Self]) -> Cow<'_, [u8]> {
+                Cow::Borrowed(unsafe { slice::from_raw_parts(key_chars.as_ptr() as *const u8, size_of::<Self>() * key_chars.len()) })
+            }
+        }
+        )*
+    }
+}
+impl_key_char_in_place!(char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Strings can be used as tokens, e.g. so that each element of a key is a word
+impl KeyChar for String {}
+
 /// A private trait representing the subset of key types that are owned and therefore 'static
 pub trait OwnedKey : 'static + Sized + Serialize + serde::de::DeserializeOwned + Key {
     fn as_string(&self) -> Option<String>;
@@ -24,6 +87,12 @@ pub trait OwnedKey : 'static + Sized + Serialize + serde::de::DeserializeOwned +
     fn from_key<K : Key + KeyUnsafe<KeyCharT = Self::KeyCharT>>(k : &K) -> Self; //TODO: Get rid of the KeyUnsafe trait when When GenericAssociatedTypes is stabilized
     fn from_string(s : String) -> Self;
     fn from_vec(v : Vec<Self::KeyCharT>) -> Self;
+    /// Returns the key folded to lower case, for comparisons in a table that isn't case-sensitive.  Keys
+    /// that aren't strings are returned unchanged
+    fn fold_case(self) -> Self;
+    /// Returns the key with its diacritics removed, for comparisons in a table that folds diacritics.  Keys
+    /// that aren't strings are returned unchanged
+    fn fold_diacritics(self) -> Self;
 }
 
 impl OwnedKey for String {
@@ -65,13 +134,11 @@ impl OwnedKey for String {
 
     #[inline(always)]
     fn move_into_buf<'a>(&'a self, buf : &'a mut Vec<char>) -> &'a Vec<char> {
-        let mut num_chars = 0;
-        for (i, the_char) in self.chars().enumerate() {
-            let element = unsafe{ buf.get_unchecked_mut(i) };
-            *element = the_char;
-            num_chars = i;
-        }
-        unsafe{ buf.set_len(num_chars+1) };
+        //NOTE: Writing through get_unchecked_mut() past the Vec's len is UB, even when the capacity is
+        // there, so we refill the buffer in place instead.  It doesn't reallocate as long as the buffer
+        // was created with MAX_KEY_LENGTH capacity
+        buf.clear();
+        buf.extend(self.chars());
 
         buf
     }
@@ -95,9 +162,19 @@ impl OwnedKey for String {
     fn from_vec(v : Vec<char>) -> Self {
         v.into_iter().collect()
     }
+
+    #[inline(always)]
+    fn fold_case(self) -> Self {
+        self.to_lowercase()
+    }
+
+    #[inline(always)]
+    fn fold_diacritics(self) -> Self {
+        unicode_strip_diacritics(&self)
+    }
 }
 
-impl <KeyCharT : 'static + Copy + Eq + Hash + Serialize + serde::de::DeserializeOwned>OwnedKey for Vec<KeyCharT> 
+impl <KeyCharT : KeyChar>OwnedKey for Vec<KeyCharT> 
 {
     #[inline(always)]
     fn as_string(&self) -> Option<String> {
@@ -144,6 +221,16 @@ impl <KeyCharT : 'static + Copy + Eq + Hash + Serialize + serde::de::Deserialize
     fn from_vec(v : Vec<KeyCharT>) -> Self {
         v
     }
+
+    #[inline(always)]
+    fn fold_case(self) -> Self {
+        self
+    }
+
+    #[inline(always)]
+    fn fold_diacritics(self) -> Self {
+        self
+    }
 }
 
 /// A convenience trait to automatically convert the passed argument into one of the acceptable [Key] types,
@@ -175,7 +262,7 @@ impl<'a> IntoKey for &'a String {
     }
 }
 
-impl<'a, KeyCharT : 'static + Copy + Eq + Hash + Serialize + serde::de::DeserializeOwned, const SIZE: usize> IntoKey for &'a [KeyCharT; SIZE] {
+impl<'a, KeyCharT : KeyChar, const SIZE: usize> IntoKey for &'a [KeyCharT; SIZE] {
     type Key = &'a [KeyCharT];
 
     #[inline(always)]
@@ -192,7 +279,7 @@ pub trait Key : Eq + Hash + Clone + KeyUnsafe {
     //type BorrowedKey<'a> : Key;
 
     fn num_chars(&self) -> usize;
-    fn as_bytes(&self) -> &[u8];
+    fn as_bytes(&self) -> Cow<'_, [u8]>;
     fn into_bytes(self) -> Vec<u8>;
     fn borrow_key_chars(&self) -> Option<&[Self::KeyCharT]>;
     fn get_key_chars(&self) -> Vec<Self::KeyCharT>;
@@ -213,7 +300,7 @@ pub trait KeyUnsafe : Eq + Hash + Clone {
 
 impl <KeyCharT>Key for &[KeyCharT]
     where
-    KeyCharT : 'static + Copy + Eq + Hash + Serialize + serde::de::DeserializeOwned,
+    KeyCharT : KeyChar,
 {
     //TODO: When GenericAssociatedTypes is stabilized, I will remove the KeyUnsafe trait in favor of an associated type
     // type BorrowedKey<'a> = &'a [KeyCharT];
@@ -224,22 +311,13 @@ impl <KeyCharT>Key for &[KeyCharT]
     }
 
     #[inline(always)]
-    fn as_bytes(&self) -> &[u8] {
-        let len = self.len();
-        unsafe { slice::from_raw_parts(self.as_ptr() as *const u8, size_of::<KeyCharT>() * len) }
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        KeyCharT::encode_key_chars(self)
     }
 
     #[inline(always)]
     fn into_bytes(self) -> Vec<u8> {
-
-        let mut owned_vec = self.to_vec();
-        let len = owned_vec.len();
-        let cap = owned_vec.capacity();
-
-        //Now transmute the vec into a vec of bytes
-        let result = unsafe { Vec::<u8>::from_raw_parts(owned_vec.as_mut_ptr() as *mut u8, size_of::<KeyCharT>() * len, size_of::<KeyCharT>() * cap) };
-        forget(owned_vec); //So we don't get a double-free
-        result
+        KeyCharT::encode_key_chars(self).into_owned()
     }
 
     #[inline(always)]
@@ -278,7 +356,7 @@ impl <KeyCharT>Key for &[KeyCharT]
 //TODO: When GenericAssociatedTypes is stabilized, I will remove the KeyUnsafe trait in favor of an associated type
 impl <'a, KeyCharT>KeyUnsafe for &'a [KeyCharT]
     where
-    KeyCharT : 'static + Copy + Eq + Hash + Serialize + serde::de::DeserializeOwned
+    KeyCharT : KeyChar
 {
     type KeyCharT = KeyCharT;
 
@@ -290,7 +368,7 @@ impl <'a, KeyCharT>KeyUnsafe for &'a [KeyCharT]
 
 impl <KeyCharT>Key for Vec<KeyCharT>
     where
-    KeyCharT : 'static + Copy + Eq + Hash + Serialize + serde::de::DeserializeOwned,
+    KeyCharT : KeyChar,
 {
     //TODO: When GenericAssociatedTypes is stabilized, I will remove the KeyUnsafe trait in favor of an associated type
     //type BorrowedKey<'a> = Vec<KeyCharT>;
@@ -301,21 +379,13 @@ impl <KeyCharT>Key for Vec<KeyCharT>
     }
 
     #[inline(always)]
-    fn as_bytes(&self) -> &[u8] {
-        let len = self.len();
-        unsafe { slice::from_raw_parts(self.as_ptr() as *const u8, size_of::<KeyCharT>() * len) }
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        KeyCharT::encode_key_chars(self)
     }
 
     #[inline(always)]
     fn into_bytes(self) -> Vec<u8> {
-        let mut mut_self = self;
-        let len = mut_self.len();
-        let cap = mut_self.capacity();
-
-        let result = unsafe { Vec::<u8>::from_raw_parts(mut_self.as_mut_ptr() as *mut u8, size_of::<KeyCharT>() * len, size_of::<KeyCharT>() * cap) };
-        forget(mut_self); //So we don't get a double-free
-
-        result
+        KeyCharT::encode_key_chars(&self).into_owned()
     }
 
     #[inline(always)]
@@ -353,7 +423,7 @@ impl <KeyCharT>Key for Vec<KeyCharT>
 
 impl <KeyCharT>KeyUnsafe for Vec<KeyCharT>
     where
-    KeyCharT : 'static + Copy + Eq + Hash + Serialize + serde::de::DeserializeOwned,
+    KeyCharT : KeyChar,
 {
     type KeyCharT = KeyCharT;
 
@@ -374,8 +444,8 @@ impl Key for &str
     }
 
     #[inline(always)]
-    fn as_bytes(&self) -> &[u8] {
-        str::as_bytes(self)
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(str::as_bytes(self))
     }
 
     #[inline(always)]
@@ -430,8 +500,8 @@ impl Key for String
     }
 
     #[inline(always)]
-    fn as_bytes(&self) -> &[u8] {
-        self.as_bytes()
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(String::as_bytes(self))
     }
 
     #[inline(always)]
@@ -0,0 +1,41 @@
+//!
+//! The KeyGroupHandle module contains the [KeyGroupHandle] object, used by the lower-level key group interface
+//! of the [Table](crate::Table).  The KeyGroupHandle struct is re-exported
+//!
+
+use super::records::RecordID;
+use super::key_groups::KeyGroupID;
+
+/// A reference to one of a record's key groups, returned by [Table::put_key_group](crate::Table::put_key_group)
+/// and [Table::key_groups](crate::Table::key_groups)
+///
+/// A key group is a set of a record's keys that share a single entry in the "keys" column family, and that
+/// the variant entries reference in place of the record.  Ordinarily a Table divides a record's keys into
+/// groups itself, based on [GROUP_VARIANT_OVERLAP_THRESHOLD](crate::TableConfig::GROUP_VARIANT_OVERLAP_THRESHOLD),
+/// but the key group interface lets the caller decide the partitioning instead.
+///
+/// A KeyGroupHandle can only be obtained from a Table, but it doesn't borrow the Table, so it becomes stale if the
+/// group is deleted, either with [Table::delete_key_group](crate::Table::delete_key_group) or because all of its
+/// keys were removed from the record.  Using a stale handle results in an error.
+#[derive(Copy, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct KeyGroupHandle {
+    pub(crate) key_group_id : KeyGroupID,
+}
+
+impl KeyGroupHandle {
+
+    pub(crate) fn new(key_group_id : KeyGroupID) -> Self {
+        Self{key_group_id}
+    }
+
+    /// Returns the record the key group belongs to
+    pub fn record_id(&self) -> RecordID {
+        self.key_group_id.record_id()
+    }
+
+    /// Returns the index that identifies the key group among the record's groups.  Indices are unique within
+    /// a record, but they aren't necessarily contiguous
+    pub fn group_index(&self) -> usize {
+        self.key_group_id.group_idx()
+    }
+}
@@ -12,9 +12,17 @@ use super::key::{*};
 use super::database::{*};
 use super::records::{*};
 use super::sym_spell::{*};
+use super::variant_scheme::VariantScheme;
 use super::table_config::{*};
 use super::perf_counters::{*};
 
+/// The group index reserved for the references to a record's injected variants
+const INJECTED_GROUP_IDX : usize = 0xFFFFF;
+
+/// The largest index of a record's key group.  The index must fit in the 20 bits of a [KeyGroupID] above the
+/// RecordID, and the largest 20-bit index is reserved for [INJECTED_GROUP_IDX]
+pub const MAX_GROUP_IDX : usize = INJECTED_GROUP_IDX - 1;
+
 /// A unique identifier for a key group, which includes its RecordID
 /// 
 /// Lower 44 bits are the RecordID, upper 20 bits are the GroupID
@@ -33,6 +41,14 @@ impl KeyGroupID {
         let group_component = group_idx << 44;
         Self(record_component + group_component)
     }
+    /// The KeyGroupID referenced by the variant entries of a record's injected variants.  It uses a reserved
+    /// group index, so it never identifies a real key group
+    pub fn injected_for_record(record_id : RecordID) -> Self {
+        Self::from_record_and_idx(record_id, INJECTED_GROUP_IDX)
+    }
+    pub fn is_injected(&self) -> bool {
+        self.group_idx() == INJECTED_GROUP_IDX
+    }
     pub fn record_id(&self) -> RecordID {
         RecordID::from(self.0 & 0xFFFFFFFFFFF)
     }
@@ -65,21 +81,24 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
         }
     }
 
-    fn next_available_group_id(&self) -> usize {
+    fn next_available_group_id(&self) -> Result<usize, String> {
         //It doesn't matter if we leave some holes, but we must not collide, therefore we'll
         //start at the length of the vec, and search forward from there
         let mut group_id = self.group_ids.len();
         while self.group_ids.contains(&group_id) {
             group_id += 1;
         }
-        group_id
+        if group_id > MAX_GROUP_IDX {
+            return Err("record has too many key groups".to_string());
+        }
+        Ok(group_id)
     }
 
     /// Adds a new key to a KeyGroups transient structure.  Doesn't touch the DB
     /// 
     /// This function is the owner of the decision whether or not to add a key to an existing
     /// group or to create a new group for a key
-    pub fn add_key_to_groups<KeyCharT : Clone, K, ConfigT : TableConfig>(&mut self, key : &K, update_reverse_map : bool, config : &ConfigT) -> Result<(), String>
+    pub fn add_key_to_groups<KeyCharT : Clone, K>(&mut self, key : &K, update_reverse_map : bool, params : &TableParams, scheme : &dyn VariantScheme<KeyCharT>) -> Result<(), String>
         where
         OwnedKeyT : OwnedKey<KeyCharT = KeyCharT>,
         K : Key<KeyCharT = KeyCharT>
@@ -91,7 +110,7 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
         }
 
         //Compute the variants for the key
-        let key_variants = SymSpell::<OwnedKeyT, UTF8_KEYS>::variants(key, config);
+        let key_variants = SymSpell::<OwnedKeyT, UTF8_KEYS>::variants(key, params, scheme);
 
         //Variables that determine which group we merge into, or whether we create a new key group
         let mut group_idx; //The index of the key group we'll merge this key into
@@ -99,7 +118,7 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
 
         //If we already have exactly this key as a variant, then we will add the key to that
         // key group
-        if let Some(existing_group) = self.variant_reverse_lookup_map.get(key.as_bytes()) {
+        if let Some(existing_group) = self.variant_reverse_lookup_map.get(key.as_bytes().as_ref()) {
             group_idx = *existing_group;
             create_new_group = false;
         } else {
@@ -107,10 +126,10 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
             // We'll need to figure out which existing key_group is the best place for this key
             // Or if we'll create a new key_group instead
 
-            //If ConfigT::GROUP_VARIANT_OVERLAP_THRESHOLD == 0, then we always add the key to group 0,
+            //If params.group_variant_overlap_threshold == 0, then we always add the key to group 0,
             //which is the record's only group.  So checking the overlap with existing groups is a
             //waste of time
-            if ConfigT::GROUP_VARIANT_OVERLAP_THRESHOLD > 0 {
+            if params.group_variant_overlap_threshold > 0 {
 
                 //Count the number of overlapping variants the key has with each existing group
                 // NOTE: It's possible the variant_reverse_lookup_map doesn't capture all of the
@@ -135,7 +154,7 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
                 .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
                 .unwrap_or((0, 0));
                 group_idx = max_group_idx;
-                create_new_group = max_overlaps < ConfigT::GROUP_VARIANT_OVERLAP_THRESHOLD; //Unless we have at least GROUP_VARIANT_OVERLAP_THRESHOLD variant overlaps we'll make a new key group.
+                create_new_group = max_overlaps < params.group_variant_overlap_threshold; //Unless we have at least GROUP_VARIANT_OVERLAP_THRESHOLD variant overlaps we'll make a new key group.
 
             } else {
                 group_idx = 0;
@@ -148,14 +167,14 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
         //B.) Combine the key and its variant into an existing group
         if create_new_group {
             //A. We have no overlap with any existing group, so we will create a new group for this key
+            //We can't count on the KeyGroupIDs not having holes so we need to use a function to
+            //find a unique ID.
+            let new_group_id = self.next_available_group_id()?;
             group_idx = self.key_group_keys.len();
             let mut new_set = HashSet::with_capacity(1);
             new_set.insert(OwnedKeyT::from_key(key));
             self.key_group_keys.push(new_set);
             self.key_group_variants.push(key_variants.clone());
-            //We can't count on the KeyGroupIDs not having holes so we need to use a function to
-            //find a unique ID.
-            let new_group_id = self.next_available_group_id();
             self.group_ids.push(new_group_id);
         } else {
             //B. We will append the key to the existing group at group_index, and merge the variants
@@ -176,7 +195,7 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
     /// Divides a list of keys up into one or more key groups based on some criteria; the primary
     /// of which is the overlap between key variants.  Keys with more overlapping variants are more
     /// likely to belong in the same group and keys with fewer or none are less likely.
-    pub fn make_groups_from_keys<'a, KeyCharT : Clone, K, KeysIterT : Iterator<Item=&'a K>, ConfigT : TableConfig>(keys_iter : KeysIterT, num_keys : usize, config : &ConfigT) -> Result<Self, String>
+    pub fn make_groups_from_keys<'a, KeyCharT : Clone, K, KeysIterT : Iterator<Item=&'a K>>(keys_iter : KeysIterT, num_keys : usize, params : &TableParams, scheme : &dyn VariantScheme<KeyCharT>) -> Result<Self, String>
         where
         OwnedKeyT : OwnedKey<KeyCharT = KeyCharT>,
         K : Key<KeyCharT = KeyCharT> + 'a
@@ -186,7 +205,7 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
         let mut groups = KeyGroups::new();
         for (key_idx, key) in keys_iter.enumerate() {
             let update_reverse_map = key_idx < num_keys-1;
-            groups.add_key_to_groups(key, update_reverse_map, config)?;
+            groups.add_key_to_groups(key, update_reverse_map, params, scheme)?;
         }
 
         Ok(groups)
@@ -195,8 +214,8 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
     /// Loads the existing key groups for a record in the [Table]
     /// 
     /// This function is used when adding new keys to a record, and figuring out which groups to
-    /// merge the keys into
-    pub fn load_key_groups<KeyCharT : Clone, ConfigT : TableConfig>(db : &DBConnection, record_id : RecordID, config : &ConfigT, perf_counters : &PerfCounters) -> Result<Self, String> 
+    /// merge the keys into.  The groups are loaded as they would be after the supplied batch were committed
+    pub fn load_key_groups<KeyCharT : Clone>(db : &DBConnection, batch : &DBWriteBatch, record_id : RecordID, params : &TableParams, scheme : &dyn VariantScheme<KeyCharT>, perf_counters : &PerfCounters) -> Result<Self, String> 
         where
         OwnedKeyT : OwnedKey<KeyCharT = KeyCharT>,
     {
@@ -204,16 +223,16 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>KeyGroups<OwnedKeyT, UTF8_KEYS> {
         let mut groups = KeyGroups::new();
 
         //Load the group indices from the rec_data table and loop over each key group
-        for (group_idx, key_group) in db.get_record_key_groups(record_id)?.enumerate() {
+        for (group_idx, key_group) in db.get_record_key_groups_in_batch(batch, record_id)?.enumerate() {
 
             let mut group_keys = HashSet::new();
             let mut group_variants = HashSet::new();
 
             //Load the group's keys and loop over each one
-            for key in db.get_keys_in_group::<OwnedKeyT>(key_group, perf_counters)? {
+            for key in db.get_keys_in_group_in_batch::<OwnedKeyT>(batch, key_group, perf_counters)? {
 
                 //Compute the variants for the key, and merge them into the group variants
-                let key_variants = SymSpell::<OwnedKeyT, UTF8_KEYS>::variants(&key, config);
+                let key_variants = SymSpell::<OwnedKeyT, UTF8_KEYS>::variants(&key, params, scheme);
 
                 //Update the reverse_lookup_map with every variant
                 for variant in key_variants.iter() {
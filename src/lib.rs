@@ -45,7 +45,7 @@
 //! //Use lookup_fuzzy, to get all matches and their distances
 //! let results : Vec<(RecordID, u8)> = table
 //!     .lookup_fuzzy("Tuesday", Some(2))
-//!     .unwrap().collect();
+//!     .unwrap().into_vec().unwrap();
 //! assert_eq!(results.len(), 2);
 //! assert!(results.contains(&(tue, 0))); //Tuesday -> Tuesday with 0 edits
 //! assert!(results.contains(&(thu, 2))); //Thursday -> Tuesday with 2 edits
@@ -71,6 +71,9 @@
 //!     U, // uracil
 //! }
 //! 
+//! //Any type can be the element of a key, once it implements KeyChar
+//! impl KeyChar for Nucleobase {}
+//! 
 //! struct Config();
 //! impl TableConfig for Config {
 //!     type KeyCharT = Nucleobase;
@@ -107,6 +110,10 @@
 //! A [Table] may be configured to encode keys as [UTF-8](https://en.wikipedia.org/wiki/UTF-8) or not, depending on your requirements.
 //! This is configured through the [TableConfig] object's [UTF8_KEYS](TableConfig::UTF8_KEYS) constant.
 //! 
+//! Variants are made by deleting individual `char`s, so an emoji or an Indic syllable made of several code points may be
+//! split apart.  Enabling the `unicode-segmentation` feature allows a Table to delete whole grapheme clusters instead.
+//! See [grapheme_clusters](TableConfig::grapheme_clusters).
+//! 
 //! ## Algorithm Details
 //! 
 //! The authoritative description of SymSpell is the ReadMe for the [SymSpell project](https://github.com/wolfgarbe/SymSpell).
@@ -167,6 +174,31 @@
 //! ```
 //! 
 //! Then, the performance counters may be reset by calling [Table::reset_perf_counters] and read by calling [Table::get_perf_counters].
+//! Alongside the counts, the time taken by each variant probe, key group load, and distance function invocation is
+//! collected in a [LatencyHistogram], from which percentiles can be estimated to see where slow lookups spend their time.
+//!
+//! ### Async API
+//!
+//! Enabling the `async` feature provides `AsyncTable`, which exposes async versions of the [Table] operations that
+//! run on the [tokio](https://docs.rs/tokio) blocking thread pool, so they can be awaited from within an async service.
+//!
+//! ### Parallel Lookups
+//!
+//! Enabling the `parallel` feature makes [Table::lookup_fuzzy_parallel] evaluate the candidate records of a lookup across
+//! the threads of the [rayon](https://docs.rs/rayon) global thread pool, when there are enough of them to be worth it.
+//! Without the feature, `lookup_fuzzy_parallel` evaluates them one after another, with the same results.
+//!
+//! ### Fuzzing
+//!
+//! Enabling the `fuzzing` feature provides `fuzz_table`, which applies a sequence of `FuzzOp`s to a [Table] and to an
+//! in-memory model, and reports the first place they disagree.  `FuzzOp::decode_ops` builds the operations from the raw
+//! bytes supplied by a fuzzer such as `cargo fuzz`, so a fuzz target can be written for any key and value types.
+//!
+//! ### CSV Import
+//!
+//! Enabling the `csv` feature provides `Table::import_csv`, which creates records from the rows of a delimited file,
+//! such as the GeoNames dataset, taking the keys from the columns chosen with a `CsvMapping`, and deserializing the
+//! rest of the columns into the value.
 //! 
 //! ### Benchmarks
 //! 
@@ -179,12 +211,14 @@
 //! 
 //! ## Database Format
 //! 
-//! DB contents are encoded using the [bincode] crate.  Currently the database contains 4 Column Families.
+//! DB contents are encoded using the [bincode] crate.  Currently the database contains 17 Column Families.
 //! 
 //! 1. The "rec_data" CF uses a little-endian-encoded [RecordID] as its key, and stores a varint-encoded `Vec` of
 //!     integers, which represent key_group indices, each of which can be combined with a `RecordID` to create a
 //!     `KeyGroupID`.  Each referenced key_group contains at least one key associated with the record.
 //!     The `rec_data` CF is the place to start when constructing the complete set of keys associated with a record.
+//!     If the record's value is small enough to be stored inline (see [INLINE_VALUE_MAX_SIZE](TableConfig::INLINE_VALUE_MAX_SIZE)),
+//!     its serialized bytes follow the `Vec` in the same entry, and there is no entry for it in the "values" CF.
 //! 
 //! 2. The "keys" CF uses a little-endian-encoded `KeyGroupID` as its key, and stores a varint-encoded `Vec` of
 //!     OwnedKeys (think Strings), each representing a key in a key_group.  In the present implementation,
@@ -198,6 +232,63 @@
 //! 4. The "values" CF uses a little-endian-encoded [RecordID] as its key, and stores the [bincode] serialized
 //!     [ValueT](TableConfig::ValueT) associated with the record.
 //! 
+//! 5. The "quarantine" CF holds the raw bytes of entries that couldn't be decoded, when
+//!     [QUARANTINE_CORRUPT_ENTRIES](TableConfig::QUARANTINE_CORRUPT_ENTRIES) is set.  Its key is the name of the
+//!     original CF, followed by a zero byte, followed by the original key.  See [QuarantinedEntry].
+//! 
+//! 6. The "stats" CF uses a little-endian-encoded [RecordID] as its key, and stores a little-endian `u64` count of
+//!     the times the record's value was retrieved, when [TRACK_ACCESS_STATS](TableConfig::TRACK_ACCESS_STATS) is set.
+//! 
+//! 7. The "variant_pages" CF holds the overflow pages of variant entries, when [VARIANT_PAGE_SIZE](TableConfig::VARIANT_PAGE_SIZE)
+//!     is set.  Its key is the serialized key variant, followed by a big-endian `u32` page number starting from 1,
+//!     and its entries have the same format as the entries in the "variants" CF, which holds page 0.
+//! 
+//! 8. The "metadata" CF holds table-wide entries, keyed by name.  The "table_params" entry holds the [TableParams]
+//!     the table was created with, so a table built with a [RuntimeConfig] is searched the same way when it's reopened.
+//!     The "preload_manifest" entry holds the `Vec` of [RecordID]s read by [preload](Table::preload).  The
+//!     "pinned_records" entry holds the `Vec` of [RecordID]s pinned with [pin](Table::pin).  The "format_version"
+//!     entry holds the version of the on-disk format the database was written in, so a Table written in a different
+//!     format is refused rather than misread.  See [migrate](Table::migrate).  The "checksums" entry records whether
+//!     the entries in the "keys", "rec_data", and "values" CFs end with a little-endian CRC32 of the rest of the
+//!     entry, as set by [CHECKSUMS](TableConfig::CHECKSUMS).  The "cipher_check" entry is only present if the
+//!     database is encrypted with a [Cipher], and holds whether the keys are encrypted, and a value encrypted with
+//!     the cipher, to check that the database is opened with the right key.
+//! 
+//! 9. The "injected" CF uses a little-endian-encoded [RecordID] as its key, and stores a varint-encoded `Vec` of the
+//!     OwnedKeys supplied to [create_with_variants](Table::create_with_variants) as extra variants for the record.
+//!     The variant entries for these reference a `KeyGroupID` with a reserved key_group index, that has no entry
+//!     in the "keys" CF.
+//! 
+//! 10. The "exact_keys" CF uses a whole serialized key as its key, folded to lower case if the table isn't
+//!     case-sensitive and stripped of diacritics if the table folds them, and stores a varint-encoded `Vec` of the [RecordID]s of the records with that key.  It is
+//!     only maintained if [EXACT_KEY_INDEX](TableConfig::EXACT_KEY_INDEX) is set.
+//! 
+//! 11. The "phonetic" CF uses a phonetic code, encoded as UTF-8, as its key, and stores a varint-encoded `Vec` of the
+//!     [RecordID]s of the records with a key that has that code.  It is only maintained if
+//!     [PHONETIC_FUNCTION](TableConfig::PHONETIC_FUNCTION) is set.
+//! 
+//! 12. The "ngrams" CF uses an encoded n-gram of a key as its key, and stores a varint-encoded `Vec` of the [RecordID]s
+//!     of the records with a key that contains that n-gram.  It is only maintained if the [INDEX_STRATEGY](TableConfig::INDEX_STRATEGY)
+//!     is [Ngrams](IndexStrategy::Ngrams).
+//! 
+//! 13. The "tokens" CF uses a variant of a whitespace-separated token of a key, encoded as UTF-8, as its key, and stores
+//!     a varint-encoded `Vec` of the [RecordID]s of the records with a key that has a token with that variant.  It is
+//!     only maintained if [TOKEN_INDEX](TableConfig::TOKEN_INDEX) is set.
+//! 
+//! 14. The "synonyms" CF uses a term, encoded as UTF-8, as its key, and stores a varint-encoded `Vec` of the terms
+//!     added as its synonyms with [add_synonyms](Table::add_synonyms).  It is only used if [SYNONYMS](TableConfig::SYNONYMS)
+//!     is set.
+//! 
+//! 15. The "record_meta" CF uses a little-endian-encoded [RecordID] as its key, and stores the record's [RecordMetadata],
+//!     encoded with bincode.  It is only maintained if [RECORD_METADATA](TableConfig::RECORD_METADATA) or [TTL](TableConfig::TTL) is set.
+//! 
+//! 16. The "tombstones" CF uses a little-endian-encoded [RecordID] as its key, and has an entry with an empty value for
+//!     each record marked as deleted by [soft_delete](Table::soft_delete) that hasn't been purged yet.
+//! 
+//! 17. The "blobs" CF uses a little-endian-encoded [RecordID] followed by a big-endian `u32` chunk index as its key,
+//!     and stores a chunk of a value that is too big for a single entry in the "values" CF, as set by
+//!     [BLOB_THRESHOLD](TableConfig::BLOB_THRESHOLD).  A value split into chunks has no entry in the "values" CF.
+//! 
 //! ## Future Work
 //! 
 //! 1. Optimization for crowded neighborhoods in the key metric-space.  The current design optimizes for the case
@@ -290,8 +381,8 @@
 //!     way to return a deterministic result set is to return more than k results. (or fewer than k, but that may
 //!     mean a set with no results, which is probably not what the caller wants.)
 //! 
-//! 5. Save the TableConfig to the database, in order to detect an error when the config changes in a way that makes
-//!     the database invalid.  Also include a software version check, and create a function to represent which
+//! 5. Save the rest of the TableConfig to the database, alongside the [TableParams], in order to detect an error when
+//!     the config changes in a way that makes the database invalid.  Also include a software version check, and create a function to represent which
 //!     software versions contain database format compatibility breaks.  Open Question: Should we store a checksum
 //!     of the distance function?  The function may be re-compiled or changed internally without changing behavior,
 //!     but we can't know that.
@@ -339,18 +430,103 @@
 pub mod unicode_string_helpers;
 mod bincode_helpers;
 mod database;
+mod lock_file;
+mod migration;
+mod backup;
+pub use backup::BackupInfo;
 mod key;
-pub use key::Key;
+pub use key::{Key, KeyChar};
 mod records;
 pub use records::RecordID;
+mod record_metadata;
+pub use record_metadata::RecordMetadata;
 mod table_config;
-pub use table_config::{TableConfig, DistanceFunction, DefaultTableConfig, MAX_KEY_LENGTH};
+pub use table_config::{TableConfig, DistanceFunction, VariantPriorityFunction, PhoneticFunction, PhoneticEncoding, DefaultTableConfig, WindowsServiceTableConfig, RuntimeConfig, RuntimeConfigBuilder, TableParams, MAX_KEY_LENGTH};
 mod key_groups;
 mod sym_spell;
+mod variant_scheme;
+pub use variant_scheme::{VariantScheme, DeletionVariants};
+mod phonetic;
+mod ngrams;
+mod tokens;
+mod synonyms;
+pub use ngrams::IndexStrategy;
+mod fallback_scan;
+pub use fallback_scan::FallbackScan;
+mod rocks_config;
+pub use rocks_config::RocksConfig;
+mod db_statistics;
+pub use db_statistics::DBStatistics;
+mod disk_usage;
+pub use disk_usage::{DiskUsage, CFDiskUsage};
+mod db_properties;
+pub use db_properties::{DBProperties, CFProperties};
+mod shared_database;
+pub use shared_database::SharedDatabase;
+mod cipher;
+pub use cipher::Cipher;
 mod perf_counters;
+mod access_stats;
+mod query_cache;
+mod pin_cache;
 mod table;
 pub use table::{Table};
-pub use perf_counters::{PerfCounterFields};
+mod banded_levenshtein;
+mod fuzzy_lookup;
+pub use fuzzy_lookup::{FuzzyRawLookup, FuzzyLookup, FuzzyScoredLookup, LookupStrategy};
+mod cursor;
+pub use cursor::LookupCursor;
+mod key_group_handle;
+pub use key_group_handle::KeyGroupHandle;
+mod transaction;
+pub use transaction::{Transaction};
+mod table_handle;
+pub use table_handle::{TableHandle};
+mod queued_table;
+pub use queued_table::{QueuedTable, WriteHandle};
+mod read_only_table;
+pub use read_only_table::{ReadOnlyTable};
+mod sharded_table;
+pub use sharded_table::{ShardedTable, ShardedRecordID};
+mod shadow_table;
+pub use shadow_table::{ShadowTable, ShadowReport, ShadowDifference, ShadowQueryKind};
+mod overlay;
+pub use overlay::{SessionOverlay, OverlayRecordID};
+pub mod registry;
+#[cfg(feature = "async")]
+mod async_table;
+#[cfg(feature = "async")]
+pub use async_table::{AsyncTable};
+pub use perf_counters::{PerfCounterFields, LatencyHistogram};
+mod quarantine;
+pub use quarantine::QuarantinedEntry;
+mod verify;
+pub use verify::{VerifyReport, GcReport, IntegrityReport, IntegrityIssue};
+mod shutdown;
+pub use shutdown::ShutdownSummary;
+mod did_you_mean;
+pub use did_you_mean::{PhraseCorrection, TokenCorrection};
+mod compound;
+pub use compound::CompoundMatch;
+mod export;
+pub use export::{RecordExport, ExportedRecord};
+mod table_merge;
+pub use table_merge::{MergeConflictPolicy, MergeSummary};
+mod parallel_insert;
+mod validation;
+pub use validation::{KeyValidator, ValueValidator};
+mod access_control;
+pub use access_control::{AccessHook, AccessOperation, AccessTarget, TenantTable, TenantTableMut};
+mod merge_diagnostics;
+pub use merge_diagnostics::{MergeDiagnostics, MergeAnomaly, MergeAnomalyKind};
+#[cfg(feature = "fuzzing")]
+mod fuzzing;
+#[cfg(feature = "fuzzing")]
+pub use fuzzing::{FuzzOp, fuzz_table};
+#[cfg(feature = "csv")]
+mod csv_import;
+#[cfg(feature = "csv")]
+pub use csv_import::CsvMapping;
 
 
 #[cfg(test)]
@@ -534,7 +710,7 @@ mod tests {
         //Test lookup_fuzzy with a perfect match, using the supplied edit_distance function
         //In this case, we should only get one match within edit-distance 2
         let results : Vec<(String, String, u8)> = table.lookup_fuzzy("Saturday", Some(2))
-            .unwrap().map(|(record_id, distance)| {
+            .unwrap().into_vec().unwrap().into_iter().map(|(record_id, distance)| {
                 let (key, val) = table.get(record_id).unwrap();
                 (key, val, distance)
             }).collect();
@@ -545,7 +721,7 @@ mod tests {
 
         //Test lookup_fuzzy with a perfect match, but where we'll hit another imperfect match as well
         let results : Vec<(String, String, u8)> = table.lookup_fuzzy("Tuesday", Some(2))
-            .unwrap().map(|(record_id, distance)| {
+            .unwrap().into_vec().unwrap().into_iter().map(|(record_id, distance)| {
                 let (key, val) = table.get(record_id).unwrap();
                 (key, val, distance)
             }).collect();
@@ -554,12 +730,12 @@ mod tests {
         assert!(results.contains(&("Thursday".to_string(), "Mokuyoubi".to_string(), 2)));
 
         //Test lookup_fuzzy where we should get no match
-        let results : Vec<(RecordID, u8)> = table.lookup_fuzzy("Rahu", Some(2)).unwrap().collect();
+        let results : Vec<(RecordID, u8)> = table.lookup_fuzzy("Rahu", Some(2)).unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 0);
 
         //Test lookup_fuzzy_raw, to get all of the SymSpell Delete variants
         //We're testing the fact that characters beyond `config.meaningful_key_len` aren't used for the comparison
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Sunday. That's my fun day.").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Sunday. That's my fun day.").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0], sun);
 
@@ -576,7 +752,7 @@ mod tests {
         //Delete "Saturday" and make sure we see no matches when we try to search for it
         table.delete(sat).unwrap();
         assert!(table.get_one_key(sat).is_err());
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Saturday").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Saturday").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 0);
 
         //Test replacing a record with another one and ensure the right data is retained
@@ -586,7 +762,7 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].0, "Miercoles");
         assert_eq!(results[0].1, "Zhousan");
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Mercoledi").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Mercoledi").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0], wed);
 
@@ -617,13 +793,13 @@ mod tests {
         //Add some new keys to it, and verify that it can be found using any of its three keys
         table.add_keys(sat, &["Sabado", "Zhouliu"]).unwrap();
         assert_eq!(table.keys_count(sat).unwrap(), 3);
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Saturday").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Saturday").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0], sat);
         let results : Vec<RecordID> = table.lookup_exact("Zhouliu").unwrap().collect();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0], sat);
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Sabato").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Sabato").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0], sat);
 
@@ -633,9 +809,9 @@ mod tests {
         assert_eq!(table.keys_count(sat).unwrap(), 2);
         let results : Vec<RecordID> = table.lookup_exact("Sabado").unwrap().collect();
         assert_eq!(results.len(), 0);
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Sabato").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Sabato").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 0);
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Saturnsday").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Saturnsday").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0], sat);
         let results : Vec<RecordID> = table.lookup_exact("Zhouliu").unwrap().collect();
@@ -653,9 +829,9 @@ mod tests {
         //Test that replacing the keys of a record doesn't leave any orphaned variants
         table.replace_keys(sat, &["Sabado"]).unwrap();
         assert_eq!(table.keys_count(sat).unwrap(), 1);
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Saturday").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Saturday").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 0);
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Zhouliu").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Zhouliu").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 0);
 
         //Test that adding the same key again doesn't result in multiple copies of the key
@@ -671,18 +847,18 @@ mod tests {
         assert_eq!(table.keys_count(fri).unwrap(), 5);
         table.remove_keys(fri, &["Vendredi"]).unwrap();
         assert_eq!(table.keys_count(fri).unwrap(), 4);
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Vendredi").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Vendredi").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1); //We'll still get Venerdi as a fuzzy match
 
         //Try deleting the non-existent key with valid variants, to make sure nothing breaks
         table.remove_keys(fri, &["Vendredi"]).unwrap();
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Vendredi").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Vendredi").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1); //We'll still get Venerdi as a fuzzy match
         assert_eq!(table.keys_count(fri).unwrap(), 4);
 
         //Finally delete "Venerdi", and make sure the variants are all gone
         table.remove_keys(fri, &["Venerdi"]).unwrap();
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Vendredi").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("Vendredi").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 0);
         assert_eq!(table.keys_count(fri).unwrap(), 3);
 
@@ -693,7 +869,7 @@ mod tests {
         let results : Vec<RecordID> = table.lookup_exact(&key_array).unwrap().collect();
         assert_eq!(results.len(), 1);
         assert!(results.contains(&sun_japanese));
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw(&key_array).unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw(&key_array).unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1);
         assert!(results.contains(&sun_japanese));
 
@@ -702,7 +878,7 @@ mod tests {
         let results : Vec<RecordID> = table.lookup_exact("土曜日").unwrap().collect();
         assert_eq!(results.len(), 1);
         assert!(results.contains(&sat_japanese));
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw("土曜日").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw("土曜日").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 2);
         assert!(results.contains(&sat_japanese));
         assert!(results.contains(&sun_japanese));
@@ -736,11 +912,2451 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert!(results.contains(&pi));
         
-        let results : Vec<RecordID> = table.lookup_fuzzy_raw(b"ne").unwrap().collect();
+        let results : Vec<RecordID> = table.lookup_fuzzy_raw(b"ne").unwrap().into_vec().unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0], one);
     }
 
+    #[test]
+    /// This tests keys made of tokens that aren't chars, in this case words
+    fn token_key_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = String;
+            type DistanceT = u8;
+            type ValueT = u32;
+            const MAX_DELETES : usize = 1;
+            const UTF8_KEYS : bool = false;
+        }
+        let mut table = Table::<Config, false>::new("token_key_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+
+        let words = |phrase : &str| -> Vec<String> { phrase.split(' ').map(|word| word.to_string()).collect() };
+        let new_york = table.insert(words("new york city"), &1).unwrap();
+        let _york = table.insert(words("york minster"), &2).unwrap();
+
+        let results : Vec<(RecordID, u8)> = table.lookup_fuzzy(words("new york"), Some(1)).unwrap().into_vec().unwrap();
+        assert_eq!(results, vec![(new_york, 1)]);
+        let results : Vec<RecordID> = table.lookup_exact(words("new york city")).unwrap().collect();
+        assert_eq!(results, vec![new_york]);
+        assert_eq!(table.get_one_key(new_york).unwrap(), words("new york city"));
+    }
+
+    #[test]
+    /// This tests that a Table can be shared across threads for concurrent lookups
+    fn concurrent_lookup_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("concurrent_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+
+        let sun = table.insert("Sunday", &"Nichiyoubi".to_string()).unwrap();
+        let sat = table.insert("Saturday", &"Douyoubi".to_string()).unwrap();
+
+        //Perform lookups from several threads at once, sharing the table through an Arc
+        let table = std::sync::Arc::new(table);
+        let threads : Vec<_> = (0..4).map(|_| {
+            let table = table.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    let results : Vec<RecordID> = table.lookup_best("Sonday").unwrap().collect();
+                    assert_eq!(results, vec![sun]);
+                    let results : Vec<RecordID> = table.lookup_best("Saturdy").unwrap().collect();
+                    assert_eq!(results, vec![sat]);
+                }
+            })
+        }).collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    /// This tests the per-record access statistics
+    fn access_stats_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const TRACK_ACCESS_STATS : bool = true;
+        }
+        let mut table = Table::<Config, true>::new("access_stats_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+
+        let mon = table.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+        let tue = table.insert("Tuesday", &"Kayoubi".to_string()).unwrap();
+        let wed = table.insert("Wednesday", &"Suiyoubi".to_string()).unwrap();
+
+        for _ in 0..3 {
+            table.get_value(tue).unwrap();
+        }
+        table.get(wed).unwrap();
+
+        assert_eq!(table.record_hits(mon).unwrap(), 0);
+        assert_eq!(table.record_hits(tue).unwrap(), 3);
+        assert_eq!(table.top_records(2).unwrap(), vec![(tue, 3), (wed, 1)]);
+        assert_eq!(table.save_preload_manifest(2).unwrap(), 2);
+
+        //Deleting a record should reset its count, and leave it out of the preload
+        table.delete(tue).unwrap();
+        assert_eq!(table.record_hits(tue).unwrap(), 0);
+        assert_eq!(table.top_records(2).unwrap(), vec![(wed, 1)]);
+        assert_eq!(table.preload().unwrap(), 1);
+
+        //Shutting down writes the hits that are still pending
+        table.get_value(mon).unwrap();
+        assert_eq!(table.shutdown().unwrap().flushed_hits, 1);
+        let table = Table::<Config, true>::new("access_stats_test.rocks", Config()).unwrap();
+        assert_eq!(table.record_hits(mon).unwrap(), 1);
+    }
+
+    #[test]
+    /// Tests that lookups beyond MAX_DELETES fail unless a fallback scan is configured, and find the distant keys if it is
+    fn fallback_scan_test() {
+
+        struct Config<const SAMPLED : bool>();
+        impl <const SAMPLED : bool>TableConfig for Config<SAMPLED> {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+            const UTF8_KEYS : bool = true;
+            const MAX_DELETES : usize = 1;
+            const FALLBACK_SCAN : FallbackScan = if SAMPLED { FallbackScan::Sampled(1) } else { FallbackScan::Full };
+        }
+
+        //Within MAX_DELETES the index is used, but beyond it the lookup must opt in
+        let mut table = Table::<DefaultTableConfig, true>::new("fallback_scan_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        table.insert("Tokyo", &"Japan".to_string()).unwrap();
+        assert_eq!(table.lookup_fuzzy_extended("Tokio", None, 1).unwrap().into_vec().unwrap().len(), 1);
+        assert!(table.lookup_fuzzy_extended("Tokio", None, DefaultTableConfig::MAX_DELETES + 1).is_err());
+        drop(table);
+
+        let mut table = Table::<Config<false>, true>::new("fallback_scan_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let kyoto = table.insert("Kyoto", &1).unwrap();
+        let osaka = table.insert("Osaka", &2).unwrap();
+        table.insert("Nagoya", &3).unwrap();
+        let mut results : Vec<(RecordID, u8)> = table.lookup_fuzzy_extended("Kiotu", None, 3).unwrap().into_vec().unwrap();
+        results.sort();
+        assert_eq!(results, vec![(kyoto, 2)]);
+        assert_eq!(table.lookup_fuzzy_extended("Osoko", None, 2).unwrap().into_vec().unwrap(), vec![(osaka, 2)]);
+        drop(table);
+
+        //A sampled scan still finds what the index finds, but only the sampled records beyond it
+        let table = Table::<Config<true>, true>::new("fallback_scan_test.rocks", Config()).unwrap();
+        assert_eq!(table.lookup_fuzzy_extended("Kyota", None, 3).unwrap().into_vec().unwrap(), vec![(kyoto, 1)]);
+        assert_eq!(table.lookup_fuzzy_extended("Kiotu", None, 3).unwrap().into_vec().unwrap(), vec![(kyoto, 2)]);
+        assert_eq!(table.lookup_fuzzy_extended("Osoko", None, 2).unwrap().into_vec().unwrap().len(), 0);
+    }
+
+    #[test]
+    /// Tests that a failed operation in a Transaction leaves none of its writes behind, while the operations
+    /// before and after it are committed
+    fn transaction_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("transaction_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        table.add_key_validator(|key| if key.iter().all(|c| c.is_alphabetic()) { Ok(()) } else { Err("keys must be alphabetic".to_string()) });
+        let mon = table.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+
+        //Replacing the keys deletes the old keys before the new ones are rejected
+        let mut transaction = table.transaction();
+        let tue = transaction.insert("Tuesday", &"Kayoubi".to_string()).unwrap();
+        assert!(transaction.replace_keys(mon, &["Monday!"]).is_err());
+        transaction.add_keys(mon, &["Lundi"]).unwrap();
+        transaction.commit().unwrap();
+
+        let mut keys : Vec<String> = table.get_keys(mon).unwrap().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["Lundi", "Monday"]);
+        assert_eq!(table.lookup_exact("Monday").unwrap().collect::<Vec<RecordID>>(), vec![mon]);
+        assert_eq!(table.get_value(tue).unwrap(), "Kayoubi");
+    }
+
+    #[test]
+    /// Tests that the query cache sees the writes of a committed Transaction, and of a parallel create
+    fn query_cache_commit_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+            const UTF8_KEYS : bool = true;
+            const QUERY_CACHE_CAPACITY : usize = 4;
+        }
+
+        let mut table = Table::<Config, true>::new("query_cache_commit_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let mon = table.insert("Monday", &1).unwrap();
+        assert_eq!(table.lookup_fuzzy_cached("Mondy", Some(1)).unwrap(), vec![(mon, 1)]);
+
+        let mut transaction = table.transaction();
+        transaction.delete(mon).unwrap();
+        let mon = transaction.insert("Mundy", &2).unwrap();
+        transaction.commit().unwrap();
+        assert_eq!(table.lookup_fuzzy_cached("Mondy", Some(1)).unwrap(), vec![(mon, 1)]);
+
+        let record_ids = table.create_parallel(&[(vec!["Monday"], 3)], 1).unwrap();
+        let mut results = table.lookup_fuzzy_cached("Mondy", Some(1)).unwrap();
+        results.sort();
+        assert_eq!(results, vec![(mon, 1), (record_ids[0], 1)]);
+    }
+
+    #[test]
+    /// Tests that lookups are expanded with the synonyms of their tokens, and stop being expanded once the synonyms
+    /// are removed
+    fn synonyms_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+            const UTF8_KEYS : bool = true;
+            const SYNONYMS : bool = true;
+            const QUERY_CACHE_CAPACITY : usize = 4;
+            fn case_sensitive(&self) -> bool {
+                false
+            }
+        }
+
+        let mut table = Table::<Config, true>::new("synonyms_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let saint_louis = table.insert("Saint Louis", &1).unwrap();
+        let main_street = table.insert("Main Street", &2).unwrap();
+        assert_eq!(table.lookup_fuzzy("St Louis", Some(1)).unwrap().into_vec().unwrap().len(), 0);
+        assert_eq!(table.lookup_fuzzy_cached("St Louis", Some(1)).unwrap(), vec![]);
+
+        //A term may belong to more than one group, without the groups becoming equivalent to each other
+        table.add_synonyms(&["St", "Saint"]).unwrap();
+        table.add_synonyms(&["st", "street"]).unwrap();
+        assert_eq!(table.synonyms("ST").unwrap(), vec!["saint".to_string(), "street".to_string()]);
+        assert_eq!(table.synonyms("saint").unwrap(), vec!["st".to_string()]);
+        assert!(table.add_synonyms(&["new york", "nyc"]).is_err());
+
+        assert_eq!(table.lookup_fuzzy("St Louis", Some(1)).unwrap().into_vec().unwrap(), vec![(saint_louis, 0)]);
+        assert_eq!(table.lookup_fuzzy_raw("Main St").unwrap().into_vec().unwrap(), vec![main_street]);
+        assert_eq!(table.lookup_fuzzy_cached("St Louis", Some(1)).unwrap(), vec![(saint_louis, 0)]);
+        assert_eq!(table.lookup_fuzzy("Saint Street", Some(1)).unwrap().into_vec().unwrap().len(), 0);
+
+        table.remove_synonyms(&["saint", "st"]).unwrap();
+        assert_eq!(table.synonyms("st").unwrap(), vec!["street".to_string()]);
+        assert!(table.synonyms("saint").unwrap().is_empty());
+        assert_eq!(table.lookup_fuzzy_cached("St Louis", Some(1)).unwrap(), vec![]);
+    }
+
+    #[test]
+    /// Tests that stop words are left out of the indexed form of keys, but not out of the stored keys
+    fn stop_words_test() {
+
+        let config = RuntimeConfig::builder().case_sensitive(false).stop_words(&["The", "inc", "llc"]).build();
+        let mut table = Table::<RuntimeConfig, true>::new("stop_words_test.rocks", config).unwrap();
+        table.reset().unwrap();
+        assert_eq!(table.params().stop_words, vec!["the".to_string(), "inc".to_string(), "llc".to_string()]);
+
+        let acme = table.insert("The Acme Company LLC", &"Acme".to_string()).unwrap();
+        let the = table.insert("The", &"The".to_string()).unwrap();
+        assert_eq!(table.get_keys(acme).unwrap().collect::<Vec<String>>(), vec!["The Acme Company LLC".to_string()]);
+
+        assert_eq!(table.lookup_exact("acme company inc").unwrap().collect::<Vec<_>>(), vec![acme]);
+        assert_eq!(table.lookup_fuzzy("Acme Compny", Some(1)).unwrap().into_vec().unwrap(), vec![(acme, 1)]);
+        assert_eq!(table.lookup_exact("the").unwrap().collect::<Vec<_>>(), vec![the]);
+    }
+
+    #[test]
+    /// Tests that each record's metadata follows the changes to the record
+    fn record_metadata_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+            const UTF8_KEYS : bool = true;
+            const RECORD_METADATA : bool = true;
+        }
+
+        let mut table = Table::<Config, true>::new("record_metadata_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let paris = table.insert("Paris", &1).unwrap();
+        let rome = table.insert("Rome", &2).unwrap();
+        let created = table.get_metadata(paris).unwrap().unwrap();
+        assert_eq!(created.created, created.modified);
+        assert_eq!(created.tag, None);
+
+        //Setting a tag isn't a modification, but changing a key or value is
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let sync_time = crate::record_metadata::now_millis();
+        table.set_tag(rome, Some("capital")).unwrap();
+        assert!(table.records_modified_since(sync_time).unwrap().is_empty());
+        table.add_keys(paris, &["Lutetia"]).unwrap();
+        table.replace_value(rome, &3).unwrap();
+        assert_eq!(table.records_modified_since(sync_time).unwrap(), vec![paris, rome]);
+
+        //A change made in the same millisecond as the sync time is still found
+        let sync_time = table.get_metadata(rome).unwrap().unwrap().modified;
+        assert!(table.records_modified_since(sync_time).unwrap().contains(&rome));
+
+        let modified = table.get_metadata(paris).unwrap().unwrap();
+        assert_eq!(modified.created, created.created);
+        assert!(modified.modified > created.modified);
+        assert_eq!(table.get_metadata(rome).unwrap().unwrap().tag.as_deref(), Some("capital"));
+
+        //A deleted record's metadata goes with it
+        table.delete(rome).unwrap();
+        assert!(table.get_metadata(rome).is_err());
+        assert_eq!(table.records_modified_since(0).unwrap(), vec![paris]);
+    }
+
+    #[test]
+    /// Tests that soft deleted records are hidden from lookups until they are purged, and that the tombstones
+    /// survive the Table being reopened
+    fn soft_delete_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+            const UTF8_KEYS : bool = true;
+            const EXACT_KEY_INDEX : bool = true;
+        }
+
+        let mut table = Table::<Config, true>::new("soft_delete_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let paris = table.insert("Paris", &1).unwrap();
+        let parish = table.insert("Parish", &2).unwrap();
+        let rome = table.insert("Rome", &3).unwrap();
+        assert_eq!(table.lookup_fuzzy_raw("Paris").unwrap().into_vec().unwrap().len(), 2);
+
+        table.soft_delete(paris).unwrap();
+        table.soft_delete(rome).unwrap();
+        assert!(table.soft_delete(RecordID::from(99)).is_err());
+        drop(table);
+
+        let mut table = Table::<Config, true>::new("soft_delete_test.rocks", Config()).unwrap();
+        assert!(table.is_soft_deleted(paris));
+        assert_eq!(table.lookup_fuzzy_raw("Paris").unwrap().into_vec().unwrap(), vec![parish]);
+        assert_eq!(table.lookup_exact("Paris").unwrap().count(), 0);
+        assert!(!table.contains_key_exact("Rome").unwrap());
+        assert_eq!(table.get_value(paris).unwrap(), 1);
+
+        //A full delete of a soft deleted record removes its tombstone too
+        table.delete(rome).unwrap();
+        assert!(!table.is_soft_deleted(rome));
+        assert_eq!(table.purge_deleted().unwrap(), 1);
+        assert!(table.get_value(paris).is_err());
+        assert_eq!(table.lookup_fuzzy_raw("Paris").unwrap().into_vec().unwrap(), vec![parish]);
+        assert!(table.verify().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that a lookup with a value prefetch returns the same results as one without
+    fn value_prefetch_test() {
+
+        struct Config<const DEPTH : usize>();
+        impl <const DEPTH : usize>TableConfig for Config<DEPTH> {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const UTF8_KEYS : bool = true;
+            const VALUE_PREFETCH_DEPTH : usize = DEPTH;
+        }
+
+        //Enough results for several batches, with one of the values coming from the PinCache
+        let mut table = Table::<Config<0>, true>::new("value_prefetch_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        for i in 0..100 {
+            table.insert(format!("k{:02}", i), &format!("value {}", i)).unwrap();
+        }
+        let pinned = table.lookup_exact("k42").unwrap().next().unwrap();
+        table.pin(pinned).unwrap();
+        let mut expected = table.lookup_fuzzy_with_values("k00", Some(2)).unwrap();
+        expected.sort();
+        assert_eq!(expected.len(), 100);
+        drop(table);
+
+        let table = Table::<Config<1>, true>::new("value_prefetch_test.rocks", Config()).unwrap();
+        let mut results = table.lookup_fuzzy_with_values("k00", Some(2)).unwrap();
+        results.sort();
+        assert_eq!(results, expected);
+        assert_eq!(table.lookup_fuzzy_with_values("zzzz", Some(1)).unwrap(), vec![]);
+    }
+
+    #[test]
+    /// Tests that pinned records stay consistent with the database as they're changed, and across reopening
+    fn pinned_records_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const UTF8_KEYS : bool = true;
+            const INLINE_VALUE_MAX_SIZE : usize = 16;
+        }
+        let mut table = Table::<Config, true>::new("pinned_records_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+
+        let mon = table.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+        let tue = table.insert("Tuesday", &"Kayoubi".to_string()).unwrap();
+        assert!(table.pin(RecordID::from(7)).is_err());
+        table.pin(mon).unwrap();
+        table.pin(tue).unwrap();
+        table.pin(mon).unwrap();
+        assert_eq!(table.pinned_records(), vec![mon, tue]);
+
+        //Changing a pinned record's keys and value, including moving the value out of line
+        table.add_keys(mon, &["Lundi"]).unwrap();
+        table.replace_value(mon, &"Getsuyoubi, the day of the moon".to_string()).unwrap();
+        assert_eq!(table.lookup_exact("Lundi").unwrap().collect::<Vec<_>>(), vec![mon]);
+        assert_eq!(table.lookup_fuzzy("Lundy", Some(1)).unwrap().into_vec().unwrap(), vec![(mon, 1)]);
+        assert_eq!(table.get_value(mon).unwrap(), "Getsuyoubi, the day of the moon");
+        assert_eq!(table.get_values(&[tue, mon]).unwrap(), vec!["Kayoubi".to_string(), "Getsuyoubi, the day of the moon".to_string()]);
+
+        //Deleting a record unpins it, so its RecordID isn't pinned when it's reused
+        table.delete(tue).unwrap();
+        assert_eq!(table.pinned_records(), vec![mon]);
+        assert!(table.get_value(tue).is_err());
+        let wed = table.insert("Wednesday", &"Suiyoubi".to_string()).unwrap();
+        assert_eq!(wed, tue);
+        assert_eq!(table.pinned_records(), vec![mon]);
+        assert_eq!(table.get_value(wed).unwrap(), "Suiyoubi");
+
+        //The pinned records are loaded again when the Table is reopened
+        drop(table);
+        let mut table = Table::<Config, true>::new("pinned_records_test.rocks", Config()).unwrap();
+        assert_eq!(table.pinned_records(), vec![mon]);
+        let mut keys : Vec<String> = table.get_keys(mon).unwrap().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["Lundi", "Monday"]);
+        table.unpin(mon).unwrap();
+        assert_eq!(table.pinned_records(), vec![]);
+        assert_eq!(table.get_value(mon).unwrap(), "Getsuyoubi, the day of the moon");
+    }
+
+    #[test]
+    /// Tests that lookups and deletes work when variant entries are split across multiple pages
+    fn variant_paging_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+            const VARIANT_PAGE_SIZE : usize = 2;
+        }
+        let mut table = Table::<Config, true>::new("variant_paging_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+
+        //Every one of these keys shares the variant "a", so its entry will need several pages
+        let record_ids : Vec<RecordID> = ["ab", "ac", "ad", "ae", "af"].iter().enumerate()
+            .map(|(i, key)| table.insert(*key, &(i as i32)).unwrap()).collect();
+
+        let mut results : Vec<RecordID> = table.lookup_fuzzy_raw("a").unwrap().into_vec().unwrap();
+        results.sort();
+        assert_eq!(results, record_ids);
+
+        //The lookup is lazy, so taking only some results should work the same way
+        assert_eq!(table.lookup_fuzzy_raw("a").unwrap().take(2).collect::<Result<Vec<_>, String>>().unwrap().len(), 2);
+        assert_eq!(table.lookup_fuzzy("a", Some(1)).unwrap().into_vec().unwrap().len(), 5);
+
+        //Delete a record from the middle, and make sure it's gone without disturbing the others
+        table.delete(record_ids[1]).unwrap();
+        let mut results : Vec<RecordID> = table.lookup_fuzzy_raw("a").unwrap().into_vec().unwrap();
+        results.sort();
+        assert_eq!(results, vec![record_ids[0], record_ids[2], record_ids[3], record_ids[4]]);
+        assert_eq!(table.lookup_exact("ad").unwrap().collect::<Vec<RecordID>>(), vec![record_ids[2]]);
+
+        //The most specific variant, "ad" itself, is probed first, so its record should be the first result
+        assert_eq!(table.lookup_fuzzy_raw("ad").unwrap().next().transpose().unwrap(), Some(record_ids[2]));
+
+        //Limiting the lookup to one delete leaves the variant "a" unprobed
+        assert_eq!(table.lookup_fuzzy_within("adx", None, 1).unwrap().into_vec().unwrap(), vec![(record_ids[2], 1)]);
+        assert_eq!(table.lookup_fuzzy_raw_within("adx", 2).unwrap().into_vec().unwrap().len(), 4);
+
+        //The closest record is found without widening, and ties are broken by RecordID
+        assert_eq!(table.lookup_best_n("adx", 1).unwrap(), vec![(record_ids[2], 1)]);
+        assert_eq!(table.lookup_best_n("adx", 3).unwrap(), vec![(record_ids[2], 1), (record_ids[0], 2), (record_ids[3], 2)]);
+
+        assert!(table.verify().unwrap().is_consistent());
+
+        //Delete everything, and the variant should disappear
+        for record_id in [record_ids[0], record_ids[2], record_ids[3], record_ids[4]] {
+            table.delete(record_id).unwrap();
+        }
+        assert_eq!(table.lookup_fuzzy_raw("a").unwrap().into_vec().unwrap().len(), 0);
+        assert!(table.verify().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that the values are retrieved together with the results of a lookup, or for several records at once
+    fn lookup_with_values_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+        }
+        let mut table = Table::<Config, true>::new("lookup_with_values_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+
+        let record_ids : Vec<RecordID> = ["ab", "ac", "ad", "ae", "af"].iter().enumerate()
+            .map(|(i, key)| table.insert(*key, &(i as i32)).unwrap()).collect();
+
+        assert_eq!(table.lookup_exact_with_values("ad").unwrap(), vec![(record_ids[2], 2)]);
+        assert_eq!(table.lookup_best_with_values("adx").unwrap(), vec![(record_ids[2], 2)]);
+
+        //The values come back in the order the RecordIDs were supplied
+        assert_eq!(table.get_values(&[record_ids[4], record_ids[0]]).unwrap(), vec![4, 0]);
+    }
+
+    #[test]
+    /// Tests that an adaptive lookup only widens to more deletes when it hasn't found enough results
+    fn lookup_fuzzy_adaptive_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+        }
+        let mut table = Table::<Config, true>::new("lookup_fuzzy_adaptive_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+
+        let record_ids : Vec<RecordID> = ["ab", "ad", "ae", "af"].iter().enumerate()
+            .map(|(i, key)| table.insert(*key, &(i as i32)).unwrap()).collect();
+
+        //"ad" is one delete away from "adx", so an adaptive lookup wanting one result stops there, but one wanting
+        // more widens to the variant "a", shared by every record
+        assert_eq!(table.lookup_fuzzy_adaptive("adx", None, 1).unwrap(), vec![(record_ids[1], 1)]);
+        assert_eq!(table.lookup_fuzzy_adaptive("adx", None, 2).unwrap().len(), 4);
+    }
+
+    #[test]
+    /// Tests that new references are appended to the last page of a paged variant entry, even once a delete
+    /// has left room in an earlier page
+    fn variant_page_append_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = i32;
+            const VARIANT_PAGE_SIZE : usize = 2;
+        }
+        let mut table = Table::<Config, true>::new("variant_page_append_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+
+        let mut record_ids : Vec<RecordID> = ["ab", "ac", "ad", "ae", "af", "ag", "ah"].iter().enumerate()
+            .map(|(i, key)| table.insert(*key, &(i as i32)).unwrap()).collect();
+        table.delete(record_ids.remove(0)).unwrap();
+        record_ids.push(table.insert("ai", &7).unwrap());
+        record_ids.push(table.insert("aj", &8).unwrap());
+        record_ids.sort();
+
+        let mut results : Vec<RecordID> = table.lookup_fuzzy_raw("a").unwrap().into_vec().unwrap();
+        results.sort();
+        assert_eq!(results, record_ids);
+        assert!(table.verify().unwrap().is_consistent());
+
+        for record_id in record_ids {
+            table.delete(record_id).unwrap();
+        }
+        assert_eq!(table.lookup_fuzzy_raw("a").unwrap().into_vec().unwrap().len(), 0);
+        assert!(table.verify().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that a secondary instance sees the primary's writes only after it catches up
+    fn secondary_instance_test() {
+
+        let mut primary = Table::<DefaultTableConfig, true>::new("secondary_instance_test.rocks", DefaultTableConfig()).unwrap();
+        primary.reset().unwrap();
+
+        //RocksDB can't catch a secondary up across the column families dropped by a reset, so reopen the primary
+        drop(primary);
+        let mut primary = Table::<DefaultTableConfig, true>::new("secondary_instance_test.rocks", DefaultTableConfig()).unwrap();
+        let first_id = primary.insert("first", &"1".to_string()).unwrap();
+
+        let mut secondary = Table::<DefaultTableConfig, true>::open_as_secondary("secondary_instance_test.rocks", "secondary_instance_test_2.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(secondary.lookup_exact("first").unwrap().collect::<Vec<RecordID>>(), vec![first_id]);
+
+        let second_id = primary.insert("second", &"2".to_string()).unwrap();
+        secondary.catch_up_with_primary().unwrap();
+        assert_eq!(secondary.lookup_exact("second").unwrap().collect::<Vec<RecordID>>(), vec![second_id]);
+        assert_eq!(secondary.get_value(second_id).unwrap(), "2");
+
+        //A read-only instance can't catch up
+        drop(primary);
+        let mut read_only = Table::<DefaultTableConfig, true>::open_read_only("secondary_instance_test.rocks", DefaultTableConfig()).unwrap();
+        assert!(read_only.catch_up_with_primary().is_err());
+    }
+
+    #[test]
+    /// Tests that a database created before the "tombstones" column family was added can still be opened
+    /// read-only, and as a secondary instance, without creating it
+    fn missing_tombstones_test() {
+        use crate::database::{COLUMN_FAMILY_NAMES, TOMBSTONES_CF_NAME};
+
+        let mut table = Table::<DefaultTableConfig, true>::new("missing_tombstones_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        drop(table);
+
+        //Take the database back to how an older version would have left it
+        let db = rocksdb::DB::open_cf(&rocksdb::Options::default(), "missing_tombstones_test.rocks", COLUMN_FAMILY_NAMES).unwrap();
+        db.drop_cf(TOMBSTONES_CF_NAME).unwrap();
+        drop(db);
+
+        let read_only = Table::<DefaultTableConfig, true>::open_read_only("missing_tombstones_test.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(read_only.lookup_best("Fryday").unwrap().collect::<Vec<RecordID>>(), vec![friday]);
+        assert!(!read_only.is_soft_deleted(friday));
+        drop(read_only);
+        let secondary = Table::<DefaultTableConfig, true>::open_as_secondary("missing_tombstones_test.rocks", "missing_tombstones_test_2.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(secondary.get_value(friday).unwrap(), "Kinyoubi");
+        drop(secondary);
+
+        //Opening it for writing creates the column family
+        let mut table = Table::<DefaultTableConfig, true>::new("missing_tombstones_test.rocks", DefaultTableConfig()).unwrap();
+        table.soft_delete(friday).unwrap();
+        assert!(table.is_soft_deleted(friday));
+    }
+
+    #[test]
+    /// Tests that an export isn't disturbed by the primary's writes, when it's made from a secondary instance
+    fn export_stream_test() {
+
+        let mut primary = Table::<DefaultTableConfig, true>::new("export_stream_test.rocks", DefaultTableConfig()).unwrap();
+        primary.reset().unwrap();
+        drop(primary);
+        let mut primary = Table::<DefaultTableConfig, true>::new("export_stream_test.rocks", DefaultTableConfig()).unwrap();
+        let record_ids : Vec<RecordID> = ["one", "two", "three"].iter().map(|key| primary.insert(*key, &key.to_string()).unwrap()).collect();
+
+        let mut secondary = Table::<DefaultTableConfig, true>::open_as_secondary("export_stream_test.rocks", "export_stream_test_2.rocks", DefaultTableConfig()).unwrap();
+        let mut export = secondary.export_stream();
+        assert_eq!(export.next().unwrap().unwrap().keys, vec!["one".to_string()]);
+
+        //The primary keeps writing while the export is in progress, reusing the deleted record's RecordID
+        primary.delete(record_ids[1]).unwrap();
+        primary.insert("four", &"four".to_string()).unwrap();
+        let rest = export.into_vec().unwrap();
+        assert_eq!(rest.iter().map(|record| record.record_id).collect::<Vec<RecordID>>(), vec![record_ids[1], record_ids[2]]);
+        assert_eq!(rest[0].value, "two");
+
+        //Once the secondary catches up, a new export sees the writes
+        secondary.catch_up_with_primary().unwrap();
+        let records = secondary.export_stream().into_vec().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].value, "four");
+    }
+
+    #[test]
+    /// Tests that a table can't be opened twice, and that a lock left behind by a crash can be removed
+    fn lock_file_test() {
+
+        let table = Table::<DefaultTableConfig, true>::new("lock_file_test.rocks", DefaultTableConfig()).unwrap();
+        let pid = std::process::id();
+        assert_eq!(Table::<DefaultTableConfig, true>::lock_owner("lock_file_test.rocks").unwrap(), Some(pid));
+        let err = Table::<DefaultTableConfig, true>::new("lock_file_test.rocks", DefaultTableConfig()).err().unwrap();
+        assert!(err.contains(&pid.to_string()));
+
+        //Dropping the table releases the lock
+        drop(table);
+        assert_eq!(Table::<DefaultTableConfig, true>::lock_owner("lock_file_test.rocks").unwrap(), None);
+
+        //Simulate a crashed owner by writing a lock file for another process, which is never removed
+        std::fs::write("lock_file_test.rocks/FUZZY_ROCKS_LOCK", "4194304").unwrap();
+        assert_eq!(Table::<DefaultTableConfig, true>::lock_owner("lock_file_test.rocks").unwrap(), Some(4194304));
+        let err = Table::<DefaultTableConfig, true>::new("lock_file_test.rocks", DefaultTableConfig()).err().unwrap();
+        assert!(err.contains("4194304"));
+        assert!(Table::<DefaultTableConfig, true>::force_unlock("lock_file_test.rocks").unwrap());
+        assert!(!Table::<DefaultTableConfig, true>::force_unlock("lock_file_test.rocks").unwrap());
+        Table::<DefaultTableConfig, true>::new("lock_file_test.rocks", DefaultTableConfig()).unwrap();
+    }
+
+    #[test]
+    /// Tests that a table's data persists after it's closed, until the table is destroyed
+    fn close_and_destroy_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("close_and_destroy_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let record_id = table.insert("persistent", &"value".to_string()).unwrap();
+        table.close().unwrap();
+
+        let table = Table::<DefaultTableConfig, true>::new("close_and_destroy_test.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(table.get_value(record_id).unwrap(), "value");
+        table.destroy().unwrap();
+        assert!(!std::path::Path::new("close_and_destroy_test.rocks").exists());
+    }
+
+    #[test]
+    /// Tests the Damerau-Levenstein distance function directly, and as a Table's DISTANCE_FUNCTION
+    fn damerau_levenstein_test() {
+
+        let distance = |a : &str, b : &str| {
+            let a : Vec<char> = a.chars().collect();
+            let b : Vec<char> = b.chars().collect();
+            (DefaultTableConfig::levenstein_distance(&a, &b), DefaultTableConfig::damerau_levenstein_distance(&a, &b))
+        };
+        assert_eq!(distance("teh", "the"), (2, 1));
+        assert_eq!(distance("acbd", "abcd"), (2, 1));
+        assert_eq!(distance("kitten", "sitting"), (3, 3));
+        assert_eq!(distance("ca", "abc"), (3, 3));
+        assert_eq!(distance("", "abc"), (3, 3));
+        assert_eq!(distance("abc", "abc"), (0, 0));
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const DISTANCE_FUNCTION : DistanceFunction<Self::KeyCharT, Self::DistanceT> = Self::damerau_levenstein_distance;
+        }
+        let mut table = Table::<Config, true>::new("damerau_levenstein_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let the = table.insert("the", &String::new()).unwrap();
+        assert_eq!(table.lookup_fuzzy("teh", Some(1)).unwrap().into_vec().unwrap(), vec![(the, 1)]);
+    }
+
+    #[test]
+    /// Tests the Jaro-Winkler distance function directly, and as a Table's DISTANCE_FUNCTION with a similarity threshold
+    fn jaro_winkler_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = f32;
+            type ValueT = String;
+            const DISTANCE_FUNCTION : DistanceFunction<Self::KeyCharT, Self::DistanceT> = Self::jaro_winkler_distance;
+        }
+
+        let similarity = |a : &str, b : &str| {
+            let a : Vec<char> = a.chars().collect();
+            let b : Vec<char> = b.chars().collect();
+            ((1.0 - Config::jaro_winkler_distance(&a, &b)) * 1000.0).round() / 1000.0
+        };
+        assert_eq!(similarity("MARTHA", "MARHTA"), 0.961);
+        assert_eq!(similarity("DWAYNE", "DUANE"), 0.84);
+        assert_eq!(similarity("DIXON", "DICKSONX"), 0.813);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+        assert_eq!(similarity("", ""), 1.0);
+
+        let mut table = Table::<Config, true>::new("jaro_winkler_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let martha = table.insert("Martha", &String::new()).unwrap();
+        let marta = table.insert("Marta", &String::new()).unwrap();
+        table.insert("Mark", &String::new()).unwrap();
+        let results : Vec<RecordID> = table.lookup_fuzzy("Marhta", Some(Config::jaro_winkler_threshold(0.9))).unwrap().into_vec().unwrap().into_iter().map(|(record_id, _)| record_id).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results.contains(&martha) && results.contains(&marta));
+        assert_eq!(table.lookup_best("Marth").unwrap().collect::<Vec<RecordID>>(), vec![martha]);
+    }
+
+    #[test]
+    /// Tests that the merge operator invocations are counted and logged without disturbing the merge results, and that
+    /// the diagnostics can be reset
+    fn merge_diagnostics_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const MERGE_DIAGNOSTIC_LOG_LEN : usize = 1;
+            const LARGE_MERGE_OPERAND_COUNT : usize = 2;
+        }
+        let mut table = Table::<Config, true>::new("merge_diagnostics_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        table.reset_merge_diagnostics();
+
+        //Each insert merges a reference into the "Friday" variant entry, and the lookup reads it back.  RocksDB may
+        // merge the operands when it reads, flushes, or compacts the entry, so only the results are certain
+        let record_ids : Vec<RecordID> = (0..3).map(|_| table.insert("Friday", &"Kinyoubi".to_string()).unwrap()).collect();
+        let mut results : Vec<RecordID> = table.lookup_exact("Friday").unwrap().collect();
+        results.sort();
+        assert_eq!(results, record_ids);
+        table.compact_all().unwrap();
+        let mut results : Vec<RecordID> = table.lookup_exact("Friday").unwrap().collect();
+        results.sort();
+        assert_eq!(results, record_ids);
+
+        //The first merge into each variant entry has nothing to merge into
+        let diagnostics = table.merge_diagnostics();
+        assert!(diagnostics.merge_count > 0);
+        assert!(diagnostics.merge_with_none_count > 0);
+        assert_eq!(diagnostics.failed_count, 0);
+        assert_eq!(diagnostics.log.len(), 1);
+        assert_eq!(diagnostics.log[0].kind, MergeAnomalyKind::MergeWithNone);
+
+        //An invocation that is both kinds of anomaly is counted as both, but the log only has room for one
+        let collector = crate::merge_diagnostics::MergeDiagnosticsCollector::new(2, 1);
+        collector.record_merge("Friday".as_bytes(), false, 3);
+        collector.record_merge("Friday".as_bytes(), true, 1);
+        let diagnostics = collector.get();
+        assert_eq!(diagnostics.merge_count, 2);
+        assert_eq!(diagnostics.merge_with_none_count, 1);
+        assert_eq!(diagnostics.large_operand_count, 1);
+        assert_eq!(diagnostics.max_operand_count, 3);
+        assert_eq!(diagnostics.log, vec![MergeAnomaly{kind : MergeAnomalyKind::MergeWithNone, variant : "Friday".as_bytes().to_vec(), operand_count : 3}]);
+
+        table.reset_merge_diagnostics();
+        assert_eq!(table.merge_diagnostics(), MergeDiagnostics::default());
+    }
+
+    #[test]
+    /// Tests that RocksDB's statistics are only available when they are enabled, and that the tickers are parsed
+    fn db_statistics_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const ROCKS_CONFIG : RocksConfig = RocksConfig {
+                statistics : true,
+                ..RocksConfig::DEFAULT
+            };
+        }
+        let mut table = Table::<Config, true>::new("db_statistics_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        table.flush().unwrap();
+        assert_eq!(table.lookup_fuzzy_raw("Fryday").unwrap().into_vec().unwrap().len(), 1);
+
+        let statistics = table.db_statistics().unwrap();
+        assert!(!statistics.tickers.is_empty());
+        assert!(statistics.ticker("rocksdb.number.keys.written") > 0);
+        assert_eq!(statistics.ticker("no.such.ticker"), 0);
+        drop(table);
+
+        //The statistics setting may change whenever the Table is opened
+        struct NoStatsConfig();
+        impl TableConfig for NoStatsConfig {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+        }
+        let table = Table::<NoStatsConfig, true>::new("db_statistics_test.rocks", NoStatsConfig()).unwrap();
+        assert!(table.db_statistics().is_err());
+    }
+
+    #[test]
+    /// Tests that the index and filter blocks of the variants CF are only kept in the block cache when the
+    /// RocksConfig sets the cache's size, by reading the options RocksDB saved in the database directory
+    fn variants_block_options_test() {
+
+        //Returns the value of a table option of the "variants" CF, from the newest OPTIONS file
+        fn variants_table_option(path : &str, option : &str) -> String {
+            let options_file = std::fs::read_dir(path).unwrap()
+                .map(|dir_entry| dir_entry.unwrap().file_name().into_string().unwrap())
+                .filter_map(|file_name| file_name.strip_prefix("OPTIONS-").and_then(|number| number.parse::<u64>().ok()))
+                .max().unwrap();
+            let options = std::fs::read_to_string(format!("{}/OPTIONS-{:06}", path, options_file)).unwrap();
+            let section = options.split("[TableOptions/BlockBasedTable \"variants\"]").nth(1).unwrap();
+            section.lines()
+                .filter_map(|line| line.trim().strip_prefix(&format!("{}=", option)))
+                .next().unwrap().to_string()
+        }
+
+        let mut table = Table::<DefaultTableConfig, true>::new("variants_block_options_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        drop(table);
+        assert_eq!(variants_table_option("variants_block_options_test.rocks", "cache_index_and_filter_blocks"), "false");
+        assert_eq!(variants_table_option("variants_block_options_test.rocks", "whole_key_filtering"), "true");
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const ROCKS_CONFIG : RocksConfig = RocksConfig {
+                block_cache_size : 16 * 1024 * 1024,
+                ..RocksConfig::DEFAULT
+            };
+        }
+        let table = Table::<Config, true>::new("variants_block_options_test.rocks", Config()).unwrap();
+        drop(table);
+        assert_eq!(variants_table_option("variants_block_options_test.rocks", "cache_index_and_filter_blocks"), "true");
+        assert_eq!(variants_table_option("variants_block_options_test.rocks", "pin_l0_filter_and_index_blocks_in_cache"), "true");
+    }
+
+    #[test]
+    /// Tests that the disk usage covers every column family, and grows as records are flushed to the database files
+    fn disk_usage_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("disk_usage_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        table.compact_all().unwrap();
+        let empty_usage = table.disk_usage().unwrap();
+        assert_eq!(empty_usage.column_families.len(), 17);
+        assert!(empty_usage.total_size > 0);
+
+        for i in 0..100 {
+            table.insert(format!("key number {}", i), &format!("value number {}", i)).unwrap();
+        }
+        table.flush().unwrap();
+        let usage = table.disk_usage().unwrap();
+        assert!(usage.cf("variants").unwrap().sst_files_size > 0);
+        assert!(usage.sst_files_size() > empty_usage.sst_files_size());
+        assert!(usage.total_size >= usage.sst_files_size());
+        assert!(usage.cf("no_such_cf").is_none());
+    }
+
+    #[test]
+    /// Tests that the estimated key counts follow the records in the Table
+    fn db_properties_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("db_properties_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        for i in 0..100 {
+            table.insert(format!("key number {}", i), &format!("value number {}", i)).unwrap();
+        }
+        table.flush().unwrap();
+
+        let properties = table.db_properties().unwrap();
+        assert_eq!(properties.column_families.len(), 17);
+        assert!(properties.estimate_record_count() >= 100);
+        assert!(properties.cf("values").unwrap().estimate_num_keys >= 100);
+        assert_eq!(properties.background_errors, 0);
+    }
+
+    #[test]
+    /// Tests that Tables with different value types can share one database, and persist independently
+    fn shared_database_test() {
+
+        struct CountConfig();
+        impl TableConfig for CountConfig {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = u32;
+        }
+
+        let _ = std::fs::remove_dir_all("shared_database_test.rocks");
+        let shared = SharedDatabase::open("shared_database_test.rocks", RocksConfig::DEFAULT).unwrap();
+        let mut names = Table::<DefaultTableConfig, true>::open_shared(&shared, "names", DefaultTableConfig()).unwrap();
+        let mut counts = Table::<CountConfig, true>::open_shared(&shared, "counts", CountConfig()).unwrap();
+        let friday = names.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        let fryday = counts.insert("Fryday", &5).unwrap();
+
+        //Each Table only sees its own records, and can't be opened twice
+        assert_eq!(names.lookup_exact("Fryday").unwrap().count(), 0);
+        assert_eq!(counts.lookup_exact("Friday").unwrap().count(), 0);
+        assert!(Table::<DefaultTableConfig, true>::open_shared(&shared, "names", DefaultTableConfig()).is_err());
+        assert!(Table::<DefaultTableConfig, true>::open_shared(&shared, "bad:name", DefaultTableConfig()).is_err());
+        assert!(shared.drop_table("names").is_err());
+        drop(names);
+        drop(counts);
+        drop(shared);
+
+        //The Tables are still there when the database is reopened, and a single-Table database can't be shared
+        assert!(Table::<DefaultTableConfig, true>::new("shared_database_test.rocks", DefaultTableConfig()).is_err());
+        let shared = SharedDatabase::open("shared_database_test.rocks", RocksConfig::DEFAULT).unwrap();
+        assert_eq!(shared.table_names().unwrap(), vec!["counts".to_string(), "names".to_string()]);
+        let names = Table::<DefaultTableConfig, true>::open_shared(&shared, "names", DefaultTableConfig()).unwrap();
+        let counts = Table::<CountConfig, true>::open_shared(&shared, "counts", CountConfig()).unwrap();
+        assert_eq!(names.get_value(friday).unwrap(), "Kinyoubi");
+        assert_eq!(counts.get_value(fryday).unwrap(), 5);
+        drop(counts);
+
+        assert!(shared.drop_table("counts").unwrap());
+        assert!(!shared.drop_table("counts").unwrap());
+        assert_eq!(shared.table_names().unwrap(), vec!["names".to_string()]);
+        drop(names);
+        drop(shared);
+
+        let table = Table::<DefaultTableConfig, true>::new("shared_database_test_single.rocks", DefaultTableConfig()).unwrap();
+        drop(table);
+        assert!(SharedDatabase::open("shared_database_test_single.rocks", RocksConfig::DEFAULT).is_err());
+    }
+
+    #[test]
+    /// Tests that copying a Table out of a SharedDatabase produces a database holding just that Table
+    fn copy_to_test() {
+
+        let _ = std::fs::remove_dir_all("copy_to_test.rocks");
+        let _ = std::fs::remove_dir_all("copy_to_test_copy.rocks");
+        let shared = SharedDatabase::open("copy_to_test.rocks", RocksConfig::DEFAULT).unwrap();
+        let mut table = Table::<DefaultTableConfig, true>::open_shared(&shared, "days", DefaultTableConfig()).unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        table.copy_to("copy_to_test_copy.rocks").unwrap();
+        assert!(table.copy_to("copy_to_test_copy.rocks").is_err());
+
+        //The copy is independent of the original
+        let mut copy = Table::<DefaultTableConfig, true>::new("copy_to_test_copy.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(copy.get_value(friday).unwrap(), "Kinyoubi");
+        assert_eq!(copy.lookup_best("Fryday").unwrap().collect::<Vec<RecordID>>(), vec![friday]);
+        copy.insert("Saturday", &"Doyoubi".to_string()).unwrap();
+        assert_eq!(table.lookup_exact("Saturday").unwrap().count(), 0);
+        assert!(copy.check_integrity().unwrap().is_consistent());
+        copy.destroy().unwrap();
+        assert!(!std::path::Path::new("copy_to_test_copy.rocks.sst").exists());
+    }
+
+    #[test]
+    /// Tests that a parallel lookup finds the same records, with the same distances, as the lazy lookup
+    fn lookup_fuzzy_parallel_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("lookup_fuzzy_parallel_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        for first in 'a'..='z' {
+            for second in 'a'..='e' {
+                table.insert(format!("{first}{second}at"), &String::new()).unwrap();
+            }
+        }
+
+        //"at" is a variant of every key, so every record is a candidate
+        let mut expected : Vec<(RecordID, u8)> = table.lookup_fuzzy("bat", None).unwrap().into_vec().unwrap();
+        expected.sort_by_key(|(record_id, distance)| (*distance, *record_id));
+        assert_eq!(expected.len(), 130);
+        assert_eq!(table.lookup_fuzzy_parallel("bat", None).unwrap(), expected);
+
+        let within_one = table.lookup_fuzzy_parallel("bat", Some(1)).unwrap();
+        assert!(within_one.iter().all(|(_, distance)| *distance <= 1));
+        assert_eq!(within_one.len(), expected.iter().filter(|(_, distance)| *distance <= 1).count());
+        assert_eq!(table.lookup_fuzzy_parallel("xyz", Some(0)).unwrap(), vec![]);
+    }
+
+    #[test]
+    /// Tests that records created across several threads get distinct RecordIDs, and can all be found
+    fn create_parallel_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("create_parallel_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let deleted = table.insert("Placeholder", &String::new()).unwrap();
+        table.delete(deleted).unwrap();
+
+        //Enough records for each worker to commit more than one batch
+        let records : Vec<(Vec<String>, String)> = (0..2500).map(|i| (vec![format!("key{i}"), format!("alias{i}")], i.to_string())).collect();
+        let record_ids = table.create_parallel(&records, 3).unwrap();
+        assert_eq!(record_ids.len(), 2500);
+        assert_eq!(record_ids.iter().collect::<std::collections::HashSet<_>>().len(), 2500);
+        assert!(record_ids.contains(&deleted));
+
+        for i in [0, 999, 1000, 2499] {
+            assert_eq!(table.get_value(record_ids[i]).unwrap(), i.to_string());
+            assert_eq!(table.lookup_exact(format!("alias{i}").as_str()).unwrap().collect::<Vec<RecordID>>(), vec![record_ids[i]]);
+        }
+        assert!(table.lookup_fuzzy_raw("key1234").unwrap().into_vec().unwrap().contains(&record_ids[1234]));
+
+        //The next record gets a RecordID of its own
+        let next = table.insert("Next", &String::new()).unwrap();
+        assert!(!record_ids.contains(&next));
+
+        let no_keys : Vec<(Vec<String>, String)> = vec![(vec![], String::new())];
+        assert!(table.create_parallel(&no_keys, 2).is_err());
+        assert!(table.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that a create_parallel that fails partway through leaves the RecordIDs it didn't use as deleted
+    /// records, rather than gaps, so the Table still finds all of its records when it's reopened
+    fn create_parallel_failure_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("create_parallel_failure_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        table.add_value_validator(|value : &String| if value == "bad" { Err("bad value".to_string()) } else { Ok(()) });
+
+        //The second of three shards fails in its first batch, while the others are committed
+        let records : Vec<(Vec<String>, String)> = (0..2500).map(|i| (vec![format!("key{i}")], if i == 1200 { "bad".to_string() } else { i.to_string() })).collect();
+        assert_eq!(table.create_parallel(&records, 3).unwrap_err(), "value rejected by validator: bad value");
+        assert_eq!(table.get_value(RecordID::from(2499)).unwrap(), "2499");
+        assert!(table.get_value(RecordID::from(1200)).is_err());
+        drop(table);
+
+        //The reopened Table finds the records past the ones that weren't created
+        let mut table = Table::<DefaultTableConfig, true>::new("create_parallel_failure_test.rocks", DefaultTableConfig()).unwrap();
+        let exported = table.export_stream().into_vec().unwrap();
+        assert_eq!(exported.len(), 2500 - 834);
+        assert_eq!(exported.last().unwrap().record_id, RecordID::from(2499));
+        let next = table.insert("Next", &String::new()).unwrap();
+        assert_eq!(next, RecordID::from(2500));
+        assert!(table.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that writes queued in a QueuedTable are applied in order, and that the queue is drained before the
+    /// Table is returned
+    fn queued_table_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("queued_table_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let queued = QueuedTable::new(table);
+
+        let handles : Vec<WriteHandle<RecordID>> = (0..100).map(|i| queued.insert(format!("key{i}"), i.to_string())).collect();
+        let mut first = queued.replace_value(RecordID::from(0), "replaced".to_string());
+        let record_ids : Vec<RecordID> = handles.into_iter().map(|handle| handle.wait().unwrap()).collect();
+        assert_eq!(record_ids[0], RecordID::from(0));
+        queued.flush().unwrap();
+        assert_eq!(first.try_wait().unwrap().as_ref().unwrap(), "0");
+
+        //A write that fails reports its error through its handle, without stopping the writer
+        assert!(queued.delete(RecordID::from(1000)).wait().is_err());
+        queued.delete(record_ids[1]).wait().unwrap();
+        let last = queued.create(vec!["last".to_string(), "final".to_string()], "last".to_string());
+
+        let table = queued.into_table().unwrap();
+        let last = last.wait().unwrap();
+        assert_eq!(table.get_value(record_ids[0]).unwrap(), "replaced");
+        assert_eq!(table.lookup_exact("key1").unwrap().count(), 0);
+        assert_eq!(table.lookup_exact("final").unwrap().collect::<Vec<RecordID>>(), vec![last]);
+    }
+
+    #[test]
+    /// Tests the percentiles estimated by a LatencyHistogram, including at the edges of its range
+    fn latency_histogram_test() {
+
+        let empty = LatencyHistogram::new();
+        assert_eq!(empty.percentile(50.0), std::time::Duration::ZERO);
+        assert_eq!(empty.mean(), std::time::Duration::ZERO);
+
+        //Short durations are counted exactly
+        let mut histogram = LatencyHistogram::new();
+        for nanos in [1, 2, 3, 4] {
+            histogram.record(std::time::Duration::from_nanos(nanos));
+        }
+        assert_eq!(histogram.percentile(0.0), std::time::Duration::from_nanos(1));
+        assert_eq!(histogram.percentile(50.0), std::time::Duration::from_nanos(2));
+        assert_eq!(histogram.percentile(100.0), std::time::Duration::from_nanos(4));
+
+        //Longer durations are estimated within the bucket width, but never beyond the longest duration
+        let mut histogram = LatencyHistogram::new();
+        for millis in 1..=1000 {
+            histogram.record(std::time::Duration::from_millis(millis));
+        }
+        for percentile in [10.0, 50.0, 99.0, 99.9] {
+            let exact = std::time::Duration::from_millis(percentile as u64 * 10);
+            let estimate = histogram.percentile(percentile);
+            assert!(estimate >= exact && estimate <= exact * 9 / 8, "p{percentile} estimated as {estimate:?}");
+        }
+        assert_eq!(histogram.percentile(100.0), std::time::Duration::from_millis(1000));
+        assert!(histogram.mean() > std::time::Duration::from_millis(495) && histogram.mean() < std::time::Duration::from_millis(505));
+
+        let mut merged = LatencyHistogram::new();
+        merged.merge(&histogram);
+        merged.record(std::time::Duration::MAX);
+        assert_eq!(merged.count(), 1001);
+        assert_eq!(merged.percentile(50.0), histogram.percentile(50.0));
+        assert_eq!(merged.max(), std::time::Duration::from_nanos(u64::MAX));
+    }
+
+    #[test]
+    /// Tests that a perf counter scope only counts the work done on its own thread, while the Table's counters
+    /// count everything
+    fn perf_counters_scope_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("perf_counters_scope_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        for key in ["Bat", "Cat", "Hat", "Mat", "Rat"] {
+            table.insert(key, &String::new()).unwrap();
+        }
+        table.reset_perf_counters();
+
+        let (scope_results, other_results) = std::thread::scope(|scope| {
+            let other = scope.spawn(|| table.with_perf_counters(|table| table.lookup_fuzzy("Pat", None).unwrap().into_vec().unwrap().len()));
+            let own = table.with_perf_counters(|table| table.lookup_fuzzy("Bat", Some(0)).unwrap().into_vec().unwrap().len());
+            (own, other.join().unwrap())
+        });
+        assert_eq!(scope_results.0, 1);
+        assert_eq!(other_results.0, 5);
+
+        //Work done outside the scope isn't counted by it
+        let (_, empty) = table.with_perf_counters(|_table| ());
+        assert_eq!(empty.records_found_count, 0);
+
+        #[cfg(feature = "perf_counters")]
+        {
+            assert_eq!(scope_results.1.records_found_count, 1);
+            assert_eq!(other_results.1.records_found_count, 5);
+            assert_eq!(table.get_perf_counters().records_found_count, 6);
+            assert_eq!(table.get_perf_counters().distance_function_invocation_count,
+                scope_results.1.distance_function_invocation_count + other_results.1.distance_function_invocation_count);
+        }
+    }
+
+    #[test]
+    /// Tests that merging Tables remaps the RecordIDs, and applies the conflict policy to colliding keys,
+    /// including collisions between the incoming records themselves
+    fn merge_from_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("merge_from_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        let mut other = Table::<DefaultTableConfig, true>::new("merge_from_test_other.rocks", DefaultTableConfig()).unwrap();
+        other.reset().unwrap();
+        let other_friday = other.create(&["Friday", "Fri"], &"Vendredi".to_string()).unwrap();
+        let other_saturday = other.insert("Saturday", &"Samedi".to_string()).unwrap();
+        let other_sat = other.insert("Sat", &"Sam".to_string()).unwrap();
+        other.add_keys(other_sat, &["Saturday"]).unwrap();
+
+        let summary = table.merge_from(&other, MergeConflictPolicy::KeepExisting).unwrap();
+        assert_eq!((summary.added, summary.skipped, summary.replaced), (1, 2, 0));
+        assert_eq!(summary.record_ids[&other_friday], friday);
+        assert_eq!(summary.record_ids[&other_sat], summary.record_ids[&other_saturday]);
+        assert_eq!(table.get_value(friday).unwrap(), "Kinyoubi");
+        assert_eq!(table.lookup_exact("Fri").unwrap().count(), 0);
+
+        let summary = table.merge_from(&other, MergeConflictPolicy::KeepBoth).unwrap();
+        assert_eq!(summary.added, 3);
+        assert_eq!(table.lookup_exact("Friday").unwrap().count(), 2);
+        assert!(table.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that merging Tables skips the records that were soft deleted in the other Table
+    fn merge_from_soft_deleted_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("merge_from_soft_deleted_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let mut other = Table::<DefaultTableConfig, true>::new("merge_from_soft_deleted_test_other.rocks", DefaultTableConfig()).unwrap();
+        other.reset().unwrap();
+        let other_friday = other.insert("Friday", &"Vendredi".to_string()).unwrap();
+        let other_saturday = other.insert("Saturday", &"Samedi".to_string()).unwrap();
+        other.soft_delete(other_saturday).unwrap();
+
+        let summary = table.merge_from(&other, MergeConflictPolicy::KeepBoth).unwrap();
+        assert_eq!((summary.added, summary.skipped, summary.replaced), (1, 0, 0));
+        assert!(summary.record_ids.contains_key(&other_friday));
+        assert!(!summary.record_ids.contains_key(&other_saturday));
+        assert_eq!(table.lookup_exact("Saturday").unwrap().count(), 0);
+        assert_eq!(table.lookup_fuzzy_raw("Saturday").unwrap().into_vec().unwrap().len(), 0);
+        assert!(table.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that the exact key index only matches whole keys, and follows changes to a record's keys
+    fn exact_key_index_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const EXACT_KEY_INDEX : bool = true;
+        }
+        let mut table = Table::<Config, true>::new("exact_key_index_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let london = table.insert("london", &"England".to_string()).unwrap();
+        let londonia = table.insert("londonia", &"Nowhere".to_string()).unwrap();
+
+        //Unlike the variant lookup, "londonia" isn't returned for "london"
+        assert_eq!(table.lookup_exact("london").unwrap().collect::<Vec<RecordID>>(), vec![london]);
+
+        table.add_keys(londonia, &["london"]).unwrap();
+        let mut results : Vec<RecordID> = table.lookup_exact("london").unwrap().collect();
+        results.sort();
+        assert_eq!(results, vec![london, londonia]);
+
+        table.remove_keys(londonia, &["london"]).unwrap();
+        assert_eq!(table.lookup_exact("london").unwrap().collect::<Vec<RecordID>>(), vec![london]);
+
+        table.replace_keys(london, &["Londinium"]).unwrap();
+        assert_eq!(table.lookup_exact("london").unwrap().count(), 0);
+        assert_eq!(table.lookup_exact("Londinium").unwrap().collect::<Vec<RecordID>>(), vec![london]);
+
+        table.delete(london).unwrap();
+        assert_eq!(table.lookup_exact("Londinium").unwrap().count(), 0);
+        assert_eq!(table.lookup_exact("londonia").unwrap().collect::<Vec<RecordID>>(), vec![londonia]);
+        assert!(!table.contains_key_exact("Londinium").unwrap());
+        assert!(!table.contains_key_exact("london").unwrap());
+        assert!(table.contains_key_exact("londonia").unwrap());
+    }
+
+    #[test]
+    /// Tests that small values stored inline can be read, replaced, and deleted like values in the "values" CF
+    fn inline_values_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const INLINE_VALUE_MAX_SIZE : usize = 8;
+        }
+        let mut table = Table::<Config, true>::new("inline_values_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let short = table.insert("Monday", &"Mon".to_string()).unwrap();
+        let long = table.create(&["Tuesday", "Tue"], &"Kayoubi, the day of fire".to_string()).unwrap();
+        assert_eq!(table.get_values(&[short, long]).unwrap(), vec!["Mon".to_string(), "Kayoubi, the day of fire".to_string()]);
+
+        //Swap the values, so each moves between the "rec_data" entry and the "values" CF
+        table.replace_value(short, &"Getsuyoubi, the day of the moon".to_string()).unwrap();
+        table.replace_value(long, &"Tue".to_string()).unwrap();
+        assert_eq!(table.get_value(short).unwrap(), "Getsuyoubi, the day of the moon");
+        assert_eq!(table.get_value(long).unwrap(), "Tue");
+
+        //Changing a record's keys keeps its inline value
+        table.add_keys(long, &["Kayoubi"]).unwrap();
+        assert_eq!(table.get_value(long).unwrap(), "Tue");
+        assert!(table.verify().unwrap().is_consistent());
+
+        //A deleted record's inline value goes with it
+        table.delete(long).unwrap();
+        assert!(table.get_value(long).is_err());
+        let exported = table.export_stream().into_vec().unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].value, "Getsuyoubi, the day of the moon");
+
+        //The size may be raised when the database is reopened, but inline values can't be turned off again
+        let wed = table.insert("Wednesday", &"Wed".to_string()).unwrap();
+        drop(table);
+        struct LargerConfig();
+        impl TableConfig for LargerConfig {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const INLINE_VALUE_MAX_SIZE : usize = 32;
+        }
+        let table = Table::<LargerConfig, true>::new("inline_values_test.rocks", LargerConfig()).unwrap();
+        assert_eq!(table.params().inline_value_max_size, 32);
+        assert_eq!(table.get_value(wed).unwrap(), "Wed");
+        drop(table);
+        assert!(Table::<DefaultTableConfig, true>::new("inline_values_test.rocks", DefaultTableConfig()).is_err());
+    }
+
+    #[test]
+    /// Tests that deterministic RecordIDs don't depend on the insertion order, and that exports can find them
+    fn deterministic_record_ids_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const DETERMINISTIC_RECORD_IDS : bool = true;
+        }
+        let days = [("Monday", "Getsuyoubi"), ("Tuesday", "Kayoubi"), ("Wednesday", "Suiyoubi")];
+
+        let mut forward = Table::<Config, true>::new("deterministic_record_ids_test_a.rocks", Config()).unwrap();
+        forward.reset().unwrap();
+        let forward_ids : Vec<RecordID> = days.iter().map(|(key, value)| forward.insert(*key, &value.to_string()).unwrap()).collect();
+
+        let mut reverse = Table::<Config, true>::new("deterministic_record_ids_test_b.rocks", Config()).unwrap();
+        reverse.reset().unwrap();
+        let mut reverse_ids : Vec<RecordID> = days.iter().rev().map(|(key, value)| reverse.insert(*key, &value.to_string()).unwrap()).collect();
+        reverse_ids.reverse();
+        assert_eq!(forward_ids, reverse_ids);
+        assert_ne!(forward_ids[0], RecordID::from(0));
+
+        //Re-inserting a deleted record gives it back the same RecordID
+        forward.delete(forward_ids[1]).unwrap();
+        assert_eq!(forward.insert("Tuesday", &"Kayoubi".to_string()).unwrap(), forward_ids[1]);
+
+        let mut exported_ids : Vec<RecordID> = forward.export_stream().into_vec().unwrap().iter().map(|record| record.record_id).collect();
+        let mut expected_ids = forward_ids.clone();
+        exported_ids.sort();
+        expected_ids.sort();
+        assert_eq!(exported_ids, expected_ids);
+    }
+
+    #[test]
+    /// Tests that injected variants find their record in fuzzy lookups, without becoming keys, and are deleted with it
+    fn injected_variants_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("injected_variants_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let street = table.create_with_variants(&["Street"], &["St", "Str"], &"road".to_string()).unwrap();
+        let stop = table.insert("Stop", &"halt".to_string()).unwrap();
+
+        //"St" is 4 edits from "Street", but it's one of the record's injected variants
+        assert_eq!(table.lookup_fuzzy("St", Some(0)).unwrap().into_vec().unwrap(), vec![(street, 0)]);
+        assert_eq!(table.lookup_fuzzy_scored("Stt", Some(1)).unwrap().into_vec().unwrap().into_iter().find(|(record_id, _, _)| *record_id == street), Some((street, "St".to_string(), 1)));
+        assert_eq!(table.lookup_exact("Str").unwrap().count(), 0);
+        assert_eq!(table.get_keys(street).unwrap().collect::<Vec<String>>(), vec!["Street".to_string()]);
+        assert!(table.verify().unwrap().is_consistent());
+
+        table.delete(street).unwrap();
+        assert_eq!(table.lookup_fuzzy("St", None).unwrap().into_vec().unwrap(), vec![(stop, 2)]);
+        assert!(table.verify().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that a key that would need a key group beyond the last index a KeyGroupID can hold is refused with
+    /// an error, rather than a panic
+    fn key_group_limit_test() {
+        use crate::key_groups::{KeyGroups, MAX_GROUP_IDX};
+
+        let table = Table::<DefaultTableConfig, true>::new("key_group_limit_test.rocks", DefaultTableConfig()).unwrap();
+        let mut groups = KeyGroups::<String, true>::make_groups_from_keys(["Friday"].iter(), 1, table.params(), &DeletionVariants).unwrap();
+
+        //Simulate a record that already has every group index up to the limit
+        groups.group_ids = (0..=MAX_GROUP_IDX).collect();
+        let err = groups.add_key_to_groups(&"Zanzibar", false, table.params(), &DeletionVariants).err().unwrap();
+        assert_eq!(err, "record has too many key groups");
+    }
+
+    #[test]
+    /// Tests that a Table that indexes n-grams finds long keys within the distance, and keeps the index up to date
+    fn ngram_index_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const UTF8_KEYS : bool = true;
+            const INDEX_STRATEGY : IndexStrategy = IndexStrategy::Ngrams(3);
+        }
+
+        let mut table = Table::<Config, true>::new("ngram_index_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let bridge = table.insert("The Bridge on the River Kwai", &"1957".to_string()).unwrap();
+        let lawrence = table.create(&["Lawrence of Arabia", "The Seven Pillars of Wisdom"], &"1962".to_string()).unwrap();
+        table.insert("A Bridge Too Far", &"1977".to_string()).unwrap();
+
+        //Both edits are beyond the MEANINGFUL_KEY_LEN, so they couldn't be found through deletion variants
+        assert_eq!(table.lookup_fuzzy("The Bridge on the Rivr Kway", None).unwrap().into_vec().unwrap(), vec![(bridge, 2)]);
+        assert_eq!(table.lookup_fuzzy("The Seven Pilars of Wisdon", None).unwrap().into_vec().unwrap(), vec![(lawrence, 2)]);
+        assert_eq!(table.lookup_best_n("Lawrence of Arabya", 1).unwrap(), vec![(lawrence, 1)]);
+        assert_eq!(table.lookup_prefix_fuzzy("The Brige", Some(2)).unwrap().into_vec().unwrap(), vec![(bridge, 1)]);
+
+        //The n-grams of a removed key are no longer indexed
+        table.remove_keys(lawrence, &["The Seven Pillars of Wisdom"]).unwrap();
+        assert_eq!(table.lookup_fuzzy("The Seven Pilars of Wisdon", None).unwrap().into_vec().unwrap().len(), 0);
+        assert_eq!(table.lookup_fuzzy_cached("Lawrence of Arabia", None).unwrap(), vec![(lawrence, 0)]);
+        table.delete(lawrence).unwrap();
+        assert_eq!(table.lookup_fuzzy_cached("Lawrence of Arabia", None).unwrap(), vec![]);
+        assert_eq!(table.lookup_fuzzy_raw("Lawrence of Arabia").unwrap().into_vec().unwrap().len(), 0);
+    }
+
+    #[test]
+    /// Tests a custom VariantScheme, and that a table can't be reopened with a different scheme
+    fn variant_scheme_test() {
+
+        //Indexes each key by its sorted chars, so a lookup finds the key's anagrams
+        struct AnagramVariants;
+        impl VariantScheme<char> for AnagramVariants {
+            fn id(&self) -> &'static str {
+                "anagram"
+            }
+            fn variants(&self, key_units : &[&[char]], _max_distance : usize) -> Vec<Vec<char>> {
+                let mut sorted_key = key_units.concat();
+                sorted_key.sort_unstable();
+                vec![sorted_key]
+            }
+        }
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const UTF8_KEYS : bool = true;
+            const VARIANT_SCHEME : &'static dyn VariantScheme<char> = &AnagramVariants;
+        }
+
+        let mut table = Table::<Config, true>::new("variant_scheme_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        assert_eq!(table.params().variant_scheme, "anagram");
+        let listen = table.insert("listen", &"hear".to_string()).unwrap();
+        table.insert("listed", &"itemized".to_string()).unwrap();
+
+        //"silent" is 4 edits from "listen", but it's an anagram so it is found
+        assert_eq!(table.lookup_fuzzy("silent", None).unwrap().into_vec().unwrap(), vec![(listen, 4)]);
+        assert!(table.verify().unwrap().is_consistent());
+        table.close().unwrap();
+
+        assert!(Table::<DefaultTableConfig, true>::new("variant_scheme_test.rocks", DefaultTableConfig()).is_err());
+    }
+
+    #[test]
+    /// Tests that a lookup cursor is refused by a lookup other than the one that produced it
+    fn lookup_cursor_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("lookup_cursor_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        for key in ["Bat", "Cat", "Hat", "Mat", "Rat"] {
+            table.insert(key, &String::new()).unwrap();
+        }
+        let mut other_table = Table::<DefaultTableConfig, true>::new("lookup_cursor_other_test.rocks", DefaultTableConfig()).unwrap();
+        other_table.reset().unwrap();
+
+        let (_, cursor) = table.lookup_fuzzy_page("Pat", Some(1), 1, None).unwrap();
+        let cursor = LookupCursor::from_bytes(&cursor.unwrap().to_bytes()).unwrap();
+        assert!(table.lookup_fuzzy_page("Pot", Some(1), 1, Some(cursor.clone())).is_err());
+        assert!(table.lookup_fuzzy_page("Pat", Some(2), 1, Some(cursor.clone())).is_err());
+        assert!(table.lookup_fuzzy_page("Pat", None, 1, Some(cursor.clone())).is_err());
+        assert!(other_table.lookup_fuzzy_page("Pat", Some(1), 1, Some(cursor.clone())).is_err());
+        assert_eq!(table.lookup_fuzzy_page("Pat", Some(1), 1, Some(cursor.clone())).unwrap().0.len(), 1);
+
+        //The cursor survives reopening the table, but not resetting it
+        drop(table);
+        let mut table = Table::<DefaultTableConfig, true>::new("lookup_cursor_test.rocks", DefaultTableConfig()).unwrap();
+        assert!(table.lookup_fuzzy_page("Pat", Some(1), 1, Some(cursor.clone())).is_ok());
+        table.reset().unwrap();
+        assert!(table.lookup_fuzzy_page("Pat", Some(1), 1, Some(cursor)).is_err());
+    }
+
+    #[test]
+    /// Tests that a relative threshold scales with the key lengths, and replaces the default threshold
+    fn relative_threshold_test() {
+
+        let config = RuntimeConfig::builder().default_threshold(Some(0)).build();
+        let mut table = Table::<RuntimeConfig, true>::new("relative_threshold_test.rocks", config).unwrap();
+        table.reset().unwrap();
+        table.insert("cat", &"short".to_string()).unwrap();
+        let wednesday = table.insert("Wednesday", &"long".to_string()).unwrap();
+
+        assert_eq!(table.lookup_fuzzy("Wensday", None).unwrap().into_vec().unwrap().len(), 0);
+        assert_eq!(table.lookup_fuzzy_relative("Wensday", 0.75).unwrap().into_vec().unwrap(), vec![(wednesday, 2)]);
+        assert_eq!(table.lookup_fuzzy_relative("Wensday", 0.9).unwrap().into_vec().unwrap().len(), 0);
+        assert_eq!(table.lookup_fuzzy_relative("bat", 0.75).unwrap().into_vec().unwrap().len(), 0);
+        assert!(table.lookup_fuzzy_relative("bat", 1.5).is_err());
+    }
+
+    #[test]
+    /// Tests that a SessionOverlay only finds the records the Table's index would find, so a distant overlay
+    /// record isn't the best match just because it's the only one
+    fn overlay_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("overlay_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let paris = table.insert("Paris", &"France".to_string()).unwrap();
+
+        let mut overlay = SessionOverlay::<DefaultTableConfig, true>::new();
+        let zanzibar = overlay.insert("Zanzibar", "Tanzania".to_string()).unwrap();
+        assert_eq!(overlay.lookup_best(&table, "London").unwrap(), vec![]);
+        assert_eq!(overlay.lookup_fuzzy(&table, "London", None).unwrap(), vec![]);
+        assert_eq!(overlay.lookup_best(&table, "Zanzibra").unwrap(), vec![OverlayRecordID::Overlay(zanzibar)]);
+
+        //Beyond MAX_DELETES, neither the Table's records nor the overlay's are found
+        let londinium = overlay.insert("Londinium", "Britannia".to_string()).unwrap();
+        assert_eq!(overlay.lookup_best(&table, "Londinum").unwrap(), vec![OverlayRecordID::Overlay(londinium)]);
+        assert_eq!(overlay.lookup_best(&table, "Lond").unwrap(), vec![]);
+        assert_eq!(overlay.lookup_best(&table, "Pariss").unwrap(), vec![OverlayRecordID::Table(paris)]);
+    }
+
+    #[test]
+    /// Tests the banded Levenshtein check against a few edit distances, and the lookups pruned with it
+    fn banded_levenshtein_test() {
+
+        let bound = banded_levenshtein::BandedLevenshtein::new("kitten".chars().collect(), 2);
+        let accepts = |key : &str| bound.accepts(&key.chars().collect::<Vec<char>>());
+        assert!(accepts("kitten"));
+        assert!(accepts("sitten"));
+        assert!(accepts("sittin"));
+        assert!(!accepts("sitting"));
+        assert!(accepts("kit ten"));
+        assert!(accepts("itte"));
+        assert!(!accepts("kit"));
+        assert!(!accepts("mittens and more"));
+        assert!(!accepts(""));
+        assert!(banded_levenshtein::BandedLevenshtein::new(vec!['a'], 1).accepts(&[]));
+
+        let mut table = Table::<DefaultTableConfig, true>::new("banded_levenshtein_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let london = table.insert("London", &"England".to_string()).unwrap();
+        let lyon = table.insert("Lyon", &"France".to_string()).unwrap();
+        table.insert("Lodz", &"Poland".to_string()).unwrap();
+
+        //The pruned lookup returns exactly the results of the unpruned lookup that are within max_edits
+        let mut pruned : Vec<(RecordID, u8)> = table.lookup_fuzzy_pruned("Lndon", None, 2).unwrap().into_vec().unwrap();
+        pruned.sort();
+        let mut unpruned : Vec<(RecordID, u8)> = table.lookup_fuzzy("Lndon", None).unwrap().into_vec().unwrap().into_iter().filter(|(_, distance)| *distance <= 2).collect();
+        unpruned.sort();
+        assert_eq!(pruned, unpruned);
+        assert!(pruned.contains(&(london, 1)));
+        assert_eq!(table.lookup_fuzzy_pruned("Lyons", None, 1).unwrap().into_vec().unwrap(), vec![(lyon, 1)]);
+
+        //A transposition shares a variant one delete away, but it's two edits, so lookup_fuzzy_within leaves it out
+        assert_eq!(table.lookup_fuzzy("Lnodon", None).unwrap().into_vec().unwrap(), vec![(london, 2)]);
+        assert_eq!(table.lookup_fuzzy_within("Lnodon", None, 1).unwrap().into_vec().unwrap().len(), 0);
+        assert_eq!(table.lookup_fuzzy_within("Lnodon", None, 2).unwrap().into_vec().unwrap(), vec![(london, 2)]);
+    }
+
+    #[test]
+    /// Tests that a pruned lookup scans the records of a small Table, and probes the variants once the Table has
+    /// grown, with the same results either way, and that records which have been deleted aren't counted
+    fn pruned_scan_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("pruned_scan_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let london = table.insert("London", &"England".to_string()).unwrap();
+        let lyon = table.insert("Lyon", &"France".to_string()).unwrap();
+        table.reset_perf_counters();
+        let lookup = table.lookup_fuzzy_pruned("Lndon", None, 2).unwrap();
+        assert_eq!(lookup.strategy(), LookupStrategy::Scan);
+        let mut results : Vec<(RecordID, u8)> = lookup.into_vec().unwrap();
+        results.sort();
+        assert_eq!(results, vec![(london, 1), (lyon, 2)]);
+        #[cfg(feature = "perf_counters")]
+        assert_eq!(table.get_perf_counters().pruned_scan_count, 1);
+
+        //lookup_fuzzy_within makes the same choice
+        let lookup = table.lookup_fuzzy_within("Lndon", None, 2).unwrap();
+        assert_eq!(lookup.strategy(), LookupStrategy::Scan);
+        let mut results : Vec<(RecordID, u8)> = lookup.into_vec().unwrap();
+        results.sort();
+        assert_eq!(results, vec![(london, 1), (lyon, 2)]);
+
+        let mut fillers = vec![];
+        for i in 0..20 {
+            fillers.push(table.insert(format!("Filler {i}"), &String::new()).unwrap());
+        }
+        table.reset_perf_counters();
+        let lookup = table.lookup_fuzzy_pruned("Lndon", None, 2).unwrap();
+        assert_eq!(lookup.strategy(), LookupStrategy::Variants);
+        let mut results : Vec<(RecordID, u8)> = lookup.into_vec().unwrap();
+        results.sort();
+        assert_eq!(results, vec![(london, 1), (lyon, 2)]);
+        assert_eq!(table.lookup_fuzzy_pruned("Lyons", None, 1).unwrap().into_vec().unwrap(), vec![(lyon, 1)]);
+        #[cfg(feature = "perf_counters")]
+        assert_eq!(table.get_perf_counters().pruned_scan_count, 0);
+
+        //Neither the deleted records nor the soft deleted ones are left for a scan to visit
+        for (i, filler) in fillers.into_iter().enumerate() {
+            if i % 2 == 0 {
+                table.delete(filler).unwrap();
+            } else {
+                table.soft_delete(filler).unwrap();
+            }
+        }
+        let lookup = table.lookup_fuzzy_pruned("Lndon", None, 2).unwrap();
+        assert_eq!(lookup.strategy(), LookupStrategy::Scan);
+        let mut results : Vec<(RecordID, u8)> = lookup.into_vec().unwrap();
+        results.sort();
+        assert_eq!(results, vec![(london, 1), (lyon, 2)]);
+    }
+
+    #[test]
+    /// Tests that phrase lookups match tokens in any order, and that the token index is kept up to date as keys are changed
+    fn phrase_lookup_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const UTF8_KEYS : bool = true;
+            const TOKEN_INDEX : bool = true;
+            fn case_sensitive(&self) -> bool {
+                false
+            }
+        }
+
+        let mut table = Table::<Config, true>::new("phrase_lookup_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let sfo = table.create(&["San Francisco Intl Airport", "SFO"], &"California".to_string()).unwrap();
+        let jfk = table.insert("John F Kennedy Intl Airport", &"New York".to_string()).unwrap();
+        let oak = table.create(&["Oakland Airport", "San Francisco Bay"], &"California".to_string()).unwrap();
+
+        //Tokens match in any order, and every phrase token must match
+        assert_eq!(table.lookup_phrase_fuzzy("airport intl", Some(0)).unwrap(), vec![(sfo, 0), (jfk, 0)]);
+        assert_eq!(table.lookup_phrase_fuzzy("fransisco airprt", Some(1)).unwrap(), vec![(sfo, 2)]);
+        assert_eq!(table.lookup_phrase_fuzzy("kenedy  JOHN", None).unwrap(), vec![(jfk, 1)]);
+        assert_eq!(table.lookup_phrase_fuzzy("Heathrow Airport", Some(1)).unwrap(), vec![]);
+        assert_eq!(table.lookup_phrase_fuzzy("   ", None).unwrap(), vec![]);
+
+        //The tokens must all match the same key, even though the record is found through several
+        assert_eq!(table.lookup_phrase_fuzzy("oakland bay", Some(0)).unwrap(), vec![]);
+        assert_eq!(table.lookup_phrase_fuzzy("francisco airport", Some(0)).unwrap(), vec![(sfo, 0)]);
+
+        //Removed keys' tokens are no longer indexed, unless another key has them
+        table.remove_keys(oak, &["San Francisco Bay"]).unwrap();
+        assert_eq!(table.lookup_phrase_fuzzy("san francisco", Some(0)).unwrap(), vec![(sfo, 0)]);
+        table.add_keys(oak, &["San Francisco Oakland"]).unwrap();
+        assert_eq!(table.lookup_phrase_fuzzy("oakland francisco", Some(0)).unwrap(), vec![(oak, 0)]);
+        table.delete(sfo).unwrap();
+        assert_eq!(table.lookup_phrase_fuzzy("san francisco", Some(0)).unwrap(), vec![(oak, 0)]);
+
+        //The database can't be opened without the token index it was built with
+        drop(table);
+        assert!(Table::<DefaultTableConfig, true>::new("phrase_lookup_test.rocks", DefaultTableConfig()).is_err());
+
+        //A Table without a token index can't serve phrase lookups, or be opened with one
+        let table = Table::<DefaultTableConfig, true>::new("phrase_lookup_default_test.rocks", DefaultTableConfig()).unwrap();
+        assert!(table.lookup_phrase_fuzzy("san francisco", None).is_err());
+        drop(table);
+        assert!(Table::<Config, true>::new("phrase_lookup_default_test.rocks", Config()).is_err());
+    }
+
+    #[test]
+    /// Tests the Soundex encoding, and that the phonetic index is kept up to date as keys are changed
+    fn phonetic_index_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const UTF8_KEYS : bool = true;
+            const PHONETIC_FUNCTION : Option<PhoneticEncoding> = Some(PhoneticEncoding::SOUNDEX);
+        }
+        let soundex = PhoneticEncoding::SOUNDEX.function;
+        assert_eq!(soundex("Ashcraft"), vec!["A261"]);
+        assert_eq!(soundex("Tymczak"), vec!["T522"]);
+        assert_eq!(soundex("Pfister"), vec!["P236"]);
+        assert_eq!(soundex("Lee"), vec!["L000"]);
+        assert!(soundex("42").is_empty());
+
+        let mut table = Table::<Config, true>::new("phonetic_index_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let smith = table.create(&["Smith", "Smyth", "Jones"], &"blacksmith".to_string()).unwrap();
+        assert_eq!(table.lookup_phonetic("Schmidt").unwrap().collect::<Vec<RecordID>>(), vec![smith]);
+
+        //The record is still found by its other key with the same code
+        table.remove_keys(smith, &["Smith"]).unwrap();
+        assert_eq!(table.lookup_phonetic("Schmidt").unwrap().collect::<Vec<RecordID>>(), vec![smith]);
+        table.remove_keys(smith, &["Smyth"]).unwrap();
+        assert_eq!(table.lookup_phonetic("Schmidt").unwrap().count(), 0);
+        assert_eq!(table.lookup_phonetic("Jonas").unwrap().collect::<Vec<RecordID>>(), vec![smith]);
+
+        table.delete(smith).unwrap();
+        assert_eq!(table.lookup_phonetic("Jonas").unwrap().count(), 0);
+
+        let table = Table::<DefaultTableConfig, true>::new("phonetic_index_default_test.rocks", DefaultTableConfig()).unwrap();
+        assert!(table.lookup_phonetic("Jonas").is_err());
+        drop(table);
+
+        //The database can't be opened with a different encoding than it was built with, or without one
+        struct MetaphoneConfig();
+        impl TableConfig for MetaphoneConfig {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const UTF8_KEYS : bool = true;
+            const PHONETIC_FUNCTION : Option<PhoneticEncoding> = Some(PhoneticEncoding::DOUBLE_METAPHONE);
+        }
+        assert!(Table::<MetaphoneConfig, true>::new("phonetic_index_test.rocks", MetaphoneConfig()).is_err());
+        assert!(Table::<MetaphoneConfig, true>::new("phonetic_index_default_test.rocks", MetaphoneConfig()).is_err());
+        assert!(Table::<DefaultTableConfig, true>::new("phonetic_index_test.rocks", DefaultTableConfig()).is_err());
+    }
+
+    #[test]
+    /// Tests the Double Metaphone encoding, and a phonetic index that uses both of its codes
+    fn double_metaphone_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const UTF8_KEYS : bool = true;
+            const PHONETIC_FUNCTION : Option<PhoneticEncoding> = Some(PhoneticEncoding::DOUBLE_METAPHONE);
+        }
+        let double_metaphone = PhoneticEncoding::DOUBLE_METAPHONE.function;
+        assert_eq!(double_metaphone("Smith"), vec!["SM0", "XMT"]);
+        assert_eq!(double_metaphone("Schmidt"), vec!["XMT", "SMT"]);
+        assert_eq!(double_metaphone("Catherine"), vec!["K0RN", "KTRN"]);
+        assert_eq!(double_metaphone("Robert"), vec!["RPRT"]);
+        assert_eq!(double_metaphone("Jose"), vec!["HS"]);
+        assert_eq!(double_metaphone("Knight"), vec!["NT"]);
+        assert!(double_metaphone("42").is_empty());
+
+        let mut table = Table::<Config, true>::new("double_metaphone_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let smith = table.insert("Smith", &"blacksmith".to_string()).unwrap();
+        let wasserman = table.insert("Wasserman", &"water carrier".to_string()).unwrap();
+
+        //"Schmidt" only shares its primary code with the alternate code of "Smith"
+        assert_eq!(table.lookup_phonetic("Schmidt").unwrap().collect::<Vec<RecordID>>(), vec![smith]);
+        assert_eq!(table.lookup_phonetic("Vasserman").unwrap().collect::<Vec<RecordID>>(), vec![wasserman]);
+        assert_eq!(table.lookup_phonetic("Jones").unwrap().count(), 0);
+    }
+
+    #[test]
+    /// Tests that a fuzzy lookup queues the references it finds missing, and that they can be restored afterwards
+    fn read_repair_test() {
+        use crate::database::{DBConnection, DBConfig, DBWriteBatch};
+        use crate::key_groups::KeyGroupID;
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const READ_REPAIR : bool = true;
+        }
+        let mut table = Table::<Config, true>::new("read_repair_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let monday = table.create(&["Monday", "Mayday"], &"Getsuyoubi".to_string()).unwrap();
+        drop(table);
+
+        //Simulate a partially applied write, that lost the references from "Mayday" and one of its variants
+        let db = DBConnection::new("read_repair_test.rocks", DBConfig::default()).unwrap();
+        let mut batch = DBWriteBatch::new();
+        db.delete_variant_references(&mut batch, KeyGroupID::from_record_and_idx(monday, 0), HashSet::from([b"Mayday".to_vec(), b"Mayda".to_vec()])).unwrap();
+        db.commit_batch(batch).unwrap();
+        drop(db);
+
+        let mut table = Table::<Config, true>::new("read_repair_test.rocks", Config()).unwrap();
+        assert_eq!(table.lookup_exact("Mayday").unwrap().count(), 0);
+        assert_eq!(table.pending_read_repairs(), 0);
+
+        //The record is still found through "Monday", which queues its key group to be checked
+        assert_eq!(table.lookup_fuzzy("Monday", Some(0)).unwrap().into_vec().unwrap(), vec![(monday, 0)]);
+        assert_eq!(table.pending_read_repairs(), 1);
+        assert_eq!(table.apply_read_repairs().unwrap(), 2);
+        assert_eq!(table.pending_read_repairs(), 0);
+        assert_eq!(table.lookup_exact("Mayday").unwrap().collect::<Vec<RecordID>>(), vec![monday]);
+        assert!(table.verify().unwrap().is_consistent());
+
+        //Nothing is restored twice
+        table.lookup_fuzzy("Monday", Some(0)).unwrap().into_vec().unwrap();
+        assert_eq!(table.apply_read_repairs().unwrap(), 0);
+    }
+
+    #[test]
+    /// Tests that the variants garbage collector removes the references left behind by a key group that no longer
+    /// exists, and nothing else
+    fn gc_variants_test() {
+        use crate::database::{DBConnection, DBConfig, DBWriteBatch};
+        use crate::key_groups::KeyGroupID;
+
+        let mut table = Table::<DefaultTableConfig, true>::new("gc_variants_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let saturday = table.insert("Saturday", &"Doyoubi".to_string()).unwrap();
+        let sunday = table.insert("Sunday", &"Nichiyoubi".to_string()).unwrap();
+        let clean_report = table.gc_variants().unwrap();
+        assert!(clean_report.refs_scanned > 0);
+        assert_eq!(clean_report.refs_removed, 0);
+        drop(table);
+
+        //Simulate a partially applied delete, that removed the key group but not the references to it
+        let db = DBConnection::new("gc_variants_test.rocks", DBConfig::default()).unwrap();
+        let mut batch = DBWriteBatch::new();
+        db.delete_key_group_entry(&mut batch, KeyGroupID::from_record_and_idx(saturday, 0)).unwrap();
+        db.put_record_key_groups(&mut batch, saturday, &[]).unwrap();
+        db.commit_batch(batch).unwrap();
+        drop(db);
+
+        let mut table = Table::<DefaultTableConfig, true>::new("gc_variants_test.rocks", DefaultTableConfig()).unwrap();
+        let stale_refs = table.verify().unwrap().stale_refs().count();
+        assert!(stale_refs > 0);
+        let report = table.gc_variants().unwrap();
+        assert_eq!(report.refs_removed, stale_refs);
+        assert_eq!(report.refs_scanned, clean_report.refs_scanned);
+        assert_eq!(report.bytes_reclaimed, stale_refs * 8);
+        assert!(table.verify().unwrap().is_consistent());
+        assert_eq!(table.lookup_best("Sundya").unwrap().collect::<Vec<RecordID>>(), vec![sunday]);
+        assert_eq!(table.gc_variants().unwrap().refs_removed, 0);
+    }
+
+    #[test]
+    /// Tests that the integrity check reports the inconsistencies between the column families, rather than failing
+    fn check_integrity_test() {
+        use crate::database::{DBConnection, DBConfig, DBWriteBatch};
+        use crate::key_groups::KeyGroupID;
+
+        let mut table = Table::<DefaultTableConfig, true>::new("check_integrity_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let monday = table.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+        let tuesday = table.insert("Tuesday", &"Kayoubi".to_string()).unwrap();
+        assert!(table.check_integrity().unwrap().is_consistent());
+        drop(table);
+
+        //Lose Monday's value, and Tuesday's key group, leaving the variant references behind
+        let db = DBConnection::new("check_integrity_test.rocks", DBConfig::default()).unwrap();
+        let mut batch = DBWriteBatch::new();
+        db.delete_value(&mut batch, monday).unwrap();
+        db.delete_key_group_entry(&mut batch, KeyGroupID::from_record_and_idx(tuesday, 0)).unwrap();
+        db.commit_batch(batch).unwrap();
+        drop(db);
+
+        let table = Table::<DefaultTableConfig, true>::new("check_integrity_test.rocks", DefaultTableConfig()).unwrap();
+        let report = table.check_integrity().unwrap();
+        let tuesday_group = table.key_groups(tuesday).unwrap()[0];
+        assert_eq!(report.issues()[0], IntegrityIssue::MissingValue(monday));
+        assert_eq!(report.issues()[1], IntegrityIssue::MissingKeyGroup(tuesday_group));
+        assert!(report.issues()[2..].iter().all(|issue| matches!(issue, IntegrityIssue::StaleVariantRef{key_group, ..} if *key_group == tuesday_group)));
+        assert_eq!(report.issues().len() - 2, table.verify().unwrap().stale_refs().count());
+    }
+
+    #[test]
+    /// Tests that a Table with checksums catches an entry that has been damaged, and names the record
+    fn checksums_test() {
+        use crate::database::{DBConnection, DBConfig, VALUES_CF_NAME};
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const CHECKSUMS : bool = true;
+        }
+        let mut table = Table::<Config, true>::new("checksums_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        let saturday = table.insert("Saturday", &"Doyoubi".to_string()).unwrap();
+        assert_eq!(table.get_value(friday).unwrap(), "Kinyoubi");
+        assert_eq!(table.lookup_best("Fridya").unwrap().collect::<Vec<RecordID>>(), vec![friday]);
+        assert!(table.check_integrity().unwrap().is_consistent());
+        drop(table);
+
+        //The database can't be opened without checksums
+        assert!(Table::<DefaultTableConfig, true>::new("checksums_test.rocks", DefaultTableConfig()).is_err());
+
+        //Flip a bit in Friday's value, leaving an entry that still decodes
+        let db = DBConnection::new("checksums_test.rocks", DBConfig{checksums : true, ..DBConfig::default()}).unwrap();
+        let (key, mut entry) = db.raw_entries(VALUES_CF_NAME).unwrap().into_iter().find(|(key, _entry)| key[..] == friday.to_le_bytes()).unwrap();
+        entry[1] ^= 0x01;
+        let mut batch = crate::database::DBWriteBatch::new();
+        db.put_raw(&mut batch, VALUES_CF_NAME, &key, entry).unwrap();
+        db.commit_batch(batch).unwrap();
+        drop(db);
+
+        let table = Table::<Config, true>::new("checksums_test.rocks", Config()).unwrap();
+        let err = table.get_value(friday).unwrap_err();
+        assert!(err.contains("Corruption") && err.contains(&format!("record {}", friday)));
+        assert_eq!(table.get_value(saturday).unwrap(), "Doyoubi");
+        let report = table.check_integrity().unwrap();
+        assert!(matches!(&report.issues()[..], [IntegrityIssue::CorruptEntry{cf_name, ..}] if cf_name == VALUES_CF_NAME));
+    }
+
+    #[test]
+    /// Tests that a value larger than the BLOB_THRESHOLD is split into chunks, and that the chunks are cleaned up
+    fn blob_test() {
+        use crate::database::{DBConnection, DBConfig, BLOBS_CF_NAME};
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const BLOB_THRESHOLD : usize = 16;
+            const CHECKSUMS : bool = true;
+        }
+        let mut table = Table::<Config, true>::new("blob_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let long_value = "Kinyoubi ".repeat(10);
+        let friday = table.insert("Friday", &long_value).unwrap();
+        let saturday = table.insert("Saturday", &"Doyoubi".repeat(10)).unwrap();
+        assert_eq!(table.get_value(friday).unwrap(), long_value);
+        assert_eq!(table.get_value(saturday).unwrap(), "Doyoubi".repeat(10));
+        assert_eq!(table.lookup_best("Fridya").unwrap().collect::<Vec<RecordID>>(), vec![friday]);
+        assert!(table.check_integrity().unwrap().is_consistent());
+
+        //Replacing a value with a small one, or deleting the record, removes the chunks
+        table.replace_value(friday, &"Kinyoubi".to_string()).unwrap();
+        assert_eq!(table.get_value(friday).unwrap(), "Kinyoubi");
+        table.delete(saturday).unwrap();
+        assert!(table.check_integrity().unwrap().is_consistent());
+        drop(table);
+
+        let db = DBConnection::new("blob_test.rocks", DBConfig{checksums : true, ..DBConfig::default()}).unwrap();
+        assert!(db.raw_entries(BLOBS_CF_NAME).unwrap().is_empty());
+    }
+
+    #[test]
+    /// Tests streaming a value in and out of a Table in chunks
+    fn stream_value_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = Vec<u8>;
+            const BLOB_THRESHOLD : usize = 64;
+            const INLINE_VALUE_MAX_SIZE : usize = 16;
+        }
+        let mut table = Table::<Config, true>::new("stream_value_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let record_id = table.insert("Blob", &vec![1, 2, 3]).unwrap();
+
+        //Stream a value in, and read it back both ways
+        let payload : Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        table.put_value_from_reader(record_id, &mut &payload[..], payload.len() as u64).unwrap();
+        assert_eq!(table.get_value(record_id).unwrap(), payload);
+        let mut streamed = vec![];
+        assert_eq!(table.get_value_to_writer(record_id, &mut streamed).unwrap(), 1000);
+        assert_eq!(streamed, payload);
+
+        //A shorter value replaces all of the chunks, and a value that isn't split can be streamed out too
+        table.put_value_from_reader(record_id, &mut &payload[..100], 100).unwrap();
+        assert_eq!(table.get_value(record_id).unwrap(), payload[..100]);
+        table.replace_value(record_id, &vec![4, 5, 6]).unwrap();
+        let mut streamed = vec![];
+        assert_eq!(table.get_value_to_writer(record_id, &mut streamed).unwrap(), 3);
+        assert_eq!(streamed, vec![4, 5, 6]);
+        assert!(table.check_integrity().unwrap().is_consistent());
+
+        //A reader that runs out early is an error, and leaves the old value in place, even if it was split too
+        assert!(table.put_value_from_reader(record_id, &mut &payload[..10], 100).is_err());
+        assert_eq!(table.get_value(record_id).unwrap(), vec![4, 5, 6]);
+        table.put_value_from_reader(record_id, &mut &payload[..500], 500).unwrap();
+        assert!(table.put_value_from_reader(record_id, &mut &payload[..300], 1000).is_err());
+        assert_eq!(table.get_value(record_id).unwrap(), payload[..500]);
+        assert!(table.check_integrity().unwrap().is_consistent());
+
+        //Each new value alternates between the record's two slots, and a pinned record sees the new value
+        table.pin(record_id).unwrap();
+        table.put_value_from_reader(record_id, &mut &payload[..], payload.len() as u64).unwrap();
+        assert_eq!(table.get_value(record_id).unwrap(), payload);
+        table.put_value_from_reader(record_id, &mut &payload[..200], 200).unwrap();
+        assert_eq!(table.get_value(record_id).unwrap(), payload[..200]);
+        assert!(table.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that a backup of a live Table can be opened as a Table of its own
+    fn backup_test() {
+
+        let _ = std::fs::remove_dir_all("backup_test_copy.rocks");
+        let mut table = Table::<DefaultTableConfig, true>::new("backup_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        table.create_backup("backup_test_copy.rocks").unwrap();
+
+        //Changes after the backup don't reach it
+        table.insert("Saturday", &"Doyoubi".to_string()).unwrap();
+        assert!(table.create_backup("backup_test_copy.rocks").is_err());
+
+        let backup = Table::<DefaultTableConfig, true>::new("backup_test_copy.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(backup.get_value(friday).unwrap(), "Kinyoubi");
+        assert_eq!(backup.lookup_best("Fridya").unwrap().collect::<Vec<RecordID>>(), vec![friday]);
+        assert!(backup.lookup_exact("Saturday").unwrap().next().is_none());
+        assert!(backup.check_integrity().unwrap().is_consistent());
+        backup.destroy().unwrap();
+    }
+
+    #[test]
+    /// Tests taking incremental backups of a Table, and restoring an older one
+    fn backup_incremental_test() {
+
+        let _ = std::fs::remove_dir_all("backup_incremental_test.backups");
+        let mut table = Table::<DefaultTableConfig, true>::new("backup_incremental_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        let first = table.backup_incremental("backup_incremental_test.backups").unwrap();
+        let saturday = table.insert("Saturday", &"Doyoubi".to_string()).unwrap();
+        let second = table.backup_incremental("backup_incremental_test.backups").unwrap();
+        assert!(second.backup_id > first.backup_id);
+        let backups = Table::<DefaultTableConfig, true>::list_backups("backup_incremental_test.backups").unwrap();
+        assert_eq!(backups, vec![first, second]);
+
+        //The Table can't be restored while it's open
+        assert!(Table::<DefaultTableConfig, true>::restore_from_backup("backup_incremental_test.backups", "backup_incremental_test.rocks", first.backup_id).is_err());
+        drop(table);
+
+        Table::<DefaultTableConfig, true>::restore_from_backup("backup_incremental_test.backups", "backup_incremental_test.rocks", first.backup_id).unwrap();
+        let table = Table::<DefaultTableConfig, true>::new("backup_incremental_test.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(table.get_value(friday).unwrap(), "Kinyoubi");
+        assert!(table.get_value(saturday).is_err());
+        assert!(table.check_integrity().unwrap().is_consistent());
+        assert!(!std::path::Path::new("backup_incremental_test.rocks.restoring").exists());
+        assert!(!std::path::Path::new("backup_incremental_test.rocks.replaced").exists());
+
+        //A backup that can't be restored leaves the Table as it was
+        drop(table);
+        assert!(Table::<DefaultTableConfig, true>::restore_from_backup("backup_incremental_test.backups", "backup_incremental_test.rocks", second.backup_id + 1).is_err());
+        let table = Table::<DefaultTableConfig, true>::new("backup_incremental_test.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(table.get_value(friday).unwrap(), "Kinyoubi");
+        drop(table);
+
+        Table::<DefaultTableConfig, true>::purge_old_backups("backup_incremental_test.backups", 1).unwrap();
+        assert_eq!(Table::<DefaultTableConfig, true>::list_backups("backup_incremental_test.backups").unwrap(), vec![second]);
+        std::fs::remove_dir_all("backup_incremental_test.backups").unwrap();
+    }
+
+    #[test]
+    /// Tests building a Table from the SST files exported from another Table
+    fn sst_test() {
+
+        let _ = std::fs::remove_dir_all("sst_test.sst");
+        let _ = std::fs::remove_dir_all("sst_test_copy.rocks");
+        let mut table = Table::<DefaultTableConfig, true>::new("sst_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        let saturday = table.insert("Saturday", &"Doyoubi".to_string()).unwrap();
+        let sunday = table.insert("Sunday", &"Nichiyoubi".to_string()).unwrap();
+        table.soft_delete(sunday).unwrap();
+        assert!(table.export_sst("sst_test.sst").unwrap() > 0);
+
+        let mut copy = Table::<DefaultTableConfig, true>::create_from_sst("sst_test_copy.rocks", "sst_test.sst", DefaultTableConfig()).unwrap();
+        assert_eq!(copy.get_value(friday).unwrap(), "Kinyoubi");
+        assert_eq!(copy.lookup_best("Saturdya").unwrap().collect::<Vec<RecordID>>(), vec![saturday]);
+        assert!(copy.is_soft_deleted(sunday));
+        assert_eq!(copy.lookup_exact("Sunday").unwrap().count(), 0);
+        assert!(copy.check_integrity().unwrap().is_consistent());
+
+        //The copy carries on from where the original left off
+        let monday = copy.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+        assert!(monday != friday && monday != saturday && monday != sunday);
+        copy.destroy().unwrap();
+
+        assert!(Table::<DefaultTableConfig, true>::create_from_sst("sst_test.rocks", "sst_test.sst", DefaultTableConfig()).is_err());
+        std::fs::remove_dir_all("sst_test.sst").unwrap();
+
+        //A Table that can't be created from the files is removed again
+        std::fs::create_dir_all("sst_test.sst").unwrap();
+        assert!(Table::<DefaultTableConfig, true>::create_from_sst("sst_test_copy.rocks", "sst_test.sst", DefaultTableConfig()).is_err());
+        assert!(!std::path::Path::new("sst_test_copy.rocks").exists());
+        std::fs::remove_dir_all("sst_test.sst").unwrap();
+    }
+
+    #[test]
+    /// Tests that the records inserted during a bulk load can be found once it is finished
+    fn bulk_load_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("bulk_load_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+
+        table.begin_bulk_load().unwrap();
+        assert!(table.begin_bulk_load().is_err());
+        let record_ids : Vec<RecordID> = (0..100).map(|i| table.insert(format!("Record {}", i), &i.to_string()).unwrap()).collect();
+        let fryday = table.insert("Fryday", &"Kinyoubi".to_string()).unwrap();
+        table.delete(fryday).unwrap();
+        table.delete(friday).unwrap();
+        assert!(table.finish_bulk_load().unwrap() > 0);
+        assert!(table.finish_bulk_load().is_err());
+
+        assert_eq!(table.lookup_exact("Record 42").unwrap().collect::<Vec<RecordID>>(), vec![record_ids[42]]);
+        assert_eq!(table.get_value(record_ids[7]).unwrap(), "7");
+        assert_eq!(table.lookup_fuzzy_raw("Fridya").unwrap().into_vec().unwrap().len(), 0);
+        assert!(table.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that flushing and compacting the Table leaves its records intact, and that a compaction of a column
+    /// family that doesn't exist is refused
+    fn compaction_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("compaction_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let record_ids : Vec<RecordID> = (0..100).map(|i| table.insert(format!("Record {}", i), &i.to_string()).unwrap()).collect();
+        for record_id in record_ids.iter().step_by(2) {
+            table.delete(*record_id).unwrap();
+        }
+        table.flush().unwrap();
+
+        table.compact_all().unwrap();
+        table.compact_range("variants", Some(b"Rec"), None).unwrap();
+        assert!(table.compact_range("no_such_cf", None, None).is_err());
+
+        assert_eq!(table.lookup_exact("Record 43").unwrap().collect::<Vec<RecordID>>(), vec![record_ids[43]]);
+        assert_eq!(table.lookup_exact("Record 42").unwrap().count(), 0);
+        assert_eq!(table.get_value(record_ids[7]).unwrap(), "7");
+        assert!(table.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    /// Tests that shutting a Table down finishes a bulk load that is still in progress
+    fn shutdown_bulk_load_test() {
+
+        let mut table = Table::<DefaultTableConfig, true>::new("shutdown_bulk_load_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        table.begin_bulk_load().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        assert!(table.shutdown().unwrap().bulk_load_variants > 0);
+
+        let table = Table::<DefaultTableConfig, true>::new("shutdown_bulk_load_test.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(table.lookup_best("Fryday").unwrap().collect::<Vec<RecordID>>(), vec![friday]);
+    }
+
+    #[test]
+    /// Tests that the lookups cached during a bulk load are discarded when it's finished
+    fn bulk_load_query_cache_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const QUERY_CACHE_CAPACITY : usize = 16;
+        }
+        let mut table = Table::<Config, true>::new("bulk_load_query_cache_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+
+        table.begin_bulk_load().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        assert_eq!(table.lookup_fuzzy_cached("Fryday", Some(1)).unwrap(), vec![]);
+        table.finish_bulk_load().unwrap();
+        assert_eq!(table.lookup_fuzzy_cached("Fryday", Some(1)).unwrap(), vec![(friday, 1)]);
+    }
+
+    #[test]
+    /// Tests that a Table with a cipher stores its keys and values encrypted, and can still be searched
+    fn cipher_test() {
+        use std::sync::Arc;
+        use crate::database::{DBConnection, DBConfig, KEYS_CF_NAME, VALUES_CF_NAME, VARIANTS_CF_NAME};
+
+        struct XorCipher(u8);
+        impl Cipher for XorCipher {
+            fn encrypt(&self, plaintext : &[u8]) -> Vec<u8> {
+                plaintext.iter().map(|byte| byte ^ self.0).collect()
+            }
+            fn encrypt_deterministic(&self, plaintext : &[u8]) -> Vec<u8> {
+                self.encrypt(plaintext)
+            }
+            fn decrypt(&self, ciphertext : &[u8]) -> Result<Vec<u8>, String> {
+                Ok(self.encrypt(ciphertext))
+            }
+        }
+        let config = |key : u8| RuntimeConfig::builder().cipher(Arc::new(XorCipher(key))).build();
+
+        let mut table = Table::<RuntimeConfig, true>::new("cipher_test.rocks", config(0x5A)).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        let saturday = table.insert("Saturday", &"Doyoubi".to_string()).unwrap();
+        assert_eq!(table.get_value(friday).unwrap(), "Kinyoubi");
+        assert_eq!(table.lookup_best("Satruday").unwrap().collect::<Vec<RecordID>>(), vec![saturday]);
+        assert_eq!(table.lookup_exact("Friday").unwrap().collect::<Vec<RecordID>>(), vec![friday]);
+        assert!(table.verify().unwrap().is_consistent());
+        assert!(table.check_integrity().unwrap().is_consistent());
+        drop(table);
+
+        //Neither the keys, the values, nor the variants are stored in the clear
+        let db = DBConnection::new("cipher_test.rocks", DBConfig::default()).unwrap();
+        let contains = |haystack : &[u8], needle : &[u8]| haystack.windows(needle.len()).any(|window| window == needle);
+        for cf_name in [KEYS_CF_NAME, VALUES_CF_NAME, VARIANTS_CF_NAME] {
+            for (key, value) in db.raw_entries(cf_name).unwrap() {
+                assert!(!contains(&key, b"Friday") && !contains(&value, b"Friday") && !contains(&value, b"Kinyoubi"));
+            }
+        }
+        drop(db);
+
+        //The database can't be opened without the cipher, or with the wrong key
+        assert!(Table::<DefaultTableConfig, true>::new("cipher_test.rocks", DefaultTableConfig()).is_err());
+        assert!(Table::<RuntimeConfig, true>::new("cipher_test.rocks", config(0x33)).is_err());
+        let table = Table::<RuntimeConfig, true>::new("cipher_test.rocks", config(0x5A)).unwrap();
+        assert_eq!(table.get_value(saturday).unwrap(), "Doyoubi");
+    }
+
+    #[test]
+    /// Tests that a Table refuses to open a database written in an earlier format, until it has been migrated
+    fn migration_test() {
+        use crate::database::{DBConnection, DBConfig, METADATA_CF_NAME, FORMAT_VERSION_METADATA_NAME};
+        use crate::migration::{MigrationStep, MigrationContext, migrate_database, FORMAT_VERSION};
+
+        let mut table = Table::<DefaultTableConfig, true>::new("migration_test.rocks", DefaultTableConfig()).unwrap();
+        table.reset().unwrap();
+        let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+        drop(table);
+
+        //Mark the database as written in the format before the current one
+        let db = DBConnection::new("migration_test.rocks", DBConfig::default()).unwrap();
+        db.put_metadata(FORMAT_VERSION_METADATA_NAME, &(FORMAT_VERSION - 1)).unwrap();
+        drop(db);
+        assert!(Table::<DefaultTableConfig, true>::new("migration_test.rocks", DefaultTableConfig()).is_err());
+
+        //A step that rewrites every metadata entry unchanged
+        fn rewrite_metadata(context : &mut MigrationContext) -> Result<(), String> {
+            for (key, value) in context.entries(METADATA_CF_NAME)? {
+                context.delete(METADATA_CF_NAME, &key)?;
+                context.put(METADATA_CF_NAME, &key, value)?;
+            }
+            Ok(())
+        }
+        let steps = [MigrationStep{from_version : FORMAT_VERSION - 1, upgrade : rewrite_metadata}];
+        let db = DBConnection::new("migration_test.rocks", DBConfig::default()).unwrap();
+        assert!(migrate_database(&db, &[], FORMAT_VERSION).is_err());
+        assert_eq!(migrate_database(&db, &steps, FORMAT_VERSION).unwrap(), 1);
+        assert_eq!(migrate_database(&db, &steps, FORMAT_VERSION).unwrap(), 0);
+        drop(db);
+
+        let table = Table::<DefaultTableConfig, true>::new("migration_test.rocks", DefaultTableConfig()).unwrap();
+        assert_eq!(table.get_value(friday).unwrap(), "Kinyoubi");
+        drop(table);
+        assert_eq!(Table::<DefaultTableConfig, true>::migrate("migration_test.rocks").unwrap(), 0);
+
+        //A database written in a later format can't be opened, or migrated
+        let db = DBConnection::new("migration_test.rocks", DBConfig::default()).unwrap();
+        db.put_metadata(FORMAT_VERSION_METADATA_NAME, &(FORMAT_VERSION + 1)).unwrap();
+        drop(db);
+        assert!(Table::<DefaultTableConfig, true>::new("migration_test.rocks", DefaultTableConfig()).is_err());
+        assert!(Table::<DefaultTableConfig, true>::migrate("migration_test.rocks").is_err());
+
+        //Put the current format back, so the next run of the test can open the database
+        let db = DBConnection::new("migration_test.rocks", DBConfig::default()).unwrap();
+        db.put_metadata(FORMAT_VERSION_METADATA_NAME, &FORMAT_VERSION).unwrap();
+    }
+
+    #[test]
+    /// Tests that a Table with a TTL skips the records that have expired, keeps its parameters, and deletes the
+    /// expired records when they are purged
+    fn ttl_test() {
+        use std::time::Duration;
+        use crate::database::{DBConnection, DBConfig, DBWriteBatch};
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const TTL : Option<Duration> = Some(Duration::from_secs(3600));
+        }
+        let mut table = Table::<Config, true>::new("ttl_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        let tuesday = table.insert("Tuesday", &"Kayoubi".to_string()).unwrap();
+        let thursday = table.insert("Thursday", &"Mokuyoubi".to_string()).unwrap();
+        assert_eq!(table.purge_expired().unwrap(), 0);
+        drop(table);
+
+        //Simulate the passing of time by moving the modified time of "Tuesday" back by two TTLs
+        let db = DBConnection::new("ttl_test.rocks", DBConfig::default()).unwrap();
+        let mut metadata = db.get_record_metadata(tuesday).unwrap().unwrap();
+        metadata.modified -= 2 * 3600 * 1000;
+        let mut batch = DBWriteBatch::new();
+        db.put_record_metadata(&mut batch, tuesday, &metadata).unwrap();
+        db.commit_batch(batch).unwrap();
+        drop(db);
+
+        //A Table with a TTL can be opened read-only, and the expired record is skipped there too
+        let read_only = Table::<Config, true>::open_read_only("ttl_test.rocks", Config()).unwrap();
+        assert_eq!(read_only.lookup_fuzzy("Tuesday", Some(0)).unwrap().into_vec().unwrap(), vec![]);
+        drop(read_only);
+
+        //The Table's parameters haven't expired, so the Table opens as the same Table
+        let mut table = Table::<Config, true>::new("ttl_test.rocks", Config()).unwrap();
+        assert_eq!(table.lookup_fuzzy("Tuesday", Some(0)).unwrap().into_vec().unwrap(), vec![]);
+        assert_eq!(table.lookup_exact("Tuesday").unwrap().count(), 0);
+        assert!(!table.contains_key_exact("Tuesday").unwrap());
+        assert_eq!(table.count_exact("Tuesday").unwrap(), 0);
+        assert!(table.get_value(tuesday).is_err());
+
+        //The raw lookups skip the expired record too.  "Tuesdy" shares no variant with "Thursday", but "Tuesday"
+        // shares "Tusday"
+        assert_eq!(table.lookup_fuzzy_raw("Tuesdy").unwrap().into_vec().unwrap(), vec![]);
+        assert!(!table.contains_key_fuzzy("Tuesdy").unwrap());
+        assert_eq!(table.count_fuzzy("Tuesdy").unwrap(), 0);
+        assert_eq!(table.lookup_fuzzy_raw("Tuesday").unwrap().into_vec().unwrap(), vec![thursday]);
+        assert_eq!(table.lookup_fuzzy("Thursday", Some(0)).unwrap().into_vec().unwrap(), vec![(thursday, 0)]);
+
+        //Purging deletes the expired record, along with its variant references
+        assert_eq!(table.purge_expired().unwrap(), 1);
+        assert_eq!(table.purge_expired().unwrap(), 0);
+        assert_eq!(table.lookup_fuzzy("Thursday", Some(2)).unwrap().into_vec().unwrap(), vec![(thursday, 0)]);
+        assert_eq!(table.get_value(thursday).unwrap(), "Mokuyoubi");
+    }
+
+    #[cfg(feature = "fuzzing")]
+    #[test]
+    /// Runs the fuzzing entry point over pseudo-random inputs, so it's exercised without a fuzzer
+    fn fuzz_table_test() {
+        use rand::prelude::*;
+        use rand_pcg::Pcg64;
+
+        let mut table = Table::<DefaultTableConfig, true>::new("fuzz_table_test.rocks", DefaultTableConfig()).unwrap();
+        let mut rng = Pcg64::seed_from_u64(1);
+        for _ in 0..50 {
+            let data : Vec<u8> = (0..256).map(|_| rng.gen()).collect();
+            let ops = FuzzOp::decode_ops(&data, |bytes| bytes.iter().map(|b| (b'a' + b % 4) as char).collect(), |bytes| format!("{:?}", bytes));
+            fuzz_table(&mut table, &ops).unwrap();
+        }
+    }
+
+    #[test]
+    /// Tests that the parameters a table is created with are kept when it's reopened with a different RuntimeConfig
+    fn runtime_config_test() {
+
+        let config = RuntimeConfig::<DefaultTableConfig>::builder().max_deletes(1).case_sensitive(false).build();
+        let mut table = Table::<RuntimeConfig, true>::new("runtime_config_test.rocks", config).unwrap();
+        table.reset().unwrap();
+        let record_id = table.insert("Paris", &"France".to_string()).unwrap();
+        assert_eq!(table.lookup_exact("pARIS").unwrap().collect::<Vec<RecordID>>(), vec![record_id]);
+        assert_eq!(table.lookup_fuzzy("pari", None).unwrap().into_vec().unwrap(), vec![(record_id, 1)]);
+
+        //Case is folded beyond ASCII, but the keys keep the casing they were inserted with
+        let ecole = table.insert("ÉCOLE", &"School".to_string()).unwrap();
+        assert_eq!(table.lookup_exact("école").unwrap().collect::<Vec<RecordID>>(), vec![ecole]);
+        assert_eq!(table.lookup_fuzzy("ecole", Some(1)).unwrap().into_vec().unwrap(), vec![(ecole, 1)]);
+        assert_eq!(table.get_keys(ecole).unwrap().collect::<Vec<String>>(), vec!["ÉCOLE".to_string()]);
+        drop(table);
+
+        //The stored parameters win over the new config
+        let config = RuntimeConfig::<DefaultTableConfig>::builder().max_deletes(3).build();
+        let mut table = Table::<RuntimeConfig, true>::new("runtime_config_test.rocks", config).unwrap();
+        assert_eq!(table.params().max_deletes, 1);
+        assert!(!table.params().case_sensitive);
+        assert_eq!(table.lookup_exact("PARIS").unwrap().collect::<Vec<RecordID>>(), vec![record_id]);
+        assert_eq!(table.lookup_exact("École").unwrap().collect::<Vec<RecordID>>(), vec![ecole]);
+
+        //Until the table is reset
+        table.reset().unwrap();
+        assert_eq!(table.params().max_deletes, 3);
+        assert!(table.params().case_sensitive);
+    }
+
+    #[test]
+    /// Tests that keys that differ only in their diacritics match, including after the table is reopened
+    fn fold_diacritics_test() {
+
+        assert_eq!(unicode_strip_diacritics("Crème Brûlée"), "Creme Brulee");
+        assert_eq!(unicode_strip_diacritics("Cafe\u{0301}"), "Cafe");
+        assert_eq!(unicode_strip_diacritics("Øresund"), "Øresund");
+        assert_eq!(unicode_strip_diacritics("Nguyễn Phương"), "Nguyen Phuong");
+        assert_eq!(unicode_strip_diacritics("Αθήνα"), "Αθηνα");
+        assert_eq!(unicode_strip_diacritics("서울"), "서울");
+
+        let config = RuntimeConfig::<DefaultTableConfig>::builder().case_sensitive(false).fold_diacritics(true).build();
+        let mut table = Table::<RuntimeConfig, true>::new("fold_diacritics_test.rocks", config).unwrap();
+        table.reset().unwrap();
+        let cafe = table.insert("Café", &"Coffee".to_string()).unwrap();
+        let uber = table.insert("über", &"Over".to_string()).unwrap();
+
+        //The accents don't use up any of the distance budget
+        assert_eq!(table.lookup_exact("CAFE").unwrap().collect::<Vec<RecordID>>(), vec![cafe]);
+        assert_eq!(table.lookup_fuzzy("uber", Some(0)).unwrap().into_vec().unwrap(), vec![(uber, 0)]);
+        assert_eq!(table.lookup_fuzzy("Übr", Some(1)).unwrap().into_vec().unwrap(), vec![(uber, 1)]);
+        assert_eq!(table.get_keys(cafe).unwrap().collect::<Vec<String>>(), vec!["Café".to_string()]);
+        drop(table);
+
+        let mut table = Table::<RuntimeConfig, true>::new("fold_diacritics_test.rocks", RuntimeConfig::builder().build()).unwrap();
+        assert!(table.params().fold_diacritics);
+        assert_eq!(table.lookup_exact("cafè").unwrap().collect::<Vec<RecordID>>(), vec![cafe]);
+        table.reset().unwrap();
+        assert!(!table.params().fold_diacritics);
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    /// Tests that a multi-char grapheme cluster is deleted and compared as a single character
+    fn grapheme_clusters_test() {
+
+        struct Config();
+        impl TableConfig for Config {
+            type KeyCharT = char;
+            type DistanceT = u8;
+            type ValueT = String;
+            const DISTANCE_FUNCTION : DistanceFunction<Self::KeyCharT, Self::DistanceT> = Self::grapheme_levenstein_distance;
+            fn grapheme_clusters(&self) -> bool {
+                true
+            }
+        }
+        let mut table = Table::<Config, true>::new("grapheme_clusters_test.rocks", Config()).unwrap();
+        table.reset().unwrap();
+        assert!(table.params().grapheme_clusters);
+
+        //The family emoji is 5 chars, but a single grapheme cluster
+        let family = table.insert("hi👨‍👩‍👧", &"Family".to_string()).unwrap();
+        assert_eq!(table.lookup_fuzzy("hi", Some(1)).unwrap().into_vec().unwrap(), vec![(family, 1)]);
+        assert_eq!(table.lookup_fuzzy("hi👍🏽", Some(1)).unwrap().into_vec().unwrap(), vec![(family, 1)]);
+        assert_eq!(table.lookup_exact("hi👨‍👩‍👧").unwrap().collect::<Vec<RecordID>>(), vec![family]);
+    }
+
+    #[test]
+    /// Tests that the bincode helpers return errors for malformed buffers, rather than panicking
+    fn bincode_helpers_test() {
+        use crate::bincode_helpers::{*};
+
+        //A well-formed Vec<u64> with 2 elements
+        let mut vec_bytes = 2usize.to_le_bytes().to_vec();
+        vec_bytes.extend_from_slice(&[0; 16]);
+        assert_eq!(bincode_vec_iter::<u64>(&vec_bytes).unwrap().len(), 2);
+
+        //Too short to hold the length, a length that disagrees with the contents, and a trailing partial element
+        assert!(bincode_vec_iter::<u64>(&[1, 2, 3]).is_err());
+        assert!(bincode_vec_iter::<u64>(&vec_bytes[..16]).is_err());
+        vec_bytes.push(0);
+        assert!(bincode_vec_iter::<u64>(&vec_bytes).is_err());
+
+        //A length so big it would overflow when multiplied by the element size
+        assert!(bincode_vec_iter::<u64>(&usize::MAX.to_le_bytes()).is_err());
+
+        //Varints
+        assert_eq!(bincode_u64_le_varint(&[7]).unwrap(), (7, 1));
+        assert_eq!(bincode_u64_le_varint(&[251, 0, 1]).unwrap(), (256, 3));
+        assert!(bincode_u64_le_varint(&[]).is_err());
+        assert!(bincode_u64_le_varint(&[252, 0, 1]).is_err());
+        assert!(bincode_u64_le_varint(&[255]).is_err());
+    }
+
     #[test]
     /// This tests the perf-counters
     fn perf_counters_test() {
@@ -780,6 +3396,9 @@ mod tests {
             assert!(table.get_perf_counters().keys_found_count > 0);
             assert!(table.get_perf_counters().distance_function_invocation_count > 0);
             assert!(table.get_perf_counters().records_found_count > 0);
+            assert!(table.get_perf_counters().variant_probe_latency.count() > 0);
+            assert!(table.get_perf_counters().key_group_load_latency.count() > 0);
+            assert_eq!(table.get_perf_counters().distance_latency.count() as usize, table.get_perf_counters().distance_function_invocation_count);
 
             //Debug Prints
             println!("-=-=-=-=-=-=-=-=- lookup_fuzzy london test -=-=-=-=-=-=-=-=-");
@@ -791,6 +3410,9 @@ mod tests {
             println!("keys_found_count {}", table.get_perf_counters().keys_found_count);
             println!("distance_function_invocation_count {}", table.get_perf_counters().distance_function_invocation_count);
             println!("records_found_count {}", table.get_perf_counters().records_found_count);
+            println!("variant_probe_latency p50 {:?} p99 {:?}", table.get_perf_counters().variant_probe_latency.percentile(50.0), table.get_perf_counters().variant_probe_latency.percentile(99.0));
+            println!("key_group_load_latency p50 {:?} p99 {:?}", table.get_perf_counters().key_group_load_latency.percentile(50.0), table.get_perf_counters().key_group_load_latency.percentile(99.0));
+            println!("distance_latency p50 {:?} p99 {:?}", table.get_perf_counters().distance_latency.percentile(50.0), table.get_perf_counters().distance_latency.percentile(99.0));
 
             //Test the perf counters with lookup_exact
             table.reset_perf_counters();
@@ -0,0 +1,86 @@
+//!
+//! The LockFile module contains the lock that keeps two processes from opening the same database for
+//! writing.  Nothing should be re-exported.
+//!
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// The name of the lock file, which lives inside the database directory alongside RocksDB's own files
+const LOCK_FILE_NAME : &str = "FUZZY_ROCKS_LOCK";
+
+/// An exclusive claim on a database directory, held for as long as the database is open for writing
+///
+/// The lock file records the PID of the process that holds it, so a process that is refused the lock can
+/// report which process owns the database.  The file is removed when the LockFile is dropped.  If the owning
+/// process crashes the file is left behind, and must be removed with [force_unlock].
+pub struct LockFile {
+    path : PathBuf,
+}
+
+impl LockFile {
+
+    /// Claims the lock for the database at `db_path`, creating the database directory if necessary
+    pub fn acquire(db_path : &str) -> Result<Self, String> {
+
+        fs::create_dir_all(db_path).map_err(|err| format!("couldn't create database directory {}: {}", db_path, err))?;
+
+        let path = lock_file_path(db_path);
+        match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                let lock = Self{path};
+                file.write_all(std::process::id().to_string().as_bytes())
+                    .map_err(|err| format!("couldn't write lock file for {}: {}", db_path, err))?;
+                Ok(lock)
+            },
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                let owner = match lock_owner(db_path)? {
+                    Some(pid) => format!("process {}", pid),
+                    None => "an unknown process".to_string(),
+                };
+                Err(format!("database {} is locked by {}.  If that process is no longer running, call Table::force_unlock to remove the lock", db_path, owner))
+            },
+            Err(err) => Err(format!("couldn't create lock file for {}: {}", db_path, err)),
+        }
+    }
+
+    /// Updates the lock after the database directory holding it has been renamed to `db_path`, so the lock file
+    /// is removed from its new location
+    pub fn renamed(&mut self, db_path : &str) {
+        self.path = lock_file_path(db_path);
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Returns the PID of the process holding the lock for the database at `db_path`, or `None` if the
+/// database isn't locked
+///
+/// If the lock file exists but doesn't contain a PID, e.g. because the owner crashed while writing it,
+/// the database is locked but `None` is returned.
+pub fn lock_owner(db_path : &str) -> Result<Option<u32>, String> {
+    match fs::read_to_string(lock_file_path(db_path)) {
+        Ok(contents) => Ok(contents.trim().parse().ok()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("couldn't read lock file for {}: {}", db_path, err)),
+    }
+}
+
+/// Removes the lock for the database at `db_path`, regardless of which process holds it.  Returns `true`
+/// if there was a lock to remove
+pub fn force_unlock(db_path : &str) -> Result<bool, String> {
+    match fs::remove_file(lock_file_path(db_path)) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(format!("couldn't remove lock file for {}: {}", db_path, err)),
+    }
+}
+
+fn lock_file_path(db_path : &str) -> PathBuf {
+    Path::new(db_path).join(LOCK_FILE_NAME)
+}
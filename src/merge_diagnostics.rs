@@ -0,0 +1,136 @@
+//!
+//! The MergeDiagnostics module contains the counters and log that record how RocksDB invokes the merge
+//! operator of the "variants" CF.  The MergeDiagnostics, MergeAnomaly, and MergeAnomalyKind types are re-exported
+//!
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// The kinds of merge operator invocations that are recorded as a [MergeAnomaly]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MergeAnomalyKind {
+    /// The merge operator was called without an existing entry to merge into
+    MergeWithNone,
+    /// The merge operator was called with at least [LARGE_MERGE_OPERAND_COUNT](crate::TableConfig::LARGE_MERGE_OPERAND_COUNT) operands
+    LargeOperandCount,
+    /// The existing entry or an operand couldn't be decoded, so the merge failed
+    Failed,
+}
+
+/// An unexpected invocation of the merge operator, recorded in the [MergeDiagnostics] log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeAnomaly {
+    pub kind : MergeAnomalyKind,
+    /// The serialized key variant of the entry being merged
+    pub variant : Vec<u8>,
+    /// The number of operands passed to the merge operator
+    pub operand_count : usize,
+}
+
+/// Counts of the times RocksDB has invoked the merge operator of the "variants" CF, returned by
+/// [Table::merge_diagnostics](crate::Table::merge_diagnostics)
+///
+/// The counts cover every invocation since the database was opened, or since the last call to
+/// [reset_merge_diagnostics](crate::Table::reset_merge_diagnostics).  RocksDB invokes the merge operator when
+/// a merged entry is read, flushed, or compacted, rather than when the merge is written, so the counts
+/// won't line up with the number of writes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeDiagnostics {
+    /// The number of times the merge operator was invoked
+    pub merge_count : u64,
+    /// The number of invocations without an existing entry to merge into
+    pub merge_with_none_count : u64,
+    /// The number of invocations with at least [LARGE_MERGE_OPERAND_COUNT](crate::TableConfig::LARGE_MERGE_OPERAND_COUNT) operands
+    pub large_operand_count : u64,
+    /// The number of invocations that failed because an entry couldn't be decoded
+    pub failed_count : u64,
+    /// The largest number of operands passed to a single invocation
+    pub max_operand_count : usize,
+    /// The first anomalies encountered, up to [MERGE_DIAGNOSTIC_LOG_LEN](crate::TableConfig::MERGE_DIAGNOSTIC_LOG_LEN)
+    /// of them, in the order they occurred
+    pub log : Vec<MergeAnomaly>,
+}
+
+/// The shared state updated by the merge operator
+pub(crate) struct MergeDiagnosticsCollector {
+    merge_count : AtomicU64,
+    merge_with_none_count : AtomicU64,
+    large_operand_count : AtomicU64,
+    failed_count : AtomicU64,
+    max_operand_count : AtomicUsize,
+    large_operand_threshold : usize,
+    log_len : usize,
+    log : Mutex<Vec<MergeAnomaly>>,
+}
+
+impl MergeDiagnosticsCollector {
+
+    pub fn new(large_operand_threshold : usize, log_len : usize) -> Self {
+        Self {
+            merge_count : AtomicU64::new(0),
+            merge_with_none_count : AtomicU64::new(0),
+            large_operand_count : AtomicU64::new(0),
+            failed_count : AtomicU64::new(0),
+            max_operand_count : AtomicUsize::new(0),
+            large_operand_threshold,
+            log_len,
+            log : Mutex::new(vec![]),
+        }
+    }
+
+    /// Records an invocation of the merge operator, before the merge is performed
+    pub fn record_merge(&self, variant : &[u8], has_existing : bool, operand_count : usize) {
+
+        self.merge_count.fetch_add(1, Ordering::Relaxed);
+        self.max_operand_count.fetch_max(operand_count, Ordering::Relaxed);
+
+        if !has_existing {
+            self.merge_with_none_count.fetch_add(1, Ordering::Relaxed);
+            self.log_anomaly(MergeAnomalyKind::MergeWithNone, variant, operand_count);
+        }
+        if self.large_operand_threshold > 0 && operand_count >= self.large_operand_threshold {
+            self.large_operand_count.fetch_add(1, Ordering::Relaxed);
+            self.log_anomaly(MergeAnomalyKind::LargeOperandCount, variant, operand_count);
+        }
+    }
+
+    /// Records a merge that failed because an entry couldn't be decoded
+    pub fn record_failure(&self, variant : &[u8], operand_count : usize) {
+        self.failed_count.fetch_add(1, Ordering::Relaxed);
+        self.log_anomaly(MergeAnomalyKind::Failed, variant, operand_count);
+    }
+
+    fn log_anomaly(&self, kind : MergeAnomalyKind, variant : &[u8], operand_count : usize) {
+
+        //NOTE: Don't take the lock unless the log is enabled, because the merge operator runs on RocksDB's
+        // background threads as well as ours
+        if self.log_len == 0 {
+            return;
+        }
+
+        let mut log = self.log.lock().unwrap();
+        if log.len() < self.log_len {
+            log.push(MergeAnomaly{kind, variant : variant.to_vec(), operand_count});
+        }
+    }
+
+    pub fn get(&self) -> MergeDiagnostics {
+        MergeDiagnostics {
+            merge_count : self.merge_count.load(Ordering::Relaxed),
+            merge_with_none_count : self.merge_with_none_count.load(Ordering::Relaxed),
+            large_operand_count : self.large_operand_count.load(Ordering::Relaxed),
+            failed_count : self.failed_count.load(Ordering::Relaxed),
+            max_operand_count : self.max_operand_count.load(Ordering::Relaxed),
+            log : self.log.lock().unwrap().clone(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.merge_count.store(0, Ordering::Relaxed);
+        self.merge_with_none_count.store(0, Ordering::Relaxed);
+        self.large_operand_count.store(0, Ordering::Relaxed);
+        self.failed_count.store(0, Ordering::Relaxed);
+        self.max_operand_count.store(0, Ordering::Relaxed);
+        self.log.lock().unwrap().clear();
+    }
+}
@@ -0,0 +1,106 @@
+//!
+//! The Migration module contains the version of the on-disk format, and the steps that upgrade a database
+//! written in an earlier format.  Nothing should be re-exported
+//!
+
+use super::database::{*};
+use super::table_config::TableParams;
+
+/// The version of the on-disk format written by this version of fuzzy_rocks, stored in the "format_version" entry
+/// of the "metadata" CF
+///
+/// It must be incremented by any change that would make a database written before the change be misread, along
+/// with a [MigrationStep] from the previous version added to [MIGRATION_STEPS].
+pub(crate) const FORMAT_VERSION : u32 = 1;
+
+/// The format version of a database without a "format_version" entry, which was written before the format version
+/// was recorded
+const UNVERSIONED_FORMAT_VERSION : u32 = 1;
+
+/// An upgrade of a database's entries from one format version to the next
+pub(crate) struct MigrationStep {
+    /// The format version the step upgrades from.  The database is at the next version once the step is applied
+    pub from_version : u32,
+    /// Rewrites the entries that changed between the versions
+    pub upgrade : fn(&mut MigrationContext) -> Result<(), String>,
+}
+
+/// The steps that upgrade each earlier format version to the next, applied by [Table::migrate](crate::Table::migrate)
+pub(crate) const MIGRATION_STEPS : &[MigrationStep] = &[];
+
+/// The access to the database given to a [MigrationStep]
+///
+/// The writes are collected in a batch, which is committed atomically along with the new format version once the
+/// step has succeeded, so a migration interrupted partway through resumes from the last step completed.
+#[allow(dead_code)] //NOTE: Only used by migration steps, and there are none until the format changes
+pub(crate) struct MigrationContext<'a> {
+    db : &'a DBConnection,
+    batch : DBWriteBatch,
+}
+
+#[allow(dead_code)]
+impl MigrationContext<'_> {
+
+    /// Returns every entry in a column family, as it was before the step began
+    pub fn entries(&self, cf_name : &'static str) -> Result<Vec<RawEntry>, String> {
+        self.db.raw_entries(cf_name)
+    }
+
+    /// Replaces an entry in a column family
+    pub fn put(&mut self, cf_name : &'static str, key : &[u8], value : Vec<u8>) -> Result<(), String> {
+        self.db.put_raw(&mut self.batch, cf_name, key, value)
+    }
+
+    /// Deletes an entry from a column family
+    pub fn delete(&mut self, cf_name : &'static str, key : &[u8]) -> Result<(), String> {
+        self.db.delete_raw(&mut self.batch, cf_name, key)
+    }
+}
+
+/// Returns the format version of a database.  A database without any table parameters is new, so it's treated
+/// as having the current format version
+pub(crate) fn database_format_version(db : &DBConnection) -> Result<u32, String> {
+    match db.get_metadata::<u32>(FORMAT_VERSION_METADATA_NAME)? {
+        Some(format_version) => Ok(format_version),
+        None if db.get_metadata::<TableParams>(TABLE_PARAMS_METADATA_NAME)?.is_some() => Ok(UNVERSIONED_FORMAT_VERSION),
+        None => Ok(FORMAT_VERSION)
+    }
+}
+
+/// Returns an error if a database with the format version can't be read without being migrated first
+pub(crate) fn check_format_version(format_version : u32) -> Result<(), String> {
+    if format_version > FORMAT_VERSION {
+        return Err(format!("the database has format version {}, which is newer than the format version {} this version of fuzzy_rocks can read", format_version, FORMAT_VERSION));
+    }
+    if format_version < FORMAT_VERSION {
+        return Err(format!("the database has format version {}, and must be upgraded to format version {} with Table::migrate", format_version, FORMAT_VERSION));
+    }
+    Ok(())
+}
+
+/// Applies the steps to bring a database from its format version up to the `target_version`, one step at a
+/// time, and returns the number of steps applied
+pub(crate) fn migrate_database(db : &DBConnection, steps : &[MigrationStep], target_version : u32) -> Result<usize, String> {
+
+    let mut format_version = database_format_version(db)?;
+    if format_version > target_version {
+        return Err(format!("the database has format version {}, which is newer than the format version {} this version of fuzzy_rocks can read", format_version, target_version));
+    }
+
+    let mut applied = 0;
+    while format_version < target_version {
+        let step = steps.iter().find(|step| step.from_version == format_version)
+            .ok_or_else(|| format!("there is no migration from format version {}", format_version))?;
+
+        let mut context = MigrationContext{db, batch : DBWriteBatch::new()};
+        (step.upgrade)(&mut context)?;
+        format_version += 1;
+        db.put_metadata_in_batch(&mut context.batch, FORMAT_VERSION_METADATA_NAME, &format_version)?;
+        db.commit_batch(context.batch)?;
+        applied += 1;
+    }
+
+    //An unversioned database that is already current still has its version recorded
+    db.put_metadata(FORMAT_VERSION_METADATA_NAME, &format_version)?;
+    Ok(applied)
+}
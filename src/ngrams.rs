@@ -0,0 +1,79 @@
+//!
+//! The Ngrams module contains the [IndexStrategy] enum, and the logic for computing the n-grams of a key for
+//! the "ngrams" column family.  The IndexStrategy enum is re-exported
+//!
+
+use std::collections::HashSet;
+
+use serde::{Serialize, Deserialize};
+
+use super::key::KeyChar;
+
+/// The way a [Table](crate::Table) indexes its keys to find the candidates for a fuzzy lookup, set with
+/// [INDEX_STRATEGY](crate::TableConfig::INDEX_STRATEGY)
+///
+/// Whichever strategy is used, the candidates are evaluated with the [DISTANCE_FUNCTION](crate::TableConfig::DISTANCE_FUNCTION)
+/// in the same way, so the strategy only determines which records a lookup is able to find.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IndexStrategy {
+    /// Each key is indexed under its deletion variants, as described by the SymSpell algorithm
+    DeletionVariants,
+    /// Each key is indexed under every run of this many chars in the key, in the "ngrams" column family, and a
+    /// fuzzy lookup's candidates are the records that share enough n-grams with the lookup key
+    ///
+    /// The number of deletion variants grows steeply with the length of a key, while a key has at most one
+    /// n-gram per char, so this strategy scales much better to long keys.  The whole key is indexed, rather than
+    /// only its [MEANINGFUL_KEY_LEN](crate::TableConfig::MEANINGFUL_KEY_LEN) prefix.  Each edit changes at most
+    /// `n` of a key's n-grams, so a record is a candidate if it shares all but `MAX_DELETES * n` of the lookup
+    /// key's distinct n-grams, and always if it shares at least one.  Keys are padded at both ends, so even short
+    /// keys have n-grams, but a record that shares no n-gram at all with the lookup key is never found.
+    ///
+    /// Keys are still indexed in the "variants" column family, but without any variants besides the key itself.
+    Ngrams(usize),
+}
+
+impl IndexStrategy {
+
+    /// Returns the length of the n-grams, or `None` if the strategy doesn't index n-grams
+    pub fn ngram_len(&self) -> Option<usize> {
+        match self {
+            Self::DeletionVariants => None,
+            Self::Ngrams(n) => Some(*n),
+        }
+    }
+}
+
+/// Returns the distinct n-grams of a key, encoded as they are stored in the "ngrams" CF
+///
+/// The key is padded with `n-1` empty positions at each end, so every char begins `n` n-grams.  Each position is
+/// encoded as a 0 byte if it's padding, or a 1 byte followed by the char's encoding.
+pub(crate) fn key_ngrams<KeyCharT : KeyChar>(key_chars : &[KeyCharT], n : usize) -> HashSet<Vec<u8>> {
+
+    if key_chars.is_empty() || n == 0 {
+        return HashSet::new();
+    }
+
+    let padding = std::iter::repeat(None).take(n-1);
+    let padded_chars : Vec<Option<&KeyCharT>> = padding.clone().chain(key_chars.iter().map(Some)).chain(padding).collect();
+    padded_chars.windows(n).map(|ngram| {
+        let mut ngram_bytes = vec![];
+        for position in ngram {
+            match position {
+                Some(key_char) => {
+                    ngram_bytes.push(1);
+                    ngram_bytes.extend_from_slice(&KeyCharT::encode_key_chars(std::slice::from_ref(*key_char)));
+                },
+                None => ngram_bytes.push(0),
+            }
+        }
+        ngram_bytes
+    }).collect()
+}
+
+/// Returns the number of a lookup key's distinct n-grams that a key within `max_distance` edits of it must share
+///
+/// `slack` is the number of the lookup key's n-grams that a match may be missing regardless, e.g. because it
+/// only has to begin with the lookup key.  A candidate must always share at least one n-gram, to be found at all.
+pub(crate) fn min_shared_ngrams(lookup_ngram_count : usize, n : usize, max_distance : usize, slack : usize) -> usize {
+    lookup_ngram_count.saturating_sub(max_distance * n + slack).max(1)
+}
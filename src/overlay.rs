@@ -0,0 +1,170 @@
+//!
+//! The Overlay module contains the [SessionOverlay] object, for layering temporary records over a [Table] at
+//! query time.  The SessionOverlay and OverlayRecordID types are re-exported
+//!
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::{*};
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+
+/// Identifies a result of a lookup through a [SessionOverlay], as either a record in the [Table] or one of the
+/// overlay's own records
+#[derive(Copy, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum OverlayRecordID {
+    /// A record stored in the Table
+    Table(RecordID),
+    /// A record held by the overlay, identified by the index returned from [SessionOverlay::insert]
+    Overlay(usize),
+}
+
+/// The keys and value of a record held by a [SessionOverlay]
+type OverlayRecord<ConfigT, const UTF8_KEYS : bool> = (Vec<<Table<ConfigT, UTF8_KEYS> as TableKeyEncoding>::OwnedKeyT>, <ConfigT as TableConfig>::ValueT);
+
+/// A small set of records held in memory, e.g. a user's recent entries, that is searched along with a [Table]
+/// without ever being written to the Table's database
+///
+/// The overlay's records are compared with each lookup key directly, using the Table's
+/// [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION) and key folding, so their distances can be ranked
+/// alongside the Table's results.  Only the keys the Table's index would find as candidates, e.g. those within
+/// [MAX_DELETES](TableConfig::MAX_DELETES), are compared, so the overlay finds the same records the Table would
+/// if they were stored in it.  Because every record is compared, an overlay is only suitable for a few
+/// hundred records at most.  The overlay doesn't borrow the Table, so the same overlay can be used with the
+/// Table for the length of a session while the Table continues to change.
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let mut table = Table::<DefaultTableConfig, true>::new("overlay_example.rocks", DefaultTableConfig()).unwrap();
+/// table.reset().unwrap();
+/// let london = table.insert("London", &"England".to_string()).unwrap();
+///
+/// let mut overlay = SessionOverlay::<DefaultTableConfig, true>::new();
+/// let londrina = overlay.insert("Londrina", "Brazil".to_string()).unwrap();
+///
+/// let results = overlay.lookup_fuzzy(&table, "Londin", Some(2)).unwrap();
+/// assert_eq!(results, vec![(OverlayRecordID::Table(london), 1), (OverlayRecordID::Overlay(londrina), 2)]);
+/// assert_eq!(overlay.get_value(&table, results[1].0).unwrap(), "Brazil");
+/// ```
+pub struct SessionOverlay<ConfigT : TableConfig, const UTF8_KEYS : bool>
+    where
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding,
+    {
+    records : Vec<Option<OverlayRecord<ConfigT, UTF8_KEYS>>>,
+}
+
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Default for SessionOverlay<ConfigT, UTF8_KEYS>
+    where
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+    fn default() -> Self {
+        Self{records : vec![]}
+    }
+}
+
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>SessionOverlay<ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Creates an empty overlay
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of records in the overlay
+    pub fn len(&self) -> usize {
+        self.records.iter().filter(|record| record.is_some()).count()
+    }
+
+    /// Returns `true` if the overlay has no records
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds a record with a single key to the overlay, and returns its index.  The record is never written to a
+    /// Table's database
+    pub fn insert<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, key : K, value : ConfigT::ValueT) -> Result<usize, String> {
+        let key = key.into_key();
+        if key.num_chars() > MAX_KEY_LENGTH {
+            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+        }
+        self.records.push(Some((vec![OwnedKeyT::from_key(&key)], value)));
+        Ok(self.records.len() - 1)
+    }
+
+    /// Removes a record from the overlay.  The indices of the other records are unaffected
+    pub fn remove(&mut self, idx : usize) -> Result<(), String> {
+        match self.records.get_mut(idx) {
+            Some(record) if record.is_some() => {
+                *record = None;
+                Ok(())
+            },
+            _ => Err(format!("no overlay record with index {}", idx))
+        }
+    }
+
+    /// Removes every record from the overlay
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// Returns the value of one of the overlay's records
+    pub fn overlay_value(&self, idx : usize) -> Option<&ConfigT::ValueT> {
+        self.records.get(idx)?.as_ref().map(|(_keys, value)| value)
+    }
+
+    /// Returns the value of a record returned by a lookup, from the overlay or the Table
+    pub fn get_value(&self, table : &Table<ConfigT, UTF8_KEYS>, id : OverlayRecordID) -> Result<ConfigT::ValueT, String>
+        where
+        ConfigT::ValueT : Clone
+    {
+        match id {
+            OverlayRecordID::Table(record_id) => table.get_value(record_id),
+            OverlayRecordID::Overlay(idx) => self.overlay_value(idx).cloned().ok_or_else(|| format!("no overlay record with index {}", idx))
+        }
+    }
+
+    /// Performs a fuzzy lookup against the Table and the overlay, and returns the merged results ordered by
+    /// ascending distance.  See [Table::lookup_fuzzy] for the meaning of `threshold`
+    ///
+    /// Unlike `Table::lookup_fuzzy`, the results aren't lazy, because the overlay's results must be ranked among
+    /// the Table's.  A Table record and an overlay record at the same distance are ordered Table record first.
+    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, table : &Table<ConfigT, UTF8_KEYS>, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(OverlayRecordID, ConfigT::DistanceT)>, String> {
+        let key = key.into_key();
+        let mut results : Vec<(OverlayRecordID, ConfigT::DistanceT)> = table.lookup_fuzzy_internal(&key, threshold, None)?.into_vec()?
+            .into_iter()
+            .map(|(record_id, distance)| (OverlayRecordID::Table(record_id), distance))
+            .collect();
+
+        for (idx, record) in self.records.iter().enumerate() {
+            if let Some((keys, _value)) = record {
+                let candidate_keys = keys.iter().filter(|record_key| table.is_fuzzy_candidate_key(&key, *record_key));
+                if let Some(distance) = table.closest_key_distance(&key, candidate_keys, threshold) {
+                    results.push((OverlayRecordID::Overlay(idx), distance));
+                }
+            }
+        }
+
+        results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(core::cmp::Ordering::Equal).then(a.0.cmp(&b.0)));
+        Ok(results)
+    }
+
+    /// Returns the records in the Table and the overlay whose keys are the shortest distance from the key supplied
+    pub fn lookup_best<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, table : &Table<ConfigT, UTF8_KEYS>, key : K) -> Result<Vec<OverlayRecordID>, String> {
+        let results = self.lookup_fuzzy(table, key, None)?;
+        let best_distance = match results.first() {
+            Some((_, distance)) => *distance,
+            None => return Ok(vec![])
+        };
+        Ok(results.into_iter().take_while(|(_, distance)| *distance == best_distance).map(|(id, _)| id).collect())
+    }
+}
@@ -0,0 +1,123 @@
+//!
+//! The ParallelInsert module contains [Table::create_parallel](crate::Table::create_parallel), for creating a large
+//! number of records on several threads at once
+//!
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::RecordID;
+use super::key::{*};
+use super::database::DBWriteBatch;
+use super::table_config::{*};
+use super::table::{*};
+
+/// The number of records each worker thread writes to the database together, in a single batch
+const PARALLEL_INSERT_BATCH_SIZE : usize = 1000;
+
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Self : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Creates a record for each of the supplied keys and values, using `num_threads` worker threads, and returns
+    /// the new records' RecordIDs, in the same order as the records
+    ///
+    /// The records are split into a contiguous shard for each thread, and each thread composes the writes for its
+    /// records and commits them in batches.  The variant references are merged into the "variants" CF by its merge
+    /// operator, so the batches of different threads never wait on each other's writes.  This pairs well with
+    /// [begin_bulk_load](Table::begin_bulk_load), which buffers the variant references in memory instead.
+    ///
+    /// The writes for some configs read the entries that creating another record could also write, so the
+    /// records are created one at a time, on the calling thread, if the Table has an [EXACT_KEY_INDEX](TableConfig::EXACT_KEY_INDEX),
+    /// a [PHONETIC_FUNCTION](TableConfig::PHONETIC_FUNCTION), a [TOKEN_INDEX](TableConfig::TOKEN_INDEX), an
+    /// n-gram [INDEX_STRATEGY](TableConfig::INDEX_STRATEGY), a [VARIANT_PAGE_SIZE](TableConfig::VARIANT_PAGE_SIZE),
+    /// or [DETERMINISTIC_RECORD_IDS](TableConfig::DETERMINISTIC_RECORD_IDS).
+    ///
+    /// Returns an error without creating anything if any of the records has no keys.  If an error is encountered
+    /// after that, the records in the batches already committed have been created, but their RecordIDs aren't
+    /// returned.  Every RecordID is written as a deleted record before the workers start, so the RecordIDs of the
+    /// records that weren't created are left deleted, rather than as gaps in the sequence of RecordIDs, even if the
+    /// process stops partway through.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("create_parallel_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let records : Vec<(Vec<String>, String)> = (0..100).map(|i| (vec![format!("Key {i}")], format!("Value {i}"))).collect();
+    ///
+    /// let record_ids = table.create_parallel(&records, 4).unwrap();
+    /// assert_eq!(record_ids.len(), 100);
+    /// assert_eq!(table.get_value(record_ids[42]).unwrap(), "Value 42");
+    /// ```
+    pub fn create_parallel<K>(&mut self, records : &[(Vec<K>, ConfigT::ValueT)], num_threads : usize) -> Result<Vec<RecordID>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT> + Sync,
+        ConfigT::ValueT : Sync,
+        Self : Sync,
+    {
+
+        if num_threads < 1 {
+            return Err("num_threads must be at least 1".to_string());
+        }
+        if records.iter().any(|(keys, _)| keys.is_empty()) {
+            return Err("record must have at least one key".to_string());
+        }
+        if !self.supports_concurrent_creates() {
+            return records.iter().map(|(keys, value)| self.insert_internal(keys.iter(), keys.len(), value)).collect();
+        }
+
+        //The RecordIDs are all claimed up front, so the workers don't need to coordinate
+        let record_ids = self.reserve_record_ids(records.len())?;
+
+        let shard_len = records.len().div_ceil(num_threads).max(1);
+        let table = &*self;
+        let shard_results : Vec<(usize, Option<String>)> = std::thread::scope(|scope| {
+            let workers : Vec<_> = records.chunks(shard_len).zip(record_ids.chunks(shard_len))
+                .map(|(shard_records, shard_ids)| scope.spawn(move || table.create_shard(shard_records, shard_ids)))
+                .collect();
+            workers.into_iter().map(|worker| worker.join().unwrap_or_else(|payload| std::panic::resume_unwind(payload))).collect()
+        });
+
+        //The records that weren't committed are left deleted, so their RecordIDs are free to be assigned again
+        let mut first_err = None;
+        for ((committed, err), shard_ids) in shard_results.into_iter().zip(record_ids.chunks(shard_len)) {
+            if let Some(err) = err {
+                for record_id in shard_ids[committed..].iter().rev() {
+                    self.release_record_id(*record_id);
+                }
+                first_err.get_or_insert(err);
+            }
+        }
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(record_ids)
+        }
+    }
+
+    /// Creates one worker's shard of the records for [create_parallel](Self::create_parallel), committing a batch
+    /// at a time.  Returns the number of records committed, along with the error that stopped the worker, if any
+    fn create_shard<K>(&self, records : &[(Vec<K>, ConfigT::ValueT)], record_ids : &[RecordID]) -> (usize, Option<String>)
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        let mut committed = 0;
+        for (batch_records, batch_ids) in records.chunks(PARALLEL_INSERT_BATCH_SIZE).zip(record_ids.chunks(PARALLEL_INSERT_BATCH_SIZE)) {
+            let mut batch = DBWriteBatch::new();
+            let result = batch_records.iter().zip(batch_ids)
+                .try_for_each(|((keys, value), record_id)| self.put_new_record(&mut batch, *record_id, keys.iter(), keys.len(), value))
+                .and_then(|_| self.commit_batch(batch));
+            if let Err(err) = result {
+                return (committed, Some(err));
+            }
+            committed += batch_records.len();
+        }
+        (committed, None)
+    }
+}
@@ -9,7 +9,15 @@
 /// 
 /// NOTE: Counters are being implemented on an as-needed basis
 #[cfg(feature = "perf_counters")]
-use core::cell::Cell;
+use std::sync::Mutex;
+#[cfg(feature = "perf_counters")]
+use std::cell::RefCell;
+
+#[cfg(feature = "perf_counters")]
+thread_local! {
+    /// The counters for each of the scopes open on this thread, opened by [PerfCounters::scoped], innermost last
+    static SCOPES : RefCell<Vec<PerfCounterFields>> = const { RefCell::new(Vec::new()) };
+}
 
 /// All of the performance counters to measure and tune the behavior of the system
 /// 
@@ -61,6 +69,15 @@ pub struct PerfCounterFields {
     /// [keys_found_count](Self::keys_found_count).  In the future, this will be optimized.
     pub distance_function_invocation_count : usize,
 
+    /// The number of keys rejected by the banded Levenshtein check during [lookup_fuzzy_pruned](crate::Table::lookup_fuzzy_pruned),
+    /// without invoking the distance function
+    pub keys_pruned_count : usize,
+
+    /// The number of [lookup_fuzzy_within](crate::Table::lookup_fuzzy_within) and [lookup_fuzzy_pruned](crate::Table::lookup_fuzzy_pruned)
+    /// lookups that scanned every record rather than probing the variants of the key, because the Table has so few
+    /// live records that the scan was estimated to be cheaper.  See [FuzzyLookup::strategy](crate::FuzzyLookup::strategy)
+    pub pruned_scan_count : usize,
+
     /// The number of unique records that were found with fuzzy lookups
     /// 
     /// This counter include doesn't include records that were rejected because of a distance threshold,
@@ -71,6 +88,18 @@ pub struct PerfCounterFields {
     /// keys to find the shortest distance.  Adjusting the [group_variant_overlap_threshold](crate::TableConfig::GROUP_VARIANT_OVERLAP_THRESHOLD) is one way to
     /// lower this ratio, although it may hurt performance in other ways.
     pub records_found_count : usize,
+
+    /// The time taken to probe each variant entry during fuzzy lookups, whether or not the entry was found
+    pub variant_probe_latency : LatencyHistogram,
+
+    /// The time taken to load and decode each key group during fuzzy lookups
+    pub key_group_load_latency : LatencyHistogram,
+
+    /// The time taken by each invocation of the distance function during fuzzy lookups
+    /// 
+    /// Reading the clock takes a noticeable fraction of the time to compare two short keys, so these
+    /// durations are inflated, and the distance function is slower with the `perf_counters` feature enabled.
+    pub distance_latency : LatencyHistogram,
 }
 
 impl PerfCounterFields {
@@ -83,32 +112,187 @@ impl PerfCounterFields {
             key_group_load_count : 0,
             keys_found_count : 0,
             distance_function_invocation_count : 0,
+            keys_pruned_count : 0,
+            pruned_scan_count : 0,
             records_found_count : 0,
+            variant_probe_latency : LatencyHistogram::new(),
+            key_group_load_latency : LatencyHistogram::new(),
+            distance_latency : LatencyHistogram::new(),
+        }
+    }
+}
+
+/// The number of bits of each duration's value kept by a [LatencyHistogram], below its most significant bit
+const HISTOGRAM_SUB_BUCKET_BITS : u32 = 3;
+
+/// The number of buckets in a [LatencyHistogram] for each power of two
+const HISTOGRAM_SUB_BUCKETS : usize = 1 << HISTOGRAM_SUB_BUCKET_BITS;
+
+/// The number of buckets needed for every nanosecond duration that fits in a u64
+const HISTOGRAM_BUCKETS : usize = (65 - HISTOGRAM_SUB_BUCKET_BITS as usize) * HISTOGRAM_SUB_BUCKETS;
+
+/// A histogram of durations, part of the [PerfCounterFields], from which percentiles can be estimated
+/// 
+/// Durations are counted in nanoseconds, in buckets whose width grows with the duration, similar to an
+/// [HDR histogram](http://hdrhistogram.org/), so any duration is placed in a bucket no more than 12.5% wider than
+/// the duration itself.
+/// 
+/// ```
+/// use std::time::Duration;
+/// use fuzzy_rocks::{*};
+/// 
+/// let mut histogram = LatencyHistogram::new();
+/// for micros in 1..=100 {
+///     histogram.record(Duration::from_micros(micros));
+/// }
+/// assert_eq!(histogram.count(), 100);
+/// let p90 = histogram.percentile(90.0);
+/// assert!(p90 >= Duration::from_micros(90) && p90 <= Duration::from_micros(90) * 9 / 8);
+/// assert_eq!(histogram.max(), Duration::from_micros(100));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts : [u64; HISTOGRAM_BUCKETS],
+    count : u64,
+    total_nanos : u128,
+    max_nanos : u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+
+    /// Creates an empty LatencyHistogram
+    pub fn new() -> Self {
+        Self {
+            counts : [0; HISTOGRAM_BUCKETS],
+            count : 0,
+            total_nanos : 0,
+            max_nanos : 0,
+        }
+    }
+
+    /// Adds a duration to the histogram
+    pub fn record(&mut self, duration : std::time::Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.counts[Self::bucket_idx(nanos)] += 1;
+        self.count += 1;
+        self.total_nanos += nanos as u128;
+        self.max_nanos = self.max_nanos.max(nanos);
+    }
+
+    /// Returns the number of durations in the histogram
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the longest duration in the histogram, or zero if it's empty
+    pub fn max(&self) -> std::time::Duration {
+        std::time::Duration::from_nanos(self.max_nanos)
+    }
+
+    /// Returns the mean of the durations in the histogram, or zero if it's empty
+    pub fn mean(&self) -> std::time::Duration {
+        match self.count {
+            0 => std::time::Duration::ZERO,
+            count => std::time::Duration::from_nanos((self.total_nanos / count as u128) as u64)
+        }
+    }
+
+    /// Returns an estimate of the duration that `percentile` percent of the durations in the histogram are no
+    /// longer than, e.g. `percentile(99.0)` for the 99th percentile, or zero if the histogram is empty
+    /// 
+    /// The estimate is the upper bound of the bucket holding the percentile, so it may overstate the true
+    /// percentile by up to 12.5%, but never by more than the longest duration recorded.
+    pub fn percentile(&self, percentile : f64) -> std::time::Duration {
+        if self.count == 0 {
+            return std::time::Duration::ZERO;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0 * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (idx, bucket_count) in self.counts.iter().enumerate() {
+            seen += bucket_count;
+            if seen >= rank {
+                return std::time::Duration::from_nanos(Self::bucket_upper_bound(idx).min(self.max_nanos));
+            }
         }
+        self.max()
+    }
+
+    /// Adds all of the durations in another histogram to this one
+    pub fn merge(&mut self, other : &Self) {
+        for (count, other_count) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other_count;
+        }
+        self.count += other.count;
+        self.total_nanos += other.total_nanos;
+        self.max_nanos = self.max_nanos.max(other.max_nanos);
+    }
+
+    /// Returns the index of the bucket for a duration, in nanoseconds.  Durations shorter than the number of
+    /// sub-buckets each have a bucket of their own, and after that, each power of two is split into sub-buckets
+    fn bucket_idx(nanos : u64) -> usize {
+        if nanos < HISTOGRAM_SUB_BUCKETS as u64 {
+            return nanos as usize;
+        }
+        let exponent = 63 - nanos.leading_zeros();
+        let mantissa = (nanos >> (exponent - HISTOGRAM_SUB_BUCKET_BITS)) as usize;
+        (exponent - HISTOGRAM_SUB_BUCKET_BITS + 1) as usize * HISTOGRAM_SUB_BUCKETS + (mantissa - HISTOGRAM_SUB_BUCKETS)
+    }
+
+    /// Returns the longest duration, in nanoseconds, that belongs in a bucket
+    fn bucket_upper_bound(idx : usize) -> u64 {
+        if idx < HISTOGRAM_SUB_BUCKETS {
+            return idx as u64;
+        }
+        let exponent = (idx / HISTOGRAM_SUB_BUCKETS) as u32 + HISTOGRAM_SUB_BUCKET_BITS - 1;
+        let mantissa = (HISTOGRAM_SUB_BUCKETS + idx % HISTOGRAM_SUB_BUCKETS) as u128;
+        (((mantissa + 1) << (exponent - HISTOGRAM_SUB_BUCKET_BITS)) - 1).min(u64::MAX as u128) as u64
     }
 }
 
 #[cfg(feature = "perf_counters")]
-pub struct PerfCounters(Cell<PerfCounterFields>);
+pub struct PerfCounters(Mutex<PerfCounterFields>);
 
 #[cfg(feature = "perf_counters")]
 impl PerfCounters {
     pub fn new() -> Self {
-        Self(Cell::new(PerfCounterFields::new()))
+        Self(Mutex::new(PerfCounterFields::new()))
     }
     pub fn reset(&self) {
         self.set(PerfCounterFields::new())
     }
     pub fn update<F : Fn(&mut PerfCounterFields)>(&self, func : F) {
-        let mut fields = self.get();
-        func(&mut fields);
-        self.set(fields);
+        func(&mut self.0.lock().unwrap());
+        SCOPES.with(|scopes| scopes.borrow_mut().iter_mut().for_each(&func));
     }
     pub fn get(&self) -> PerfCounterFields {
-        self.0.get()
+        *self.0.lock().unwrap()
     }
     pub fn set(&self, fields : PerfCounterFields) {
-        self.0.set(fields);
+        *self.0.lock().unwrap() = fields;
+    }
+    /// Runs `func`, and returns its result along with everything counted on this thread while it ran, by any
+    /// PerfCounters.  Scopes may be nested, and each one counts the activity of the scopes inside it
+    pub fn scoped<R, F : FnOnce() -> R>(func : F) -> (R, PerfCounterFields) {
+
+        //The scope is closed even if `func` panics
+        struct ScopeGuard();
+        impl Drop for ScopeGuard {
+            fn drop(&mut self) {
+                SCOPES.with(|scopes| scopes.borrow_mut().pop());
+            }
+        }
+
+        SCOPES.with(|scopes| scopes.borrow_mut().push(PerfCounterFields::new()));
+        let _guard = ScopeGuard();
+        let result = func();
+        let fields = SCOPES.with(|scopes| *scopes.borrow().last().unwrap());
+        (result, fields)
     }
 }
 
@@ -125,6 +309,9 @@ impl PerfCounters {
     pub fn get(&self) -> PerfCounterFields {
         PerfCounterFields::new()
     }
+    pub fn scoped<R, F : FnOnce() -> R>(func : F) -> (R, PerfCounterFields) {
+        (func(), PerfCounterFields::new())
+    }
 }
 
 
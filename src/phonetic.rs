@@ -0,0 +1,631 @@
+//!
+//! The Phonetic module contains the phonetic encodings that can be used to build a phonetic index.  Nothing
+//! from here should be re-exported
+//!
+
+use super::unicode_string_helpers::unicode_strip_diacritics;
+
+/// Returns the [American Soundex](https://en.wikipedia.org/wiki/Soundex) code of a string, or `None` if the
+/// string has no letters
+///
+/// Diacritics are stripped, and any char that still isn't an ASCII letter is ignored, so "Müller, Jr." is
+/// encoded the same as "MullerJr".
+pub fn soundex(s : &str) -> Option<String> {
+
+    let mut letters = unicode_strip_diacritics(s).chars()
+        .filter(|the_char| the_char.is_ascii_alphabetic())
+        .map(|the_char| the_char.to_ascii_uppercase())
+        .collect::<Vec<char>>()
+        .into_iter();
+
+    //The first letter is kept as it is
+    let first_letter = letters.next()?;
+    let mut code = String::with_capacity(4);
+    code.push(first_letter);
+
+    //Each following letter is replaced by its digit, except that adjacent letters with the same digit are only
+    //encoded once, even if they are separated by an 'H' or 'W'.  Vowels aren't encoded, but they separate letters
+    let mut prev_digit = soundex_digit(first_letter);
+    for letter in letters {
+        if code.len() == 4 {
+            break;
+        }
+        if letter == 'H' || letter == 'W' {
+            continue;
+        }
+        let digit = soundex_digit(letter);
+        if digit != '0' && digit != prev_digit {
+            code.push(digit);
+        }
+        prev_digit = digit;
+    }
+
+    //Pad the code with zeros, if there weren't enough letters to fill it
+    while code.len() < 4 {
+        code.push('0');
+    }
+
+    Some(code)
+}
+
+/// Returns the digit a letter is encoded as, or '0' for vowels and other letters that aren't encoded
+fn soundex_digit(letter : char) -> char {
+    match letter {
+        'B' | 'F' | 'P' | 'V' => '1',
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => '2',
+        'D' | 'T' => '3',
+        'L' => '4',
+        'M' | 'N' => '5',
+        'R' => '6',
+        _ => '0'
+    }
+}
+
+/// The length of each of the codes returned by [double_metaphone]
+const DOUBLE_METAPHONE_CODE_LEN : usize = 4;
+
+/// Returns the primary and alternate [Double Metaphone](https://en.wikipedia.org/wiki/Metaphone#Double_Metaphone)
+/// codes of a string, or `None` if the string has nothing to encode
+///
+/// This follows Lawrence Philips' original algorithm, as implemented by most libraries, with codes of up to 4
+/// letters.  The alternate code is the same as the primary code unless part of the string has a second plausible
+/// pronunciation, e.g. ("XMT", "SMT") for "Schmidt".  Diacritics other than those of 'Ç' and 'Ñ' are stripped.
+pub fn double_metaphone(s : &str) -> Option<(String, String)> {
+
+    let value : Vec<char> = s.trim().to_uppercase().chars()
+        .flat_map(|the_char| match the_char {
+            'Ç' | 'Ñ' => vec![the_char],
+            _ => unicode_strip_diacritics(the_char.encode_utf8(&mut [0; 4])).chars().collect()
+        })
+        .collect();
+
+    let mut encoder = DoubleMetaphone {
+        slavo_germanic : value.contains(&'W') || value.contains(&'K') || value.windows(2).any(|pair| pair == ['C', 'Z']),
+        value,
+        primary : String::new(),
+        alternate : String::new(),
+    };
+    encoder.encode();
+
+    if encoder.primary.is_empty() && encoder.alternate.is_empty() {
+        return None;
+    }
+    Some((encoder.primary, encoder.alternate))
+}
+
+/// The state of a [double_metaphone] encoding
+struct DoubleMetaphone {
+    value : Vec<char>,
+    slavo_germanic : bool,
+    primary : String,
+    alternate : String,
+}
+
+impl DoubleMetaphone {
+
+    /// Encodes the whole value, one letter or group of letters at a time
+    fn encode(&mut self) {
+
+        //The first letter of these pairs is silent at the start of a word
+        let mut idx = if ["GN", "KN", "PN", "WR", "PS"].iter().any(|start| self.contains(0, &[start])) { 1 } else { 0 };
+
+        while !self.is_complete() && idx < self.value.len() {
+            idx = match self.value[idx] {
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                    if idx == 0 {
+                        self.append("A");
+                    }
+                    idx + 1
+                },
+                'B' => { self.append("P"); self.skip_double(idx, 'B') },
+                'Ç' => { self.append("S"); idx + 1 },
+                'C' => self.encode_c(idx),
+                'D' => self.encode_d(idx),
+                'F' => { self.append("F"); self.skip_double(idx, 'F') },
+                'G' => self.encode_g(idx),
+                'H' => self.encode_h(idx),
+                'J' => self.encode_j(idx),
+                'K' => { self.append("K"); self.skip_double(idx, 'K') },
+                'L' => self.encode_l(idx),
+                'M' => {
+                    self.append("M");
+                    let silent_b = self.contains(idx as isize - 1, &["UMB"]) && (idx + 2 == self.value.len() || self.contains(idx as isize + 2, &["ER"]));
+                    if self.char_at(idx as isize + 1) == 'M' || silent_b { idx + 2 } else { idx + 1 }
+                },
+                'N' => { self.append("N"); self.skip_double(idx, 'N') },
+                'Ñ' => { self.append("N"); idx + 1 },
+                'P' => {
+                    if self.char_at(idx as isize + 1) == 'H' {
+                        self.append("F");
+                        idx + 2
+                    } else {
+                        self.append("P");
+                        if self.contains(idx as isize + 1, &["P", "B"]) { idx + 2 } else { idx + 1 }
+                    }
+                },
+                'Q' => { self.append("K"); self.skip_double(idx, 'Q') },
+                'R' => self.encode_r(idx),
+                'S' => self.encode_s(idx),
+                'T' => self.encode_t(idx),
+                'V' => { self.append("F"); self.skip_double(idx, 'V') },
+                'W' => self.encode_w(idx),
+                'X' => self.encode_x(idx),
+                'Z' => self.encode_z(idx),
+                _ => idx + 1
+            };
+        }
+    }
+
+    fn encode_c(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        //Various Germanic, e.g. "Bacher", "Macher"
+        if self.germanic_ach(idx) {
+            self.append("K");
+            return idx + 2;
+        }
+        if idx == 0 && self.contains(i, &["CAESAR"]) {
+            self.append("S");
+            return idx + 2;
+        }
+        if self.contains(i, &["CH"]) {
+            return self.encode_ch(idx);
+        }
+        if self.contains(i, &["CZ"]) && !self.contains(i - 2, &["WICZ"]) {
+            //"Czerny"
+            self.append_alt("S", "X");
+            return idx + 2;
+        }
+        if self.contains(i + 1, &["CIA"]) {
+            //"Focaccia"
+            self.append("X");
+            return idx + 3;
+        }
+        if self.contains(i, &["CC"]) && !(idx == 1 && self.char_at(0) == 'M') {
+            //Double "CC", but not "McClelland"
+            if self.contains(i + 2, &["I", "E", "H"]) && !self.contains(i + 2, &["HU"]) {
+                if (idx == 1 && self.char_at(0) == 'A') || self.contains(i - 1, &["UCCEE", "UCCES"]) {
+                    //"Accident", "Accede", "Succeed"
+                    self.append("KS");
+                } else {
+                    //"Bacci", "Bertucci", and other Italian
+                    self.append("X");
+                }
+                return idx + 3;
+            }
+            self.append("K");
+            return idx + 2;
+        }
+        if self.contains(i, &["CK", "CG", "CQ"]) {
+            self.append("K");
+            return idx + 2;
+        }
+        if self.contains(i, &["CI", "CE", "CY"]) {
+            //Italian vs. English
+            if self.contains(i, &["CIO", "CIE", "CIA"]) {
+                self.append_alt("S", "X");
+            } else {
+                self.append("S");
+            }
+            return idx + 2;
+        }
+
+        self.append("K");
+        if self.contains(i + 1, &[" C", " Q", " G"]) {
+            //"Mac Caffrey", "Mac Gregor"
+            idx + 3
+        } else if self.contains(i + 1, &["C", "K", "Q"]) && !self.contains(i + 1, &["CE", "CI"]) {
+            idx + 2
+        } else {
+            idx + 1
+        }
+    }
+
+    /// Returns `true` for a "C" in a Germanic "ACH" that is pronounced "K", e.g. "Bacher", but not "Achilles"
+    fn germanic_ach(&self, idx : usize) -> bool {
+        let i = idx as isize;
+        if self.contains(i, &["CHIA"]) {
+            return true;
+        }
+        if idx <= 1 || is_vowel(self.char_at(i - 2)) || !self.contains(i - 1, &["ACH"]) {
+            return false;
+        }
+        let next = self.char_at(i + 2);
+        (next != 'I' && next != 'E') || self.contains(i - 2, &["BACHER", "MACHER"])
+    }
+
+    fn encode_ch(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if idx > 0 && self.contains(i, &["CHAE"]) {
+            //"Michael"
+            self.append_alt("K", "X");
+        } else if idx == 0 && (self.contains(i + 1, &["HARAC", "HARIS"]) || self.contains(i + 1, &["HOR", "HYM", "HIA", "HEM"])) && !self.contains(0, &["CHORE"]) {
+            //Greek roots, e.g. "Chemistry", "Chorus"
+            self.append("K");
+        } else if self.contains(0, &["VAN ", "VON ", "SCH"])
+            || self.contains(i - 2, &["ORCHES", "ARCHIT", "ORCHID"])
+            || self.contains(i + 2, &["T", "S"])
+            || ((idx == 0 || self.contains(i - 1, &["A", "O", "U", "E"]))
+                && (self.contains(i + 2, &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "]) || idx + 2 == self.value.len())) {
+            //Germanic, Greek, or otherwise "CH" for a "KH" sound
+            self.append("K");
+        } else if idx > 0 {
+            if self.contains(0, &["MC"]) {
+                self.append("K");
+            } else {
+                self.append_alt("X", "K");
+            }
+        } else {
+            self.append("X");
+        }
+        idx + 2
+    }
+
+    fn encode_d(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+        if self.contains(i, &["DG"]) {
+            if self.contains(i + 2, &["I", "E", "Y"]) {
+                //"Edge"
+                self.append("J");
+                idx + 3
+            } else {
+                //"Edgar"
+                self.append("TK");
+                idx + 2
+            }
+        } else if self.contains(i, &["DT", "DD"]) {
+            self.append("T");
+            idx + 2
+        } else {
+            self.append("T");
+            idx + 1
+        }
+    }
+
+    fn encode_g(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if self.char_at(i + 1) == 'H' {
+            return self.encode_gh(idx);
+        }
+        if self.char_at(i + 1) == 'N' {
+            if idx == 1 && is_vowel(self.char_at(0)) && !self.slavo_germanic {
+                self.append_alt("KN", "N");
+            } else if !self.contains(i + 2, &["EY"]) && self.char_at(i + 1) != 'Y' && !self.slavo_germanic {
+                self.append_alt("N", "KN");
+            } else {
+                self.append("KN");
+            }
+            return idx + 2;
+        }
+        if self.contains(i + 1, &["LI"]) && !self.slavo_germanic {
+            //"Tagliaro"
+            self.append_alt("KL", "L");
+            return idx + 2;
+        }
+        if idx == 0 && (self.char_at(i + 1) == 'Y' || self.contains(i + 1, &["ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER"])) {
+            //"-ges-", "-gep-", "-gel-", "-gie-" at the beginning
+            self.append_alt("K", "J");
+            return idx + 2;
+        }
+        if (self.contains(i + 1, &["ER"]) || self.char_at(i + 1) == 'Y')
+            && !self.contains(0, &["DANGER", "RANGER", "MANGER"])
+            && !self.contains(i - 1, &["E", "I"])
+            && !self.contains(i - 1, &["RGY", "OGY"]) {
+            //"-ger-", "-gy-"
+            self.append_alt("K", "J");
+            return idx + 2;
+        }
+        if self.contains(i + 1, &["E", "I", "Y"]) || self.contains(i - 1, &["AGGI", "OGGI"]) {
+            //Italian, e.g. "Biaggi"
+            if self.contains(0, &["VAN ", "VON ", "SCH"]) || self.contains(i + 1, &["ET"]) {
+                //Obviously Germanic
+                self.append("K");
+            } else if self.contains(i + 1, &["IER"]) {
+                self.append("J");
+            } else {
+                self.append_alt("J", "K");
+            }
+            return idx + 2;
+        }
+
+        self.append("K");
+        self.skip_double(idx, 'G')
+    }
+
+    fn encode_gh(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if idx > 0 && !is_vowel(self.char_at(i - 1)) {
+            self.append("K");
+        } else if idx == 0 {
+            //"Ghislane", "Ghiradelli"
+            if self.char_at(i + 2) == 'I' {
+                self.append("J");
+            } else {
+                self.append("K");
+            }
+        } else if (idx > 1 && self.contains(i - 2, &["B", "H", "D"]))
+            || (idx > 2 && self.contains(i - 3, &["B", "H", "D"]))
+            || (idx > 3 && self.contains(i - 4, &["B", "H"])) {
+            //Parker's rule, e.g. "Hugh"
+        } else if idx > 2 && self.char_at(i - 1) == 'U' && self.contains(i - 3, &["C", "G", "L", "R", "T"]) {
+            //"Laugh", "McLaughlin", "Cough", "Gough", "Rough", "Tough"
+            self.append("F");
+        } else if self.char_at(i - 1) != 'I' {
+            self.append("K");
+        }
+        idx + 2
+    }
+
+    fn encode_h(&mut self, idx : usize) -> usize {
+        //Only kept if first and before a vowel, or between two vowels
+        if (idx == 0 || is_vowel(self.char_at(idx as isize - 1))) && is_vowel(self.char_at(idx as isize + 1)) {
+            self.append("H");
+            idx + 2
+        } else {
+            idx + 1
+        }
+    }
+
+    fn encode_j(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if self.contains(i, &["JOSE"]) || self.contains(0, &["SAN "]) {
+            //Obviously Spanish, e.g. "Jose", "San Jacinto"
+            if (idx == 0 && self.char_at(i + 4) == ' ') || self.value.len() == 4 || self.contains(0, &["SAN "]) {
+                self.append("H");
+            } else {
+                self.append_alt("J", "H");
+            }
+            return idx + 1;
+        }
+
+        if idx == 0 {
+            //"Yankelovich", "Jankelowicz"
+            self.append_alt("J", "A");
+        } else if is_vowel(self.char_at(i - 1)) && !self.slavo_germanic && (self.char_at(i + 1) == 'A' || self.char_at(i + 1) == 'O') {
+            //Spanish pronunciation of e.g. "Bajador"
+            self.append_alt("J", "H");
+        } else if idx + 1 == self.value.len() {
+            self.append_alt("J", "");
+        } else if !self.contains(i + 1, &["L", "T", "K", "S", "N", "M", "B", "Z"]) && !self.contains(i - 1, &["S", "K", "L"]) {
+            self.append("J");
+        }
+        self.skip_double(idx, 'J')
+    }
+
+    fn encode_l(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+        if self.char_at(i + 1) != 'L' {
+            self.append("L");
+            return idx + 1;
+        }
+
+        //Spanish, e.g. "Cabrillo", "Gallegos"
+        let len = self.value.len() as isize;
+        let spanish = (i == len - 3 && self.contains(i - 1, &["ILLO", "ILLA", "ALLE"]))
+            || ((self.contains(len - 2, &["AS", "OS"]) || self.contains(len - 1, &["A", "O"])) && self.contains(i - 1, &["ALLE"]));
+        if spanish {
+            self.append_alt("L", "");
+        } else {
+            self.append("L");
+        }
+        idx + 2
+    }
+
+    fn encode_r(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        //French, e.g. "Rogier", but not "Hochmeier"
+        if idx + 1 == self.value.len() && !self.slavo_germanic && self.contains(i - 2, &["IE"]) && !self.contains(i - 4, &["ME", "MA"]) {
+            self.append_alt("", "R");
+        } else {
+            self.append("R");
+        }
+        self.skip_double(idx, 'R')
+    }
+
+    fn encode_s(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if self.contains(i - 1, &["ISL", "YSL"]) {
+            //"Island", "Isle", "Carlisle", "Carlysle"
+            return idx + 1;
+        }
+        if idx == 0 && self.contains(i, &["SUGAR"]) {
+            self.append_alt("X", "S");
+            return idx + 1;
+        }
+        if self.contains(i, &["SH"]) {
+            if self.contains(i + 1, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+                //Germanic
+                self.append("S");
+            } else {
+                self.append("X");
+            }
+            return idx + 2;
+        }
+        if self.contains(i, &["SIO", "SIA"]) {
+            //Italian and Armenian
+            if self.slavo_germanic {
+                self.append("S");
+            } else {
+                self.append_alt("S", "X");
+            }
+            return idx + 3;
+        }
+        if (idx == 0 && self.contains(i + 1, &["M", "N", "L", "W"])) || self.contains(i + 1, &["Z"]) {
+            //German and anglicisations, e.g. "Smith" matching "Schmidt", and "-sz-" in Slavic languages
+            self.append_alt("S", "X");
+            return if self.contains(i + 1, &["Z"]) { idx + 2 } else { idx + 1 };
+        }
+        if self.contains(i, &["SC"]) {
+            return self.encode_sc(idx);
+        }
+
+        //French, e.g. "Resnais", "Artois"
+        if idx + 1 == self.value.len() && self.contains(i - 2, &["AI", "OI"]) {
+            self.append_alt("", "S");
+        } else {
+            self.append("S");
+        }
+        if self.contains(i + 1, &["S", "Z"]) { idx + 2 } else { idx + 1 }
+    }
+
+    fn encode_sc(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if self.char_at(i + 2) == 'H' {
+            //Schlesinger's rule
+            if self.contains(i + 3, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                //Dutch origin, e.g. "School", "Schooner", "Schermerhorn", "Schenker"
+                if self.contains(i + 3, &["ER", "EN"]) {
+                    self.append_alt("X", "SK");
+                } else {
+                    self.append("SK");
+                }
+            } else if idx == 0 && !is_vowel(self.char_at(3)) && self.char_at(3) != 'W' {
+                self.append_alt("X", "S");
+            } else {
+                self.append("X");
+            }
+        } else if self.contains(i + 2, &["I", "E", "Y"]) {
+            self.append("S");
+        } else {
+            self.append("SK");
+        }
+        idx + 3
+    }
+
+    fn encode_t(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if self.contains(i, &["TION", "TIA", "TCH"]) {
+            self.append("X");
+            return idx + 3;
+        }
+        if self.contains(i, &["TH", "TTH"]) {
+            //"Thomas", "Thames", or Germanic
+            if self.contains(i + 2, &["OM", "AM"]) || self.contains(0, &["VAN ", "VON ", "SCH"]) {
+                self.append("T");
+            } else {
+                self.append_alt("0", "T");
+            }
+            return idx + 2;
+        }
+        self.append("T");
+        if self.contains(i + 1, &["T", "D"]) { idx + 2 } else { idx + 1 }
+    }
+
+    fn encode_w(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if self.contains(i, &["WR"]) {
+            self.append("R");
+            return idx + 2;
+        }
+        if idx == 0 && (is_vowel(self.char_at(i + 1)) || self.contains(i, &["WH"])) {
+            if is_vowel(self.char_at(i + 1)) {
+                //"Wasserman" matching "Vasserman"
+                self.append_alt("A", "F");
+            } else {
+                //"Uomo" matching "Womo"
+                self.append("A");
+            }
+            return idx + 1;
+        }
+        if (idx + 1 == self.value.len() && is_vowel(self.char_at(i - 1)))
+            || self.contains(i - 1, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+            || self.contains(0, &["SCH"]) {
+            //"Arnow" matching "Arnoff"
+            self.append_alt("", "F");
+            return idx + 1;
+        }
+        if self.contains(i, &["WICZ", "WITZ"]) {
+            //Polish, e.g. "Filipowicz"
+            self.append_alt("TS", "FX");
+            return idx + 4;
+        }
+        idx + 1
+    }
+
+    fn encode_x(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if idx == 0 {
+            //"Xavier"
+            self.append("S");
+            return idx + 1;
+        }
+
+        //French, e.g. "Breaux"
+        if !(idx + 1 == self.value.len() && (self.contains(i - 3, &["IAU", "EAU"]) || self.contains(i - 2, &["AU", "OU"]))) {
+            self.append("KS");
+        }
+        if self.contains(i + 1, &["C", "X"]) { idx + 2 } else { idx + 1 }
+    }
+
+    fn encode_z(&mut self, idx : usize) -> usize {
+        let i = idx as isize;
+
+        if self.char_at(i + 1) == 'H' {
+            //Chinese pinyin, e.g. "Zhao"
+            self.append("J");
+            return idx + 2;
+        }
+        if self.contains(i + 1, &["ZO", "ZI", "ZA"]) || (self.slavo_germanic && idx > 0 && self.char_at(i - 1) != 'T') {
+            self.append_alt("S", "TS");
+        } else {
+            self.append("S");
+        }
+        self.skip_double(idx, 'Z')
+    }
+
+    /// Returns the index after the letter at `idx`, skipping the next letter too if it's the same
+    fn skip_double(&self, idx : usize, letter : char) -> usize {
+        if self.char_at(idx as isize + 1) == letter { idx + 2 } else { idx + 1 }
+    }
+
+    /// Returns the char at an index, or '\0' if the index is outside the value
+    fn char_at(&self, idx : isize) -> char {
+        if idx < 0 {
+            return '\0';
+        }
+        self.value.get(idx as usize).copied().unwrap_or('\0')
+    }
+
+    /// Returns `true` if the value has one of the supplied strings at the index.  The strings must all be the same
+    /// length
+    fn contains(&self, idx : isize, strings : &[&str]) -> bool {
+        let len = strings[0].len();
+        if idx < 0 || idx as usize + len > self.value.len() {
+            return false;
+        }
+        let window = &self.value[idx as usize..idx as usize + len];
+        strings.iter().any(|string| string.chars().eq(window.iter().copied()))
+    }
+
+    fn is_complete(&self) -> bool {
+        self.primary.len() >= DOUBLE_METAPHONE_CODE_LEN && self.alternate.len() >= DOUBLE_METAPHONE_CODE_LEN
+    }
+
+    /// Appends the same letters to both codes
+    fn append(&mut self, letters : &str) {
+        self.append_alt(letters, letters);
+    }
+
+    /// Appends letters to the primary and alternate codes, without making either longer than the code length
+    fn append_alt(&mut self, primary : &str, alternate : &str) {
+        for (code, letters) in [(&mut self.primary, primary), (&mut self.alternate, alternate)] {
+            let room = DOUBLE_METAPHONE_CODE_LEN.saturating_sub(code.len());
+            code.extend(letters.chars().take(room));
+        }
+    }
+}
+
+/// Returns `true` for the letters Double Metaphone treats as vowels
+fn is_vowel(the_char : char) -> bool {
+    matches!(the_char, 'A' | 'E' | 'I' | 'O' | 'U' | 'Y')
+}
@@ -0,0 +1,105 @@
+//!
+//! The PinCache module contains the in-memory cache of the entries that belong to pinned records, used
+//! by the database layer.  Nothing should be re-exported
+//!
+
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use super::records::RecordID;
+
+/// The raw entries belonging to the records pinned with [pin](crate::Table::pin), keyed by column family and key
+///
+/// An entry of `None` records that there is no such entry in the database, so reading it doesn't need to reach
+/// the database either.  The cache holds the same bytes as the database, and is updated with the writes as each
+/// batch is committed, so it never needs to be invalidated.
+pub(crate) struct PinCache {
+    inner : RwLock<PinCacheInner>,
+}
+
+/// The cached entries in one column family, keyed by the entry's key, with the record each entry belongs to
+type CFEntries = HashMap<Vec<u8>, (RecordID, Option<Vec<u8>>)>;
+
+/// An entry to cache for a pinned record, as the column family name, the key, and the entry
+pub(crate) type PinnedEntry = (&'static str, Vec<u8>, Option<Vec<u8>>);
+
+#[derive(Default)]
+struct PinCacheInner {
+    record_ids : HashSet<RecordID>,
+    entries : HashMap<&'static str, CFEntries>,
+}
+
+impl PinCache {
+
+    pub fn new() -> Self {
+        Self {
+            inner : RwLock::new(PinCacheInner::default()),
+        }
+    }
+
+    /// Returns `true` if no records are pinned
+    pub fn is_empty(&self) -> bool {
+        self.inner.read().unwrap().record_ids.is_empty()
+    }
+
+    /// Returns `true` if the record is pinned
+    pub fn is_pinned(&self, record_id : RecordID) -> bool {
+        self.inner.read().unwrap().record_ids.contains(&record_id)
+    }
+
+    /// Returns the pinned records
+    pub fn record_ids(&self) -> HashSet<RecordID> {
+        self.inner.read().unwrap().record_ids.clone()
+    }
+
+    /// Returns the cached entry, or `None` if the entry isn't cached
+    pub fn get(&self, cf_name : &'static str, key : &[u8]) -> Option<Option<Vec<u8>>> {
+
+        let inner = self.inner.read().unwrap();
+        if inner.record_ids.is_empty() {
+            return None;
+        }
+        inner.entries.get(cf_name)?.get(key).map(|(_record_id, entry)| entry.clone())
+    }
+
+    /// Pins a record, replacing any entries already cached for it with the supplied entries
+    pub fn pin(&self, record_id : RecordID, entries : Vec<PinnedEntry>) {
+
+        let mut inner = self.inner.write().unwrap();
+        Self::remove_entries(&mut inner, record_id);
+        inner.record_ids.insert(record_id);
+        for (cf_name, key, entry) in entries {
+            inner.entries.entry(cf_name).or_default().insert(key, (record_id, entry));
+        }
+    }
+
+    /// Unpins a record, and discards its cached entries
+    pub fn unpin(&self, record_id : RecordID) {
+
+        let mut inner = self.inner.write().unwrap();
+        Self::remove_entries(&mut inner, record_id);
+        inner.record_ids.remove(&record_id);
+    }
+
+    /// Updates an entry, if it belongs to a pinned record
+    pub fn update(&self, cf_name : &'static str, key : &[u8], record_id : RecordID, entry : Option<Vec<u8>>) {
+
+        let mut inner = self.inner.write().unwrap();
+        if inner.record_ids.contains(&record_id) {
+            inner.entries.entry(cf_name).or_default().insert(key.to_vec(), (record_id, entry));
+        }
+    }
+
+    /// Unpins every record
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.record_ids.clear();
+        inner.entries.clear();
+    }
+
+    fn remove_entries(inner : &mut PinCacheInner, record_id : RecordID) {
+        for cf_entries in inner.entries.values_mut() {
+            cf_entries.retain(|_key, (entry_record_id, _entry)| *entry_record_id != record_id);
+        }
+    }
+}
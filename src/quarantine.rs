@@ -0,0 +1,46 @@
+//!
+//! The Quarantine module contains the format for entries that have been moved out of the table
+//! because they were found to be corrupt.  The QuarantinedEntry struct is re-exported
+//!
+
+/// A raw database entry that couldn't be decoded, and was moved into the "quarantine" column family
+/// rather than being left in place
+///
+/// Entries are only quarantined if the table's [QUARANTINE_CORRUPT_ENTRIES](crate::TableConfig::QUARANTINE_CORRUPT_ENTRIES)
+/// is set.  The raw bytes are preserved exactly as they were found, for debugging purposes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedEntry {
+    /// The name of the column family where the entry was found
+    pub cf_name : String,
+    /// The key of the entry within its original column family
+    pub key : Vec<u8>,
+    /// The raw bytes of the entry
+    pub value : Vec<u8>,
+}
+
+impl QuarantinedEntry {
+
+    /// Composes the key for an entry in the "quarantine" column family, which records the original
+    /// column family name and key, separated by a zero byte
+    pub(crate) fn quarantine_key(cf_name : &str, key : &[u8]) -> Vec<u8> {
+        let mut quarantine_key = Vec::with_capacity(cf_name.len() + 1 + key.len());
+        quarantine_key.extend_from_slice(cf_name.as_bytes());
+        quarantine_key.push(0);
+        quarantine_key.extend_from_slice(key);
+        quarantine_key
+    }
+
+    /// Reconstructs a QuarantinedEntry from an entry in the "quarantine" column family.  Returns `None`
+    /// if the quarantine key wasn't composed by [quarantine_key](Self::quarantine_key)
+    pub(crate) fn from_quarantine_entry(quarantine_key : &[u8], value : &[u8]) -> Option<Self> {
+        let separator_idx = quarantine_key.iter().position(|byte| *byte == 0)?;
+        let (cf_name_bytes, remainder) = quarantine_key.split_at(separator_idx);
+        let cf_name = std::str::from_utf8(cf_name_bytes).ok()?;
+
+        Some(Self {
+            cf_name : cf_name.to_string(),
+            key : remainder[1..].to_vec(),
+            value : value.to_vec(),
+        })
+    }
+}
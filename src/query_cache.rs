@@ -0,0 +1,86 @@
+//!
+//! The QueryCache module contains the bounded cache of fuzzy lookup results used by
+//! [Table::lookup_fuzzy_cached](crate::Table::lookup_fuzzy_cached)
+//!
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+
+use super::records::RecordID;
+
+/// The results of recent fuzzy lookups, keyed by the normalized lookup key and the threshold
+///
+/// Each entry remembers the variants that were probed to produce it, so it can be discarded when a write
+/// changes the keys that reference one of those variants.  The cache is bounded, and the least recently
+/// used entry is evicted to make room for a new one.
+///
+/// NOTE: The entries are searched linearly, on the assumption that the cache is small.
+pub(crate) struct QueryCache<DistanceT> {
+    capacity : usize,
+    entries : Mutex<VecDeque<CachedQuery<DistanceT>>>,
+}
+
+struct CachedQuery<DistanceT> {
+    query : Vec<u8>,
+    threshold : Option<DistanceT>,
+    variants : HashSet<Vec<u8>>,
+    results : Vec<(RecordID, DistanceT)>,
+}
+
+impl <DistanceT : Copy + PartialEq>QueryCache<DistanceT> {
+
+    pub fn new(capacity : usize) -> Self {
+        Self {
+            capacity,
+            entries : Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Returns `true` if the cache can hold any entries
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Returns the cached results for a query, and marks them as the most recently used
+    pub fn get(&self, query : &[u8], threshold : Option<DistanceT>) -> Option<Vec<(RecordID, DistanceT)>> {
+
+        let mut entries = self.entries.lock().unwrap();
+        let idx = entries.iter().position(|entry| entry.query == query && entry.threshold == threshold)?;
+        let entry = entries.remove(idx).unwrap();
+        let results = entry.results.clone();
+        entries.push_back(entry);
+        Some(results)
+    }
+
+    /// Adds the results of a query, evicting the least recently used entry if the cache is full
+    pub fn insert(&self, query : Vec<u8>, threshold : Option<DistanceT>, variants : HashSet<Vec<u8>>, results : Vec<(RecordID, DistanceT)>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| !(entry.query == query && entry.threshold == threshold));
+        entries.push_back(CachedQuery{query, threshold, variants, results});
+        while entries.len() > self.capacity {
+            entries.pop_front();
+        }
+    }
+
+    /// Discards the entries that may be affected by a change to a record's keys, either because they probed one
+    /// of the variants of the keys that changed, or because the record is among their results
+    pub fn invalidate(&self, record_id : RecordID, variants : &HashSet<Vec<u8>>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| {
+            entry.variants.is_disjoint(variants) && !entry.results.iter().any(|(result_id, _)| *result_id == record_id)
+        });
+    }
+
+    /// Discards every entry
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
@@ -0,0 +1,216 @@
+//!
+//! The QueuedTable module contains the [QueuedTable] object, which applies the writes to a [Table] on a
+//! dedicated writer thread.  The QueuedTable and WriteHandle structs are re-exported
+//!
+
+use std::sync::{Arc, RwLock, mpsc};
+use std::thread::JoinHandle;
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::RecordID;
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+
+/// The most queued writes the writer thread applies under a single acquisition of the Table's lock, so a
+/// steady stream of writes can't shut out the readers
+const MAX_WRITES_PER_LOCK : usize = 64;
+
+/// A write waiting in a [QueuedTable]'s queue, called with the Table, or the error from locking it
+type WriteJob<ConfigT, const UTF8_KEYS : bool> = Box<dyn FnOnce(Result<&mut Table<ConfigT, UTF8_KEYS>, String>) + Send>;
+
+/// A wrapper around a [Table] that queues writes to be applied by a dedicated writer thread, so the caller
+/// isn't blocked while RocksDB writes the record, e.g. during a write stall caused by a compaction
+///
+/// Each queued write returns a [WriteHandle] right away, which can be used to wait for the write's result.  The
+/// writes are applied in the order they were queued, and a lookup made after a write's handle has returned its
+/// result will see the write.  Lookups are made against the Table returned by [table](QueuedTable::table), and may
+/// run concurrently with each other, while the writer thread holds the lock to apply the writes.
+///
+/// Unlike [Table], the keys and values passed to a QueuedTable are owned, because they must be moved onto the
+/// writer thread.  When the QueuedTable is dropped, the writes that are still queued are applied first.
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let mut table = Table::<DefaultTableConfig, true>::new("queued_example.rocks", DefaultTableConfig()).unwrap();
+/// table.reset().unwrap();
+/// let queued = QueuedTable::new(table);
+///
+/// let mon = queued.insert("Monday", "Getsuyoubi".to_string());
+/// let tue = queued.insert("Tuesday", "Kayoubi".to_string());
+/// let mon = mon.wait().unwrap();
+/// queued.delete(tue.wait().unwrap()).wait().unwrap();
+///
+/// let table = queued.table().read().unwrap();
+/// assert_eq!(table.lookup_best("Mnday").unwrap().collect::<Vec<RecordID>>(), vec![mon]);
+/// assert_eq!(table.lookup_exact("Tuesday").unwrap().count(), 0);
+/// ```
+pub struct QueuedTable<ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    table : Arc<RwLock<Table<ConfigT, UTF8_KEYS>>>,
+    sender : Option<mpsc::Sender<WriteJob<ConfigT, UTF8_KEYS>>>,
+    writer : Option<JoinHandle<()>>,
+}
+
+/// A handle to a write queued in a [QueuedTable], to wait for the write's result
+pub struct WriteHandle<T> {
+    receiver : mpsc::Receiver<Result<T, String>>,
+    result : Option<Result<T, String>>,
+}
+
+impl <T>WriteHandle<T> {
+
+    /// Blocks until the write has been applied, and returns its result
+    pub fn wait(self) -> Result<T, String> {
+        match self.result {
+            Some(result) => result,
+            None => self.receiver.recv().unwrap_or_else(|_| Err("the writer thread stopped before the write was applied".to_string()))
+        }
+    }
+
+    /// Returns the write's result if it has been applied, or `None` if it's still queued
+    pub fn try_wait(&mut self) -> Option<&Result<T, String>> {
+        if self.result.is_none() {
+            self.result = match self.receiver.try_recv() {
+                Ok(result) => Some(result),
+                Err(mpsc::TryRecvError::Empty) => None,
+                Err(mpsc::TryRecvError::Disconnected) => Some(Err("the writer thread stopped before the write was applied".to_string()))
+            };
+        }
+        self.result.as_ref()
+    }
+}
+
+impl <ConfigT : TableConfig, const UTF8_KEYS : bool>Drop for QueuedTable<ConfigT, UTF8_KEYS> {
+    fn drop(&mut self) {
+        //The writer thread stops once the queue is empty and the sender is gone
+        drop(self.sender.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>QueuedTable<ConfigT, UTF8_KEYS>
+    where
+    ConfigT : 'static + Send + Sync,
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8> + Send,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned + Send,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT> + Send,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Creates a new QueuedTable, serving the supplied Table, and starts its writer thread
+    pub fn new(table : Table<ConfigT, UTF8_KEYS>) -> Self {
+        let table = Arc::new(RwLock::new(table));
+        let (sender, receiver) = mpsc::channel();
+        let writer_table = table.clone();
+        let writer = std::thread::spawn(move || run_writer(writer_table, receiver));
+        Self {
+            table,
+            sender : Some(sender),
+            writer : Some(writer),
+        }
+    }
+
+    /// Returns the underlying Table, for lookups and for operations that aren't available on the QueuedTable
+    ///
+    /// WARNING: Writing to the Table directly bypasses the queue, so the write may be applied before writes
+    /// that were queued earlier.
+    pub fn table(&self) -> &Arc<RwLock<Table<ConfigT, UTF8_KEYS>>> {
+        &self.table
+    }
+
+    /// Queues a new key-value pair to be inserted into the table.  The handle returns the RecordID of the new record
+    ///
+    /// Functions the same as [Table::insert]
+    pub fn insert<K : Into<OwnedKeyT>>(&self, key : K, value : ConfigT::ValueT) -> WriteHandle<RecordID> {
+        let key = key.into();
+        self.enqueue(move |table| table.insert_internal([&key].iter().copied(), 1, &value))
+    }
+
+    /// Queues a new record to be created in the table.  The handle returns the RecordID of the new record
+    ///
+    /// Functions the same as [Table::create]
+    pub fn create(&self, keys : Vec<OwnedKeyT>, value : ConfigT::ValueT) -> WriteHandle<RecordID> {
+        self.enqueue(move |table| table.insert_internal(keys.iter(), keys.len(), &value))
+    }
+
+    /// Queues a record to be deleted from the Table
+    ///
+    /// Functions the same as [Table::delete]
+    pub fn delete(&self, record_id : RecordID) -> WriteHandle<()> {
+        self.enqueue(move |table| table.delete(record_id))
+    }
+
+    /// Queues a record's value to be replaced with the supplied value.  The handle returns the value that was replaced
+    ///
+    /// Functions the same as [Table::replace_value]
+    pub fn replace_value(&self, record_id : RecordID, value : ConfigT::ValueT) -> WriteHandle<ConfigT::ValueT> {
+        self.enqueue(move |table| table.replace_value(record_id, &value))
+    }
+
+    /// Blocks until every write queued so far has been applied
+    pub fn flush(&self) -> Result<(), String> {
+        self.enqueue(|_table| Ok(())).wait()
+    }
+
+    /// Applies every queued write, stops the writer thread, and returns the Table
+    ///
+    /// Returns an error if the Table is still in use, e.g. by another clone of the [Arc] returned by
+    /// [table](Self::table).
+    pub fn into_table(mut self) -> Result<Table<ConfigT, UTF8_KEYS>, String> {
+        drop(self.sender.take());
+        if let Some(writer) = self.writer.take() {
+            writer.join().map_err(|_| "the writer thread panicked".to_string())?;
+        }
+        let table = self.table.clone();
+        drop(self);
+        let table = Arc::try_unwrap(table).map_err(|_| "the Table is still in use".to_string())?;
+        table.into_inner().map_err(|err| err.to_string())
+    }
+
+    /// Adds a write to the back of the queue, and returns the handle to its result
+    fn enqueue<R, F>(&self, write : F) -> WriteHandle<R>
+        where
+        R : 'static + Send,
+        F : 'static + Send + FnOnce(&mut Table<ConfigT, UTF8_KEYS>) -> Result<R, String>,
+    {
+        let (result_sender, receiver) = mpsc::channel();
+        let job : WriteJob<ConfigT, UTF8_KEYS> = Box::new(move |table| {
+            let _ = result_sender.send(table.and_then(write));
+        });
+
+        //If the writer thread has stopped, the job is dropped along with its result sender, so the handle
+        // reports the error
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(job);
+        }
+        WriteHandle{receiver, result : None}
+    }
+}
+
+/// The body of a [QueuedTable]'s writer thread, which applies the queued writes in order until the QueuedTable
+/// is dropped
+fn run_writer<ConfigT : TableConfig, const UTF8_KEYS : bool>(table : Arc<RwLock<Table<ConfigT, UTF8_KEYS>>>, receiver : mpsc::Receiver<WriteJob<ConfigT, UTF8_KEYS>>) {
+
+    //The writes that were queued while one was being applied are applied together, without releasing the lock
+    while let Ok(job) = receiver.recv() {
+        let mut guard = table.write().map_err(|err| err.to_string());
+        let mut next_job = Some(job);
+        let mut applied = 0;
+        while let Some(job) = next_job.take() {
+            match &mut guard {
+                Ok(guard) => job(Ok(&mut **guard)),
+                Err(err) => job(Err(err.clone()))
+            }
+            applied += 1;
+            if applied < MAX_WRITES_PER_LOCK {
+                next_job = receiver.try_recv().ok();
+            }
+        }
+    }
+}
@@ -0,0 +1,540 @@
+//!
+//! The ReadOnlyTable module contains the [ReadOnlyTable] object, returned by [Table::open_read_only],
+//! [Table::open_read_only_column_families], and [Table::open_as_secondary].
+//! The ReadOnlyTable struct is re-exported
+//!
+
+use std::io::Write;
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::RecordID;
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+use super::perf_counters::PerfCounterFields;
+use super::quarantine::QuarantinedEntry;
+use super::fuzzy_lookup::{*};
+use super::cursor::LookupCursor;
+use super::key_group_handle::KeyGroupHandle;
+use super::verify::{*};
+use super::did_you_mean::PhraseCorrection;
+use super::compound::CompoundMatch;
+use super::export::RecordExport;
+use super::merge_diagnostics::MergeDiagnostics;
+use super::db_statistics::DBStatistics;
+use super::disk_usage::DiskUsage;
+use super::db_properties::DBProperties;
+use super::record_metadata::RecordMetadata;
+
+/// A [Table] opened with [Table::open_read_only], [Table::open_read_only_column_families], or [Table::open_as_secondary],
+/// which can be queried but not modified
+///
+/// A ReadOnlyTable provides the Table's lookup and retrieval methods, which function the same as they do on the
+/// Table.  The methods that write to the database, including those that only take `&Table`, such as
+/// [compact_all](Table::compact_all) or [flush_access_stats](Table::flush_access_stats), aren't provided, so an
+/// attempt to modify a ReadOnlyTable is caught at compile time.
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let mut table = Table::<DefaultTableConfig, true>::new("read_only_example.rocks", DefaultTableConfig()).unwrap();
+/// table.reset().unwrap();
+/// table.insert("Hello", &"Greeting".to_string()).unwrap();
+/// drop(table);
+///
+/// let table = Table::<DefaultTableConfig, true>::open_read_only("read_only_example.rocks", DefaultTableConfig()).unwrap();
+/// let record_id = table.lookup_exact("Hello").unwrap().next().unwrap();
+/// assert_eq!(table.get_value(record_id).unwrap(), "Greeting");
+/// ```
+///
+/// ```compile_fail
+/// use fuzzy_rocks::{*};
+///
+/// let table = Table::<DefaultTableConfig, true>::open_read_only("read_only_example.rocks", DefaultTableConfig()).unwrap();
+/// table.insert("Hello", &"Greeting".to_string()).unwrap();
+/// ```
+///
+/// ```compile_fail
+/// use fuzzy_rocks::{*};
+///
+/// let table = Table::<DefaultTableConfig, true>::open_read_only("read_only_example.rocks", DefaultTableConfig()).unwrap();
+/// table.compact_all().unwrap();
+/// ```
+pub struct ReadOnlyTable<ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    table : Table<ConfigT, UTF8_KEYS>,
+}
+
+impl <ConfigT : TableConfig, const UTF8_KEYS : bool>ReadOnlyTable<ConfigT, UTF8_KEYS> {
+
+    pub(crate) fn new(table : Table<ConfigT, UTF8_KEYS>) -> Self {
+        Self {
+            table
+        }
+    }
+
+    /// Brings the Table up to date with the writes made by the primary instance since the Table was opened,
+    /// or since the last call to this function
+    /// 
+    /// Returns an error unless the Table was opened with [Table::open_as_secondary].  RocksDB doesn't pin
+    /// snapshots on a secondary instance, so this borrows the Table mutably to ensure that no lookup or
+    /// [export](Table::export_stream) is in progress while the Table catches up.
+    pub fn catch_up_with_primary(&mut self) -> Result<(), String> {
+        self.table.catch_up_with_primary()
+    }
+
+    /// Functions the same as [Table::path]
+    pub fn path(&self) -> &str {
+        self.table.path()
+    }
+
+    /// Functions the same as [Table::params]
+    pub fn params(&self) -> &TableParams {
+        self.table.params()
+    }
+
+    /// Functions the same as [Table::record_hits]
+    pub fn record_hits(&self, record_id : RecordID) -> Result<u64, String> {
+        self.table.record_hits(record_id)
+    }
+
+    /// Functions the same as [Table::top_records]
+    pub fn top_records(&self, n : usize) -> Result<Vec<(RecordID, u64)>, String> {
+        self.table.top_records(n)
+    }
+
+    /// Functions the same as [Table::pinned_records]
+    pub fn pinned_records(&self) -> Vec<RecordID> {
+        self.table.pinned_records()
+    }
+
+    /// Functions the same as [Table::get_metadata]
+    pub fn get_metadata(&self, record_id : RecordID) -> Result<Option<RecordMetadata>, String> {
+        self.table.get_metadata(record_id)
+    }
+
+    /// Functions the same as [Table::records_modified_since]
+    pub fn records_modified_since(&self, since : u64) -> Result<Vec<RecordID>, String> {
+        self.table.records_modified_since(since)
+    }
+
+    /// Functions the same as [Table::synonyms]
+    pub fn synonyms(&self, term : &str) -> Result<Vec<String>, String> {
+        self.table.synonyms(term)
+    }
+
+    /// Functions the same as [Table::merge_diagnostics]
+    pub fn merge_diagnostics(&self) -> MergeDiagnostics {
+        self.table.merge_diagnostics()
+    }
+}
+
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>ReadOnlyTable<ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Functions the same as [Table::export_stream]
+    pub fn export_stream(&self) -> RecordExport<'_, OwnedKeyT, ConfigT::ValueT> {
+        self.table.export_stream()
+    }
+
+    /// Functions the same as [Table::preload]
+    pub fn preload(&self) -> Result<usize, String> {
+        self.table.preload()
+    }
+
+    /// Functions the same as [Table::quarantined_entries]
+    pub fn quarantined_entries(&self) -> Result<Vec<QuarantinedEntry>, String> {
+        self.table.quarantined_entries()
+    }
+
+    /// Functions the same as [Table::verify]
+    pub fn verify(&self) -> Result<VerifyReport, String> {
+        self.table.verify()
+    }
+
+    /// Functions the same as [Table::check_integrity]
+    pub fn check_integrity(&self) -> Result<IntegrityReport, String> {
+        self.table.check_integrity()
+    }
+
+    /// Functions the same as [Table::disk_usage]
+    pub fn disk_usage(&self) -> Result<DiskUsage, String> {
+        self.table.disk_usage()
+    }
+
+    /// Functions the same as [Table::db_properties]
+    pub fn db_properties(&self) -> Result<DBProperties, String> {
+        self.table.db_properties()
+    }
+
+    /// Functions the same as [Table::db_statistics]
+    pub fn db_statistics(&self) -> Result<DBStatistics, String> {
+        self.table.db_statistics()
+    }
+
+    /// Functions the same as [Table::is_soft_deleted]
+    pub fn is_soft_deleted(&self, record_id : RecordID) -> bool {
+        self.table.is_soft_deleted(record_id)
+    }
+
+    /// Functions the same as [Table::key_groups]
+    pub fn key_groups(&self, record_id : RecordID) -> Result<Vec<KeyGroupHandle>, String> {
+        self.table.key_groups(record_id)
+    }
+
+    /// Functions the same as [Table::key_group_keys]
+    pub fn key_group_keys(&self, key_group : KeyGroupHandle) -> Result<Vec<OwnedKeyT>, String> {
+        self.table.key_group_keys(key_group)
+    }
+
+    /// Functions the same as [Table::get_value]
+    pub fn get_value(&self, record_id : RecordID) -> Result<ConfigT::ValueT, String> {
+        self.table.get_value(record_id)
+    }
+
+    /// Functions the same as [Table::get_value_to_writer]
+    pub fn get_value_to_writer<W : Write>(&self, record_id : RecordID, writer : &mut W) -> Result<u64, String> {
+        self.table.get_value_to_writer(record_id, writer)
+    }
+
+    /// Functions the same as [Table::get_values]
+    pub fn get_values(&self, record_ids : &[RecordID]) -> Result<Vec<ConfigT::ValueT>, String> {
+        self.table.get_values(record_ids)
+    }
+
+    /// Functions the same as [Table::keys_count]
+    pub fn keys_count(&self, record_id : RecordID) -> Result<usize, String> {
+        self.table.keys_count(record_id)
+    }
+
+    /// Functions the same as [Table::reset_perf_counters]
+    pub fn reset_perf_counters(&self) {
+        self.table.reset_perf_counters()
+    }
+
+    /// Functions the same as [Table::get_perf_counters]
+    pub fn get_perf_counters(&self) -> PerfCounterFields {
+        self.table.get_perf_counters()
+    }
+}
+
+impl <ConfigT : TableConfig<KeyCharT = char>>ReadOnlyTable<ConfigT, true> {
+
+    /// Functions the same as [Table::get]
+    pub fn get(&self, record_id : RecordID) -> Result<(String, ConfigT::ValueT), String> {
+        self.table.get(record_id)
+    }
+
+    /// Functions the same as [Table::get_keys]
+    pub fn get_keys(&self, record_id : RecordID) -> Result<impl Iterator<Item=String> + '_, String> {
+        self.table.get_keys(record_id)
+    }
+
+    /// Functions the same as [Table::get_one_key]
+    pub fn get_one_key(&self, record_id : RecordID) -> Result<String, String> {
+        self.table.get_one_key(record_id)
+    }
+
+    /// Functions the same as [Table::lookup_exact]
+    pub fn lookup_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
+        self.table.lookup_exact(key)
+    }
+
+    /// Functions the same as [Table::lookup_phonetic]
+    pub fn lookup_phonetic<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
+        self.table.lookup_phonetic(key)
+    }
+
+    /// Functions the same as [Table::lookup_phrase_fuzzy]
+    pub fn lookup_phrase_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, phrase : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.table.lookup_phrase_fuzzy(phrase, threshold)
+    }
+
+    /// Functions the same as [Table::contains_key_exact]
+    pub fn contains_key_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<bool, String> {
+        self.table.contains_key_exact(key)
+    }
+
+    /// Functions the same as [Table::contains_key_fuzzy]
+    pub fn contains_key_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<bool, String> {
+        self.table.contains_key_fuzzy(key)
+    }
+
+    /// Functions the same as [Table::count_exact]
+    pub fn count_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<usize, String> {
+        self.table.count_exact(key)
+    }
+
+    /// Functions the same as [Table::count_fuzzy]
+    pub fn count_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<usize, String> {
+        self.table.count_fuzzy(key)
+    }
+
+    /// Functions the same as [Table::lookup_compound]
+    pub fn lookup_compound<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, max_splits : usize) -> Result<Vec<CompoundMatch<String>>, String> {
+        self.table.lookup_compound(key, max_splits)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_raw]
+    pub fn lookup_fuzzy_raw<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<FuzzyRawLookup<'_>, String> {
+        self.table.lookup_fuzzy_raw(key)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_raw_within]
+    pub fn lookup_fuzzy_raw_within<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, max_deletes : usize) -> Result<FuzzyRawLookup<'_>, String> {
+        self.table.lookup_fuzzy_raw_within(key, max_deletes)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy]
+    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_scored]
+    pub fn lookup_fuzzy_scored<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyScoredLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_scored(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_within]
+    pub fn lookup_fuzzy_within<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_within(key, threshold, max_distance)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_pruned]
+    pub fn lookup_fuzzy_pruned<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_edits : usize) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_pruned(key, threshold, max_edits)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_extended]
+    pub fn lookup_fuzzy_extended<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_extended(key, threshold, max_distance)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_cached]
+    pub fn lookup_fuzzy_cached<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.table.lookup_fuzzy_cached(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_parallel]
+    pub fn lookup_fuzzy_parallel<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String>
+        where
+        ConfigT::DistanceT : Send + Sync,
+    {
+        self.table.lookup_fuzzy_parallel(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_page]
+    pub fn lookup_fuzzy_page<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, page_size : usize, cursor : Option<LookupCursor>) -> Result<FuzzyResultsPage<ConfigT>, String>
+        where
+        ConfigT::DistanceT : Serialize,
+    {
+        self.table.lookup_fuzzy_page(key, threshold, page_size, cursor)
+    }
+
+    /// Functions the same as [Table::lookup_prefix_fuzzy]
+    pub fn lookup_prefix_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, prefix : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.table.lookup_prefix_fuzzy(prefix, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_relative]
+    pub fn lookup_fuzzy_relative<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, min_similarity : f32) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_relative(key, min_similarity)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_adaptive]
+    pub fn lookup_fuzzy_adaptive<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, min_results : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.table.lookup_fuzzy_adaptive(key, threshold, min_results)
+    }
+
+    /// Functions the same as [Table::lookup_best]
+    pub fn lookup_best<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
+        self.table.lookup_best(key)
+    }
+
+    /// Functions the same as [Table::lookup_best_n]
+    pub fn lookup_best_n<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, n : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.table.lookup_best_n(key, n)
+    }
+
+    /// Functions the same as [Table::lookup_exact_with_values]
+    pub fn lookup_exact_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.table.lookup_exact_with_values(key)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_raw_with_values]
+    pub fn lookup_fuzzy_raw_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.table.lookup_fuzzy_raw_with_values(key)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_with_values]
+    pub fn lookup_fuzzy_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyResultsWithValues<ConfigT>, String> {
+        self.table.lookup_fuzzy_with_values(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_best_with_values]
+    pub fn lookup_best_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.table.lookup_best_with_values(key)
+    }
+
+    /// Functions the same as [Table::did_you_mean]
+    pub fn did_you_mean(&self, phrase : &str, max_suggestions : usize) -> Result<Vec<PhraseCorrection<ConfigT::DistanceT>>, String> {
+        self.table.did_you_mean(phrase, max_suggestions)
+    }
+}
+
+impl <ConfigT : TableConfig>ReadOnlyTable<ConfigT, false> {
+
+    /// Functions the same as [Table::get]
+    pub fn get(&self, record_id : RecordID) -> Result<(Vec<ConfigT::KeyCharT>, ConfigT::ValueT), String> {
+        self.table.get(record_id)
+    }
+
+    /// Functions the same as [Table::get_keys]
+    pub fn get_keys(&self, record_id : RecordID) -> Result<impl Iterator<Item=Vec<ConfigT::KeyCharT>> + '_, String> {
+        self.table.get_keys(record_id)
+    }
+
+    /// Functions the same as [Table::get_one_key]
+    pub fn get_one_key(&self, record_id : RecordID) -> Result<Vec<ConfigT::KeyCharT>, String> {
+        self.table.get_one_key(record_id)
+    }
+
+    /// Functions the same as [Table::lookup_exact]
+    pub fn lookup_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
+        self.table.lookup_exact(key)
+    }
+
+    /// Functions the same as [Table::contains_key_exact]
+    pub fn contains_key_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<bool, String> {
+        self.table.contains_key_exact(key)
+    }
+
+    /// Functions the same as [Table::contains_key_fuzzy]
+    pub fn contains_key_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<bool, String> {
+        self.table.contains_key_fuzzy(key)
+    }
+
+    /// Functions the same as [Table::count_exact]
+    pub fn count_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<usize, String> {
+        self.table.count_exact(key)
+    }
+
+    /// Functions the same as [Table::count_fuzzy]
+    pub fn count_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<usize, String> {
+        self.table.count_fuzzy(key)
+    }
+
+    /// Functions the same as [Table::lookup_compound]
+    pub fn lookup_compound<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, max_splits : usize) -> Result<Vec<CompoundMatch<Vec<ConfigT::KeyCharT>>>, String> {
+        self.table.lookup_compound(key, max_splits)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_raw]
+    pub fn lookup_fuzzy_raw<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<FuzzyRawLookup<'_>, String> {
+        self.table.lookup_fuzzy_raw(key)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_raw_within]
+    pub fn lookup_fuzzy_raw_within<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, max_deletes : usize) -> Result<FuzzyRawLookup<'_>, String> {
+        self.table.lookup_fuzzy_raw_within(key, max_deletes)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy]
+    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_scored]
+    pub fn lookup_fuzzy_scored<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyScoredLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_scored(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_within]
+    pub fn lookup_fuzzy_within<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_within(key, threshold, max_distance)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_pruned]
+    pub fn lookup_fuzzy_pruned<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_edits : usize) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_pruned(key, threshold, max_edits)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_extended]
+    pub fn lookup_fuzzy_extended<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_extended(key, threshold, max_distance)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_cached]
+    pub fn lookup_fuzzy_cached<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.table.lookup_fuzzy_cached(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_parallel]
+    pub fn lookup_fuzzy_parallel<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String>
+        where
+        ConfigT::KeyCharT : Send + Sync,
+        ConfigT::DistanceT : Send + Sync,
+    {
+        self.table.lookup_fuzzy_parallel(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_page]
+    pub fn lookup_fuzzy_page<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, page_size : usize, cursor : Option<LookupCursor>) -> Result<FuzzyResultsPage<ConfigT>, String>
+        where
+        ConfigT::DistanceT : Serialize,
+    {
+        self.table.lookup_fuzzy_page(key, threshold, page_size, cursor)
+    }
+
+    /// Functions the same as [Table::lookup_prefix_fuzzy]
+    pub fn lookup_prefix_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, prefix : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.table.lookup_prefix_fuzzy(prefix, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_relative]
+    pub fn lookup_fuzzy_relative<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, min_similarity : f32) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.table.lookup_fuzzy_relative(key, min_similarity)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_adaptive]
+    pub fn lookup_fuzzy_adaptive<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, min_results : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.table.lookup_fuzzy_adaptive(key, threshold, min_results)
+    }
+
+    /// Functions the same as [Table::lookup_best]
+    pub fn lookup_best<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
+        self.table.lookup_best(key)
+    }
+
+    /// Functions the same as [Table::lookup_best_n]
+    pub fn lookup_best_n<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, n : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.table.lookup_best_n(key, n)
+    }
+
+    /// Functions the same as [Table::lookup_exact_with_values]
+    pub fn lookup_exact_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.table.lookup_exact_with_values(key)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_raw_with_values]
+    pub fn lookup_fuzzy_raw_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.table.lookup_fuzzy_raw_with_values(key)
+    }
+
+    /// Functions the same as [Table::lookup_fuzzy_with_values]
+    pub fn lookup_fuzzy_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyResultsWithValues<ConfigT>, String> {
+        self.table.lookup_fuzzy_with_values(key, threshold)
+    }
+
+    /// Functions the same as [Table::lookup_best_with_values]
+    pub fn lookup_best_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.table.lookup_best_with_values(key)
+    }
+}
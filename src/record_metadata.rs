@@ -0,0 +1,41 @@
+//!
+//! The RecordMetadata module contains the [RecordMetadata] struct, kept for each record in the "record_meta"
+//! column family.  The RecordMetadata struct is re-exported
+//!
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+/// The metadata kept for a record when [RECORD_METADATA](crate::TableConfig::RECORD_METADATA) is set, returned
+/// by [get_metadata](crate::Table::get_metadata)
+///
+/// The times are in milliseconds since the Unix epoch, taken from the system clock when the change is composed,
+/// so they are only as reliable as the clock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordMetadata {
+    /// When the record was created
+    pub created : u64,
+    /// When the record's keys or value were last changed, or when it was created if they haven't been
+    pub modified : u64,
+    /// A tag set with [set_tag](crate::Table::set_tag).  Setting the tag doesn't change the `modified` time
+    pub tag : Option<String>,
+}
+
+impl RecordMetadata {
+
+    /// Returns the metadata for a record created now
+    pub(crate) fn new_now() -> Self {
+        let now = now_millis();
+        Self {
+            created : now,
+            modified : now,
+            tag : None,
+        }
+    }
+}
+
+/// Returns the current time, in milliseconds since the Unix epoch
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis() as u64)
+}
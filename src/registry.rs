@@ -0,0 +1,179 @@
+//!
+//! The Registry module contains a process-wide registry of named [Table]s, so the parts of an application
+//! can share a Table without passing its handle through every layer
+//!
+//! A Table is registered once, usually at startup, with [register], and it is opened the first time any
+//! part of the application calls [open] with its name.  Every subsequent call to `open` returns the same
+//! [TableHandle], so the Table's database is only ever opened once.  Registering two names for the same path
+//! is an error.
+//!
+//! ```
+//! use fuzzy_rocks::{*};
+//!
+//! registry::register::<DefaultTableConfig, true>("registry_example", "registry_example.rocks", DefaultTableConfig()).unwrap();
+//!
+//! //Somewhere else in the application
+//! let handle = registry::open::<DefaultTableConfig, true>("registry_example").unwrap();
+//! let table = handle.current();
+//! assert_eq!(table.path(), "registry_example.rocks");
+//!
+//! //Every call returns the same handle
+//! assert!(std::sync::Arc::ptr_eq(&handle, &registry::open::<DefaultTableConfig, true>("registry_example").unwrap()));
+//!
+//! registry::close("registry_example").unwrap();
+//! ```
+//!
+
+use core::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+use super::table_handle::{*};
+
+/// A function that is called when a registered [Table] is opened or closed, with the name and path
+/// the Table was registered with.  See [on_open] and [on_close]
+pub type RegistryHook = fn(name : &str, path : &str);
+
+/// A type-erased `Arc<TableHandle<ConfigT, UTF8_KEYS>>`
+type AnyHandle = Arc<dyn Any + Send + Sync>;
+
+/// Opens the Table for a registered name
+type Opener = Box<dyn FnOnce() -> Result<AnyHandle, String> + Send>;
+
+enum EntryState {
+    Unopened(Opener),
+    Open(AnyHandle),
+}
+
+struct Entry {
+    path : String,
+    state : EntryState,
+}
+
+#[derive(Default)]
+struct Registry {
+    entries : HashMap<String, Entry>,
+    open_hooks : Vec<RegistryHook>,
+    close_hooks : Vec<RegistryHook>,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY : OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+/// Registers a [Table] under `name`, to be opened from `path` with `config` the first time [open] is called
+/// with that name
+///
+/// Returns an error if the name is already registered, or if another name is registered with the same path.
+pub fn register<ConfigT, const UTF8_KEYS : bool>(name : &str, path : &str, config : ConfigT) -> Result<(), String>
+    where
+    ConfigT : TableConfig + 'static + Send + Sync,
+    ConfigT::DistanceT : Send,
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding,
+    <Table<ConfigT, UTF8_KEYS> as TableKeyEncoding>::OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+{
+    let mut registry = registry().lock().unwrap();
+
+    if registry.entries.contains_key(name) {
+        return Err(format!("a table named \"{}\" is already registered", name));
+    }
+    if let Some((other_name, _)) = registry.entries.iter().find(|(_, entry)| entry.path == path) {
+        return Err(format!("the path \"{}\" is already registered as \"{}\"", path, other_name));
+    }
+
+    let table_path = path.to_string();
+    let opener : Opener = Box::new(move || {
+        let table = Table::<ConfigT, UTF8_KEYS>::new(&table_path, config)?;
+        Ok(Arc::new(TableHandle::new(table)) as AnyHandle)
+    });
+    registry.entries.insert(name.to_string(), Entry{path : path.to_string(), state : EntryState::Unopened(opener)});
+
+    Ok(())
+}
+
+/// Returns the [TableHandle] for the Table registered under `name`, opening the Table if this is the first
+/// call for that name
+///
+/// The `ConfigT` and `UTF8_KEYS` parameters must match the ones the Table was registered with.  Returns an
+/// error if the name isn't registered, or if the parameters don't match.  If the Table can't be opened, the
+/// error is returned and the name is unregistered.
+pub fn open<ConfigT, const UTF8_KEYS : bool>(name : &str) -> Result<Arc<TableHandle<ConfigT, UTF8_KEYS>>, String>
+    where
+    ConfigT : TableConfig + 'static + Send + Sync,
+    ConfigT::DistanceT : Send,
+{
+    //NOTE: The registry stays locked while the Table is opened, so two threads can't open it at once
+    let mut registry = registry().lock().unwrap();
+    let entry = registry.entries.remove(name).ok_or_else(|| format!("no table named \"{}\" is registered", name))?;
+    let (handle, newly_opened) = match entry.state {
+        EntryState::Open(handle) => (handle, false),
+        EntryState::Unopened(opener) => (opener()?, true),
+    };
+    registry.entries.insert(name.to_string(), Entry{path : entry.path.clone(), state : EntryState::Open(handle.clone())});
+
+    if newly_opened {
+        let open_hooks = registry.open_hooks.clone();
+        drop(registry);
+        for hook in open_hooks {
+            hook(name, &entry.path);
+        }
+    }
+
+    downcast_handle(name, handle)
+}
+
+/// Removes the Table registered under `name` from the registry
+///
+/// Handles that have already been returned by [open] remain valid, and the Table is closed when the last
+/// of them is dropped.  A subsequent call to `open` with the name will fail unless it is registered again.
+pub fn close(name : &str) -> Result<(), String> {
+
+    let (entry, close_hooks) = {
+        let mut registry = registry().lock().unwrap();
+        let entry = registry.entries.remove(name).ok_or_else(|| format!("no table named \"{}\" is registered", name))?;
+        (entry, registry.close_hooks.clone())
+    };
+
+    //Only Tables that were opened are reported as closed
+    if let EntryState::Open(_) = entry.state {
+        for hook in close_hooks {
+            hook(name, &entry.path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the names of all registered Tables, whether they have been opened or not
+pub fn registered_names() -> Vec<String> {
+    registry().lock().unwrap().entries.keys().cloned().collect()
+}
+
+/// Adds a hook that will be called each time a registered Table is opened
+pub fn on_open(hook : RegistryHook) {
+    registry().lock().unwrap().open_hooks.push(hook);
+}
+
+/// Adds a hook that will be called each time an opened Table is removed from the registry by [close]
+pub fn on_close(hook : RegistryHook) {
+    registry().lock().unwrap().close_hooks.push(hook);
+}
+
+fn downcast_handle<ConfigT, const UTF8_KEYS : bool>(name : &str, handle : AnyHandle) -> Result<Arc<TableHandle<ConfigT, UTF8_KEYS>>, String>
+    where
+    ConfigT : TableConfig + 'static + Send + Sync,
+    ConfigT::DistanceT : Send,
+{
+    handle.downcast::<TableHandle<ConfigT, UTF8_KEYS>>()
+        .map_err(|_| format!("the table named \"{}\" was registered with a different TableConfig", name))
+}
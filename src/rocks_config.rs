@@ -0,0 +1,99 @@
+//!
+//! The RocksConfig module contains the [RocksConfig] struct, which passes tuning options through to RocksDB.
+//! The RocksConfig struct is re-exported
+//!
+
+/// Tuning options passed through to RocksDB when a Table's database is opened, set with
+/// [ROCKS_CONFIG](crate::TableConfig::ROCKS_CONFIG)
+///
+/// Each size or count left at 0 keeps RocksDB's own default.  The defaults suit a general-purpose key-value store,
+/// but a Table mostly makes point lookups of variants, most of which miss, so a large Table usually benefits
+/// from a bigger block cache, and from bigger files for the "variants" CF.  The column families are described
+/// in the crate documentation.
+///
+/// The exception is the variant CFs, "variants" and "variant_pages", which have bloom filters by default.  Without
+/// a filter, every probe for a variant that isn't in the Table reads an index block and a data block from each
+/// level; with one, almost all of those probes are answered from the filter.  Filters are only built as files are
+/// written, so changing the filter settings of an existing Table takes effect as it's compacted, e.g. with
+/// [compact_all](crate::Table::compact_all).
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// struct Config();
+/// impl TableConfig for Config {
+///     type KeyCharT = char;
+///     type DistanceT = u8;
+///     type ValueT = String;
+///     const ROCKS_CONFIG : RocksConfig = RocksConfig {
+///         block_cache_size : 256 * 1024 * 1024,
+///         max_background_jobs : 4,
+///         cf_target_file_sizes : &[("variants", 256 * 1024 * 1024)],
+///         ..RocksConfig::DEFAULT
+///     };
+/// }
+///
+/// let mut table = Table::<Config, true>::new("rocks_config_example.rocks", Config()).unwrap();
+/// table.reset().unwrap();
+/// table.insert("Hello", &"Greeting".to_string()).unwrap();
+/// assert_eq!(table.lookup_fuzzy_raw("Hallo").unwrap().into_vec().unwrap().len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RocksConfig {
+    /// The size, in bytes, of an LRU block cache shared by all of the column families.  RocksDB's default is
+    /// a separate 8MB cache for each column family
+    pub block_cache_size : usize,
+    /// The size, in bytes, of each column family's memtable, which is written to a file once it's full.  RocksDB's
+    /// default is 64MB
+    pub write_buffer_size : usize,
+    /// The maximum number of background flushes and compactions that may run at once.  RocksDB's default is 2
+    pub max_background_jobs : i32,
+    /// The target size, in bytes, of the files of each column family.  RocksDB's default is 64MB
+    pub target_file_size : u64,
+    /// The target file sizes of particular column families, by name, which take the place of `target_file_size`
+    pub cf_target_file_sizes : &'static [(&'static str, u64)],
+    /// The bits per key of the bloom filters of the variant CFs.  10 bits gives about 1% false positives.  0 to
+    /// build no filters
+    pub variants_bloom_bits_per_key : u32,
+    /// Whether the filters of the variant CFs hold whole keys.  Only whole-key filters help the point lookups made
+    /// for fuzzy lookups
+    pub variants_whole_key_filtering : bool,
+    /// Whether the index and filter blocks of the variant CFs are kept in the block cache, so their memory is
+    /// bounded by the cache, rather than held for every open file.  The blocks of the files in level 0 are pinned
+    /// in the cache, since they are probed by every lookup.  Only takes effect if `block_cache_size` is set, so
+    /// the blocks don't crowd the data out of RocksDB's small default cache
+    pub variants_cache_index_and_filter_blocks : bool,
+    /// Whether RocksDB collects the statistics returned by [db_statistics](crate::Table::db_statistics).  Collecting
+    /// them costs a little on every operation, so they are off by default
+    pub statistics : bool,
+}
+
+impl Default for RocksConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl RocksConfig {
+
+    /// The RocksConfig that keeps RocksDB's defaults, apart from the bloom filters of the variant CFs
+    pub const DEFAULT : RocksConfig = RocksConfig {
+        block_cache_size : 0,
+        write_buffer_size : 0,
+        max_background_jobs : 0,
+        target_file_size : 0,
+        cf_target_file_sizes : &[],
+        variants_bloom_bits_per_key : 10,
+        variants_whole_key_filtering : true,
+        variants_cache_index_and_filter_blocks : true,
+        statistics : false,
+    };
+
+    /// Returns the target size of the files of the named column family, or 0 for RocksDB's default
+    pub fn cf_target_file_size(&self, cf_name : &str) -> u64 {
+        self.cf_target_file_sizes.iter()
+            .find(|(name, _size)| *name == cf_name)
+            .map(|(_name, size)| *size)
+            .unwrap_or(self.target_file_size)
+    }
+}
@@ -0,0 +1,236 @@
+//!
+//! The ShadowTable module contains the [ShadowTable] object, for comparing a candidate [Table] against a primary
+//! Table using real queries.  The ShadowTable, ShadowReport, ShadowDifference, and ShadowQueryKind types are re-exported
+//!
+
+use std::sync::Mutex;
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::{*};
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+
+/// The kinds of query that a [ShadowTable] compares
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ShadowQueryKind {
+    /// A call to [ShadowTable::lookup_exact]
+    Exact,
+    /// A call to [ShadowTable::lookup_fuzzy]
+    Fuzzy,
+    /// A call to [ShadowTable::lookup_best]
+    Best,
+}
+
+/// A query whose results from the candidate Table didn't match the results from the primary Table, recorded
+/// in the [ShadowReport]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowDifference<OwnedKeyT> {
+    pub kind : ShadowQueryKind,
+    /// The key supplied to the query
+    pub key : OwnedKeyT,
+    /// The records returned by the primary Table but not by the candidate, in ascending order
+    pub only_in_primary : Vec<RecordID>,
+    /// The records returned by the candidate Table but not by the primary, in ascending order
+    pub only_in_candidate : Vec<RecordID>,
+    /// The error returned by the candidate Table, if the query failed there
+    pub candidate_error : Option<String>,
+}
+
+/// A summary of the queries compared by a [ShadowTable], returned by [ShadowTable::report]
+///
+/// The counts cover every query since the ShadowTable was created, or since the last call to
+/// [reset_report](ShadowTable::reset_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowReport<OwnedKeyT> {
+    /// The number of queries answered by both Tables
+    pub query_count : u64,
+    /// The number of queries where the candidate's results differed from the primary's, or the candidate failed
+    pub mismatch_count : u64,
+    /// The number of queries that failed on the candidate Table
+    pub candidate_error_count : u64,
+    /// The first differences encountered, up to the `log_len` supplied to [ShadowTable::new], in the order
+    /// they occurred
+    pub differences : Vec<ShadowDifference<OwnedKeyT>>,
+}
+
+impl <OwnedKeyT>Default for ShadowReport<OwnedKeyT> {
+    fn default() -> Self {
+        Self {
+            query_count : 0,
+            mismatch_count : 0,
+            candidate_error_count : 0,
+            differences : vec![],
+        }
+    }
+}
+
+/// A pair of [Table]s, where every query is sent to both a primary Table and a candidate Table, and only the
+/// primary's results are returned
+///
+/// A ShadowTable is for validating a change to a Table's configuration, for example a new `MAX_DELETES` or
+/// case-sensitivity, against production traffic before switching to it.  The candidate's results are compared
+/// with the primary's and any differences are counted and logged in a [ShadowReport].  An error from the candidate
+/// Table is recorded in the report rather than returned, so the candidate can't break the queries it shadows.
+///
+/// Results are compared by [RecordID], so the comparison is only meaningful if both Tables were built from the
+/// same records, inserted in the same order, or if both use [DETERMINISTIC_RECORD_IDS](TableConfig::DETERMINISTIC_RECORD_IDS).
+/// Records are compared as sets, so a difference in ordering or in the reported distance isn't a mismatch.
+///
+/// A ShadowTable only forwards queries.  Writes must be applied to both Tables, using [tables_mut](ShadowTable::tables_mut).
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let config = RuntimeConfig::builder().case_sensitive(false).build();
+/// let mut primary = Table::<DefaultTableConfig, true>::new("shadow_primary_example.rocks", DefaultTableConfig()).unwrap();
+/// let mut candidate = Table::<RuntimeConfig, true>::new("shadow_candidate_example.rocks", config).unwrap();
+/// primary.reset().unwrap();
+/// candidate.reset().unwrap();
+/// let mut table = ShadowTable::new(primary, candidate, 10);
+///
+/// let (primary, candidate) = table.tables_mut();
+/// let monday = primary.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+/// candidate.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+///
+/// assert_eq!(table.lookup_exact("Monday").unwrap(), vec![monday]);
+/// assert_eq!(table.lookup_exact("monday").unwrap(), vec![]);
+///
+/// let report = table.report();
+/// assert_eq!(report.query_count, 2);
+/// assert_eq!(report.mismatch_count, 1);
+/// assert_eq!(report.differences[0].key, "monday");
+/// assert_eq!(report.differences[0].only_in_candidate, vec![monday]);
+/// ```
+pub struct ShadowTable<ConfigT : TableConfig, CandidateConfigT : TableConfig, const UTF8_KEYS : bool, OwnedKeyT> {
+    primary : Table<ConfigT, UTF8_KEYS>,
+    candidate : Table<CandidateConfigT, UTF8_KEYS>,
+    log_len : usize,
+    report : Mutex<ShadowReport<OwnedKeyT>>,
+}
+
+impl <OwnedKeyT, ConfigT : TableConfig, CandidateConfigT : TableConfig, const UTF8_KEYS : bool>ShadowTable<ConfigT, CandidateConfigT, UTF8_KEYS, OwnedKeyT>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    CandidateConfigT : TableConfig<KeyCharT = ConfigT::KeyCharT, DistanceT = ConfigT::DistanceT>,
+    CandidateConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT> + Clone,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    Table<CandidateConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Creates a ShadowTable from a primary Table, whose results are returned, and a candidate Table, whose results
+    /// are compared against them
+    ///
+    /// `log_len` is the maximum number of [ShadowDifference]s kept in the report.  The differences beyond
+    /// that are still counted.
+    pub fn new(primary : Table<ConfigT, UTF8_KEYS>, candidate : Table<CandidateConfigT, UTF8_KEYS>, log_len : usize) -> Self {
+        Self {
+            primary,
+            candidate,
+            log_len,
+            report : Mutex::new(ShadowReport::default()),
+        }
+    }
+
+    /// Returns the primary Table
+    pub fn primary(&self) -> &Table<ConfigT, UTF8_KEYS> {
+        &self.primary
+    }
+
+    /// Returns the candidate Table
+    pub fn candidate(&self) -> &Table<CandidateConfigT, UTF8_KEYS> {
+        &self.candidate
+    }
+
+    /// Returns the primary and candidate Tables, so writes can be applied to both
+    pub fn tables_mut(&mut self) -> (&mut Table<ConfigT, UTF8_KEYS>, &mut Table<CandidateConfigT, UTF8_KEYS>) {
+        (&mut self.primary, &mut self.candidate)
+    }
+
+    /// Takes the ShadowTable apart, returning the primary and candidate Tables
+    pub fn into_tables(self) -> (Table<ConfigT, UTF8_KEYS>, Table<CandidateConfigT, UTF8_KEYS>) {
+        (self.primary, self.candidate)
+    }
+
+    /// Returns a copy of the report of the queries compared so far
+    pub fn report(&self) -> ShadowReport<OwnedKeyT> {
+        self.report.lock().unwrap().clone()
+    }
+
+    /// Clears the report, so subsequent calls to [report](Self::report) only cover the queries made afterwards
+    pub fn reset_report(&self) {
+        *self.report.lock().unwrap() = ShadowReport::default();
+    }
+
+    /// Locates the records with keys that precisely match the key supplied, in both Tables, and returns the
+    /// primary's results.  See [Table::lookup_exact] for the caveats
+    pub fn lookup_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<RecordID>, String> {
+        let key = key.into_key();
+        let results = self.primary.lookup_exact_internal(&key)?;
+        let candidate_results = self.candidate.lookup_exact_internal(&key);
+        self.compare(ShadowQueryKind::Exact, &key, &results, candidate_results);
+        Ok(results)
+    }
+
+    /// Performs a fuzzy lookup in both Tables, and returns the primary's results.  See [Table::lookup_fuzzy] for
+    /// the meaning of `threshold`
+    ///
+    /// Unlike `Table::lookup_fuzzy`, the results aren't lazy, because the candidate's results can't be compared
+    /// until they have all been found.
+    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        let key = key.into_key();
+        let results = self.primary.lookup_fuzzy_internal(&key, threshold, None)?.into_vec()?;
+        let candidate_results = self.candidate.lookup_fuzzy_internal(&key, threshold, None)
+            .and_then(|lookup| lookup.into_vec())
+            .map(|results| results.into_iter().map(|(record_id, _)| record_id).collect());
+        let record_ids : Vec<RecordID> = results.iter().map(|(record_id, _)| *record_id).collect();
+        self.compare(ShadowQueryKind::Fuzzy, &key, &record_ids, candidate_results);
+        Ok(results)
+    }
+
+    /// Returns the records in the primary Table whose keys are the shortest distance from the key supplied,
+    /// after comparing them with the best matches in the candidate Table
+    pub fn lookup_best<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<RecordID>, String> {
+        let key = key.into_key();
+        let results : Vec<RecordID> = self.primary.lookup_best_internal(&key)?.collect();
+        let candidate_results = self.candidate.lookup_best_internal(&key).map(|iter| iter.collect());
+        self.compare(ShadowQueryKind::Best, &key, &results, candidate_results);
+        Ok(results)
+    }
+
+    /// Records the outcome of a query in the report
+    fn compare<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, kind : ShadowQueryKind, key : &K, primary_results : &[RecordID], candidate_results : Result<Vec<RecordID>, String>) {
+
+        let (only_in_primary, only_in_candidate, candidate_error) = match candidate_results {
+            Ok(candidate_results) => {
+                let mut only_in_primary : Vec<RecordID> = primary_results.iter().copied().filter(|id| !candidate_results.contains(id)).collect();
+                let mut only_in_candidate : Vec<RecordID> = candidate_results.iter().copied().filter(|id| !primary_results.contains(id)).collect();
+                only_in_primary.sort_unstable();
+                only_in_primary.dedup();
+                only_in_candidate.sort_unstable();
+                only_in_candidate.dedup();
+                (only_in_primary, only_in_candidate, None)
+            },
+            Err(err) => (vec![], vec![], Some(err))
+        };
+
+        let mut report = self.report.lock().unwrap();
+        report.query_count += 1;
+        if only_in_primary.is_empty() && only_in_candidate.is_empty() && candidate_error.is_none() {
+            return;
+        }
+        report.mismatch_count += 1;
+        if candidate_error.is_some() {
+            report.candidate_error_count += 1;
+        }
+        if report.differences.len() < self.log_len {
+            let key = OwnedKeyT::from_key(key);
+            report.differences.push(ShadowDifference{kind, key, only_in_primary, only_in_candidate, candidate_error});
+        }
+    }
+}
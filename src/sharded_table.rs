@@ -0,0 +1,182 @@
+//!
+//! The ShardedTable module contains the [ShardedTable] object, for partitioning records across several [Table]s.
+//! The ShardedTable and ShardedRecordID structs are re-exported
+//!
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::{*};
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+
+/// Identifies a record in a [ShardedTable], by the shard that holds it and its [RecordID] within that shard
+#[derive(Copy, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ShardedRecordID {
+    pub shard : usize,
+    pub record_id : RecordID,
+}
+
+/// A set of [Table]s, with records partitioned between them by a hash of their key
+///
+/// Each shard is an ordinary Table, so the shards may live on different disks, and each has its own RocksDB
+/// instance to absorb writes.  A record is inserted into the shard chosen by hashing its key, after folding the
+/// key if the Tables fold keys, e.g. because they aren't case-sensitive.  Lookups are made against every shard and the results
+/// are merged, so a record will be found regardless of the shard that holds it.
+///
+/// The hash is stable, but the shard a key is routed to depends on the number of shards, so a ShardedTable must
+/// always be assembled from the same Tables, in the same order.
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let mut shards = vec![];
+/// for i in 0..2 {
+///     let mut shard = Table::<DefaultTableConfig, true>::new(&format!("sharded_example_{}.rocks", i), DefaultTableConfig()).unwrap();
+///     shard.reset().unwrap();
+///     shards.push(shard);
+/// }
+/// let mut table = ShardedTable::new(shards).unwrap();
+///
+/// let monday = table.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+/// table.insert("Tuesday", &"Kayoubi".to_string()).unwrap();
+/// assert_eq!(monday.shard, table.shard_for_key("Monday"));
+///
+/// let results = table.lookup_fuzzy("Bonday", Some(1)).unwrap();
+/// assert_eq!(results, vec![(monday, 1)]);
+/// assert_eq!(table.get_value(monday).unwrap(), "Getsuyoubi");
+/// ```
+pub struct ShardedTable<ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    shards : Vec<Table<ConfigT, UTF8_KEYS>>,
+}
+
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>ShardedTable<ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Creates a ShardedTable from its shards
+    ///
+    /// Returns an error if there are no shards, or if the shards don't agree on whether they are case-sensitive or
+    /// fold diacritics, which would route a key differently depending on the shard consulted.
+    pub fn new(shards : Vec<Table<ConfigT, UTF8_KEYS>>) -> Result<Self, String> {
+
+        let first_shard = shards.first().ok_or_else(|| "a ShardedTable needs at least one shard".to_string())?;
+        let folding = (first_shard.params().case_sensitive, first_shard.params().fold_diacritics);
+        if let Some(shard) = shards.iter().find(|shard| (shard.params().case_sensitive, shard.params().fold_diacritics) != folding) {
+            return Err(format!("shard {} doesn't match the key folding of shard {}", shard.path(), first_shard.path()));
+        }
+
+        Ok(Self{shards})
+    }
+
+    /// Returns the number of shards
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the Tables that make up the ShardedTable, in order
+    pub fn shards(&self) -> &[Table<ConfigT, UTF8_KEYS>] {
+        &self.shards
+    }
+
+    /// Takes the ShardedTable apart, returning its shards in order
+    pub fn into_shards(self) -> Vec<Table<ConfigT, UTF8_KEYS>> {
+        self.shards
+    }
+
+    /// Returns the index of the shard that a record with the supplied key is inserted into
+    pub fn shard_for_key<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> usize {
+        self.shard_for_key_internal(&key.into_key())
+    }
+
+    fn shard_for_key_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> usize {
+
+        let owned_key = OwnedKeyT::from_key(key);
+        let owned_key = self.shards[0].params().fold_key(owned_key);
+
+        //NOTE: The records already in the shards depend on the hash, so it must be stable
+        (stable_key_hash(&owned_key) % self.shards.len() as u64) as usize
+    }
+
+    /// Resets every shard, dropping all of the records in the ShardedTable
+    pub fn reset(&mut self) -> Result<(), String> {
+        for shard in self.shards.iter_mut() {
+            shard.reset()?;
+        }
+        Ok(())
+    }
+
+    /// Inserts a new key-value pair into the shard chosen by the key, and returns the ShardedRecordID of the new record
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn insert<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, key : K, value : &ConfigT::ValueT) -> Result<ShardedRecordID, String> {
+        let key = key.into_key();
+        let shard = self.shard_for_key_internal(&key);
+        let record_id = self.shards[shard].insert_internal([&key].iter().copied(), 1, value)?;
+        Ok(ShardedRecordID{shard, record_id})
+    }
+
+    /// Deletes a record from its shard
+    pub fn delete(&mut self, id : ShardedRecordID) -> Result<(), String> {
+        self.shard_mut(id)?.delete(id.record_id)
+    }
+
+    /// Returns the value associated with the specified record
+    pub fn get_value(&self, id : ShardedRecordID) -> Result<ConfigT::ValueT, String> {
+        self.shard(id)?.get_value(id.record_id)
+    }
+
+    /// Locates the records in every shard with keys that precisely match the key supplied.  See
+    /// [Table::lookup_exact] for the caveats
+    pub fn lookup_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<ShardedRecordID>, String> {
+        let key = key.into_key();
+        let mut results = vec![];
+        for (shard, table) in self.shards.iter().enumerate() {
+            results.extend(table.lookup_exact_internal(&key)?.into_iter().map(|record_id| ShardedRecordID{shard, record_id}));
+        }
+        Ok(results)
+    }
+
+    /// Performs a fuzzy lookup against every shard, and returns the merged results ordered by ascending distance.
+    /// See [Table::lookup_fuzzy] for the meaning of `threshold`
+    ///
+    /// Unlike `Table::lookup_fuzzy`, the results aren't lazy, because they can't be ordered until every shard
+    /// has been searched.
+    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(ShardedRecordID, ConfigT::DistanceT)>, String> {
+        let key = key.into_key();
+        let mut results = vec![];
+        for (shard, table) in self.shards.iter().enumerate() {
+            let shard_results = table.lookup_fuzzy_internal(&key, threshold, None)?.into_vec()?;
+            results.extend(shard_results.into_iter().map(|(record_id, distance)| (ShardedRecordID{shard, record_id}, distance)));
+        }
+
+        //NOTE: A stable sort, so records at the same distance stay in shard order
+        results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(core::cmp::Ordering::Equal));
+        Ok(results)
+    }
+
+    /// Returns the records in every shard whose keys are the shortest distance from the key supplied
+    pub fn lookup_best<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<ShardedRecordID>, String> {
+        let results = self.lookup_fuzzy(key, None)?;
+        let best_distance = match results.first() {
+            Some((_, distance)) => *distance,
+            None => return Ok(vec![])
+        };
+        Ok(results.into_iter().take_while(|(_, distance)| *distance == best_distance).map(|(id, _)| id).collect())
+    }
+
+    fn shard(&self, id : ShardedRecordID) -> Result<&Table<ConfigT, UTF8_KEYS>, String> {
+        self.shards.get(id.shard).ok_or_else(|| format!("no shard with index {}", id.shard))
+    }
+
+    fn shard_mut(&mut self, id : ShardedRecordID) -> Result<&mut Table<ConfigT, UTF8_KEYS>, String> {
+        self.shards.get_mut(id.shard).ok_or_else(|| format!("no shard with index {}", id.shard))
+    }
+}
@@ -0,0 +1,70 @@
+//!
+//! The SharedDatabase module contains the [SharedDatabase] struct, which lets several named Tables live in
+//! a single RocksDB database.  The SharedDatabase struct is re-exported
+//!
+
+use super::database::SharedDB;
+use super::rocks_config::RocksConfig;
+
+/// A single RocksDB database that holds several logically separate Tables, each with its own name
+///
+/// Each Table opened with [Table::open_shared](crate::Table::open_shared) keeps its own set of column families,
+/// named after the Table, e.g. "cities:variants", so the Tables may have different configs and value types.
+/// The Tables share RocksDB's files, write-ahead log, background threads, and block cache, so many small Tables
+/// cost far fewer file handles than the same Tables each in a directory of its own.
+///
+/// The [RocksConfig] the SharedDatabase is opened with applies to every Table in it, in place of the Tables'
+/// own [ROCKS_CONFIG](crate::TableConfig::ROCKS_CONFIG).  A Table's [merge_diagnostics](crate::Table::merge_diagnostics)
+/// count the merges of every Table in the SharedDatabase, without logging anomalies.  Checkpoints and backups of any of
+/// the Tables hold the whole SharedDatabase.
+///
+/// The SharedDatabase takes the database's write lock, the same way [Table::new](crate::Table::new) does, and
+/// the lock is held until the SharedDatabase and every Table opened in it are dropped.
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let shared = SharedDatabase::open("shared_example.rocks", RocksConfig::DEFAULT).unwrap();
+/// let mut greetings = Table::<DefaultTableConfig, true>::open_shared(&shared, "greetings", DefaultTableConfig()).unwrap();
+/// let mut farewells = Table::<DefaultTableConfig, true>::open_shared(&shared, "farewells", DefaultTableConfig()).unwrap();
+/// greetings.reset().unwrap();
+/// farewells.reset().unwrap();
+/// greetings.insert("Hello", &"English".to_string()).unwrap();
+/// farewells.insert("Goodbye", &"English".to_string()).unwrap();
+///
+/// assert_eq!(greetings.lookup_fuzzy_raw("Hallo").unwrap().into_vec().unwrap().len(), 1);
+/// assert_eq!(farewells.lookup_fuzzy_raw("Hallo").unwrap().into_vec().unwrap().len(), 0);
+/// assert_eq!(shared.table_names().unwrap(), vec!["farewells".to_string(), "greetings".to_string()]);
+/// ```
+pub struct SharedDatabase {
+    pub(crate) db : SharedDB,
+}
+
+impl SharedDatabase {
+
+    /// Opens the SharedDatabase at the path provided, creating it if it doesn't exist
+    ///
+    /// Returns an error if the path holds a database created by [Table::new](crate::Table::new), which belongs
+    /// to a single Table, or if another process has the database open.
+    pub fn open(path : &str, rocks_config : RocksConfig) -> Result<Self, String> {
+        Ok(Self{db : SharedDB::open(path, rocks_config)?})
+    }
+
+    /// Returns the path that the SharedDatabase was opened from
+    pub fn path(&self) -> &str {
+        self.db.path()
+    }
+
+    /// Returns the names of the Tables in the SharedDatabase, in alphabetical order
+    pub fn table_names(&self) -> Result<Vec<String>, String> {
+        self.db.table_names()
+    }
+
+    /// Deletes the named Table, and all of its records, from the SharedDatabase.  Returns `false` if there was
+    /// no such Table
+    ///
+    /// A Table that is open can't be dropped.
+    pub fn drop_table(&self, name : &str) -> Result<bool, String> {
+        self.db.drop_table(name)
+    }
+}
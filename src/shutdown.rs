@@ -0,0 +1,21 @@
+//!
+//! The Shutdown module contains the summary produced by [Table::shutdown](crate::Table::shutdown).  The
+//! ShutdownSummary struct is re-exported
+//!
+
+/// The pending work that was completed by [Table::shutdown](crate::Table::shutdown) before the database was closed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    /// The number of variant entries written by finishing a bulk load that was still in progress.  See
+    /// [begin_bulk_load](crate::Table::begin_bulk_load)
+    pub bulk_load_variants : usize,
+    /// The number of record hits that were counted in memory and written to the "stats" column family.  See
+    /// [TRACK_ACCESS_STATS](crate::TableConfig::TRACK_ACCESS_STATS)
+    pub flushed_hits : usize,
+    /// The number of missing variant references that were restored.  See [READ_REPAIR](crate::TableConfig::READ_REPAIR)
+    pub read_repairs : usize,
+    /// The number of expired records that were deleted.  See [TTL](crate::TableConfig::TTL)
+    pub expired_records : usize,
+    /// The number of records recorded in the preload manifest.  See [PRELOAD_MANIFEST_LEN](crate::TableConfig::PRELOAD_MANIFEST_LEN)
+    pub preload_manifest_len : usize,
+}
@@ -8,6 +8,8 @@ use std::collections::{HashSet};
 use super::unicode_string_helpers::{*};
 use super::key::{*};
 use super::table_config::{*};
+use super::ngrams::IndexStrategy;
+use super::variant_scheme::VariantScheme;
 
 pub struct SymSpell<OwnedKeyT, const UTF8_KEYS : bool> {
     phantom_key: PhantomData<OwnedKeyT>
@@ -16,7 +18,17 @@ pub struct SymSpell<OwnedKeyT, const UTF8_KEYS : bool> {
 impl <OwnedKeyT, const UTF8_KEYS : bool>SymSpell<OwnedKeyT, UTF8_KEYS> {
 
     /// Returns all of the variants of a key, for querying or adding to the variants database
-    pub fn variants<KeyCharT : Clone, K, ConfigT : TableConfig>(key: &K, config : &ConfigT) -> HashSet<Vec<u8>>
+    pub fn variants<KeyCharT : Clone, K>(key: &K, params : &TableParams, scheme : &dyn VariantScheme<KeyCharT>) -> HashSet<Vec<u8>>
+        where
+        OwnedKeyT : OwnedKey<KeyCharT = KeyCharT>,
+        K : Key<KeyCharT = KeyCharT>
+    {
+        Self::variants_within(key, params.max_deletes, params, scheme)
+    }
+
+    /// Returns the variants of a key that are no more than `max_deletes` deletes away from the key.  `max_deletes`
+    /// should not exceed `params.max_deletes`, or the variants will include some that were never added to the database
+    pub fn variants_within<KeyCharT : Clone, K>(key: &K, max_deletes : usize, params : &TableParams, scheme : &dyn VariantScheme<KeyCharT>) -> HashSet<Vec<u8>>
         where
         OwnedKeyT : OwnedKey<KeyCharT = KeyCharT>,
         K : Key<KeyCharT = KeyCharT>
@@ -28,10 +40,23 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>SymSpell<OwnedKeyT, UTF8_KEYS> {
         if key.num_chars() > 0 {
 
             //We'll only build variants from the meaningful portion of the key
-            let meaningful_key = Self::meaningful_key_substring(key, config);
+            let meaningful_key = Self::meaningful_key_substring(key, params);
+
+            //If the table indexes n-grams instead, the key itself is the only variant
+            if 0 < max_deletes && params.index_strategy == IndexStrategy::DeletionVariants {
+                let key_chars = meaningful_key.get_key_chars();
+                let unit_lens = if UTF8_KEYS { str_unit_lens(meaningful_key.borrow_key_str().unwrap(), params) } else { vec![1; key_chars.len()] };
+                let mut key_units = Vec::with_capacity(unit_lens.len());
+                let mut remaining_chars = &key_chars[..];
+                for unit_len in unit_lens {
+                    let (unit, remainder) = remaining_chars.split_at(unit_len);
+                    key_units.push(unit);
+                    remaining_chars = remainder;
+                }
 
-            if 0 < ConfigT::MAX_DELETES {
-                Self::variants_recursive(&meaningful_key, 0, &mut variants_set, config);
+                for variant in scheme.variants(&key_units, max_deletes) {
+                    variants_set.insert(OwnedKeyT::from_vec(variant).into_bytes());
+                }
             }
             variants_set.insert(meaningful_key.into_bytes());    
         }
@@ -39,49 +64,25 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>SymSpell<OwnedKeyT, UTF8_KEYS> {
         variants_set
     }
 
-    // The recursive part of the variants() function
-    pub fn variants_recursive<KeyCharT, K, ConfigT : TableConfig>(key: &K, edit_distance: usize, variants_set: &mut HashSet<Vec<u8>>, config : &ConfigT)
-        where
-        OwnedKeyT : OwnedKey<KeyCharT = KeyCharT>,
-        K : Key<KeyCharT = KeyCharT>
-    {
-
-        let edit_distance = edit_distance + 1;
-
-        let key_len = key.num_chars();
-
-        if key_len > 1 {
-            for i in 0..key_len {
-                let variant = Self::remove_char_from_key(key, i);
-
-                if !variants_set.contains(variant.as_bytes()) {
-
-                    if edit_distance < ConfigT::MAX_DELETES {
-                        Self::variants_recursive(&variant, edit_distance, variants_set, config);
-                    }
-
-                    variants_set.insert(variant.into_bytes());
-                }
-            }
-        }
-    }
-
     // Returns the "meaningful" part of a key, that is used as the starting point to generate the variants
-    pub fn meaningful_key_substring<KeyCharT : Clone, K, ConfigT : TableConfig>(key: &K, _config : &ConfigT) -> OwnedKeyT
+    // If the table folds keys, e.g. because it is case-insensitive, the key is folded first
+    pub fn meaningful_key_substring<KeyCharT : Clone, K>(key: &K, params : &TableParams) -> OwnedKeyT
         where
         OwnedKeyT : OwnedKey<KeyCharT = KeyCharT>,
         K : Key<KeyCharT = KeyCharT>,
     {
         if UTF8_KEYS {
-            let result_string = if let Some(key_str) = key.borrow_key_str() {
-                unicode_truncate(key_str, ConfigT::MEANINGFUL_KEY_LEN) //NOTE: Fast path
+            let result_string = if params.folds_keys() {
+                truncate_str(&params.fold_key(key.get_key_string()), params.meaningful_key_len, params)
+            } else if let Some(key_str) = key.borrow_key_str() {
+                truncate_str(key_str, params.meaningful_key_len, params) //NOTE: Fast path
             } else {
-                unicode_truncate(&key.get_key_string(), ConfigT::MEANINGFUL_KEY_LEN) //NOTE: Slow path, allocates a temp String
+                truncate_str(&key.get_key_string(), params.meaningful_key_len, params) //NOTE: Slow path, allocates a temp String
             };
             OwnedKeyT::from_string(result_string)
         } else {
-            let result_vec = if key.num_chars() > ConfigT::MEANINGFUL_KEY_LEN {
-                let (prefix, _remainder) = key.borrow_key_chars().unwrap().split_at(ConfigT::MEANINGFUL_KEY_LEN);
+            let result_vec = if key.num_chars() > params.meaningful_key_len {
+                let (prefix, _remainder) = key.borrow_key_chars().unwrap().split_at(params.meaningful_key_len);
                 prefix.to_vec()
             } else {
                 key.get_key_chars()
@@ -90,21 +91,25 @@ impl <OwnedKeyT, const UTF8_KEYS : bool>SymSpell<OwnedKeyT, UTF8_KEYS> {
         }
     }
 
-    // Returns a new owned key, that is a variant of the supplied key, without the character at the
-    // specified index
-    pub fn remove_char_from_key<KeyCharT, K>(key: &K, idx : usize) -> OwnedKeyT
-        where
-        OwnedKeyT : OwnedKey<KeyCharT = KeyCharT>,
-        K : Key<KeyCharT = KeyCharT>
-    {
-        if UTF8_KEYS {
-            let result_string = unicode_remove_char(key.borrow_key_str().unwrap(), idx);
-            OwnedKeyT::from_string(result_string)
-        } else {
-            let mut result_vec = key.get_key_chars();
-            result_vec.remove(idx);
-            OwnedKeyT::from_vec(result_vec)
-        }
+}
+
+// Returns the first `len` grapheme clusters of a utf-8 key if the table uses them, or the first `len` chars otherwise
+#[cfg_attr(not(feature = "unicode-segmentation"), allow(unused_variables))]
+fn truncate_str(s : &str, len : usize, params : &TableParams) -> String {
+    #[cfg(feature = "unicode-segmentation")]
+    if params.grapheme_clusters {
+        return unicode_truncate_graphemes(s, len);
     }
+    unicode_truncate(s, len)
+}
 
+// Returns the length in chars of each unit in a utf-8 key, which is a grapheme cluster if the table uses them, or a
+// single char otherwise
+#[cfg_attr(not(feature = "unicode-segmentation"), allow(unused_variables))]
+fn str_unit_lens(s : &str, params : &TableParams) -> Vec<usize> {
+    #[cfg(feature = "unicode-segmentation")]
+    if params.grapheme_clusters {
+        return unicode_grapheme_char_lens(s);
+    }
+    vec![1; unicode_len(s)]
 }
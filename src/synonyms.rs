@@ -0,0 +1,34 @@
+//!
+//! The Synonyms module contains the logic for expanding a lookup key with the synonyms of its tokens, stored in
+//! the "synonyms" column family.  Nothing should be re-exported
+//!
+
+/// The most alternate forms a lookup key is expanded into, so a key with several tokens that each have a few
+/// synonyms doesn't multiply into an enormous lookup
+pub(crate) const MAX_SYNONYM_EXPANSIONS : usize = 16;
+
+/// Returns the alternate forms of a key, formed by replacing any of its tokens with one of the token's synonyms,
+/// up to [MAX_SYNONYM_EXPANSIONS].  `token_synonyms` holds the synonyms of each token, in the same order as `tokens`
+///
+/// The key itself isn't included, and the tokens of each form are joined with single spaces.
+pub(crate) fn synonym_expansions(tokens : &[&str], token_synonyms : &[Vec<String>]) -> Vec<String> {
+
+    let mut forms : Vec<Vec<&str>> = vec![vec![]];
+    for (token, synonyms) in tokens.iter().zip(token_synonyms) {
+        let mut next_forms = vec![];
+        for form in forms.iter() {
+            for alternative in core::iter::once(*token).chain(synonyms.iter().map(|synonym| synonym.as_str())) {
+                if next_forms.len() > MAX_SYNONYM_EXPANSIONS {
+                    break;
+                }
+                let mut next_form = form.clone();
+                next_form.push(alternative);
+                next_forms.push(next_form);
+            }
+        }
+        forms = next_forms;
+    }
+
+    //The first form is the one that keeps every token
+    forms.into_iter().skip(1).map(|form| form.join(" ")).collect()
+}
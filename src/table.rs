@@ -2,8 +2,10 @@
 //! The Table module contains the main [Table] object
 //! 
 
+use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet};
-use std::collections::hash_map::Entry;
+use std::io::{Read, Write};
+use std::sync::Mutex;
 
 use num_traits::Zero;
 use serde::{Serialize};
@@ -14,11 +16,69 @@ use super::database::{*};
 use super::table_config::{*};
 use super::sym_spell::{*};
 use super::key_groups::{*};
-use super::bincode_helpers::{*};
 use super::perf_counters::{*};
+use super::access_stats::{*};
+use super::query_cache::QueryCache;
+use super::quarantine::{*};
+use super::transaction::{*};
+use super::fuzzy_lookup::{*};
+use super::cursor::{LookupCursor, new_table_id, threshold_bytes};
+use super::key_group_handle::KeyGroupHandle;
+use super::verify::{*};
+use super::backup::{*};
+use super::shutdown::ShutdownSummary;
+use super::read_only_table::{*};
+use super::lock_file::{lock_owner, force_unlock};
+use super::did_you_mean::{*};
+use super::compound::{*};
+use super::export::{*};
+use super::validation::{*};
+use super::access_control::{*};
+use super::merge_diagnostics::MergeDiagnostics;
+use super::db_statistics::DBStatistics;
+use super::disk_usage::DiskUsage;
+use super::db_properties::DBProperties;
+use super::shared_database::SharedDatabase;
+use super::ngrams::{IndexStrategy, key_ngrams, min_shared_ngrams};
+use super::banded_levenshtein::BandedLevenshtein;
+use super::variant_scheme::DELETION_VARIANT_SCHEME_ID;
+use super::fallback_scan::{FallbackScan, sample_records};
+use super::tokens::{key_tokens, token_variants, phrase_distance};
+use super::synonyms::synonym_expansions;
+use super::record_metadata::{RecordMetadata, now_millis};
+use super::migration::{*};
+use super::cipher::{cipher_check, verify_cipher};
+
+/// The results of a fuzzy lookup, with each record's distance and value
+pub(crate) type FuzzyResultsWithValues<ConfigT> = Vec<(RecordID, <ConfigT as TableConfig>::DistanceT, <ConfigT as TableConfig>::ValueT)>;
+
+/// One page of the results of a fuzzy lookup, with the cursor to continue from
+pub(crate) type FuzzyResultsPage<ConfigT> = (Vec<(RecordID, <ConfigT as TableConfig>::DistanceT)>, Option<LookupCursor>);
+
+/// The number of results whose values are read together by each batch of a value prefetch.  See
+/// [VALUE_PREFETCH_DEPTH](TableConfig::VALUE_PREFETCH_DEPTH)
+const VALUE_PREFETCH_BATCH_SIZE : usize = 32;
+
+/// The fewest candidate records that [Table::lookup_fuzzy_parallel] evaluates across threads.  Fewer than this
+/// are evaluated on the calling thread, where they're cheaper than the cost of handing them out
+#[cfg(feature = "parallel")]
+const PARALLEL_LOOKUP_MIN_RECORDS : usize = 64;
+
+/// The estimated cost of evaluating one record while scanning every record, for [Table::lookup_fuzzy_within],
+/// measured in variant probes.  Each record scanned costs a step of the "keys" CF iterator, and a load of the
+/// record's key groups
+const SCAN_COST_PER_RECORD : usize = 4;
+
+/// The most key groups that lookups queue to be checked by [Table::apply_read_repairs].  Once the queue is full,
+/// the key groups lookups come across aren't queued until it has been drained
+const MAX_PENDING_READ_REPAIRS : usize = 4096;
 
 /// A collection containing records that may be searched using [Key]s
 ///
+/// A Table is [Send] and [Sync], as long as its [DistanceT](TableConfig::DistanceT) is [Send], so it may be shared across threads behind an [Arc](std::sync::Arc), and
+/// lookups may be performed from multiple threads concurrently.  Operations that modify the Table require
+/// `&mut self`, so they must be serialized, e.g. with an [RwLock](std::sync::RwLock).
+///
 /// IMPLEMENTATION NOTE: Currently Rust doesn't let us bound an impl by an associated constant.  In other words
 /// the bound `ConfigT : TableConfig<UTF8_KEYS = true>` won't work, and we need to reflect the UTF8_KEYS associated
 /// constant as a const generic parameter on Table.  This is tracked by <https://github.com/rust-lang/rust/issues/70256>
@@ -33,8 +93,29 @@ pub struct Table<ConfigT : TableConfig, const UTF8_KEYS : bool> {
     record_count : usize,
     db : DBConnection,
     config : ConfigT,
+    params : TableParams,
+    table_id : u64,
     deleted_records : Vec<RecordID>, //NOTE: Currently we don't try to hold onto deleted records across unloads, but we may change this in the future.
     perf_counters : PerfCounters,
+    access_stats : AccessStats,
+    validators : Validators<ConfigT::KeyCharT, ConfigT::ValueT>,
+    access_hook : Option<AccessHook<ConfigT::KeyCharT>>,
+    query_cache : QueryCache<ConfigT::DistanceT>,
+    read_repairs : Mutex<HashSet<KeyGroupID>>,
+}
+
+impl <ConfigT : TableConfig, const UTF8_KEYS : bool>Drop for Table<ConfigT, UTF8_KEYS> {
+    fn drop(&mut self) {
+        //NOTE: A bulk load must be finished, or the buffered variants, and the writes that skipped the log, are
+        // lost.  There's no caller to return the error to, so it's reported on stderr
+        if self.db.is_bulk_loading() {
+            if let Err(err) = self.finish_bulk_load() {
+                eprintln!("fuzzy_rocks: failed to finish the bulk load of {} when the Table was dropped: {}", self.db.path(), err);
+            }
+        }
+        let _ = self.access_stats.flush(&self.db);
+        let _ = self.save_preload_manifest(ConfigT::PRELOAD_MANIFEST_LEN);
+    }
 }
 
 /// A private trait implemented by a [Table] to provide access to the keys in the DB, 
@@ -52,519 +133,2989 @@ impl <ConfigT : TableConfig>TableKeyEncoding for Table<ConfigT, false> {
     type OwnedKeyT = Vec<ConfigT::KeyCharT>;
 }
 
-/// The implementation of the shared parts of Table, that are the same regardless of UTF8_KEYS
-impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UTF8_KEYS>
-    where
-    ConfigT::KeyCharT : 'static + Copy + PartialEq + Serialize + serde::de::DeserializeOwned,
-    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
-    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
-    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
-    Self : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
-    {
+/// The parts of Table that don't depend on the key encoding or the serialization of keys and values
+impl <ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UTF8_KEYS> {
 
-    /// Creates a new Table, backed by the database at the path provided
+    /// Returns the path of the database backing the Table
+    pub fn path(&self) -> &str {
+        self.db.path()
+    }
+
+    /// Returns the parameters the Table was created with, which are stored in the database and take precedence
+    /// over the ones in the config the Table was opened with
+    pub fn params(&self) -> &TableParams {
+        &self.params
+    }
+
+    /// Adds a validator that every key is checked with before it is written to the Table.  If the validator
+    /// returns an error, the write is rejected with that error and nothing is written
+    ///
+    /// Validators aren't stored in the database, so they must be added each time the Table is opened.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("validator_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// table.add_key_validator(|key| {
+    ///     if key.iter().all(|c| c.is_alphabetic()) { Ok(()) } else { Err("keys must be alphabetic".to_string()) }
+    /// });
+    /// table.add_value_validator(|value| {
+    ///     if value.len() <= 16 { Ok(()) } else { Err("value is too long".to_string()) }
+    /// });
+    ///
+    /// assert!(table.insert("Hello", &"Greeting".to_string()).is_ok());
+    /// assert!(table.insert("Hello!", &"Greeting".to_string()).is_err());
+    /// assert!(table.insert("Goodbye", &"A very long farewell".to_string()).is_err());
+    /// assert_eq!(table.lookup_exact("Goodbye").unwrap().count(), 0);
+    /// ```
+    pub fn add_key_validator<F : Fn(&[ConfigT::KeyCharT]) -> Result<(), String> + Send + Sync + 'static>(&mut self, validator : F) {
+        self.validators.add_key_validator(Box::new(validator));
+    }
+
+    /// Adds a validator that every value is checked with before it is written to the Table.  If the validator
+    /// returns an error, the write is rejected with that error and nothing is written
+    ///
+    /// Validators aren't stored in the database, so they must be added each time the Table is opened.
+    pub fn add_value_validator<F : Fn(&ConfigT::ValueT) -> Result<(), String> + Send + Sync + 'static>(&mut self, validator : F) {
+        self.validators.add_value_validator(Box::new(validator));
+    }
+
+    /// Sets the hook that decides which operations each tenant may perform through a [TenantTable] or [TenantTableMut],
+    /// replacing any hook that was set before
+    ///
+    /// The hook is called with the operation, the tenant supplied to [as_tenant](Table::as_tenant) or
+    /// [as_tenant_mut](Table::as_tenant_mut), and the key or record involved, and returns `true` to allow the
+    /// operation.  The hook only applies to the tenant views, so a service that serves several tenants should
+    /// make all of its queries and writes through them.  Like validators, the hook isn't stored in the database.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("access_hook_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let acme = table.insert("acme:Widget", &"Gear".to_string()).unwrap();
+    /// let globex = table.insert("globex:Widget", &"Lever".to_string()).unwrap();
+    ///
+    /// //Each tenant may only use keys with its own prefix, and only touch the records it owns
+    /// let owners = std::collections::HashMap::from([(acme, "acme"), (globex, "globex")]);
+    /// table.set_access_hook(move |_operation, tenant, target| match target {
+    ///     AccessTarget::Key(key) => key.iter().collect::<String>().starts_with(&format!("{}:", tenant)),
+    ///     AccessTarget::Record(record_id) => owners.get(&record_id) == Some(&tenant),
+    /// });
+    ///
+    /// let acme_table = table.as_tenant("acme");
+    /// assert_eq!(acme_table.lookup_fuzzy("acme:Widgat", Some(1)).unwrap(), vec![(acme, 1)]);
+    /// assert!(acme_table.lookup_exact("globex:Widget").is_err());
+    ///
+    /// assert!(acme_table.get_value(globex).is_err());
+    ///
+    /// let mut acme_table = table.as_tenant_mut("acme");
+    /// assert!(acme_table.insert("globex:Gadget", &"Spring".to_string()).is_err());
+    /// assert!(acme_table.delete(globex).is_err());
+    /// ```
+    pub fn set_access_hook<F : Fn(AccessOperation, &str, AccessTarget<'_, ConfigT::KeyCharT>) -> bool + Send + Sync + 'static>(&mut self, hook : F) {
+        self.access_hook = Some(Box::new(hook));
+    }
+
+    /// Returns a [TenantTable], which performs lookups and reads on behalf of `tenant`, checking each one with the
+    /// hook set by [set_access_hook](Table::set_access_hook)
+    pub fn as_tenant<'a>(&'a self, tenant : &'a str) -> TenantTable<'a, ConfigT, UTF8_KEYS> {
+        TenantTable::new(self, tenant)
+    }
+
+    /// Returns a [TenantTableMut], which performs writes on behalf of `tenant`, checking each one with the hook
+    /// set by [set_access_hook](Table::set_access_hook)
+    pub fn as_tenant_mut<'a>(&'a mut self, tenant : &'a str) -> TenantTableMut<'a, ConfigT, UTF8_KEYS> {
+        TenantTableMut::new(self, tenant)
+    }
+
+    pub(crate) fn access_hook(&self) -> Option<&AccessHook<ConfigT::KeyCharT>> {
+        self.access_hook.as_ref()
+    }
+
+    /// Returns the PID of the process that has the Table at `path` open for writing, or `None` if the Table
+    /// isn't locked
+    pub fn lock_owner(path : &str) -> Result<Option<u32>, String> {
+        lock_owner(&native_path(path)?)
+    }
+
+    /// Removes the lock that keeps two processes from opening the Table at `path` for writing at the same
+    /// time.  Returns `true` if there was a lock to remove
     /// 
-    /// WARNING:  No sanity checks are performed to ensure the database being opened matches the parameters
-    /// of the table being created.  Therefore you may see bugs if you are opening a table that was created
-    /// using a different set of parameters.
+    /// This is only needed to recover after the process that had the Table open crashed without releasing
+    /// the lock.  WARNING: Removing the lock from a Table that is still open in another process will let a
+    /// second process open it, and may corrupt the database.
+    pub fn force_unlock(path : &str) -> Result<bool, String> {
+        force_unlock(&native_path(path)?)
+    }
+
+    /// Returns the approximate number of times a record's value has been retrieved
     /// 
-    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
-    /// unwrapped RocksDB error.
-    pub fn new(path : &str, config : ConfigT) -> Result<Self, String> {
+    /// Always returns 0 unless [TRACK_ACCESS_STATS](TableConfig::TRACK_ACCESS_STATS) is set for the Table's config.
+    pub fn record_hits(&self, record_id : RecordID) -> Result<u64, String> {
+        let stored_hits = self.db.get_access_count(record_id)?;
+        Ok(stored_hits + self.access_stats.pending_hits(record_id))
+    }
 
-        //Make sure the Config agrees with the UTF8_KEYS const generic param
-        //NOTE: this check will be unnecessary when Rust lets us bound an impl by an associated constant
-        if UTF8_KEYS != ConfigT::UTF8_KEYS {
-            panic!("Config Error! UTF8_KEYS generic constant must match ConfigT");
+    /// Returns up to `n` records with the most hits, and their hit counts, in descending order of hits
+    /// 
+    /// This function scans the hit counts for every record, so it is intended for occasional use by an operator
+    /// rather than in the course of serving queries.
+    pub fn top_records(&self, n : usize) -> Result<Vec<(RecordID, u64)>, String> {
+        self.flush_access_stats()?;
+        let mut counts = self.db.access_counts()?;
+        counts.sort_unstable_by(|(a_id, a_hits), (b_id, b_hits)| b_hits.cmp(a_hits).then(a_id.cmp(b_id)));
+        counts.truncate(n);
+        Ok(counts)
+    }
+
+    /// Records the `n` most frequently accessed records in the preload manifest, replacing the previous manifest,
+    /// and returns the number of records recorded.  See [preload](Table::preload)
+    /// 
+    /// This happens automatically when the Table is closed, if [PRELOAD_MANIFEST_LEN](TableConfig::PRELOAD_MANIFEST_LEN)
+    /// is set.  Does nothing if `n` is 0, or if the Table isn't writable or doesn't track access stats.
+    pub fn save_preload_manifest(&self, n : usize) -> Result<usize, String> {
+        if n == 0 || !self.db.is_writable() || !ConfigT::TRACK_ACCESS_STATS {
+            return Ok(0);
         }
 
-        //Open the Database
-        let db = DBConnection::new(path)?;
+        let record_ids : Vec<RecordID> = self.top_records(n)?.into_iter().map(|(record_id, _hits)| record_id).collect();
+        self.db.put_metadata(PRELOAD_MANIFEST_METADATA_NAME, &record_ids)?;
+        Ok(record_ids.len())
+    }
 
-        //Find the next value for new RecordIDs, by probing the entries in the "rec_data" column family
-        let record_count = db.record_count()?;
+    /// Pins a record, so its keys and value are kept in memory, and reading them never needs to reach RocksDB
+    ///
+    /// Pinning is intended for the small number of records that are looked up far more often than the rest, to
+    /// give the head of the query distribution a predictable latency, regardless of what else has been evicted
+    /// from RocksDB's caches.  The pinned records are stored in the database, and their entries are loaded
+    /// again when the Table is opened.  The cached entries are updated as the record is changed, and a record
+    /// is unpinned when it's deleted.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("pin_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let london = table.insert("London", &"England".to_string()).unwrap();
+    /// table.pin(london).unwrap();
+    /// assert_eq!(table.pinned_records(), vec![london]);
+    ///
+    /// //Changes to a pinned record are reflected in its cached entries
+    /// table.replace_value(london, &"United Kingdom".to_string()).unwrap();
+    /// assert_eq!(table.get_value(london).unwrap(), "United Kingdom");
+    /// ```
+    pub fn pin(&mut self, record_id : RecordID) -> Result<(), String> {
+
+        let mut batch = DBWriteBatch::new();
+        if !self.db.record_is_live_in_batch(&batch, record_id)? {
+            return Err("Invalid record_id".to_string());
+        }
+        let mut pinned_records = self.db.get_pinned_records_in_batch(&batch)?;
+        if pinned_records.contains(&record_id) {
+            return Ok(());
+        }
+        pinned_records.push(record_id);
+        self.db.put_pinned_records(&mut batch, &pinned_records)?;
+        self.db.commit_batch(batch)
+    }
 
-        Ok(Self {
-            record_count,
-            config,
-            db,
-            deleted_records : vec![],
-            perf_counters : PerfCounters::new(),
-        })
+    /// Unpins a record pinned with [pin](Self::pin), so its entries are read from RocksDB again.  Does nothing
+    /// if the record isn't pinned
+    pub fn unpin(&mut self, record_id : RecordID) -> Result<(), String> {
+
+        let mut batch = DBWriteBatch::new();
+        self.unpin_internal(&mut batch, record_id)?;
+        self.db.commit_batch(batch)
     }
 
-    /// Resets a Table, dropping every record in the table and restoring it to an empty state.
-    /// 
-    /// (Dropping in a database sense, not a Rust sense)
-    pub fn reset(&mut self) -> Result<(), String> {
+    /// Returns the records that are pinned.  See [pin](Self::pin)
+    pub fn pinned_records(&self) -> Vec<RecordID> {
+        let mut record_ids : Vec<RecordID> = self.db.pinned_record_ids().into_iter().collect();
+        record_ids.sort();
+        record_ids
+    }
 
-        //Reset the database
-        self.db.reset_database()?;
+    /// Removes a record from the pinned records, if it's pinned
+    fn unpin_internal(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
 
-        //Reset the record_count, so newly inserted entries begin at 0 again
-        self.record_count = 0;
+        let mut pinned_records = self.db.get_pinned_records_in_batch(batch)?;
+        if let Some(idx) = pinned_records.iter().position(|pinned_id| *pinned_id == record_id) {
+            pinned_records.remove(idx);
+            self.db.put_pinned_records(batch, &pinned_records)?;
+        }
         Ok(())
     }
 
-    /// Deletes a record from the Table.
+    /// Returns the [RecordMetadata] of a record, or `None` if the record has none because it was created before
+    /// [RECORD_METADATA](TableConfig::RECORD_METADATA) was set
     /// 
-    /// A deleted record cannot be accessed or otherwise found.  All of the record's associated keys
-    /// and the associated value may be purged from the database. 
-    pub fn delete(&mut self, record_id : RecordID) -> Result<(), String> {
+    /// Returns an error if the Table doesn't keep record metadata, or if the record doesn't exist.
+    /// 
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// struct Config();
+    /// impl TableConfig for Config {
+    ///     type KeyCharT = char;
+    ///     type DistanceT = u8;
+    ///     type ValueT = String;
+    ///     const UTF8_KEYS : bool = true;
+    ///     const RECORD_METADATA : bool = true;
+    /// }
+    ///
+    /// let mut table = Table::<Config, true>::new("record_metadata_example.rocks", Config()).unwrap();
+    /// table.reset().unwrap();
+    /// let london = table.insert("London", &"England".to_string()).unwrap();
+    /// table.set_tag(london, Some("import")).unwrap();
+    ///
+    /// let metadata = table.get_metadata(london).unwrap().unwrap();
+    /// assert_eq!(metadata.tag.as_deref(), Some("import"));
+    /// assert_eq!(table.records_modified_since(metadata.created).unwrap(), vec![london]);
+    /// ```
+    pub fn get_metadata(&self, record_id : RecordID) -> Result<Option<RecordMetadata>, String> {
+        self.check_record_metadata(record_id)?;
+        self.db.get_record_metadata(record_id)
+    }
 
-        self.delete_keys_internal(record_id)?;
-        self.db.delete_value(record_id)?;
-        self.deleted_records.push(record_id);
+    /// Sets or clears the tag in a record's [RecordMetadata].  See [get_metadata](Self::get_metadata)
+    pub fn set_tag(&mut self, record_id : RecordID, tag : Option<&str>) -> Result<(), String> {
 
-        Ok(())
+        self.check_record_metadata(record_id)?;
+        let mut batch = DBWriteBatch::new();
+        let mut metadata = self.db.get_record_metadata_in_batch(&batch, record_id)?.unwrap_or_else(RecordMetadata::new_now);
+        metadata.tag = tag.map(|tag| tag.to_string());
+        self.db.put_record_metadata(&mut batch, record_id, &metadata)?;
+        self.db.commit_batch(batch)
     }
 
-    /// Deletes all of the keys belonging to a record, and all associated variants
+    /// Returns the records that were created or modified at or after the supplied time, in milliseconds since the
+    /// Unix epoch, in order of RecordID.  See [RECORD_METADATA](TableConfig::RECORD_METADATA)
     /// 
-    /// Leaves the record in a half-composed state, so should only be called as part of another
-    /// operation.
-    fn delete_keys_internal(&mut self, record_id : RecordID) -> Result<(), String> {
+    /// The times only have millisecond resolution, so a record changed in the same millisecond as `since` is
+    /// returned, even if the change was already seen by the sync that took place at `since`.  So a sync should
+    /// pass the time it took the previous sync's snapshot, and expect to see some of the records it saw then
+    /// again, rather than passing a later time and missing the changes made in the same millisecond.
+    /// 
+    /// This reads every entry in the "record_meta" column family, so its cost grows with the number of records,
+    /// not the number of changes.
+    /// 
+    /// NOTE: A record's metadata is deleted along with the record, so deleted records aren't returned.  Compare
+    /// the records known from a previous sync against the Table to find those.
+    pub fn records_modified_since(&self, since : u64) -> Result<Vec<RecordID>, String> {
+        if !ConfigT::RECORD_METADATA {
+            return Err("the Table doesn't keep RECORD_METADATA".to_string());
+        }
+        Ok(self.db.all_record_metadata()?.into_iter()
+            .filter(|(_record_id, metadata)| metadata.modified >= since)
+            .map(|(record_id, _metadata)| record_id)
+            .collect())
+    }
 
-        //Get all of the key-groups belonging to the record
-        for key_group in self.db.get_record_key_groups(record_id)? {
+    /// Checks that the Table keeps record metadata, and that the record exists
+    fn check_record_metadata(&self, record_id : RecordID) -> Result<(), String> {
+        if !ConfigT::RECORD_METADATA {
+            return Err("the Table doesn't keep RECORD_METADATA".to_string());
+        }
+        if !self.db.record_is_live_in_batch(&DBWriteBatch::new(), record_id)? {
+            return Err("Invalid record_id".to_string());
+        }
+        Ok(())
+    }
 
-            //Get all the keys for the group we're removing, so we can compute all the variants
-            let keys_iter = self.db.get_keys_in_group::<<Self as TableKeyEncoding>::OwnedKeyT>(key_group, &self.perf_counters)?;
-            let mut variants = HashSet::new();
-            for key in keys_iter {
-                let key_variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS >::variants(&key, &self.config);
-                variants.extend(key_variants);
-            }
+    /// Updates a record's metadata to record a change composed in the batch, if the Table keeps
+    /// [RECORD_METADATA](TableConfig::RECORD_METADATA), or has a [TTL](TableConfig::TTL), which counts from the
+    /// `modified` time.  A new record gets new metadata
+    fn touch_record_metadata(&self, batch : &mut DBWriteBatch, record_id : RecordID, created : bool) -> Result<(), String> {
 
-            //Remove the variants' reference to this key group
-            self.db.delete_variant_references(key_group, variants)?;
-            
-            //Delete the key group entry in the table
-            self.db.delete_key_group_entry(key_group)?;
+        if !ConfigT::RECORD_METADATA && ConfigT::TTL.is_none() {
+            return Ok(());
         }
+        let metadata = match self.db.get_record_metadata_in_batch(batch, record_id)? {
+            Some(mut metadata) if !created => {
+                metadata.modified = now_millis().max(metadata.modified);
+                metadata
+            },
+            _ => RecordMetadata::new_now()
+        };
+        self.db.put_record_metadata(batch, record_id, &metadata)
+    }
 
-        //Now replace the key groups vec in the "rec_data" table with an empty sentinel vec
-        //NOTE: We replace the record rather than delete it because we assume there are no gaps in the
-        // RecordIDs, when assigning new a RecordID
-        self.db.put_record_key_groups(record_id, &[])?;
+    /// Adds a group of equivalent terms, e.g. `["st", "saint"]`, so a fuzzy lookup for a key containing any one of
+    /// them also finds the keys containing the others.  Each term must be a single token.  See [SYNONYMS](TableConfig::SYNONYMS)
+    /// 
+    /// The terms are added to the synonyms each one already has, so the same term may belong to several groups,
+    /// e.g. `["st", "street"]` as well.  Terms are only equivalent to the other terms in a group they were added
+    /// with, so "saint" and "street" wouldn't be equivalent.  Any cached lookups are discarded, so they're
+    /// performed again with the new synonyms.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// struct Config();
+    /// impl TableConfig for Config {
+    ///     type KeyCharT = char;
+    ///     type DistanceT = u8;
+    ///     type ValueT = String;
+    ///     const UTF8_KEYS : bool = true;
+    ///     const SYNONYMS : bool = true;
+    /// }
+    ///
+    /// let mut table = Table::<Config, true>::new("synonyms_example.rocks", Config()).unwrap();
+    /// table.reset().unwrap();
+    /// let saint_louis = table.insert("Saint Louis", &"Missouri".to_string()).unwrap();
+    /// table.add_synonyms(&["St", "Saint"]).unwrap();
+    ///
+    /// let results : Vec<RecordID> = table.lookup_fuzzy("St Louis", Some(1)).unwrap().into_vec().unwrap().into_iter().map(|(record_id, _distance)| record_id).collect();
+    /// assert_eq!(results, vec![saint_louis]);
+    /// ```
+    pub fn add_synonyms(&mut self, terms : &[&str]) -> Result<(), String> {
+
+        let terms = self.synonym_terms(terms)?;
+        let mut batch = DBWriteBatch::new();
+        for term in terms.iter() {
+            let mut synonyms = self.db.get_synonyms_in_batch(&batch, term)?;
+            for other_term in terms.iter() {
+                if other_term != term && !synonyms.contains(other_term) {
+                    synonyms.push(other_term.clone());
+                }
+            }
+            self.db.put_synonyms(&mut batch, term, &synonyms)?;
+        }
+        self.db.commit_batch(batch)?;
+        self.query_cache.clear();
+        Ok(())
+    }
+
+    /// Removes a group of terms added with [add_synonyms](Self::add_synonyms), so none of them is a synonym of
+    /// another anymore.  Any synonyms the terms have outside the group are left alone
+    pub fn remove_synonyms(&mut self, terms : &[&str]) -> Result<(), String> {
 
+        let terms = self.synonym_terms(terms)?;
+        let mut batch = DBWriteBatch::new();
+        for term in terms.iter() {
+            let mut synonyms = self.db.get_synonyms_in_batch(&batch, term)?;
+            synonyms.retain(|synonym| !terms.contains(synonym));
+            self.db.put_synonyms(&mut batch, term, &synonyms)?;
+        }
+        self.db.commit_batch(batch)?;
+        self.query_cache.clear();
         Ok(())
     }
 
-    /// Divides the keys up into key groups and assigns them to a record.
-    /// 
-    /// Should NEVER be called on a record that already has keys or orphaned database entries will result
-    fn put_record_keys<'a, K, KeysIterT : Iterator<Item=&'a K>>(&mut self, record_id : RecordID, keys_iter : KeysIterT, num_keys : usize) -> Result<(), String>
-        where
-        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
-    {
-    
-        //Make groups for the keys
-        let groups = KeyGroups::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::make_groups_from_keys(keys_iter, num_keys, &self.config).unwrap();
-        let num_groups = groups.key_group_keys.len();
+    /// Returns the synonyms of a term, in the order they were added
+    pub fn synonyms(&self, term : &str) -> Result<Vec<String>, String> {
+        let terms = self.synonym_terms(&[term])?;
+        self.db.get_synonyms(&terms[0])
+    }
 
-        //Put the variants for each group into the right table
-        for (idx, variant_set) in groups.key_group_variants.into_iter().enumerate() {
-            let key_group_id = KeyGroupID::from_record_and_idx(record_id, idx); 
-            self.db.put_variant_references(key_group_id, variant_set)?;
+    /// Checks that synonyms are enabled and that each term is a single token, and returns the distinct terms
+    /// folded the way the Table folds keys
+    fn synonym_terms(&self, terms : &[&str]) -> Result<Vec<String>, String> {
+
+        if !ConfigT::SYNONYMS {
+            return Err("the Table doesn't have SYNONYMS enabled".to_string());
         }
-        
-        //Put the keys for each group into the table
-        for (idx, key_set) in groups.key_group_keys.into_iter().enumerate() {
-            let key_group_id = KeyGroupID::from_record_and_idx(record_id, idx); 
-            self.db.put_key_group_entry(key_group_id, &key_set)?;
+        let mut folded_terms : Vec<String> = vec![];
+        for term in terms {
+            let folded_term = self.params.fold_key(term.to_string());
+            if folded_term.split_whitespace().count() != 1 || folded_term.trim() != folded_term {
+                return Err(format!("synonym \"{}\" isn't a single token", term));
+            }
+            if !folded_terms.contains(&folded_term) {
+                folded_terms.push(folded_term);
+            }
         }
+        Ok(folded_terms)
+    }
 
-        //Put the key group record into the rec_data table
-        let group_indices : Vec<usize> = (0..num_groups).into_iter().collect();
-        self.db.put_record_key_groups(record_id, &group_indices[..])
+    /// Writes any hit counts that are accumulated in memory to the database
+    /// 
+    /// This happens periodically as hits are counted, and when the Table is dropped, so there is usually
+    /// no need to call this function directly.
+    pub fn flush_access_stats(&self) -> Result<(), String> {
+        self.access_stats.flush(&self.db)
     }
 
-    /// Add additional keys to a record, including creation of all associated variants
-    fn add_keys_internal<'a, K, KeysIterT : Iterator<Item=&'a K>>(&mut self, record_id : RecordID, keys_iter : KeysIterT, num_keys : usize) -> Result<(), String>
-        where
-        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
-    {
+    /// Resets the hit counts for every record to zero
+    pub fn reset_access_stats(&mut self) -> Result<(), String> {
+        self.access_stats.reset();
+        self.db.clear_access_counts()
+    }
 
-        //Get the record's existing key groups and variants, so we can figure out the
-        //best places for each additional new key
-        let mut groups = KeyGroups::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::load_key_groups(&self.db, record_id, &self.config, &self.perf_counters)?;
+    /// Returns the counts of the times RocksDB has invoked the "variants" CF merge operator since the Table
+    /// was opened, along with a log of the unexpected invocations if [MERGE_DIAGNOSTIC_LOG_LEN](TableConfig::MERGE_DIAGNOSTIC_LOG_LEN)
+    /// is set
+    pub fn merge_diagnostics(&self) -> MergeDiagnostics {
+        self.db.merge_diagnostics()
+    }
 
-        //Clone the existing groups, so we can determine which variants were added where
-        let existing_groups_variants = groups.key_group_variants.clone();
+    /// Clears the counts and log returned by [merge_diagnostics](Self::merge_diagnostics)
+    pub fn reset_merge_diagnostics(&self) {
+        self.db.reset_merge_diagnostics()
+    }
 
-        //Go over each key and add it to the key groups,
-        // This add_key_to_groups function encapsulates the logic to add each key to
-        // the correct group or create a new group
-        for (key_idx, key) in keys_iter.enumerate() {
-            let update_reverse_index = key_idx < num_keys-1;
-            groups.add_key_to_groups(key, update_reverse_index, &self.config)?;
+    /// Closes the Table, writing any pending hit counts and in-memory data to the database, and returns any
+    /// error encountered along the way
+    /// 
+    /// Dropping a Table also closes it, but errors are discarded.  Either way, the database persists on disk,
+    /// and may be opened again with [new](Self::new).
+    pub fn close(self) -> Result<(), String> {
+        self.access_stats.flush(&self.db)?;
+        self.save_preload_manifest(ConfigT::PRELOAD_MANIFEST_LEN)?;
+        self.db.flush()
+    }
+
+    /// Backs up the Table to a new directory at `path`, which must not already exist, without stopping writes
+    /// 
+    /// The backup is a RocksDB checkpoint, i.e. a consistent copy of the database as of the moment it was taken.
+    /// Where possible its files are hard-linked to the Table's own files rather than copied, so a backup on the
+    /// same file system is fast.  The backup is a complete database, and can be opened as a Table with
+    /// [new](Self::new), using the same config.
+    /// 
+    /// Hit counts accumulated in memory are written to the database first, so they are included.
+    pub fn create_backup(&self, path : &str) -> Result<(), String> {
+        self.access_stats.flush(&self.db)?;
+        self.db.create_checkpoint(path)
+    }
+
+    /// Writes the Table's entries to SST files in the directory at `dir`, which is created if it doesn't exist,
+    /// one file for each column family.  Returns the number of files written
+    /// 
+    /// The files hold a consistent copy of the Table as of the moment the export started, and writes may carry
+    /// on while it runs.  A new Table can be created from them with [create_from_sst](Self::create_from_sst).
+    pub fn export_sst(&self, dir : &str) -> Result<usize, String> {
+        self.access_stats.flush(&self.db)?;
+        let dir = native_path(dir)?;
+        std::fs::create_dir_all(&dir).map_err(|err| format!("couldn't create directory {}: {}", dir, err))?;
+        self.db.export_sst_files(&dir)
+    }
+
+    /// Copies the Table, with all of its records, values, and index, to a new database at `path`, which must not
+    /// already exist.  Writes may carry on while the copy is made
+    /// 
+    /// The copy is independent of the Table, and can be opened with [new](Self::new), using the same config.
+    /// A Table in its own database is copied with a RocksDB checkpoint, so on the same file system the copy's
+    /// files are hard-linked to the Table's own until either moves on.  A Table in a [SharedDatabase] is
+    /// exported to SST files, which are ingested into the new database, so the copy holds only that Table.
+    /// 
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let _ = std::fs::remove_dir_all("copy_to_example_copy.rocks");
+    /// let mut table = Table::<DefaultTableConfig, true>::new("copy_to_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let record_id = table.insert("Hello", &"Greeting".to_string()).unwrap();
+    /// table.copy_to("copy_to_example_copy.rocks").unwrap();
+    ///
+    /// let copy = Table::<DefaultTableConfig, true>::new("copy_to_example_copy.rocks", DefaultTableConfig()).unwrap();
+    /// assert_eq!(copy.get_value(record_id).unwrap(), "Greeting");
+    /// copy.destroy().unwrap();
+    /// ```
+    pub fn copy_to(&self, path : &str) -> Result<(), String> {
+
+        self.access_stats.flush(&self.db)?;
+        let copy_dir = native_path(path)?;
+        if std::path::Path::new(&copy_dir).exists() {
+            return Err(format!("can't copy the Table to {}, because it already exists", path));
+        }
+        if !self.db.is_shared() {
+            return self.db.create_checkpoint(path);
         }
 
-        //Go over each group, work out the variants we need to add, then add them and update the group
-        let empty_set = HashSet::new();
-        for (group_idx, keys_set) in groups.key_group_keys.iter().enumerate() {
+        //The SST files are staged in a directory alongside the copy, and removed once they have been ingested
+        let sst_dir = native_path(&format!("{}.sst", path))?;
+        std::fs::create_dir_all(&sst_dir).map_err(|err| format!("couldn't create directory {}: {}", sst_dir, err))?;
+        let result = self.db.export_sst_files(&sst_dir).and_then(|_file_count| {
+            let copy_db = DBConnection::new(path, Self::db_config())?;
+            copy_db.ingest_sst_files(&sst_dir)?;
+            copy_db.flush_and_sync()
+        });
+        let _ = std::fs::remove_dir_all(&sst_dir);
+
+        //A half-built copy is removed, so it doesn't stand in the way of another attempt
+        if result.is_err() {
+            let _ = std::fs::remove_dir_all(&copy_dir);
+        }
+        result
+    }
 
-            //Get the variants for the group, as it existed prior to adding any keys
-            let existing_keys_variants = match existing_groups_variants.get(group_idx) {
-                Some(variant_set) => variant_set,
-                None => &empty_set
-            };
+    /// Adds a backup of the Table to the backup directory at `backup_dir`, which is created if it doesn't exist,
+    /// and returns a description of the new backup.  Writes may carry on while the backup is taken
+    /// 
+    /// The backups are kept with RocksDB's BackupEngine, so only the database files that aren't already in
+    /// the directory are copied, and rolling backups of a large Table are cheap.  Old backups can be discarded
+    /// with [purge_old_backups](Self::purge_old_backups), and any backup can be restored with
+    /// [restore_from_backup](Self::restore_from_backup).
+    pub fn backup_incremental(&self, backup_dir : &str) -> Result<BackupInfo, String> {
+        self.access_stats.flush(&self.db)?;
+        let mut engine = open_backup_engine(&native_path(backup_dir)?)?;
+        let existing_backups = engine_backups(&engine);
+        self.db.create_engine_backup(&mut engine)?;
+
+        //NOTE: Another process may add backups to the same directory, but they aren't seen by this engine
+        engine_backups(&engine).into_iter()
+            .find(|info| !existing_backups.iter().any(|existing| existing.backup_id == info.backup_id))
+            .ok_or_else(|| "the backup wasn't recorded".to_string())
+    }
 
-            //Calculate the set of variants that is unique to the keys we'll be adding
-            let mut unique_keys_variants = HashSet::new();
-            for unique_keys_variant in groups.key_group_variants[group_idx].difference(existing_keys_variants) {
-                unique_keys_variants.insert(unique_keys_variant.to_owned());
-            }
+    /// Returns the backups in the backup directory at `backup_dir`, oldest first
+    pub fn list_backups(backup_dir : &str) -> Result<Vec<BackupInfo>, String> {
+        list_backups(&native_path(backup_dir)?)
+    }
 
-            //Add the key_group_id to the appropriate entries for each of the new variants
-            let key_group_id = KeyGroupID::from_record_and_idx(record_id, groups.group_ids[group_idx]);
-            self.db.put_variant_references(key_group_id, unique_keys_variants)?;
+    /// Deletes all but the newest `keep` backups in the backup directory at `backup_dir`
+    pub fn purge_old_backups(backup_dir : &str, keep : usize) -> Result<(), String> {
+        purge_old_backups(&native_path(backup_dir)?, keep)
+    }
 
-            //Add the new keys to the key group's entry in the keys table by replacing the keys vector
-            // with the superset
-            self.db.put_key_group_entry(key_group_id, keys_set)?;
+    /// Replaces the Table at `path` with the backup identified by `backup_id`, from the backup directory at
+    /// `backup_dir`.  The Table must not be open
+    /// 
+    /// The backup's files are checked before anything is replaced, and the restored Table can then be opened
+    /// with [new](Self::new), using the same config as the Table that was backed up.  WARNING: Everything in
+    /// the Table written since the backup was taken is lost.
+    pub fn restore_from_backup(backup_dir : &str, path : &str, backup_id : u32) -> Result<(), String> {
+        restore_from_backup(&native_path(backup_dir)?, &native_path(path)?, backup_id)
+    }
+
+    /// Closes the Table and deletes its database, along with all of its files
+    /// 
+    /// A Table in a [SharedDatabase] can't be destroyed this way, because the database holds the other Tables
+    /// too.  Use [drop_table](SharedDatabase::drop_table) instead.
+    pub fn destroy(self) -> Result<(), String> {
+        if self.db.is_shared() {
+            return Err("a Table in a SharedDatabase must be deleted with SharedDatabase::drop_table".to_string());
         }
+        let path = self.path().to_string();
+        drop(self);
+        destroy_database(&path)
+    }
 
-        //Put the new rec_data entry, to reflect all the associated key groups
-        self.db.put_record_key_groups(record_id, &groups.group_ids[..])
+    /// Returns the RecordID the next new record will be given, unless [DETERMINISTIC_RECORD_IDS](TableConfig::DETERMINISTIC_RECORD_IDS)
+    /// is set.  Reuses a deleted record if we have one, otherwise returns the next unique RecordID
+    pub(crate) fn next_record_id(&self) -> RecordID {
+        self.deleted_records.last().copied().unwrap_or_else(|| RecordID::from(self.record_count))
     }
 
-    /// Removes the specified keys from the keys associated with a record
-    /// 
-    /// If one of the specified keys is not associated with the record then that specified
-    /// key will be ignored.
-    fn remove_keys_internal<K>(&mut self, record_id : RecordID, remove_keys : &HashSet<&K>) -> Result<(), String>
-        where
-        K : Key<KeyCharT = ConfigT::KeyCharT>
-    {
+    /// Claims the RecordID returned by [insert_in_batch](Self::insert_in_batch), so it won't be assigned
+    /// to another record
+    pub(crate) fn claim_record_id(&mut self, record_id : RecordID) {
+        if ConfigT::DETERMINISTIC_RECORD_IDS {
+            return;
+        }
+        if self.deleted_records.last() == Some(&record_id) {
+            self.deleted_records.pop();
+        } else {
+            self.record_count += 1;
+        }
+    }
 
-        //Get all of the existing groups
-        let group_ids : Vec<KeyGroupID> = self.db.get_record_key_groups(record_id)?.collect();
+    /// Discards the hit count a deleted record accumulated in memory.  Called once the record's deletion is
+    /// committed, so the count isn't lost if the commit fails
+    pub(crate) fn forget_access_count(&self, record_id : RecordID) {
+        self.access_stats.forget(record_id);
+    }
 
-        //Go through each existing group, and build a HashSet containing the keys that
-        // we will delete and the keys that will remain after the removal
-        let mut remaining_key_count = 0;
-        let mut deleted_group_keys_sets = vec![];
-        let mut remaining_group_keys_sets = vec![];
-        for key_group in group_ids.iter() {
-            let mut deleted_keys = HashSet::new();
-            let mut remaining_keys = HashSet::new();
-            for existing_key in self.db.get_keys_in_group::<<Self as TableKeyEncoding>::OwnedKeyT>(*key_group, &self.perf_counters)? {
+    /// Records that a RecordID is free to be reused, after the record has been deleted
+    pub(crate) fn release_record_id(&mut self, record_id : RecordID) {
+        if ConfigT::DETERMINISTIC_RECORD_IDS {
+            return;
+        }
+        self.deleted_records.push(record_id);
+    }
 
-                //NOTE: We know this is safe because the unsafety comes from the fact that
-                // query_key might borrow existing_key, which is temporary, while query_key's
-                // lifetime is 'a, which is beyond this function.  However, query_key doesn't
-                // outlast this unsafe scope and we know HashSet::contains won't hold onto it.
-                let set_contains_key = unsafe {
-                    let query_key = K::from_owned_unsafe(&existing_key);
-                    remove_keys.contains(&query_key)
-                };
-                
-                //TODO: When GenericAssociatedTypes is stabilized, I will remove the KeyUnsafe trait in favor of an associated type
-                // let query_key = K::new_borrowed_from_owned(&existing_key);
-                // let set_contains_key = remove_keys.contains(&query_key);
-                
-                if !set_contains_key {
-                    remaining_keys.insert(existing_key);
-                    remaining_key_count += 1;
-                } else {
-                    deleted_keys.insert(existing_key);
-                }
+    /// Claims `count` RecordIDs for new records, and writes each of them as a deleted record, as a single atomic
+    /// write, so the RecordIDs in the database have no gaps, however many of the records are then created
+    ///
+    /// If the write fails, the RecordIDs are released again.
+    pub(crate) fn reserve_record_ids(&mut self, count : usize) -> Result<Vec<RecordID>, String> {
+
+        let mut record_ids = Vec::with_capacity(count);
+        let mut batch = DBWriteBatch::new();
+        for _ in 0..count {
+            let record_id = self.next_record_id();
+            self.claim_record_id(record_id);
+            record_ids.push(record_id);
+            self.db.put_record_key_groups(&mut batch, record_id, &[])?;
+        }
+        if let Err(err) = self.db.commit_batch(batch) {
+            for record_id in record_ids.iter().rev() {
+                self.release_record_id(*record_id);
             }
-            deleted_group_keys_sets.push(deleted_keys);
-            remaining_group_keys_sets.push(remaining_keys);
+            return Err(err);
         }
+        Ok(record_ids)
+    }
 
-        //If we're left with no remaining keys, we should throw an error because all records must
-        //have at least one key
-        if remaining_key_count < 1 {
-            return Err("cannot remove all keys from record".to_string());
-        }
+    /// Returns the state used to assign RecordIDs, so it can be restored if a [Transaction](crate::Transaction)
+    /// is rolled back
+    pub(crate) fn record_id_state(&self) -> (usize, Vec<RecordID>) {
+        (self.record_count, self.deleted_records.clone())
+    }
 
-        //Go through each group and update its keys and variants, or remove the group altogether
-        let mut remaining_group_indices = vec![];
-        for (idx, group_id) in group_ids.into_iter().enumerate() {
+    /// Restores the state returned by [record_id_state](Self::record_id_state)
+    pub(crate) fn restore_record_id_state(&mut self, state : (usize, Vec<RecordID>)) {
+        let (record_count, deleted_records) = state;
+        self.record_count = record_count;
+        self.deleted_records = deleted_records;
+    }
 
-            //If we didn't remove any keys from this group, there is nothing to do here.
-            if deleted_group_keys_sets[idx].is_empty() {
-                remaining_group_indices.push(group_id.group_idx());
-                continue;
-            }
+    /// Applies the writes made by the primary instance, if the Table was opened as a secondary instance
+    pub(crate) fn catch_up_with_primary(&self) -> Result<(), String> {
+        self.query_cache.clear();
+        self.db.catch_up_with_primary()
+    }
+
+    /// Commits a batch composed by a [Transaction](crate::Transaction), or by [create_parallel](Self::create_parallel)
+    ///
+    /// The cached queries are invalidated as the batch is composed, but a lookup made before the batch is committed
+    /// can cache results without its writes, so the whole query cache is cleared once the batch is committed.
+    pub(crate) fn commit_batch(&self, batch : DBWriteBatch) -> Result<(), String> {
+        self.db.commit_batch(batch)?;
+        self.query_cache.clear();
+        Ok(())
+    }
+
+    /// Puts the Table into bulk-load mode, to insert a large number of records quickly
+    /// 
+    /// Until [finish_bulk_load](Self::finish_bulk_load) is called, automatic compactions are stopped, writes skip
+    /// RocksDB's write-ahead log, and the references from each variant to the new records are collected in
+    /// memory, rather than merged into the "variants" CF one at a time.  When the bulk load is finished, the
+    /// variant entries are written in large batches, in order.
+    /// 
+    /// Lookups made during a bulk load won't find the records inserted during it.  If the Table is shut
+    /// down with [shutdown](Self::shutdown) during a bulk load, the bulk load is finished first, and so it is if
+    /// the Table is dropped, although then an error finishing it can only be reported on stderr.  If the process
+    /// crashes, everything written since the bulk load began may be lost.  References to paged
+    /// variant entries, when [VARIANT_PAGE_SIZE](TableConfig::VARIANT_PAGE_SIZE) is set, are written as usual.
+    pub fn begin_bulk_load(&mut self) -> Result<(), String> {
+        self.db.begin_bulk_load()
+    }
+
+    /// Finishes a bulk load begun with [begin_bulk_load](Self::begin_bulk_load), writing out the variant
+    /// references collected in memory, and flushing everything written during the bulk load to the database
+    /// files.  Returns the number of variant entries written
+    pub fn finish_bulk_load(&mut self) -> Result<usize, String> {
+        let variant_count = self.db.finish_bulk_load()?;
+
+        //Lookups cached during the bulk load couldn't see the records inserted during it
+        self.query_cache.clear();
+
+        Ok(variant_count)
+    }
+}
+
+/// The implementation of the shared parts of Table, that are the same regardless of UTF8_KEYS
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Self : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Creates a new Table, backed by the database at the path provided
+    /// 
+    /// The [TableParams] are stored in the database when it's created, and an existing database is searched with
+    /// the params it was built with, e.g. its [MAX_DELETES](TableConfig::MAX_DELETES), rather than the config's.
+    /// The settings that can't be reconciled that way are checked instead, and an error is returned if the config's
+    /// [PHONETIC_FUNCTION](TableConfig::PHONETIC_FUNCTION), [TOKEN_INDEX](TableConfig::TOKEN_INDEX),
+    /// [VARIANT_SCHEME](TableConfig::VARIANT_SCHEME), [CHECKSUMS](TableConfig::CHECKSUMS), or [cipher](TableConfig::cipher)
+    /// doesn't match the database, or if the config sets [INLINE_VALUE_MAX_SIZE](TableConfig::INLINE_VALUE_MAX_SIZE)
+    /// to 0 for a database that may have values stored inline.  A database written in a different on-disk format
+    /// (See [migrate](Self::migrate)), one that uses grapheme clusters without the `unicode-segmentation` feature, and
+    /// one with an n-gram length of 0 are refused too, as is a config that asks for a phonetic index, a token index,
+    /// or synonyms without [UTF8_KEYS](TableConfig::UTF8_KEYS).
+    /// 
+    /// Only one Table may have a database open at a time.  If another Table, in this process or any other,
+    /// already has the database open, the error names the process that owns it.  See [force_unlock](Self::force_unlock).
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn new(path : &str, config : ConfigT) -> Result<Self, String> {
+
+        //Make sure the Config agrees with the UTF8_KEYS const generic param
+        //NOTE: this check will be unnecessary when Rust lets us bound an impl by an associated constant
+        if UTF8_KEYS != ConfigT::UTF8_KEYS {
+            panic!("Config Error! UTF8_KEYS generic constant must match ConfigT");
+        }
+
+        //Open the Database
+        let db = DBConnection::new(path, Self::db_config())?;
+
+        Self::with_db(db, config, ConfigT::TRACK_ACCESS_STATS)
+    }
+
+    /// Opens the Table with the specified name in a [SharedDatabase], creating it if the SharedDatabase doesn't
+    /// have a Table with that name yet
+    /// 
+    /// Each Table in a SharedDatabase is independent of the others, and may have a different config, but a named
+    /// Table may only be open once at a time.  The same WARNING as for [new](Self::new) applies: the config must
+    /// match the config the Table was created with.  See [SharedDatabase] for how the Tables share the database.
+    pub fn open_shared(shared : &SharedDatabase, name : &str, config : ConfigT) -> Result<Self, String> {
+
+        if UTF8_KEYS != ConfigT::UTF8_KEYS {
+            panic!("Config Error! UTF8_KEYS generic constant must match ConfigT");
+        }
+
+        let db = DBConnection::open_shared(&shared.db, name, Self::db_config())?;
+
+        Self::with_db(db, config, ConfigT::TRACK_ACCESS_STATS)
+    }
+
+    /// Creates a new Table at `path` from the SST files written by [export_sst](Self::export_sst), which must
+    /// not already have a database
+    /// 
+    /// This is much faster than inserting the records, because the files are added to the database as they are,
+    /// so an index can be built on one machine, exported, and dropped into place on another.  The SST files are
+    /// copied, so they can be used again.  The config must match the config of the Table that was exported.
+    pub fn create_from_sst(path : &str, sst_dir : &str, config : ConfigT) -> Result<Self, String> {
+
+        if UTF8_KEYS != ConfigT::UTF8_KEYS {
+            panic!("Config Error! UTF8_KEYS generic constant must match ConfigT");
+        }
+        if std::path::Path::new(&native_path(path)?).exists() {
+            return Err(format!("can't create a Table from SST files at {}, because it already exists", path));
+        }
+
+        //Remove the partly built database if anything fails, so the Table can be created again
+        let result = DBConnection::new(path, Self::db_config()).and_then(|db| {
+            match db.ingest_sst_files(&native_path(sst_dir)?)? {
+                0 => Err(format!("there are no SST files in {}", sst_dir)),
+                _ => Self::with_db(db, config, ConfigT::TRACK_ACCESS_STATS)
+            }
+        });
+        if result.is_err() {
+            let _ = std::fs::remove_dir_all(native_path(path)?);
+        }
+        result
+    }
+
+    /// Opens the Table backed by an existing database at the path provided, without taking the database's
+    /// write lock, so several processes may open the same Table at once
+    /// 
+    /// The returned [ReadOnlyTable] provides all of the Table's lookup and retrieval methods, but none of the
+    /// methods that modify the Table.  Hits are not counted, even if [TRACK_ACCESS_STATS](TableConfig::TRACK_ACCESS_STATS)
+    /// is set, and corrupt entries are never quarantined.
+    /// 
+    /// The same WARNING as for [new](Self::new) applies: the config must match the config the database was
+    /// created with.
+    pub fn open_read_only(path : &str, config : ConfigT) -> Result<ReadOnlyTable<ConfigT, UTF8_KEYS>, String> {
+
+        if UTF8_KEYS != ConfigT::UTF8_KEYS {
+            panic!("Config Error! UTF8_KEYS generic constant must match ConfigT");
+        }
+
+        let db = DBConnection::open_read_only(path, Self::db_config(), &COLUMN_FAMILY_NAMES)?;
+
+        Ok(ReadOnlyTable::new(Self::with_db(db, config, false)?))
+    }
+
+    /// Opens the Table backed by an existing database read-only, like [open_read_only](Self::open_read_only),
+    /// but only opens the named column families
+    /// 
+    /// This is meant for auxiliary tools that only need part of the Table, e.g. the "values" column family to
+    /// export the values, or the "variants" column family to gather index statistics.  The column families that
+    /// aren't needed are never loaded, which saves memory and startup time on a large Table.  See the
+    /// [crate documentation](crate) for the column families and what they hold.  The "rec_data", "metadata", and
+    /// "tombstones" column families are always opened, because the Table can't be opened without them, although
+    /// a database created before soft deletes were added has no "tombstones" column family, and is treated as
+    /// having no soft deleted records.
+    /// 
+    /// Any method that needs a column family that wasn't opened returns an error naming it.
+    /// 
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("column_families_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let record_id = table.insert("Hello", &"Greeting".to_string()).unwrap();
+    /// drop(table);
+    ///
+    /// let table = Table::<DefaultTableConfig, true>::open_read_only_column_families("column_families_example.rocks", DefaultTableConfig(), &["values"]).unwrap();
+    /// assert_eq!(table.get_value(record_id).unwrap(), "Greeting");
+    /// assert!(table.lookup_exact("Hello").is_err());
+    /// ```
+    pub fn open_read_only_column_families(path : &str, config : ConfigT, column_families : &[&str]) -> Result<ReadOnlyTable<ConfigT, UTF8_KEYS>, String> {
+
+        if UTF8_KEYS != ConfigT::UTF8_KEYS {
+            panic!("Config Error! UTF8_KEYS generic constant must match ConfigT");
+        }
+
+        let db = DBConnection::open_read_only(path, Self::db_config(), column_families)?;
+
+        Ok(ReadOnlyTable::new(Self::with_db(db, config, false)?))
+    }
+
+    /// Opens the Table backed by the database at `primary_path` as a RocksDB secondary instance, so it can
+    /// follow the writes made by another process that has the Table open with [new](Self::new)
+    /// 
+    /// The secondary instance keeps its own log files in `secondary_path`, which must be distinct for each
+    /// secondary instance.  Call [catch_up_with_primary](ReadOnlyTable::catch_up_with_primary) to see the
+    /// writes made by the primary since the Table was opened.  Otherwise the returned [ReadOnlyTable] behaves
+    /// the same as one returned by [open_read_only](Self::open_read_only).
+    /// 
+    /// WARNING: RocksDB can't catch a secondary instance up with a primary that has called [reset](Self::reset)
+    /// since the primary was opened, and may crash if asked to.  Reopen the primary after resetting it, before
+    /// any secondary instances are opened or caught up.
+    pub fn open_as_secondary(primary_path : &str, secondary_path : &str, config : ConfigT) -> Result<ReadOnlyTable<ConfigT, UTF8_KEYS>, String> {
+
+        if UTF8_KEYS != ConfigT::UTF8_KEYS {
+            panic!("Config Error! UTF8_KEYS generic constant must match ConfigT");
+        }
+
+        let db = DBConnection::open_as_secondary(primary_path, secondary_path, Self::db_config())?;
+
+        Ok(ReadOnlyTable::new(Self::with_db(db, config, false)?))
+    }
+
+    /// Returns the settings for the database layer, derived from ConfigT
+    fn db_config() -> DBConfig {
+        DBConfig {
+            quarantine_corrupt_entries : ConfigT::QUARANTINE_CORRUPT_ENTRIES,
+            variant_page_size : ConfigT::VARIANT_PAGE_SIZE,
+            strict_decoding : ConfigT::STRICT_DECODING,
+            large_merge_operand_count : ConfigT::LARGE_MERGE_OPERAND_COUNT,
+            merge_diagnostic_log_len : ConfigT::MERGE_DIAGNOSTIC_LOG_LEN,
+            inline_value_max_size : ConfigT::INLINE_VALUE_MAX_SIZE,
+            max_open_files : ConfigT::MAX_OPEN_FILES,
+            table_cache_num_shard_bits : ConfigT::TABLE_CACHE_NUM_SHARD_BITS,
+            ttl : ConfigT::TTL,
+            checksums : ConfigT::CHECKSUMS,
+            blob_threshold : ConfigT::BLOB_THRESHOLD,
+            rocks_config : ConfigT::ROCKS_CONFIG,
+        }
+    }
+
+    /// Upgrades the database at the path provided from an earlier on-disk format to the format written by this
+    /// version of fuzzy_rocks, and returns the number of upgrade steps applied
+    ///
+    /// Each version of fuzzy_rocks records the version of the on-disk format in the database, and refuses to open a
+    /// database written in another format rather than misreading it.  A database in an earlier format must be
+    /// migrated before it can be opened, and the upgrade is applied one format version at a time, with each step
+    /// committed atomically, so an interrupted migration can simply be run again.  A database written in a later
+    /// format can't be migrated.  Migrating a database that is already current does nothing.
+    ///
+    /// Like [new](Self::new), this takes the database's write lock, so the database can't be open elsewhere.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let table = Table::<DefaultTableConfig, true>::new("migrate_example.rocks", DefaultTableConfig()).unwrap();
+    /// drop(table);
+    ///
+    /// assert_eq!(Table::<DefaultTableConfig, true>::migrate("migrate_example.rocks").unwrap(), 0);
+    /// ```
+    pub fn migrate(path : &str) -> Result<usize, String> {
+        let db = DBConnection::new(path, Self::db_config())?;
+        migrate_database(&db, MIGRATION_STEPS, FORMAT_VERSION)
+    }
+
+    /// Creates a Table around a database connection that has already been opened
+    fn with_db(mut db : DBConnection, config : ConfigT, track_access_stats : bool) -> Result<Self, String> {
+
+        //The cipher must be in place before any encrypted entries are read
+        if let Some(cipher) = config.cipher() {
+            db.set_cipher(cipher);
+        }
+
+        //Refuse a database written in another format, rather than misreading it.  A database written before the
+        // format version was recorded has its version recorded now
+        let format_version = database_format_version(&db)?;
+        check_format_version(format_version)?;
+        if db.is_writable() && db.get_metadata::<u32>(FORMAT_VERSION_METADATA_NAME)?.is_none() {
+            db.put_metadata(FORMAT_VERSION_METADATA_NAME, &format_version)?;
+        }
+
+        //Find the next value for new RecordIDs, by probing the entries in the "rec_data" column family
+        let record_count = db.record_count()?;
+
+        //Use the parameters stored with the table if there are any, so the table is searched the same way it
+        //was built.  Otherwise, take them from the config and store them for next time
+        let stored_params = db.get_metadata::<TableParams>(TABLE_PARAMS_METADATA_NAME)?;
+        let new_table = stored_params.is_none();
+        let mut params = match stored_params {
+            Some(params) => params,
+            None => {
+                let params = TableParams::from_config(&config);
+                if db.is_writable() {
+                    db.put_metadata(TABLE_PARAMS_METADATA_NAME, &params)?;
+                    db.put_metadata(CHECKSUMS_METADATA_NAME, &ConfigT::CHECKSUMS)?;
+                    if let Some(cipher) = db.cipher() {
+                        db.put_metadata(CIPHER_CHECK_METADATA_NAME, &cipher_check(cipher))?;
+                    }
+                }
+                params
+            }
+        };
+        #[cfg(not(feature = "unicode-segmentation"))]
+        if params.grapheme_clusters {
+            return Err("the database uses grapheme clusters, which requires the `unicode-segmentation` feature".to_string());
+        }
+        if ConfigT::PHONETIC_FUNCTION.is_some() && !ConfigT::UTF8_KEYS {
+            return Err("a phonetic index requires UTF8_KEYS".to_string());
+        }
+        let phonetic_function = ConfigT::PHONETIC_FUNCTION.map(|encoding| encoding.name);
+        if params.phonetic_function.as_deref() != phonetic_function {
+            let describe = |name : Option<&str>| name.map(|name| format!("the \"{name}\" phonetic function")).unwrap_or_else(|| "no phonetic function".to_string());
+            return Err(format!("the database was built with {}, but the config has {}", describe(params.phonetic_function.as_deref()), describe(phonetic_function)));
+        }
+        if ConfigT::TOKEN_INDEX && !ConfigT::UTF8_KEYS {
+            return Err("a token index requires UTF8_KEYS".to_string());
+        }
+        if params.token_index != ConfigT::TOKEN_INDEX {
+            return Err(format!("the database was built {} a token index, but the config sets TOKEN_INDEX to {}", if params.token_index { "with" } else { "without" }, ConfigT::TOKEN_INDEX));
+        }
+        if ConfigT::SYNONYMS && !ConfigT::UTF8_KEYS {
+            return Err("synonyms require UTF8_KEYS".to_string());
+        }
+        if params.index_strategy == IndexStrategy::Ngrams(0) {
+            return Err("the n-gram length must be at least 1".to_string());
+        }
+        if params.variant_scheme != ConfigT::VARIANT_SCHEME.id() {
+            return Err(format!("the database was built with the \"{}\" variant scheme, but the config uses \"{}\"", params.variant_scheme, ConfigT::VARIANT_SCHEME.id()));
+        }
+
+        //The inline value size may change, but values already stored inline can't be read without inline values
+        if params.inline_value_max_size != ConfigT::INLINE_VALUE_MAX_SIZE {
+            if params.inline_value_max_size > 0 && ConfigT::INLINE_VALUE_MAX_SIZE == 0 {
+                return Err(format!("the database may have values stored inline, up to {} bytes, but the config sets INLINE_VALUE_MAX_SIZE to 0", params.inline_value_max_size));
+            }
+            params.inline_value_max_size = ConfigT::INLINE_VALUE_MAX_SIZE;
+            if db.is_writable() {
+                db.put_metadata(TABLE_PARAMS_METADATA_NAME, &params)?;
+            }
+        }
+
+        //A database written before checksums were recorded doesn't have them
+        let checksums = match db.get_metadata::<bool>(CHECKSUMS_METADATA_NAME)? {
+            Some(checksums) => checksums,
+            None => new_table && ConfigT::CHECKSUMS
+        };
+        if checksums != ConfigT::CHECKSUMS {
+            return Err(format!("the database was written {} checksums, but the config sets CHECKSUMS to {}", if checksums { "with" } else { "without" }, ConfigT::CHECKSUMS));
+        }
+
+        //Make sure the database is opened with the cipher, and the key, it was written with
+        let stored_check = match db.get_metadata::<(bool, Vec<u8>)>(CIPHER_CHECK_METADATA_NAME)? {
+            None if new_table => db.cipher().map(cipher_check),
+            stored_check => stored_check
+        };
+        verify_cipher(stored_check.as_ref(), db.cipher())?;
+
+        //A database written before the table's id was recorded is given one now
+        let table_id = match db.get_metadata::<u64>(TABLE_ID_METADATA_NAME)? {
+            Some(table_id) => table_id,
+            None => {
+                let table_id = new_table_id();
+                if db.is_writable() {
+                    db.put_metadata(TABLE_ID_METADATA_NAME, &table_id)?;
+                }
+                table_id
+            }
+        };
+
+        let table = Self {
+            record_count,
+            config,
+            params,
+            table_id,
+            db,
+            deleted_records : vec![],
+            perf_counters : PerfCounters::new(),
+            access_stats : AccessStats::new(track_access_stats),
+            validators : Validators::new(),
+            access_hook : None,
+            query_cache : QueryCache::new(ConfigT::QUERY_CACHE_CAPACITY),
+            read_repairs : Mutex::new(HashSet::new()),
+        };
+
+        //NOTE: Preloading only warms the cache, so a Table whose manifest can't be read is still opened.  There's
+        // no caller to return the error to, so it's reported on stderr
+        if ConfigT::PRELOAD_MANIFEST_LEN > 0 {
+            if let Err(err) = table.preload() {
+                eprintln!("fuzzy_rocks: failed to preload {} when the Table was opened: {}", table.db.path(), err);
+            }
+        }
+        table.db.load_pinned_records()?;
+
+        Ok(table)
+    }
+
+    /// Resets a Table, dropping every record in the table and restoring it to an empty state.
+    /// 
+    /// (Dropping in a database sense, not a Rust sense)
+    pub fn reset(&mut self) -> Result<(), String> {
+
+        //Reset the database
+        self.db.reset_database()?;
+        self.access_stats.reset();
+        self.query_cache.clear();
+        self.read_repairs.get_mut().unwrap().clear();
+
+        //A reset table takes its parameters from the current config again
+        self.params = TableParams::from_config(&self.config);
+        self.db.put_metadata(TABLE_PARAMS_METADATA_NAME, &self.params)?;
+        self.db.put_metadata(FORMAT_VERSION_METADATA_NAME, &FORMAT_VERSION)?;
+        self.db.put_metadata(CHECKSUMS_METADATA_NAME, &ConfigT::CHECKSUMS)?;
+        if let Some(cipher) = self.db.cipher() {
+            self.db.put_metadata(CIPHER_CHECK_METADATA_NAME, &cipher_check(cipher))?;
+        }
+
+        //The cursors of lookups made before the reset refer to key groups that are gone
+        self.table_id = new_table_id();
+        self.db.put_metadata(TABLE_ID_METADATA_NAME, &self.table_id)?;
+
+        //Reset the record_count, so newly inserted entries begin at 0 again.  The RecordIDs of records deleted
+        // before the reset must be forgotten too, or they would be handed out a second time
+        self.record_count = 0;
+        self.deleted_records.clear();
+        Ok(())
+    }
+
+    /// Returns a lazy iterator over every record in the Table, with its keys and value, as the Table was when
+    /// `export_stream` was called
+    ///
+    /// The export reads from a snapshot of the database, so it's consistent even if the database is written to
+    /// while the export is in progress.  To take a logical backup of a Table that is in service, an external tool
+    /// can open it with [open_as_secondary](Self::open_as_secondary) and export it from there, while the service
+    /// continues writing to the primary instance.
+    ///
+    /// The RecordIDs of the records are read from the snapshot when the export is started, so a gap in the
+    /// RecordIDs doesn't end the export early, but the keys and values are only read as each record is produced.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("export_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let hello = table.create(&["Hello", "Hi"], &"Greeting".to_string()).unwrap();
+    /// let goodbye = table.insert("Goodbye", &"Farewell".to_string()).unwrap();
+    /// table.delete(goodbye).unwrap();
+    ///
+    /// let records = table.export_stream().into_vec().unwrap();
+    /// assert_eq!(records.len(), 1);
+    /// assert_eq!(records[0].record_id, hello);
+    /// assert_eq!(records[0].value, "Greeting");
+    ///
+    /// //Each record can be serialized for the backup
+    /// let bytes = bincode::serialize(&records[0]).unwrap();
+    /// ```
+    pub fn export_stream(&self) -> RecordExport<'_, OwnedKeyT, ConfigT::ValueT> {
+        RecordExport::new(self.db.snapshot(), &self.perf_counters)
+    }
+
+    /// Reads the entries for the records in the preload manifest, so they are in RocksDB's cache before the
+    /// first lookup that needs them, and returns the number of records preloaded
+    /// 
+    /// The manifest is saved by [save_preload_manifest](Table::save_preload_manifest), and this happens automatically
+    /// when the Table is opened, if [PRELOAD_MANIFEST_LEN](TableConfig::PRELOAD_MANIFEST_LEN) is set.  For each record, its
+    /// keys, value, and the variant entries that find its keys by exact lookup are read.  Records that were deleted
+    /// since the manifest was saved are skipped.  If the automatic preload fails, the error is reported on stderr
+    /// and the Table is opened anyway.  Call this function to retry it, and get the error.
+    pub fn preload(&self) -> Result<usize, String> {
+
+        let record_ids = match self.db.get_metadata::<Vec<RecordID>>(PRELOAD_MANIFEST_METADATA_NAME)? {
+            Some(record_ids) => record_ids,
+            None => return Ok(0)
+        };
+
+        let snapshot = self.db.snapshot();
+        let mut preloaded = 0;
+        for record_id in record_ids {
+            let key_groups = match snapshot.get_record_key_groups(record_id)? {
+                Some(key_groups) if !key_groups.is_empty() => key_groups,
+                _ => continue
+            };
+            for key_group in key_groups {
+                for key in snapshot.get_keys_in_group::<OwnedKeyT>(key_group, &self.perf_counters)? {
+                    let variant = SymSpell::<OwnedKeyT, UTF8_KEYS>::meaningful_key_substring(&key, &self.params).into_bytes();
+                    self.db.visit_exact_variant(&variant, |_key_group_ids| {})?;
+                }
+            }
+            snapshot.get_value::<ConfigT::ValueT>(record_id)?;
+            preloaded += 1;
+        }
+
+        Ok(preloaded)
+    }
+
+    /// Returns every entry that has been moved into the quarantine because it couldn't be decoded
+    /// 
+    /// Entries are only quarantined if [QUARANTINE_CORRUPT_ENTRIES](TableConfig::QUARANTINE_CORRUPT_ENTRIES)
+    /// is set for the Table's config.
+    pub fn quarantined_entries(&self) -> Result<Vec<QuarantinedEntry>, String> {
+        self.db.quarantined_entries()
+    }
+
+    /// Audits the Table's exact-key references against the keys in the database, and returns a [VerifyReport]
+    /// describing any divergences
+    ///
+    /// Each key must be referenced by the variant entry for the key itself, which is the reverse index used by
+    /// [lookup_exact](Table::lookup_exact).  A reference is reported as missing if that entry doesn't reference the
+    /// key's group.  A reference is reported as stale if any variant entry references a key group that no longer
+    /// exists.
+    ///
+    /// NOTE: This scans the entire database, so it can take a long time on a large Table.
+    pub fn verify(&self) -> Result<VerifyReport, String> {
+
+        let key_group_ids = self.db.key_group_ids()?;
+        let mut report = VerifyReport::default();
+
+        //Check that every key is referenced by the variant entry for its meaningful key
+        let mut sorted_key_group_ids : Vec<KeyGroupID> = key_group_ids.iter().copied().collect();
+        sorted_key_group_ids.sort();
+        report.missing_refs = self.find_missing_refs(&sorted_key_group_ids)?;
+
+        //Check that every variant references only key groups that exist, or the injected variants of records that
+        // have them
+        report.stale_refs = self.find_stale_refs(&key_group_ids, |_num_refs| {})?;
+
+        Ok(report)
+    }
+
+    /// Returns the references from the variant entry for each key in the key groups to the key's group that
+    /// are missing
+    fn find_missing_refs(&self, key_group_ids : &[KeyGroupID]) -> Result<Vec<(Vec<u8>, KeyGroupID)>, String> {
+
+        let mut missing_refs = vec![];
+        for &key_group_id in key_group_ids {
+            let mut missing_variants = HashSet::new();
+            for key in self.db.get_keys_in_group::<<Self as TableKeyEncoding>::OwnedKeyT>(key_group_id, &self.perf_counters)? {
+
+                //Empty keys don't have any variants
+                if key.num_chars() == 0 {
+                    continue;
+                }
+
+                let variant = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::meaningful_key_substring(&key, &self.params).into_bytes();
+                let mut found = false;
+                self.db.visit_exact_variant(&variant, |mut key_group_ids| {
+                    found = found || key_group_ids.any(|other_key_group_id| other_key_group_id == key_group_id);
+                })?;
+                if !found {
+                    missing_variants.insert(variant);
+                }
+            }
+            missing_refs.extend(missing_variants.into_iter().map(|variant| (variant, key_group_id)));
+        }
+
+        Ok(missing_refs)
+    }
+
+    /// Cross-checks the "rec_data", "keys", "values", and "variants" column families against each other, and
+    /// returns an [IntegrityReport] of every inconsistency found
+    ///
+    /// Every entry is decoded, every key group listed by a record must exist and belong to it, every record must have
+    /// a value and no deleted record may, and the variant entries are checked as in [verify](Table::verify).  Entries
+    /// that can't be decoded are reported rather than returned as an error, and are never quarantined, so the check
+    /// can be run on a damaged Table to find out what [repair](Table::repair) or [gc_variants](Table::gc_variants)
+    /// would need to fix.  A variant entry that can't be decoded still ends the check with an error.
+    ///
+    /// NOTE: This scans the entire database, so it can take a long time on a large Table.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("check_integrity_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let madrid = table.insert("Madrid", &"Spain".to_string()).unwrap();
+    /// table.add_keys(madrid, &["Madrit"]).unwrap();
+    ///
+    /// let report = table.check_integrity().unwrap();
+    /// assert!(report.is_consistent());
+    /// ```
+    pub fn check_integrity(&self) -> Result<IntegrityReport, String> {
+
+        let scan = self.db.scan_entries::<<Self as TableKeyEncoding>::OwnedKeyT, ConfigT::ValueT>()?;
+        let mut report = IntegrityReport::default();
+
+        //A key group whose entry can't be decoded still exists, but its keys can't be checked against the variants
+        let mut corrupt_key_groups = HashSet::new();
+        for (cf_name, key, error) in scan.corrupt_entries.iter() {
+            if *cf_name == KEYS_CF_NAME && key.len() == 8 {
+                corrupt_key_groups.insert(KeyGroupID::from(usize::from_le_bytes(key[..].try_into().unwrap())));
+            }
+            report.issues.push(IntegrityIssue::CorruptEntry{cf_name : cf_name.to_string(), key : key.clone(), error : error.clone()});
+        }
+
+        //Check each record's key groups and value against the "keys" and "values" CFs
+        let mut record_ids : Vec<RecordID> = scan.records.keys().copied().collect();
+        record_ids.sort();
+        for record_id in record_ids.iter() {
+            let key_groups = &scan.records[record_id];
+            for key_group_id in key_groups {
+                if !scan.key_groups.contains(key_group_id) {
+                    report.issues.push(IntegrityIssue::MissingKeyGroup(KeyGroupHandle::new(*key_group_id)));
+                }
+            }
+            match (key_groups.is_empty(), scan.values.contains(record_id)) {
+                (false, false) => report.issues.push(IntegrityIssue::MissingValue(*record_id)),
+                (true, true) => report.issues.push(IntegrityIssue::OrphanedValue(*record_id)),
+                _ => {}
+            }
+        }
+
+        //Check for key groups and values that no record accounts for
+        let mut sorted_key_group_ids : Vec<KeyGroupID> = scan.key_groups.iter().copied().collect();
+        sorted_key_group_ids.sort();
+        for key_group_id in sorted_key_group_ids.iter() {
+            if !scan.records.get(&key_group_id.record_id()).is_some_and(|key_groups| key_groups.contains(key_group_id)) {
+                report.issues.push(IntegrityIssue::OrphanedKeyGroup(KeyGroupHandle::new(*key_group_id)));
+            }
+        }
+        let mut value_record_ids : Vec<RecordID> = scan.values.iter().copied().filter(|record_id| !scan.records.contains_key(record_id)).collect();
+        value_record_ids.sort();
+        report.issues.extend(value_record_ids.into_iter().map(IntegrityIssue::OrphanedValue));
+
+        //Check the variant entries against the keys that can be decoded
+        sorted_key_group_ids.retain(|key_group_id| !corrupt_key_groups.contains(key_group_id));
+        for (variant, key_group_id) in self.find_missing_refs(&sorted_key_group_ids)? {
+            report.issues.push(IntegrityIssue::MissingVariantRef{variant, key_group : KeyGroupHandle::new(key_group_id)});
+        }
+        for (variant, key_group_id) in self.find_stale_refs(&scan.key_groups, |_num_refs| {})? {
+            report.issues.push(IntegrityIssue::StaleVariantRef{variant, key_group : KeyGroupHandle::new(key_group_id)});
+        }
+
+        Ok(report)
+    }
+
+    /// Scans every variant entry, and returns the references to key groups that don't exist, along with the
+    /// references to injected variants that their records no longer have.  `visit_entry` is called with the number
+    /// of references in each entry scanned
+    fn find_stale_refs<F : FnMut(usize)>(&self, key_group_ids : &HashSet<KeyGroupID>, mut visit_entry : F) -> Result<Vec<(Vec<u8>, KeyGroupID)>, String> {
+
+        let mut stale_refs = vec![];
+        let mut injected_refs = vec![];
+        self.db.visit_all_variants(|variant, referenced_ids| {
+            let mut num_refs = 0;
+            for key_group_id in referenced_ids {
+                num_refs += 1;
+                if key_group_id.is_injected() {
+                    injected_refs.push((variant.to_vec(), key_group_id));
+                } else if !key_group_ids.contains(&key_group_id) {
+                    stale_refs.push((variant.to_vec(), key_group_id));
+                }
+            }
+            visit_entry(num_refs);
+        })?;
+        for (variant, key_group_id) in injected_refs {
+            let injected_variants = self.db.get_injected_variants::<<Self as TableKeyEncoding>::OwnedKeyT>(key_group_id.record_id())?;
+            if !injected_variants.iter().any(|injected| SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::meaningful_key_substring(injected, &self.params).into_bytes() == variant) {
+                stale_refs.push((variant, key_group_id));
+            }
+        }
+
+        Ok(stale_refs)
+    }
+
+    /// Scans the variant entries for references to key groups that no longer exist, e.g. after a crash or a
+    /// partially applied delete, removes them, and returns a [GcReport] of the space reclaimed
+    ///
+    /// Unlike [repair](Table::repair), the keys aren't checked for missing references, so the cost is one pass over
+    /// the "keys" column family and one over the variant entries.  Dangling references don't make lookups return
+    /// wrong results, but each one costs a lookup that comes across it a wasted read, so this is meant to be run
+    /// from a periodic maintenance task.  The references are removed atomically.
+    pub fn gc_variants(&mut self) -> Result<GcReport, String> {
+
+        let key_group_ids = self.db.key_group_ids()?;
+        let mut report = GcReport::default();
+        let stale_refs = self.find_stale_refs(&key_group_ids, |num_refs| {
+            report.entries_scanned += 1;
+            report.refs_scanned += num_refs;
+        })?;
+        if stale_refs.is_empty() {
+            return Ok(report);
+        }
+
+        let mut stale_variants : HashMap<KeyGroupID, HashSet<Vec<u8>>> = HashMap::new();
+        for (variant, key_group_id) in stale_refs {
+            stale_variants.entry(key_group_id).or_default().insert(variant);
+        }
+        let mut batch = DBWriteBatch::new();
+        for (key_group_id, variants) in stale_variants {
+            report.refs_removed += variants.len();
+            self.db.delete_variant_references(&mut batch, key_group_id, variants)?;
+        }
+        self.db.commit_batch(batch)?;
+        self.query_cache.clear();
+
+        report.bytes_reclaimed = report.refs_removed * core::mem::size_of::<KeyGroupID>();
+        Ok(report)
+    }
+
+    /// Audits the Table using [verify](Table::verify), and fixes every divergence found, by adding the missing
+    /// references and removing the stale ones.  Returns the [VerifyReport] describing what was fixed
+    ///
+    /// The fixes are applied atomically, so the Table is never left partially repaired.
+    pub fn repair(&mut self) -> Result<VerifyReport, String> {
+
+        let report = self.verify()?;
+        if report.is_consistent() {
+            return Ok(report);
+        }
+
+        let mut batch = DBWriteBatch::new();
+        for (variant, key_group_id) in report.missing_refs.iter() {
+            self.db.put_variant_references(&mut batch, *key_group_id, HashSet::from([variant.clone()]))?;
+        }
+        for (variant, key_group_id) in report.stale_refs.iter() {
+            self.db.delete_variant_references(&mut batch, *key_group_id, HashSet::from([variant.clone()]))?;
+        }
+        self.db.commit_batch(batch)?;
+        self.query_cache.clear();
+
+        Ok(report)
+    }
+
+    /// Returns the number of key groups that lookups have queued to be checked by [apply_read_repairs](Table::apply_read_repairs).
+    /// See [READ_REPAIR](TableConfig::READ_REPAIR)
+    pub fn pending_read_repairs(&self) -> usize {
+        self.read_repairs.lock().unwrap().len()
+    }
+
+    /// Checks the key groups that lookups have come across since the last call, restores every variant reference
+    /// they're missing, and returns the number of references restored.  See [READ_REPAIR](TableConfig::READ_REPAIR)
+    ///
+    /// Each variant of each of a key group's keys is checked, not just the variant for the key itself, so a key
+    /// group left with only some of its references by a partially applied write gets all of them back.  A key group
+    /// that has been removed since the lookup is skipped.  The references are restored atomically.
+    pub fn apply_read_repairs(&mut self) -> Result<usize, String> {
+
+        let mut queued : Vec<KeyGroupID> = std::mem::take(self.read_repairs.get_mut().unwrap()).into_iter().collect();
+        queued.sort();
+        let mut batch = DBWriteBatch::new();
+        let mut restored = 0;
+        for key_group_id in queued {
+
+            //The key group may have been removed after the lookup
+            let keys : Vec<<Self as TableKeyEncoding>::OwnedKeyT> = match self.db.get_keys_in_group::<<Self as TableKeyEncoding>::OwnedKeyT>(key_group_id, &self.perf_counters) {
+                Ok(keys) => keys.collect(),
+                Err(_) => continue
+            };
+            let mut variants = HashSet::new();
+            for key in keys.iter() {
+                variants.extend(SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(key, &self.params, ConfigT::VARIANT_SCHEME));
+            }
+
+            let mut missing_variants = HashSet::new();
+            for variant in variants {
+                let mut found = false;
+                self.db.visit_exact_variant(&variant, |mut key_group_ids| {
+                    found = found || key_group_ids.any(|other_key_group_id| other_key_group_id == key_group_id);
+                })?;
+                if !found {
+                    missing_variants.insert(variant);
+                }
+            }
+            restored += missing_variants.len();
+            self.db.put_variant_references(&mut batch, key_group_id, missing_variants)?;
+        }
+
+        if restored > 0 {
+            self.db.commit_batch(batch)?;
+            self.query_cache.clear();
+        }
+        Ok(restored)
+    }
+
+    /// Deletes every record that has expired, as a single atomic write, and returns the number of records deleted.
+    /// See [TTL](TableConfig::TTL)
+    ///
+    /// Lookups already skip the records that have expired, so this only reclaims the space they take up, and
+    /// removes their references from the variants index.
+    pub fn purge_expired(&mut self) -> Result<usize, String> {
+
+        if !self.db.has_ttl() {
+            return Ok(0);
+        }
+        let mut record_ids = vec![];
+        for (record_id, _metadata) in self.db.all_record_metadata()? {
+            if self.db.is_expired(record_id)? {
+                record_ids.push(record_id);
+            }
+        }
+        if record_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut batch = DBWriteBatch::new();
+        for record_id in record_ids.iter() {
+            self.delete_internal(&mut batch, *record_id)?;
+        }
+        self.db.commit_batch(batch)?;
+        for record_id in record_ids.iter() {
+            self.release_record_id(*record_id);
+            self.access_stats.forget(*record_id);
+        }
+        self.query_cache.clear();
+
+        Ok(record_ids.len())
+    }
+
+    /// Shuts the Table down, completing all of its pending work before the database is closed, and returns a
+    /// [ShutdownSummary] of the work that was done
+    ///
+    /// A [bulk load](Table::begin_bulk_load) still in progress is finished, the queued
+    /// [read repairs](Table::apply_read_repairs) are applied, the [expired records](Table::purge_expired) are
+    /// deleted, the hit counts held in memory are written, the preload manifest is saved, the write-ahead log is
+    /// synced, and every column family is flushed to the database files.  Unlike [close](Table::close), which
+    /// leaves the WAL to be replayed when the database is next opened, a database that has been shut down can be
+    /// copied or opened without any recovery.  The first error stops the shutdown, and the database is then
+    /// closed as if the Table were dropped.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("shutdown_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// table.insert("Hello", &"Greeting".to_string()).unwrap();
+    ///
+    /// let summary = table.shutdown().unwrap();
+    /// assert_eq!(summary, ShutdownSummary::default());
+    /// ```
+    pub fn shutdown(mut self) -> Result<ShutdownSummary, String> {
+
+        //The rest of the work is written as usual, rather than being buffered by the bulk load
+        let bulk_load_variants = if self.db.is_bulk_loading() { self.finish_bulk_load()? } else { 0 };
+
+        let read_repairs = if self.db.is_writable() { self.apply_read_repairs()? } else { 0 };
+        let expired_records = if self.db.is_writable() { self.purge_expired()? } else { 0 };
+
+        let flushed_hits = self.access_stats.pending_total();
+        self.access_stats.flush(&self.db)?;
+
+        let preload_manifest_len = self.save_preload_manifest(ConfigT::PRELOAD_MANIFEST_LEN)?;
+        self.db.flush_and_sync()?;
+
+        Ok(ShutdownSummary{bulk_load_variants, flushed_hits, read_repairs, expired_records, preload_manifest_len})
+    }
+
+    /// Begins a [Transaction], to apply several record operations to the Table as a single all-or-nothing unit
+    pub fn transaction(&mut self) -> Transaction<'_, ConfigT, UTF8_KEYS> {
+        Transaction::new(self)
+    }
+
+    /// Writes any hit counts accumulated in memory, along with the RocksDB write-ahead log and memtables, out
+    /// to the database files, e.g. before taking a snapshot of the file system
+    pub fn flush(&self) -> Result<(), String> {
+        self.access_stats.flush(&self.db)?;
+        self.db.flush_and_sync()
+    }
+
+    /// Compacts every column family of the Table's database, and waits for the compaction to finish
+    /// 
+    /// RocksDB compacts the database in the background, as it sees fit.  Compacting it explicitly, e.g. after
+    /// deleting many records, reclaims the space held by the deleted entries straight away.
+    pub fn compact_all(&self) -> Result<(), String> {
+        for cf_name in COLUMN_FAMILY_NAMES {
+            self.db.compact_range(cf_name, None, None)?;
+        }
+        Ok(())
+    }
+
+    /// Compacts the entries of the named column family with keys from `start` up to `end`, or to either end of
+    /// the column family if they are `None`, and waits for the compaction to finish.  The column families are
+    /// described in the crate documentation
+    pub fn compact_range(&self, cf_name : &str, start : Option<&[u8]>, end : Option<&[u8]>) -> Result<(), String> {
+        self.db.compact_range(cf_name, start, end)
+    }
+
+    /// Returns the space taken up by the Table's database, both in total and by each column family
+    /// 
+    /// The column families are described in the crate documentation.  Most of a Table's space usually goes to
+    /// the "variants" CF, and to the "values" CF if the values are large.  The column families are the Table's
+    /// own, but for a Table in a [SharedDatabase], the total size covers the whole database directory, including
+    /// the other Tables.
+    pub fn disk_usage(&self) -> Result<DiskUsage, String> {
+        self.db.disk_usage()
+    }
+
+    /// Returns a selection of the properties RocksDB reports about the Table's database, such as the estimated
+    /// number of entries in each column family and the number of compactions running
+    /// 
+    /// The estimates are cheap to read, unlike an exact count, so they suit rough sizing questions, e.g. with
+    /// [estimate_record_count](DBProperties::estimate_record_count).
+    pub fn db_properties(&self) -> Result<DBProperties, String> {
+        self.db.properties()
+    }
+
+    /// Returns the statistics RocksDB has collected since the Table was opened, such as the block cache hit rate
+    /// and how often the bloom filters spared a read
+    /// 
+    /// Returns an error unless [statistics](crate::RocksConfig::statistics) is set in the Table's
+    /// [ROCKS_CONFIG](TableConfig::ROCKS_CONFIG).
+    pub fn db_statistics(&self) -> Result<DBStatistics, String> {
+        self.db.statistics()?.ok_or_else(|| "the Table doesn't have statistics enabled in its ROCKS_CONFIG".to_string())
+    }
+
+    /// Discards all of the entries in the quarantine
+    pub fn clear_quarantine(&mut self) -> Result<(), String> {
+        self.db.clear_quarantine()
+    }
+
+    /// Deletes a record from the Table.
+    /// 
+    /// A deleted record cannot be accessed or otherwise found.  All of the record's associated keys
+    /// and the associated value may be purged from the database. 
+    pub fn delete(&mut self, record_id : RecordID) -> Result<(), String> {
+
+        let mut batch = DBWriteBatch::new();
+        self.delete_internal(&mut batch, record_id)?;
+        self.db.commit_batch(batch)?;
+        self.release_record_id(record_id);
+        self.access_stats.forget(record_id);
+
+        Ok(())
+    }
+
+    /// Marks a record as deleted, so it's no longer found by any lookup, without touching the variants index.  The
+    /// record is deleted along with the other soft deleted records by [purge_deleted](Self::purge_deleted)
+    ///
+    /// A [delete](Self::delete) must remove the references to the record from the entry of every variant of every
+    /// one of its keys, which dominates the cost of a workload that deletes many records.  A soft delete only writes
+    /// a tombstone, so the work can be done for many records at once, e.g. from a periodic maintenance task.  Until
+    /// the record is purged, its keys and value can still be read by its RecordID, and the RecordID isn't reused.
+    /// Soft deleting a record that is already soft deleted does nothing.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("soft_delete_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let paris = table.insert("Paris", &"France".to_string()).unwrap();
+    /// table.soft_delete(paris).unwrap();
+    /// assert_eq!(table.lookup_best("Paris").unwrap().count(), 0);
+    /// assert!(table.is_soft_deleted(paris));
+    ///
+    /// assert_eq!(table.purge_deleted().unwrap(), 1);
+    /// assert!(!table.is_soft_deleted(paris));
+    /// ```
+    pub fn soft_delete(&mut self, record_id : RecordID) -> Result<(), String> {
+
+        let mut batch = DBWriteBatch::new();
+        if !self.db.record_is_live_in_batch(&batch, record_id)? {
+            return Err("Invalid record_id".to_string());
+        }
+        self.db.put_tombstone(&mut batch, record_id)?;
+        self.db.commit_batch(batch)?;
+
+        //Results cached before the soft delete may include the record
+        self.query_cache.clear();
+
+        Ok(())
+    }
+
+    /// Returns `true` if the record has been marked as deleted with [soft_delete](Self::soft_delete), and hasn't
+    /// been purged yet
+    pub fn is_soft_deleted(&self, record_id : RecordID) -> bool {
+        self.db.is_tombstoned(record_id)
+    }
+
+    /// Deletes every record marked with [soft_delete](Self::soft_delete), as a single atomic write, and returns
+    /// the number of records deleted
+    pub fn purge_deleted(&mut self) -> Result<usize, String> {
+
+        let record_ids = self.db.tombstoned_records();
+        let mut batch = DBWriteBatch::new();
+        for record_id in record_ids.iter() {
+            self.delete_internal(&mut batch, *record_id)?;
+        }
+        self.db.commit_batch(batch)?;
+        for record_id in record_ids.iter() {
+            self.release_record_id(*record_id);
+            self.access_stats.forget(*record_id);
+        }
+
+        Ok(record_ids.len())
+    }
+
+    /// Deletes a record's keys and value.  The caller is responsible for recording the RecordID as
+    /// deleted, and discarding the record's pending hit count, once the batch is committed
+    pub(crate) fn delete_internal(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+        self.delete_keys_internal(batch, record_id)?;
+        self.delete_injected_variants_internal(batch, record_id)?;
+        self.db.delete_value(batch, record_id)?;
+        self.db.delete_access_count(batch, record_id)?;
+        self.unpin_internal(batch, record_id)?;
+        if ConfigT::RECORD_METADATA || ConfigT::TTL.is_some() {
+            self.db.delete_record_metadata(batch, record_id)?;
+        }
+        self.db.delete_tombstone(batch, record_id)?;
+        Ok(())
+    }
+
+    /// Deletes all of the keys belonging to a record, and all associated variants
+    /// 
+    /// Leaves the record in a half-composed state, so should only be called as part of another
+    /// operation.
+    fn delete_keys_internal(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+
+        //Get all of the key-groups belonging to the record
+        let key_groups : Vec<KeyGroupID> = self.db.get_record_key_groups_in_batch(batch, record_id)?.collect();
+        let mut exact_keys = HashSet::new();
+        let mut phonetic_codes = HashSet::new();
+        let mut ngrams = HashSet::new();
+        let mut token_variants = HashSet::new();
+        for key_group in key_groups {
+
+            //Get all the keys for the group we're removing, so we can compute all the variants
+            let keys_iter = self.db.get_keys_in_group_in_batch::<<Self as TableKeyEncoding>::OwnedKeyT>(batch, key_group, &self.perf_counters)?;
+            let mut variants = HashSet::new();
+            for key in keys_iter {
+                let key_variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS >::variants(&key, &self.params, ConfigT::VARIANT_SCHEME);
+                variants.extend(key_variants);
+                if ConfigT::EXACT_KEY_INDEX {
+                    exact_keys.insert(self.exact_key_bytes(&key));
+                }
+                phonetic_codes.extend(self.phonetic_codes(&key));
+                ngrams.extend(self.key_ngrams(&key));
+                token_variants.extend(self.token_variants(&key));
+            }
+
+            //Remove the variants' reference to this key group
+            self.query_cache.invalidate(record_id, &variants);
+            self.db.delete_variant_references(batch, key_group, variants)?;
+            
+            //Delete the key group entry in the table
+            self.db.delete_key_group_entry(batch, key_group)?;
+        }
+
+        //Remove the record from the exact key, phonetic, n-gram, and token indices
+        self.db.delete_exact_key_references(batch, record_id, exact_keys.into_iter())?;
+        self.db.delete_phonetic_references(batch, record_id, phonetic_codes.into_iter())?;
+        self.db.delete_ngram_references(batch, record_id, ngrams.into_iter())?;
+        self.db.delete_token_references(batch, record_id, token_variants.into_iter())?;
+
+        //Now replace the key groups vec in the "rec_data" table with an empty sentinel vec
+        //NOTE: We replace the record rather than delete it because we assume there are no gaps in the
+        // RecordIDs, when assigning new a RecordID
+        self.db.put_record_key_groups(batch, record_id, &[])?;
+
+        Ok(())
+    }
+
+    /// Stores extra variants for a record, that reference the record without being its keys
+    /// 
+    /// Should NEVER be called on a record that already has injected variants or orphaned references will result
+    fn put_injected_variants_internal<K>(&self, batch : &mut DBWriteBatch, record_id : RecordID, variants : &[K]) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        if variants.iter().any(|variant| variant.num_chars() == 0) {
+            return Err("injected variant can't be empty".to_string());
+        }
+        if variants.iter().any(|variant| variant.num_chars() > MAX_KEY_LENGTH) {
+            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+        }
+
+        //Each injected variant is stored as-is, without the variants made by deleting from it
+        let variants : Vec<<Self as TableKeyEncoding>::OwnedKeyT> = variants.iter().map(|variant| <Self as TableKeyEncoding>::OwnedKeyT::from_key(variant)).collect();
+        let variant_set = variants.iter().map(|variant| SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::meaningful_key_substring(variant, &self.params).into_bytes()).collect();
+        self.query_cache.invalidate(record_id, &variant_set);
+        self.db.put_variant_references(batch, KeyGroupID::injected_for_record(record_id), variant_set)?;
+        self.db.put_injected_variants(batch, record_id, &variants)
+    }
+
+    /// Deletes a record's injected variants, and the references to the record from their variant entries
+    fn delete_injected_variants_internal(&self, batch : &mut DBWriteBatch, record_id : RecordID) -> Result<(), String> {
+
+        let variants = self.db.get_injected_variants_in_batch::<<Self as TableKeyEncoding>::OwnedKeyT>(batch, record_id)?;
+        if variants.is_empty() {
+            return Ok(());
+        }
+
+        let variant_set = variants.iter().map(|variant| SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::meaningful_key_substring(variant, &self.params).into_bytes()).collect();
+        self.query_cache.invalidate(record_id, &variant_set);
+        self.db.delete_variant_references(batch, KeyGroupID::injected_for_record(record_id), variant_set)?;
+        self.db.put_injected_variants::<<Self as TableKeyEncoding>::OwnedKeyT>(batch, record_id, &[])
+    }
+
+    /// Divides the keys up into key groups and assigns them to a record.
+    /// 
+    /// Should NEVER be called on a record that already has keys or orphaned database entries will result
+    fn put_record_keys<'a, K, KeysIterT : Iterator<Item=&'a K>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, keys_iter : KeysIterT, num_keys : usize) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
+    {
+
+        //Validate all of the keys before any of them are written
+        let keys : Vec<&K> = keys_iter.collect();
+        for key in keys.iter() {
+            self.validators.validate_key(*key)?;
+        }
+
+        //Add the record to the exact key, phonetic, n-gram, and token indices
+        if ConfigT::EXACT_KEY_INDEX {
+            self.db.put_exact_key_references(batch, record_id, keys.iter().map(|key| self.exact_key_bytes(*key)))?;
+        }
+        if ConfigT::PHONETIC_FUNCTION.is_some() {
+            self.db.put_phonetic_references(batch, record_id, keys.iter().flat_map(|key| self.phonetic_codes(*key)))?;
+        }
+        if ConfigT::TOKEN_INDEX {
+            self.db.put_token_references(batch, record_id, keys.iter().flat_map(|key| self.token_variants(*key)))?;
+        }
+        self.put_key_ngrams(batch, record_id, keys.iter().copied())?;
+
+        //Make groups for the keys
+        let groups = KeyGroups::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::make_groups_from_keys(keys.into_iter(), num_keys, &self.params, ConfigT::VARIANT_SCHEME)?;
+        let num_groups = groups.key_group_keys.len();
+
+        //Put the variants for each group into the right table
+        for (idx, variant_set) in groups.key_group_variants.into_iter().enumerate() {
+            let key_group_id = KeyGroupID::from_record_and_idx(record_id, idx); 
+            self.query_cache.invalidate(record_id, &variant_set);
+            self.db.put_variant_references(batch, key_group_id, variant_set)?;
+        }
+        
+        //Put the keys for each group into the table
+        for (idx, key_set) in groups.key_group_keys.into_iter().enumerate() {
+            let key_group_id = KeyGroupID::from_record_and_idx(record_id, idx); 
+            self.db.put_key_group_entry(batch, key_group_id, &key_set)?;
+        }
+
+        //Put the key group record into the rec_data table
+        let group_indices : Vec<usize> = (0..num_groups).into_iter().collect();
+        self.db.put_record_key_groups(batch, record_id, &group_indices[..])
+    }
+
+    /// Add additional keys to a record, including creation of all associated variants
+    pub(crate) fn add_keys_internal<'a, K, KeysIterT : Iterator<Item=&'a K>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, keys_iter : KeysIterT, num_keys : usize) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
+    {
+
+        //Validate all of the keys before any of them are written
+        let keys : Vec<&K> = keys_iter.collect();
+        for key in keys.iter() {
+            self.validators.validate_key(*key)?;
+        }
+
+        //Add the record to the exact key, phonetic, n-gram, and token indices for the new keys
+        if ConfigT::EXACT_KEY_INDEX {
+            self.db.put_exact_key_references(batch, record_id, keys.iter().map(|key| self.exact_key_bytes(*key)))?;
+        }
+        if ConfigT::PHONETIC_FUNCTION.is_some() {
+            self.db.put_phonetic_references(batch, record_id, keys.iter().flat_map(|key| self.phonetic_codes(*key)))?;
+        }
+        if ConfigT::TOKEN_INDEX {
+            self.db.put_token_references(batch, record_id, keys.iter().flat_map(|key| self.token_variants(*key)))?;
+        }
+        self.put_key_ngrams(batch, record_id, keys.iter().copied())?;
+
+        //Discard the cached lookups that may find the new keys, including through variants the record already had
+        if self.query_cache.is_enabled() {
+            let mut new_keys_variants = HashSet::new();
+            for key in keys.iter() {
+                new_keys_variants.extend(SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(*key, &self.params, ConfigT::VARIANT_SCHEME));
+            }
+            self.query_cache.invalidate(record_id, &new_keys_variants);
+        }
+
+        //Get the record's existing key groups and variants, so we can figure out the
+        //best places for each additional new key
+        let mut groups = KeyGroups::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::load_key_groups(&self.db, batch, record_id, &self.params, ConfigT::VARIANT_SCHEME, &self.perf_counters)?;
+
+        //Clone the existing groups, so we can determine which variants were added where
+        let existing_groups_variants = groups.key_group_variants.clone();
+
+        //Go over each key and add it to the key groups,
+        // This add_key_to_groups function encapsulates the logic to add each key to
+        // the correct group or create a new group
+        for (key_idx, key) in keys.into_iter().enumerate() {
+            let update_reverse_index = key_idx < num_keys-1;
+            groups.add_key_to_groups(key, update_reverse_index, &self.params, ConfigT::VARIANT_SCHEME)?;
+        }
+
+        //Go over each group, work out the variants we need to add, then add them and update the group
+        let empty_set = HashSet::new();
+        for (group_idx, keys_set) in groups.key_group_keys.iter().enumerate() {
+
+            //Get the variants for the group, as it existed prior to adding any keys
+            let existing_keys_variants = match existing_groups_variants.get(group_idx) {
+                Some(variant_set) => variant_set,
+                None => &empty_set
+            };
+
+            //Calculate the set of variants that is unique to the keys we'll be adding
+            let mut unique_keys_variants = HashSet::new();
+            for unique_keys_variant in groups.key_group_variants[group_idx].difference(existing_keys_variants) {
+                unique_keys_variants.insert(unique_keys_variant.to_owned());
+            }
+
+            //Add the key_group_id to the appropriate entries for each of the new variants
+            let key_group_id = KeyGroupID::from_record_and_idx(record_id, groups.group_ids[group_idx]);
+            self.db.put_variant_references(batch, key_group_id, unique_keys_variants)?;
+
+            //Add the new keys to the key group's entry in the keys table by replacing the keys vector
+            // with the superset
+            self.db.put_key_group_entry(batch, key_group_id, keys_set)?;
+        }
+
+        //Put the new rec_data entry, to reflect all the associated key groups
+        self.db.put_record_key_groups(batch, record_id, &groups.group_ids[..])?;
+        self.touch_record_metadata(batch, record_id, false)
+    }
+
+    /// Removes the specified keys from the keys associated with a record
+    /// 
+    /// If one of the specified keys is not associated with the record then that specified
+    /// key will be ignored.
+    pub(crate) fn remove_keys_internal<K>(&self, batch : &mut DBWriteBatch, record_id : RecordID, remove_keys : &HashSet<&K>) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        //Get all of the existing groups
+        let group_ids : Vec<KeyGroupID> = self.db.get_record_key_groups_in_batch(batch, record_id)?.collect();
+
+        //Go through each existing group, and build a HashSet containing the keys that
+        // we will delete and the keys that will remain after the removal
+        let mut remaining_key_count = 0;
+        let mut deleted_group_keys_sets = vec![];
+        let mut remaining_group_keys_sets = vec![];
+        for key_group in group_ids.iter() {
+            let mut deleted_keys = HashSet::new();
+            let mut remaining_keys = HashSet::new();
+            for existing_key in self.db.get_keys_in_group_in_batch::<<Self as TableKeyEncoding>::OwnedKeyT>(batch, *key_group, &self.perf_counters)? {
+
+                //NOTE: We know this is safe because the unsafety comes from the fact that
+                // query_key might borrow existing_key, which is temporary, while query_key's
+                // lifetime is 'a, which is beyond this function.  However, query_key doesn't
+                // outlast this unsafe scope and we know HashSet::contains won't hold onto it.
+                let set_contains_key = unsafe {
+                    let query_key = K::from_owned_unsafe(&existing_key);
+                    remove_keys.contains(&query_key)
+                };
+                
+                //TODO: When GenericAssociatedTypes is stabilized, I will remove the KeyUnsafe trait in favor of an associated type
+                // let query_key = K::new_borrowed_from_owned(&existing_key);
+                // let set_contains_key = remove_keys.contains(&query_key);
+                
+                if !set_contains_key {
+                    remaining_keys.insert(existing_key);
+                    remaining_key_count += 1;
+                } else {
+                    deleted_keys.insert(existing_key);
+                }
+            }
+            deleted_group_keys_sets.push(deleted_keys);
+            remaining_group_keys_sets.push(remaining_keys);
+        }
+
+        //If we're left with no remaining keys, we should throw an error because all records must
+        //have at least one key
+        if remaining_key_count < 1 {
+            return Err("cannot remove all keys from record".to_string());
+        }
+
+        //Remove the record from the exact key index for the removed keys, unless a remaining key is the same
+        // once its case is folded
+        if ConfigT::EXACT_KEY_INDEX {
+            let remaining_exact_keys : HashSet<Vec<u8>> = remaining_group_keys_sets.iter().flatten().map(|key| self.exact_key_bytes(key)).collect();
+            let removed_exact_keys : HashSet<Vec<u8>> = deleted_group_keys_sets.iter().flatten().map(|key| self.exact_key_bytes(key)).collect();
+            self.db.delete_exact_key_references(batch, record_id, removed_exact_keys.into_iter().filter(|key| !remaining_exact_keys.contains(key)))?;
+        }
+
+        //Likewise for the phonetic index, keeping the codes that a remaining key also has
+        if ConfigT::PHONETIC_FUNCTION.is_some() {
+            let remaining_codes : HashSet<Vec<u8>> = remaining_group_keys_sets.iter().flatten().flat_map(|key| self.phonetic_codes(key)).collect();
+            let removed_codes : HashSet<Vec<u8>> = deleted_group_keys_sets.iter().flatten().flat_map(|key| self.phonetic_codes(key)).collect();
+            self.db.delete_phonetic_references(batch, record_id, removed_codes.into_iter().filter(|code| !remaining_codes.contains(code)))?;
+        }
+
+        //And for the token index, keeping the token variants that a remaining key also has
+        if ConfigT::TOKEN_INDEX {
+            let remaining_variants : HashSet<Vec<u8>> = remaining_group_keys_sets.iter().flatten().flat_map(|key| self.token_variants(key)).collect();
+            let removed_variants : HashSet<Vec<u8>> = deleted_group_keys_sets.iter().flatten().flat_map(|key| self.token_variants(key)).collect();
+            self.db.delete_token_references(batch, record_id, removed_variants.into_iter().filter(|variant| !remaining_variants.contains(variant)))?;
+        }
+
+        //And for the n-gram index, keeping the n-grams that a remaining key also has
+        if self.params.index_strategy.ngram_len().is_some() {
+            let remaining_ngrams : HashSet<Vec<u8>> = remaining_group_keys_sets.iter().flatten().flat_map(|key| self.key_ngrams(key)).collect();
+            let removed_ngrams : HashSet<Vec<u8>> = deleted_group_keys_sets.iter().flatten().flat_map(|key| self.key_ngrams(key)).collect();
+            self.db.delete_ngram_references(batch, record_id, removed_ngrams.into_iter().filter(|ngram| !remaining_ngrams.contains(ngram)))?;
+        }
+
+        //Go through each group and update its keys and variants, or remove the group altogether
+        let mut remaining_group_indices = vec![];
+        for (idx, group_id) in group_ids.into_iter().enumerate() {
+
+            //If we didn't remove any keys from this group, there is nothing to do here.
+            if deleted_group_keys_sets[idx].is_empty() {
+                remaining_group_indices.push(group_id.group_idx());
+                continue;
+            }
+
+            //Compute all variants for the keys we're removing from this group
+            let mut remove_keys_variants = HashSet::new();
+            for remove_key in deleted_group_keys_sets[idx].iter() {
+                let keys_variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(remove_key, &self.params, ConfigT::VARIANT_SCHEME);
+                remove_keys_variants.extend(keys_variants);
+            }
+
+            self.query_cache.invalidate(record_id, &remove_keys_variants);
+
+            //Compute all the variants for the keys that must remain in the group
+            let mut remaining_keys_variants = HashSet::new();
+            for remaining_key in remaining_group_keys_sets[idx].iter() {
+                let keys_variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(remaining_key, &self.params, ConfigT::VARIANT_SCHEME);
+                remaining_keys_variants.extend(keys_variants);
+            }
+
+            //Exclude all of the overlapping variants, leaving only the variants that are unique
+            //to the keys we're removing
+            let mut unique_keys_variants = HashSet::new();
+            for unique_keys_variant in remove_keys_variants.difference(&remaining_keys_variants) {
+                unique_keys_variants.insert(unique_keys_variant.to_owned());
+            }
+
+            //Delete our KeyGroupID from each variant's list, and remove the variant list if we made it empty
+            self.db.delete_variant_references(batch, group_id, unique_keys_variants)?;
+
+            //Update or delete the group
+            if remaining_group_keys_sets[idx].is_empty() {
+                //Delete the group's keys record if we made the group empty
+                self.db.delete_key_group_entry(batch, group_id)?;
+            } else {
+                //Otherwise update the group's keys record
+                self.db.put_key_group_entry(batch, group_id, &remaining_group_keys_sets[idx])?;
+                remaining_group_indices.push(group_id.group_idx());
+            }
+            
+        }
+
+        //Update the record's rec_data entry to reflect the new groups after deletion
+        self.db.put_record_key_groups(batch, record_id, &remaining_group_indices[..])?;
+        self.touch_record_metadata(batch, record_id, false)
+    }
+
+    /// Replaces all of the keys in a record with the supplied keys
+    pub(crate) fn replace_keys_internal<K>(&self, batch : &mut DBWriteBatch, record_id : RecordID, keys : &[K]) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        if keys.is_empty() {
+            return Err("record must have at least one key".to_string());
+        }
+        if keys.iter().any(|key| key.num_chars() > MAX_KEY_LENGTH) {
+            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+        }
+
+        //Delete the old keys
+        self.delete_keys_internal(batch, record_id)?;
+
+        //Set the keys on the new record
+        self.put_record_keys(batch, record_id, keys.iter(), keys.len())?;
+        self.touch_record_metadata(batch, record_id, false)
+    }
+
+    /// Replaces a record's value with the supplied value.  Returns the value that was replaced
+    /// 
+    /// The supplied `record_id` must references an existing record that has not been deleted.
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn replace_value(&mut self, record_id : RecordID, value : &ConfigT::ValueT) -> Result<ConfigT::ValueT, String> {
+
+        let mut batch = DBWriteBatch::new();
+        let old_value = self.replace_value_internal(&mut batch, record_id, value)?;
+        self.db.commit_batch(batch)?;
+
+        Ok(old_value)
+    }
+
+    /// Replaces a record's value with a value read from `reader`, without holding the whole value in memory
+    /// 
+    /// The value must be one that serializes as a sequence of `len` bytes, such as a `Vec<u8>` or a `String`.  It
+    /// can be read back with [get_value](Table::get_value), like any other value, or streamed out again with
+    /// [get_value_to_writer](Table::get_value_to_writer).  The bytes are written in chunks of
+    /// [BLOB_THRESHOLD](TableConfig::BLOB_THRESHOLD), which must be set.  A value can't be streamed into a Table
+    /// with a [cipher](TableConfig::cipher), or with value validators, because they need the whole value.
+    /// 
+    /// The chunks are written alongside the old value, and the record switches to them in a single batch once
+    /// the whole value has been read, so the old value can still be read until then.  If reading fails part
+    /// way through, the chunks written so far are deleted, and the record keeps its old value.
+    pub fn put_value_from_reader<R : Read>(&mut self, record_id : RecordID, reader : &mut R, len : u64) -> Result<(), String> {
+
+        if self.validators.has_value_validators() {
+            return Err("a value can't be streamed into a Table with value validators".to_string());
+        }
+        if self.db.get_live_record_key_groups(record_id)?.is_none() {
+            return Err("Invalid record_id".to_string());
+        }
+
+        let mut batch = DBWriteBatch::new();
+        self.db.put_value_from_reader(&mut batch, record_id, reader, len)?;
+        self.touch_record_metadata(&mut batch, record_id, false)?;
+        self.db.commit_batch(batch)?;
+        self.db.refresh_pinned_record(record_id)
+    }
+
+    /// Replaces a record's value, returning the value as it was before the write
+    pub(crate) fn replace_value_internal(&self, batch : &mut DBWriteBatch, record_id : RecordID, value : &ConfigT::ValueT) -> Result<ConfigT::ValueT, String> {
+
+        self.validators.validate_value(value)?;
+
+        let old_value = self.db.get_value_in_batch(batch, record_id)?;
+        self.db.put_value(batch, record_id, value)?;
+        self.touch_record_metadata(batch, record_id, false)?;
+
+        Ok(old_value)
+    }
+
+    /// Inserts a record into the Table, called by insert(), which is implemented differently depending
+    /// on the UTF8_KEYS constant
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub(crate) fn insert_internal<'a, K, KeysIterT : Iterator<Item=&'a K>>(&mut self, keys_iter : KeysIterT, num_keys : usize, value : &ConfigT::ValueT) -> Result<RecordID, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
+    {
+
+        //Write the whole record at once.  We don't claim the RecordID until the batch has been committed
+        let mut batch = DBWriteBatch::new();
+        let new_record_id = self.insert_in_batch(&mut batch, keys_iter, num_keys, value)?;
+        self.db.commit_batch(batch)?;
+        self.claim_record_id(new_record_id);
+
+        Ok(new_record_id)
+    }
+
+    /// Creates a new record with the supplied keys, and extra variants that will find the record in fuzzy lookups
+    pub(crate) fn create_with_variants_internal<K>(&mut self, keys : &[K], variants : &[K], value : &ConfigT::ValueT) -> Result<RecordID, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        let mut batch = DBWriteBatch::new();
+        let new_record_id = self.insert_in_batch(&mut batch, keys.iter(), keys.len(), value)?;
+        self.put_injected_variants_internal(&mut batch, new_record_id, variants)?;
+        self.db.commit_batch(batch)?;
+        self.claim_record_id(new_record_id);
+
+        Ok(new_record_id)
+    }
+
+    /// Composes the writes to create a new record in the supplied batch, and returns the RecordID that
+    /// the record will have.  The RecordID must be claimed with [claim_record_id](Self::claim_record_id)
+    /// before another record is inserted
+    pub(crate) fn insert_in_batch<'a, K, KeysIterT : Iterator<Item=&'a K>>(&self, batch : &mut DBWriteBatch, keys_iter : KeysIterT, num_keys : usize, value : &ConfigT::ValueT) -> Result<RecordID, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
+    {
+
+        if num_keys < 1 {
+            return Err("record must have at least one key".to_string());
+        }
+
+        let keys : Vec<&K> = keys_iter.collect();
+        let new_record_id = if ConfigT::DETERMINISTIC_RECORD_IDS {
+            self.deterministic_record_id(batch, keys[0])?
+        } else {
+            self.next_record_id()
+        };
+        self.put_new_record(batch, new_record_id, keys.into_iter(), num_keys, value)?;
+
+        Ok(new_record_id)
+    }
+
+    /// Composes the writes to create a new record with the supplied RecordID in the batch.  The RecordID must
+    /// not belong to a live record
+    pub(crate) fn put_new_record<'a, K, KeysIterT : Iterator<Item=&'a K>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, keys_iter : KeysIterT, num_keys : usize, value : &ConfigT::ValueT) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
+    {
+
+        self.validators.validate_value(value)?;
+
+        //Set the keys on the new record
+        self.put_record_keys(batch, record_id, keys_iter, num_keys)?;
+
+        //Put the value into its appropriate table
+        self.db.put_value(batch, record_id, value)?;
+        self.touch_record_metadata(batch, record_id, true)
+    }
+
+    /// Returns `true` if new records can be written by several batches at once, because creating a record only
+    /// merges into the entries it shares with other records, rather than reading and rewriting them
+    ///
+    /// The exact key, phonetic, token, and n-gram indices, and paged variant entries, are all read and rewritten,
+    /// and a deterministic RecordID depends on which RecordIDs have already been taken.
+    pub(crate) fn supports_concurrent_creates(&self) -> bool {
+        !ConfigT::DETERMINISTIC_RECORD_IDS
+            && !ConfigT::EXACT_KEY_INDEX
+            && ConfigT::PHONETIC_FUNCTION.is_none()
+            && !ConfigT::TOKEN_INDEX
+            && ConfigT::VARIANT_PAGE_SIZE == 0
+            && self.params.index_strategy.ngram_len().is_none()
+    }
+
+    /// Returns the RecordID derived from a hash of the key, or the next free RecordID after it if that
+    /// RecordID is already taken, for when [DETERMINISTIC_RECORD_IDS](TableConfig::DETERMINISTIC_RECORD_IDS) is set
+    fn deterministic_record_id<K>(&self, batch : &DBWriteBatch, key : &K) -> Result<RecordID, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        //NOTE: RecordIDs must fit in the lower 44 bits of a KeyGroupID
+        const RECORD_ID_MASK : u64 = 0xFFFFFFFFFFF;
+
+        let owned_key = self.params.fold_key(OwnedKeyT::from_key(key));
+        let mut record_id_val = stable_key_hash(&owned_key) & RECORD_ID_MASK;
+
+        while self.db.record_is_live_in_batch(batch, RecordID::from(record_id_val as usize))? {
+            record_id_val = (record_id_val + 1) & RECORD_ID_MASK;
+        }
+
+        Ok(RecordID::from(record_id_val as usize))
+    }
+
+    /// Adds the supplied keys to a record, as a single atomic write
+    pub(crate) fn add_keys_atomic<'a, K, KeysIterT : Iterator<Item=&'a K>>(&mut self, record_id : RecordID, keys_iter : KeysIterT, num_keys : usize) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
+    {
+        let mut batch = DBWriteBatch::new();
+        self.add_keys_internal(&mut batch, record_id, keys_iter, num_keys)?;
+        self.db.commit_batch(batch)
+    }
+
+    /// Removes the supplied keys from a record, as a single atomic write
+    pub(crate) fn remove_keys_atomic<K>(&mut self, record_id : RecordID, remove_keys : &HashSet<&K>) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        let mut batch = DBWriteBatch::new();
+        self.remove_keys_internal(&mut batch, record_id, remove_keys)?;
+        self.db.commit_batch(batch)
+    }
+
+    /// Replaces all of the keys in a record, as a single atomic write
+    pub(crate) fn replace_keys_atomic<K>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        let mut batch = DBWriteBatch::new();
+        self.replace_keys_internal(&mut batch, record_id, keys)?;
+        self.db.commit_batch(batch)
+    }
+
+    /// Adds a new key group to an existing record, containing exactly the supplied keys, and points each of
+    /// the keys' variants at it, as a single atomic write
+    ///
+    /// Unlike [add_keys](Table::add_keys), the keys aren't merged into the record's existing groups, so the
+    /// caller controls how the record's keys are partitioned.  This is useful when building a Table with a
+    /// custom sharding or bulk-loading strategy.  Keys subsequently added with `add_keys` may still be merged
+    /// into the new group.
+    ///
+    /// The new group is given the lowest index the record's groups don't already use.  Returns an error if `keys`
+    /// is empty, if any of the keys already belong to the record, or if the record already has as many key groups
+    /// as a [KeyGroupHandle] can index.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("key_group_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let record_id = table.create(&["Friday"], &"Kinyoubi".to_string()).unwrap();
+    ///
+    /// let group = table.put_key_group(record_id, &["Freitag", "Vendredi"]).unwrap();
+    /// assert_eq!(table.key_groups(record_id).unwrap().len(), 2);
+    /// assert_eq!(table.key_group_keys(group).unwrap().len(), 2);
+    /// assert_eq!(table.lookup_best("Vendredy").unwrap().next().unwrap(), record_id);
+    ///
+    /// table.delete_key_group(group).unwrap();
+    /// assert_eq!(table.get_keys(record_id).unwrap().collect::<Vec<String>>(), vec!["Friday"]);
+    ///
+    /// //The deleted group's index is free to be used again
+    /// assert_eq!(table.put_key_group(record_id, &["Viernes"]).unwrap().group_index(), group.group_index());
+    /// ```
+    pub fn put_key_group<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<KeyGroupHandle, String> {
+        let mut batch = DBWriteBatch::new();
+        let key_group_id = self.put_key_group_internal(&mut batch, record_id, keys)?;
+        self.touch_record_metadata(&mut batch, record_id, false)?;
+        self.db.commit_batch(batch)?;
+        Ok(KeyGroupHandle::new(key_group_id))
+    }
+
+    /// Returns handles to all of a record's key groups
+    pub fn key_groups(&self, record_id : RecordID) -> Result<Vec<KeyGroupHandle>, String> {
+        Ok(self.db.get_record_key_groups(record_id)?.map(KeyGroupHandle::new).collect())
+    }
+
+    /// Returns the keys in a single key group
+    pub fn key_group_keys(&self, key_group : KeyGroupHandle) -> Result<Vec<OwnedKeyT>, String> {
+        self.check_key_group(key_group)?;
+        Ok(self.db.get_keys_in_group::<OwnedKeyT>(key_group.key_group_id, &self.perf_counters)?.collect())
+    }
+
+    /// Removes a key group from its record, along with its keys and the variant references that point at it,
+    /// as a single atomic write
+    ///
+    /// Returns an error if it is the record's only remaining key group, because all records must have at
+    /// least one key.
+    pub fn delete_key_group(&mut self, key_group : KeyGroupHandle) -> Result<(), String> {
+        self.check_key_group(key_group)?;
+        let keys = self.key_group_keys(key_group)?;
+        let keys_set : HashSet<&OwnedKeyT> = keys.iter().collect();
+        self.remove_keys_atomic(key_group.record_id(), &keys_set)
+    }
+
+    /// Returns an error if the key group is no longer one of its record's key groups
+    fn check_key_group(&self, key_group : KeyGroupHandle) -> Result<(), String> {
+        if self.db.get_record_key_groups(key_group.record_id())?.any(|group_id| group_id == key_group.key_group_id) {
+            Ok(())
+        } else {
+            Err("key group no longer exists".to_string())
+        }
+    }
+
+    /// Adds a new key group containing the supplied keys to a record, including creation of all associated variants
+    fn put_key_group_internal<K>(&self, batch : &mut DBWriteBatch, record_id : RecordID, keys : &[K]) -> Result<KeyGroupID, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        if keys.is_empty() {
+            return Err("key group must contain at least one key".to_string());
+        }
+        for key in keys.iter() {
+            if key.num_chars() > MAX_KEY_LENGTH {
+                return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+            }
+            self.validators.validate_key(key)?;
+        }
+
+        //Make sure none of the keys already belong to the record, so each key is only in one group
+        let group_ids : Vec<KeyGroupID> = self.db.get_record_key_groups_in_batch(batch, record_id)?.collect();
+        if group_ids.is_empty() {
+            return Err("Invalid record_id".to_string());
+        }
+        for group_id in group_ids.iter() {
+            for existing_key in self.db.get_keys_in_group_in_batch::<OwnedKeyT>(batch, *group_id, &self.perf_counters)? {
+                if keys.iter().any(|key| key.as_bytes() == existing_key.as_bytes()) {
+                    return Err("key already belongs to record".to_string());
+                }
+            }
+        }
+
+        //Pick the lowest group index that doesn't collide with the record's existing groups.  The indices above
+        // MAX_GROUP_IDX don't fit in a KeyGroupID, or are reserved
+        let mut group_indices : Vec<usize> = group_ids.iter().map(|group_id| group_id.group_idx()).collect();
+        let new_group_idx = (0..=MAX_GROUP_IDX).find(|group_idx| !group_indices.contains(group_idx))
+            .ok_or_else(|| "record has too many key groups".to_string())?;
+        let key_group_id = KeyGroupID::from_record_and_idx(record_id, new_group_idx);
+
+        //Add the record to the exact key, phonetic, n-gram, and token indices for the new keys
+        if ConfigT::EXACT_KEY_INDEX {
+            self.db.put_exact_key_references(batch, record_id, keys.iter().map(|key| self.exact_key_bytes(key)))?;
+        }
+        if ConfigT::PHONETIC_FUNCTION.is_some() {
+            self.db.put_phonetic_references(batch, record_id, keys.iter().flat_map(|key| self.phonetic_codes(key)))?;
+        }
+        if ConfigT::TOKEN_INDEX {
+            self.db.put_token_references(batch, record_id, keys.iter().flat_map(|key| self.token_variants(key)))?;
+        }
+        self.put_key_ngrams(batch, record_id, keys.iter())?;
+
+        //Point the keys' variants at the new group
+        let mut variants = HashSet::new();
+        for key in keys.iter() {
+            variants.extend(SymSpell::<OwnedKeyT, UTF8_KEYS>::variants(key, &self.params, ConfigT::VARIANT_SCHEME));
+        }
+        self.query_cache.invalidate(record_id, &variants);
+        self.db.put_variant_references(batch, key_group_id, variants)?;
+
+        //Put the keys into the group's entry, and attach the group to the record
+        let key_set : HashSet<OwnedKeyT> = keys.iter().map(|key| OwnedKeyT::from_key(key)).collect();
+        self.db.put_key_group_entry(batch, key_group_id, &key_set)?;
+        group_indices.push(new_group_idx);
+        self.db.put_record_key_groups(batch, record_id, &group_indices[..])?;
+
+        Ok(key_group_id)
+    }
+
+    /// Creates the variants for a given fuzzy search key, based on config.max_deletes or the smaller `max_distance`
+    /// if one is supplied, and returns a [KeyGroupCandidates] that will probe them for candidate KeyGroups as it is advanced.
+    fn fuzzy_candidates<K>(&self, key : &K, max_distance : Option<usize>) -> Result<KeyGroupCandidates<'_>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        if key.num_chars() > MAX_KEY_LENGTH {
+            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+        }
+
+        //If the Table indexes n-grams, the candidates come from the "ngrams" CF instead
+        let max_deletes = max_distance.map_or(self.params.max_deletes, |max_distance| max_distance.min(self.params.max_deletes));
+        if self.params.index_strategy.ngram_len().is_some() {
+            return self.ngram_candidates(key, max_deletes, false);
+        }
+
+        //Create all of the potential variants based off of the "meaningful" part of the key
+        let variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants_within(key, max_deletes, &self.params, ConfigT::VARIANT_SCHEME);
+
+        Ok(KeyGroupCandidates::new(&self.db, &self.perf_counters, variants, ConfigT::VARIANT_PRIORITY_FUNCTION))
+    }
+
+    /// Returns a [KeyGroupCandidates] for the records with a key that begins with something within
+    /// [MAX_DELETES](TableConfig::MAX_DELETES) of `prefix`
+    fn prefix_fuzzy_candidates<K>(&self, prefix : &K) -> Result<KeyGroupCandidates<'_>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        if self.params.index_strategy.ngram_len().is_some() {
+            if prefix.num_chars() > MAX_KEY_LENGTH {
+                return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+            }
+            self.ngram_candidates(prefix, self.params.max_deletes, true)
+        } else {
+            Ok(self.fuzzy_candidates(prefix, None)?.matching_prefixes())
+        }
+    }
+
+    /// Finds the records that share enough n-grams with a key to be within `max_distance` edits of it, and returns
+    /// a [KeyGroupCandidates] that produces them, the records that share the most n-grams first
+    /// 
+    /// If `prefix` is set, the records only need to have a key that begins with something within `max_distance`
+    /// of the key, so the n-grams that span the end of the key aren't required.
+    fn ngram_candidates<K>(&self, key : &K, max_distance : usize, prefix : bool) -> Result<KeyGroupCandidates<'_>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        let n = self.params.index_strategy.ngram_len().unwrap();
+        let ngrams = self.key_ngrams(key);
+        let slack = if prefix { n - 1 } else { 0 };
+        let min_shared = min_shared_ngrams(ngrams.len(), n, max_distance, slack);
+
+        //Count the n-grams each record shares with the key
+        let mut shared_counts : HashMap<RecordID, usize> = HashMap::new();
+        for ngram in ngrams {
+            for record_id in self.db.get_ngram_records(&ngram)? {
+                *shared_counts.entry(record_id).or_insert(0) += 1;
+            }
+        }
+
+        //Keep the records that share enough, ordered so the records that share the most are produced first
+        let mut candidates : Vec<(RecordID, usize)> = shared_counts.into_iter().filter(|(_, shared_count)| *shared_count >= min_shared).collect();
+        candidates.sort_unstable_by_key(|(record_id, shared_count)| (*shared_count, Reverse(*record_id)));
+        let record_ids = candidates.into_iter().map(|(record_id, _)| record_id).collect();
+        Ok(KeyGroupCandidates::from_records(&self.db, &self.perf_counters, record_ids, ConfigT::VARIANT_PRIORITY_FUNCTION))
+    }
+
+    /// Works like [fuzzy_candidates](Self::fuzzy_candidates), but also finds the candidates for each of the key's
+    /// synonym expansions, which are returned along with the candidates
+    fn fuzzy_candidates_with_synonyms<K>(&self, key : &K, max_distance : Option<usize>) -> Result<(KeyGroupCandidates<'_>, Vec<OwnedKeyT>), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        let mut candidates = self.fuzzy_candidates(key, max_distance)?;
+        let expansions = self.synonym_expansions(key)?;
+        for expansion in expansions.iter() {
+            candidates = candidates.merge(self.fuzzy_candidates(expansion, max_distance)?);
+        }
+        Ok((candidates, expansions))
+    }
+
+    /// Returns the alternate forms of a lookup key, formed by replacing its tokens with their synonyms, or nothing
+    /// if the Table doesn't consult [SYNONYMS](TableConfig::SYNONYMS)
+    fn synonym_expansions<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> Result<Vec<OwnedKeyT>, String> {
+
+        if !ConfigT::SYNONYMS {
+            return Ok(vec![]);
+        }
+        let folded_key = self.params.fold_key(key.get_key_string());
+        let tokens : Vec<&str> = folded_key.split_whitespace().collect();
+        let token_synonyms = tokens.iter().map(|token| self.db.get_synonyms(token)).collect::<Result<Vec<Vec<String>>, String>>()?;
+
+        //An expansion that's too long to look up is skipped, rather than failing the lookup of the key itself
+        Ok(synonym_expansions(&tokens, &token_synonyms).into_iter()
+            .filter(|expansion| expansion.chars().count() <= MAX_KEY_LENGTH)
+            .map(OwnedKeyT::from_string)
+            .collect())
+    }
+
+    /// Wraps a distance function, so a key's distance is its distance from the closest of the lookup key and the
+    /// lookup key's synonym expansions
+    fn with_synonym_distances<'a, F>(&self, expansions : &[OwnedKeyT], distance_function : F) -> BoxedDistanceFunction<'a, ConfigT::KeyCharT, ConfigT::DistanceT>
+        where
+        F : Fn(&[ConfigT::KeyCharT], &[ConfigT::KeyCharT]) -> ConfigT::DistanceT + 'a
+    {
+        if expansions.is_empty() {
+            return Box::new(distance_function);
+        }
+        let expansions_chars : Vec<Vec<ConfigT::KeyCharT>> = expansions.iter().map(|expansion| self.comparable_key_chars(expansion)).collect();
+        Box::new(move |key : &[ConfigT::KeyCharT], lookup_key : &[ConfigT::KeyCharT]| {
+            expansions_chars.iter().fold(distance_function(key, lookup_key), |closest, expansion_chars| {
+                let distance = distance_function(key, expansion_chars);
+                if distance < closest { distance } else { closest }
+            })
+        })
+    }
+
+    pub(crate) fn lookup_fuzzy_raw_internal<K>(&self, key : &K, max_distance : Option<usize>) -> Result<FuzzyRawLookup<'_>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        Ok(FuzzyRawLookup::new(self.fuzzy_candidates_with_synonyms(key, max_distance)?.0))
+    }
+
+    /// Returns a lazy iterator over all RecordIDs and smallest distance values found with a fuzzy lookup,
+    /// evaluating the supplied distance function for each candidate record as it is reached.
+    /// 
+    /// NOTE: In order to return a reliable smallest distance for a record without first visiting every
+    /// candidate key group, the distance function is evaluated against all of the record's keys the first
+    /// time the record is encountered.  [lookup_fuzzy_raw_internal] could be used instead if the caller
+    /// doesn't need the distances.
+    pub(crate) fn lookup_fuzzy_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K, threshold : Option<ConfigT::DistanceT>, max_distance : Option<usize>) -> Result<FuzzyLookup<'_, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_with_internal(key, threshold, max_distance, ConfigT::DISTANCE_FUNCTION)
+    }
+
+    /// Works like [lookup_fuzzy_internal](Self::lookup_fuzzy_internal), but evaluates the supplied distance function
+    /// instead of the Table's [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION)
+    pub(crate) fn lookup_fuzzy_with_internal<'a, K, F>(&'a self, key : &K, threshold : Option<ConfigT::DistanceT>, max_distance : Option<usize>, distance_function : F) -> Result<FuzzyLookup<'a, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>,
+        F : Fn(&[ConfigT::KeyCharT], &[ConfigT::KeyCharT]) -> ConfigT::DistanceT + 'a
+    {
+
+        let (candidates, expansions) = self.fuzzy_candidates_with_synonyms(key, max_distance)?;
+        let threshold = threshold.or_else(|| self.config.default_threshold());
+        Ok(self.new_fuzzy_lookup(candidates, self.comparable_key_chars(key), self.with_synonym_distances(&expansions, distance_function), threshold))
+    }
+
+    /// Works like [lookup_fuzzy_internal](Self::lookup_fuzzy_internal), collecting the results sorted by distance,
+    /// except that every variant is probed before any candidate record is evaluated, so the records may be
+    /// evaluated concurrently
+    pub(crate) fn lookup_fuzzy_parallel_internal<K>(&self, key : &K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>,
+        ConfigT::KeyCharT : Send + Sync,
+        ConfigT::DistanceT : Send + Sync,
+    {
+
+        let (candidates, expansions) = self.fuzzy_candidates_with_synonyms(key, None)?;
+        let threshold = threshold.or_else(|| self.config.default_threshold());
+        let records = candidates.into_records()?;
+
+        //A key's distance is its distance from the closest of the lookup key and its synonym expansions, the same
+        // as with_synonym_distances, but without boxing a closure that can't be shared between threads
+        let lookup_keys_chars : Vec<Vec<ConfigT::KeyCharT>> = std::iter::once(self.comparable_key_chars(key))
+            .chain(expansions.iter().map(|expansion| self.comparable_key_chars(expansion)))
+            .collect();
+        let distance = |key_chars : &[ConfigT::KeyCharT]| {
+            lookup_keys_chars[1..].iter().fold(ConfigT::DISTANCE_FUNCTION(key_chars, &lookup_keys_chars[0]), |closest, lookup_key_chars| {
+                let distance = ConfigT::DISTANCE_FUNCTION(key_chars, lookup_key_chars);
+                if distance < closest { distance } else { closest }
+            })
+        };
+
+        let db = &self.db;
+        let perf_counters = &self.perf_counters;
+        let key_params = self.params.folds_keys().then_some(&self.params);
+        let evaluate = |&(record_id, include_injected) : &(RecordID, bool)| -> Result<Option<(RecordID, ConfigT::DistanceT)>, String> {
+            let closest = closest_record_distance::<OwnedKeyT, _, _>(db, perf_counters, key_params, record_id, include_injected, &distance)?;
+            Ok(closest.filter(|closest| threshold.is_none_or(|threshold| *closest <= threshold)).map(|closest| (record_id, closest)))
+        };
+
+        #[cfg(feature = "parallel")]
+        let evaluated : Vec<Option<(RecordID, ConfigT::DistanceT)>> = if records.len() >= PARALLEL_LOOKUP_MIN_RECORDS {
+            use rayon::prelude::*;
+            records.par_iter().map(evaluate).collect::<Result<_, String>>()?
+        } else {
+            records.iter().map(evaluate).collect::<Result<_, String>>()?
+        };
+        #[cfg(not(feature = "parallel"))]
+        let evaluated : Vec<Option<(RecordID, ConfigT::DistanceT)>> = records.iter().map(evaluate).collect::<Result<_, String>>()?;
+
+        let mut results : Vec<(RecordID, ConfigT::DistanceT)> = evaluated.into_iter().flatten().collect();
+
+        #[cfg(feature = "perf_counters")]
+        { self.perf_counters.update(|fields| fields.records_found_count += results.len()); }
+
+        sort_by_distance(&mut results);
+        Ok(results)
+    }
 
-            //Compute all variants for the keys we're removing from this group
-            let mut remove_keys_variants = HashSet::new();
-            for remove_key in deleted_group_keys_sets[idx].iter() {
-                let keys_variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(remove_key, &self.config);
-                remove_keys_variants.extend(keys_variants);
-            }
+    /// Works like [lookup_fuzzy_internal](Self::lookup_fuzzy_internal) followed by [with_distances_and_values](Self::with_distances_and_values),
+    /// except that if [VALUE_PREFETCH_DEPTH](TableConfig::VALUE_PREFETCH_DEPTH) is set, the values are read on
+    /// another thread while the lookup is still confirming candidates
+    pub(crate) fn lookup_fuzzy_with_values_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyResultsWithValues<ConfigT>, String> {
 
-            //Compute all the variants for the keys that must remain in the group
-            let mut remaining_keys_variants = HashSet::new();
-            for remaining_key in remaining_group_keys_sets[idx].iter() {
-                let keys_variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(remaining_key, &self.config);
-                remaining_keys_variants.extend(keys_variants);
-            }
+        let lookup = self.lookup_fuzzy_internal(key, threshold, None)?;
+        if ConfigT::VALUE_PREFETCH_DEPTH == 0 {
+            return self.with_distances_and_values(lookup.into_vec()?);
+        }
 
-            //Exclude all of the overlapping variants, leaving only the variants that are unique
-            //to the keys we're removing
-            let mut unique_keys_variants = HashSet::new();
-            for unique_keys_variant in remove_keys_variants.difference(&remaining_keys_variants) {
-                unique_keys_variants.insert(unique_keys_variant.to_owned());
+        //NOTE: ValueT isn't required to be Send, so the prefetch thread only reads the bytes, and the values
+        // are decoded back on this thread
+        let (results, value_bytes) = std::thread::scope(|scope| -> Result<_, String> {
+            let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<RecordID>>(ConfigT::VALUE_PREFETCH_DEPTH);
+            let db = &self.db;
+            let prefetcher = scope.spawn(move || -> Result<Vec<ValueBytes>, String> {
+                let mut value_bytes = vec![];
+                for batch in receiver {
+                    value_bytes.extend(db.get_value_bytes_multi(&batch)?);
+                }
+                Ok(value_bytes)
+            });
+
+            let mut results = vec![];
+            let mut batch = vec![];
+            for result in lookup {
+                let (record_id, distance) = result?;
+                results.push((record_id, distance));
+                batch.push(record_id);
+                //If the send fails, the prefetch thread has stopped with an error, which is returned by join
+                if batch.len() == VALUE_PREFETCH_BATCH_SIZE && sender.send(core::mem::take(&mut batch)).is_err() {
+                    break;
+                }
             }
+            if !batch.is_empty() {
+                let _ = sender.send(batch);
+            }
+            drop(sender);
+            let value_bytes = prefetcher.join().unwrap_or_else(|payload| std::panic::resume_unwind(payload));
+            Ok((results, value_bytes))
+        })?;
 
-            //Delete our KeyGroupID from each variant's list, and remove the variant list if we made it empty
-            self.db.delete_variant_references(group_id, unique_keys_variants)?;
+        let value_bytes = value_bytes?;
+        let mut results_with_values = Vec::with_capacity(results.len());
+        for ((record_id, distance), value_bytes) in results.into_iter().zip(value_bytes.iter()) {
+            let value = self.db.decode_value_bytes(value_bytes)?;
+            self.access_stats.record_hit(&self.db, record_id)?;
+            results_with_values.push((record_id, distance, value));
+        }
+        Ok(results_with_values)
+    }
 
-            //Update or delete the group
-            if remaining_group_keys_sets[idx].is_empty() {
-                //Delete the group's keys record if we made the group empty
-                self.db.delete_key_group_entry(group_id)?;
-            } else {
-                //Otherwise update the group's keys record
-                self.db.put_key_group_entry(group_id, &remaining_group_keys_sets[idx])?;
-                remaining_group_indices.push(group_id.group_idx());
-            }
-            
+    /// Creates a [FuzzyLookup] that evaluates the candidates, and queues each key group it loads to be checked by
+    /// [apply_read_repairs](Table::apply_read_repairs) if [READ_REPAIR](TableConfig::READ_REPAIR) is set
+    fn new_fuzzy_lookup<'a>(&'a self, candidates : KeyGroupCandidates<'a>, lookup_key_chars : Vec<ConfigT::KeyCharT>, distance_function : BoxedDistanceFunction<'a, ConfigT::KeyCharT, ConfigT::DistanceT>, threshold : Option<ConfigT::DistanceT>) -> FuzzyLookup<'a, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT> {
+        let lookup = FuzzyLookup::new(candidates, lookup_key_chars, distance_function, threshold, self.params.folds_keys().then_some(&self.params));
+
+        //A read-only Table could never apply the repairs, so there's no point queuing them
+        if ConfigT::READ_REPAIR && self.db.is_writable() {
+            lookup.with_key_group_check(Box::new(move |key_group_id| self.queue_read_repair(key_group_id)))
+        } else {
+            lookup
         }
+    }
 
-        //Update the record's rec_data entry to reflect the new groups after deletion
-        self.db.put_record_key_groups(record_id, &remaining_group_indices[..])
+    /// Queues a key group to be checked for missing references, unless the queue is full.  Nothing is read from
+    /// the database, so the lookup isn't slowed by the check
+    fn queue_read_repair(&self, key_group_id : KeyGroupID) {
+        let mut read_repairs = self.read_repairs.lock().unwrap();
+        if read_repairs.len() < MAX_PENDING_READ_REPAIRS {
+            read_repairs.insert(key_group_id);
+        }
     }
 
-    /// Replaces all of the keys in a record with the supplied keys
-    fn replace_keys_internal<K>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String>
+    /// Performs a fuzzy lookup that returns at most `page_size` results, continuing from the cursor if one is
+    /// supplied, and returns a cursor for the next page unless there is nothing left to probe
+    pub(crate) fn lookup_fuzzy_page_internal<K>(&self, key : &K, threshold : Option<ConfigT::DistanceT>, page_size : usize, cursor : Option<LookupCursor>) -> Result<FuzzyResultsPage<ConfigT>, String>
         where
-        K : Key<KeyCharT = ConfigT::KeyCharT>
+        K : Key<KeyCharT = ConfigT::KeyCharT>,
+        ConfigT::DistanceT : Serialize,
     {
 
-        if keys.is_empty() {
-            return Err("record must have at least one key".to_string());
+        if page_size == 0 {
+            return Err("page_size must be at least 1".to_string());
         }
-        if keys.iter().any(|key| key.num_chars() > MAX_KEY_LENGTH) {
-            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+
+        //The cursor's variants and key groups are only meaningful for the lookup, and the table, that produced it
+        let lookup_key = self.exact_key_bytes(key);
+        let threshold_bytes = threshold_bytes(&threshold)?;
+        let mut lookup = self.lookup_fuzzy_internal(key, threshold, None)?;
+        if let Some(cursor) = cursor {
+            if cursor.table_id != self.table_id {
+                return Err("the cursor was produced by a lookup on a different table, or before the table was reset".to_string());
+            }
+            if cursor.lookup_key != lookup_key {
+                return Err("the cursor was produced by a lookup with a different key".to_string());
+            }
+            if cursor.threshold != threshold_bytes {
+                return Err("the cursor was produced by a lookup with a different threshold".to_string());
+            }
+            lookup = lookup.resume(cursor);
         }
 
-        //Delete the old keys
-        self.delete_keys_internal(record_id)?;
+        let results = lookup.by_ref().take(page_size).collect::<Result<Vec<(RecordID, ConfigT::DistanceT)>, String>>()?;
 
-        //Set the keys on the new record
-        self.put_record_keys(record_id, keys.iter(), keys.len())
+        Ok((results, lookup.into_cursor(self.table_id, lookup_key, threshold_bytes)))
     }
 
-    /// Replaces a record's value with the supplied value.  Returns the value that was replaced
-    /// 
-    /// The supplied `record_id` must references an existing record that has not been deleted.
-    /// 
-    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
-    /// unwrapped RocksDB error.
-    pub fn replace_value(&mut self, record_id : RecordID, value : &ConfigT::ValueT) -> Result<ConfigT::ValueT, String> {
+    /// Performs a fuzzy lookup and collects the results, or returns the results cached from an identical lookup
+    pub(crate) fn lookup_fuzzy_cached_internal<K>(&self, key : &K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
 
-        let old_value = self.db.get_value(record_id)?;
+        if key.num_chars() > MAX_KEY_LENGTH {
+            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+        }
 
-        self.db.put_value(record_id, value)?;
+        //The cache is keyed by the key as it's compared, so keys that differ only by case share an entry if the
+        // Table isn't case-sensitive
+        let threshold = threshold.or_else(|| self.config.default_threshold());
+        let query = self.exact_key_bytes(key);
+        if let Some(results) = self.query_cache.get(&query, threshold) {
+            return Ok(results);
+        }
 
-        Ok(old_value)
+        //The entry is discarded when a write touches one of the variants, or n-grams, that were probed for the
+        // key or any of its synonym expansions
+        let (mut candidates, mut probed) = self.cached_lookup_candidates(key)?;
+        let expansions = self.synonym_expansions(key)?;
+        for expansion in expansions.iter() {
+            let (expansion_candidates, expansion_probed) = self.cached_lookup_candidates(expansion)?;
+            candidates = candidates.merge(expansion_candidates);
+            probed.extend(expansion_probed);
+        }
+        let results = self.new_fuzzy_lookup(candidates, self.comparable_key_chars(key), self.with_synonym_distances(&expansions, ConfigT::DISTANCE_FUNCTION), threshold).into_vec()?;
+
+        self.query_cache.insert(query, threshold, probed, results.clone());
+        Ok(results)
     }
 
-    /// Inserts a record into the Table, called by insert(), which is implemented differently depending
-    /// on the UTF8_KEYS constant
-    /// 
-    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
-    /// unwrapped RocksDB error.
-    fn insert_internal<'a, K, KeysIterT : Iterator<Item=&'a K>>(&mut self, keys_iter : KeysIterT, num_keys : usize, value : &ConfigT::ValueT) -> Result<RecordID, String>
+    /// Returns the candidates for a cached lookup of a key, along with the variants, or n-grams, that were probed
+    fn cached_lookup_candidates<K>(&self, key : &K) -> Result<(KeyGroupCandidates<'_>, HashSet<Vec<u8>>), String>
         where
-        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
+        K : Key<KeyCharT = ConfigT::KeyCharT>
     {
-
-        if num_keys < 1 {
-            return Err("record must have at least one key".to_string());
+        if self.params.index_strategy.ngram_len().is_some() {
+            Ok((self.fuzzy_candidates(key, None)?, self.key_ngrams(key)))
+        } else {
+            let variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(key, &self.params, ConfigT::VARIANT_SCHEME);
+            Ok((KeyGroupCandidates::new(&self.db, &self.perf_counters, variants.clone(), ConfigT::VARIANT_PRIORITY_FUNCTION), variants))
         }
+    }
 
-        let new_record_id = match self.deleted_records.pop() {
-            None => {
-                //We'll be creating a new record, so get the next unique record_id
-                let new_record_id = RecordID::from(self.record_count);
-                self.record_count += 1;
-                new_record_id
-            },
-            Some(record_id) => record_id
+    /// Performs a fuzzy lookup for records with a key that begins with something close to `prefix`
+    pub(crate) fn lookup_prefix_fuzzy_internal<K>(&self, prefix : &K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        let candidates = self.prefix_fuzzy_candidates(prefix)?;
+        let threshold = threshold.or_else(|| self.config.default_threshold());
+
+        //A key's distance is the distance of its closest prefix, among the prefixes within MAX_DELETES chars of
+        // the length of the lookup prefix
+        let max_deletes = self.params.max_deletes;
+        let distance_function = move |key : &[ConfigT::KeyCharT], prefix : &[ConfigT::KeyCharT]| {
+            let min_len = prefix.len().saturating_sub(max_deletes).max(1);
+            let max_len = (prefix.len() + max_deletes).min(key.len());
+            (min_len..=max_len)
+                .map(|len| ConfigT::DISTANCE_FUNCTION(&key[..len], prefix))
+                .reduce(|closest, distance| if distance < closest { distance } else { closest })
+                .unwrap_or_else(|| ConfigT::DISTANCE_FUNCTION(key, prefix))
         };
+        Ok(self.new_fuzzy_lookup(candidates, self.comparable_key_chars(prefix), Box::new(distance_function), threshold))
+    }
 
-        //Set the keys on the new record
-        self.put_record_keys(new_record_id, keys_iter, num_keys)?;
+    /// Performs a fuzzy lookup where each key's threshold is `1 - min_similarity` times the length of the longer of
+    /// that key and the lookup key, rounded down
+    pub(crate) fn lookup_fuzzy_relative_internal<K>(&self, key : &K, min_similarity : f32) -> Result<FuzzyLookup<'_, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
 
-        //Put the value into its appropriate table
-        self.db.put_value(new_record_id, value)?;
+        if !(0.0..=1.0).contains(&min_similarity) {
+            return Err("min_similarity must be between 0.0 and 1.0".to_string());
+        }
 
-        Ok(new_record_id)
+        //The relative threshold replaces the default threshold, rather than being applied along with it
+        let (candidates, expansions) = self.fuzzy_candidates_with_synonyms(key, None)?;
+        let lookup = self.new_fuzzy_lookup(candidates, self.comparable_key_chars(key), self.with_synonym_distances(&expansions, ConfigT::DISTANCE_FUNCTION), None);
+        Ok(lookup.with_key_threshold(Box::new(move |key_len, lookup_key_len| {
+            //NOTE: Keys can't be longer than MAX_KEY_LENGTH, so the bound always fits in a u8
+            let bound = ((1.0 - min_similarity) * key_len.max(lookup_key_len) as f32).floor();
+            ConfigT::DistanceT::from(bound as u8)
+        })))
     }
 
-    /// Visits all possible candidate keys for a given fuzzy search key, based on config.max_deletes,
-    /// and invokes the supplied closure for each candidate KeyGroup found.
-    /// 
-    /// NOTE: The same group may be found via multiple variants.  It is the responsibility of
-    /// the closure to avoid doing duplicate work.
-    /// 
-    /// QUESTION: should the visitor closure be able to return a bool, to mean "stop" or "keep going"?
-    fn visit_fuzzy_candidates<K, F : FnMut(KeyGroupID)>(&self, key : &K, mut visitor : F) -> Result<(), String>
+    /// Performs a fuzzy lookup within `max_edits` deletes, that skips the keys a banded Levenshtein check against the
+    /// lookup key rejects, before they reach the distance function
+    pub(crate) fn lookup_fuzzy_pruned_internal<K>(&self, key : &K, threshold : Option<ConfigT::DistanceT>, max_edits : usize) -> Result<FuzzyLookup<'_, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT>, String>
         where
         K : Key<KeyCharT = ConfigT::KeyCharT>
     {
+        self.lookup_fuzzy_within_internal(key, threshold, max_edits)
+    }
 
-        if key.num_chars() > MAX_KEY_LENGTH {
-            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
-        }
+    /// Performs a fuzzy lookup that only probes the variants within `max_distance` deletes of the key, and prunes
+    /// the candidate keys that are more than `max_distance` edits from it with a banded Levenshtein check
+    ///
+    /// If scanning every record is estimated to be cheaper than probing the variants, the records are scanned
+    /// instead.  Every key the variants could find within `max_distance` is found by the scan too, and the banded
+    /// check rejects the rest, so the results are the same either way.
+    pub(crate) fn lookup_fuzzy_within_internal<K>(&self, key : &K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        let lookup = match self.lookup_strategy(key, max_distance) {
+            LookupStrategy::Variants => self.lookup_fuzzy_internal(key, threshold, Some(max_distance))?,
+            LookupStrategy::Scan => {
 
-        //Create all of the potential variants based off of the "meaningful" part of the key
-        let variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(key, &self.config);
+                #[cfg(feature = "perf_counters")]
+                { self.perf_counters.update(|fields| fields.pruned_scan_count += 1); }
 
-        #[cfg(feature = "perf_counters")]
-        { self.perf_counters.update(|fields| fields.variant_lookup_count += variants.len() ); }
+                let candidates = KeyGroupCandidates::from_records(&self.db, &self.perf_counters, self.scan_record_ids()?, ConfigT::VARIANT_PRIORITY_FUNCTION);
+                let expansions = self.synonym_expansions(key)?;
+                let threshold = threshold.or_else(|| self.config.default_threshold());
+                self.new_fuzzy_lookup(candidates, self.comparable_key_chars(key), self.with_synonym_distances(&expansions, ConfigT::DISTANCE_FUNCTION), threshold)
+                    .with_strategy(LookupStrategy::Scan)
+            }
+        };
+        let bound = BandedLevenshtein::new(self.comparable_key_chars(key), max_distance);
+        Ok(lookup.with_key_filter(Box::new(move |key_chars| bound.accepts(key_chars))))
+    }
 
-        //Check to see if we have entries in the "variants" database for any of the key variants
-        self.db.visit_variants(variants, |variant_vec_bytes| {
+    /// Returns [Scan](LookupStrategy::Scan) if scanning every record is estimated to be cheaper than probing the
+    /// deletion variants of the key, to find the keys within `max_edits` of it, or [Variants](LookupStrategy::Variants)
+    /// otherwise
+    ///
+    /// The variants can only be relied on to find every key within `max_edits` if it isn't beyond [MAX_DELETES](TableConfig::MAX_DELETES),
+    /// so a larger `max_edits` always uses the variants, to keep the results the same as they are for a large Table.
+    /// So does a Table with n-grams or a [VARIANT_SCHEME](TableConfig::VARIANT_SCHEME) other than the deletion
+    /// variants, because a scan can't find the same candidates they do.
+    fn lookup_strategy<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K, max_edits : usize) -> LookupStrategy {
+        if max_edits > self.params.max_deletes || self.params.index_strategy.ngram_len().is_some() || self.params.variant_scheme != DELETION_VARIANT_SCHEME_ID {
+            return LookupStrategy::Variants;
+        }
+        let live_records = match self.live_record_estimate() {
+            Some(live_records) => live_records,
+            None => return LookupStrategy::Variants
+        };
+        let scan_cost = live_records.saturating_mul(SCAN_COST_PER_RECORD);
+        let variant_count = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants_within(key, max_edits, &self.params, ConfigT::VARIANT_SCHEME).len();
+        if scan_cost < variant_count { LookupStrategy::Scan } else { LookupStrategy::Variants }
+    }
 
-            #[cfg(feature = "perf_counters")]
-            {
-                let num_key_group_ids = bincode_vec_fixint_len(variant_vec_bytes);
-                let mut counter_fields = self.perf_counters.get();
-                counter_fields.variant_load_count += 1;
-                counter_fields.key_group_ref_count += num_key_group_ids;
-                if counter_fields.max_variant_entry_refs < num_key_group_ids {
-                    counter_fields.max_variant_entry_refs = num_key_group_ids;
-                }
-                self.perf_counters.set(counter_fields);
-            }
-    
-            // Call the visitor for each KeyGroup we found
-            for key_group_id_bytes in bincode_vec_iter::<KeyGroupID>(variant_vec_bytes) {
-                visitor(KeyGroupID::from(usize::from_le_bytes(key_group_id_bytes.try_into().unwrap())));
-            }
-        })
+    /// Returns an estimate of the number of records a scan would visit, which leaves out the RecordIDs of deleted
+    /// records and the records with a tombstone, or `None` if the Table has [DETERMINISTIC_RECORD_IDS](TableConfig::DETERMINISTIC_RECORD_IDS),
+    /// and so doesn't keep count of its RecordIDs
+    fn live_record_estimate(&self) -> Option<usize> {
+        if ConfigT::DETERMINISTIC_RECORD_IDS {
+            return None;
+        }
+        Some(self.record_count.saturating_sub(self.deleted_records.len()).saturating_sub(self.db.tombstone_count()))
     }
 
-    fn lookup_fuzzy_raw_internal<K>(&self, key : &K) -> Result<impl Iterator<Item=RecordID>, String>
+    /// Performs a fuzzy lookup within `max_distance`, scanning the records that the index can't rule out, as set
+    /// by the [FALLBACK_SCAN](TableConfig::FALLBACK_SCAN), if `max_distance` is beyond [MAX_DELETES](TableConfig::MAX_DELETES)
+    pub(crate) fn lookup_fuzzy_extended_internal<K>(&self, key : &K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT>, String>
         where
         K : Key<KeyCharT = ConfigT::KeyCharT>
     {
+        if max_distance <= self.params.max_deletes {
+            return self.lookup_fuzzy_within_internal(key, threshold, max_distance);
+        }
 
-        //Create a new HashSet to hold all of the RecordIDs that we find
-        let mut result_set = HashSet::new(); //TODO, may want to allocate this with a non-zero capacity
-
-        //Our visitor closure just puts the KeyGroup's RecordID into a HashSet
-        let raw_visitor_closure = |key_group_id : KeyGroupID| {
-            result_set.insert(key_group_id.record_id());
+        let candidates = match ConfigT::FALLBACK_SCAN {
+            FallbackScan::Disabled => return Err(format!("max_distance {} is beyond the MAX_DELETES of {}, and FALLBACK_SCAN is disabled", max_distance, self.params.max_deletes)),
+            FallbackScan::Full => KeyGroupCandidates::from_records(&self.db, &self.perf_counters, self.scan_record_ids()?, ConfigT::VARIANT_PRIORITY_FUNCTION),
+            FallbackScan::Sampled(n) => self.fuzzy_candidates(key, None)?.with_records(sample_records(self.scan_record_ids()?, n)),
         };
+        let threshold = threshold.or_else(|| self.config.default_threshold());
+        let bound = BandedLevenshtein::new(self.comparable_key_chars(key), max_distance);
+        let lookup = self.new_fuzzy_lookup(candidates, self.comparable_key_chars(key), Box::new(ConfigT::DISTANCE_FUNCTION), threshold);
+        Ok(lookup.with_key_filter(Box::new(move |key_chars| bound.accepts(key_chars))))
+    }
 
-        //Visit all the potential records
-        self.visit_fuzzy_candidates(key, raw_visitor_closure)?;
-
-        #[cfg(feature = "perf_counters")]
-        { self.perf_counters.update(|fields| fields.records_found_count += result_set.len() ); }
-
-        //Return an iterator through the HashSet we just made
-        Ok(result_set.into_iter())
+    /// Returns the RecordID of every record that has keys, in ascending order, by scanning the "keys" CF
+    fn scan_record_ids(&self) -> Result<Vec<RecordID>, String> {
+        let record_ids : HashSet<RecordID> = self.db.key_group_ids()?.into_iter().map(|key_group_id| key_group_id.record_id()).collect();
+        let mut record_ids : Vec<RecordID> = record_ids.into_iter().collect();
+        record_ids.sort();
+        Ok(record_ids)
     }
 
-    /// Returns an iterator over all RecordIDs and smallest distance values found with a fuzzy lookup
-    /// after evaluating the supplied  distance function for every found candidate key.
-    /// 
-    /// NOTE: This function evaluates the distance function for all keys in advance of returning the
-    /// iterator.  A lazy evaluation would be possible but would incur a sort of the KeyGroupIDs.
-    /// This would be needed to ensure that the smallest distance value for a given record was returned.
-    /// It would be necessary to evaluate every key group for a particular record before returning the
-    /// record.  The decision not to do this is on account of the fact that [lookup_fuzzy_raw_internal]
-    /// could be used instead if the caller wants a quick-to-return iterator.
-    fn lookup_fuzzy_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K, threshold : Option<ConfigT::DistanceT>) -> Result<impl Iterator<Item=(RecordID, ConfigT::DistanceT)>, String> {
+    /// Performs a fuzzy lookup with the variants one delete away from the key, then widens the lookup one delete
+    /// at a time, up to [MAX_DELETES](TableConfig::MAX_DELETES), until at least `min_results` results are found
+    pub(crate) fn lookup_fuzzy_adaptive_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K, threshold : Option<ConfigT::DistanceT>, min_results : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.lookup_fuzzy_widening(key, threshold, |results, _max_deletes| Ok(results.len() >= min_results))
+    }
 
-        let distance_function = ConfigT::DISTANCE_FUNCTION;
+    /// Returns the `n` records closest to the key, ordered by ascending distance and then by RecordID
+    ///
+    /// The lookup is widened one delete at a time, and stops as soon as `n` records have been found within the
+    /// number of deletes probed so far.  Any record that hasn't been found yet is further from the key than that,
+    /// as long as the distance function never returns less than the number of deletes needed to find a key.
+    pub(crate) fn lookup_best_n_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K, n : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+
+        let mut results = self.lookup_fuzzy_widening(key, None, |results, max_deletes| {
+            sort_by_distance(results);
+            let max_deletes = u8::try_from(max_deletes).map_err(|_| format!("can't compare {} deletes with a distance", max_deletes))?;
+            Ok(n == 0 || (results.len() >= n && results[n-1].1 <= ConfigT::DistanceT::from(max_deletes)))
+        })?;
+        sort_by_distance(&mut results);
+        results.truncate(n);
+        Ok(results)
+    }
 
-        //Create a new HashMap to hold all of the RecordIDs that we might want to return, and the lowest
-        // distance we find for that particular record
-        let mut result_map = HashMap::new(); //TODO, may want to allocate this with a non-zero capacity
-        let mut visited_groups = HashSet::new();
+    /// Performs a fuzzy lookup with the variants one delete away from the key, then widens the lookup one delete
+    /// at a time, up to [MAX_DELETES](TableConfig::MAX_DELETES), until `done` returns `true`
+    ///
+    /// `done` is called with the results found so far, and the number of deletes that have been probed.  An error
+    /// from `done` ends the lookup.
+    fn lookup_fuzzy_widening<K, DoneF>(&self, key : &K, threshold : Option<ConfigT::DistanceT>, mut done : DoneF) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>,
+        DoneF : FnMut(&mut Vec<(RecordID, ConfigT::DistanceT)>, usize) -> Result<bool, String>
+    {
 
-        //If we can borrow the lookup chars directly then do it, otherwise get them from a buffer
-        let key_chars_vec;
-        let looup_key_chars = if let Some(key_chars) = key.borrow_key_chars() {
-            key_chars
-        } else {
-            key_chars_vec = key.get_key_chars();
-            &key_chars_vec[..]
-        };
+        if key.num_chars() > MAX_KEY_LENGTH {
+            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+        }
 
-        //pre-allocate the buffer we'll expand the key-chars into
-        let mut key_chars_buf : Vec<ConfigT::KeyCharT> = Vec::with_capacity(MAX_KEY_LENGTH);
+        let threshold = threshold.or_else(|| self.config.default_threshold());
+        let mut probed_variants : HashSet<Vec<u8>> = HashSet::new();
+        let mut lookup : Option<FuzzyLookup<'_, <Self as TableKeyEncoding>::OwnedKeyT, ConfigT::DistanceT>> = None;
+        let mut results = vec![];
+
+        //A Table that indexes n-grams finds all of the candidates at once, so there's nothing to widen
+        let indexes_ngrams = self.params.index_strategy.ngram_len().is_some();
+        let min_deletes = if indexes_ngrams { self.params.max_deletes } else { self.params.max_deletes.min(1) };
+        for max_deletes in min_deletes..=self.params.max_deletes {
+
+            //Only probe the variants that weren't already probed at a smaller distance
+            let mut variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants_within(key, max_deletes, &self.params, ConfigT::VARIANT_SCHEME);
+            variants.retain(|variant| !probed_variants.contains(variant));
+            probed_variants.extend(variants.iter().cloned());
+
+            let lookup = match &mut lookup {
+                Some(lookup) => {
+                    lookup.push_variants(variants);
+                    lookup
+                },
+                None => {
+                    let candidates = if indexes_ngrams {
+                        self.fuzzy_candidates(key, Some(max_deletes))?
+                    } else {
+                        KeyGroupCandidates::new(&self.db, &self.perf_counters, variants, ConfigT::VARIANT_PRIORITY_FUNCTION)
+                    };
+                    lookup.insert(self.new_fuzzy_lookup(candidates, self.comparable_key_chars(key), Box::new(ConfigT::DISTANCE_FUNCTION), threshold))
+                }
+            };
+            for result in lookup.by_ref() {
+                results.push(result?);
+            }
 
-        //Our visitor closure tests the key using the distance function and threshold
-        let lookup_fuzzy_visitor_closure = |key_group_id : KeyGroupID| {
+            if done(&mut results, max_deletes)? {
+                break;
+            }
+        }
 
-            //QUESTION: Should we have an alternate fast path that only evaluates until we find
-            // any distance smaller than threshold?  It would mean we couldn't return a reliable
-            // distance but would save us evaluating distance for potentially many keys
-            
-            if !visited_groups.contains(&key_group_id) {
+        Ok(results)
+    }
 
-                //Check the record's keys with the distance function and find the smallest distance
-                let mut record_keys_iter = self.db.get_keys_in_group::<<Self as TableKeyEncoding>::OwnedKeyT>(key_group_id, &self.perf_counters).unwrap();
-                
-                let record_key = record_keys_iter.next().unwrap(); //If we have a zero-element keys array, it's a bug elsewhere, so this unwrap should always succeed
-                let record_key_chars = record_key.move_into_buf(&mut key_chars_buf);
-                let mut smallest_distance = distance_function(&record_key_chars[..], looup_key_chars);
-                #[cfg(feature = "perf_counters")]
-                { self.perf_counters.update(|fields| fields.distance_function_invocation_count += 1); }
+    /// Returns the chars of a lookup key, as they should be passed to the distance function.  The key is folded
+    /// if the Table folds keys, e.g. because it isn't case-sensitive
+    fn comparable_key_chars<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> Vec<ConfigT::KeyCharT> {
+        if self.params.folds_keys() {
+            self.params.fold_key(<Self as TableKeyEncoding>::OwnedKeyT::from_key(key)).into_vec()
+        } else {
+            key.get_key_chars()
+        }
+    }
 
-                for record_key in record_keys_iter {
-                    let record_key_chars = record_key.move_into_buf(&mut key_chars_buf);
-                    let distance = distance_function(record_key_chars, looup_key_chars);
-                    if distance < smallest_distance {
-                        smallest_distance = distance;
-                    }
+    /// Returns the smallest distance between a lookup key and any of the supplied keys, if it's within the
+    /// threshold, evaluated the same way a fuzzy lookup evaluates the keys of a record
+    pub(crate) fn closest_key_distance<'k, K, KeysIterT>(&self, key : &K, keys_iter : KeysIterT, threshold : Option<ConfigT::DistanceT>) -> Option<ConfigT::DistanceT>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>,
+        KeysIterT : Iterator<Item=&'k OwnedKeyT>,
+        OwnedKeyT : 'k
+    {
+        let threshold = threshold.or_else(|| self.config.default_threshold());
+        let lookup_key_chars = self.comparable_key_chars(key);
+        keys_iter
+            .map(|other_key| ConfigT::DISTANCE_FUNCTION(&self.comparable_key_chars(other_key), &lookup_key_chars))
+            .filter(|distance| threshold.is_none_or(|threshold| *distance <= threshold))
+            .min_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal))
+    }
 
-                    #[cfg(feature = "perf_counters")]
-                    { self.perf_counters.update(|fields| fields.distance_function_invocation_count += 1); }    
-                }
+    /// Returns `true` if the index would find `key` as a candidate in a fuzzy lookup of `lookup_key`, because they
+    /// share a variant within [MAX_DELETES](TableConfig::MAX_DELETES), or enough n-grams if the Table indexes
+    /// n-grams.  The lookup key's synonym expansions aren't considered
+    pub(crate) fn is_fuzzy_candidate_key<K, OtherK>(&self, lookup_key : &K, key : &OtherK) -> bool
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>,
+        OtherK : Key<KeyCharT = ConfigT::KeyCharT>,
+    {
+        if let Some(n) = self.params.index_strategy.ngram_len() {
+            let lookup_ngrams = self.key_ngrams(lookup_key);
+            let shared_count = self.key_ngrams(key).intersection(&lookup_ngrams).count();
+            return shared_count >= min_shared_ngrams(lookup_ngrams.len(), n, self.params.max_deletes, 0);
+        }
+        let lookup_variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants_within(lookup_key, self.params.max_deletes, &self.params, ConfigT::VARIANT_SCHEME);
+        let key_variants = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::variants(key, &self.params, ConfigT::VARIANT_SCHEME);
+        !lookup_variants.is_disjoint(&key_variants)
+    }
 
-                let insert_distance = match threshold {
-                    Some(threshold) if smallest_distance <= threshold => {
-                        Some(smallest_distance)
-                    }
-                    None => Some(smallest_distance),
-                    _ => None
-                };
+    /// Returns the phonetic codes of a key, as they're stored in the "phonetic" CF, or nothing if the Table
+    /// doesn't have a phonetic index
+    fn phonetic_codes<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> Vec<Vec<u8>> {
+        match ConfigT::PHONETIC_FUNCTION {
+            Some(encoding) => (encoding.function)(&key.get_key_string()).into_iter().map(|code| code.into_bytes()).collect(),
+            None => vec![]
+        }
+    }
 
-                if let Some(smallest_distance) = insert_distance {
-                    match result_map.entry(key_group_id.record_id()) {
-                        Entry::Occupied(mut entry) => {
-                            let current_distance = *entry.get();
-                            if smallest_distance < current_distance {
-                                entry.insert(smallest_distance);
-                            }
-                        }
-                        Entry::Vacant(entry) => {
-                            entry.insert(smallest_distance);
-                        }
-                    }
-                }
+    /// Returns the folded tokens of a key, as they're indexed in the "tokens" CF
+    fn folded_key_tokens<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> Vec<String> {
+        key_tokens(&self.params.fold_key(key.get_key_string()))
+    }
 
-                //Record that we've visited this key group, we we can skip it if we encounter it
-                //via a different variant
-                visited_groups.insert(key_group_id);
-            }
-        };
+    /// Returns the variants of every token of a key, as they're stored in the "tokens" CF, or nothing if the Table
+    /// doesn't have a token index
+    fn token_variants<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> HashSet<Vec<u8>> {
+        if !ConfigT::TOKEN_INDEX {
+            return HashSet::new();
+        }
+        self.folded_key_tokens(key).iter().flat_map(|token| token_variants(token, self.params.max_deletes)).collect()
+    }
 
-        //Visit all the potential records
-        self.visit_fuzzy_candidates(key, lookup_fuzzy_visitor_closure)?;
+    /// Returns the distinct n-grams of a key, as they're stored in the "ngrams" CF, or nothing if the Table doesn't
+    /// index n-grams.  The n-grams are taken from the whole key, folded if the Table folds keys
+    fn key_ngrams<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> HashSet<Vec<u8>> {
+        match self.params.index_strategy.ngram_len() {
+            Some(n) => key_ngrams(&self.comparable_key_chars(key), n),
+            None => HashSet::new()
+        }
+    }
 
-        #[cfg(feature = "perf_counters")]
-        { self.perf_counters.update(|fields| fields.records_found_count += result_map.len() ); }
+    /// Adds the record to the "ngrams" entries of the keys' n-grams, if the Table indexes n-grams, and discards
+    /// the cached lookups that may now find the record through them
+    fn put_key_ngrams<'a, K, KeysIterT : Iterator<Item=&'a K>>(&self, batch : &mut DBWriteBatch, record_id : RecordID, keys_iter : KeysIterT) -> Result<(), String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT> + 'a
+    {
+        if self.params.index_strategy.ngram_len().is_none() {
+            return Ok(());
+        }
+        let ngrams : HashSet<Vec<u8>> = keys_iter.flat_map(|key| self.key_ngrams(key)).collect();
+        self.query_cache.invalidate(record_id, &ngrams);
+        self.db.put_ngram_references(batch, record_id, ngrams.into_iter())
+    }
 
-        //Return an iterator through the HashSet we just made
-        Ok(result_map.into_iter())
+    /// Returns the bytes of a key, as they're stored in the "exact_keys" CF.  The key is folded if the Table
+    /// folds keys, e.g. because it isn't case-sensitive
+    fn exact_key_bytes<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> Vec<u8> {
+        self.params.fold_key(<Self as TableKeyEncoding>::OwnedKeyT::from_key(key)).into_bytes()
     }
 
-    fn lookup_best_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> Result<impl Iterator<Item=RecordID>, String> {
+    pub(crate) fn lookup_best_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K) -> Result<impl Iterator<Item=RecordID>, String> {
 
         //First, we should check to see if lookup_exact gives us what we want.  Because if it does,
         // it's muuuuuuch faster.  If we have an exact result, no other key will be a better match
@@ -575,7 +3126,7 @@ impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UT
         
         //Assuming lookup_exact didn't work, we'll need to perform the whole fuzzy lookup and iterate each key
         //to figure out the closest distance
-        let mut result_iter = self.lookup_fuzzy_internal(key, None)?;
+        let mut result_iter = self.lookup_fuzzy_internal(key, None, None)?.into_vec()?.into_iter();
         
         if let Some(first_result) = result_iter.next() {
             let mut best_distance = first_result.1;
@@ -596,10 +3147,84 @@ impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UT
         Ok(vec![].into_iter())
     }
 
+    /// Returns the records with a key that shares a phonetic code with the key supplied, from the "phonetic" CF
+    pub(crate) fn lookup_phonetic_internal<K>(&self, key : &K) -> Result<Vec<RecordID>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        if ConfigT::PHONETIC_FUNCTION.is_none() {
+            return Err("the Table doesn't have a phonetic index".to_string());
+        }
+
+        //A key may have more than one code, so a record may be found under several of them
+        let mut record_ids = vec![];
+        for code in self.phonetic_codes(key) {
+            for record_id in self.db.get_phonetic_records(&code)? {
+                if !record_ids.contains(&record_id) {
+                    record_ids.push(record_id);
+                }
+            }
+        }
+        Ok(record_ids)
+    }
+
+    /// Returns the records with a key that has a close match for every token of the phrase, from the "tokens" CF,
+    /// along with the sum of the distances of the closest matches, evaluated with `distance_function`
+    pub(crate) fn lookup_phrase_fuzzy_internal<K, F>(&self, phrase : &K, threshold : Option<ConfigT::DistanceT>, distance_function : F) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>,
+        F : Fn(&[char], &[char]) -> ConfigT::DistanceT
+    {
+        if !ConfigT::TOKEN_INDEX {
+            return Err("the Table doesn't have a token index".to_string());
+        }
+        let phrase_tokens = self.folded_key_tokens(phrase);
+        if phrase_tokens.is_empty() {
+            return Ok(vec![]);
+        }
+
+        //A record is only a candidate if every token of the phrase finds it through one of the token's variants
+        let mut candidates : Option<HashSet<RecordID>> = None;
+        for token in phrase_tokens.iter() {
+            let mut token_records = HashSet::new();
+            for variant in token_variants(token, self.params.max_deletes) {
+                token_records.extend(self.db.get_token_records(&variant)?);
+            }
+            candidates = Some(match candidates {
+                Some(candidates) => candidates.intersection(&token_records).copied().collect(),
+                None => token_records
+            });
+        }
+
+        //The tokens of all of a candidate's keys are evaluated, but every phrase token must match a token of the
+        // same key
+        let threshold = threshold.or_else(|| self.config.default_threshold());
+        let phrase_token_chars : Vec<Vec<char>> = phrase_tokens.iter().map(|token| token.chars().collect()).collect();
+        let mut results = vec![];
+        for record_id in candidates.unwrap_or_default() {
+            let mut closest : Option<ConfigT::DistanceT> = None;
+            for key_group in self.db.get_record_key_groups(record_id)? {
+                for key in self.db.get_keys_in_group::<OwnedKeyT>(key_group, &self.perf_counters)? {
+                    let key_token_chars : Vec<Vec<char>> = self.folded_key_tokens(&key).iter().map(|token| token.chars().collect()).collect();
+                    if let Some(distance) = phrase_distance(&phrase_token_chars, &key_token_chars, threshold, &distance_function) {
+                        if closest.is_none_or(|closest_distance| distance < closest_distance) {
+                            closest = Some(distance);
+                        }
+                    }
+                }
+            }
+            if let Some(distance) = closest {
+                results.push((record_id, distance));
+            }
+        }
+        sort_by_distance(&mut results);
+        Ok(results)
+    }
+
     /// Checks the table for records with keys that precisely match the key supplied
     /// 
     /// This function will be more efficient than a fuzzy lookup.
-    fn lookup_exact_internal<K>(&self, lookup_key : &K) -> Result<Vec<RecordID>, String>
+    pub(crate) fn lookup_exact_internal<K>(&self, lookup_key : &K) -> Result<Vec<RecordID>, String>
         where
         K : Key<KeyCharT = ConfigT::KeyCharT>
     {
@@ -609,7 +3234,12 @@ impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UT
             return Err("key length exceeds MAX_KEY_LENGTH".to_string());
         }
 
-        let meaningful_key = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::meaningful_key_substring(lookup_key, &self.config);
+        //If we have the exact key index, it has the answer without any need to check the keys
+        if ConfigT::EXACT_KEY_INDEX {
+            return self.db.get_exact_key_records(&self.exact_key_bytes(lookup_key));
+        }
+
+        let meaningful_key = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::meaningful_key_substring(lookup_key, &self.params);
 
         //BUG!! This "meaningful_noop" code path is flawed!!!
         // A variant could point to a key group, without it being an exact match.  For example,
@@ -624,44 +3254,175 @@ impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UT
 
         //Get the variant for our meaningful_key
         let mut record_ids : Vec<RecordID> = vec![];
-        self.db.visit_exact_variant(meaningful_key.as_bytes(), |variant_vec_bytes| {
+        //NOTE: The closure is called once for each page, if the variant entry is paged
+        self.db.visit_exact_variant(meaningful_key.as_bytes().as_ref(), |key_group_ids| {
 
             #[cfg(feature = "perf_counters")]
             {
-                let num_key_group_ids = bincode_vec_fixint_len(variant_vec_bytes);
+                let num_key_group_ids = key_group_ids.len();
                 self.perf_counters.update(|fields| { 
                     fields.variant_load_count += 1;
                     fields.key_group_ref_count += num_key_group_ids;
                 } );
             }        
 
-            record_ids = if meaningful_noop {
+            if meaningful_noop {
 
                 //If the meaningful_key exactly equals our key, we can just return the variant's results
-                bincode_vec_iter::<KeyGroupID>(variant_vec_bytes).map(|key_group_id_bytes| {
-                    KeyGroupID::from(usize::from_le_bytes(key_group_id_bytes.try_into().unwrap()))
-                }).map(|key_group_id| key_group_id.record_id()).collect()
+                //Injected variants aren't keys, so they never make an exact match
+                record_ids.extend(key_group_ids.filter(|key_group_id| !key_group_id.is_injected()).map(|key_group_id| key_group_id.record_id()));
 
             } else {
 
                 //But if they are different, we need to Iterate every KeyGroupID in the variant in order
                 //  to check if we really have a match on the whole key
-                let owned_lookup_key = <Self as TableKeyEncoding>::OwnedKeyT::from_key(lookup_key);
-                bincode_vec_iter::<KeyGroupID>(variant_vec_bytes)
-                .filter_map(|key_group_id_bytes| {
-                    let key_group_id = KeyGroupID::from(usize::from_le_bytes(key_group_id_bytes.try_into().unwrap()));
+                let folds_keys = self.params.folds_keys();
+                let owned_lookup_key = self.params.fold_key(<Self as TableKeyEncoding>::OwnedKeyT::from_key(lookup_key));
+                record_ids.extend(key_group_ids
+                .filter(|key_group_id| !key_group_id.is_injected())
+                .filter_map(|key_group_id| {
                     
                     // Return only the KeyGroupIDs for records if their keys match the key we are looking up
                     let mut keys_iter = self.db.get_keys_in_group::<<Self as TableKeyEncoding>::OwnedKeyT>(key_group_id, &self.perf_counters).ok()?;
-                    if keys_iter.any(|key| key == owned_lookup_key) {
+                    if keys_iter.any(|key| if folds_keys { self.params.fold_key(key) == owned_lookup_key } else { key == owned_lookup_key }) {
                         Some(key_group_id)
                     } else {
                         None
                     }
-                }).map(|key_group_id| key_group_id.record_id()).collect()
-            };
+                }).map(|key_group_id| key_group_id.record_id()));
+            }
+        })?;
+        if self.db.has_tombstones() {
+            record_ids.retain(|record_id| !self.db.is_tombstoned(*record_id));
+        }
+        self.db.retain_unexpired(&mut record_ids)?;
+
+        Ok(record_ids)
+    }
+
+    /// Returns `true` if [lookup_exact_internal](Table::lookup_exact_internal) would find any records, without
+    /// loading any values, and without loading any keys if the key can be answered from the variants alone
+    pub(crate) fn contains_key_exact_internal<K>(&self, lookup_key : &K) -> Result<bool, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+
+        let lookup_key_len = lookup_key.num_chars();
+        if lookup_key_len > MAX_KEY_LENGTH {
+            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+        }
+
+        //The entries don't tell whether the records they reference have been soft deleted, or have expired
+        if self.db.has_tombstones() || self.db.has_ttl() {
+            return Ok(!self.lookup_exact_internal(lookup_key)?.is_empty());
+        }
+
+        //With the exact key index, the existence of the entry is the answer
+        if ConfigT::EXACT_KEY_INDEX {
+            return self.db.exact_key_exists(&self.exact_key_bytes(lookup_key));
+        }
+
+        //If the meaningful_key is the whole key, the variant entry has the answer.  Otherwise we need the
+        // keys to tell a match from a key that only shares the meaningful prefix
+        let meaningful_key = SymSpell::<<Self as TableKeyEncoding>::OwnedKeyT, UTF8_KEYS>::meaningful_key_substring(lookup_key, &self.params);
+        if meaningful_key.num_chars() != lookup_key_len {
+            return Ok(!self.lookup_exact_internal(lookup_key)?.is_empty());
+        }
+
+        #[cfg(feature = "perf_counters")]
+        { self.perf_counters.update(|fields| fields.variant_lookup_count += 1 ); }
+
+        let mut found = false;
+        self.db.visit_exact_variant(meaningful_key.as_bytes().as_ref(), |mut key_group_ids| {
+
+            #[cfg(feature = "perf_counters")]
+            { self.perf_counters.update(|fields| fields.variant_load_count += 1 ); }
+
+            //Injected variants aren't keys, so they never make an exact match
+            found = found || key_group_ids.any(|key_group_id| !key_group_id.is_injected());
+        })?;
+
+        Ok(found)
+    }
+
+    /// Returns `true` if [lookup_fuzzy_raw_internal](Table::lookup_fuzzy_raw_internal) would find any records.  The
+    /// variants are probed in priority order and probing stops at the first one that references a key group
+    pub(crate) fn contains_key_fuzzy_internal<K>(&self, key : &K) -> Result<bool, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        let mut lookup = self.lookup_fuzzy_raw_internal(key, None)?;
+        Ok(lookup.next().transpose()?.is_some())
+    }
+
+    /// Returns the number of distinct records that [lookup_exact_internal](Table::lookup_exact_internal) would find
+    pub(crate) fn count_exact_internal<K>(&self, lookup_key : &K) -> Result<usize, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        //NOTE: A variant may reference more than one key group belonging to the same record
+        let record_ids : HashSet<RecordID> = self.lookup_exact_internal(lookup_key)?.into_iter().collect();
+        Ok(record_ids.len())
+    }
+
+    /// Returns the number of distinct records that [lookup_fuzzy_raw_internal](Table::lookup_fuzzy_raw_internal)
+    /// would find
+    pub(crate) fn count_fuzzy_internal<K>(&self, key : &K) -> Result<usize, String>
+        where
+        K : Key<KeyCharT = ConfigT::KeyCharT>
+    {
+        //NOTE: The raw lookup already resolves each KeyGroupID to its record only once
+        let mut count = 0;
+        for result in self.lookup_fuzzy_raw_internal(key, None)? {
+            result?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Splits the key into segments, and returns every split in which each segment exactly matches the key of
+    /// at least one record
+    pub(crate) fn lookup_compound_internal<K : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : &K, max_splits : usize) -> Result<Vec<CompoundMatch<<Self as TableKeyEncoding>::OwnedKeyT>>, String> {
+
+        if key.num_chars() > MAX_KEY_LENGTH {
+            return Err("key length exceeds MAX_KEY_LENGTH".to_string());
+        }
+
+        let key_chars = key.get_key_chars();
+        let mut segment_records : HashMap<(usize, usize), Vec<RecordID>> = HashMap::new();
+        let splits = compound_splits(key_chars.len(), max_splits, |start, end| {
+            let record_ids = self.records_with_key(&key_chars[start..end])?;
+            let matches = !record_ids.is_empty();
+            segment_records.insert((start, end), record_ids);
+            Ok(matches)
         })?;
 
+        Ok(splits.into_iter().map(|split| {
+            let mut segments = Vec::with_capacity(split.len());
+            let mut record_ids = Vec::with_capacity(split.len());
+            let mut start = 0;
+            for end in split {
+                segments.push(<Self as TableKeyEncoding>::OwnedKeyT::from_vec(key_chars[start..end].to_vec()));
+                record_ids.push(segment_records[&(start, end)].clone());
+                start = end;
+            }
+            CompoundMatch{segments, record_ids}
+        }).collect())
+    }
+
+    /// Returns the records with a key that matches the supplied key exactly, leaving out the records that
+    /// [lookup_exact_internal](Table::lookup_exact_internal) returns because one of their keys contains the supplied key
+    fn records_with_key(&self, key_chars : &[ConfigT::KeyCharT]) -> Result<Vec<RecordID>, String> {
+
+        let owned_key = self.params.fold_key(<Self as TableKeyEncoding>::OwnedKeyT::from_vec(key_chars.to_vec()));
+
+        let mut record_ids = vec![];
+        for record_id in self.lookup_exact_internal(&owned_key)? {
+            let mut keys_iter = self.get_keys_internal(record_id)?;
+            if keys_iter.any(|key| if self.params.folds_keys() { self.params.fold_key(key) == owned_key } else { key == owned_key }) {
+                record_ids.push(record_id);
+            }
+        }
         Ok(record_ids)
     }
 
@@ -670,7 +3431,49 @@ impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UT
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
     pub fn get_value(&self, record_id : RecordID) -> Result<ConfigT::ValueT, String> {
-        self.db.get_value(record_id)
+        let value = self.db.get_value(record_id)?;
+        self.access_stats.record_hit(&self.db, record_id)?;
+        Ok(value)
+    }
+
+    /// Writes the bytes of a record's value to `writer`, without holding the whole value in memory if it has
+    /// been split into chunks.  Returns the number of bytes written
+    /// 
+    /// The value must be one that serializes as a sequence of bytes, such as a `Vec<u8>` or a `String`, e.g.
+    /// one written with [put_value_from_reader](Table::put_value_from_reader).  An encrypted value is decrypted
+    /// whole before it is written.
+    pub fn get_value_to_writer<W : Write>(&self, record_id : RecordID, writer : &mut W) -> Result<u64, String> {
+        let len = self.db.get_value_to_writer(record_id, writer)?;
+        self.access_stats.record_hit(&self.db, record_id)?;
+        Ok(len)
+    }
+
+    /// Returns the values associated with each of the specified records, in the same order
+    /// 
+    /// The values are fetched from the database in a single batched read, so this is more efficient than
+    /// calling [get_value](Table::get_value) for each record.
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn get_values(&self, record_ids : &[RecordID]) -> Result<Vec<ConfigT::ValueT>, String> {
+        let values = self.db.get_values_multi(record_ids)?;
+        for record_id in record_ids {
+            self.access_stats.record_hit(&self.db, *record_id)?;
+        }
+        Ok(values)
+    }
+
+    /// Pairs each RecordID from a lookup with its value, fetching all of the values in a single batched read
+    pub(crate) fn with_values(&self, record_ids : Vec<RecordID>) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        let values = self.get_values(&record_ids)?;
+        Ok(record_ids.into_iter().zip(values).collect())
+    }
+
+    /// Pairs each result from a fuzzy lookup with its value, fetching all of the values in a single batched read
+    pub(crate) fn with_distances_and_values(&self, results : Vec<(RecordID, ConfigT::DistanceT)>) -> Result<FuzzyResultsWithValues<ConfigT>, String> {
+        let record_ids : Vec<RecordID> = results.iter().map(|(record_id, _distance)| *record_id).collect();
+        let values = self.get_values(&record_ids)?;
+        Ok(results.into_iter().zip(values).map(|((record_id, distance), value)| (record_id, distance, value)).collect())
     }
 
     /// Returns the number of keys associated with a specified record
@@ -687,12 +3490,15 @@ impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UT
     }
 
     /// Returns all of the keys for a record, across all key groups
-    fn get_keys_internal(&self, record_id : RecordID) -> Result<impl Iterator<Item=<Self as TableKeyEncoding>::OwnedKeyT> + '_, String> {
+    pub(crate) fn get_keys_internal(&self, record_id : RecordID) -> Result<impl Iterator<Item=<Self as TableKeyEncoding>::OwnedKeyT> + '_, String> {
 
+        //NOTE: We fetch all of the key groups up front so that a corrupt group can be reported as an error
         let key_groups_iter = self.db.get_record_key_groups(record_id)?;
-        let result_iter = key_groups_iter.flat_map(move |key_group| self.db.get_keys_in_group::<<Self as TableKeyEncoding>::OwnedKeyT>(key_group, &self.perf_counters).unwrap());
+        let key_group_iters = key_groups_iter
+            .map(|key_group| self.db.get_keys_in_group::<<Self as TableKeyEncoding>::OwnedKeyT>(key_group, &self.perf_counters))
+            .collect::<Result<Vec<_>, String>>()?;
 
-        Ok(result_iter)
+        Ok(key_group_iters.into_iter().flatten())
     }
 
     /// Resets all values in the performance counters, so the information returned by [get_perf_counters](Table::get_perf_counters) only
@@ -706,6 +3512,30 @@ impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UT
     pub fn get_perf_counters(&self) -> PerfCounterFields {
         self.perf_counters.get()
     }
+
+    /// Runs `func` with the Table, and returns its result along with the performance counters for only the work
+    /// done on this thread while it ran, e.g. to log the cost of each lookup a service performs
+    ///
+    /// Unlike [get_perf_counters](Table::get_perf_counters), the counters aren't affected by lookups running
+    /// concurrently on other threads.  But they do count the work of any other Table used on this thread inside
+    /// `func`, and they don't count the records evaluated on rayon's threads by [lookup_fuzzy_parallel](Table::lookup_fuzzy_parallel).
+    /// Lookups are lazy, so their results must be consumed inside `func` to be counted.  The Table's own counters
+    /// are updated as usual.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("perf_scope_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// table.insert("Hello", &"Greeting".to_string()).unwrap();
+    ///
+    /// let (results, counters) = table.with_perf_counters(|table| table.lookup_fuzzy("Hallo", None).unwrap().into_vec().unwrap());
+    /// assert_eq!(results.len(), 1);
+    /// println!("{} distance function invocations", counters.distance_function_invocation_count);
+    /// ```
+    pub fn with_perf_counters<R, F : FnOnce(&Self) -> R>(&self, func : F) -> (R, PerfCounterFields) {
+        PerfCounters::scoped(|| func(self))
+    }
 }
 
 impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
@@ -754,6 +3584,21 @@ impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
         self.insert_internal(keys.iter(), keys.len(), value)
     }
 
+    /// Creates a new record like [create](Self::create), along with extra variants that will find the record in
+    /// fuzzy lookups, such as the abbreviation "St" for a record with the key "Street"
+    /// 
+    /// The injected variants are stored in the variants CF as they are, without the variants created by deleting
+    /// chars from them, so a lookup finds the record if one of the lookup key's variants is an injected variant.
+    /// A record found this way is scored against its injected variants as well as its keys.  Injected variants
+    /// aren't keys, so they aren't returned by [get_keys](Self::get_keys) and don't match in [lookup_exact](Self::lookup_exact).
+    /// They are deleted along with the record.
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn create_with_variants<K : Key<KeyCharT = char>>(&mut self, keys : &[K], variants : &[K], value : &ConfigT::ValueT) -> Result<RecordID, String> {
+        self.create_with_variants_internal(keys, variants, value)
+    }
+
     /// Adds the supplied keys to the record's keys
     /// 
     /// The supplied `record_id` must references an existing record that has not been deleted.
@@ -761,7 +3606,7 @@ impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
     pub fn add_keys<K : Key<KeyCharT = char>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
-        self.add_keys_internal(record_id, keys.iter(), keys.len())
+        self.add_keys_atomic(record_id, keys.iter(), keys.len())
     }
 
     /// Removes the supplied keys from the keys associated with a record
@@ -776,7 +3621,7 @@ impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
     /// unwrapped RocksDB error.
     pub fn remove_keys<K : Key<KeyCharT = char>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
         let keys_set : HashSet<&K> = HashSet::from_iter(keys.iter());
-        self.remove_keys_internal(record_id, &keys_set)
+        self.remove_keys_atomic(record_id, &keys_set)
     }
 
     /// Replaces a record's keys with the supplied keys
@@ -786,7 +3631,7 @@ impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
     pub fn replace_keys<K : Key<KeyCharT = char>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
-        self.replace_keys_internal(record_id, keys)
+        self.replace_keys_atomic(record_id, keys)
     }
 
     /// Returns an iterator over all of the key associated with the specified record
@@ -821,6 +3666,7 @@ impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
     /// with the key "londonia".  We may opt to keep this functionality as a separate function
     /// as its performance is better than as loading the exact keys for all records is
     /// significant overhead.
+    /// Tables with [EXACT_KEY_INDEX](TableConfig::EXACT_KEY_INDEX) set don't have this problem.
     /// 
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
@@ -828,24 +3674,530 @@ impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
         self.lookup_exact_internal(&key.into_key()).map(|result_vec| result_vec.into_iter())
     }
 
+    /// Locates all records in the table with a key that sounds like the key supplied, i.e. that shares a code
+    /// with it under the Table's [PHONETIC_FUNCTION](TableConfig::PHONETIC_FUNCTION)
+    ///
+    /// Returns an error if the Table doesn't have a phonetic index.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// struct Config();
+    /// impl TableConfig for Config {
+    ///     type KeyCharT = char;
+    ///     type DistanceT = u8;
+    ///     type ValueT = String;
+    ///     const UTF8_KEYS : bool = true;
+    ///     const PHONETIC_FUNCTION : Option<PhoneticEncoding> = Some(PhoneticEncoding::SOUNDEX);
+    /// }
+    /// let mut table = Table::<Config, true>::new("phonetic_example.rocks", Config()).unwrap();
+    /// table.reset().unwrap();
+    /// let robert = table.insert("Robert", &"Bob".to_string()).unwrap();
+    /// table.insert("Rubin", &"Ruby".to_string()).unwrap();
+    ///
+    /// assert_eq!(table.lookup_phonetic("Rupert").unwrap().collect::<Vec<RecordID>>(), vec![robert]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_phonetic<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
+        self.lookup_phonetic_internal(&key.into_key()).map(|result_vec| result_vec.into_iter())
+    }
+
+    /// Locates all records in the table with a key that contains a close match for each whitespace-separated token
+    /// of the phrase supplied, regardless of the order of the tokens, and returns them ordered by distance
+    ///
+    /// Each phrase token is matched to the closest token of the record's key, using the Table's
+    /// [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION), and the record's distance is the sum of the distances
+    /// of those matches.  If there is a `threshold`, every phrase token must be within it of a token of the key.
+    /// The key may have tokens that don't match any phrase token.  A record with several keys is returned once,
+    /// with the distance of its closest key, but all of the phrase tokens must match tokens of the same key.
+    ///
+    /// Returns an error if the Table doesn't have a token index.  See [TOKEN_INDEX](TableConfig::TOKEN_INDEX).
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// struct Config();
+    /// impl TableConfig for Config {
+    ///     type KeyCharT = char;
+    ///     type DistanceT = u8;
+    ///     type ValueT = String;
+    ///     const UTF8_KEYS : bool = true;
+    ///     const TOKEN_INDEX : bool = true;
+    /// }
+    /// let mut table = Table::<Config, true>::new("phrase_example.rocks", Config()).unwrap();
+    /// table.reset().unwrap();
+    /// let sfo = table.insert("san francisco intl airport", &"SFO".to_string()).unwrap();
+    /// table.insert("san jose intl airport", &"SJC".to_string()).unwrap();
+    ///
+    /// assert_eq!(table.lookup_phrase_fuzzy("airport fransisco", Some(1)).unwrap(), vec![(sfo, 1)]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_phrase_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, phrase : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.lookup_phrase_fuzzy_internal(&phrase.into_key(), threshold, ConfigT::DISTANCE_FUNCTION)
+    }
+
+    /// Returns `true` if [lookup_exact](Table::lookup_exact) would find any records with the key supplied, without
+    /// loading any values
+    ///
+    /// The answer comes from the variant entry of the key, without loading any of the keys, unless the key is
+    /// longer than [MEANINGFUL_KEY_LEN](TableConfig::MEANINGFUL_KEY_LEN).  Tables with [EXACT_KEY_INDEX](TableConfig::EXACT_KEY_INDEX)
+    /// set never load any keys, and don't share the BUG described for `lookup_exact`.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("contains_key_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// table.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+    ///
+    /// assert!(table.contains_key_exact("Monday").unwrap());
+    /// assert!(!table.contains_key_exact("Tuesday").unwrap());
+    /// assert!(table.contains_key_fuzzy("Mondy").unwrap());
+    /// assert!(!table.contains_key_fuzzy("Tuesday").unwrap());
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn contains_key_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<bool, String> {
+        self.contains_key_exact_internal(&key.into_key())
+    }
+
+    /// Returns `true` if [lookup_fuzzy_raw](Table::lookup_fuzzy_raw) would find any records for the key supplied,
+    /// probing the variants until one of them references a record, without loading any keys or values
+    ///
+    /// The distance function isn't evaluated, so this may return `true` when [lookup_fuzzy](Table::lookup_fuzzy)
+    /// with a threshold finds nothing.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn contains_key_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<bool, String> {
+        self.contains_key_fuzzy_internal(&key.into_key())
+    }
+
+    /// Returns the number of records that [lookup_exact](Table::lookup_exact) would find with the key supplied,
+    /// without loading any values, and with the same caveats about loading keys as [contains_key_exact](Table::contains_key_exact)
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("count_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// table.insert("Mat", &"Floor".to_string()).unwrap();
+    /// table.insert("Mat", &"Door".to_string()).unwrap();
+    /// table.insert("Cat", &"Pet".to_string()).unwrap();
+    ///
+    /// assert_eq!(table.count_exact("Mat").unwrap(), 2);
+    /// assert_eq!(table.count_fuzzy("Pat").unwrap(), 3);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn count_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<usize, String> {
+        self.count_exact_internal(&key.into_key())
+    }
+
+    /// Returns the number of records that [lookup_fuzzy_raw](Table::lookup_fuzzy_raw) would find for the key
+    /// supplied, probing every variant but without loading any keys or values
+    ///
+    /// The distance function isn't evaluated, so the count may include records that [lookup_fuzzy](Table::lookup_fuzzy)
+    /// with a threshold wouldn't return.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn count_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<usize, String> {
+        self.count_fuzzy_internal(&key.into_key())
+    }
+
+    /// Splits a key into segments, and returns every split in which each segment exactly matches the key of
+    /// at least one record, so a query with a missing space can still find the keys it was made from
+    ///
+    /// The key is split at most `max_splits` times, and splits with fewer segments are returned first.
+    /// A key that matches a record without being split isn't returned, because [lookup_exact](Table::lookup_exact)
+    /// will find it.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut places = Table::<DefaultTableConfig, true>::new("compound_example.rocks", DefaultTableConfig()).unwrap();
+    /// places.reset().unwrap();
+    /// for word in ["new", "york", "yo", "rk"] {
+    ///     places.insert(word, &String::new()).unwrap();
+    /// }
+    ///
+    /// let matches = places.lookup_compound("newyork", 2).unwrap();
+    /// assert_eq!(matches[0].segments, vec!["new", "york"]);
+    /// assert_eq!(matches[1].segments, vec!["new", "yo", "rk"]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_compound<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, max_splits : usize) -> Result<Vec<CompoundMatch<String>>, String> {
+        self.lookup_compound_internal(&key.into_key(), max_splits)
+    }
+
     /// Locates all records in the table with a key that is within a deletion distance of [config.max_deletes] of
     /// the key supplied, based on the SymSpell algorithm.
     /// 
     /// This function underlies all fuzzy lookups, and does no further filtering based on any distance function.
     /// 
+    /// The returned [FuzzyRawLookup] is lazy, so the database is only probed as far as the results are consumed.
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_raw<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<FuzzyRawLookup<'_>, String> {
+        self.lookup_fuzzy_raw_internal(&key.into_key(), None)
+    }
+
+    /// Works like [lookup_fuzzy_raw](Table::lookup_fuzzy_raw), but only probes the variants within `max_deletes`
+    /// deletes of the key.  A `max_deletes` larger than the Table's [MAX_DELETES](TableConfig::MAX_DELETES) is
+    /// treated as `MAX_DELETES`
+    ///
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
-    pub fn lookup_fuzzy_raw<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
-        self.lookup_fuzzy_raw_internal(&key.into_key())
+    pub fn lookup_fuzzy_raw_within<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, max_deletes : usize) -> Result<FuzzyRawLookup<'_>, String> {
+        self.lookup_fuzzy_raw_internal(&key.into_key(), Some(max_deletes))
     }
 
     /// Locates all records in the table for which the Table's DISTANCE_FUNCTION(TableConfig::DISTANCE_FUNCTION) evaluates to a result smaller
     /// than the supplied `threshold` when comparing the record's key with the supplied `key`
     /// 
+    /// The returned [FuzzyLookup] is lazy, so the database is only probed as far as the results are consumed.
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_internal(&key.into_key(), threshold, None)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but also returns the key of each record that was closest to
+    /// the supplied `key`, along with its distance
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("scored_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let record_id = table.create(&["Monday", "Mon"], &"Getsuyoubi".to_string()).unwrap();
+    ///
+    /// let results = table.lookup_fuzzy_scored("Mom", Some(1)).unwrap().into_vec().unwrap();
+    /// assert_eq!(results, vec![(record_id, "Mon".to_string(), 1)]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_scored<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyScoredLookup<'_, String, ConfigT::DistanceT>, String> {
+        Ok(self.lookup_fuzzy_internal(&key.into_key(), threshold, None)?.scored())
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but only returns the records with a key within `max_distance`
+    /// Levenshtein edits of the lookup key, so a Table built with a larger [MAX_DELETES](TableConfig::MAX_DELETES) can
+    /// also serve stricter lookups without the cost of probing every variant
+    ///
+    /// Only the variants within `max_distance` deletes of the key are probed, and the candidate keys are pruned
+    /// with a banded Levenshtein check, as in [lookup_fuzzy_pruned](Table::lookup_fuzzy_pruned), before the `threshold`
+    /// is applied to their distances in the same way as it is by `lookup_fuzzy`.  The variants can't be probed beyond
+    /// `MAX_DELETES`, so a larger `max_distance` may miss keys that [lookup_fuzzy_extended](Table::lookup_fuzzy_extended)
+    /// would find.
+    ///
+    /// If the Table has so few live records that running every one of them through the check is estimated to cost
+    /// less than probing the variants of the key, the records are scanned instead.  The results are the same either
+    /// way, and the lookup's [strategy](FuzzyLookup::strategy) reports which was chosen.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_within<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_within_internal(&key.into_key(), threshold, max_distance)
+    }
+
+    /// Works like [lookup_fuzzy_within](Table::lookup_fuzzy_within), which runs each candidate key through a
+    /// banded Levenshtein check against the lookup key, and skips the keys that are more than `max_edits` edits
+    /// from it without invoking the [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION), and may scan the records
+    /// instead of probing the variants
+    ///
+    /// The check rejects most non-matching keys after only a few chars, so this is cheaper than evaluating
+    /// every candidate when the variants find many keys that aren't close, e.g. with short keys or a large
+    /// [MAX_DELETES](TableConfig::MAX_DELETES).  The distances of the results still come from the `DISTANCE_FUNCTION`,
+    /// and the `threshold` is applied to them as usual.  The pruning is only appropriate when a key can't be
+    /// within the `threshold` unless it is within `max_edits` Levenshtein edits of the lookup key.
+    ///
+    /// If the Table has so few live records that running every one of them through the check is estimated to cost
+    /// less than probing the variants of the key, the records are scanned instead.  Deleted records, and records
+    /// awaiting [purge_deleted](Table::purge_deleted), aren't counted.  The results are the same either way, and the
+    /// lookup's [strategy](FuzzyLookup::strategy) reports which was chosen.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("pruned_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let cat = table.insert("cat", &"feline".to_string()).unwrap();
+    /// table.insert("act", &"deed".to_string()).unwrap();
+    ///
+    /// let results : Vec<(RecordID, u8)> = table.lookup_fuzzy_pruned("cap", None, 1).unwrap().into_vec().unwrap();
+    /// assert_eq!(results, vec![(cat, 1)]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_pruned<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_edits : usize) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_pruned_internal(&key.into_key(), threshold, max_edits)
+    }
+
+    /// Works like [lookup_fuzzy_within](Table::lookup_fuzzy_within), but a `max_distance` beyond the Table's
+    /// [MAX_DELETES](TableConfig::MAX_DELETES) isn't treated as `MAX_DELETES`.  Instead, the lookup falls back to
+    /// scanning the records that the index can't rule out, as set by the [FALLBACK_SCAN](TableConfig::FALLBACK_SCAN)
+    ///
+    /// This lets a Table be indexed at a small `MAX_DELETES`, which keeps the index small and most lookups fast,
+    /// while still serving the occasional wider lookup.  A lookup beyond `MAX_DELETES` returns an error if
+    /// `FALLBACK_SCAN` is [Disabled](FallbackScan::Disabled), rather than silently returning results that may be
+    /// incomplete.  The scanned keys are pruned with a banded Levenshtein check, as in
+    /// [lookup_fuzzy_pruned](Table::lookup_fuzzy_pruned), so only keys within `max_distance` edits are returned.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// struct Config();
+    /// impl TableConfig for Config {
+    ///     type KeyCharT = char;
+    ///     type DistanceT = u8;
+    ///     type ValueT = String;
+    ///     const UTF8_KEYS : bool = true;
+    ///     const MAX_DELETES : usize = 1;
+    ///     const FALLBACK_SCAN : FallbackScan = FallbackScan::Full;
+    /// }
+    ///
+    /// let mut table = Table::<Config, true>::new("lookup_extended_example.rocks", Config()).unwrap();
+    /// table.reset().unwrap();
+    /// let saturday = table.insert("Saturday", &"Doyoubi".to_string()).unwrap();
+    ///
+    /// //Two edits away, so the index alone can't find it
+    /// assert_eq!(table.lookup_fuzzy_within("Saterdey", None, 2).unwrap().into_vec().unwrap().len(), 0);
+    /// let results : Vec<(RecordID, u8)> = table.lookup_fuzzy_extended("Saterdey", None, 2).unwrap().into_vec().unwrap();
+    /// assert_eq!(results, vec![(saturday, 2)]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_extended<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_extended_internal(&key.into_key(), threshold, max_distance)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but evaluates the supplied `distance_function` for this
+    /// lookup, instead of the Table's [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION)
+    ///
+    /// The distance function may be a closure, so it can carry weights or other domain-specific state, such as the
+    /// costs of characters that OCR commonly confuses.  It is called with a key from the record and the lookup key,
+    /// in that order.  The candidate records are found with the Table's variants in the same way as `lookup_fuzzy`,
+    /// so records further than [MAX_DELETES](TableConfig::MAX_DELETES) deletes away are never evaluated.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("lookup_fuzzy_with_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let bob = table.insert("B0B", &"scanned".to_string()).unwrap();
+    ///
+    /// //Treat '0' and 'O' as the same char, as an OCR engine might confuse them
+    /// let ocr_chars = |key : &[char]| -> Vec<char> { key.iter().map(|c| if *c == '0' { 'O' } else { *c }).collect() };
+    /// let results : Vec<(RecordID, u8)> = table.lookup_fuzzy_with("BOB", Some(0), |a, b| {
+    ///     DefaultTableConfig::levenstein_distance(&ocr_chars(a), &ocr_chars(b))
+    /// }).unwrap().into_vec().unwrap();
+    /// assert_eq!(results, vec![(bob, 0)]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_with<'a, K, KeyT, F>(&'a self, key : K, threshold : Option<ConfigT::DistanceT>, distance_function : F) -> Result<FuzzyLookup<'a, String, ConfigT::DistanceT>, String>
+        where
+        K : IntoKey<Key = KeyT>,
+        KeyT : Key<KeyCharT = char>,
+        F : Fn(&[char], &[char]) -> ConfigT::DistanceT + 'a
+    {
+        self.lookup_fuzzy_with_internal(&key.into_key(), threshold, None, distance_function)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but collects the results, and keeps them in a cache of up to
+    /// [QUERY_CACHE_CAPACITY](TableConfig::QUERY_CACHE_CAPACITY) lookups, so an identical lookup can return them
+    /// without probing any variants
+    ///
+    /// Lookups are identical if they have the same key, after its case is folded for a Table that isn't case-sensitive,
+    /// and the same `threshold`.  A cached lookup is discarded when a record's keys are changed in a way that could
+    /// change its results.  If `QUERY_CACHE_CAPACITY` is 0, this always performs the lookup.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// struct Config();
+    /// impl TableConfig for Config {
+    ///     type KeyCharT = char;
+    ///     type DistanceT = u8;
+    ///     type ValueT = String;
+    ///     const QUERY_CACHE_CAPACITY : usize = 64;
+    /// }
+    /// let mut table = Table::<Config, true>::new("lookup_fuzzy_cached_example.rocks", Config()).unwrap();
+    /// table.reset().unwrap();
+    /// let friday = table.insert("Friday", &"Kinyoubi".to_string()).unwrap();
+    /// assert_eq!(table.lookup_fuzzy_cached("Fryday", Some(1)).unwrap(), vec![(friday, 1)]);
+    ///
+    /// //Inserting a record that the lookup would find discards the cached results
+    /// let fryday = table.insert("Fryday", &"Typo".to_string()).unwrap();
+    /// let mut results = table.lookup_fuzzy_cached("Fryday", Some(1)).unwrap();
+    /// results.sort();
+    /// assert_eq!(results, vec![(friday, 1), (fryday, 0)]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_cached<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.lookup_fuzzy_cached_internal(&key.into_key(), threshold)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but collects the results sorted by distance, and probes every
+    /// variant before evaluating any of the candidate records
+    ///
+    /// With the `parallel` feature enabled, a lookup that finds many candidate records evaluates them across the
+    /// threads of [rayon](https://docs.rs/rayon)'s global thread pool, so a high fan-out lookup, e.g. for a short key in
+    /// a large Table, isn't limited to a single core.  Without the feature, or with only a few candidates, they're
+    /// evaluated on the calling thread.  The results are the same either way.
+    ///
+    /// Unlike the lazy lookups, this doesn't queue [read repairs](TableConfig::READ_REPAIR).
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("lookup_fuzzy_parallel_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let cat = table.insert("Cat", &String::new()).unwrap();
+    /// let hat = table.insert("Hat", &String::new()).unwrap();
+    /// table.insert("Horse", &String::new()).unwrap();
+    /// assert_eq!(table.lookup_fuzzy_parallel("Cat", Some(1)).unwrap(), vec![(cat, 0), (hat, 1)]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_parallel<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String>
+        where
+        ConfigT::DistanceT : Send + Sync,
+    {
+        self.lookup_fuzzy_parallel_internal(&key.into_key(), threshold)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but returns at most `page_size` results, along with a
+    /// [LookupCursor] that can be passed to a later call to continue with the next page
+    ///
+    /// The first page is requested without a cursor.  The cursor is `None` once every variant has been probed,
+    /// although the last page may be empty if the remaining candidates were all beyond the threshold.  Each
+    /// call must be made on the same Table, with the same key and threshold as the lookup that produced the
+    /// cursor, or an error is returned.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("lookup_fuzzy_page_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// for key in ["Bat", "Cat", "Hat", "Mat", "Rat"] {
+    ///     table.insert(key, &String::new()).unwrap();
+    /// }
+    ///
+    /// let mut results = vec![];
+    /// let mut cursor_bytes : Option<Vec<u8>> = None;
+    /// loop {
+    ///     //The cursor could be sent to a client between pages
+    ///     let cursor = cursor_bytes.map(|bytes| LookupCursor::from_bytes(&bytes).unwrap());
+    ///     let (page, next_cursor) = table.lookup_fuzzy_page("Pat", Some(1), 2, cursor).unwrap();
+    ///     assert!(page.len() <= 2);
+    ///     results.extend(page);
+    ///     match next_cursor {
+    ///         Some(cursor) => cursor_bytes = Some(cursor.to_bytes()),
+    ///         None => break
+    ///     }
+    /// }
+    /// assert_eq!(results.len(), 5);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_page<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, page_size : usize, cursor : Option<LookupCursor>) -> Result<FuzzyResultsPage<ConfigT>, String>
+        where
+        ConfigT::DistanceT : Serialize,
+    {
+        self.lookup_fuzzy_page_internal(&key.into_key(), threshold, page_size, cursor)
+    }
+
+    /// Locates all records with a key that begins with something close to the supplied `prefix`, for autocomplete
+    ///
+    /// A record's distance is the [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION) evaluated between `prefix`
+    /// and the closest prefix of any of the record's keys, considering the prefixes up to [MAX_DELETES](TableConfig::MAX_DELETES)
+    /// chars shorter or longer than `prefix`.  Records are returned if their distance is within `threshold`, in
+    /// the same way as [lookup_fuzzy](Table::lookup_fuzzy).
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("lookup_prefix_fuzzy_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// let wednesday = table.insert("Wednesday", &"Suiyoubi".to_string()).unwrap();
+    /// table.insert("Weekend", &"Shuumatsu".to_string()).unwrap();
+    ///
+    /// //"Wedm" is one edit from "Wedn"
+    /// let results : Vec<(RecordID, u8)> = table.lookup_prefix_fuzzy("Wedm", Some(1)).unwrap().into_vec().unwrap();
+    /// assert_eq!(results, vec![(wednesday, 1)]);
+    /// ```
+    ///
+    /// NOTE: Each variant of `prefix` is matched against every variant in the Table that begins with it, so a very
+    /// short `prefix` may visit a large part of the Table.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_prefix_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, prefix : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.lookup_prefix_fuzzy_internal(&prefix.into_key(), threshold)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but the threshold is a ratio of the key lengths rather than
+    /// an absolute distance
+    ///
+    /// Each of a record's keys is accepted if its distance is no more than `1 - min_similarity` times the length of
+    /// the longer of that key and the lookup key, rounded down.  So a `min_similarity` of `0.75` accepts no edits
+    /// between 3-character keys, but 2 edits between 8-character keys.  The Table's [default_threshold](TableConfig::default_threshold)
+    /// isn't applied.  This is intended for edit distances such as the default [levenstein_distance](TableConfig::levenstein_distance),
+    /// where a distance counts chars.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut table = Table::<DefaultTableConfig, true>::new("lookup_fuzzy_relative_example.rocks", DefaultTableConfig()).unwrap();
+    /// table.reset().unwrap();
+    /// table.insert("cat", &"short".to_string()).unwrap();
+    /// let wednesday = table.insert("Wednesday", &"long".to_string()).unwrap();
+    ///
+    /// //One edit is too many for "cat", but not for "Wednesday"
+    /// assert_eq!(table.lookup_fuzzy_relative("bat", 0.75).unwrap().into_vec().unwrap().len(), 0);
+    /// let results : Vec<(RecordID, u8)> = table.lookup_fuzzy_relative("Wensday", 0.75).unwrap().into_vec().unwrap();
+    /// assert_eq!(results, vec![(wednesday, 2)]);
+    /// ```
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_relative<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, min_similarity : f32) -> Result<FuzzyLookup<'_, String, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_relative_internal(&key.into_key(), min_similarity)
+    }
+
+    /// Performs a fuzzy lookup like [lookup_fuzzy](Self::lookup_fuzzy), but begins with only the variants one delete
+    /// away from `key`, and only widens the lookup, one delete at a time up to [MAX_DELETES](TableConfig::MAX_DELETES),
+    /// if fewer than `min_results` results have been found
+    /// 
+    /// A well-spelled key usually finds enough results without the much larger number of variants at greater
+    /// distances, so this is often much faster than `lookup_fuzzy`, while a badly-spelled key will still reach
+    /// every result `lookup_fuzzy` would find.  Results found at a smaller distance are returned first.
+    /// 
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
-    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<impl Iterator<Item=(RecordID, ConfigT::DistanceT)>, String> {
-        self.lookup_fuzzy_internal(&key.into_key(), threshold)
+    pub fn lookup_fuzzy_adaptive<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, min_results : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.lookup_fuzzy_adaptive_internal(&key.into_key(), threshold, min_results)
     }
 
     /// Locates the record in the table for which the Table's DISTANCE_FUNCTION(TableConfig::DISTANCE_FUNCTION) evaluates to the lowest value
@@ -861,6 +4213,103 @@ impl <ConfigT : TableConfig<KeyCharT = char>>Table<ConfigT, true> {
     pub fn lookup_best<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
         self.lookup_best_internal(&key.into_key())
     }
+
+    /// Returns up to `n` of the records closest to the supplied `key`, along with their distances, ordered by
+    /// ascending distance.  Records at the same distance are ordered by RecordID
+    ///
+    /// Variants are probed one delete at a time, so the lookup can stop early once `n` records have been found
+    /// that are closer than any record that hasn't been found yet.  This relies on the distance function never
+    /// returning less than the number of deletes needed to find a key, which holds for both
+    /// [levenstein_distance](TableConfig::levenstein_distance) and [damerau_levenstein_distance](TableConfig::damerau_levenstein_distance),
+    /// but not for [jaro_winkler_distance](TableConfig::jaro_winkler_distance).
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_best_n<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, n : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.lookup_best_n_internal(&key.into_key(), n)
+    }
+
+    /// Locates all records in the table with keys that precisely match the key supplied, and returns each
+    /// record's value along with its RecordID
+    /// 
+    /// Functions the same as [lookup_exact](Table::lookup_exact), followed by [get_values](Table::get_values)
+    pub fn lookup_exact_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.with_values(self.lookup_exact_internal(&key.into_key())?)
+    }
+
+    /// Locates all records in the table with a key that is within a deletion distance of [config.max_deletes] of
+    /// the key supplied, and returns each record's value along with its RecordID
+    /// 
+    /// Functions the same as [lookup_fuzzy_raw](Table::lookup_fuzzy_raw), followed by [get_values](Table::get_values)
+    pub fn lookup_fuzzy_raw_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.with_values(self.lookup_fuzzy_raw_internal(&key.into_key(), None)?.into_vec()?)
+    }
+
+    /// Locates all records in the table for which the Table's DISTANCE_FUNCTION(TableConfig::DISTANCE_FUNCTION) evaluates
+    /// to a result smaller than the supplied `threshold`, and returns each record's distance and value along with its RecordID
+    /// 
+    /// Functions the same as [lookup_fuzzy](Table::lookup_fuzzy), followed by [get_values](Table::get_values)
+    pub fn lookup_fuzzy_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyResultsWithValues<ConfigT>, String> {
+        self.lookup_fuzzy_with_values_internal(&key.into_key(), threshold)
+    }
+
+    /// Locates the record in the table for which the Table's DISTANCE_FUNCTION(TableConfig::DISTANCE_FUNCTION) evaluates
+    /// to the lowest value, and returns each record's value along with its RecordID
+    /// 
+    /// Functions the same as [lookup_best](Table::lookup_best), followed by [get_values](Table::get_values)
+    pub fn lookup_best_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = char>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.with_values(self.lookup_best_internal(&key.into_key())?.collect())
+    }
+
+    /// Splits `phrase` into whitespace-separated tokens, corrects each token against the keys in the Table,
+    /// and returns up to `max_suggestions` corrected phrases, ranked by ascending total distance
+    /// 
+    /// Each token is corrected with a fuzzy lookup, so only keys within [MAX_DELETES](TableConfig::MAX_DELETES)
+    /// of the token are considered.  A token with no close keys is left as it is, with a distance of zero.
+    /// To correct phrases against a vocabulary separate from the records being searched, call this method on a
+    /// companion Table whose keys are the individual words.
+    /// 
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut words = Table::<DefaultTableConfig, true>::new("did_you_mean_example.rocks", DefaultTableConfig()).unwrap();
+    /// words.reset().unwrap();
+    /// for word in ["hello", "world", "word"] {
+    ///     words.insert(word, &String::new()).unwrap();
+    /// }
+    ///
+    /// let suggestions = words.did_you_mean("helo wrld", 3).unwrap();
+    /// assert_eq!(suggestions[0].phrase(), "hello world");
+    /// assert_eq!(suggestions[0].distance, 2);
+    /// assert_eq!(suggestions[1].phrase(), "hello word");
+    /// ```
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn did_you_mean(&self, phrase : &str, max_suggestions : usize) -> Result<Vec<PhraseCorrection<ConfigT::DistanceT>>, String> {
+
+        let mut token_candidates = vec![];
+        for token in phrase.split_whitespace() {
+            //Find the closest records, and the key of each record that was closest to the token
+            let mut candidates : Vec<TokenCorrection<ConfigT::DistanceT>> = vec![];
+            for (record_id, key, distance) in self.lookup_fuzzy_internal(&token, None, None)?.scored().into_vec()? {
+
+                //NOTE: Several records may share the same key, but the phrase only needs one correction for it
+                if !candidates.iter().any(|candidate| candidate.corrected == key) {
+                    candidates.push(TokenCorrection{original : token.to_string(), corrected : key, record_id : Some(record_id), distance});
+                }
+            }
+            candidates.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(core::cmp::Ordering::Equal));
+            candidates.truncate(max_suggestions);
+
+            if candidates.is_empty() {
+                candidates.push(TokenCorrection{original : token.to_string(), corrected : token.to_string(), record_id : None, distance : ConfigT::DistanceT::zero()});
+            }
+            token_candidates.push(candidates);
+        }
+
+        Ok(rank_phrases(token_candidates, max_suggestions))
+    }
 }
 
 impl <ConfigT : TableConfig>Table<ConfigT, false> {
@@ -909,6 +4358,21 @@ impl <ConfigT : TableConfig>Table<ConfigT, false> {
         self.insert_internal(keys.iter(), keys.len(), value)
     }
 
+    /// Creates a new record like [create](Self::create), along with extra variants that will find the record in
+    /// fuzzy lookups, such as the abbreviation "St" for a record with the key "Street"
+    /// 
+    /// The injected variants are stored in the variants CF as they are, without the variants created by deleting
+    /// chars from them, so a lookup finds the record if one of the lookup key's variants is an injected variant.
+    /// A record found this way is scored against its injected variants as well as its keys.  Injected variants
+    /// aren't keys, so they aren't returned by [get_keys](Self::get_keys) and don't match in [lookup_exact](Self::lookup_exact).
+    /// They are deleted along with the record.
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn create_with_variants<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, keys : &[K], variants : &[K], value : &ConfigT::ValueT) -> Result<RecordID, String> {
+        self.create_with_variants_internal(keys, variants, value)
+    }
+
     /// Adds the supplied keys to the record's keys
     /// 
     /// The supplied `record_id` must references an existing record that has not been deleted.
@@ -916,7 +4380,7 @@ impl <ConfigT : TableConfig>Table<ConfigT, false> {
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
     pub fn add_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
-        self.add_keys_internal(record_id, keys.iter(), keys.len())
+        self.add_keys_atomic(record_id, keys.iter(), keys.len())
     }
 
     /// Removes the supplied keys from the keys associated with a record
@@ -931,7 +4395,7 @@ impl <ConfigT : TableConfig>Table<ConfigT, false> {
     /// unwrapped RocksDB error.
     pub fn remove_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
         let keys_set : HashSet<&K> = HashSet::from_iter(keys.iter());
-        self.remove_keys_internal(record_id, &keys_set)
+        self.remove_keys_atomic(record_id, &keys_set)
     }
 
     /// Replaces a record's keys with the supplied keys
@@ -941,7 +4405,7 @@ impl <ConfigT : TableConfig>Table<ConfigT, false> {
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
     pub fn replace_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
-        self.replace_keys_internal(record_id, keys)
+        self.replace_keys_atomic(record_id, keys)
     }
 
     /// Returns an iterator over all of the key associated with the specified record
@@ -969,6 +4433,7 @@ impl <ConfigT : TableConfig>Table<ConfigT, false> {
     /// with the key "londonia".  We may opt to keep this functionality as a separate function
     /// as its performance is better than as loading the exact keys for all records is
     /// significant overhead.
+    /// Tables with [EXACT_KEY_INDEX](TableConfig::EXACT_KEY_INDEX) set don't have this problem.
     /// 
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
@@ -976,24 +4441,261 @@ impl <ConfigT : TableConfig>Table<ConfigT, false> {
         self.lookup_exact_internal(&key.into_key()).map(|result_vec| result_vec.into_iter())
     }
 
+    /// Returns `true` if [lookup_exact](Table::lookup_exact) would find any records with the key supplied, without
+    /// loading any values
+    ///
+    /// The answer comes from the variant entry of the key, without loading any of the keys, unless the key is
+    /// longer than [MEANINGFUL_KEY_LEN](TableConfig::MEANINGFUL_KEY_LEN).  Tables with [EXACT_KEY_INDEX](TableConfig::EXACT_KEY_INDEX)
+    /// set never load any keys, and don't share the BUG described for `lookup_exact`.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn contains_key_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<bool, String> {
+        self.contains_key_exact_internal(&key.into_key())
+    }
+
+    /// Returns `true` if [lookup_fuzzy_raw](Table::lookup_fuzzy_raw) would find any records for the key supplied,
+    /// probing the variants until one of them references a record, without loading any keys or values
+    ///
+    /// The distance function isn't evaluated, so this may return `true` when [lookup_fuzzy](Table::lookup_fuzzy)
+    /// with a threshold finds nothing.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn contains_key_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<bool, String> {
+        self.contains_key_fuzzy_internal(&key.into_key())
+    }
+
+    /// Returns the number of records that [lookup_exact](Table::lookup_exact) would find with the key supplied,
+    /// without loading any values, and with the same caveats about loading keys as [contains_key_exact](Table::contains_key_exact)
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn count_exact<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<usize, String> {
+        self.count_exact_internal(&key.into_key())
+    }
+
+    /// Returns the number of records that [lookup_fuzzy_raw](Table::lookup_fuzzy_raw) would find for the key
+    /// supplied, probing every variant but without loading any keys or values
+    ///
+    /// The distance function isn't evaluated, so the count may include records that [lookup_fuzzy](Table::lookup_fuzzy)
+    /// with a threshold wouldn't return.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn count_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<usize, String> {
+        self.count_fuzzy_internal(&key.into_key())
+    }
+
+    /// Splits a key into segments, and returns every split in which each segment exactly matches the key of
+    /// at least one record, so a query with a missing separator can still find the keys it was made from
+    ///
+    /// The key is split at most `max_splits` times, and splits with fewer segments are returned first.
+    /// A key that matches a record without being split isn't returned, because [lookup_exact](Table::lookup_exact)
+    /// will find it.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_compound<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, max_splits : usize) -> Result<Vec<CompoundMatch<Vec<ConfigT::KeyCharT>>>, String> {
+        self.lookup_compound_internal(&key.into_key(), max_splits)
+    }
+
     /// Locates all records in the table with a key that is within a deletion distance of `config.max_deletes` of
     /// the key supplied, based on the SymSpell algorithm.
     /// 
     /// This function underlies all fuzzy lookups, and does no further filtering based on any distance function.
     /// 
+    /// The returned [FuzzyRawLookup] is lazy, so the database is only probed as far as the results are consumed.
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_raw<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<FuzzyRawLookup<'_>, String> {
+        self.lookup_fuzzy_raw_internal(&key.into_key(), None)
+    }
+
+    /// Works like [lookup_fuzzy_raw](Table::lookup_fuzzy_raw), but only probes the variants within `max_deletes`
+    /// deletes of the key.  A `max_deletes` larger than the Table's [MAX_DELETES](TableConfig::MAX_DELETES) is
+    /// treated as `MAX_DELETES`
+    ///
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
-    pub fn lookup_fuzzy_raw<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
-        self.lookup_fuzzy_raw_internal(&key.into_key())
+    pub fn lookup_fuzzy_raw_within<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, max_deletes : usize) -> Result<FuzzyRawLookup<'_>, String> {
+        self.lookup_fuzzy_raw_internal(&key.into_key(), Some(max_deletes))
     }
 
     /// Locates all records in the table for which the Table's DISTANCE_FUNCTION(TableConfig::DISTANCE_FUNCTION) evaluates to a result smaller
     /// than the supplied `threshold` when comparing the record's key with the supplied `key`
     /// 
+    /// The returned [FuzzyLookup] is lazy, so the database is only probed as far as the results are consumed.
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_internal(&key.into_key(), threshold, None)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but also returns the key of each record that was closest to
+    /// the supplied `key`, along with its distance
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_scored<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyScoredLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        Ok(self.lookup_fuzzy_internal(&key.into_key(), threshold, None)?.scored())
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but only returns the records with a key within `max_distance`
+    /// Levenshtein edits of the lookup key, so a Table built with a larger [MAX_DELETES](TableConfig::MAX_DELETES) can
+    /// also serve stricter lookups without the cost of probing every variant
+    ///
+    /// Only the variants within `max_distance` deletes of the key are probed, and the candidate keys are pruned
+    /// with a banded Levenshtein check, as in [lookup_fuzzy_pruned](Table::lookup_fuzzy_pruned), before the `threshold`
+    /// is applied to their distances in the same way as it is by `lookup_fuzzy`.  The variants can't be probed beyond
+    /// `MAX_DELETES`, so a larger `max_distance` may miss keys that [lookup_fuzzy_extended](Table::lookup_fuzzy_extended)
+    /// would find.
+    ///
+    /// If the Table has so few live records that running every one of them through the check is estimated to cost
+    /// less than probing the variants of the key, the records are scanned instead.  The results are the same either
+    /// way, and the lookup's [strategy](FuzzyLookup::strategy) reports which was chosen.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_within<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_within_internal(&key.into_key(), threshold, max_distance)
+    }
+
+    /// Works like [lookup_fuzzy_within](Table::lookup_fuzzy_within), which runs each candidate key through a
+    /// banded Levenshtein check against the lookup key, and skips the keys that are more than `max_edits` edits
+    /// from it without invoking the [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION), and may scan the records
+    /// instead of probing the variants
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_pruned<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_edits : usize) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_pruned_internal(&key.into_key(), threshold, max_edits)
+    }
+
+    /// Works like [lookup_fuzzy_within](Table::lookup_fuzzy_within), but a `max_distance` beyond the Table's
+    /// [MAX_DELETES](TableConfig::MAX_DELETES) isn't treated as `MAX_DELETES`.  Instead, the lookup falls back to
+    /// scanning the records that the index can't rule out, as set by the [FALLBACK_SCAN](TableConfig::FALLBACK_SCAN)
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_extended<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, max_distance : usize) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_extended_internal(&key.into_key(), threshold, max_distance)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but evaluates the supplied `distance_function` for this
+    /// lookup, instead of the Table's [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION)
+    ///
+    /// The distance function may be a closure, so it can carry weights or other domain-specific state, such as the
+    /// costs of characters that OCR commonly confuses.  It is called with a key from the record and the lookup key,
+    /// in that order.  The candidate records are found with the Table's variants in the same way as `lookup_fuzzy`,
+    /// so records further than [MAX_DELETES](TableConfig::MAX_DELETES) deletes away are never evaluated.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_with<'a, K, KeyT, F>(&'a self, key : K, threshold : Option<ConfigT::DistanceT>, distance_function : F) -> Result<FuzzyLookup<'a, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String>
+        where
+        K : IntoKey<Key = KeyT>,
+        KeyT : Key<KeyCharT = ConfigT::KeyCharT>,
+        F : Fn(&[ConfigT::KeyCharT], &[ConfigT::KeyCharT]) -> ConfigT::DistanceT + 'a
+    {
+        self.lookup_fuzzy_with_internal(&key.into_key(), threshold, None, distance_function)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but collects the results, and keeps them in a cache of up to
+    /// [QUERY_CACHE_CAPACITY](TableConfig::QUERY_CACHE_CAPACITY) lookups, so an identical lookup can return them
+    /// without probing any variants
+    ///
+    /// Lookups are identical if they have the same key, after its case is folded for a Table that isn't case-sensitive,
+    /// and the same `threshold`.  A cached lookup is discarded when a record's keys are changed in a way that could
+    /// change its results.  If `QUERY_CACHE_CAPACITY` is 0, this always performs the lookup.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_cached<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.lookup_fuzzy_cached_internal(&key.into_key(), threshold)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but collects the results sorted by distance, and probes every
+    /// variant before evaluating any of the candidate records
+    ///
+    /// With the `parallel` feature enabled, a lookup that finds many candidate records evaluates them across the
+    /// threads of [rayon](https://docs.rs/rayon)'s global thread pool.  Without the feature, or with only a few
+    /// candidates, they're evaluated on the calling thread.  The results are the same either way.
+    ///
+    /// Unlike the lazy lookups, this doesn't queue [read repairs](TableConfig::READ_REPAIR).
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_parallel<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String>
+        where
+        ConfigT::KeyCharT : Send + Sync,
+        ConfigT::DistanceT : Send + Sync,
+    {
+        self.lookup_fuzzy_parallel_internal(&key.into_key(), threshold)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but returns at most `page_size` results, along with a
+    /// [LookupCursor] that can be passed to a later call to continue with the next page
+    ///
+    /// The first page is requested without a cursor.  The cursor is `None` once every variant has been probed,
+    /// although the last page may be empty if the remaining candidates were all beyond the threshold.  Each
+    /// call must be made on the same Table, with the same key and threshold as the lookup that produced the
+    /// cursor, or an error is returned.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_page<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, page_size : usize, cursor : Option<LookupCursor>) -> Result<FuzzyResultsPage<ConfigT>, String>
+        where
+        ConfigT::DistanceT : Serialize,
+    {
+        self.lookup_fuzzy_page_internal(&key.into_key(), threshold, page_size, cursor)
+    }
+
+    /// Locates all records with a key that begins with something close to the supplied `prefix`, for autocomplete
+    ///
+    /// A record's distance is the [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION) evaluated between `prefix`
+    /// and the closest prefix of any of the record's keys, considering the prefixes up to [MAX_DELETES](TableConfig::MAX_DELETES)
+    /// chars shorter or longer than `prefix`.  Records are returned if their distance is within `threshold`, in
+    /// the same way as [lookup_fuzzy](Table::lookup_fuzzy).
+    ///
+    /// NOTE: Each variant of `prefix` is matched against every variant in the Table that begins with it, so a very
+    /// short `prefix` may visit a large part of the Table.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_prefix_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, prefix : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.lookup_prefix_fuzzy_internal(&prefix.into_key(), threshold)
+    }
+
+    /// Works like [lookup_fuzzy](Table::lookup_fuzzy), but the threshold is a ratio of the key lengths rather than
+    /// an absolute distance
+    ///
+    /// Each of a record's keys is accepted if its distance is no more than `1 - min_similarity` times the length of
+    /// the longer of that key and the lookup key, rounded down.  So a `min_similarity` of `0.75` accepts no edits
+    /// between 3-character keys, but 2 edits between 8-character keys.  The Table's [default_threshold](TableConfig::default_threshold)
+    /// isn't applied.  This is intended for edit distances such as the default [levenstein_distance](TableConfig::levenstein_distance),
+    /// where a distance counts chars.
+    ///
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_fuzzy_relative<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, min_similarity : f32) -> Result<FuzzyLookup<'_, Vec<ConfigT::KeyCharT>, ConfigT::DistanceT>, String> {
+        self.lookup_fuzzy_relative_internal(&key.into_key(), min_similarity)
+    }
+
+    /// Performs a fuzzy lookup like [lookup_fuzzy](Self::lookup_fuzzy), but begins with only the variants one delete
+    /// away from `key`, and only widens the lookup, one delete at a time up to [MAX_DELETES](TableConfig::MAX_DELETES),
+    /// if fewer than `min_results` results have been found
+    /// 
+    /// A well-spelled key usually finds enough results without the much larger number of variants at greater
+    /// distances, so this is often much faster than `lookup_fuzzy`, while a badly-spelled key will still reach
+    /// every result `lookup_fuzzy` would find.  Results found at a smaller distance are returned first.
+    /// 
     /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
     /// unwrapped RocksDB error.
-    pub fn lookup_fuzzy<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<impl Iterator<Item=(RecordID, ConfigT::DistanceT)>, String> {
-        self.lookup_fuzzy_internal(&key.into_key(), threshold)
+    pub fn lookup_fuzzy_adaptive<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>, min_results : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.lookup_fuzzy_adaptive_internal(&key.into_key(), threshold, min_results)
     }
 
     /// Locates the record in the table for which the Table's DISTANCE_FUNCTION(TableConfig::DISTANCE_FUNCTION) evaluates to the lowest value
@@ -1009,4 +4711,56 @@ impl <ConfigT : TableConfig>Table<ConfigT, false> {
     pub fn lookup_best<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<impl Iterator<Item=RecordID>, String> {
         self.lookup_best_internal(&key.into_key())
     }
+
+    /// Returns up to `n` of the records closest to the supplied `key`, along with their distances, ordered by
+    /// ascending distance.  Records at the same distance are ordered by RecordID
+    ///
+    /// Variants are probed one delete at a time, so the lookup can stop early once `n` records have been found
+    /// that are closer than any record that hasn't been found yet.  This relies on the distance function never
+    /// returning less than the number of deletes needed to find a key, which holds for both
+    /// [levenstein_distance](TableConfig::levenstein_distance) and [damerau_levenstein_distance](TableConfig::damerau_levenstein_distance),
+    /// but not for [jaro_winkler_distance](TableConfig::jaro_winkler_distance).
+    /// 
+    /// NOTE: [rocksdb::Error] is a wrapper around a string, so if an error occurs it will be the
+    /// unwrapped RocksDB error.
+    pub fn lookup_best_n<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, n : usize) -> Result<Vec<(RecordID, ConfigT::DistanceT)>, String> {
+        self.lookup_best_n_internal(&key.into_key(), n)
+    }
+
+    /// Locates all records in the table with keys that precisely match the key supplied, and returns each
+    /// record's value along with its RecordID
+    /// 
+    /// Functions the same as [lookup_exact](Table::lookup_exact), followed by [get_values](Table::get_values)
+    pub fn lookup_exact_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.with_values(self.lookup_exact_internal(&key.into_key())?)
+    }
+
+    /// Locates all records in the table with a key that is within a deletion distance of [config.max_deletes] of
+    /// the key supplied, and returns each record's value along with its RecordID
+    /// 
+    /// Functions the same as [lookup_fuzzy_raw](Table::lookup_fuzzy_raw), followed by [get_values](Table::get_values)
+    pub fn lookup_fuzzy_raw_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.with_values(self.lookup_fuzzy_raw_internal(&key.into_key(), None)?.into_vec()?)
+    }
+
+    /// Locates all records in the table for which the Table's DISTANCE_FUNCTION(TableConfig::DISTANCE_FUNCTION) evaluates
+    /// to a result smaller than the supplied `threshold`, and returns each record's distance and value along with its RecordID
+    /// 
+    /// Functions the same as [lookup_fuzzy](Table::lookup_fuzzy), followed by [get_values](Table::get_values)
+    pub fn lookup_fuzzy_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K, threshold : Option<ConfigT::DistanceT>) -> Result<FuzzyResultsWithValues<ConfigT>, String> {
+        self.lookup_fuzzy_with_values_internal(&key.into_key(), threshold)
+    }
+
+    /// Locates the record in the table for which the Table's DISTANCE_FUNCTION(TableConfig::DISTANCE_FUNCTION) evaluates
+    /// to the lowest value, and returns each record's value along with its RecordID
+    /// 
+    /// Functions the same as [lookup_best](Table::lookup_best), followed by [get_values](Table::get_values)
+    pub fn lookup_best_with_values<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&self, key : K) -> Result<Vec<(RecordID, ConfigT::ValueT)>, String> {
+        self.with_values(self.lookup_best_internal(&key.into_key())?.collect())
+    }
+}
+
+/// Sorts lookup results by ascending distance, breaking ties by RecordID so the order is deterministic
+fn sort_by_distance<DistanceT : PartialOrd>(results : &mut [(RecordID, DistanceT)]) {
+    results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(core::cmp::Ordering::Equal).then(a.0.cmp(&b.0)));
 }
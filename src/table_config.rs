@@ -3,13 +3,26 @@
 //! is re-exported.
 //! 
 
-use core::hash::Hash;
-use core::cmp::{min};
+use core::cmp::{min, max};
 
 use std::mem::{MaybeUninit};
+use std::sync::Arc;
+use std::time::Duration;
+
+use core::marker::PhantomData;
 
 use num_traits::Zero;
-use serde::{Serialize};
+use serde::{Serialize, Deserialize};
+
+use super::key::{OwnedKey, KeyChar};
+use super::phonetic;
+use super::ngrams::IndexStrategy;
+use super::variant_scheme::{VariantScheme, DeletionVariants};
+use super::fallback_scan::FallbackScan;
+use super::cipher::Cipher;
+use super::rocks_config::RocksConfig;
+#[cfg(feature = "unicode-segmentation")]
+use unicode_segmentation::UnicodeSegmentation;
 
 /// The maximum number of characters allowable in a key.  Longer keys will cause an error
 pub const MAX_KEY_LENGTH : usize = 95;
@@ -49,9 +62,10 @@ pub trait TableConfig {
     /// Valid [Key](crate::Key) types for a [Table](crate::Table) must be losslessly convertible to and from `Vec<KeyCharT>`.
     /// 
     /// In a typical implementation, `KeyCharT` is a [char] for unicode keys or a [u8] for simple [ASCII](https://en.wikipedia.org/wiki/ASCII) keys,
-    /// although it could be a data type of another size.  `KeyCharT` must implement the [Copy] trait,
-    /// so that keys will be contiguous in memory and may not include any references.
-    type KeyCharT : 'static + Copy + Eq + Hash + Serialize + serde::de::DeserializeOwned;
+    /// although it could be any token type, such as a token ID, a phoneme, or a DNA codon, as long as it implements
+    /// the [KeyChar](crate::KeyChar) trait.  Keys are then sequences of tokens, and variants are made by deleting
+    /// tokens.  A `KeyCharT` other than `char` requires `UTF8_KEYS = false`.
+    type KeyCharT : KeyChar;
 
     /// A generic type that represents a scalar distance in the [Metric Space](https://en.wikipedia.org/wiki/Metric_space) that contains
     /// all keys in the [Table](crate::Table).  A `DistanceT` is the return type of the [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION).
@@ -139,8 +153,396 @@ pub trait TableConfig {
 
     /// The `DISTANCE_FUNCTION` is a [DistanceFunction] associated with a [Table](crate::Table) and defines
     /// the [Metric Space](https://en.wikipedia.org/wiki/Metric_space) that contains all [Key](crate::Key)s in the Table.
+    /// 
+    /// The default is [levenstein_distance](TableConfig::levenstein_distance).  [damerau_levenstein_distance](TableConfig::damerau_levenstein_distance)
+    /// is also provided, for keys where transposed characters are a common error, along with [jaro_winkler_distance](TableConfig::jaro_winkler_distance)
+    /// for matching names.
     const DISTANCE_FUNCTION : DistanceFunction<Self::KeyCharT, Self::DistanceT> = Self::levenstein_distance;
 
+    /// The `VARIANT_PRIORITY_FUNCTION` is a [VariantPriorityFunction] that determines the order in which the
+    /// variants of a fuzzy lookup key are probed.  Variants with a higher priority are probed first, and the
+    /// candidates they reference are returned before any lower-priority variant is loaded.
+    /// 
+    /// The default, [longest_variant_first](TableConfig::longest_variant_first), probes the most specific
+    /// variants first, so the [lazy](crate::FuzzyLookup) lookup results that are most likely to be close
+    /// matches are found without loading the large entries of short, noisy variants.
+    const VARIANT_PRIORITY_FUNCTION : VariantPriorityFunction = Self::longest_variant_first;
+
+    /// The `INDEX_STRATEGY` is the [IndexStrategy](crate::IndexStrategy) that determines how keys are indexed to
+    /// find the candidates for a fuzzy lookup.
+    /// 
+    /// The default, [DeletionVariants](crate::IndexStrategy::DeletionVariants), indexes the deletion variants
+    /// described by the SymSpell algorithm.  [Ngrams](crate::IndexStrategy::Ngrams) indexes the n-grams of
+    /// each key instead, which is much smaller for long keys.  The strategy is recorded in the [TableParams] when a
+    /// table is created, and is used whenever the table is opened again.
+    const INDEX_STRATEGY : IndexStrategy = IndexStrategy::DeletionVariants;
+
+    /// The `VARIANT_SCHEME` is the [VariantScheme](crate::VariantScheme) that generates the variants of each key, when
+    /// the [INDEX_STRATEGY](TableConfig::INDEX_STRATEGY) is [DeletionVariants](crate::IndexStrategy::DeletionVariants).
+    /// 
+    /// The default, [DeletionVariants](crate::DeletionVariants), deletes up to [MAX_DELETES](TableConfig::MAX_DELETES)
+    /// chars from each key, as described by the SymSpell algorithm.  The scheme's id is recorded in the [TableParams]
+    /// when a table is created, and the table can't be opened with a different scheme.
+    const VARIANT_SCHEME : &'static dyn VariantScheme<Self::KeyCharT> = &DeletionVariants;
+
+    /// The `FALLBACK_SCAN` is the [FallbackScan](crate::FallbackScan) that determines how
+    /// [lookup_fuzzy_extended](crate::Table::lookup_fuzzy_extended) finds the candidates for a lookup with a
+    /// `max_distance` beyond [MAX_DELETES](TableConfig::MAX_DELETES).
+    /// 
+    /// The default, [Disabled](crate::FallbackScan::Disabled), returns an error for such a lookup, so a table
+    /// indexed at a small `MAX_DELETES` must explicitly opt in to serving the occasional wider lookup with a scan.
+    /// Lookups within `MAX_DELETES` always use the index, regardless of this setting.
+    const FALLBACK_SCAN : FallbackScan = FallbackScan::Disabled;
+
+    /// The `PHONETIC_FUNCTION` is an optional [PhoneticEncoding] that encodes keys by how they sound.  If it is set,
+    /// the Table maintains a supplemental index from each phonetic code to the records with a key that has that code,
+    /// in the "phonetic" column family, which is searched by [lookup_phonetic](crate::Table::lookup_phonetic).
+    /// 
+    /// [SOUNDEX](PhoneticEncoding::SOUNDEX) and [DOUBLE_METAPHONE](PhoneticEncoding::DOUBLE_METAPHONE) are provided,
+    /// and other encodings can be supplied as a [PhoneticFunction] with a name of their own.
+    /// 
+    /// A phonetic index requires [UTF8_KEYS](TableConfig::UTF8_KEYS).  The default is `None`, for no phonetic index.
+    /// 
+    /// The encoding's name is stored in the database when it is created, and the database can't be opened with a
+    /// different encoding, or without one, once it is created.
+    const PHONETIC_FUNCTION : Option<PhoneticEncoding> = None;
+
+    /// A `const bool` that specifies whether the Table maintains a supplemental index of the whitespace-separated
+    /// tokens of each key, in the "tokens" column family, which is searched by
+    /// [lookup_phrase_fuzzy](crate::Table::lookup_phrase_fuzzy).
+    /// 
+    /// Each token is indexed under every variant formed by deleting up to [MAX_DELETES](TableConfig::MAX_DELETES)
+    /// of its chars, so a multi-word key can be found from a query with its words misspelled or in another order.
+    /// A token index requires [UTF8_KEYS](TableConfig::UTF8_KEYS).  The default is `false`, for no token index.
+    /// 
+    /// This setting is stored in the database when it is created, and the database can't be opened with a
+    /// different setting once it is created.
+    const TOKEN_INDEX : bool = false;
+
+    /// A `const bool` that specifies whether fuzzy lookups consult the synonyms added with
+    /// [add_synonyms](crate::Table::add_synonyms), e.g. so a lookup for "St Louis" also finds "Saint Louis".
+    /// 
+    /// Each token of the lookup key that has synonyms is replaced with each of them in turn, and the variants of
+    /// every resulting form of the key are probed along with the variants of the key itself.  A record's distance
+    /// is its distance from the closest form.  Synonyms require [UTF8_KEYS](TableConfig::UTF8_KEYS).  The default
+    /// is `false`, because consulting the synonyms costs a read for each token of every lookup key.
+    const SYNONYMS : bool = false;
+
+    /// A `const bool` that specifies whether the Table keeps a [RecordMetadata](crate::RecordMetadata) for each
+    /// record, in the "record_meta" column family, with the times the record was created and last modified, and
+    /// an optional tag.
+    /// 
+    /// The metadata is returned by [get_metadata](crate::Table::get_metadata), and
+    /// [records_modified_since](crate::Table::records_modified_since) finds the records that changed after a given
+    /// time, e.g. for an incremental sync.  The default is `false`, because keeping the metadata costs an extra
+    /// read and write for every change to a record.
+    const RECORD_METADATA : bool = false;
+
+    /// A `const bool` that specifies whether entries that can't be decoded should be moved into the
+    /// "quarantine" column family when they are encountered.
+    /// 
+    /// When `false`, a corrupt entry causes an error but is left in place.  When `true`, the raw bytes
+    /// are preserved along with the original column family and key, and can be retrieved with
+    /// [quarantined_entries](crate::Table::quarantined_entries) for debugging.  Either way, the operation
+    /// that encountered the corrupt entry returns an error.
+    const QUARANTINE_CORRUPT_ENTRIES : bool = false;
+
+    /// A `const bool` that specifies whether the [Table](crate::Table) should count how many times each record's
+    /// value is retrieved.
+    /// 
+    /// The counts are accumulated in memory and written to the "stats" column family in batches, so they are
+    /// approximate.  They can be inspected with [record_hits](crate::Table::record_hits) and
+    /// [top_records](crate::Table::top_records).
+    const TRACK_ACCESS_STATS : bool = false;
+
+    /// A `usize` that specifies how many of the most frequently accessed records are recorded in a preload manifest
+    /// when the [Table](crate::Table) is closed, or 0 to keep no manifest.  Requires [TRACK_ACCESS_STATS](TableConfig::TRACK_ACCESS_STATS).
+    /// 
+    /// When a Table with a manifest is opened, the entries for the records in the manifest are read, so they are
+    /// in RocksDB's cache before the first lookup.  A failure to preload doesn't stop the Table from opening.  See
+    /// [preload](crate::Table::preload).
+    const PRELOAD_MANIFEST_LEN : usize = 0;
+
+    /// A `usize` that specifies how many batches of values may be queued for prefetching by
+    /// [lookup_fuzzy_with_values](crate::Table::lookup_fuzzy_with_values), or 0 to disable prefetching.
+    /// 
+    /// When prefetching is enabled, the values of the confirmed results are read in batches on a separate thread,
+    /// while the lookup goes on confirming the remaining candidates, so the reads overlap with the distance
+    /// evaluations instead of following them.  Once this many batches are waiting to be read, the lookup pauses
+    /// until the reads catch up.  The default is 0, because a lookup with only a few results gains nothing from
+    /// spawning a thread.
+    const VALUE_PREFETCH_DEPTH : usize = 0;
+
+    /// A `usize` that specifies the maximum number of key group references stored in a single physical
+    /// variant entry, or 0 to store every variant entry in one piece.
+    /// 
+    /// Pathological variants, such as those for very short or very common keys, may be referenced by a huge
+    /// number of key groups.  Without paging, every probe of such a variant loads the whole entry, and every
+    /// update re-serializes it.  With paging, an entry is split across pages of at most `VARIANT_PAGE_SIZE`
+    /// references, and only the page being changed is rewritten.  The cost is an additional read for each
+    /// variant probed, and a read of each page when the variant is updated.
+    /// 
+    /// WARNING: This setting must not change over the life of a database.  A database written with paging
+    /// enabled will return incomplete lookup results if it is opened with paging disabled.
+    const VARIANT_PAGE_SIZE : usize = 0;
+
+    /// A `usize` that specifies the largest serialized size, in bytes, of a value that is stored inline in the
+    /// record's "rec_data" entry instead of the "values" column family, or 0 to never store values inline.
+    /// 
+    /// Small values, such as short ID strings, can then be read along with the record's key groups, which are
+    /// usually already in RocksDB's cache after a lookup.  Larger values are stored in the "values" CF as usual.
+    /// The cost is an additional read of the "rec_data" entry when a value isn't inline, and when a record's key
+    /// groups are updated.
+    /// 
+    /// This may be raised or lowered over the life of a database, but once it has been set, the database can't
+    /// be opened with it set back to 0, because the values stored inline couldn't be read.  The size the database
+    /// was last opened with is stored in the database to enforce this.
+    const INLINE_VALUE_MAX_SIZE : usize = 0;
+
+    /// A `usize` that specifies the largest stored size, in bytes, of a value kept in a single entry of the "values"
+    /// column family, or 0 to never split values.
+    /// 
+    /// A larger value is split into chunks of this size, stored in the "blobs" column family, and reassembled
+    /// whenever the value is read, so a few very large values don't slow down the compaction of the "values" CF.
+    /// Values of a few MB or more are good candidates.
+    /// 
+    /// WARNING: This may be raised or lowered over the life of a database, but once any value has been split it
+    /// must not be set back to 0, or the chunks of a value that is later overwritten will be left behind.
+    const BLOB_THRESHOLD : usize = 0;
+
+    /// A `bool` that specifies whether the Table maintains an index from each whole key to the records that
+    /// have it, in the "exact_keys" column family.
+    /// 
+    /// With the index, [lookup_exact](crate::Table::lookup_exact) reads a single entry, instead of probing the
+    /// key's variant and checking the keys of every record it references.  It also only returns records with
+    /// exactly the key, whereas without the index, a key no longer than [MEANINGFUL_KEY_LEN](TableConfig::MEANINGFUL_KEY_LEN)
+    /// also matches the records with longer keys that begin with it.  The cost is an additional read and write
+    /// for each key when a record's keys are changed.
+    /// 
+    /// WARNING: This setting must not change over the life of a database.  A database written without the
+    /// index will return incomplete results from `lookup_exact` if it is opened with the index enabled.
+    const EXACT_KEY_INDEX : bool = false;
+
+    /// A `usize` that specifies the number of lookups whose results are kept by [lookup_fuzzy_cached](crate::Table::lookup_fuzzy_cached),
+    /// or 0 to disable the cache.
+    /// 
+    /// A cached lookup is discarded when a record's keys change in a way that could affect its results, and
+    /// the least recently used lookup is discarded when the cache is full.
+    const QUERY_CACHE_CAPACITY : usize = 0;
+
+    /// A `bool` that specifies whether each new record's [RecordID](crate::RecordID) is derived from a hash of its
+    /// first key, rather than assigned sequentially.
+    /// 
+    /// With sequential RecordIDs, a record's RecordID depends on the order records were inserted and deleted.
+    /// With deterministic RecordIDs, Tables built independently from the same records are given the same
+    /// RecordIDs regardless of order, so replication and merge logic can rely on them.  If two keys hash to the
+    /// same RecordID, the record inserted later takes the next free RecordID, so identical keys only get identical
+    /// RecordIDs if the colliding records are inserted in the same order.
+    /// 
+    /// NOTE: The entries that hold a record's keys and variant references are sets, so the order of their
+    /// elements isn't stable.  Use [export_stream](crate::Table::export_stream) to compare two Tables, rather than
+    /// comparing the database files directly.
+    /// 
+    /// WARNING: This setting must not change over the life of a database.  Sequential RecordIDs are assigned
+    /// after the highest RecordID found when the database is opened, so they would collide with hashed ones.
+    const DETERMINISTIC_RECORD_IDS : bool = false;
+
+    /// A `bool` that specifies whether database entries that are well-formed, but can't be valid, should be
+    /// treated as corrupt.  Malformed entries always produce an error, regardless of this setting.
+    /// 
+    /// When set, entries that reference the same key group more than once, and hit counts that aren't exactly
+    /// 8 bytes, are reported as corrupt (and quarantined if [QUARANTINE_CORRUPT_ENTRIES](TableConfig::QUARANTINE_CORRUPT_ENTRIES)
+    /// is set).  Otherwise duplicate references are tolerated, and malformed hit counts are read as zero.
+    /// The extra checks cost some performance on every read.
+    const STRICT_DECODING : bool = false;
+
+    /// A `const bool` that specifies whether a CRC32 checksum is appended to each entry in the "keys", "rec_data",
+    /// and "values" column families, and checked whenever the entry is read.
+    /// 
+    /// Without checksums, an entry damaged by bit rot is only caught if it no longer decodes, and may otherwise be
+    /// read back as a different key or value.  With them, a damaged entry returns an error naming the record it
+    /// belongs to, and is quarantined if [QUARANTINE_CORRUPT_ENTRIES](TableConfig::QUARANTINE_CORRUPT_ENTRIES) is
+    /// set.  The checksums cost 4 bytes per entry, and a little time on every read and write.
+    /// 
+    /// The setting is recorded in the database when it is created, and a Table can't be opened with a different
+    /// setting than the one its database was written with.
+    const CHECKSUMS : bool = false;
+
+    /// The number of operands at which an invocation of the "variants" CF merge operator is counted as an
+    /// anomaly in the [merge_diagnostics](crate::Table::merge_diagnostics), or 0 to never count it.
+    const LARGE_MERGE_OPERAND_COUNT : usize = 1024;
+
+    /// The maximum number of anomalous merge operator invocations kept in the log returned by
+    /// [merge_diagnostics](crate::Table::merge_diagnostics), or 0 to keep no log.  The counts are kept regardless.
+    /// 
+    /// Every time a variant entry is first created, the merge operator is invoked without an existing entry,
+    /// so the log fills up quickly.  It is meant for diagnosing RocksDB's behavior, not for production use.
+    const MERGE_DIAGNOSTIC_LOG_LEN : usize = 0;
+
+    /// A `bool` that specifies whether fuzzy lookups queue the key groups of each record they evaluate, to be
+    /// checked for missing variant references, which are then restored.
+    /// 
+    /// A write that was only partially applied, e.g. to a database restored from an inconsistent copy, can leave a
+    /// record that is found through some of its variants but not others, so recall silently degrades.  With
+    /// read-repair, lookups note the key groups they come across, and [apply_read_repairs](crate::Table::apply_read_repairs)
+    /// checks every variant of their keys and restores the missing references, e.g. from a periodic maintenance task.
+    /// Lookups only take `&self`, so they never write to the database themselves, and they don't read anything
+    /// extra either; the cost to a lookup is adding each key group to the queue, which holds at most a few thousand.
+    const READ_REPAIR : bool = false;
+
+    /// An `i32` that limits the number of files RocksDB keeps open at once, or -1 to keep every file open once
+    /// it has been opened, which is RocksDB's default.
+    /// 
+    /// A large Table spreads its "variants" column family across many files, and with no limit each of them holds
+    /// a file handle for as long as the Table is open.  Where handles are scarce, e.g. for a Windows service, a
+    /// limit keeps the Table from exhausting them, at the cost of reopening files that have been evicted.  RocksDB
+    /// raises any limit below 20 to 20.  A [secondary](crate::Table::open_as_secondary) Table always keeps every file open.
+    const MAX_OPEN_FILES : i32 = -1;
+
+    /// An `i32` that specifies the number of bits used to shard RocksDB's cache of open files, so 2 to the power
+    /// of this many shards are locked independently.  RocksDB's default is 6.
+    /// 
+    /// With a small [MAX_OPEN_FILES](TableConfig::MAX_OPEN_FILES), fewer shards keep each shard from being too
+    /// small to hold the files it's given.
+    const TABLE_CACHE_NUM_SHARD_BITS : i32 = 6;
+
+    /// A [RocksConfig] with the tuning options passed through to RocksDB, such as the size of the block cache.
+    /// The default keeps RocksDB's defaults, apart from bloom filters on the variant CFs.
+    /// 
+    /// Unlike the parameters that shape the index, these may be changed whenever the Table is opened.
+    const ROCKS_CONFIG : RocksConfig = RocksConfig::DEFAULT;
+
+    /// An `Option<Duration>` that, if set, makes the records of a cache-style Table age out once they haven't
+    /// been modified for longer than this
+    /// 
+    /// The TTL counts from the `modified` time in the record's [RecordMetadata](crate::RecordMetadata), which is
+    /// kept for every record written while the TTL is set, even if [RECORD_METADATA](TableConfig::RECORD_METADATA)
+    /// isn't.  A record without metadata, e.g. one written before the TTL was set, never expires.  Only records expire.
+    /// The Table's parameters, format version, and the rest of the "metadata" and "tombstones" column families are
+    /// kept, so a Table with a TTL can be reopened, and [reset](crate::Table::reset) leaves it with the same TTL.
+    /// 
+    /// An expired record is left out of every lookup, and other accessors, such as [get_value](crate::Table::get_value),
+    /// return an "Invalid record_id" error for it, which a caller should treat as a miss.  Its entries stay in the
+    /// database until it is deleted by [purge_expired](crate::Table::purge_expired), which [shutdown](crate::Table::shutdown)
+    /// also calls.  Replacing an expired record's keys or value makes it live again.  The TTL is only checked
+    /// against the system clock, so it may change from one open to the next, and a Table with a TTL may be opened
+    /// [read-only](crate::Table::open_read_only).
+    const TTL : Option<Duration> = None;
+
+    /// Returns the number of deletes used to create variants.  By default this is [MAX_DELETES](TableConfig::MAX_DELETES),
+    /// but a config such as [RuntimeConfig] may choose it at runtime.
+    /// 
+    /// This, and the other runtime parameters, are only consulted when a database is created (or [reset](crate::Table::reset)).
+    /// They are stored in the database, and the stored values are used whenever the database is opened again.
+    fn max_deletes(&self) -> usize {
+        Self::MAX_DELETES
+    }
+
+    /// Returns the number of characters of a key used to create variants.  By default this is
+    /// [MEANINGFUL_KEY_LEN](TableConfig::MEANINGFUL_KEY_LEN)
+    fn meaningful_key_len(&self) -> usize {
+        Self::MEANINGFUL_KEY_LEN
+    }
+
+    /// Returns the number of variants a key must share with a key group in order to join it.  By default this
+    /// is [GROUP_VARIANT_OVERLAP_THRESHOLD](TableConfig::GROUP_VARIANT_OVERLAP_THRESHOLD)
+    fn group_variant_overlap_threshold(&self) -> usize {
+        Self::GROUP_VARIANT_OVERLAP_THRESHOLD
+    }
+
+    /// Returns whether keys that differ only in case are treated as different keys.  The default is `true`
+    /// 
+    /// When `false`, keys are lowercased (according to Unicode) before variants are created and before
+    /// keys are compared or passed to the [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION), but the keys
+    /// are stored, and returned, exactly as they were inserted.  Only tables with [UTF8_KEYS](TableConfig::UTF8_KEYS)
+    /// can be case-insensitive.
+    /// 
+    /// The setting is stored in the database along with the other [TableParams], so lookups fold keys the same
+    /// way they were folded when the keys were inserted, even if the Table is reopened with a different config.
+    /// 
+    /// NOTE: Keys are folded with [str::to_lowercase], which handles every cased script but isn't full Unicode
+    /// case folding, so a lowercase letter with no single-char uppercase form, such as "ß", doesn't match its
+    /// multi-char uppercase form, such as "SS".
+    fn case_sensitive(&self) -> bool {
+        true
+    }
+
+    /// Returns whether keys that differ only in their diacritics, such as "café" and "cafe", are treated as the
+    /// same key.  The default is `false`
+    /// 
+    /// When `true`, diacritics are stripped from keys (see [unicode_strip_diacritics](crate::unicode_string_helpers::unicode_strip_diacritics))
+    /// before variants are created and before keys are compared or passed to the [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION),
+    /// so an accent doesn't use up any of the distance budget of a fuzzy lookup.  As with [case_sensitive](TableConfig::case_sensitive),
+    /// the keys are stored, and returned, exactly as they were inserted, the setting is stored in the database, and
+    /// only tables with [UTF8_KEYS](TableConfig::UTF8_KEYS) can fold diacritics.
+    fn fold_diacritics(&self) -> bool {
+        false
+    }
+
+    /// Returns the stop words, such as "the", "inc", or "llc", that are left out of the indexed form of a multi-word
+    /// key.  The default is none
+    /// 
+    /// Each whitespace-separated token of a key that matches a stop word, once the key has been folded according to
+    /// [case_sensitive](TableConfig::case_sensitive) and [fold_diacritics](TableConfig::fold_diacritics), is dropped
+    /// before variants are created and before keys are compared or passed to the [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION),
+    /// so "The Acme Company LLC" is indexed and compared as "Acme Company".  A key made up only of stop words is
+    /// left whole.  As with `case_sensitive`, the keys are stored, and returned, exactly as they were inserted, the
+    /// stop words are stored in the database, and only tables with [UTF8_KEYS](TableConfig::UTF8_KEYS) can have them.
+    fn stop_words(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Returns whether variants are created by deleting whole grapheme clusters, i.e. user-perceived characters,
+    /// rather than individual chars.  The default is `false`
+    /// 
+    /// An emoji with a skin-tone modifier, or a syllable in an Indic script, may be made of several chars.  When
+    /// `true`, deleting one of them makes a single variant, rather than several variants that split the character,
+    /// and [MAX_DELETES](TableConfig::MAX_DELETES) and [MEANINGFUL_KEY_LEN](TableConfig::MEANINGFUL_KEY_LEN) count
+    /// grapheme clusters.  It should be used along with a distance function that counts grapheme clusters too, such
+    /// as [grapheme_levenstein_distance](TableConfig::grapheme_levenstein_distance).  The setting is stored in the
+    /// database, and only tables with [UTF8_KEYS](TableConfig::UTF8_KEYS) can use grapheme clusters.
+    /// 
+    /// NOTE: This requires the `unicode-segmentation` feature.  A database created with grapheme clusters can't be
+    /// opened without it.
+    #[cfg(feature = "unicode-segmentation")]
+    fn grapheme_clusters(&self) -> bool {
+        false
+    }
+
+    /// Returns the [Cipher] used to encrypt the Table's entries, or `None` to store them unencrypted.  The default
+    /// is `None`
+    /// 
+    /// The key material is normally supplied when the Table is opened, by giving the config a field to hold it.
+    /// Whether the database is encrypted, and a check value, are stored in the database when it is created, so a
+    /// database can't be opened without a cipher, or with the wrong key, once it is encrypted, and vice versa.
+    fn cipher(&self) -> Option<Arc<dyn Cipher>> {
+        None
+    }
+
+    /// Returns the threshold used by [lookup_fuzzy](crate::Table::lookup_fuzzy), and the other fuzzy lookups
+    /// that take a `threshold`, when they are called without one.  The default is `None`, meaning no threshold
+    /// 
+    /// [lookup_fuzzy_relative](crate::Table::lookup_fuzzy_relative) takes a threshold relative to the key lengths
+    /// instead, so the default threshold isn't applied to it.
+    /// 
+    /// Unlike the other runtime parameters, the default threshold only affects lookups, so it isn't stored in
+    /// the database, and may change each time the database is opened.
+    fn default_threshold(&self) -> Option<Self::DistanceT> {
+        None
+    }
+
+    /// A [VariantPriorityFunction] that prioritizes variants by their encoded length, so the longest (most
+    /// specific) variants are probed first.  This is the default.
+    fn longest_variant_first(variant : &[u8]) -> usize {
+        variant.len()
+    }
+
+    /// A [VariantPriorityFunction] that gives every variant the same priority, so variants are probed in
+    /// an unspecified order.
+    fn unordered_variants(_variant : &[u8]) -> usize {
+        0
+    }
+
     /// An implementation of the basic [Levenstein Distance](https://en.wikipedia.org/wiki/Levenshtein_distance) function, which is used by the [DefaultTableConfig],
     /// and may be used anywhere a distance function is required.
     /// 
@@ -205,6 +607,172 @@ pub trait TableConfig {
 
         Self::DistanceT::from(unsafe{ d[m-1][n-1].assume_init() })
     }
+
+    /// An implementation of the [Damerau-Levenstein Distance](https://en.wikipedia.org/wiki/Damerau%E2%80%93Levenshtein_distance)
+    /// function, which counts a transposition of two adjacent characters, e.g. "teh" for "the", as a single edit
+    /// rather than two.  Select it by setting [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION) to
+    /// `Self::damerau_levenstein_distance`.
+    /// 
+    /// This implementation computes the "optimal string alignment" variant, in which no substring is edited
+    /// more than once, so "ca" -> "abc" is 3 edits rather than 2.
+    fn damerau_levenstein_distance(key_a : &[Self::KeyCharT], key_b : &[Self::KeyCharT]) -> Self::DistanceT {
+
+        let n = key_b.len()+1;
+
+        //We only ever look back two rows, so we keep three rows of distances and rotate through them
+        let mut two_rows_back = [0u8; MAX_KEY_LENGTH + 1];
+        let mut prev_row = [0u8; MAX_KEY_LENGTH + 1];
+        let mut row = [0u8; MAX_KEY_LENGTH + 1];
+        for (j, element) in prev_row.iter_mut().enumerate().take(n) {
+            *element = j as u8;
+        }
+
+        for i in 1..=key_a.len() {
+            row[0] = i as u8;
+            for j in 1..n {
+
+                let substitution_cost = if key_a[i-1] == key_b[j-1] {
+                    0
+                } else {
+                    1
+                };
+
+                let deletion_distance = prev_row[j] + 1;
+                let insertion_distance = row[j-1] + 1;
+                let substitution_distance = prev_row[j-1] + substitution_cost;
+                let mut smallest_distance = min(min(deletion_distance, insertion_distance), substitution_distance);
+
+                if i > 1 && j > 1 && key_a[i-1] == key_b[j-2] && key_a[i-2] == key_b[j-1] {
+                    let transposition_distance = two_rows_back[j-2] + 1;
+                    smallest_distance = min(smallest_distance, transposition_distance);
+                }
+
+                row[j] = smallest_distance;
+            }
+
+            core::mem::swap(&mut two_rows_back, &mut prev_row);
+            core::mem::swap(&mut prev_row, &mut row);
+        }
+
+        Self::DistanceT::from(prev_row[n-1])
+    }
+
+    /// An implementation of the [Levenstein Distance](https://en.wikipedia.org/wiki/Levenshtein_distance) function that
+    /// counts edits to whole grapheme clusters, i.e. user-perceived characters, rather than to individual chars.
+    /// Select it by setting [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION) to `Self::grapheme_levenstein_distance`,
+    /// along with [grapheme_clusters](TableConfig::grapheme_clusters).
+    /// 
+    /// NOTE: This requires the `unicode-segmentation` feature.
+    #[cfg(feature = "unicode-segmentation")]
+    fn grapheme_levenstein_distance(key_a : &[Self::KeyCharT], key_b : &[Self::KeyCharT]) -> Self::DistanceT
+        where Self::KeyCharT : Into<char>
+    {
+        let string_a : String = key_a.iter().map(|the_char| the_char.clone().into()).collect();
+        let string_b : String = key_b.iter().map(|the_char| the_char.clone().into()).collect();
+        let graphemes_a : Vec<&str> = string_a.graphemes(true).collect();
+        let graphemes_b : Vec<&str> = string_b.graphemes(true).collect();
+
+        let n = graphemes_b.len()+1;
+
+        //There are never more grapheme clusters than chars, so the rows never exceed MAX_KEY_LENGTH
+        let mut prev_row = [0u8; MAX_KEY_LENGTH + 1];
+        let mut row = [0u8; MAX_KEY_LENGTH + 1];
+        for (j, element) in prev_row.iter_mut().enumerate().take(n) {
+            *element = j as u8;
+        }
+
+        for (i, grapheme_a) in graphemes_a.iter().enumerate() {
+            row[0] = i as u8 + 1;
+            for j in 1..n {
+                let substitution_cost = if *grapheme_a == graphemes_b[j-1] { 0 } else { 1 };
+                row[j] = min(min(prev_row[j] + 1, row[j-1] + 1), prev_row[j-1] + substitution_cost);
+            }
+            core::mem::swap(&mut prev_row, &mut row);
+        }
+
+        Self::DistanceT::from(prev_row[n-1])
+    }
+
+    /// A distance function based on the [Jaro-Winkler Similarity](https://en.wikipedia.org/wiki/Jaro%E2%80%93Winkler_distance),
+    /// which is well suited to short keys such as person names, and favors keys that share a common prefix.
+    /// Select it by setting [DISTANCE_FUNCTION](TableConfig::DISTANCE_FUNCTION) to `Self::jaro_winkler_distance`.
+    ///
+    /// The distance is `1.0 - similarity`, so it ranges from `0.0` for identical keys to `1.0` for keys with
+    /// nothing in common, and `DistanceT` must be a fractional type such as [f32].  Use [jaro_winkler_threshold](TableConfig::jaro_winkler_threshold)
+    /// to express a lookup threshold as a minimum similarity.
+    ///
+    /// NOTE: The distance function is only used to verify and rank candidates.  Keys further than
+    /// [MAX_DELETES](TableConfig::MAX_DELETES) deletes from the lookup key are never evaluated, however similar they may be.
+    fn jaro_winkler_distance(key_a : &[Self::KeyCharT], key_b : &[Self::KeyCharT]) -> Self::DistanceT
+        where Self::DistanceT : From<f32>
+    {
+        Self::DistanceT::from(1.0 - jaro_winkler_similarity(key_a, key_b))
+    }
+
+    /// Converts a minimum [Jaro-Winkler Similarity](TableConfig::jaro_winkler_distance) between `0.0` and `1.0`
+    /// into the threshold to pass to a fuzzy lookup, or to return from [default_threshold](TableConfig::default_threshold)
+    fn jaro_winkler_threshold(min_similarity : f32) -> Self::DistanceT
+        where Self::DistanceT : From<f32>
+    {
+        Self::DistanceT::from(1.0 - min_similarity)
+    }
+}
+
+/// Computes the Jaro-Winkler Similarity between two keys, with the standard prefix scale of 0.1 applied to
+/// a common prefix of up to 4 chars
+fn jaro_winkler_similarity<KeyCharT : PartialEq>(key_a : &[KeyCharT], key_b : &[KeyCharT]) -> f32 {
+
+    if key_a.is_empty() && key_b.is_empty() {
+        return 1.0;
+    }
+    if key_a.is_empty() || key_b.is_empty() {
+        return 0.0;
+    }
+
+    //Chars only match if they are within this many positions of each other
+    let match_window = (max(key_a.len(), key_b.len()) / 2).saturating_sub(1);
+
+    let mut a_matched = [false; MAX_KEY_LENGTH];
+    let mut b_matched = [false; MAX_KEY_LENGTH];
+    let mut matches = 0;
+    for (i, a_char) in key_a.iter().enumerate() {
+        let start = i.saturating_sub(match_window);
+        let end = min(i + match_window + 1, key_b.len());
+        for j in start..end {
+            if !b_matched[j] && key_b[j] == *a_char {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+
+    if matches == 0 {
+        return 0.0;
+    }
+
+    //Count the matched chars that are out of order.  Each transposition is two of them
+    let mut out_of_order = 0;
+    let mut j = 0;
+    for (i, a_char) in key_a.iter().enumerate() {
+        if a_matched[i] {
+            while !b_matched[j] {
+                j += 1;
+            }
+            if key_b[j] != *a_char {
+                out_of_order += 1;
+            }
+            j += 1;
+        }
+    }
+
+    let matches = matches as f32;
+    let transpositions = (out_of_order / 2) as f32;
+    let jaro = (matches / key_a.len() as f32 + matches / key_b.len() as f32 + (matches - transpositions) / matches) / 3.0;
+
+    let prefix_len = key_a.iter().zip(key_b.iter()).take(4).take_while(|(a, b)| a == b).count();
+    jaro + prefix_len as f32 * 0.1 * (1.0 - jaro)
 }
 
 /// A type for a function to compute the distance between two keys. Used in a [TableConfig]
@@ -250,6 +818,66 @@ pub trait TableConfig {
 /// between the two keys, and the delete distance is irrelevant from that point onwards.
 pub type DistanceFunction<KeyCharT, DistanceT> = fn(key_a : &[KeyCharT], key_b : &[KeyCharT]) -> DistanceT;
 
+/// A type for a function to encode a key by how it sounds, for a phonetic index. Used in a [TableConfig]
+/// 
+/// A `PhoneticFunction` returns every code a key should be indexed under, which may be none.  Two keys are
+/// phonetic matches if they have a code in common.
+pub type PhoneticFunction = fn(key : &str) -> Vec<String>;
+
+/// A phonetic encoding for a phonetic index, made of a [PhoneticFunction] and the name that identifies it in the
+/// database. Used in a [TableConfig]
+/// 
+/// The name of each encoding must be unique, because a database built with one encoding can only be opened
+/// with an encoding of the same name.
+#[derive(Clone, Copy)]
+pub struct PhoneticEncoding {
+    pub name : &'static str,
+    pub function : PhoneticFunction,
+}
+
+impl PhoneticEncoding {
+
+    /// Encodes a key as its [American Soundex](https://en.wikipedia.org/wiki/Soundex) code, e.g. "R163" for both
+    /// "Robert" and "Rupert".  A key without any letters has no code.
+    pub const SOUNDEX : Self = Self{name : "soundex", function : soundex_codes};
+
+    /// Encodes a key as its primary and alternate [Double Metaphone](https://en.wikipedia.org/wiki/Metaphone#Double_Metaphone)
+    /// codes, e.g. "XMT" and "SMT" for "Schmidt", which shares "XMT" with "Smith".  The alternate code is only
+    /// returned if it differs from the primary code.
+    pub const DOUBLE_METAPHONE : Self = Self{name : "double_metaphone", function : double_metaphone_codes};
+}
+
+/// The [PhoneticFunction] of [PhoneticEncoding::SOUNDEX]
+fn soundex_codes(key : &str) -> Vec<String> {
+    phonetic::soundex(key).into_iter().collect()
+}
+
+/// The [PhoneticFunction] of [PhoneticEncoding::DOUBLE_METAPHONE]
+fn double_metaphone_codes(key : &str) -> Vec<String> {
+    match phonetic::double_metaphone(key) {
+        Some((primary, alternate)) => {
+            let mut codes = vec![];
+            for code in [primary, alternate] {
+                if !code.is_empty() && !codes.contains(&code) {
+                    codes.push(code);
+                }
+            }
+            codes
+        },
+        None => vec![]
+    }
+}
+
+/// A type for a function to rank the variants of a fuzzy lookup key. Used in a [TableConfig]
+/// 
+/// A `VariantPriorityFunction` is given the encoded bytes of a variant, and returns its priority.  Variants are
+/// probed in descending order of priority; the order among variants with equal priority is unspecified.
+/// 
+/// The priority only changes the order in which results are produced by the lazy lookup iterators, such as
+/// [FuzzyLookup](crate::FuzzyLookup).  Once a lookup has run to completion, the set of results is the same
+/// regardless of the priority function.
+pub type VariantPriorityFunction = fn(variant : &[u8]) -> usize;
+
 /// A struct that implements [TableConfig] with default values.  This can be passed as a convenience
 /// when a default configuration for [Table](crate::Table) is acceptable
 pub struct DefaultTableConfig();
@@ -264,3 +892,327 @@ impl TableConfig for DefaultTableConfig {
     const GROUP_VARIANT_OVERLAP_THRESHOLD : usize = 5;
     const DISTANCE_FUNCTION : DistanceFunction<Self::KeyCharT, Self::DistanceT> = Self::levenstein_distance;
 }
+
+/// A struct that implements [TableConfig] with the [DefaultTableConfig] values, but with RocksDB's file handle
+/// usage limited to suit a Windows service
+/// 
+/// The default config keeps every database file open, and a large Table can easily exhaust the handles that a
+/// service account is allowed.  This config caps the number of open files at 512, and shards the file cache to
+/// match.  Paths longer than `MAX_PATH` are handled on Windows regardless of the config.
+/// 
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let mut table = Table::<WindowsServiceTableConfig, true>::new("windows_service_example.rocks", WindowsServiceTableConfig()).unwrap();
+/// table.reset().unwrap();
+/// table.insert("Hello", &"Greeting".to_string()).unwrap();
+/// assert_eq!(table.lookup_fuzzy_raw("Hallo").unwrap().into_vec().unwrap().len(), 1);
+/// ```
+pub struct WindowsServiceTableConfig();
+
+impl TableConfig for WindowsServiceTableConfig {
+    type KeyCharT = char;
+    type DistanceT =  u8;
+    type ValueT = String;
+    const UTF8_KEYS : bool = true;
+    const MAX_DELETES : usize = 2;
+    const MEANINGFUL_KEY_LEN : usize = 12;
+    const GROUP_VARIANT_OVERLAP_THRESHOLD : usize = 5;
+    const DISTANCE_FUNCTION : DistanceFunction<Self::KeyCharT, Self::DistanceT> = Self::levenstein_distance;
+    const MAX_OPEN_FILES : i32 = 512;
+    const TABLE_CACHE_NUM_SHARD_BITS : i32 = 4;
+}
+
+/// A [TableConfig] whose parameters are chosen at runtime, e.g. from a configuration file, rather than
+/// at compile time.  Create one with a [RuntimeConfigBuilder]
+/// 
+/// A RuntimeConfig takes its types, and all of the parameters that can't be set with the builder, from another
+/// TableConfig, `BaseT`, which is [DefaultTableConfig] if not specified.
+/// 
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let config = RuntimeConfig::builder()
+///     .max_deletes(1)
+///     .meaningful_key_len(8)
+///     .case_sensitive(false)
+///     .build();
+/// let mut table = Table::<RuntimeConfig, true>::new("runtime_config_example.rocks", config).unwrap();
+/// table.reset().unwrap();
+/// table.insert("Hello", &"Greeting".to_string()).unwrap();
+/// assert_eq!(table.lookup_fuzzy_raw("hELLO").unwrap().into_vec().unwrap().len(), 1);
+/// ```
+/// 
+/// The parameters a database was created with are stored in it, and are used whenever the database is
+/// opened again, regardless of the config used to open it.  To change them, [reset](crate::Table::reset)
+/// the Table and insert the records again.
+pub struct RuntimeConfig<BaseT : TableConfig = DefaultTableConfig> {
+    max_deletes : usize,
+    meaningful_key_len : usize,
+    group_variant_overlap_threshold : usize,
+    case_sensitive : bool,
+    fold_diacritics : bool,
+    stop_words : Vec<String>,
+    #[cfg(feature = "unicode-segmentation")]
+    grapheme_clusters : bool,
+    default_threshold : Option<BaseT::DistanceT>,
+    cipher : Option<Arc<dyn Cipher>>,
+    phantom : PhantomData<BaseT>,
+}
+
+impl RuntimeConfig {
+
+    /// Returns a builder for a RuntimeConfig based on the [DefaultTableConfig].  Use
+    /// [RuntimeConfigBuilder::new] to base the config on another TableConfig
+    pub fn builder() -> RuntimeConfigBuilder {
+        RuntimeConfigBuilder::new()
+    }
+}
+
+impl <BaseT : TableConfig>TableConfig for RuntimeConfig<BaseT> {
+    type KeyCharT = BaseT::KeyCharT;
+    type DistanceT = BaseT::DistanceT;
+    type ValueT = BaseT::ValueT;
+    const UTF8_KEYS : bool = BaseT::UTF8_KEYS;
+    const MAX_DELETES : usize = BaseT::MAX_DELETES;
+    const MEANINGFUL_KEY_LEN : usize = BaseT::MEANINGFUL_KEY_LEN;
+    const GROUP_VARIANT_OVERLAP_THRESHOLD : usize = BaseT::GROUP_VARIANT_OVERLAP_THRESHOLD;
+    const DISTANCE_FUNCTION : DistanceFunction<Self::KeyCharT, Self::DistanceT> = BaseT::DISTANCE_FUNCTION;
+    const VARIANT_PRIORITY_FUNCTION : VariantPriorityFunction = BaseT::VARIANT_PRIORITY_FUNCTION;
+    const INDEX_STRATEGY : IndexStrategy = BaseT::INDEX_STRATEGY;
+    const VARIANT_SCHEME : &'static dyn VariantScheme<Self::KeyCharT> = BaseT::VARIANT_SCHEME;
+    const FALLBACK_SCAN : FallbackScan = BaseT::FALLBACK_SCAN;
+    const PHONETIC_FUNCTION : Option<PhoneticEncoding> = BaseT::PHONETIC_FUNCTION;
+    const TOKEN_INDEX : bool = BaseT::TOKEN_INDEX;
+    const SYNONYMS : bool = BaseT::SYNONYMS;
+    const RECORD_METADATA : bool = BaseT::RECORD_METADATA;
+    const QUARANTINE_CORRUPT_ENTRIES : bool = BaseT::QUARANTINE_CORRUPT_ENTRIES;
+    const TRACK_ACCESS_STATS : bool = BaseT::TRACK_ACCESS_STATS;
+    const PRELOAD_MANIFEST_LEN : usize = BaseT::PRELOAD_MANIFEST_LEN;
+    const VALUE_PREFETCH_DEPTH : usize = BaseT::VALUE_PREFETCH_DEPTH;
+    const VARIANT_PAGE_SIZE : usize = BaseT::VARIANT_PAGE_SIZE;
+    const INLINE_VALUE_MAX_SIZE : usize = BaseT::INLINE_VALUE_MAX_SIZE;
+    const BLOB_THRESHOLD : usize = BaseT::BLOB_THRESHOLD;
+    const EXACT_KEY_INDEX : bool = BaseT::EXACT_KEY_INDEX;
+    const QUERY_CACHE_CAPACITY : usize = BaseT::QUERY_CACHE_CAPACITY;
+    const DETERMINISTIC_RECORD_IDS : bool = BaseT::DETERMINISTIC_RECORD_IDS;
+    const STRICT_DECODING : bool = BaseT::STRICT_DECODING;
+    const CHECKSUMS : bool = BaseT::CHECKSUMS;
+    const LARGE_MERGE_OPERAND_COUNT : usize = BaseT::LARGE_MERGE_OPERAND_COUNT;
+    const MERGE_DIAGNOSTIC_LOG_LEN : usize = BaseT::MERGE_DIAGNOSTIC_LOG_LEN;
+    const READ_REPAIR : bool = BaseT::READ_REPAIR;
+    const MAX_OPEN_FILES : i32 = BaseT::MAX_OPEN_FILES;
+    const TABLE_CACHE_NUM_SHARD_BITS : i32 = BaseT::TABLE_CACHE_NUM_SHARD_BITS;
+    const ROCKS_CONFIG : RocksConfig = BaseT::ROCKS_CONFIG;
+    const TTL : Option<Duration> = BaseT::TTL;
+
+    fn max_deletes(&self) -> usize {
+        self.max_deletes
+    }
+    fn meaningful_key_len(&self) -> usize {
+        self.meaningful_key_len
+    }
+    fn group_variant_overlap_threshold(&self) -> usize {
+        self.group_variant_overlap_threshold
+    }
+    fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+    fn fold_diacritics(&self) -> bool {
+        self.fold_diacritics
+    }
+    fn stop_words(&self) -> Vec<String> {
+        self.stop_words.clone()
+    }
+    #[cfg(feature = "unicode-segmentation")]
+    fn grapheme_clusters(&self) -> bool {
+        self.grapheme_clusters
+    }
+    fn cipher(&self) -> Option<Arc<dyn Cipher>> {
+        self.cipher.clone()
+    }
+    fn default_threshold(&self) -> Option<Self::DistanceT> {
+        self.default_threshold
+    }
+}
+
+/// Builds a [RuntimeConfig].  Any parameter that isn't set takes its value from `BaseT`
+pub struct RuntimeConfigBuilder<BaseT : TableConfig = DefaultTableConfig> {
+    config : RuntimeConfig<BaseT>,
+}
+
+impl <BaseT : TableConfig>RuntimeConfigBuilder<BaseT> {
+
+    /// Creates a builder with all of the parameters taken from `BaseT`
+    pub fn new() -> Self {
+        Self {
+            config : RuntimeConfig {
+                max_deletes : BaseT::MAX_DELETES,
+                meaningful_key_len : BaseT::MEANINGFUL_KEY_LEN,
+                group_variant_overlap_threshold : BaseT::GROUP_VARIANT_OVERLAP_THRESHOLD,
+                case_sensitive : true,
+                fold_diacritics : false,
+                stop_words : vec![],
+                #[cfg(feature = "unicode-segmentation")]
+                grapheme_clusters : false,
+                default_threshold : None,
+                cipher : None,
+                phantom : PhantomData,
+            }
+        }
+    }
+
+    /// Sets the number of deletes used to create variants.  See [MAX_DELETES](TableConfig::MAX_DELETES)
+    pub fn max_deletes(mut self, max_deletes : usize) -> Self {
+        self.config.max_deletes = max_deletes;
+        self
+    }
+
+    /// Sets the number of characters of each key used to create variants.  See [MEANINGFUL_KEY_LEN](TableConfig::MEANINGFUL_KEY_LEN)
+    pub fn meaningful_key_len(mut self, meaningful_key_len : usize) -> Self {
+        self.config.meaningful_key_len = meaningful_key_len;
+        self
+    }
+
+    /// Sets the number of variants a key must share with a key group in order to join it.  See
+    /// [GROUP_VARIANT_OVERLAP_THRESHOLD](TableConfig::GROUP_VARIANT_OVERLAP_THRESHOLD)
+    pub fn group_variant_overlap_threshold(mut self, group_variant_overlap_threshold : usize) -> Self {
+        self.config.group_variant_overlap_threshold = group_variant_overlap_threshold;
+        self
+    }
+
+    /// Sets whether keys that differ only in case are treated as different keys.  See [TableConfig::case_sensitive]
+    pub fn case_sensitive(mut self, case_sensitive : bool) -> Self {
+        self.config.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Sets whether keys that differ only in their diacritics are treated as the same key.  See [TableConfig::fold_diacritics]
+    pub fn fold_diacritics(mut self, fold_diacritics : bool) -> Self {
+        self.config.fold_diacritics = fold_diacritics;
+        self
+    }
+
+    /// Sets the stop words left out of the indexed form of keys.  See [TableConfig::stop_words]
+    pub fn stop_words(mut self, stop_words : &[&str]) -> Self {
+        self.config.stop_words = stop_words.iter().map(|stop_word| stop_word.to_string()).collect();
+        self
+    }
+
+    /// Sets whether variants are created by deleting whole grapheme clusters.  See [TableConfig::grapheme_clusters]
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn grapheme_clusters(mut self, grapheme_clusters : bool) -> Self {
+        self.config.grapheme_clusters = grapheme_clusters;
+        self
+    }
+
+    /// Sets the threshold used by fuzzy lookups that aren't given one.  See [TableConfig::default_threshold]
+    pub fn default_threshold(mut self, default_threshold : Option<BaseT::DistanceT>) -> Self {
+        self.config.default_threshold = default_threshold;
+        self
+    }
+
+    /// Sets the cipher used to encrypt the Table's entries.  See [TableConfig::cipher]
+    pub fn cipher(mut self, cipher : Arc<dyn Cipher>) -> Self {
+        self.config.cipher = Some(cipher);
+        self
+    }
+
+    /// Returns the finished RuntimeConfig
+    pub fn build(self) -> RuntimeConfig<BaseT> {
+        self.config
+    }
+}
+
+impl <BaseT : TableConfig>Default for RuntimeConfigBuilder<BaseT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The parameters of a [Table](crate::Table) that affect the contents of its database, and are therefore
+/// stored in the database when it is created
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableParams {
+    pub max_deletes : usize,
+    pub meaningful_key_len : usize,
+    pub group_variant_overlap_threshold : usize,
+    pub case_sensitive : bool,
+    pub fold_diacritics : bool,
+    /// Whether variants are made by deleting grapheme clusters rather than chars.  Always `false` unless the
+    /// `unicode-segmentation` feature is enabled
+    pub grapheme_clusters : bool,
+    /// The table's [INDEX_STRATEGY](TableConfig::INDEX_STRATEGY)
+    pub index_strategy : IndexStrategy,
+    /// The table's [stop_words](TableConfig::stop_words), folded the same way as keys.  Always empty unless the
+    /// table has UTF8_KEYS
+    pub stop_words : Vec<String>,
+    /// The name of the table's [PHONETIC_FUNCTION](TableConfig::PHONETIC_FUNCTION), if it has one
+    pub phonetic_function : Option<String>,
+    /// Whether the table has a [TOKEN_INDEX](TableConfig::TOKEN_INDEX)
+    pub token_index : bool,
+    /// The table's [INLINE_VALUE_MAX_SIZE](TableConfig::INLINE_VALUE_MAX_SIZE), as of when it was last opened
+    pub inline_value_max_size : usize,
+    /// The [id](crate::VariantScheme::id) of the table's [VARIANT_SCHEME](TableConfig::VARIANT_SCHEME)
+    pub variant_scheme : String,
+}
+
+impl TableParams {
+
+    /// Takes the parameters from a config
+    pub fn from_config<ConfigT : TableConfig>(config : &ConfigT) -> Self {
+        let mut params = Self {
+            max_deletes : config.max_deletes(),
+            meaningful_key_len : config.meaningful_key_len(),
+            group_variant_overlap_threshold : config.group_variant_overlap_threshold(),
+            //NOTE: Case and diacritics can only be folded for UTF-8 keys
+            case_sensitive : config.case_sensitive() || !ConfigT::UTF8_KEYS,
+            fold_diacritics : config.fold_diacritics() && ConfigT::UTF8_KEYS,
+            #[cfg(feature = "unicode-segmentation")]
+            grapheme_clusters : config.grapheme_clusters() && ConfigT::UTF8_KEYS,
+            #[cfg(not(feature = "unicode-segmentation"))]
+            grapheme_clusters : false,
+            index_strategy : ConfigT::INDEX_STRATEGY,
+            stop_words : vec![],
+            phonetic_function : ConfigT::PHONETIC_FUNCTION.map(|encoding| encoding.name.to_string()),
+            token_index : ConfigT::TOKEN_INDEX,
+            inline_value_max_size : ConfigT::INLINE_VALUE_MAX_SIZE,
+            variant_scheme : ConfigT::VARIANT_SCHEME.id().to_string(),
+        };
+
+        //The stop words are compared with the tokens of folded keys, so they're folded too
+        if ConfigT::UTF8_KEYS {
+            params.stop_words = config.stop_words().into_iter().map(|stop_word| params.fold_key(stop_word)).collect();
+        }
+        params
+    }
+
+    /// Returns `true` if keys are folded, to lower case, by stripping their diacritics, or by dropping their stop
+    /// words, before they are indexed or compared
+    pub fn folds_keys(&self) -> bool {
+        !self.case_sensitive || self.fold_diacritics || !self.stop_words.is_empty()
+    }
+
+    /// Returns the key as it's indexed and compared, according to the parameters
+    pub(crate) fn fold_key<OwnedKeyT : OwnedKey>(&self, key : OwnedKeyT) -> OwnedKeyT {
+        let key = if self.case_sensitive { key } else { key.fold_case() };
+        let key = if self.fold_diacritics { key.fold_diacritics() } else { key };
+        if self.stop_words.is_empty() { key } else { self.drop_stop_words(key) }
+    }
+
+    /// Returns the key without the tokens that are stop words, joined with single spaces, unless every token is
+    /// a stop word
+    fn drop_stop_words<OwnedKeyT : OwnedKey>(&self, key : OwnedKeyT) -> OwnedKeyT {
+        let key_string = match key.borrow_str() {
+            Some(key_string) => key_string,
+            None => return key
+        };
+        let tokens : Vec<&str> = key_string.split_whitespace()
+            .filter(|token| !self.stop_words.iter().any(|stop_word| stop_word == token))
+            .collect();
+        if tokens.is_empty() {
+            return key;
+        }
+        OwnedKeyT::from_string(tokens.join(" "))
+    }
+}
@@ -0,0 +1,132 @@
+//!
+//! The TableHandle module contains the [TableHandle] object, for swapping a replacement [Table] in
+//! while queries are running.  The TableHandle struct is re-exported
+//!
+
+use std::sync::{Arc, RwLock, Mutex};
+
+use super::table_config::{*};
+use super::table::{*};
+
+/// A shared handle to a [Table], that allows a newly built replacement Table to be swapped in atomically
+///
+/// Queries are made against the Table returned by [current](TableHandle::current).  A query holding the old
+/// Table will continue against it undisturbed when [swap](TableHandle::swap) is called, and every subsequent
+/// call to `current` will return the new Table.  Once the last reference to the old Table is released, it is
+/// closed, and its database is deleted if that was requested at the time of the swap.
+///
+/// The retired Tables are only checked for queries still using them by [swap](TableHandle::swap),
+/// [release_retired](TableHandle::release_retired), and when the TableHandle is dropped.  If a retired Table is
+/// still in use when the TableHandle is dropped, it's closed when the last query releases it, but its database
+/// isn't deleted, even if that was requested.  So the queries should be finished before the handle is dropped.
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let mut table = Table::<DefaultTableConfig, true>::new("handle_example.rocks", DefaultTableConfig()).unwrap();
+/// table.reset().unwrap();
+/// table.insert("Hello", &"Old Greeting".to_string()).unwrap();
+/// let handle = TableHandle::new(table);
+///
+/// //Build the replacement table while the old one is still in service
+/// let mut new_table = Table::<DefaultTableConfig, true>::new("handle_example_2.rocks", DefaultTableConfig()).unwrap();
+/// new_table.reset().unwrap();
+/// new_table.insert("Hello", &"New Greeting".to_string()).unwrap();
+///
+/// let old_table = handle.current();
+/// handle.swap(new_table, true).unwrap();
+///
+/// //The query that was in flight still sees the old table
+/// let record_id = old_table.lookup_exact("Hello").unwrap().next().unwrap();
+/// assert_eq!(old_table.get_value(record_id).unwrap(), "Old Greeting");
+/// drop(old_table);
+///
+/// //New queries see the new table
+/// let table = handle.current();
+/// let record_id = table.lookup_exact("Hello").unwrap().next().unwrap();
+/// assert_eq!(table.get_value(record_id).unwrap(), "New Greeting");
+/// ```
+pub struct TableHandle<ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    current : RwLock<Arc<Table<ConfigT, UTF8_KEYS>>>,
+    retired : Mutex<Vec<RetiredTable<ConfigT, UTF8_KEYS>>>,
+}
+
+/// A Table that has been swapped out, but may still be in use by queries that began before the swap
+struct RetiredTable<ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    table : Arc<Table<ConfigT, UTF8_KEYS>>,
+    delete : bool,
+}
+
+impl <ConfigT : TableConfig, const UTF8_KEYS : bool>TableHandle<ConfigT, UTF8_KEYS> {
+
+    /// Creates a new TableHandle, serving the supplied Table
+    pub fn new(table : Table<ConfigT, UTF8_KEYS>) -> Self {
+        Self {
+            current : RwLock::new(Arc::new(table)),
+            retired : Mutex::new(vec![]),
+        }
+    }
+
+    /// Returns the Table currently being served by the handle
+    ///
+    /// The returned Table will remain valid even if another Table is swapped in, so a query should call
+    /// `current` once and make all of its lookups against the returned Table.
+    pub fn current(&self) -> Arc<Table<ConfigT, UTF8_KEYS>> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Atomically replaces the Table being served by the handle with `new_table`
+    ///
+    /// The old Table is closed as soon as no queries are using it.  If `delete_old` is `true`, the old
+    /// Table's database will be deleted from disk after it is closed, unless the TableHandle is dropped while
+    /// the old Table is still in use.  Returns an error if a previously retired Table couldn't be deleted.
+    pub fn swap(&self, new_table : Table<ConfigT, UTF8_KEYS>, delete_old : bool) -> Result<(), String> {
+
+        let old_table = {
+            let mut current = self.current.write().unwrap();
+            core::mem::replace(&mut *current, Arc::new(new_table))
+        };
+
+        self.retired.lock().unwrap().push(RetiredTable{table : old_table, delete : delete_old});
+        self.release_retired()
+    }
+
+    /// Closes (and deletes, if that was requested) every retired Table that is no longer being used by any
+    /// queries
+    ///
+    /// This happens automatically on every call to [swap](TableHandle::swap), and when the TableHandle is
+    /// dropped, but it can be called explicitly to release the old Table's resources sooner.
+    pub fn release_retired(&self) -> Result<(), String> {
+
+        let mut retired = self.retired.lock().unwrap();
+        let mut result = Ok(());
+        let mut still_retired = vec![];
+        for retired_table in retired.drain(..) {
+            match Arc::try_unwrap(retired_table.table) {
+                Ok(table) => {
+                    let closed = if retired_table.delete { table.destroy() } else { table.close() };
+                    if let Err(err) = closed {
+                        result = Err(err);
+                    }
+                },
+                Err(table) => still_retired.push(RetiredTable{table, delete : retired_table.delete}),
+            }
+        }
+        *retired = still_retired;
+
+        result
+    }
+}
+
+impl <ConfigT : TableConfig, const UTF8_KEYS : bool>Drop for TableHandle<ConfigT, UTF8_KEYS> {
+    fn drop(&mut self) {
+        //NOTE: There's no caller to return the errors to, so they're reported on stderr, along with the
+        // databases that can't be deleted because their Tables are still in use
+        if let Err(err) = self.release_retired() {
+            eprintln!("fuzzy_rocks: failed to release a retired Table when the TableHandle was dropped: {}", err);
+        }
+        for retired_table in self.retired.lock().unwrap().iter().filter(|retired_table| retired_table.delete) {
+            eprintln!("fuzzy_rocks: the retired Table at {} was still in use when the TableHandle was dropped, so its database wasn't deleted", retired_table.table.path());
+        }
+    }
+}
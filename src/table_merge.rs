@@ -0,0 +1,155 @@
+//!
+//! The TableMerge module contains [Table::merge_from](crate::Table::merge_from), for importing the records of
+//! one Table into another.  The MergeConflictPolicy and MergeSummary types are re-exported
+//!
+
+use std::collections::HashMap;
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::RecordID;
+use super::key::{*};
+use super::table_config::{*};
+use super::table::{*};
+
+/// The number of records written to the database together, in a single [Transaction](crate::Transaction)
+const MERGE_BATCH_SIZE : usize = 1000;
+
+/// What [Table::merge_from](crate::Table::merge_from) does with an incoming record that has a key exactly
+/// matching a key of a record already in the Table
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MergeConflictPolicy {
+    /// The incoming record is added anyway, alongside the existing record
+    KeepBoth,
+    /// The incoming record is skipped, and the existing record is left as it was
+    KeepExisting,
+    /// The existing record's value is replaced with the incoming record's value.  The existing record keeps
+    /// its own keys
+    ReplaceValue,
+}
+
+/// The outcome of a [Table::merge_from](crate::Table::merge_from)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// The RecordID in this Table of each record from the other Table, by its RecordID in the other Table.  A
+    /// record that collided with an existing record, and wasn't added, maps to the existing record
+    pub record_ids : HashMap<RecordID, RecordID>,
+    /// The number of records added to the Table
+    pub added : usize,
+    /// The number of incoming records skipped under [KeepExisting](MergeConflictPolicy::KeepExisting)
+    pub skipped : usize,
+    /// The number of existing records whose values were replaced under [ReplaceValue](MergeConflictPolicy::ReplaceValue)
+    pub replaced : usize,
+}
+
+impl <OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Table<ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Self : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Imports every record from another Table into this one, and returns a [MergeSummary] with the RecordIDs
+    /// the records were given
+    ///
+    /// The other Table may have a different config, as long as its keys and values are the same types, e.g.
+    /// to unify shards of an index that were built separately.  Each record is indexed again under this Table's
+    /// config, and is given a new RecordID.  Deleted records, including the records marked
+    /// with [soft_delete](Self::soft_delete), aren't imported.  An incoming record with a key that
+    /// exactly matches a key of a record already in this Table, including one imported earlier in the same merge,
+    /// is handled according to `policy`.
+    ///
+    /// The records are written in batches, each as a single [Transaction](crate::Transaction), so if an error is
+    /// encountered, the records of the earlier batches have already been imported.  The other Table is read
+    /// from a snapshot, so it may carry on being written while the merge runs.
+    ///
+    /// ```
+    /// use fuzzy_rocks::{*};
+    ///
+    /// let mut east = Table::<DefaultTableConfig, true>::new("merge_from_example_east.rocks", DefaultTableConfig()).unwrap();
+    /// east.reset().unwrap();
+    /// east.insert("Tokyo", &"Japan".to_string()).unwrap();
+    /// let mut west = Table::<DefaultTableConfig, true>::new("merge_from_example_west.rocks", DefaultTableConfig()).unwrap();
+    /// west.reset().unwrap();
+    /// west.insert("London", &"UK".to_string()).unwrap();
+    /// west.insert("Tokyo", &"Nihon".to_string()).unwrap();
+    ///
+    /// let summary = east.merge_from(&west, MergeConflictPolicy::ReplaceValue).unwrap();
+    /// assert_eq!((summary.added, summary.replaced), (1, 1));
+    /// let tokyo = east.lookup_exact("Tokyo").unwrap().next().unwrap();
+    /// assert_eq!(east.get_value(tokyo).unwrap(), "Nihon");
+    /// ```
+    pub fn merge_from<OtherConfigT>(&mut self, other : &Table<OtherConfigT, UTF8_KEYS>, policy : MergeConflictPolicy) -> Result<MergeSummary, String>
+        where
+        OtherConfigT : TableConfig<KeyCharT = ConfigT::KeyCharT, ValueT = ConfigT::ValueT>,
+        OtherConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+        Table<OtherConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+        let mut summary = MergeSummary::default();
+
+        //The keys of the records imported so far, which the lookups on the Table won't see until their batch
+        // is committed
+        let mut merged_keys : HashMap<OwnedKeyT, RecordID> = HashMap::new();
+
+        let mut records = other.export_stream();
+        loop {
+            let mut batch = records.by_ref().take(MERGE_BATCH_SIZE).collect::<Result<Vec<_>, String>>()?;
+            if batch.is_empty() {
+                break;
+            }
+            batch.retain(|record| !other.is_soft_deleted(record.record_id));
+
+            //Look up the collisions with the committed records before the Transaction borrows the Table
+            let mut committed_collisions = Vec::with_capacity(batch.len());
+            for record in batch.iter() {
+                let mut collision = None;
+                if policy != MergeConflictPolicy::KeepBoth {
+                    for key in record.keys.iter() {
+                        if let Some(record_id) = self.lookup_exact_internal(key)?.into_iter().next() {
+                            collision = Some(record_id);
+                            break;
+                        }
+                    }
+                }
+                committed_collisions.push(collision);
+            }
+
+            let mut transaction = self.transaction();
+            for (record, committed_collision) in batch.into_iter().zip(committed_collisions) {
+                let collision = match policy {
+                    MergeConflictPolicy::KeepBoth => None,
+                    _ => record.keys.iter().find_map(|key| merged_keys.get(key).copied()).or(committed_collision)
+                };
+                let record_id = match collision {
+                    None => {
+                        let record_id = transaction.create(&record.keys[..], &record.value)?;
+                        summary.added += 1;
+                        record_id
+                    },
+                    Some(existing_id) if policy == MergeConflictPolicy::ReplaceValue => {
+                        transaction.replace_value(existing_id, &record.value)?;
+                        summary.replaced += 1;
+                        existing_id
+                    },
+                    Some(existing_id) => {
+                        summary.skipped += 1;
+                        existing_id
+                    }
+                };
+                summary.record_ids.insert(record.record_id, record_id);
+                if policy != MergeConflictPolicy::KeepBoth {
+                    for key in record.keys {
+                        merged_keys.entry(key).or_insert(record_id);
+                    }
+                }
+            }
+            transaction.commit()?;
+        }
+
+        Ok(summary)
+    }
+}
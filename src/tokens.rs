@@ -0,0 +1,65 @@
+//!
+//! The Tokens module contains the logic for splitting keys into tokens, and computing the token variants
+//! stored in the "tokens" column family.  Nothing should be re-exported
+//!
+
+use std::collections::HashSet;
+
+use num_traits::Zero;
+
+/// Returns the distinct whitespace-separated tokens of a key, in the order they first appear
+pub(crate) fn key_tokens(key_string : &str) -> Vec<String> {
+    let mut tokens : Vec<String> = vec![];
+    for token in key_string.split_whitespace() {
+        if !tokens.iter().any(|existing| existing == token) {
+            tokens.push(token.to_string());
+        }
+    }
+    tokens
+}
+
+/// Returns the variants of a token, formed by deleting up to `max_deletes` chars, including the token itself,
+/// encoded as they are stored in the "tokens" CF
+///
+/// The empty variant is left out, so a token is never found only because it is short.
+pub(crate) fn token_variants(token : &str, max_deletes : usize) -> HashSet<Vec<u8>> {
+
+    let mut variants = HashSet::new();
+    let mut current : HashSet<Vec<char>> = HashSet::from([token.chars().collect()]);
+    for _ in 0..=max_deletes {
+        let mut next = HashSet::new();
+        for variant in current {
+            if variant.is_empty() {
+                continue;
+            }
+            for idx in 0..variant.len() {
+                let mut shorter = variant.clone();
+                shorter.remove(idx);
+                next.insert(shorter);
+            }
+            variants.insert(variant.into_iter().collect::<String>().into_bytes());
+        }
+        current = next;
+    }
+    variants
+}
+
+/// Returns the sum of the distances from each of the phrase's tokens to the closest of the key's tokens, or `None`
+/// if a phrase token has no key token within the `threshold`
+///
+/// The key's tokens may be in any order, and a key token may be the closest match for more than one phrase token.
+pub(crate) fn phrase_distance<DistanceT, F>(phrase_tokens : &[Vec<char>], key_tokens : &[Vec<char>], threshold : Option<DistanceT>, distance_function : F) -> Option<DistanceT>
+    where
+    DistanceT : Copy + PartialOrd + Zero,
+    F : Fn(&[char], &[char]) -> DistanceT
+{
+    let mut total = DistanceT::zero();
+    for phrase_token in phrase_tokens {
+        let closest = key_tokens.iter()
+            .map(|key_token| distance_function(key_token, phrase_token))
+            .filter(|distance| threshold.is_none_or(|threshold| *distance <= threshold))
+            .min_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal))?;
+        total = total + closest;
+    }
+    Some(total)
+}
@@ -0,0 +1,183 @@
+//!
+//! The Transaction module contains the [Transaction] object, for applying several record operations
+//! to a [Table] as a single all-or-nothing unit.  The Transaction struct is re-exported
+//!
+
+use std::collections::HashSet;
+
+use num_traits::Zero;
+use serde::{Serialize};
+
+use super::records::RecordID;
+use super::key::{*};
+use super::database::{*};
+use super::table_config::{*};
+use super::table::{*};
+
+/// A set of record insertions, deletions, and updates, that will be applied to a [Table] atomically
+///
+/// A Transaction is created with [Table::transaction].  Nothing is written to the database until
+/// [commit](Transaction::commit) is called, and operations within the Transaction will see the effects of
+/// the earlier operations in the same Transaction.  Lookups made on the Table won't see any of the
+/// Transaction's operations until it is committed.  If an operation returns an error, none of its writes are
+/// kept, and the Transaction can carry on with the operations that succeeded.
+///
+/// If the Transaction is dropped without being committed, it is rolled back, as if [rollback](Transaction::rollback)
+/// had been called.
+///
+/// ```
+/// use fuzzy_rocks::{*};
+///
+/// let mut table = Table::<DefaultTableConfig, true>::new("transaction_example.rocks", DefaultTableConfig()).unwrap();
+/// table.reset().unwrap();
+/// let mon = table.insert("Monday", &"Getsuyoubi".to_string()).unwrap();
+///
+/// //Replace Monday with Tuesday, as a single unit
+/// let mut transaction = table.transaction();
+/// transaction.delete(mon).unwrap();
+/// let tue = transaction.insert("Tuesday", &"Kayoubi".to_string()).unwrap();
+/// transaction.commit().unwrap();
+///
+/// assert_eq!(table.lookup_exact("Monday").unwrap().count(), 0);
+/// assert_eq!(table.get_value(tue).unwrap(), "Kayoubi");
+///
+/// //A Transaction that is rolled back leaves the Table untouched
+/// let mut transaction = table.transaction();
+/// transaction.delete(tue).unwrap();
+/// transaction.rollback();
+/// assert_eq!(table.get_value(tue).unwrap(), "Kayoubi");
+/// ```
+///
+/// NOTE: The writes are composed into a RocksDB `WriteBatch`, so a Transaction is atomic and isolated
+/// from readers, but it doesn't detect conflicts.  That isn't needed because the Transaction borrows the
+/// Table mutably, so there can't be any other writers while it is open.
+pub struct Transaction<'a, ConfigT : TableConfig, const UTF8_KEYS : bool> {
+    table : &'a mut Table<ConfigT, UTF8_KEYS>,
+    batch : Option<DBWriteBatch>,
+    record_id_state : Option<(usize, Vec<RecordID>)>,
+    deleted_records : Vec<RecordID>,
+}
+
+impl <'a, OwnedKeyT, ConfigT : TableConfig, const UTF8_KEYS : bool>Transaction<'a, ConfigT, UTF8_KEYS>
+    where
+    ConfigT::KeyCharT : 'static + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    ConfigT::DistanceT : 'static + Copy + Zero + PartialOrd + PartialEq + From<u8>,
+    ConfigT::ValueT : 'static + Serialize + serde::de::DeserializeOwned,
+    OwnedKeyT : OwnedKey<KeyCharT = ConfigT::KeyCharT>,
+    Table<ConfigT, UTF8_KEYS> : TableKeyEncoding<OwnedKeyT = OwnedKeyT>,
+    {
+
+    /// Begins a new Transaction on the supplied Table.  Called by [Table::transaction]
+    pub(crate) fn new(table : &'a mut Table<ConfigT, UTF8_KEYS>) -> Self {
+        let record_id_state = table.record_id_state();
+        Self {
+            table,
+            batch : Some(DBWriteBatch::new()),
+            record_id_state : Some(record_id_state),
+            deleted_records : vec![],
+        }
+    }
+
+    /// Inserts a new key-value pair as part of the Transaction, and returns the RecordID the new record
+    /// will have once the Transaction is committed
+    ///
+    /// Functions the same as [Table::insert]
+    pub fn insert<K : IntoKey<Key = KeyT>, KeyT : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, key : K, value : &ConfigT::ValueT) -> Result<RecordID, String> {
+        let key = key.into_key();
+        let record_id = self.compose(|table, batch| table.insert_in_batch(batch, [&key].iter().copied(), 1, value))?;
+        self.table.claim_record_id(record_id);
+        Ok(record_id)
+    }
+
+    /// Creates a new record as part of the Transaction, and returns the RecordID the new record will
+    /// have once the Transaction is committed
+    ///
+    /// Functions the same as [Table::create]
+    pub fn create<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, keys : &[K], value : &ConfigT::ValueT) -> Result<RecordID, String> {
+        let record_id = self.compose(|table, batch| table.insert_in_batch(batch, keys.iter(), keys.len(), value))?;
+        self.table.claim_record_id(record_id);
+        Ok(record_id)
+    }
+
+    /// Deletes a record as part of the Transaction
+    ///
+    /// Functions the same as [Table::delete]
+    pub fn delete(&mut self, record_id : RecordID) -> Result<(), String> {
+        self.compose(|table, batch| table.delete_internal(batch, record_id))?;
+        self.table.release_record_id(record_id);
+        self.deleted_records.push(record_id);
+        Ok(())
+    }
+
+    /// Replaces a record's value as part of the Transaction.  Returns the value that was replaced
+    ///
+    /// Functions the same as [Table::replace_value]
+    pub fn replace_value(&mut self, record_id : RecordID, value : &ConfigT::ValueT) -> Result<ConfigT::ValueT, String> {
+        self.compose(|table, batch| table.replace_value_internal(batch, record_id, value))
+    }
+
+    /// Adds the supplied keys to the record's keys as part of the Transaction
+    ///
+    /// Functions the same as [Table::add_keys]
+    pub fn add_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
+        self.compose(|table, batch| table.add_keys_internal(batch, record_id, keys.iter(), keys.len()))
+    }
+
+    /// Removes the supplied keys from the keys associated with a record as part of the Transaction
+    ///
+    /// Functions the same as [Table::remove_keys]
+    pub fn remove_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
+        let keys_set : HashSet<&K> = HashSet::from_iter(keys.iter());
+        self.compose(|table, batch| table.remove_keys_internal(batch, record_id, &keys_set))
+    }
+
+    /// Replaces a record's keys with the supplied keys as part of the Transaction
+    ///
+    /// Functions the same as [Table::replace_keys]
+    pub fn replace_keys<K : Key<KeyCharT = ConfigT::KeyCharT>>(&mut self, record_id : RecordID, keys : &[K]) -> Result<(), String> {
+        self.compose(|table, batch| table.replace_keys_internal(batch, record_id, keys))
+    }
+
+    /// Atomically applies all of the Transaction's operations to the Table
+    ///
+    /// If the commit fails then none of the operations are applied, and the Transaction is rolled back.
+    pub fn commit(mut self) -> Result<(), String> {
+        let batch = self.batch.take().unwrap();
+        self.table.commit_batch(batch)?;
+        self.record_id_state = None;
+        for record_id in self.deleted_records.iter() {
+            self.table.forget_access_count(*record_id);
+        }
+        Ok(())
+    }
+
+    /// Discards all of the Transaction's operations, leaving the Table as it was when the Transaction
+    /// began
+    pub fn rollback(self) {
+        //The rollback happens when the Transaction is dropped
+    }
+
+    /// Composes an operation in a scratch batch on top of the Transaction's batch, and merges the operation's
+    /// writes into the Transaction's batch only if it succeeds, so a failed operation leaves nothing behind
+    fn compose<R, F>(&mut self, operation : F) -> Result<R, String>
+        where
+        F : FnOnce(&mut Table<ConfigT, UTF8_KEYS>, &mut DBWriteBatch) -> Result<R, String>
+    {
+        let mut scratch = self.batch.take().unwrap().scratch();
+        let result = operation(self.table, &mut scratch);
+        self.batch = Some(match result {
+            Ok(_) => scratch.merge_scratch(),
+            Err(_) => scratch.discard_scratch()
+        });
+        result
+    }
+}
+
+impl <'a, ConfigT : TableConfig, const UTF8_KEYS : bool>Drop for Transaction<'a, ConfigT, UTF8_KEYS> {
+    fn drop(&mut self) {
+        //If we weren't committed, restore the RecordIDs handed out by the Transaction
+        if let Some(record_id_state) = self.record_id_state.take() {
+            self.table.restore_record_id_state(record_id_state);
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use unicode_normalization::UnicodeNormalization;
+#[cfg(feature = "unicode-segmentation")]
+use unicode_segmentation::UnicodeSegmentation;
+
 
 
 /// Returns the number of chars in a utf-8 string
@@ -15,14 +19,36 @@ pub fn unicode_truncate(s: &str, len: usize) -> String {
     new_string
 }
 
-/// Removes a single unicode character at the specified index from a utf-8 string stored
-pub fn unicode_remove_char(s: &str, idx: usize) -> String {
-    let new_str : String = s.chars()
-        .enumerate()
-        .filter(|(i, _)| *i != idx)
-        .map(|(_, the_char)| the_char)
-        .collect();
-    new_str
+/// Returns the first n grapheme clusters up to len from a utf-8 string
+#[cfg(feature = "unicode-segmentation")]
+pub fn unicode_truncate_graphemes(s: &str, len: usize) -> String {
+    s.graphemes(true).take(len).collect()
+}
+
+/// Returns the number of chars in each grapheme cluster of a utf-8 string
+#[cfg(feature = "unicode-segmentation")]
+pub fn unicode_grapheme_char_lens(s: &str) -> Vec<usize> {
+    s.graphemes(true)
+        .map(|grapheme| grapheme.chars().count())
+        .collect()
+}
+
+/// Returns `true` if the char is a combining mark, from one of the Combining Diacritical Marks blocks
+fn is_combining_mark(c : char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}' | '\u{FE20}'..='\u{FE2F}')
+}
+
+/// Returns a utf-8 string with its diacritics removed, so "café" becomes "cafe" and "über" becomes "uber"
+///
+/// The string is decomposed into its canonical decomposition (NFD), the combining marks are dropped, and what's
+/// left is composed again (NFC), so letters in any script that decompose into a base letter and diacritical
+/// marks are replaced by their base letter.  Marks outside the Combining Diacritical Marks blocks, such as the
+/// vowel signs of Indic scripts, are kept, and so are letters that don't decompose, such as "ø" or "ł".
+pub fn unicode_strip_diacritics(s: &str) -> String {
+    s.nfd()
+        .filter(|the_char| !is_combining_mark(*the_char))
+        .nfc()
+        .collect()
 }
 
 //NOTE: Currently unneeded
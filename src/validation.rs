@@ -0,0 +1,66 @@
+//!
+//! The Validation module contains the validators that a [Table](crate::Table) runs before writing keys and values.
+//! The KeyValidator and ValueValidator types are re-exported
+//!
+
+use super::key::{*};
+
+/// A function that checks a key before it is written to a [Table](crate::Table), and returns an error describing
+/// the problem if the key should be rejected.  See [add_key_validator](crate::Table::add_key_validator)
+pub type KeyValidator<KeyCharT> = Box<dyn Fn(&[KeyCharT]) -> Result<(), String> + Send + Sync>;
+
+/// A function that checks a value before it is written to a [Table](crate::Table), and returns an error describing
+/// the problem if the value should be rejected.  See [add_value_validator](crate::Table::add_value_validator)
+pub type ValueValidator<ValueT> = Box<dyn Fn(&ValueT) -> Result<(), String> + Send + Sync>;
+
+/// The validators registered with a Table
+pub(crate) struct Validators<KeyCharT, ValueT> {
+    key_validators : Vec<KeyValidator<KeyCharT>>,
+    value_validators : Vec<ValueValidator<ValueT>>,
+}
+
+impl <KeyCharT, ValueT>Validators<KeyCharT, ValueT> {
+
+    pub fn new() -> Self {
+        Self {
+            key_validators : vec![],
+            value_validators : vec![],
+        }
+    }
+
+    pub fn add_key_validator(&mut self, validator : KeyValidator<KeyCharT>) {
+        self.key_validators.push(validator);
+    }
+
+    pub fn add_value_validator(&mut self, validator : ValueValidator<ValueT>) {
+        self.value_validators.push(validator);
+    }
+
+    /// Runs every key validator on the key, and returns the first error
+    pub fn validate_key<K : Key<KeyCharT = KeyCharT>>(&self, key : &K) -> Result<(), String> {
+
+        //NOTE: Don't pay to expand the key into chars unless there's a validator to look at them
+        if self.key_validators.is_empty() {
+            return Ok(());
+        }
+
+        let key_chars = key.get_key_chars();
+        for validator in self.key_validators.iter() {
+            validator(&key_chars).map_err(|err| format!("key rejected by validator: {}", err))?;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if any value validators have been added
+    pub fn has_value_validators(&self) -> bool {
+        !self.value_validators.is_empty()
+    }
+
+    /// Runs every value validator on the value, and returns the first error
+    pub fn validate_value(&self, value : &ValueT) -> Result<(), String> {
+        for validator in self.value_validators.iter() {
+            validator(value).map_err(|err| format!("value rejected by validator: {}", err))?;
+        }
+        Ok(())
+    }
+}
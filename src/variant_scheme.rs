@@ -0,0 +1,81 @@
+//!
+//! The VariantScheme module contains the [VariantScheme] trait, which generates the variants that are used to
+//! find the candidates for a fuzzy lookup.  The VariantScheme trait and DeletionVariants struct are re-exported
+//!
+
+use std::collections::HashSet;
+
+use super::key::KeyChar;
+
+/// The [id](VariantScheme::id) of [DeletionVariants]
+pub(crate) const DELETION_VARIANT_SCHEME_ID : &str = "deletion";
+
+/// A strategy for generating the variants of a key, set with [VARIANT_SCHEME](crate::TableConfig::VARIANT_SCHEME)
+///
+/// The variants of each key are indexed in the "variants" CF when it is inserted, and the variants of the lookup
+/// key are probed there when performing a fuzzy lookup.  Any record that shares a variant with the lookup key is a
+/// candidate, and the [DISTANCE_FUNCTION](crate::TableConfig::DISTANCE_FUNCTION) then decides which of the
+/// candidates are close enough to be returned.  So a scheme determines which records a lookup is able to find,
+/// but not the distances that are reported for them.
+///
+/// The default scheme is [DeletionVariants], which is the SymSpell algorithm.  A Table with an n-gram
+/// [INDEX_STRATEGY](crate::TableConfig::INDEX_STRATEGY) indexes only the keys themselves in the "variants" CF, so
+/// its scheme is never asked for variants.  The phonetic and token indices keep their own column families.
+///
+/// A scheme's [id](VariantScheme::id) is stored in the [TableParams](crate::TableParams) when the table is created,
+/// and the table can't be opened with a different scheme, because the variants it has indexed would no longer agree
+/// with the variants that are probed.
+pub trait VariantScheme<KeyCharT> : Sync {
+
+    /// Returns a short name that identifies the scheme
+    ///
+    /// Two schemes with the same id must produce the same variants for every key.  The id `"deletion"` is
+    /// reserved for [DeletionVariants].
+    fn id(&self) -> &'static str;
+
+    /// Returns the variants of a key, other than the key itself, which is always indexed
+    ///
+    /// The key is the meaningful part of the key (See [MEANINGFUL_KEY_LEN](crate::TableConfig::MEANINGFUL_KEY_LEN)),
+    /// after the key has been folded, if the table folds keys.  It's divided into the units an edit applies to,
+    /// which are single chars, or grapheme clusters if the table uses them.
+    /// `max_distance` is the table's [MAX_DELETES](crate::TableConfig::MAX_DELETES) when indexing a key, and may be
+    /// smaller when a lookup has been limited to a shorter distance.
+    fn variants(&self, key_units : &[&[KeyCharT]], max_distance : usize) -> Vec<Vec<KeyCharT>>;
+}
+
+/// The default [VariantScheme], which creates the variants of a key by deleting up to `max_distance` units
+/// from it, as described by the SymSpell algorithm
+///
+/// Every key within `max_distance` edits of the lookup key shares at least one variant with it, so a lookup
+/// can find all of them.
+pub struct DeletionVariants;
+
+impl <KeyCharT : KeyChar>VariantScheme<KeyCharT> for DeletionVariants {
+
+    fn id(&self) -> &'static str {
+        DELETION_VARIANT_SCHEME_ID
+    }
+
+    fn variants(&self, key_units : &[&[KeyCharT]], max_distance : usize) -> Vec<Vec<KeyCharT>> {
+        let mut variants_set = HashSet::new();
+        deletion_variants_recursive(key_units, max_distance, &mut variants_set);
+        variants_set.into_iter().collect()
+    }
+}
+
+// The recursive part of DeletionVariants::variants().  A variant that has already been found has had its own
+// variants found as well, so it isn't visited again
+fn deletion_variants_recursive<KeyCharT : KeyChar>(key_units : &[&[KeyCharT]], remaining_deletes : usize, variants_set : &mut HashSet<Vec<KeyCharT>>) {
+    if remaining_deletes == 0 || key_units.len() < 2 {
+        return;
+    }
+    for i in 0..key_units.len() {
+        let mut variant_units = key_units.to_vec();
+        variant_units.remove(i);
+        let variant = variant_units.concat();
+        if !variants_set.contains(&variant) {
+            deletion_variants_recursive(&variant_units, remaining_deletes - 1, variants_set);
+            variants_set.insert(variant);
+        }
+    }
+}
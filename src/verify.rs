@@ -0,0 +1,94 @@
+//!
+//! The Verify module contains the reports produced by [Table::verify](crate::Table::verify),
+//! [Table::repair](crate::Table::repair), [Table::gc_variants](crate::Table::gc_variants), and
+//! [Table::check_integrity](crate::Table::check_integrity).  The VerifyReport, GcReport, and IntegrityReport
+//! structs, and the IntegrityIssue enum, are re-exported
+//!
+
+use super::records::RecordID;
+use super::key_groups::{*};
+use super::key_group_handle::KeyGroupHandle;
+
+/// The divergences found by auditing a [Table](crate::Table)'s database for consistency
+///
+/// Each reference is reported as the raw bytes of the variant, together with the [RecordID] of the record
+/// that owns the referenced key group.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    pub(crate) missing_refs : Vec<(Vec<u8>, KeyGroupID)>,
+    pub(crate) stale_refs : Vec<(Vec<u8>, KeyGroupID)>,
+}
+
+impl VerifyReport {
+
+    /// Returns `true` if no divergences were found
+    pub fn is_consistent(&self) -> bool {
+        self.missing_refs.is_empty() && self.stale_refs.is_empty()
+    }
+
+    /// Returns the exact-key references that should exist but don't.  A record with a missing reference
+    /// can't be found by [lookup_exact](crate::Table::lookup_exact) using that key
+    pub fn missing_refs(&self) -> impl Iterator<Item=(&[u8], RecordID)> + '_ {
+        self.missing_refs.iter().map(|(variant, key_group_id)| (&variant[..], key_group_id.record_id()))
+    }
+
+    /// Returns the variant references to key groups that no longer exist.  A stale reference may cause
+    /// a lookup to fail when it tries to load the key group
+    pub fn stale_refs(&self) -> impl Iterator<Item=(&[u8], RecordID)> + '_ {
+        self.stale_refs.iter().map(|(variant, key_group_id)| (&variant[..], key_group_id.record_id()))
+    }
+}
+
+/// The work done by [Table::gc_variants](crate::Table::gc_variants) to remove dangling variant references
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// The number of variant entries scanned, counting each page of a paged entry separately.  See
+    /// [VARIANT_PAGE_SIZE](crate::TableConfig::VARIANT_PAGE_SIZE)
+    pub entries_scanned : usize,
+    /// The number of KeyGroupID references scanned
+    pub refs_scanned : usize,
+    /// The number of dangling references removed
+    pub refs_removed : usize,
+    /// The number of bytes the removed references took up in the variant entries.  The space is reclaimed on disk
+    /// when RocksDB next compacts the entries
+    pub bytes_reclaimed : usize,
+}
+
+/// An inconsistency found by [Table::check_integrity](crate::Table::check_integrity)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// An entry can't be decoded, as the name of its column family, its key, and the decoding error
+    CorruptEntry{cf_name : String, key : Vec<u8>, error : String},
+    /// A record lists a key group that has no entry in the "keys" column family
+    MissingKeyGroup(KeyGroupHandle),
+    /// A key group has an entry in the "keys" column family, but its record doesn't list it
+    OrphanedKeyGroup(KeyGroupHandle),
+    /// A record that hasn't been deleted has no value
+    MissingValue(RecordID),
+    /// A record that has been deleted, or that doesn't exist, has a value
+    OrphanedValue(RecordID),
+    /// A key isn't referenced by the variant entry for the key itself, as in [VerifyReport::missing_refs]
+    MissingVariantRef{variant : Vec<u8>, key_group : KeyGroupHandle},
+    /// A variant entry references a key group that doesn't exist, as in [VerifyReport::stale_refs]
+    StaleVariantRef{variant : Vec<u8>, key_group : KeyGroupHandle},
+}
+
+/// The inconsistencies found by [Table::check_integrity](crate::Table::check_integrity)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub(crate) issues : Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+
+    /// Returns `true` if no inconsistencies were found
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the inconsistencies found, with the corrupt entries first, followed by the issues found from the
+    /// records' entries, and then those found from the variant entries
+    pub fn issues(&self) -> &[IntegrityIssue] {
+        &self.issues
+    }
+}